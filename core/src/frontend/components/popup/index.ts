@@ -12,5 +12,7 @@ export type {
 export { default as McpPopup } from './McpPopup.vue'
 export { default as MemorySuggestionModal } from './MemorySuggestionModal.vue'
 export { default as PopupContent } from './PopupContent.vue'
+export { default as PopupForm } from './PopupForm.vue'
 export { default as PopupHeader } from './PopupHeader.vue'
 export { default as PopupInput } from './PopupInput.vue'
+export { default as PopupPreview } from './PopupPreview.vue'
@@ -2,6 +2,7 @@ import { invoke } from '@tauri-apps/api/core'
 import { listen } from '@tauri-apps/api/event'
 import { ref } from 'vue'
 import type { MemorySuggestion } from './useMemory'
+import { useToast } from './useToast'
 
 /**
  * MCP处理组合式函数
@@ -12,9 +13,40 @@ interface McpRequestData {
   message?: string
   predefined_options?: string[]
   is_markdown?: boolean
+  multi_select?: boolean
+  timeout_secs?: number | null
+  default_option?: string | null
   [key: string]: unknown
 }
 
+interface McpNotification {
+  message: string
+  title?: string
+  level?: 'info' | 'success' | 'warning' | 'error'
+  sound?: boolean
+}
+
+/**
+ * 播放简短提示音
+ */
+function playNotificationSound() {
+  try {
+    const ctx = new AudioContext()
+    const oscillator = ctx.createOscillator()
+    const gain = ctx.createGain()
+    oscillator.frequency.value = 880
+    gain.gain.setValueAtTime(0.15, ctx.currentTime)
+    gain.gain.exponentialRampToValueAtTime(0.001, ctx.currentTime + 0.3)
+    oscillator.connect(gain).connect(ctx.destination)
+    oscillator.start()
+    oscillator.stop(ctx.currentTime + 0.3)
+    oscillator.onended = () => ctx.close()
+  }
+  catch (error) {
+    console.error('播放提示音失败:', error)
+  }
+}
+
 export function useMcpHandler() {
   const mcpRequest = ref<McpRequestData | null>(null)
   const showMcpPopup = ref(false)
@@ -99,6 +131,20 @@ export function useMcpHandler() {
     }
   }
 
+  /**
+   * 以 toast 显示 notify 工具的通知
+   */
+  function showNotificationToast(notification: McpNotification) {
+    const toast = useToast()
+    const text = notification.title
+      ? `${notification.title}: ${notification.message}`
+      : notification.message
+    const level = notification.level ?? 'info'
+    toast[level](text, { duration: level === 'error' ? 6000 : 4000 })
+    if (notification.sound)
+      playNotificationSound()
+  }
+
   /**
    * 设置 Daemon 模式的 MCP popup 监听器
    * 用于新的 HTTP daemon 架构
@@ -118,6 +164,19 @@ export function useMcpHandler() {
 
         // 注意：响应通过 handleDaemonPopupResponse 发送
       })
+      // 超时后 daemon 已返回默认选项或超时错误，关闭过期的弹窗
+      await listen('mcp-popup-timeout', (event) => {
+        const requestId = event.payload as string
+        if (mcpRequest.value?.id === requestId) {
+          console.log('[Daemon MCP] Popup timed out:', requestId)
+          showMcpPopup.value = false
+          mcpRequest.value = null
+        }
+      })
+      // notify 工具的非阻塞通知，不需要响应
+      await listen('mcp-notification', (event) => {
+        showNotificationToast(event.payload as McpNotification)
+      })
       console.log('[Daemon MCP] Popup listener initialized')
     }
     catch (error) {
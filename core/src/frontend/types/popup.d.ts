@@ -5,6 +5,41 @@ export interface McpRequest {
   message: string
   predefined_options?: string[]
   is_markdown?: boolean
+  multi_select?: boolean
+  input?: InputConstraints | null
+  preview?: PopupPreview | null
+  form?: PopupForm | null
+}
+
+// 多字段表单
+export interface FormField {
+  name: string
+  label?: string | null
+  type?: 'text' | 'select' | 'checkbox'
+  options?: string[]
+  required?: boolean
+  default?: string | boolean | null
+  placeholder?: string | null
+}
+
+export interface PopupForm {
+  fields: FormField[]
+}
+
+export type FormValues = Record<string, string | boolean>
+
+// 弹窗预览内容
+export type PopupPreview
+  = | { type: 'diff', diff: string, language?: string | null }
+    | { type: 'image', data?: string | null, path?: string | null, media_type?: string | null }
+
+// 自由输入约束
+export interface InputConstraints {
+  placeholder?: string | null
+  pattern?: string | null
+  min_length?: number | null
+  max_length?: number | null
+  error_message?: string | null
 }
 
 // 自定义prompt类型定义
@@ -67,6 +102,7 @@ export interface McpResponse {
   user_input: string | null
   selected_options: string[]
   images: ImageAttachment[]
+  form_values?: FormValues | null
   metadata: ResponseMetadata
 }
 
@@ -8,6 +8,8 @@ pub fn build_tauri_app() -> Builder<tauri::Wry> {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
             log_important!(info, "Another instance attempted to start, focusing existing window");
             // Optionally bring the existing window to front
@@ -55,6 +57,8 @@ pub fn build_tauri_app() -> Builder<tauri::Wry> {
             set_mcp_tool_enabled,
             get_mcp_tools_status,
             reset_mcp_tools_config,
+            get_output_language,
+            set_output_language,
             handle_mcp_popup_response,
             send_mcp_response,
             get_cli_args,
@@ -68,6 +72,13 @@ pub fn build_tauri_app() -> Builder<tauri::Wry> {
             crate::mcp::tools::acemcp::commands::clear_acemcp_cache,
             crate::mcp::tools::acemcp::commands::debug_acemcp_search,
             crate::mcp::tools::acemcp::commands::execute_acemcp_tool,
+            crate::mcp::tools::acemcp::commands::get_store_stats,
+            crate::mcp::tools::acemcp::commands::get_index_dashboard,
+            crate::mcp::tools::acemcp::commands::reindex_project,
+            crate::mcp::tools::acemcp::commands::cancel_indexing,
+            crate::mcp::tools::acemcp::commands::get_projects,
+            crate::mcp::tools::acemcp::commands::set_project_settings,
+            crate::mcp::tools::acemcp::commands::remove_project,
 
             // 上下文编排器命令
             crate::daemon::commands::set_context_orchestrator_config,
@@ -109,10 +120,14 @@ pub fn build_tauri_app() -> Builder<tauri::Wry> {
             download_and_install_update,
             get_current_version,
             restart_app,
+            get_changelog_since_current,
+            get_update_channel,
+            set_update_channel,
 
             // AGENTS.md 编辑器命令
             crate::ui::agents_commands::detect_project_agents,
             crate::ui::agents_commands::load_agents_config,
+            crate::ui::agents_commands::load_scoped_agents_config,
             crate::ui::agents_commands::save_agents_config,
             crate::ui::agents_commands::set_project_path,
             crate::ui::agents_commands::get_index_status,
@@ -120,12 +135,54 @@ pub fn build_tauri_app() -> Builder<tauri::Wry> {
             // Interact 历史记录命令
             get_interact_history_cmd,
             search_interact_history_cmd,
+            query_interact_history_cmd,
+            export_interact_history_cmd,
             clear_interact_history_cmd,
 
             // 嵌入配置命令
             get_embedding_config_cmd,
             save_embedding_config_cmd,
-            test_embedding_connection_cmd
+            test_embedding_connection_cmd,
+
+            // 配置档案命令
+            list_config_profiles,
+            create_config_profile,
+            switch_config_profile,
+
+            // 首次运行引导命令
+            crate::ui::setup_commands::get_first_run_status,
+            crate::ui::setup_commands::complete_first_run,
+            crate::ui::setup_commands::detect_search_engines,
+            crate::ui::setup_commands::list_embedding_provider_presets,
+            crate::ui::setup_commands::setup_embedding_provider,
+            crate::ui::setup_commands::suggest_setup_projects,
+            crate::ui::setup_commands::start_setup_indexing,
+
+            // 设置导出/导入命令
+            crate::ui::settings_commands::export_settings_bundle,
+            crate::ui::settings_commands::preview_settings_import,
+            crate::ui::settings_commands::apply_settings_import,
+
+            // 日志命令
+            crate::ui::log_commands::get_log_settings,
+            crate::ui::log_commands::set_log_level,
+            crate::ui::log_commands::get_recent_logs,
+            crate::ui::log_commands::get_log_directory,
+            crate::ui::log_commands::open_log_directory,
+
+            // 崩溃报告命令
+            crate::ui::crash_commands::get_pending_crash_reports,
+            crate::ui::crash_commands::get_crash_report,
+            crate::ui::crash_commands::get_crash_issue_url,
+            crate::ui::crash_commands::dismiss_crash_report,
+
+            // 匿名使用统计命令
+            crate::ui::telemetry_commands::get_telemetry_status,
+            crate::ui::telemetry_commands::set_telemetry_enabled,
+            crate::ui::telemetry_commands::clear_telemetry_queue,
+
+            // 代码图谱命令
+            crate::ui::graph_commands::export_code_graph
         ])
         .setup(|app| {
             let app_handle = app.handle().clone();
@@ -147,6 +204,11 @@ pub fn build_tauri_app() -> Builder<tauri::Wry> {
 /// 运行Tauri应用
 pub fn run_tauri_app() {
     build_tauri_app()
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            if let tauri::RunEvent::Exit = event {
+                crate::ui::exit_handler::cleanup_exit_handlers(app_handle);
+            }
+        });
 }
@@ -1,9 +1,25 @@
 use crate::app::builder::run_tauri_app;
+use crate::app::headless::{is_headless_command, print_headless_help, run_headless_command};
 use anyhow::Result;
 
 /// 处理命令行参数
 pub fn handle_cli_args() -> Result<()> {
-    let args: Vec<String> = std::env::args().collect();
+    let args = apply_global_args(std::env::args().collect())?;
+
+    // 仅 MCP stdio 服务：不启动界面和 daemon
+    if args.len() >= 2 && args[1] == "--mcp" {
+        return run_stdio_mcp(&args[2..]);
+    }
+
+    // 导出 NSP JSON Schema 后退出
+    if args.len() >= 2 && args[1] == "--nsp-schema" {
+        return print_nsp_schema(args.get(2).map(String::as_str));
+    }
+
+    // 无界面子命令：index / search / memory
+    if args.len() >= 2 && is_headless_command(&args[1]) {
+        return run_headless_command(&args[1..]);
+    }
 
     match args.len() {
         // 无参数：正常启动GUI
@@ -37,6 +53,79 @@ pub fn handle_cli_args() -> Result<()> {
     Ok(())
 }
 
+/// 取出子命令之前的全局参数并应用，返回其余参数
+///
+/// 只识别第一个子命令或位置参数之前的 `--portable`、`--profile <名称>` 与
+/// `--log-level <规则>`（如 `debug` 或 `warn,acemcp=debug`）；之后的同名文本
+/// （如 `memory add "use --portable builds"`）原样交给子命令
+fn apply_global_args(args: Vec<String>) -> Result<Vec<String>> {
+    let mut args = args.into_iter().peekable();
+    let mut remaining: Vec<String> = args.next().into_iter().collect();
+
+    let mut portable = false;
+    let mut profile = None;
+    let mut log_level = None;
+    while let Some(flag) = args.next_if(|a| matches!(a.as_str(), "--portable" | "--profile" | "--log-level")) {
+        match flag.as_str() {
+            "--portable" => portable = true,
+            "--profile" => {
+                profile = Some(args.next().ok_or_else(|| anyhow::anyhow!("--profile 需要指定档案名"))?);
+            }
+            _ => {
+                log_level = Some(args.next().ok_or_else(|| anyhow::anyhow!("--log-level 需要指定日志级别"))?);
+            }
+        }
+    }
+
+    // 便携模式决定所有目录的根，需先于档案解析
+    if portable {
+        crate::config::enable_portable_mode();
+    }
+    if let Some(name) = profile {
+        crate::config::set_profile_override(&name)?;
+    }
+    if let Some(spec) = log_level {
+        crate::utils::set_log_filter(&spec).map_err(|e| anyhow::anyhow!(e))?;
+    }
+
+    remaining.extend(args);
+    Ok(remaining)
+}
+
+/// `--mcp [--tools search,memory]`：只运行 MCP stdio 服务器，stdin 关闭后退出
+fn run_stdio_mcp(args: &[String]) -> Result<()> {
+    let tools = match args {
+        [] => None,
+        [flag, list] if flag == "--tools" => Some(
+            list.split(',')
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect::<Vec<_>>(),
+        ),
+        _ => return Err(anyhow::anyhow!("用法: --mcp [--tools <工具1,工具2>]")),
+    };
+    if tools.as_ref().is_some_and(|t| t.is_empty()) {
+        return Err(anyhow::anyhow!("--tools 需要至少指定一个工具"));
+    }
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime
+        .block_on(crate::mcp::run_server_with_tools(tools))
+        .map_err(|e| anyhow::anyhow!("{}", e))
+}
+
+/// `--nsp-schema [plan|step|meta]`：把 NSP 结构的 JSON Schema 输出到 stdout（默认 plan）
+fn print_nsp_schema(part: Option<&str>) -> Result<()> {
+    use crate::neurospec::models::{nsp_json_schema, NSP_SCHEMA_PARTS};
+
+    let part = part.unwrap_or("plan");
+    let schema = nsp_json_schema(part).ok_or_else(|| {
+        anyhow::anyhow!("未知的结构: {}（可选 {}）", part, NSP_SCHEMA_PARTS.join(" / "))
+    })?;
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
+
 /// 处理MCP请求
 fn handle_mcp_request(_request_file: &str) -> Result<()> {
     // 启动GUI处理弹窗
@@ -51,8 +140,19 @@ fn print_help() {
     println!("用法:");
     println!("  等一下                    启动设置界面");
     println!("  等一下 --mcp-request <文件>  处理 MCP 请求");
+    println!("  等一下 --mcp [--tools search,memory]");
+    println!("                             仅启动 MCP stdio 服务（可选工具子集），不启动界面和 daemon");
+    println!("  等一下 --nsp-schema [plan|step|meta]");
+    println!("                             输出 NSP 计划结构的 JSON Schema（默认 plan），供 CI 校验计划");
     println!("  等一下 --help             显示此帮助信息");
     println!("  等一下 --version          显示版本信息");
+    println!("  以下全局参数需放在子命令之前：");
+    println!("  等一下 --profile <名称> ...  使用指定配置档案（也可设置 NEUROSPEC_PROFILE）");
+    println!("  等一下 --portable ...       便携模式：数据保存在程序目录的 neurospec-data 中");
+    println!("                             （也可放置 neurospec.portable 标记文件或设置 NEUROSPEC_PORTABLE=1）");
+    println!("  等一下 --log-level <规则> ... 日志级别，支持按模块设置，如 warn,acemcp=debug（同 RUST_LOG）");
+    println!();
+    print_headless_help();
 }
 
 /// 显示版本信息
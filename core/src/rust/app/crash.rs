@@ -0,0 +1,201 @@
+//! 崩溃报告
+//!
+//! panic 时写入结构化的崩溃报告（panic 信息、调用栈、最近日志、各项目索引状态），
+//! 保存在数据目录的 `crash_reports` 中。下次启动时前端读取未处理的报告，
+//! 由用户决定是否附加到 GitHub issue，处理后的报告移入 `archived`
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::mcp::tools::unified_store::{index_state_snapshot, IndexState};
+
+/// 崩溃报告目录名（位于数据目录）
+const CRASH_REPORTS_DIR: &str = "crash_reports";
+
+/// 已处理报告的子目录
+const ARCHIVED_DIR: &str = "archived";
+
+/// 报告中附带的最近日志行数
+const LOG_TAIL_LINES: usize = 200;
+
+/// 最多保留的已处理报告数
+const MAX_ARCHIVED_REPORTS: usize = 20;
+
+/// issue 正文中附带的日志行数（URL 长度有限）
+const ISSUE_LOG_LINES: usize = 30;
+
+/// 崩溃报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub id: String,
+    pub timestamp: String,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    /// 可执行文件名（区分 GUI 与 MCP 服务）
+    pub process: String,
+    pub thread: String,
+    pub message: String,
+    pub location: Option<String>,
+    pub backtrace: String,
+    pub log_tail: Vec<String>,
+    /// 项目路径 -> 索引状态
+    pub index_states: HashMap<String, IndexState>,
+}
+
+/// 崩溃报告摘要
+#[derive(Debug, Clone, Serialize)]
+pub struct CrashReportSummary {
+    pub id: String,
+    pub timestamp: String,
+    pub app_version: String,
+    pub message: String,
+    pub path: String,
+}
+
+/// 崩溃报告目录
+pub fn crash_reports_dir() -> Option<PathBuf> {
+    crate::config::base_data_dir().map(|d| d.join(CRASH_REPORTS_DIR))
+}
+
+/// 安装 panic hook：先写崩溃报告，再交给原有 hook 输出
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic payload".to_string());
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+
+        match write_crash_report(message, location) {
+            Ok(path) => log::error!("程序崩溃，报告已保存: {}", path.display()),
+            Err(e) => log::error!("写入崩溃报告失败: {}", e),
+        }
+
+        previous(info);
+    }));
+}
+
+fn write_crash_report(message: String, location: Option<String>) -> anyhow::Result<PathBuf> {
+    let dir = crash_reports_dir().ok_or_else(|| anyhow::anyhow!("无法获取数据目录"))?;
+    fs::create_dir_all(&dir)?;
+
+    let now = chrono::Local::now();
+    let id = format!("crash-{}-{}", now.format("%Y%m%d-%H%M%S"), std::process::id());
+    let report = CrashReport {
+        id: id.clone(),
+        timestamp: now.to_rfc3339(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        process: std::env::current_exe()
+            .ok()
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+            .unwrap_or_default(),
+        thread: std::thread::current().name().unwrap_or("unnamed").to_string(),
+        message,
+        location,
+        backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+        log_tail: crate::utils::recent_logs(LOG_TAIL_LINES),
+        index_states: index_state_snapshot().unwrap_or_default(),
+    };
+
+    let path = dir.join(format!("{}.json", id));
+    fs::write(&path, serde_json::to_string_pretty(&report)?)?;
+    Ok(path)
+}
+
+fn report_path(id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("无效的崩溃报告 ID: {}", id));
+    }
+    let dir = crash_reports_dir().ok_or("无法获取数据目录")?;
+    Ok(dir.join(format!("{}.json", id)))
+}
+
+/// 读取崩溃报告
+pub fn load_crash_report(id: &str) -> Result<CrashReport, String> {
+    let content = fs::read_to_string(report_path(id)?).map_err(|e| format!("读取崩溃报告失败: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析崩溃报告失败: {}", e))
+}
+
+/// 未处理的崩溃报告，按时间倒序
+pub fn pending_crash_reports() -> Vec<CrashReportSummary> {
+    let Some(entries) = crash_reports_dir().and_then(|d| fs::read_dir(d).ok()) else {
+        return Vec::new();
+    };
+
+    let mut reports: Vec<CrashReportSummary> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().map(|ext| ext == "json").unwrap_or(false))
+        .filter_map(|path| {
+            let report: CrashReport = serde_json::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+            Some(CrashReportSummary {
+                id: report.id,
+                timestamp: report.timestamp,
+                app_version: report.app_version,
+                message: report.message,
+                path: path.to_string_lossy().to_string(),
+            })
+        })
+        .collect();
+
+    reports.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    reports
+}
+
+/// 将报告标记为已处理（移入 archived，只保留最近的若干份）
+pub fn archive_crash_report(id: &str) -> Result<(), String> {
+    let path = report_path(id)?;
+    let archived = path.with_file_name(ARCHIVED_DIR);
+    fs::create_dir_all(&archived).map_err(|e| format!("创建目录失败: {}", e))?;
+    fs::rename(&path, archived.join(format!("{}.json", id))).map_err(|e| format!("移动崩溃报告失败: {}", e))?;
+
+    if let Ok(entries) = fs::read_dir(&archived) {
+        let mut files: Vec<PathBuf> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+        // 文件名含时间戳，按名称排序即按时间排序
+        files.sort();
+        let excess = files.len().saturating_sub(MAX_ARCHIVED_REPORTS);
+        for old in files.into_iter().take(excess) {
+            let _ = fs::remove_file(old);
+        }
+    }
+    Ok(())
+}
+
+/// 生成预填内容的 GitHub issue 链接（正文只含摘要与少量日志，完整报告由用户手动附加）
+pub fn crash_issue_url(report: &CrashReport) -> Result<String, String> {
+    let title = format!("[Crash] {}", report.message.lines().next().unwrap_or_default());
+    let skip = report.log_tail.len().saturating_sub(ISSUE_LOG_LINES);
+    let body = format!(
+        "**版本**: {} ({} {})\n**进程**: {} / 线程 {}\n**位置**: {}\n**时间**: {}\n\n\
+         **Panic**:\n```\n{}\n```\n\n**最近日志**:\n```\n{}\n```\n\n\
+         > 完整崩溃报告（含调用栈与索引状态）: `{}.json`，请拖入此处附加\n",
+        report.app_version,
+        report.os,
+        report.arch,
+        report.process,
+        report.thread,
+        report.location.as_deref().unwrap_or("unknown"),
+        report.timestamp,
+        report.message,
+        report.log_tail[skip..].join("\n"),
+        report.id,
+    );
+
+    reqwest::Url::parse_with_params(
+        &format!("{}/issues/new", crate::constants::app::REPOSITORY),
+        &[("title", title.as_str()), ("body", body.as_str()), ("labels", "crash")],
+    )
+    .map(|url| url.to_string())
+    .map_err(|e| format!("生成 issue 链接失败: {}", e))
+}
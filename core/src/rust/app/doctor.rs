@@ -0,0 +1,268 @@
+//! `doctor` 诊断命令
+//!
+//! 检查配置文件、外部工具、daemon 端口、各项目索引健康度、嵌入配置和记忆数据库，
+//! 对每个问题给出可执行的修复建议。只读取配置与持久化的索引状态，不启动文件监听或后台索引
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::daemon::{is_daemon_running, DEFAULT_DAEMON_PORT};
+use crate::mcp::tools::acemcp::local_engine::{CtagsIndexer, RipgrepSearcher};
+use crate::mcp::tools::memory::SqliteStorage;
+use crate::mcp::tools::unified_store::{assess_index_health, known_projects, IndexHealth};
+use crate::neurospec::services::embedding::{embedding_config_path, load_embedding_config};
+use crate::neurospec::services::embedding::provider::create_provider;
+
+const MEMORY_DB: &str = "memory.db";
+
+/// 检查结果级别
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Warn,
+    Fail,
+}
+
+/// 单项检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// 修复建议
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+impl DoctorCheck {
+    fn ok(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self { name: name.into(), status: CheckStatus::Ok, detail: detail.into(), fix: None }
+    }
+
+    fn warn(name: impl Into<String>, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { name: name.into(), status: CheckStatus::Warn, detail: detail.into(), fix: Some(fix.into()) }
+    }
+
+    fn fail(name: impl Into<String>, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { name: name.into(), status: CheckStatus::Fail, detail: detail.into(), fix: Some(fix.into()) }
+    }
+}
+
+/// 执行全部检查
+pub fn collect_checks() -> Vec<DoctorCheck> {
+    let mut checks = vec![
+        check_storage(),
+        check_config(),
+        check_ripgrep(),
+        check_ctags(),
+        check_daemon(),
+        check_embedding_config(),
+    ];
+    for project in known_projects() {
+        checks.push(check_index_health(&project));
+        if let Some(check) = check_memory_db(&project) {
+            checks.push(check);
+        }
+    }
+    checks
+}
+
+/// 运行 doctor 并打印结果；存在失败项时返回错误，进程以非零退出码结束
+pub fn run_doctor(json: bool) -> Result<()> {
+    let checks = collect_checks();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&checks)?);
+    } else {
+        for check in &checks {
+            let mark = match check.status {
+                CheckStatus::Ok => "✔",
+                CheckStatus::Warn => "⚠",
+                CheckStatus::Fail => "✘",
+            };
+            println!("{} {}: {}", mark, check.name, check.detail);
+            if let Some(ref fix) = check.fix {
+                println!("    修复: {}", fix);
+            }
+        }
+
+        let warnings = checks.iter().filter(|c| c.status == CheckStatus::Warn).count();
+        let failures = checks.iter().filter(|c| c.status == CheckStatus::Fail).count();
+        println!();
+        println!("{} 项检查，{} 个警告，{} 个错误", checks.len(), warnings, failures);
+    }
+
+    let failures = checks.iter().filter(|c| c.status == CheckStatus::Fail).count();
+    if failures > 0 {
+        return Err(anyhow::anyhow!("{} 项检查未通过", failures));
+    }
+    Ok(())
+}
+
+fn check_storage() -> DoctorCheck {
+    let profile = crate::config::active_profile();
+    let Some(root) = crate::config::portable_root() else {
+        return DoctorCheck::ok("storage", format!("用户目录（档案: {}）", profile));
+    };
+
+    // 便携模式常见于只读介质，检查数据目录是否可写
+    let probe = root.join(".write-test");
+    let writable = std::fs::create_dir_all(root)
+        .and_then(|_| std::fs::write(&probe, b""))
+        .map(|_| {
+            let _ = std::fs::remove_file(&probe);
+        })
+        .is_ok();
+
+    if writable {
+        DoctorCheck::ok("storage", format!("便携模式: {}（档案: {}）", root.display(), profile))
+    } else {
+        DoctorCheck::fail(
+            "storage",
+            format!("便携数据目录不可写: {}", root.display()),
+            "将程序复制到可写位置，或移除 neurospec.portable 标记文件改用用户目录",
+        )
+    }
+}
+
+fn check_config() -> DoctorCheck {
+    match crate::config::load_standalone_config() {
+        Ok(_) => DoctorCheck::ok("config", "配置文件可解析"),
+        Err(e) => DoctorCheck::fail(
+            "config",
+            format!("配置文件无法读取或解析: {}", e),
+            "检查当前档案配置目录中 config.json 的 JSON 格式，或删除该文件恢复默认配置",
+        ),
+    }
+}
+
+pub(crate) fn check_ripgrep() -> DoctorCheck {
+    if RipgrepSearcher::is_available() {
+        DoctorCheck::ok("ripgrep", "rg 可用")
+    } else {
+        DoctorCheck::warn(
+            "ripgrep",
+            "未找到 rg，索引未就绪时无法回退到文本搜索",
+            "安装 ripgrep（https://github.com/BurntSushi/ripgrep）并确保 rg 在 PATH 中",
+        )
+    }
+}
+
+pub(crate) fn check_ctags() -> DoctorCheck {
+    if CtagsIndexer::is_available() {
+        DoctorCheck::ok("ctags", "ctags 可用")
+    } else {
+        DoctorCheck::warn(
+            "ctags",
+            "未找到 ctags，AST 不支持的语言将缺少符号信息",
+            "安装 Universal Ctags（https://ctags.io）并确保 ctags 在 PATH 中",
+        )
+    }
+}
+
+fn check_daemon() -> DoctorCheck {
+    let name = format!("daemon (127.0.0.1:{})", DEFAULT_DAEMON_PORT);
+
+    let running = tokio::runtime::Runtime::new()
+        .map(|rt| rt.block_on(is_daemon_running(None)))
+        .unwrap_or(false);
+    if running {
+        return DoctorCheck::ok(name, "daemon 正在运行");
+    }
+
+    // 端口被占用但健康检查失败：可能是其他进程占用了端口
+    match std::net::TcpListener::bind(("127.0.0.1", DEFAULT_DAEMON_PORT)) {
+        Ok(_) => DoctorCheck::warn(
+            name,
+            "daemon 未运行，MCP 交互弹窗不可用",
+            "启动 NeuroSpec 桌面应用",
+        ),
+        Err(_) => DoctorCheck::fail(
+            name,
+            "端口已被占用，但不是 NeuroSpec daemon",
+            format!("关闭占用端口 {} 的进程后重启 NeuroSpec", DEFAULT_DAEMON_PORT),
+        ),
+    }
+}
+
+fn check_embedding_config() -> DoctorCheck {
+    let path = embedding_config_path();
+    if !path.exists() {
+        return DoctorCheck::ok("embedding", "未配置嵌入服务，语义搜索已禁用");
+    }
+
+    let Some(config) = load_embedding_config() else {
+        return DoctorCheck::fail(
+            "embedding",
+            format!("配置文件无法解析: {}", path.display()),
+            "检查 JSON 格式及 provider/api_key/model/base_url/cache_enabled 字段，或删除该文件",
+        );
+    };
+
+    if config.api_key.trim().is_empty() {
+        return DoctorCheck::warn(
+            "embedding",
+            "缺少 API Key，嵌入服务不会启动",
+            format!("在 {} 中填写 api_key", path.display()),
+        );
+    }
+
+    match create_provider(&config) {
+        Ok(_) => DoctorCheck::ok("embedding", format!("{} / {}", config.provider, config.model)),
+        Err(e) => DoctorCheck::fail(
+            "embedding",
+            format!("配置无效: {}", e),
+            "provider 可选 jina / siliconflow / openai / dashscope / deepseek",
+        ),
+    }
+}
+
+fn check_index_health(project: &Path) -> DoctorCheck {
+    let name = format!("index {}", project.display());
+
+    if !project.exists() {
+        return DoctorCheck::warn(name, "项目目录已不存在", "忽略，或删除该项目的索引状态记录");
+    }
+
+    match assess_index_health(project) {
+        IndexHealth::Healthy => DoctorCheck::ok(name, "索引健康"),
+        IndexHealth::Degraded { reason } => DoctorCheck::warn(
+            name,
+            reason,
+            format!("运行 `neurospec index {}` 更新索引", project.display()),
+        ),
+        IndexHealth::Unhealthy { reason } => DoctorCheck::fail(
+            name,
+            reason,
+            format!("运行 `neurospec index {} --rebuild` 重建索引", project.display()),
+        ),
+    }
+}
+
+fn check_memory_db(project: &Path) -> Option<DoctorCheck> {
+    let memory_dir: PathBuf = crate::config::project_memory_dir(project);
+    if !memory_dir.join(MEMORY_DB).exists() {
+        return None;
+    }
+
+    let name = format!("memory {}", project.display());
+    let result = SqliteStorage::new(&memory_dir, &project.to_string_lossy())
+        .and_then(|storage| storage.integrity_check());
+
+    Some(match result {
+        Ok(problems) if problems.is_empty() => DoctorCheck::ok(name, "记忆数据库完好"),
+        Ok(problems) => DoctorCheck::fail(
+            name,
+            format!("完整性检查失败: {}", problems.join("; ")),
+            format!("备份后删除 {} 以重建记忆数据库", memory_dir.join(MEMORY_DB).display()),
+        ),
+        Err(e) => DoctorCheck::fail(
+            name,
+            format!("无法打开记忆数据库: {}", e),
+            format!("检查 {} 的读写权限", memory_dir.display()),
+        ),
+    })
+}
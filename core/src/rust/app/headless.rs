@@ -0,0 +1,392 @@
+//! 无界面命令行子命令
+//!
+//! 不启动 Tauri 界面即可执行索引、搜索、记忆管理和 Git 钩子安装，适用于 CI 与远程开发机
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::mcp::dispatcher::ensure_search_system_initialized;
+use crate::mcp::tools::acemcp::local_engine::{IndexingLock, SearchResult};
+use crate::mcp::tools::memory::commands::parse_category;
+use crate::mcp::tools::memory::{
+    capture_commit, install_post_commit_hook, uninstall_post_commit_hook, MemoryCategory, MemoryManager,
+};
+use crate::mcp::tools::unified_store::{
+    create_searcher_for_project, finish_indexing, flush_on_shutdown, get_global_search_config,
+    is_project_indexed, mark_indexing_started, with_global_store, with_live_indexer,
+};
+
+/// 无界面子命令名称
+const HEADLESS_COMMANDS: &[&str] = &["index", "search", "memory", "hook", "doctor", "perf"];
+
+/// 判断参数是否为无界面子命令
+pub fn is_headless_command(arg: &str) -> bool {
+    HEADLESS_COMMANDS.contains(&arg)
+}
+
+/// 执行无界面子命令（`args` 不含程序名）
+pub fn run_headless_command(args: &[String]) -> Result<()> {
+    let (command, rest) = args
+        .split_first()
+        .ok_or_else(|| anyhow::anyhow!("缺少子命令"))?;
+    let parsed = ParsedArgs::parse(rest);
+
+    match command.as_str() {
+        "index" => run_index(&parsed),
+        "search" => run_search(&parsed),
+        "memory" => run_memory(&parsed),
+        "hook" => run_hook(&parsed),
+        "doctor" => crate::app::doctor::run_doctor(parsed.flag("json")),
+        "perf" => run_perf(&parsed),
+        other => Err(anyhow::anyhow!("未知子命令: {}", other)),
+    }
+}
+
+/// 打印子命令帮助
+pub fn print_headless_help() {
+    println!("无界面子命令:");
+    println!("  index <路径> [--rebuild] [--json]");
+    println!("      索引项目（默认增量，--rebuild 全量重建）");
+    println!("  search <查询> [--mode text|symbol] [--project <路径>] [--limit <数量>] [--json]");
+    println!("      搜索已索引项目（项目未索引时先自动索引）");
+    println!("  memory list [--project <路径>] [--category <分类>] [--json]");
+    println!("  memory add <内容> [--project <路径>] [--category rule|preference|pattern|context]");
+    println!("      管理项目记忆");
+    println!("  hook install|uninstall [--project <路径>]");
+    println!("      安装 / 移除 post-commit 钩子：每次提交后自动记录一条代码修改记忆");
+    println!("  hook record [--commit <提交>] [--project <路径>] [--json]");
+    println!("      为提交记录代码修改记忆（默认 HEAD；通过 daemon 记录，daemon 未运行时直接写入）");
+    println!("  doctor [--json]");
+    println!("      诊断外部工具、daemon、索引健康度、嵌入配置和记忆数据库");
+    println!("  perf [--project <路径>] [--queries <查询1,查询2>] [--iterations <次数>] [--no-rebuild] [--json]");
+    println!("      性能基准：重建索引耗时、搜索与记忆召回的 p50/p95 延迟");
+}
+
+/// 解析后的参数：位置参数 + `--key value` / `--flag`
+struct ParsedArgs {
+    positionals: Vec<String>,
+    options: HashMap<String, Option<String>>,
+}
+
+/// 不带值的布尔开关
+const BOOL_FLAGS: &[&str] = &["json", "rebuild", "no-rebuild"];
+
+impl ParsedArgs {
+    fn parse(args: &[String]) -> Self {
+        let mut positionals = Vec::new();
+        let mut options = HashMap::new();
+        let mut iter = args.iter();
+
+        while let Some(arg) = iter.next() {
+            if let Some(key) = arg.strip_prefix("--") {
+                if BOOL_FLAGS.contains(&key) {
+                    options.insert(key.to_string(), None);
+                } else {
+                    options.insert(key.to_string(), iter.next().cloned());
+                }
+            } else {
+                positionals.push(arg.clone());
+            }
+        }
+
+        Self { positionals, options }
+    }
+
+    fn flag(&self, key: &str) -> bool {
+        self.options.contains_key(key)
+    }
+
+    fn value(&self, key: &str) -> Option<&str> {
+        self.options.get(key).and_then(|v| v.as_deref())
+    }
+
+    /// `--project` 指定的项目根目录，默认当前目录
+    fn project_root(&self) -> Result<PathBuf> {
+        let root = match self.value("project") {
+            Some(path) => PathBuf::from(path),
+            None => std::env::current_dir()?,
+        };
+        resolve_project_root(root)
+    }
+}
+
+fn resolve_project_root(path: PathBuf) -> Result<PathBuf> {
+    if let Err(e) = path.metadata() {
+        return Err(anyhow::anyhow!("项目路径无效 '{}': {}", path.display(), e));
+    }
+    Ok(crate::utils::canonical_path(&path))
+}
+
+// ============================================================================
+// index
+// ============================================================================
+
+fn run_index(args: &ParsedArgs) -> Result<()> {
+    let root = match args.positionals.first() {
+        Some(path) => resolve_project_root(PathBuf::from(path))?,
+        None => return Err(anyhow::anyhow!("用法: index <路径> [--rebuild] [--json]")),
+    };
+
+    ensure_search_system_initialized();
+    let file_count = index_project(&root, args.flag("rebuild"))?;
+
+    let stats = with_global_store(|store| store.index_project(&root))?;
+    flush_on_shutdown();
+
+    if args.flag("json") {
+        let output = serde_json::json!({
+            "project_root": root.to_string_lossy(),
+            "indexed_files": file_count,
+            "symbols": stats,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        println!("已索引: {}", root.display());
+        println!("  搜索索引文件数: {}", file_count);
+        println!(
+            "  符号: {} 个（{} 个文件重新解析，{} 个未变化，{} 个已删除）",
+            stats.total_symbols, stats.indexed, stats.skipped, stats.removed
+        );
+        let skipped = stats.skipped_files;
+        if skipped.total() > 0 {
+            println!(
+                "  跳过: {} 个文件（{} 个过大，{} 个二进制，{} 个压缩文件）",
+                skipped.total(), skipped.too_large, skipped.binary, skipped.minified
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// 构建 Tantivy 索引并同步索引状态
+///
+/// 与后台索引共用进程间索引锁，锁被其他索引任务持有时返回错误
+pub(crate) fn index_project(root: &std::path::Path, rebuild: bool) -> Result<usize> {
+    let config = get_global_search_config()?.for_project(root);
+    let Some(_lock) = IndexingLock::try_acquire(&config.index_path)? else {
+        return Err(anyhow::anyhow!("项目正在被其他索引任务索引: {}", root.display()));
+    };
+
+    mark_indexing_started(root);
+    let result = with_live_indexer(&config, root, |indexer| {
+        if rebuild {
+            indexer.rebuild_index(root)
+        } else {
+            indexer.index_directory(root)
+        }
+    });
+
+    finish_indexing(root, &result);
+    result
+}
+
+// ============================================================================
+// search
+// ============================================================================
+
+fn run_search(args: &ParsedArgs) -> Result<()> {
+    let query = args.positionals.join(" ");
+    if query.trim().is_empty() {
+        return Err(anyhow::anyhow!("用法: search <查询> [--mode text|symbol] [--project <路径>] [--json]"));
+    }
+
+    let symbol_mode = match args.value("mode").unwrap_or("text") {
+        "text" => false,
+        "symbol" => true,
+        other => return Err(anyhow::anyhow!("不支持的搜索模式: {}（可选 text / symbol）", other)),
+    };
+    let limit = match args.value("limit") {
+        Some(v) => Some(v.parse::<usize>().map_err(|_| anyhow::anyhow!("无效的 --limit: {}", v))?),
+        None => None,
+    };
+
+    let root = args.project_root()?;
+    ensure_search_system_initialized();
+
+    if !is_project_indexed(&root) {
+        eprintln!("项目尚未索引，正在索引: {}", root.display());
+        index_project(&root, false)?;
+    }
+
+    let searcher = create_searcher_for_project(&root)?;
+    let mut results: Vec<SearchResult> = if symbol_mode {
+        searcher.search_symbol(&query)?
+    } else {
+        let runtime = tokio::runtime::Runtime::new()?;
+        runtime.block_on(searcher.search_with_embedding(&query))?
+    };
+    if let Some(limit) = limit {
+        results.truncate(limit);
+    }
+
+    if args.flag("json") {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+        return Ok(());
+    }
+
+    if results.is_empty() {
+        println!("未找到结果");
+        return Ok(());
+    }
+    for result in &results {
+        println!("{}:{} (score {:.2})", result.path, result.line_number, result.score);
+        for line in result.snippet.lines() {
+            println!("    {}", line);
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+// ============================================================================
+// memory
+// ============================================================================
+
+fn run_memory(args: &ParsedArgs) -> Result<()> {
+    let (action, rest) = args
+        .positionals
+        .split_first()
+        .ok_or_else(|| anyhow::anyhow!("用法: memory <list|add> ..."))?;
+
+    let root = args.project_root()?;
+    let manager = MemoryManager::new(&root.to_string_lossy())?;
+    let category = match args.value("category") {
+        Some(name) => Some(
+            parse_category(name).ok_or_else(|| anyhow::anyhow!("未知记忆分类: {}", name))?,
+        ),
+        None => None,
+    };
+
+    match action.as_str() {
+        "list" => {
+            let memories = match category {
+                Some(category) => manager.get_memories_by_category(category)?,
+                None => manager.get_all_memories()?,
+            };
+
+            if args.flag("json") {
+                println!("{}", serde_json::to_string_pretty(&memories)?);
+            } else if memories.is_empty() {
+                println!("暂无记忆");
+            } else {
+                for memory in &memories {
+                    println!("[{}] {:?}: {}", memory.id, memory.category, memory.content);
+                }
+            }
+            Ok(())
+        }
+        "add" => {
+            let content = rest.join(" ");
+            if content.trim().is_empty() {
+                return Err(anyhow::anyhow!("用法: memory add <内容> [--category <分类>]"));
+            }
+            let id = manager.add_memory(&content, category.unwrap_or(MemoryCategory::Context))?;
+            if args.flag("json") {
+                println!("{}", serde_json::json!({ "id": id }));
+            } else {
+                println!("已添加记忆: {}", id);
+            }
+            Ok(())
+        }
+        other => Err(anyhow::anyhow!("未知 memory 操作: {}（可选 list / add）", other)),
+    }
+}
+
+// ============================================================================
+// hook
+// ============================================================================
+
+fn run_hook(args: &ParsedArgs) -> Result<()> {
+    let action = args
+        .positionals
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("用法: hook <install|uninstall|record> ..."))?;
+    let root = args.project_root()?;
+
+    match action.as_str() {
+        "install" => {
+            let path = install_post_commit_hook(&root)?;
+            println!("已安装 post-commit 钩子: {}", path.display());
+            Ok(())
+        }
+        "uninstall" => {
+            match uninstall_post_commit_hook(&root)? {
+                Some(path) => println!("已移除 post-commit 钩子: {}", path.display()),
+                None => println!("未安装 post-commit 钩子"),
+            }
+            Ok(())
+        }
+        "record" => {
+            let commit = args.value("commit").unwrap_or("HEAD");
+            let root_str = root.to_string_lossy().to_string();
+
+            let runtime = tokio::runtime::Runtime::new()?;
+            let client = crate::daemon::DaemonClient::new(None);
+            let capture = match runtime.block_on(client.record_commit(&root_str, commit)) {
+                Ok(capture) => capture,
+                // 只在 daemon 未运行时本地记录，daemon 返回的错误直接报告
+                Err(e) if is_connection_error(&e) => {
+                    crate::log_debug!("Recording commit without daemon: {}", e);
+                    ensure_search_system_initialized();
+                    serde_json::to_value(capture_commit(&root, commit)?)?
+                }
+                Err(e) => return Err(e),
+            };
+
+            if args.flag("json") {
+                println!("{}", serde_json::to_string_pretty(&capture)?);
+            } else {
+                let hash = capture["commit"].as_str().unwrap_or_default();
+                match capture["memory_id"].as_str() {
+                    Some(id) => println!("已为提交 {} 记录修改记忆: {}", hash, id),
+                    None => println!("提交 {} 已有关联的修改记忆", hash),
+                }
+            }
+            Ok(())
+        }
+        other => Err(anyhow::anyhow!("未知 hook 操作: {}（可选 install / uninstall / record）", other)),
+    }
+}
+
+/// 错误是否因为无法连接 daemon
+fn is_connection_error(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.is_connect())
+}
+
+// ============================================================================
+// perf
+// ============================================================================
+
+fn run_perf(args: &ParsedArgs) -> Result<()> {
+    use crate::app::perf::{run_perf_command, PerfOptions};
+
+    let mut options = PerfOptions {
+        rebuild: !args.flag("no-rebuild"),
+        ..PerfOptions::default()
+    };
+    if let Some(queries) = args.value("queries") {
+        options.queries = queries
+            .split(',')
+            .map(|q| q.trim().to_string())
+            .filter(|q| !q.is_empty())
+            .collect();
+        if options.queries.is_empty() {
+            return Err(anyhow::anyhow!("--queries 需要至少指定一个查询"));
+        }
+    }
+    if let Some(v) = args.value("iterations") {
+        options.iterations = v
+            .parse::<usize>()
+            .ok()
+            .filter(|n| *n > 0)
+            .ok_or_else(|| anyhow::anyhow!("无效的 --iterations: {}", v))?;
+    }
+
+    run_perf_command(args.project_root()?, options, args.flag("json"))
+}
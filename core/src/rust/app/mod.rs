@@ -2,6 +2,12 @@ pub mod setup;
 pub mod commands;
 pub mod builder;
 pub mod cli;
+pub mod headless;
+pub mod doctor;
+pub mod perf;
+pub mod crash;
+pub mod telemetry;
+pub mod otel;
 
 pub use setup::*;
 pub use commands::*;
@@ -0,0 +1,106 @@
+//! OpenTelemetry 链路追踪导出（默认关闭，需在设置中开启）
+//!
+//! MCP 工具分发、daemon 请求、搜索和嵌入调用都带有 `tracing` span。开启后 span 通过
+//! OTLP/HTTP 批量导出到收集器（otel-collector、Jaeger、Tempo 等）；MCP 服务进程调用 daemon 时
+//! 以 W3C `traceparent` 传递上下文，两个进程中的 span 属于同一条链路。
+//! 未开启时不安装订阅者，span 不产生任何数据
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use opentelemetry::propagation::TextMapPropagator;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::TracerProvider;
+use opentelemetry_sdk::{runtime, Resource};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::layer::SubscriberExt;
+
+use crate::config::TracingConfig;
+
+/// GUI/daemon 进程的服务名
+pub const DAEMON_SERVICE_NAME: &str = "neurospec-daemon";
+
+/// MCP 服务进程的服务名
+pub const MCP_SERVICE_NAME: &str = "neurospec-mcp";
+
+/// OTLP/HTTP 链路数据路径
+const TRACES_PATH: &str = "/v1/traces";
+
+static PROVIDER: OnceLock<TracerProvider> = OnceLock::new();
+
+/// 按配置启用链路追踪导出，返回是否已启用
+///
+/// 需要在 Tokio 运行时中调用（批量导出任务运行在当前运行时上）；每个进程只初始化一次，修改配置后重启生效
+pub fn init(config: &TracingConfig, service_name: &'static str) -> Result<bool> {
+    if !config.enabled {
+        return Ok(false);
+    }
+    if PROVIDER.get().is_some() {
+        return Ok(true);
+    }
+
+    let mut exporter = SpanExporter::builder().with_http();
+    if let Some(endpoint) = config.otlp_endpoint.as_deref().map(str::trim).filter(|e| !e.is_empty()) {
+        exporter = exporter.with_endpoint(traces_endpoint(endpoint));
+    }
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter.build()?, runtime::Tokio)
+        .with_resource(Resource::new(vec![
+            KeyValue::new("service.name", service_name),
+            KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+        ]))
+        .build();
+
+    let layer = tracing_opentelemetry::layer().with_tracer(provider.tracer("neurospec"));
+    tracing::subscriber::set_global_default(tracing_subscriber::registry().with(layer))?;
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+    let _ = PROVIDER.set(provider);
+
+    crate::log_important!(
+        info,
+        "OTLP trace export enabled ({})",
+        config.otlp_endpoint.as_deref().unwrap_or("default endpoint")
+    );
+    Ok(true)
+}
+
+/// 导出剩余的 span 并关闭导出器（退出时调用，会阻塞直到导出完成或超时）
+pub fn shutdown() {
+    if let Some(provider) = PROVIDER.get() {
+        if let Err(e) = provider.shutdown() {
+            crate::log_important!(warn, "Failed to flush traces: {}", e);
+        }
+    }
+}
+
+/// 当前 span 的链路上下文，随请求发给 daemon（未启用时为空）
+pub fn current_context() -> HashMap<String, String> {
+    let mut carrier = HashMap::new();
+    if PROVIDER.get().is_some() {
+        let context = tracing::Span::current().context();
+        TraceContextPropagator::new().inject_context(&context, &mut carrier);
+    }
+    carrier
+}
+
+/// 以调用方传来的链路上下文作为 span 的父级
+pub fn set_parent(span: &tracing::Span, carrier: &HashMap<String, String>) {
+    if PROVIDER.get().is_none() || carrier.is_empty() {
+        return;
+    }
+    span.set_parent(TraceContextPropagator::new().extract(carrier));
+}
+
+/// 基础地址补全为链路数据地址（`http://host:4318` → `http://host:4318/v1/traces`）
+fn traces_endpoint(endpoint: &str) -> String {
+    let endpoint = endpoint.trim_end_matches('/');
+    if endpoint.ends_with(TRACES_PATH) {
+        endpoint.to_string()
+    } else {
+        format!("{}{}", endpoint, TRACES_PATH)
+    }
+}
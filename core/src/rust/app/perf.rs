@@ -0,0 +1,192 @@
+//! 性能基准（`perf` 子命令与 `perf_profile` 工具）
+//!
+//! 对当前项目运行一组固定的基准：全量重建索引耗时、查询集的全文/符号搜索延迟
+//! （p50 / p95）以及记忆召回延迟。查询集和迭代次数固定时，不同版本的报告可以直接对比
+
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::app::headless::index_project;
+use crate::mcp::dispatcher::ensure_search_system_initialized;
+use crate::mcp::tools::memory::MemoryManager;
+use crate::mcp::tools::unified_store::{create_searcher_for_project, is_project_indexed};
+
+/// 默认查询集：覆盖常见的短词、多词和标识符查询
+pub const DEFAULT_QUERIES: &[&str] = &["config", "error handling", "parse", "init", "test", "request"];
+
+/// 每个查询默认重复次数
+pub const DEFAULT_ITERATIONS: usize = 5;
+
+/// 基准选项
+#[derive(Debug, Clone)]
+pub struct PerfOptions {
+    pub queries: Vec<String>,
+    pub iterations: usize,
+    /// 是否测量全量重建索引（耗时较长）
+    pub rebuild: bool,
+}
+
+impl Default for PerfOptions {
+    fn default() -> Self {
+        Self {
+            queries: DEFAULT_QUERIES.iter().map(|q| q.to_string()).collect(),
+            iterations: DEFAULT_ITERATIONS,
+            rebuild: true,
+        }
+    }
+}
+
+/// 一组延迟样本的统计（毫秒）
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LatencyStats {
+    pub samples: usize,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+}
+
+impl LatencyStats {
+    fn from_samples(mut samples: Vec<f64>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort_by(|a, b| a.total_cmp(b));
+        Self {
+            samples: samples.len(),
+            p50_ms: percentile(&samples, 0.50),
+            p95_ms: percentile(&samples, 0.95),
+            max_ms: samples[samples.len() - 1],
+        }
+    }
+}
+
+/// 基准报告
+#[derive(Debug, Clone, Serialize)]
+pub struct PerfReport {
+    pub project_root: String,
+    pub version: String,
+    /// 报告生成时间（RFC 3339）
+    pub generated_at: String,
+    pub queries: Vec<String>,
+    pub iterations: usize,
+    pub indexed_files: usize,
+    /// 全量重建索引耗时（未测量时为 None）
+    pub index_rebuild_ms: Option<f64>,
+    pub text_search: LatencyStats,
+    pub symbol_search: LatencyStats,
+    pub memory_recall: LatencyStats,
+    pub memory_count: usize,
+}
+
+/// 对项目运行基准
+pub fn run_profile(root: &Path, options: &PerfOptions) -> Result<PerfReport> {
+    ensure_search_system_initialized();
+
+    let (indexed_files, index_rebuild_ms) = if options.rebuild || !is_project_indexed(root) {
+        let start = Instant::now();
+        let count = index_project(root, options.rebuild)?;
+        let elapsed = elapsed_ms(start);
+        (count, options.rebuild.then_some(elapsed))
+    } else {
+        (crate::mcp::tools::unified_store::get_indexed_file_count(root).unwrap_or(0), None)
+    };
+
+    // 先打开 reader，避免把首次打开索引的耗时计入查询延迟
+    let searcher = create_searcher_for_project(root)?;
+    let mut text_samples = Vec::new();
+    let mut symbol_samples = Vec::new();
+    for _ in 0..options.iterations {
+        for query in &options.queries {
+            let start = Instant::now();
+            searcher.search(query)?;
+            text_samples.push(elapsed_ms(start));
+
+            let start = Instant::now();
+            searcher.search_symbol(query)?;
+            symbol_samples.push(elapsed_ms(start));
+        }
+    }
+
+    // 没有记忆目录的项目不测量召回，避免为基准创建空数据库
+    let mut memory_count = 0;
+    let mut recall_samples = Vec::new();
+    if crate::config::project_memory_dir(root).exists() {
+        let manager = MemoryManager::new(&root.to_string_lossy())?;
+        memory_count = manager.count_memories()?;
+        for _ in 0..options.iterations {
+            for query in &options.queries {
+                let start = Instant::now();
+                manager.smart_recall(Some(query), 10, None)?;
+                recall_samples.push(elapsed_ms(start));
+            }
+        }
+    }
+
+    Ok(PerfReport {
+        project_root: root.to_string_lossy().to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        queries: options.queries.clone(),
+        iterations: options.iterations,
+        indexed_files,
+        index_rebuild_ms,
+        text_search: LatencyStats::from_samples(text_samples),
+        symbol_search: LatencyStats::from_samples(symbol_samples),
+        memory_recall: LatencyStats::from_samples(recall_samples),
+        memory_count,
+    })
+}
+
+/// 以文本形式输出报告
+pub fn format_report(report: &PerfReport) -> String {
+    let mut out = format!(
+        "性能基准 v{} — {}\n  {} 个查询 × {} 次，已索引 {} 个文件，{} 条记忆\n\n",
+        report.version,
+        report.project_root,
+        report.queries.len(),
+        report.iterations,
+        report.indexed_files,
+        report.memory_count
+    );
+    match report.index_rebuild_ms {
+        Some(ms) => out.push_str(&format!("  全量重建索引: {:.1} ms\n", ms)),
+        None => out.push_str("  全量重建索引: 未测量\n"),
+    }
+    for (name, stats) in [
+        ("全文搜索", &report.text_search),
+        ("符号搜索", &report.symbol_search),
+        ("记忆召回", &report.memory_recall),
+    ] {
+        out.push_str(&format!(
+            "  {}: p50 {:.2} ms / p95 {:.2} ms / max {:.2} ms（{} 次）\n",
+            name, stats.p50_ms, stats.p95_ms, stats.max_ms, stats.samples
+        ));
+    }
+    out
+}
+
+/// `perf [--project <路径>] [--queries a,b] [--iterations N] [--no-rebuild] [--json]`
+pub fn run_perf_command(root: PathBuf, options: PerfOptions, json: bool) -> Result<()> {
+    let report = run_profile(&root, &options)?;
+    crate::mcp::tools::unified_store::flush_on_shutdown();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", format_report(&report));
+    }
+    Ok(())
+}
+
+fn elapsed_ms(start: Instant) -> f64 {
+    start.elapsed().as_secs_f64() * 1000.0
+}
+
+/// 最近秩法百分位（样本已排序）
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
@@ -5,7 +5,7 @@ use crate::ui::tray::create_tray;
 use crate::daemon::start_daemon_server_with_app;
 use crate::mcp::tools::interaction::init_interact_history;
 use crate::log_important;
-use tauri::{AppHandle, Manager};
+use tauri::{AppHandle, Emitter, Manager};
 
 /// 应用设置和初始化
 pub async fn setup_application(app_handle: &AppHandle) -> Result<(), String> {
@@ -16,6 +16,12 @@ pub async fn setup_application(app_handle: &AppHandle) -> Result<(), String> {
         log_important!(warn, "加载配置失败: {}", e);
     }
 
+    // 链路追踪导出（默认关闭）
+    let tracing_config = state.config.lock().map(|config| config.tracing_config.clone()).unwrap_or_default();
+    if let Err(e) = crate::app::otel::init(&tracing_config, crate::app::otel::DAEMON_SERVICE_NAME) {
+        log_important!(warn, "启用链路追踪导出失败: {}", e);
+    }
+
     // 初始化交互历史记录系统
     if let Err(e) = init_interact_history() {
         log_important!(warn, "初始化交互历史失败: {}", e);
@@ -54,6 +60,15 @@ pub async fn setup_application(app_handle: &AppHandle) -> Result<(), String> {
         log_important!(info, "Config watcher started successfully");
     }
 
+    // 将存储/索引更新事件转发给前端
+    forward_store_events(app_handle.clone());
+
+    // 配置变化时重新初始化受影响的子系统
+    apply_config_changes(app_handle.clone());
+
+    // 匿名使用统计定期上报（未开启时不发送任何数据）
+    crate::app::telemetry::start_upload_task();
+
     // Explicitly show main window to ensure it appears in taskbar
     if let Some(window) = app_handle.get_webview_window("main") {
         if let Err(e) = window.show() {
@@ -66,3 +81,82 @@ pub async fn setup_application(app_handle: &AppHandle) -> Result<(), String> {
 
     Ok(())
 }
+
+/// 订阅存储事件并以 `store-event` 转发到前端
+fn forward_store_events(app_handle: AppHandle) {
+    use crate::mcp::tools::unified_store::subscribe_store_events;
+    use tokio::sync::broadcast::error::RecvError;
+
+    let mut receiver = subscribe_store_events();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    let _ = app_handle.emit("store-event", &event);
+                }
+                // 前端只关心最新状态，落后时跳过即可
+                Err(RecvError::Lagged(skipped)) => {
+                    crate::log_debug!("Store event forwarder lagged, skipped {} events", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+/// 订阅配置变更，按变化的配置段重新初始化嵌入、搜索、daemon 和记忆子系统，
+/// 并以 `config-changed` 通知前端
+fn apply_config_changes(app_handle: AppHandle) {
+    use crate::config::{subscribe_config_changes, ConfigSection};
+    use tokio::sync::broadcast::error::RecvError;
+
+    let mut receiver = subscribe_config_changes();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let event = match receiver.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    crate::log_debug!("Config event consumer lagged, skipped {} events", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+
+            if event.touches(ConfigSection::Embedding) {
+                match crate::neurospec::services::reload_embedding_service().await {
+                    Ok(enabled) => log_important!(info, "Embedding service reloaded (enabled: {})", enabled),
+                    Err(e) => log_important!(warn, "Failed to reload embedding service: {}", e),
+                }
+            }
+
+            if event.touches(ConfigSection::Search) {
+                if let Err(e) = crate::mcp::tools::unified_store::apply_search_config(&event.config.search_config) {
+                    log_important!(warn, "Failed to apply search config: {}", e);
+                }
+            }
+
+            // WebSocket 开关与 HTTP 超时由 MCP 服务进程在每次请求时读取，这里只更新 daemon 侧的弹窗超时
+            if event.touches(ConfigSection::Daemon) {
+                crate::daemon::set_daemon_config(event.config.daemon_config.clone());
+            }
+
+            if event.touches(ConfigSection::Orchestrator) {
+                crate::daemon::set_orchestrator_config((&event.config.orchestrator_config).into());
+            }
+
+            if event.touches(ConfigSection::Mcp) {
+                crate::mcp::tools::memory::MemoryTool::reset_caches();
+            }
+
+            if event.touches(ConfigSection::Telemetry) {
+                crate::app::telemetry::set_enabled(event.config.telemetry_config.enabled);
+            }
+
+            if event.touches(ConfigSection::Projects) {
+                crate::config::invalidate_project_settings();
+            }
+
+            let _ = app_handle.emit("config-changed", &event.sections);
+        }
+    });
+}
@@ -0,0 +1,359 @@
+//! 匿名使用统计（默认关闭，需用户在设置中主动开启）
+//!
+//! 只统计聚合计数：各工具调用次数与失败次数、索引过的语言、错误类别。
+//! 不记录代码内容、文件路径、搜索词或错误信息正文。
+//!
+//! 计数先在进程内累加，定期追加到本地队列 `telemetry/queue.jsonl`
+//! （GUI 与 MCP 服务进程共用），由 GUI 进程每天合并上报一次；
+//! 上报失败或未配置上报地址时数据保留在本地
+
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::TtlCache;
+
+/// 统计目录名（位于数据目录）
+const TELEMETRY_DIR: &str = "telemetry";
+
+/// 本地队列文件
+const QUEUE_FILE: &str = "queue.jsonl";
+
+/// 待上报批次文件前缀
+const PENDING_PREFIX: &str = "pending-";
+
+/// 进程内计数写入队列的间隔
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 上报间隔
+const UPLOAD_INTERVAL: Duration = Duration::from_secs(24 * 3600);
+
+/// 启动后首次上报前的等待时间
+const UPLOAD_INITIAL_DELAY: Duration = Duration::from_secs(10 * 60);
+
+/// 重新读取配置中开关状态的间隔
+const ENABLED_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 最多保留的待上报批次（长期离线时丢弃最旧的）
+const MAX_PENDING_BATCHES: usize = 30;
+
+/// 聚合计数
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageCounters {
+    /// 工具名 -> 调用次数
+    #[serde(default)]
+    pub tool_calls: BTreeMap<String, u64>,
+    /// 工具名 -> 失败次数
+    #[serde(default)]
+    pub tool_errors: BTreeMap<String, u64>,
+    /// 语言 -> 索引次数
+    #[serde(default)]
+    pub languages_indexed: BTreeMap<String, u64>,
+    /// 错误类别 -> 次数
+    #[serde(default)]
+    pub errors: BTreeMap<String, u64>,
+}
+
+impl UsageCounters {
+    fn is_empty(&self) -> bool {
+        self.tool_calls.is_empty()
+            && self.tool_errors.is_empty()
+            && self.languages_indexed.is_empty()
+            && self.errors.is_empty()
+    }
+
+    fn merge(&mut self, other: UsageCounters) {
+        fn add(target: &mut BTreeMap<String, u64>, source: BTreeMap<String, u64>) {
+            for (key, count) in source {
+                *target.entry(key).or_insert(0) += count;
+            }
+        }
+        add(&mut self.tool_calls, other.tool_calls);
+        add(&mut self.tool_errors, other.tool_errors);
+        add(&mut self.languages_indexed, other.languages_indexed);
+        add(&mut self.errors, other.errors);
+    }
+}
+
+/// 队列中的一条记录
+#[derive(Debug, Serialize, Deserialize)]
+struct QueuedBatch {
+    recorded_at: String,
+    usage: UsageCounters,
+}
+
+/// 上报内容（设置界面可预览完整内容）
+#[derive(Debug, Clone, Serialize)]
+pub struct TelemetryReport {
+    pub install_id: String,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub period_start: Option<String>,
+    pub period_end: Option<String>,
+    pub usage: UsageCounters,
+}
+
+struct PendingUsage {
+    counters: UsageCounters,
+    last_flush: Instant,
+}
+
+lazy_static! {
+    static ref PENDING: Mutex<PendingUsage> = Mutex::new(PendingUsage {
+        counters: UsageCounters::default(),
+        last_flush: Instant::now(),
+    });
+}
+
+static ENABLED: TtlCache<bool> = TtlCache::new(ENABLED_REFRESH_INTERVAL);
+
+/// 是否开启统计
+///
+/// 开关状态定期从配置文件重新读取
+pub fn is_enabled() -> bool {
+    ENABLED.get_or_refresh(|| {
+        crate::config::load_standalone_config()
+            .map(|c| c.telemetry_config.enabled)
+            .unwrap_or(false)
+    })
+}
+
+/// 更新开关状态；关闭时清空进程内计数与本地队列
+pub fn set_enabled(enabled: bool) {
+    ENABLED.set(enabled);
+    if !enabled {
+        if let Ok(mut pending) = PENDING.lock() {
+            pending.counters = UsageCounters::default();
+        }
+        clear_queue();
+    }
+}
+
+/// 生成新的匿名安装 ID
+pub fn new_install_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+fn record(update: impl FnOnce(&mut UsageCounters)) {
+    if !is_enabled() {
+        return;
+    }
+    let Ok(mut pending) = PENDING.lock() else {
+        return;
+    };
+    update(&mut pending.counters);
+    if pending.last_flush.elapsed() >= FLUSH_INTERVAL {
+        flush_locked(&mut pending);
+    }
+}
+
+/// 记录一次工具调用（只记录已注册的工具名）
+pub fn record_tool_call(tool: &str, success: bool) {
+    record(|counters| {
+        *counters.tool_calls.entry(tool.to_string()).or_insert(0) += 1;
+        if !success {
+            *counters.tool_errors.entry(tool.to_string()).or_insert(0) += 1;
+        }
+    });
+}
+
+/// 记录一次索引涉及的语言
+pub fn record_languages_indexed<'a>(languages: impl IntoIterator<Item = &'a str>) {
+    record(|counters| {
+        for language in languages {
+            *counters.languages_indexed.entry(language.to_string()).or_insert(0) += 1;
+        }
+    });
+}
+
+/// 记录一次错误（类别为固定字符串，不含错误信息）
+pub fn record_error(category: &'static str) {
+    record(|counters| {
+        *counters.errors.entry(category.to_string()).or_insert(0) += 1;
+    });
+}
+
+fn telemetry_dir() -> Option<PathBuf> {
+    crate::config::base_data_dir().map(|d| d.join(TELEMETRY_DIR))
+}
+
+fn flush_locked(pending: &mut PendingUsage) {
+    pending.last_flush = Instant::now();
+    if pending.counters.is_empty() {
+        return;
+    }
+
+    let batch = QueuedBatch {
+        recorded_at: chrono::Utc::now().to_rfc3339(),
+        usage: std::mem::take(&mut pending.counters),
+    };
+    if let Err(e) = append_to_queue(&batch) {
+        crate::log_debug!("写入统计队列失败: {}", e);
+    }
+}
+
+fn append_to_queue(batch: &QueuedBatch) -> anyhow::Result<()> {
+    let dir = telemetry_dir().ok_or_else(|| anyhow::anyhow!("无法获取数据目录"))?;
+    fs::create_dir_all(&dir)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(dir.join(QUEUE_FILE))?;
+    writeln!(file, "{}", serde_json::to_string(batch)?)?;
+    Ok(())
+}
+
+/// 将进程内计数写入本地队列（退出前调用）
+pub fn flush_queue() {
+    if !is_enabled() {
+        return;
+    }
+    if let Ok(mut pending) = PENDING.lock() {
+        flush_locked(&mut pending);
+    }
+}
+
+/// 清空本地队列
+pub fn clear_queue() {
+    if let Some(dir) = telemetry_dir() {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+/// 把队列文件转为待上报批次，返回全部批次文件（按时间排序）
+fn take_pending_batches() -> Vec<PathBuf> {
+    let Some(dir) = telemetry_dir() else {
+        return Vec::new();
+    };
+
+    let queue = dir.join(QUEUE_FILE);
+    if queue.exists() {
+        let target = dir.join(format!("{}{}.jsonl", PENDING_PREFIX, chrono::Utc::now().timestamp_millis()));
+        // 其他进程正在写入时重命名可能失败，留到下次处理
+        let _ = fs::rename(&queue, target);
+    }
+
+    let mut batches: Vec<PathBuf> = fs::read_dir(&dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| {
+                    p.file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|n| n.starts_with(PENDING_PREFIX))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    batches.sort();
+
+    let excess = batches.len().saturating_sub(MAX_PENDING_BATCHES);
+    for old in batches.drain(..excess) {
+        let _ = fs::remove_file(old);
+    }
+    batches
+}
+
+/// 汇总待上报内容（未开启或没有数据时返回 None）
+pub fn pending_report() -> Option<TelemetryReport> {
+    let config = crate::config::load_standalone_config().ok()?.telemetry_config;
+    if !config.enabled {
+        return None;
+    }
+    flush_queue();
+    build_report(config.install_id?, &take_pending_batches())
+}
+
+fn build_report(install_id: String, batches: &[PathBuf]) -> Option<TelemetryReport> {
+    let mut usage = UsageCounters::default();
+    let mut recorded: Vec<String> = Vec::new();
+
+    for path in batches {
+        let Ok(content) = fs::read_to_string(path) else {
+            continue;
+        };
+        for batch in content.lines().filter_map(|l| serde_json::from_str::<QueuedBatch>(l).ok()) {
+            recorded.push(batch.recorded_at);
+            usage.merge(batch.usage);
+        }
+    }
+
+    if usage.is_empty() {
+        return None;
+    }
+
+    Some(TelemetryReport {
+        install_id,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        period_start: recorded.iter().min().cloned(),
+        period_end: recorded.iter().max().cloned(),
+        usage,
+    })
+}
+
+/// 上报本地队列中的统计，返回是否实际发送
+///
+/// 未配置上报地址时数据保留在本地队列
+pub async fn upload_pending() -> anyhow::Result<bool> {
+    let config = crate::config::load_standalone_config()?.telemetry_config;
+    if !config.enabled {
+        return Ok(false);
+    }
+    let Some(endpoint) = config
+        .endpoint
+        .clone()
+        .filter(|e| !e.trim().is_empty())
+        .or_else(|| crate::constants::app::TELEMETRY_ENDPOINT.map(|e| e.to_string()))
+    else {
+        return Ok(false);
+    };
+    let Some(install_id) = config.install_id else {
+        return Ok(false);
+    };
+
+    flush_queue();
+    let batches = take_pending_batches();
+    let Some(report) = build_report(install_id, &batches) else {
+        return Ok(false);
+    };
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .user_agent(crate::constants::app::USER_AGENT)
+        .build()?;
+    client
+        .post(&endpoint)
+        .json(&report)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    for path in batches {
+        let _ = fs::remove_file(path);
+    }
+    Ok(true)
+}
+
+/// 启动定期上报任务（仅 GUI 进程）
+pub fn start_upload_task() {
+    tauri::async_runtime::spawn(async {
+        tokio::time::sleep(UPLOAD_INITIAL_DELAY).await;
+        while !crate::utils::is_shutting_down() {
+            if is_enabled() {
+                match upload_pending().await {
+                    Ok(true) => log::info!("匿名使用统计已上报"),
+                    Ok(false) => {}
+                    Err(e) => crate::log_debug!("匿名使用统计上报失败: {}", e),
+                }
+            }
+            tokio::time::sleep(UPLOAD_INTERVAL).await;
+        }
+    });
+}
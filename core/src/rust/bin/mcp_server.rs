@@ -6,6 +6,22 @@ use neurospec::daemon::{is_daemon_running, DEFAULT_DAEMON_PORT};
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize logging system
     auto_init_logger()?;
+    neurospec::app::crash::install_panic_hook();
+
+    // --log-level <规则> 覆盖 RUST_LOG，如 warn,acemcp=debug
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(spec) = args.iter().position(|a| a == "--log-level").and_then(|i| args.get(i + 1)) {
+        if let Err(e) = neurospec::utils::set_log_filter(spec) {
+            log_important!(warn, "Invalid --log-level '{}': {}", spec, e);
+        }
+    }
+
+    // 链路追踪导出（默认关闭）
+    if let Ok(config) = neurospec::config::load_standalone_config() {
+        if let Err(e) = neurospec::app::otel::init(&config.tracing_config, neurospec::app::otel::MCP_SERVICE_NAME) {
+            log_important!(warn, "Failed to enable OTLP trace export: {}", e);
+        }
+    }
 
     log_important!(info, "Starting NeuroSpec MCP Server (Client Mode)");
     
@@ -30,5 +46,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         log_important!(info, "Daemon health check passed");
     }
     
-    run_server().await
+    let result = run_server().await;
+    // 导出器关闭时会阻塞等待剩余 span 发送完成
+    let _ = tokio::task::spawn_blocking(neurospec::app::otel::shutdown).await;
+    result
 }
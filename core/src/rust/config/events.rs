@@ -0,0 +1,140 @@
+//! 配置变更事件
+//!
+//! 配置文件重新加载后与上次生效的配置逐段比较，只广播发生变化的配置段，
+//! 嵌入服务、搜索、daemon 和记忆子系统订阅后各自重新初始化受影响的组件
+
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use super::settings::AppConfig;
+
+/// 广播通道容量（配置变化频率很低）
+const EVENT_CHANNEL_CAPACITY: usize = 16;
+
+/// 配置段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSection {
+    Ui,
+    Reply,
+    Mcp,
+    CustomPrompt,
+    Shortcut,
+    Daemon,
+    Search,
+    Orchestrator,
+    Telemetry,
+    Updater,
+    Tracing,
+    /// 嵌入服务配置（独立文件 embedding_config.json）
+    Embedding,
+    /// 按项目的设置（项目注册表 projects.json）
+    Projects,
+}
+
+/// 配置变更事件
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigChanged {
+    /// 发生变化的配置段
+    pub sections: Vec<ConfigSection>,
+    /// 变更后的完整配置
+    #[serde(skip)]
+    pub config: AppConfig,
+}
+
+impl ConfigChanged {
+    /// 是否包含指定配置段
+    pub fn touches(&self, section: ConfigSection) -> bool {
+        self.sections.contains(&section)
+    }
+}
+
+lazy_static! {
+    static ref CONFIG_EVENTS: broadcast::Sender<ConfigChanged> = broadcast::channel(EVENT_CHANNEL_CAPACITY).0;
+
+    /// 上次生效的配置（用于计算变化的配置段）
+    static ref LAST_APPLIED: Mutex<Option<AppConfig>> = Mutex::new(None);
+}
+
+/// 订阅配置变更事件
+///
+/// 只会收到订阅之后发生的事件
+pub fn subscribe_config_changes() -> broadcast::Receiver<ConfigChanged> {
+    CONFIG_EVENTS.subscribe()
+}
+
+/// 比较两份配置，返回发生变化的配置段
+pub fn diff_config(old: &AppConfig, new: &AppConfig) -> Vec<ConfigSection> {
+    fn changed<T: Serialize>(a: &T, b: &T) -> bool {
+        serde_json::to_value(a).ok() != serde_json::to_value(b).ok()
+    }
+
+    let mut sections = Vec::new();
+    if changed(&old.ui_config, &new.ui_config) {
+        sections.push(ConfigSection::Ui);
+    }
+    if changed(&old.reply_config, &new.reply_config) {
+        sections.push(ConfigSection::Reply);
+    }
+    if changed(&old.mcp_config, &new.mcp_config) {
+        sections.push(ConfigSection::Mcp);
+    }
+    if changed(&old.custom_prompt_config, &new.custom_prompt_config) {
+        sections.push(ConfigSection::CustomPrompt);
+    }
+    if changed(&old.shortcut_config, &new.shortcut_config) {
+        sections.push(ConfigSection::Shortcut);
+    }
+    if changed(&old.daemon_config, &new.daemon_config) {
+        sections.push(ConfigSection::Daemon);
+    }
+    if changed(&old.search_config, &new.search_config) {
+        sections.push(ConfigSection::Search);
+    }
+    if changed(&old.orchestrator_config, &new.orchestrator_config) {
+        sections.push(ConfigSection::Orchestrator);
+    }
+    if changed(&old.telemetry_config, &new.telemetry_config) {
+        sections.push(ConfigSection::Telemetry);
+    }
+    if changed(&old.updater_config, &new.updater_config) {
+        sections.push(ConfigSection::Updater);
+    }
+    if changed(&old.tracing_config, &new.tracing_config) {
+        sections.push(ConfigSection::Tracing);
+    }
+    sections
+}
+
+/// 记录新生效的配置并广播变化的配置段
+///
+/// 首次调用只记录基线，不广播（各子系统启动时已按当前配置初始化）
+pub fn publish_config(config: &AppConfig) {
+    let sections = {
+        let mut last = match LAST_APPLIED.lock() {
+            Ok(guard) => guard,
+            Err(_) => return,
+        };
+        let sections = last
+            .as_ref()
+            .map(|old| diff_config(old, config))
+            .unwrap_or_default();
+        *last = Some(config.clone());
+        sections
+    };
+
+    if !sections.is_empty() {
+        emit_config_changed(sections, config.clone());
+    }
+}
+
+/// 广播配置变更（没有订阅者时直接丢弃）
+pub fn emit_config_changed(sections: Vec<ConfigSection>, config: AppConfig) {
+    crate::log_important!(info, "Config sections changed: {:?}", sections);
+    if CONFIG_EVENTS.receiver_count() > 0 {
+        let _ = CONFIG_EVENTS.send(ConfigChanged { sections, config });
+    }
+}
@@ -1,7 +1,15 @@
+pub mod events;
+pub mod portable;
+pub mod profile;
+pub mod projects;
 pub mod settings;
 pub mod storage;
 pub mod watcher;
 
+pub use events::*;
+pub use portable::*;
+pub use profile::*;
+pub use projects::*;
 pub use settings::*;
 pub use storage::*;
 pub use watcher::*;
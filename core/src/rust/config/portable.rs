@@ -0,0 +1,141 @@
+//! 便携模式
+//!
+//! 开启后所有状态（配置、缓存、索引、记忆数据库、交互历史）都保存在可执行文件旁的
+//! `neurospec-data` 目录中，不再写入用户目录或系统缓存目录，适用于 U 盘和受限的企业环境。
+//!
+//! 开启方式（任一即可）：
+//! - 可执行文件旁存在 `neurospec.portable` 标记文件
+//! - 命令行 `--portable`
+//! - 环境变量 `NEUROSPEC_PORTABLE=1`
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// 便携模式标记文件名（与可执行文件同目录）
+pub const PORTABLE_MARKER_FILE: &str = "neurospec.portable";
+
+/// 开启便携模式的环境变量
+pub const PORTABLE_ENV_VAR: &str = "NEUROSPEC_PORTABLE";
+
+/// 便携数据目录名（与可执行文件同目录）
+const PORTABLE_DATA_DIR: &str = "neurospec-data";
+
+/// 便携数据根目录（None 表示未开启便携模式）
+static PORTABLE_ROOT: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// 命令行开启便携模式（需在任何路径解析之前调用）
+pub fn enable_portable_mode() {
+    let _ = PORTABLE_ROOT.set(executable_dir().map(|dir| dir.join(PORTABLE_DATA_DIR)));
+}
+
+/// 便携数据根目录
+pub fn portable_root() -> Option<&'static Path> {
+    PORTABLE_ROOT.get_or_init(resolve_portable_root).as_deref()
+}
+
+/// 是否处于便携模式
+pub fn is_portable_mode() -> bool {
+    portable_root().is_some()
+}
+
+fn resolve_portable_root() -> Option<PathBuf> {
+    let exe_dir = executable_dir()?;
+
+    let env_enabled = std::env::var(PORTABLE_ENV_VAR)
+        .map(|v| matches!(v.trim(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+
+    if env_enabled || exe_dir.join(PORTABLE_MARKER_FILE).exists() {
+        let root = exe_dir.join(PORTABLE_DATA_DIR);
+        log::info!("便携模式已开启，数据目录: {}", root.display());
+        Some(root)
+    } else {
+        None
+    }
+}
+
+fn executable_dir() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|p| p.to_path_buf()))
+}
+
+/// 全局配置根目录：便携模式下为 `<数据目录>/config`，否则为系统配置目录下的 `neurospec`
+pub fn base_config_dir() -> Option<PathBuf> {
+    match portable_root() {
+        Some(root) => Some(root.join("config")),
+        None => dirs::config_dir().map(|d| d.join("neurospec")),
+    }
+}
+
+/// 缓存根目录（符号存储、搜索索引）
+pub fn base_cache_dir() -> PathBuf {
+    match portable_root() {
+        Some(root) => root.join("cache"),
+        None => dirs::cache_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("neurospec"),
+    }
+}
+
+/// 应用数据目录（交互历史、项目配置）
+pub fn base_data_dir() -> Option<PathBuf> {
+    match portable_root() {
+        Some(root) => Some(root.join("data")),
+        None => dirs::data_dir()
+            .or_else(dirs::home_dir)
+            .map(|d| d.join("neurospec")),
+    }
+}
+
+/// 嵌入服务根目录（替代 `~/.neurospec`）
+pub fn base_embedding_dir() -> PathBuf {
+    match portable_root() {
+        Some(root) => root.join("embedding"),
+        None => dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".neurospec"),
+    }
+}
+
+/// 本地搜索索引根目录（替代 `~/.acemcp`）
+pub fn base_index_dir() -> PathBuf {
+    match portable_root() {
+        Some(root) => root.join("index"),
+        None => dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".acemcp"),
+    }
+}
+
+/// 项目记忆根目录
+///
+/// 非便携模式下位于项目内的 `.neurospec-memory`；
+/// 便携模式下按项目路径哈希存放在 `<数据目录>/memory/<项目名>-<哈希>`，不在项目中写入任何文件
+pub fn base_memory_dir(project_root: &Path) -> PathBuf {
+    match portable_root() {
+        Some(root) => {
+            let name = project_root
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| "project".to_string());
+            let dir_for_key = |key: &str| {
+                let digest = ring::digest::digest(&ring::digest::SHA256, key.as_bytes());
+                root.join("memory")
+                    .join(format!("{}-{}", name, &hex::encode(digest.as_ref())[..16]))
+            };
+
+            let dir = dir_for_key(&crate::utils::path_key(project_root));
+            // 旧版本按原始路径字符串计算哈希，首次访问时迁移到规范化键对应的目录
+            let legacy = dir_for_key(&project_root.to_string_lossy().replace('\\', "/"));
+            if legacy != dir && legacy.exists() && !dir.exists() {
+                if let Err(e) = std::fs::rename(&legacy, &dir) {
+                    log::debug!("迁移记忆目录失败: {}", e);
+                    return legacy;
+                }
+            }
+            dir
+        }
+        None => project_root.join(".neurospec-memory"),
+    }
+}
@@ -0,0 +1,174 @@
+//! 配置档案（profile）
+//!
+//! 同一台机器上可以维护多套相互隔离的配置，例如使用公司嵌入服务的 "work"
+//! 与使用本地模型的 "personal"。嵌入服务、搜索索引、符号存储与记忆数据库
+//! 的路径都通过当前档案解析。
+//!
+//! 当前档案的确定顺序：
+//! 1. 命令行 `--profile <名称>`
+//! 2. 环境变量 `NEUROSPEC_PROFILE`
+//! 3. 持久化的默认档案（托盘菜单 / 设置界面切换后写入）
+//! 4. `default`
+//!
+//! `default` 档案沿用原有路径，已有数据无需迁移
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::Result;
+
+/// 默认档案名
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// 指定档案的环境变量
+pub const PROFILE_ENV_VAR: &str = "NEUROSPEC_PROFILE";
+
+/// 命名档案在各根目录下的子目录
+const PROFILES_DIR: &str = "profiles";
+
+/// 持久化默认档案的文件（位于全局配置目录，不随档案变化）
+const ACTIVE_PROFILE_FILE: &str = "active_profile";
+
+/// 进程内生效的档案（首次解析后固定，切换档案需重启）
+static ACTIVE_PROFILE: OnceLock<String> = OnceLock::new();
+
+/// 校验档案名：仅允许字母、数字、`-` 与 `_`
+pub fn validate_profile_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > 64 {
+        return Err(anyhow::anyhow!("档案名长度需在 1-64 之间"));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(anyhow::anyhow!("档案名只能包含字母、数字、- 和 _: {}", name));
+    }
+    Ok(())
+}
+
+/// 使用命令行指定的档案（需在任何路径解析之前调用）
+pub fn set_profile_override(name: &str) -> Result<()> {
+    validate_profile_name(name)?;
+    ACTIVE_PROFILE
+        .set(name.to_string())
+        .map_err(|_| anyhow::anyhow!("档案已确定为 {}，无法再切换", active_profile()))
+}
+
+/// 当前生效的档案名
+pub fn active_profile() -> &'static str {
+    ACTIVE_PROFILE.get_or_init(resolve_profile)
+}
+
+/// 是否为默认档案
+pub fn is_default_profile() -> bool {
+    active_profile() == DEFAULT_PROFILE
+}
+
+fn resolve_profile() -> String {
+    let candidates = [
+        std::env::var(PROFILE_ENV_VAR).ok(),
+        read_persisted_profile(),
+    ];
+
+    for name in candidates.into_iter().flatten() {
+        let name = name.trim().to_string();
+        match validate_profile_name(&name) {
+            Ok(()) => return name,
+            Err(e) => log::warn!("忽略无效的配置档案: {}", e),
+        }
+    }
+
+    DEFAULT_PROFILE.to_string()
+}
+
+/// 全局配置根目录（不区分档案）
+fn global_config_root() -> Option<PathBuf> {
+    super::base_config_dir()
+}
+
+fn read_persisted_profile() -> Option<String> {
+    let path = global_config_root()?.join(ACTIVE_PROFILE_FILE);
+    fs::read_to_string(path).ok().filter(|s| !s.trim().is_empty())
+}
+
+/// 将根目录映射到当前档案：默认档案返回原路径，命名档案返回 `<root>/profiles/<名称>`
+pub fn scoped(root: PathBuf) -> PathBuf {
+    if is_default_profile() {
+        root
+    } else {
+        root.join(PROFILES_DIR).join(active_profile())
+    }
+}
+
+/// 当前档案的配置目录（config.json、index_state.json 所在目录）
+pub fn profile_config_dir() -> Option<PathBuf> {
+    global_config_root().map(scoped)
+}
+
+/// 当前档案的缓存目录（符号存储、搜索索引）
+pub fn profile_cache_dir() -> PathBuf {
+    scoped(super::base_cache_dir())
+}
+
+/// 当前档案的嵌入服务目录（embedding_config.json、embedding_cache）
+pub fn profile_embedding_dir() -> PathBuf {
+    scoped(super::base_embedding_dir())
+}
+
+/// 当前档案的本地搜索索引目录
+pub fn profile_local_index_dir() -> PathBuf {
+    scoped(super::base_index_dir()).join("local_index")
+}
+
+/// 当前档案下项目的记忆目录
+pub fn project_memory_dir(project_root: &Path) -> PathBuf {
+    scoped(super::base_memory_dir(project_root))
+}
+
+/// 列出所有档案（含 default）
+pub fn list_profiles() -> Vec<String> {
+    let mut profiles = vec![DEFAULT_PROFILE.to_string()];
+
+    if let Some(dir) = global_config_root().map(|d| d.join(PROFILES_DIR)) {
+        if let Ok(entries) = fs::read_dir(dir) {
+            let mut named: Vec<String> = entries
+                .filter_map(|e| e.ok())
+                .filter(|e| e.path().is_dir())
+                .filter_map(|e| e.file_name().to_str().map(|s| s.to_string()))
+                .filter(|name| validate_profile_name(name).is_ok() && name != DEFAULT_PROFILE)
+                .collect();
+            named.sort();
+            profiles.extend(named);
+        }
+    }
+
+    if !profiles.iter().any(|p| p == active_profile()) {
+        profiles.push(active_profile().to_string());
+    }
+    profiles
+}
+
+/// 创建档案（仅创建配置目录，其余目录按需创建）
+pub fn create_profile(name: &str) -> Result<()> {
+    validate_profile_name(name)?;
+    if name == DEFAULT_PROFILE {
+        return Ok(());
+    }
+
+    let dir = global_config_root()
+        .ok_or_else(|| anyhow::anyhow!("无法获取配置目录"))?
+        .join(PROFILES_DIR)
+        .join(name);
+    fs::create_dir_all(dir)?;
+    Ok(())
+}
+
+/// 设置下次启动时使用的默认档案
+pub fn set_default_profile(name: &str) -> Result<()> {
+    create_profile(name)?;
+
+    let root = global_config_root().ok_or_else(|| anyhow::anyhow!("无法获取配置目录"))?;
+    fs::create_dir_all(&root)?;
+    fs::write(root.join(ACTIVE_PROFILE_FILE), name)?;
+
+    log::info!("默认配置档案已设置为: {}", name);
+    Ok(())
+}
@@ -0,0 +1,207 @@
+//! 项目注册表
+//!
+//! 记录所有索引或查询过的项目（首次出现与最近使用时间）以及按项目的设置：
+//! 打开时是否自动索引、是否监听文件变化。保存在当前档案配置目录的
+//! `projects.json`，GUI 与 MCP 服务进程共用
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+/// 注册表文件名
+const PROJECTS_FILE: &str = "projects.json";
+
+/// 同一项目两次写入最近使用时间的最小间隔（查询频繁时避免反复写文件）
+const TOUCH_WRITE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 按项目的设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectSettings {
+    /// 首次搜索未索引的项目时自动在后台建立索引
+    #[serde(default = "default_true")]
+    pub auto_index: bool,
+    /// 索引完成后监听文件变化并增量更新
+    #[serde(default = "default_true")]
+    pub watch: bool,
+}
+
+impl Default for ProjectSettings {
+    fn default() -> Self {
+        Self { auto_index: true, watch: true }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// 注册表中的项目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectRecord {
+    pub path: String,
+    pub first_seen: String,
+    pub last_used: String,
+    #[serde(default)]
+    pub settings: ProjectSettings,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ProjectRegistry {
+    #[serde(default)]
+    projects: HashMap<String, ProjectRecord>,
+}
+
+lazy_static! {
+    /// 进程内的读改写锁（跨进程的并发写入以最后一次为准）
+    static ref REGISTRY_LOCK: Mutex<()> = Mutex::new(());
+
+    /// 项目 -> 上次写入最近使用时间的时刻
+    static ref LAST_TOUCH_WRITE: Mutex<HashMap<String, Instant>> = Mutex::new(HashMap::new());
+
+    /// 项目键 -> 设置（每次搜索都会读取，避免反复解析注册表；None 表示需要重新加载）
+    static ref SETTINGS_CACHE: RwLock<Option<HashMap<String, ProjectSettings>>> = RwLock::new(None);
+}
+
+fn registry_path() -> Option<PathBuf> {
+    super::profile_config_dir().map(|d| d.join(PROJECTS_FILE))
+}
+
+/// 与索引状态使用相同的项目键
+fn project_key(project_root: &Path) -> String {
+    crate::utils::path_key(project_root)
+}
+
+fn load_registry() -> ProjectRegistry {
+    registry_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_registry(registry: &ProjectRegistry) -> Result<()> {
+    let path = registry_path().ok_or_else(|| anyhow::anyhow!("无法获取配置目录"))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(registry)?)?;
+    Ok(())
+}
+
+fn update_registry<R>(update: impl FnOnce(&mut ProjectRegistry) -> R) -> Result<R> {
+    let _guard = REGISTRY_LOCK.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+    let mut registry = load_registry();
+    let result = update(&mut registry);
+    save_registry(&registry)?;
+    cache_settings(&registry);
+    Ok(result)
+}
+
+fn cache_settings(registry: &ProjectRegistry) {
+    let settings = registry
+        .projects
+        .iter()
+        .map(|(key, record)| (key.clone(), record.settings.clone()))
+        .collect();
+    if let Ok(mut cache) = SETTINGS_CACHE.write() {
+        *cache = Some(settings);
+    }
+}
+
+/// 丢弃缓存的项目设置（注册表文件被其他进程修改后由配置变更事件触发）
+pub fn invalidate_project_settings() {
+    if let Ok(mut cache) = SETTINGS_CACHE.write() {
+        *cache = None;
+    }
+}
+
+fn new_record(key: &str, now: &str) -> ProjectRecord {
+    ProjectRecord {
+        path: key.to_string(),
+        first_seen: now.to_string(),
+        last_used: now.to_string(),
+        settings: ProjectSettings::default(),
+    }
+}
+
+/// 记录项目被使用（索引或查询），未登记的项目自动加入注册表
+pub fn record_project_used(project_root: &Path) {
+    let key = project_key(project_root);
+
+    if let Ok(mut last) = LAST_TOUCH_WRITE.lock() {
+        if last.get(&key).is_some_and(|t| t.elapsed() < TOUCH_WRITE_INTERVAL) {
+            return;
+        }
+        last.insert(key.clone(), Instant::now());
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let result = update_registry(|registry| {
+        registry
+            .projects
+            .entry(key.clone())
+            .or_insert_with(|| new_record(&key, &now))
+            .last_used = now.clone();
+    });
+    if let Err(e) = result {
+        log::debug!("更新项目注册表失败: {}", e);
+    }
+}
+
+/// 注册表中的全部项目，按最近使用时间倒序
+pub fn list_project_records() -> Vec<ProjectRecord> {
+    let mut records: Vec<ProjectRecord> = load_registry().projects.into_values().collect();
+    records.sort_by(|a, b| b.last_used.cmp(&a.last_used));
+    records
+}
+
+/// 读取项目注册记录
+pub fn get_project_record(project_root: &Path) -> Option<ProjectRecord> {
+    load_registry().projects.remove(&project_key(project_root))
+}
+
+/// 读取项目设置（未登记的项目使用默认设置）
+pub fn project_settings(project_root: &Path) -> ProjectSettings {
+    let key = project_key(project_root);
+    if let Ok(cache) = SETTINGS_CACHE.read() {
+        if let Some(settings) = cache.as_ref() {
+            return settings.get(&key).cloned().unwrap_or_default();
+        }
+    }
+
+    let registry = load_registry();
+    cache_settings(&registry);
+    registry
+        .projects
+        .get(&key)
+        .map(|record| record.settings.clone())
+        .unwrap_or_default()
+}
+
+/// 修改项目设置，未登记的项目会一并加入注册表
+pub fn set_project_settings(project_root: &Path, settings: ProjectSettings) -> Result<ProjectRecord> {
+    let key = project_key(project_root);
+    let now = chrono::Utc::now().to_rfc3339();
+    update_registry(|registry| {
+        let record = registry
+            .projects
+            .entry(key.clone())
+            .or_insert_with(|| new_record(&key, &now));
+        record.settings = settings;
+        record.clone()
+    })
+}
+
+/// 从注册表移除项目（不删除索引与记忆数据），返回是否存在
+pub fn remove_project_record(project_root: &Path) -> Result<bool> {
+    let key = project_key(project_root);
+    if let Ok(mut last) = LAST_TOUCH_WRITE.lock() {
+        last.remove(&key);
+    }
+    update_registry(|registry| registry.projects.remove(&key).is_some())
+}
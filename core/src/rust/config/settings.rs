@@ -17,6 +17,16 @@ pub struct AppConfig {
     pub shortcut_config: ShortcutConfig, // 自定义快捷键配置
     #[serde(default = "default_daemon_config")]
     pub daemon_config: DaemonConfig, // Daemon 通讯配置
+    #[serde(default = "default_search_config")]
+    pub search_config: SearchConfig, // 本地搜索配置
+    #[serde(default = "default_context_orchestrator_config")]
+    pub orchestrator_config: ContextOrchestratorConfig, // 上下文编排配置
+    #[serde(default)]
+    pub telemetry_config: TelemetryConfig, // 匿名使用统计配置（默认关闭）
+    #[serde(default)]
+    pub updater_config: UpdaterConfig, // 自动更新配置（更新通道）
+    #[serde(default)]
+    pub tracing_config: TracingConfig, // 链路追踪导出配置（默认关闭）
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -104,6 +114,36 @@ pub struct McpConfig {
     pub acemcp_max_lines_per_blob: Option<u32>, // acemcp最大行数/块
     pub acemcp_text_extensions: Option<Vec<String>>, // acemcp文件扩展名
     pub acemcp_exclude_patterns: Option<Vec<String>>, // acemcp排除模式
+    #[serde(default)]
+    pub output_language: OutputLanguage, // 工具输出语言（弹窗结果、搜索、记忆）
+    #[serde(default)]
+    pub headless_policy: HeadlessPolicy, // 无 GUI/daemon 时交互工具的处理方式
+}
+
+/// 无 GUI/daemon 时交互工具的回退策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HeadlessPolicy {
+    /// 客户端支持 elicitation 时向客户端提问，否则自动使用默认答案（没有默认答案时返回错误）
+    #[default]
+    Auto,
+    /// 只通过 MCP elicitation 向客户端提问
+    Elicitation,
+    /// 自动使用默认答案（default_option / 表单默认值）
+    DefaultAnswer,
+    /// 不回退，直接返回 daemon 连接错误
+    Disabled,
+}
+
+/// MCP 工具输出语言
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputLanguage {
+    /// 简体中文
+    Zh,
+    /// English（默认，与未提供该设置前的工具输出保持一致）
+    #[default]
+    En,
 }
 
 // 自定义prompt结构
@@ -186,6 +226,110 @@ pub struct DaemonConfig {
     pub http_client_timeout_secs: u64,
 }
 
+// 本地搜索配置
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchConfig {
+    /// 额外排除的路径模式（gitignore 语法，如 `vendor/**`、`*.min.js`）
+    #[serde(default)]
+    pub exclude_patterns: Vec<String>,
+
+    /// 单次搜索最大结果数
+    #[serde(default = "default_search_max_results")]
+    pub max_results: usize,
+
+    /// 单个文件最大索引大小（KB，0 表示不限制），超出的文件不进入索引
+    #[serde(default = "default_search_max_file_size_kb")]
+    pub max_file_size_kb: u64,
+
+    /// 跳过二进制文件
+    #[serde(default = "default_search_skip_binary_files")]
+    pub skip_binary_files: bool,
+
+    /// 跳过压缩 / 打包生成的文件（`*.min.js`、单行 bundle 等）
+    #[serde(default = "default_search_skip_minified_files")]
+    pub skip_minified_files: bool,
+
+    /// 符号存储最多常驻内存的项目数，超出时按 LRU 淘汰
+    #[serde(default = "default_search_max_resident_projects")]
+    pub max_resident_projects: usize,
+
+    /// 符号存储最多常驻内存的符号总数，超出时按 LRU 淘汰
+    #[serde(default = "default_search_max_resident_symbols")]
+    pub max_resident_symbols: usize,
+}
+
+// 上下文编排配置
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ContextOrchestratorConfig {
+    /// 是否启用自动上下文注入
+    #[serde(default = "default_orchestrator_enabled")]
+    pub enabled: bool,
+
+    /// 最大记忆数量
+    #[serde(default = "default_orchestrator_max_memories")]
+    pub max_memories: usize,
+
+    /// 最大代码片段数量
+    #[serde(default = "default_orchestrator_max_code_snippets")]
+    pub max_code_snippets: usize,
+
+    /// 是否显示上下文来源
+    #[serde(default)]
+    pub show_source: bool,
+}
+
+// 匿名使用统计配置
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TelemetryConfig {
+    /// 是否开启（默认关闭，需用户主动开启）
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// 匿名安装 ID（开启时随机生成，关闭时清除）
+    #[serde(default)]
+    pub install_id: Option<String>,
+
+    /// 自定义上报地址（为空时使用构建时指定的地址）
+    #[serde(default)]
+    pub endpoint: Option<String>,
+}
+
+// 更新通道
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateChannel {
+    /// 正式版
+    #[default]
+    Stable,
+    /// 测试版（包含预发布版本）
+    Beta,
+}
+
+// 自动更新配置
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct UpdaterConfig {
+    /// 更新通道（默认正式版）
+    #[serde(default)]
+    pub channel: UpdateChannel,
+
+    /// 分阶段发布分桶用的随机 ID（首次检查更新时生成，只在本机使用，不上报）
+    #[serde(default)]
+    pub rollout_id: Option<String>,
+}
+
+// 链路追踪导出配置
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TracingConfig {
+    /// 是否通过 OTLP 导出链路追踪（默认关闭，修改后重启生效）
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// OTLP/HTTP 收集器地址，如 `http://localhost:4318`
+    /// （为空时使用 `OTEL_EXPORTER_OTLP_ENDPOINT` 环境变量或 `http://localhost:4318`）
+    #[serde(default)]
+    pub otlp_endpoint: Option<String>,
+}
+
 #[derive(Debug)]
 pub struct AppState {
     pub config: Mutex<AppConfig>,
@@ -204,6 +348,11 @@ impl Default for AppConfig {
             custom_prompt_config: default_custom_prompt_config(),
             shortcut_config: default_shortcut_config(),
             daemon_config: default_daemon_config(),
+            search_config: default_search_config(),
+            orchestrator_config: default_context_orchestrator_config(),
+            telemetry_config: TelemetryConfig::default(),
+            updater_config: UpdaterConfig::default(),
+            tracing_config: TracingConfig::default(),
         }
     }
 }
@@ -238,6 +387,8 @@ pub fn default_mcp_config() -> McpConfig {
         acemcp_max_lines_per_blob: None,
         acemcp_text_extensions: None,
         acemcp_exclude_patterns: None,
+        output_language: OutputLanguage::default(),
+        headless_policy: HeadlessPolicy::default(),
     }
 }
 
@@ -319,7 +470,19 @@ pub fn default_mcp_tools() -> HashMap<String, bool> {
     tools.insert(mcp::TOOL_SEARCH.to_string(), true);    // Search tool default enabled
     // NeuroSpec 高级工具（重构辅助）
     tools.insert(mcp::TOOL_NEUROSPEC_IMPACT_ANALYSIS.to_string(), true);
+    tools.insert(mcp::TOOL_NEUROSPEC_IMPACT_CHANGESET.to_string(), true);
+    tools.insert(mcp::TOOL_NEUROSPEC_GRAPH_CALLERS.to_string(), true);
+    tools.insert(mcp::TOOL_NEUROSPEC_GRAPH_CYCLES.to_string(), true);
+    tools.insert(mcp::TOOL_NEUROSPEC_GRAPH_EXPORT.to_string(), true);
+    tools.insert(mcp::TOOL_NEUROSPEC_ARCHITECTURE_CHECK.to_string(), true);
     tools.insert(mcp::TOOL_NEUROSPEC_RENAME.to_string(), true);
+    tools.insert(mcp::TOOL_NEUROSPEC_PLAN_VALIDATE.to_string(), true);
+    tools.insert(mcp::TOOL_NEUROSPEC_PLAN_ASSESS_RISK.to_string(), true);
+    tools.insert(mcp::TOOL_NEUROSPEC_PLAN_DRY_RUN.to_string(), true);
+    tools.insert(mcp::TOOL_NEUROSPEC_PLAN_EXECUTE.to_string(), true);
+    tools.insert(mcp::TOOL_NEUROSPEC_PLAN_RESUME.to_string(), true);
+    tools.insert(mcp::TOOL_NEUROSPEC_PLANS.to_string(), true);
+    tools.insert(mcp::TOOL_NEUROSPEC_PLAN_SCHEMA.to_string(), true);
     tools
 }
 
@@ -657,3 +820,64 @@ pub fn default_heartbeat_interval_secs() -> u64 {
 pub fn default_http_client_timeout_secs() -> u64 {
     crate::constants::mcp::DEFAULT_HTTP_CLIENT_TIMEOUT_SECS
 }
+
+// ==================== 搜索配置默认值函数 ====================
+
+pub fn default_search_config() -> SearchConfig {
+    SearchConfig {
+        exclude_patterns: Vec::new(),
+        max_results: default_search_max_results(),
+        max_file_size_kb: default_search_max_file_size_kb(),
+        skip_binary_files: default_search_skip_binary_files(),
+        skip_minified_files: default_search_skip_minified_files(),
+        max_resident_projects: default_search_max_resident_projects(),
+        max_resident_symbols: default_search_max_resident_symbols(),
+    }
+}
+
+pub fn default_search_max_results() -> usize {
+    10
+}
+
+pub fn default_search_max_file_size_kb() -> u64 {
+    1024
+}
+
+pub fn default_search_skip_binary_files() -> bool {
+    true
+}
+
+pub fn default_search_skip_minified_files() -> bool {
+    true
+}
+
+pub fn default_search_max_resident_projects() -> usize {
+    8
+}
+
+pub fn default_search_max_resident_symbols() -> usize {
+    500_000
+}
+
+// ==================== 上下文编排配置默认值函数 ====================
+
+pub fn default_context_orchestrator_config() -> ContextOrchestratorConfig {
+    ContextOrchestratorConfig {
+        enabled: default_orchestrator_enabled(),
+        max_memories: default_orchestrator_max_memories(),
+        max_code_snippets: default_orchestrator_max_code_snippets(),
+        show_source: false,
+    }
+}
+
+pub fn default_orchestrator_enabled() -> bool {
+    true
+}
+
+pub fn default_orchestrator_max_memories() -> usize {
+    5
+}
+
+pub fn default_orchestrator_max_code_snippets() -> usize {
+    3
+}
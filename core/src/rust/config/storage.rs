@@ -41,7 +41,7 @@ pub async fn save_config(state: &State<'_, AppState>, app: &AppHandle) -> Result
 pub async fn load_config(state: &State<'_, AppState>, app: &AppHandle) -> Result<()> {
     let config_path = get_config_path(app)?;
 
-    if config_path.exists() {
+    let config = if config_path.exists() {
         let config_json = fs::read_to_string(&config_path)?;
         let mut config: AppConfig = serde_json::from_str(&config_json)?;
 
@@ -52,8 +52,18 @@ pub async fn load_config(state: &State<'_, AppState>, app: &AppHandle) -> Result
             .config
             .lock()
             .map_err(|e| anyhow::anyhow!("获取配置锁失败: {}", e))?;
-        *config_guard = config;
-    }
+        *config_guard = config.clone();
+        config
+    } else {
+        state
+            .config
+            .lock()
+            .map_err(|e| anyhow::anyhow!("获取配置锁失败: {}", e))?
+            .clone()
+    };
+
+    // 通知各子系统按变化的配置段重新初始化
+    super::publish_config(&config);
 
     Ok(())
 }
@@ -139,6 +149,23 @@ pub fn load_standalone_config() -> Result<AppConfig> {
 
 /// 获取独立配置文件路径（不依赖Tauri）
 fn get_standalone_config_path() -> Result<PathBuf> {
+    // 便携模式不读取用户目录，无需迁移旧配置
+    if !super::is_portable_mode() {
+        migrate_legacy_config_dir()?;
+    }
+
+    // 命名档案、便携模式使用各自的配置目录
+    let config_dir = super::profile_config_dir()
+        .ok_or_else(|| anyhow::anyhow!("无法获取配置目录"))?;
+
+    // 确保目录存在
+    fs::create_dir_all(&config_dir)?;
+
+    Ok(config_dir.join("config.json"))
+}
+
+/// 将旧版 cunzhi 配置目录迁移到 neurospec
+fn migrate_legacy_config_dir() -> Result<()> {
     // 使用标准的配置目录
     let base_config_dir = dirs::config_dir()
         .ok_or_else(|| anyhow::anyhow!("无法获取配置目录"))?;
@@ -162,10 +189,7 @@ fn get_standalone_config_path() -> Result<PathBuf> {
         }
     }
 
-    // 确保目录存在
-    fs::create_dir_all(&new_config_dir)?;
-
-    Ok(new_config_dir.join("config.json"))
+    Ok(())
 }
 
 /// 递归复制目录
@@ -7,6 +7,13 @@ use tauri::{AppHandle, Emitter, Manager};
 use crate::{log_important, log_debug};
 use super::storage::{get_config_path, load_config};
 use super::settings::AppState;
+use super::events::{emit_config_changed, ConfigSection};
+
+/// 嵌入服务配置文件名（位于当前档案的嵌入目录）
+const EMBEDDING_CONFIG_FILE: &str = "embedding_config.json";
+
+/// 项目注册表文件名（与 config.json 位于同一目录）
+const PROJECTS_FILE: &str = "projects.json";
 
 /// 启动配置文件监听器
 pub fn start_config_watcher(app_handle: AppHandle) -> Result<()> {
@@ -33,6 +40,13 @@ pub fn start_config_watcher(app_handle: AppHandle) -> Result<()> {
         watcher.watch(parent, RecursiveMode::NonRecursive)?;
         log_debug!("Watching directory: {:?}", parent);
     }
+
+    // 监听嵌入服务配置所在目录
+    let embedding_dir = super::profile_embedding_dir();
+    if std::fs::create_dir_all(&embedding_dir).is_ok() {
+        watcher.watch(&embedding_dir, RecursiveMode::NonRecursive)?;
+        log_debug!("Watching directory: {:?}", embedding_dir);
+    }
     
     // 在后台线程处理文件变化事件
     std::thread::spawn(move || {
@@ -40,6 +54,43 @@ pub fn start_config_watcher(app_handle: AppHandle) -> Result<()> {
         let _watcher = watcher;
         
         while let Ok(event) = rx.recv() {
+            if crate::utils::is_shutting_down() {
+                break;
+            }
+
+            // 嵌入服务配置：创建、修改或删除都需要重建服务
+            if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_))
+                && event.paths.iter().any(|p| p.ends_with(EMBEDDING_CONFIG_FILE))
+            {
+                std::thread::sleep(Duration::from_millis(100));
+                let config = app_handle
+                    .state::<AppState>()
+                    .config
+                    .lock()
+                    .map(|c| c.clone())
+                    .ok();
+                if let Some(config) = config {
+                    emit_config_changed(vec![ConfigSection::Embedding], config);
+                }
+                continue;
+            }
+
+            // 项目注册表：MCP 服务进程也会写入，需要刷新缓存的项目设置
+            if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_))
+                && event.paths.iter().any(|p| p.ends_with(PROJECTS_FILE))
+            {
+                let config = app_handle
+                    .state::<AppState>()
+                    .config
+                    .lock()
+                    .map(|c| c.clone())
+                    .ok();
+                if let Some(config) = config {
+                    emit_config_changed(vec![ConfigSection::Projects], config);
+                }
+                continue;
+            }
+
             // 只处理修改事件
             if let EventKind::Modify(_) = event.kind {
                 // 检查是否是配置文件
@@ -21,6 +21,12 @@ pub const HOMEPAGE: &str = "https://github.com/neurospec/neurospec";
 /// 应用程序仓库
 pub const REPOSITORY: &str = "https://github.com/neurospec/neurospec";
 
+/// 匿名使用统计的上报地址（发布构建时通过环境变量注入，未注入时只在本地排队）
+pub const TELEMETRY_ENDPOINT: Option<&str> = option_env!("NEUROSPEC_TELEMETRY_ENDPOINT");
+
+/// 更新清单签名公钥（Base64 编码的 Ed25519 公钥，发布构建时注入；未注入时不使用增量更新）
+pub const UPDATE_PUBLIC_KEY: Option<&str> = option_env!("NEUROSPEC_UPDATE_PUBLIC_KEY");
+
 /// 应用程序许可证
 pub const LICENSE: &str = "MIT";
 
@@ -11,7 +11,19 @@ pub const TOOL_SEARCH: &str = "search";
 
 /// NeuroSpec 高级工具标识符（重构辅助）
 pub const TOOL_NEUROSPEC_IMPACT_ANALYSIS: &str = "neurospec_graph_impact_analysis";
+pub const TOOL_NEUROSPEC_IMPACT_CHANGESET: &str = "neurospec_impact_changeset";
+pub const TOOL_NEUROSPEC_GRAPH_CALLERS: &str = "neurospec_graph_callers";
+pub const TOOL_NEUROSPEC_GRAPH_CYCLES: &str = "neurospec_graph_cycles";
+pub const TOOL_NEUROSPEC_GRAPH_EXPORT: &str = "neurospec_graph_export";
+pub const TOOL_NEUROSPEC_ARCHITECTURE_CHECK: &str = "neurospec_architecture_check";
 pub const TOOL_NEUROSPEC_RENAME: &str = "neurospec_refactor_rename";
+pub const TOOL_NEUROSPEC_PLAN_VALIDATE: &str = "neurospec_plan_validate";
+pub const TOOL_NEUROSPEC_PLAN_ASSESS_RISK: &str = "neurospec_plan_assess_risk";
+pub const TOOL_NEUROSPEC_PLAN_DRY_RUN: &str = "neurospec_plan_dry_run";
+pub const TOOL_NEUROSPEC_PLAN_EXECUTE: &str = "neurospec_plan_execute";
+pub const TOOL_NEUROSPEC_PLAN_RESUME: &str = "neurospec_plan_resume";
+pub const TOOL_NEUROSPEC_PLANS: &str = "neurospec_plans";
+pub const TOOL_NEUROSPEC_PLAN_SCHEMA: &str = "neurospec_plan_schema";
 
 /// Default enabled tools list
 pub const DEFAULT_ENABLED_TOOLS: &[&str] = &[
@@ -19,7 +31,19 @@ pub const DEFAULT_ENABLED_TOOLS: &[&str] = &[
     TOOL_MEMORY,
     TOOL_SEARCH,
     TOOL_NEUROSPEC_IMPACT_ANALYSIS,
+    TOOL_NEUROSPEC_IMPACT_CHANGESET,
+    TOOL_NEUROSPEC_GRAPH_CALLERS,
+    TOOL_NEUROSPEC_GRAPH_CYCLES,
+    TOOL_NEUROSPEC_GRAPH_EXPORT,
+    TOOL_NEUROSPEC_ARCHITECTURE_CHECK,
     TOOL_NEUROSPEC_RENAME,
+    TOOL_NEUROSPEC_PLAN_VALIDATE,
+    TOOL_NEUROSPEC_PLAN_ASSESS_RISK,
+    TOOL_NEUROSPEC_PLAN_DRY_RUN,
+    TOOL_NEUROSPEC_PLAN_EXECUTE,
+    TOOL_NEUROSPEC_PLAN_RESUME,
+    TOOL_NEUROSPEC_PLANS,
+    TOOL_NEUROSPEC_PLAN_SCHEMA,
 ];
 
 /// 继续回复默认启用状态
@@ -46,6 +70,36 @@ pub const MIN_POPUP_TIMEOUT_SECS: u64 = 60;
 /// 最大弹窗超时（秒）- 1小时
 pub const MAX_POPUP_TIMEOUT_SECS: u64 = 3600;
 
+/// 单次交互请求可指定的最小超时（秒）
+pub const MIN_REQUEST_POPUP_TIMEOUT_SECS: u64 = 10;
+
+/// 超时后返回默认选项时响应中的来源标记
+pub const POPUP_TIMEOUT_DEFAULT_SOURCE: &str = "timeout_default";
+
+/// 超时使用默认选项时附加在响应文本开头的提示
+pub const POPUP_TIMEOUT_NOTICE: &str = "⏱️ 用户未在规定时间内响应，已使用默认选项";
+
+/// 超时提示（英文输出）
+pub const POPUP_TIMEOUT_NOTICE_EN: &str = "⏱️ The user did not respond in time; the default option was used";
+
+/// 无 GUI 时自动使用默认答案的来源标记
+pub const HEADLESS_DEFAULT_SOURCE: &str = "headless_default";
+
+/// 通过 MCP elicitation 由客户端回答的来源标记
+pub const ELICITATION_SOURCE: &str = "elicitation";
+
+/// 无 GUI 自动作答时附加在响应文本开头的提示
+pub const HEADLESS_DEFAULT_NOTICE: &str = "🤖 当前无可用的交互界面（headless 模式），已自动使用默认答案";
+
+/// 无 GUI 自动作答提示（英文输出）
+pub const HEADLESS_DEFAULT_NOTICE_EN: &str = "🤖 No interactive UI is available (headless mode); the default answer was used";
+
+/// 弹窗超时错误信息前缀（跨 daemon 传递后用于识别超时）
+pub const POPUP_TIMEOUT_ERROR: &str = "Popup response timeout";
+
+/// 客户端等待 daemon 响应时在弹窗超时之外预留的时间（秒）
+pub const POPUP_CLIENT_TIMEOUT_MARGIN_SECS: u64 = 30;
+
 /// 默认心跳间隔（秒）
 pub const DEFAULT_HEARTBEAT_INTERVAL_SECS: u64 = 30;
 
@@ -93,7 +147,19 @@ impl Default for McpConfig {
                 McpToolConfig::new(TOOL_SEARCH, true, true),    // Search tool enabled by default
                 // NeuroSpec 高级工具（重构辅助）
                 McpToolConfig::new(TOOL_NEUROSPEC_IMPACT_ANALYSIS, true, true),
+                McpToolConfig::new(TOOL_NEUROSPEC_IMPACT_CHANGESET, true, true),
+                McpToolConfig::new(TOOL_NEUROSPEC_GRAPH_CALLERS, true, true),
+                McpToolConfig::new(TOOL_NEUROSPEC_GRAPH_CYCLES, true, true),
+                McpToolConfig::new(TOOL_NEUROSPEC_GRAPH_EXPORT, true, true),
+                McpToolConfig::new(TOOL_NEUROSPEC_ARCHITECTURE_CHECK, true, true),
                 McpToolConfig::new(TOOL_NEUROSPEC_RENAME, true, true),
+                McpToolConfig::new(TOOL_NEUROSPEC_PLAN_VALIDATE, true, true),
+                McpToolConfig::new(TOOL_NEUROSPEC_PLAN_ASSESS_RISK, true, true),
+                McpToolConfig::new(TOOL_NEUROSPEC_PLAN_DRY_RUN, true, true),
+                McpToolConfig::new(TOOL_NEUROSPEC_PLAN_EXECUTE, true, true),
+                McpToolConfig::new(TOOL_NEUROSPEC_PLAN_RESUME, true, true),
+                McpToolConfig::new(TOOL_NEUROSPEC_PLANS, true, true),
+                McpToolConfig::new(TOOL_NEUROSPEC_PLAN_SCHEMA, true, true),
             ],
             continue_reply_enabled: DEFAULT_CONTINUE_REPLY_ENABLED,
             auto_continue_threshold: DEFAULT_AUTO_CONTINUE_THRESHOLD,
@@ -159,6 +225,18 @@ pub fn is_valid_tool_id(tool_id: &str) -> bool {
         tool_id,
         TOOL_INTERACT | TOOL_MEMORY | TOOL_SEARCH |
         TOOL_NEUROSPEC_IMPACT_ANALYSIS |
-        TOOL_NEUROSPEC_RENAME
+        TOOL_NEUROSPEC_IMPACT_CHANGESET |
+        TOOL_NEUROSPEC_GRAPH_CALLERS |
+        TOOL_NEUROSPEC_GRAPH_CYCLES |
+        TOOL_NEUROSPEC_GRAPH_EXPORT |
+        TOOL_NEUROSPEC_ARCHITECTURE_CHECK |
+        TOOL_NEUROSPEC_RENAME |
+        TOOL_NEUROSPEC_PLAN_VALIDATE |
+        TOOL_NEUROSPEC_PLAN_ASSESS_RISK |
+        TOOL_NEUROSPEC_PLAN_DRY_RUN |
+        TOOL_NEUROSPEC_PLAN_EXECUTE |
+        TOOL_NEUROSPEC_PLAN_RESUME |
+        TOOL_NEUROSPEC_PLANS |
+        TOOL_NEUROSPEC_PLAN_SCHEMA
     )
 }
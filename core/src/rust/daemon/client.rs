@@ -18,6 +18,7 @@ fn get_http_client_timeout_secs() -> u64 {
 pub struct DaemonClient {
     client: Client,
     base_url: String,
+    timeout_secs: u64,
 }
 
 impl DaemonClient {
@@ -34,7 +35,7 @@ impl DaemonClient {
             .build()
             .expect("Failed to create HTTP client");
         
-        Self { client, base_url }
+        Self { client, base_url, timeout_secs }
     }
     
     /// Execute a tool via the daemon server
@@ -43,8 +44,13 @@ impl DaemonClient {
         
         log_debug!("Sending request to daemon: {:?}", request);
         
-        let response = self.client
-            .post(&url)
+        let timeout = Duration::from_secs(request.client_timeout_secs(self.timeout_secs));
+        let mut builder = self.client.post(&url).timeout(timeout);
+        // 链路上下文随请求头传给 daemon（未启用链路追踪时为空）
+        for (name, value) in crate::app::otel::current_context() {
+            builder = builder.header(name, value);
+        }
+        let response = builder
             .json(&request)
             .send()
             .await
@@ -67,16 +73,33 @@ impl DaemonClient {
         let daemon_response: DaemonResponse = response.json().await?;
         
         if !daemon_response.success {
-            if let Some(error) = daemon_response.error {
-                anyhow::bail!("Tool execution failed: {}", error);
-            } else {
-                anyhow::bail!("Tool execution failed with unknown error");
-            }
+            return Err(daemon_response.into_error("Tool execution failed with unknown error"));
         }
         
         Ok(daemon_response)
     }
     
+    /// Record a change memory for a commit (used by the post-commit hook)
+    pub async fn record_commit(&self, project_root: &str, commit: &str) -> Result<serde_json::Value> {
+        let url = format!("{}/memory/record-commit", self.base_url);
+
+        let response = self
+            .client
+            .post(&url)
+            .json(&serde_json::json!({ "project_root": project_root, "commit": commit }))
+            .send()
+            .await?;
+
+        let status = response.status();
+        let body: serde_json::Value = response.json().await?;
+        if !status.is_success() {
+            let error = body.get("error").and_then(|e| e.as_str()).unwrap_or("unknown error");
+            return Err(anyhow::anyhow!("Daemon failed to record commit: {}", error));
+        }
+
+        Ok(body)
+    }
+
     /// Check if daemon is healthy
     pub async fn health_check(&self) -> Result<bool> {
         let url = format!("{}/health", self.base_url);
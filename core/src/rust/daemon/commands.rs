@@ -9,16 +9,36 @@ pub struct ContextOrchestratorConfigArgs {
     pub show_source: Option<bool>,
 }
 
-/// 设置上下文编排器配置
+/// 设置上下文编排器配置（立即生效并写入配置文件）
 #[command]
-pub async fn set_context_orchestrator_config(args: ContextOrchestratorConfigArgs) -> Result<(), String> {
+pub async fn set_context_orchestrator_config(
+    args: ContextOrchestratorConfigArgs,
+    state: tauri::State<'_, crate::config::AppState>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
     let config = OrchestratorConfig {
         enabled: args.enabled,
         max_memories: args.max_memories.unwrap_or(5),
         max_code_snippets: args.max_code_snippets.unwrap_or(3),
         show_source: args.show_source.unwrap_or(false),
     };
-    
+
+    {
+        let mut app_config = state
+            .config
+            .lock()
+            .map_err(|e| format!("获取配置失败: {}", e))?;
+        app_config.orchestrator_config = crate::config::ContextOrchestratorConfig {
+            enabled: config.enabled,
+            max_memories: config.max_memories,
+            max_code_snippets: config.max_code_snippets,
+            show_source: config.show_source,
+        };
+    }
+
     set_orchestrator_config(config);
-    Ok(())
+
+    crate::config::save_config(&state, &app)
+        .await
+        .map_err(|e| format!("保存配置失败: {}", e))
 }
@@ -34,6 +34,17 @@ impl Default for OrchestratorConfig {
     }
 }
 
+impl From<&crate::config::ContextOrchestratorConfig> for OrchestratorConfig {
+    fn from(config: &crate::config::ContextOrchestratorConfig) -> Self {
+        Self {
+            enabled: config.enabled,
+            max_memories: config.max_memories,
+            max_code_snippets: config.max_code_snippets,
+            show_source: config.show_source,
+        }
+    }
+}
+
 /// 增强后的上下文
 #[derive(Debug, Clone)]
 pub struct EnhancedContext {
@@ -112,7 +123,7 @@ impl ContextOrchestrator {
     
     /// 从配置文件加载已保存的项目路径
     fn load_saved_project_path() -> Option<String> {
-        let config_path = dirs::data_dir()?.join("neurospec").join("project_config.json");
+        let config_path = crate::config::base_data_dir()?.join("project_config.json");
         
         if !config_path.exists() {
             return None;
@@ -307,16 +318,27 @@ impl ContextOrchestrator {
 
 // 全局编排器实例
 lazy_static::lazy_static! {
-    static ref GLOBAL_ORCHESTRATOR: std::sync::Mutex<ContextOrchestrator> = 
-        std::sync::Mutex::new(ContextOrchestrator::with_defaults());
+    static ref GLOBAL_ORCHESTRATOR: std::sync::Mutex<ContextOrchestrator> = {
+        // 优先使用配置文件中的编排设置
+        let orchestrator = match crate::config::load_standalone_config() {
+            Ok(config) => ContextOrchestrator::new((&config.orchestrator_config).into()),
+            Err(_) => ContextOrchestrator::with_defaults(),
+        };
+        std::sync::Mutex::new(orchestrator)
+    };
 }
 
 /// 增强消息（全局函数）
-pub fn enhance_message_with_context(message: &str) -> String {
-    match GLOBAL_ORCHESTRATOR.lock() {
-        Ok(orchestrator) => orchestrator.enhance_message(message),
-        Err(_) => message.to_string(),
-    }
+///
+/// 记忆读取是同步 IO，在阻塞线程池中执行，避免占用 daemon 的 tokio 工作线程
+pub async fn enhance_message_with_context(message: &str) -> String {
+    let owned = message.to_string();
+    tokio::task::spawn_blocking(move || match GLOBAL_ORCHESTRATOR.lock() {
+        Ok(orchestrator) => orchestrator.enhance_message(&owned),
+        Err(_) => owned,
+    })
+    .await
+    .unwrap_or_else(|_| message.to_string())
 }
 
 /// 设置编排器配置
@@ -0,0 +1,50 @@
+//! 非阻塞通知
+//!
+//! 窗口处于前台时在应用内显示 toast，否则发送系统通知；
+//! 调用方不等待用户响应
+
+use anyhow::Result;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::log_debug;
+use crate::mcp::types::{NotifyLevel, NotifyRequest};
+
+/// 显示通知，返回实际使用的方式（"toast" 或 "system"）
+pub fn show_notification(app_handle: &AppHandle, request: &NotifyRequest) -> Result<&'static str> {
+    let window = app_handle.get_webview_window("main");
+    let window_active = window.as_ref().is_some_and(|w| {
+        w.is_visible().unwrap_or(false) && w.is_focused().unwrap_or(false)
+    });
+
+    match (request.system.unwrap_or(!window_active), window) {
+        (false, Some(window)) => {
+            window.emit("mcp-notification", request)?;
+            log_debug!("Notification shown as toast");
+            Ok("toast")
+        }
+        _ => {
+            let title = request
+                .title
+                .clone()
+                .unwrap_or_else(|| default_title(request.level));
+            let mut builder = app_handle.notification().builder().title(title).body(&request.message);
+            if request.sound {
+                builder = builder.sound("default");
+            }
+            builder.show()?;
+            log_debug!("Notification shown as system notification");
+            Ok("system")
+        }
+    }
+}
+
+fn default_title(level: NotifyLevel) -> String {
+    let name = crate::constants::app::NAME;
+    match level {
+        NotifyLevel::Info => name.to_string(),
+        NotifyLevel::Success => format!("{} ✅", name),
+        NotifyLevel::Warning => format!("{} ⚠️", name),
+        NotifyLevel::Error => format!("{} ❌", name),
+    }
+}
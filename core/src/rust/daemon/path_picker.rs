@@ -0,0 +1,94 @@
+//! 原生文件/目录选择器
+//!
+//! 交互请求带 `picker` 时由 daemon 打开系统选择器代替弹窗，
+//! 起始目录为项目根目录，项目之外的路径不会返回给调用方
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Result;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::{DialogExt, FilePath};
+
+use crate::log_important;
+use crate::mcp::types::{PathPickerRequest, PickedPaths, PickerMode};
+use crate::mcp::utils::popup_timeout_error;
+
+/// 打开选择器并等待用户选择，超过 `timeout_secs` 未选择时返回超时错误
+pub async fn pick_paths(
+    app_handle: &AppHandle,
+    title: &str,
+    request: &PathPickerRequest,
+    timeout_secs: u64,
+) -> Result<PickedPaths> {
+    let root = request
+        .project_root
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("Project root is required for the path picker"))?;
+    let root = normalize_path(Path::new(root))
+        .map_err(|e| anyhow::anyhow!("Project root does not exist: {} ({})", root, e))?;
+
+    let mut dialog = app_handle.dialog().file().set_title(title).set_directory(&root);
+    if let Some(window) = app_handle.get_webview_window("main") {
+        dialog = dialog.set_parent(&window);
+    }
+    if request.mode != PickerMode::Directory && !request.extensions.is_empty() {
+        let extensions: Vec<&str> = request.extensions.iter().map(|e| e.trim_start_matches('.')).collect();
+        dialog = dialog.add_filter(extensions.join(", "), &extensions);
+    }
+
+    log_important!(info, "[Picker] Opening {:?} picker in {}", request.mode, root.display());
+
+    // 系统对话框会阻塞调用线程；超时后对话框无法关闭，之后的选择会被丢弃
+    let mode = request.mode;
+    let task = tokio::task::spawn_blocking(move || -> Vec<FilePath> {
+        match mode {
+            PickerMode::File => dialog.blocking_pick_file().into_iter().collect(),
+            PickerMode::Files => dialog.blocking_pick_files().unwrap_or_default(),
+            PickerMode::Directory => dialog.blocking_pick_folder().into_iter().collect(),
+        }
+    });
+    let picked = match tokio::time::timeout(Duration::from_secs(timeout_secs), task).await {
+        Ok(joined) => joined.map_err(|e| anyhow::anyhow!("Picker task failed: {}", e))?,
+        Err(_) => {
+            log_important!(warn, "[Picker] No selection within {}s", timeout_secs);
+            return Err(popup_timeout_error(timeout_secs).into());
+        }
+    };
+
+    let mut result = PickedPaths {
+        project_root: root.to_string_lossy().replace('\\', "/"),
+        paths: Vec::new(),
+        rejected: Vec::new(),
+        cancelled: picked.is_empty(),
+    };
+
+    for path in picked.into_iter().filter_map(|p| p.into_path().ok()) {
+        match normalize_path(&path).ok().and_then(|p| relative_to(&p, &root)) {
+            Some(relative) => result.paths.push(relative),
+            None => result.rejected.push(path.to_string_lossy().replace('\\', "/")),
+        }
+    }
+
+    if !result.rejected.is_empty() {
+        log_important!(warn, "[Picker] Rejected {} path(s) outside the project root", result.rejected.len());
+    }
+    Ok(result)
+}
+
+/// 规范化路径（Windows 下去掉 `\\?\` 前缀，便于与项目根目录比较和展示）
+fn normalize_path(path: &Path) -> std::io::Result<PathBuf> {
+    let canonical = path.canonicalize()?;
+    let text = canonical.to_string_lossy();
+    Ok(match text.strip_prefix(r"\\?\") {
+        Some(stripped) => PathBuf::from(stripped),
+        None => canonical,
+    })
+}
+
+/// 相对项目根目录的路径，不在项目内时返回 None
+fn relative_to(path: &Path, root: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let text = relative.to_string_lossy().replace('\\', "/");
+    Some(if text.is_empty() { ".".to_string() } else { text })
+}
@@ -1,13 +1,14 @@
 use anyhow::Result;
 use tauri::{AppHandle, Manager, Emitter};
 use tokio::sync::{oneshot, broadcast, Mutex};
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 use std::collections::hash_map::DefaultHasher;
 use std::time::Instant;
 
-use crate::mcp::types::PopupRequest;
+use crate::config::DaemonConfig;
+use crate::mcp::types::{build_send_response, InputConstraints, McpResponse, PopupForm, PopupPreview, PopupRequest};
 use crate::{log_important, log_debug};
 use super::context_orchestrator::enhance_message_with_context;
 
@@ -23,25 +24,60 @@ const BROADCAST_CAPACITY: usize = 16;
 // 已完成响应的缓存保留时间（秒）
 const RESPONSE_CACHE_TTL_SECS: u64 = 30;
 
+/// 配置变更后生效的 daemon 配置（未收到变更时读取配置文件）
+static DAEMON_CONFIG: RwLock<Option<DaemonConfig>> = RwLock::new(None);
+
+/// 更新 daemon 配置，之后的弹窗请求按新配置处理
+pub fn set_daemon_config(config: DaemonConfig) {
+    if let Ok(mut current) = DAEMON_CONFIG.write() {
+        *current = Some(config);
+    }
+}
+
 /// 获取弹窗超时时间（秒）
-/// 优先从配置文件读取，失败时使用默认值
+/// 优先使用最近一次配置变更的值，其次读取配置文件，失败时使用默认值
 fn get_popup_timeout_secs() -> u64 {
-    match crate::config::load_standalone_config() {
-        Ok(config) => {
-            let timeout = config.daemon_config.popup_timeout_secs;
-            // 确保在合理范围内
-            timeout.clamp(
-                crate::constants::mcp::MIN_POPUP_TIMEOUT_SECS,
-                crate::constants::mcp::MAX_POPUP_TIMEOUT_SECS,
-            )
-        }
-        Err(_) => crate::constants::mcp::DEFAULT_POPUP_TIMEOUT_SECS,
+    let live = DAEMON_CONFIG
+        .read()
+        .ok()
+        .and_then(|config| config.as_ref().map(|c| c.popup_timeout_secs));
+    let timeout = match live {
+        Some(timeout) => timeout,
+        None => match crate::config::load_standalone_config() {
+            Ok(config) => config.daemon_config.popup_timeout_secs,
+            Err(_) => return crate::constants::mcp::DEFAULT_POPUP_TIMEOUT_SECS,
+        },
+    };
+    // 确保在合理范围内
+    timeout.clamp(
+        crate::constants::mcp::MIN_POPUP_TIMEOUT_SECS,
+        crate::constants::mcp::MAX_POPUP_TIMEOUT_SECS,
+    )
+}
+
+/// 请求的超时时间：请求指定时优先，否则使用配置
+pub fn resolve_popup_timeout_secs(requested: Option<u64>) -> u64 {
+    match requested {
+        Some(secs) => secs.clamp(
+            crate::constants::mcp::MIN_REQUEST_POPUP_TIMEOUT_SECS,
+            crate::constants::mcp::MAX_POPUP_TIMEOUT_SECS,
+        ),
+        None => get_popup_timeout_secs(),
     }
 }
 
+/// 等待前端响应的弹窗
+struct PendingPopup {
+    sender: oneshot::Sender<String>,
+    /// 自由输入约束（提交时在此校验，不通过则弹窗保持打开）
+    input: Option<InputConstraints>,
+    /// 表单定义（提交的字段值同样在此校验）
+    form: Option<PopupForm>,
+}
+
 // Global storage for pending popup responses (使用 tokio::sync::Mutex 避免异步上下文问题)
 lazy_static::lazy_static! {
-    static ref PENDING_RESPONSES: Arc<Mutex<HashMap<String, oneshot::Sender<String>>>> = 
+    static ref PENDING_RESPONSES: Arc<Mutex<HashMap<String, PendingPopup>>> = 
         Arc::new(Mutex::new(HashMap::new()));
     
     // 进行中的请求缓存：基于消息内容 hash，允许多个请求者共享同一个弹窗响应
@@ -55,8 +91,13 @@ lazy_static::lazy_static! {
         Arc::new(Mutex::new(HashMap::new()));
 }
 
-/// 计算消息内容的 hash 值
-fn compute_message_hash(message: &str, options: &Option<Vec<String>>) -> u64 {
+/// 计算消息内容的 hash 值（预览内容或表单不同的弹窗不能共享响应）
+fn compute_message_hash(
+    message: &str,
+    options: &Option<Vec<String>>,
+    preview: &Option<PopupPreview>,
+    form: &Option<PopupForm>,
+) -> u64 {
     let mut hasher = DefaultHasher::new();
     message.hash(&mut hasher);
     if let Some(opts) = options {
@@ -64,13 +105,24 @@ fn compute_message_hash(message: &str, options: &Option<Vec<String>>) -> u64 {
             opt.hash(&mut hasher);
         }
     }
+    if let Some(preview) = preview {
+        serde_json::to_string(preview).unwrap_or_default().hash(&mut hasher);
+    }
+    if let Some(form) = form {
+        serde_json::to_string(form).unwrap_or_default().hash(&mut hasher);
+    }
     hasher.finish()
 }
 
 // Show popup via Tauri window and wait for response
 pub async fn show_popup_and_wait(app_handle: &AppHandle, request: &PopupRequest) -> Result<String> {
     // 计算消息 hash，用于去重
-    let message_hash = compute_message_hash(&request.message, &request.predefined_options);
+    let message_hash = compute_message_hash(
+        &request.message,
+        &request.predefined_options,
+        &request.preview,
+        &request.form,
+    );
     
     // 首先检查是否有已完成的缓存响应（防止降级请求导致重复弹窗）
     {
@@ -111,12 +163,20 @@ pub async fn show_popup_and_wait(app_handle: &AppHandle, request: &PopupRequest)
     }
     
     // 上下文增强：自动注入项目信息和相关记忆
-    let enhanced_message = enhance_message_with_context(&request.message);
+    let enhanced_message = enhance_message_with_context(&request.message).await;
     let enhanced_request = PopupRequest {
         id: request.id.clone(),
         message: enhanced_message,
         predefined_options: request.predefined_options.clone(),
         is_markdown: request.is_markdown,
+        multi_select: request.multi_select,
+        input: request.input.clone(),
+        preview: request.preview.clone(),
+        picker: request.picker.clone(),
+        timeout_secs: request.timeout_secs,
+        default_option: request.default_option.clone(),
+        form: request.form.clone(),
+        language: request.language,
     };
     log_debug!("Popup request with context enhancement");
     
@@ -141,7 +201,11 @@ pub async fn show_popup_and_wait(app_handle: &AppHandle, request: &PopupRequest)
             return Err(anyhow::anyhow!("Duplicate request ID: {}", request_id));
         }
         
-        pending.insert(request_id.clone(), tx);
+        pending.insert(request_id.clone(), PendingPopup {
+            sender: tx,
+            input: request.input.clone(),
+            form: request.form.clone(),
+        });
         log_important!(info, "[Popup] Registered pending request: {}, total pending: {}", request_id, pending.len());
     }
     
@@ -190,8 +254,8 @@ pub async fn show_popup_and_wait(app_handle: &AppHandle, request: &PopupRequest)
     
     log_debug!("Popup request sent to frontend, waiting for response...");
     
-    // 从配置获取超时时间
-    let timeout_secs = get_popup_timeout_secs();
+    // 请求未指定时从配置获取超时时间
+    let timeout_secs = resolve_popup_timeout_secs(request.timeout_secs);
     log_debug!("Using popup timeout: {} seconds", timeout_secs);
     
     // Wait for response with timeout
@@ -221,9 +285,33 @@ pub async fn show_popup_and_wait(app_handle: &AppHandle, request: &PopupRequest)
         Err(_) => {
             log_important!(warn, "Popup response timeout after {} seconds", timeout_secs);
             // Clean up pending response
-            let mut pending = PENDING_RESPONSES.lock().await;
-            pending.remove(&request_id);
-            Err(anyhow::anyhow!("Popup response timeout ({} seconds)", timeout_secs))
+            PENDING_RESPONSES.lock().await.remove(&request_id);
+
+            // 通知前端关闭已过期的弹窗
+            if let Err(e) = window.emit("mcp-popup-timeout", &request_id) {
+                log_important!(warn, "Failed to emit popup timeout: {}", e);
+            }
+
+            match request.default_option.clone() {
+                Some(default_option) => {
+                    log_important!(info, "[Popup] Using default option after timeout: {}", default_option);
+                    let response = build_send_response(
+                        None,
+                        vec![default_option],
+                        vec![],
+                        Some(request_id.clone()),
+                        crate::constants::mcp::POPUP_TIMEOUT_DEFAULT_SOURCE,
+                    );
+                    // 等待同一弹窗的请求也收到默认选项
+                    let _ = broadcast_tx.send(response.clone());
+                    Ok(response)
+                }
+                None => Err(anyhow::anyhow!(
+                    "{} ({} seconds)",
+                    crate::constants::mcp::POPUP_TIMEOUT_ERROR,
+                    timeout_secs
+                )),
+            }
         }
     };
     
@@ -260,9 +348,16 @@ pub async fn handle_popup_response(request_id: String, response: String) -> Resu
         log_important!(info, "[Popup] Pending request_id: {}", key);
     }
     
-    if let Some(tx) = pending.remove(&request_id) {
+    if let Some(popup) = pending.get(&request_id) {
+        if let Err(message) = validate_popup_response(popup, &response) {
+            log_important!(info, "[Popup] Input rejected for request {}: {}", request_id, message);
+            return Err(anyhow::anyhow!(message));
+        }
+    }
+
+    if let Some(popup) = pending.remove(&request_id) {
         log_important!(info, "[Popup] Found pending request, sending response...");
-        if tx.send(response).is_err() {
+        if popup.sender.send(response).is_err() {
             log_important!(warn, "[Popup] Failed to send response through channel (receiver dropped)");
         } else {
             log_important!(info, "[Popup] Response sent successfully through channel");
@@ -272,4 +367,26 @@ pub async fn handle_popup_response(request_id: String, response: String) -> Resu
         log_important!(error, "[Popup] No pending request found for ID: {}", request_id);
         Err(anyhow::anyhow!("No pending request found for ID: {}", request_id))
     }
-}
\ No newline at end of file
+}
+/// 校验弹窗提交的表单字段与自由输入
+///
+/// 只校验用户直接提交的响应：继续/取消不受约束，只选择了预定义选项时不校验自由输入
+fn validate_popup_response(popup: &PendingPopup, response: &str) -> std::result::Result<(), String> {
+    let Ok(parsed) = serde_json::from_str::<McpResponse>(response) else {
+        return Ok(());
+    };
+    if parsed.metadata.source.as_deref() != Some("popup") {
+        return Ok(());
+    }
+    if let Some(ref form) = popup.form {
+        form.validate(&parsed.form_values.clone().unwrap_or_default())?;
+    }
+    if let Some(ref input) = popup.input {
+        let text = parsed.user_input.as_deref().unwrap_or_default().trim();
+        if text.is_empty() && !parsed.selected_options.is_empty() {
+            return Ok(());
+        }
+        input.validate(text)?;
+    }
+    Ok(())
+}
@@ -1,11 +1,12 @@
 use axum::{
-    extract::State,
+    extract::{Query, State},
     http::StatusCode,
     response::{IntoResponse, Json},
     routing::{get, post},
     Router,
 };
 use super::ws_handler::ws_upgrade_handler;
+use tower_http::trace::TraceLayer;
 use std::sync::Arc;
 use std::time::Instant;
 use tauri::AppHandle;
@@ -13,6 +14,7 @@ use tauri::AppHandle;
 use super::types::{DaemonRequest, DaemonResponse, HealthResponse};
 use super::context_orchestrator::enhance_message_with_context;
 use crate::mcp::tools::{MemoryTool, AcemcpTool};
+use crate::mcp::utils::{McpToolError, ToolError, ToolErrorCode};
 use crate::log_debug;
 
 // Validation constants for DoS protection
@@ -48,8 +50,13 @@ pub fn create_router() -> Router {
     
     Router::new()
         .route("/health", get(health_check))
+        .route("/index/dashboard", get(index_dashboard))
+        .route("/index/reindex", post(reindex_project))
+        .route("/index/cancel", post(cancel_indexing))
+        .route("/memory/record-commit", post(record_commit))
         .route("/mcp/execute", post(execute_tool))
         .route("/ws", get(ws_upgrade_handler))  // WebSocket endpoint
+        .layer(TraceLayer::new_for_http().make_span_with(http_request_span))
         .with_state(state)
 }
 
@@ -59,11 +66,28 @@ pub fn create_router_with_app(app_handle: AppHandle) -> Router {
     
     Router::new()
         .route("/health", get(health_check))
+        .route("/index/dashboard", get(index_dashboard))
+        .route("/index/reindex", post(reindex_project))
+        .route("/index/cancel", post(cancel_indexing))
+        .route("/memory/record-commit", post(record_commit))
         .route("/mcp/execute", post(execute_tool))
         .route("/ws", get(ws_upgrade_handler))  // WebSocket endpoint
+        .layer(TraceLayer::new_for_http().make_span_with(http_request_span))
         .with_state(state)
 }
 
+/// HTTP 请求的 span，以 MCP 服务进程传来的 `traceparent` 作为父级
+fn http_request_span(request: &axum::http::Request<axum::body::Body>) -> tracing::Span {
+    let span = tracing::info_span!("daemon.http", method = %request.method(), path = %request.uri().path());
+    let carrier = request
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| Some((name.as_str().to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+    crate::app::otel::set_parent(&span, &carrier);
+    span
+}
+
 /// Health check endpoint
 async fn health_check(
     State(state): State<Arc<DaemonAppState>>,
@@ -77,7 +101,100 @@ async fn health_check(
     })
 }
 
+/// Query parameters for the index dashboard endpoint
+#[derive(Debug, serde::Deserialize)]
+struct DashboardQuery {
+    /// Limit the dashboard to one project (all known projects if omitted)
+    project_root: Option<String>,
+}
+
+/// Index health dashboard endpoint (same data as the `get_index_dashboard` Tauri command)
+async fn index_dashboard(Query(query): Query<DashboardQuery>) -> impl IntoResponse {
+    let dashboard = tokio::task::spawn_blocking(move || {
+        crate::mcp::tools::acemcp::dashboard::collect_index_dashboard(query.project_root.as_deref())
+    })
+    .await;
+
+    match dashboard {
+        Ok(dashboard) => (StatusCode::OK, Json(serde_json::json!(dashboard))),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": format!("Dashboard task failed: {}", e) })),
+        ),
+    }
+}
+
+/// Request body for the index admin endpoints
+#[derive(Debug, serde::Deserialize)]
+struct IndexAdminBody {
+    project_root: String,
+}
+
+/// Force-rebuild endpoint (same as the `reindex_project` Tauri command)
+async fn reindex_project(Json(body): Json<IndexAdminBody>) -> impl IntoResponse {
+    index_admin_response(
+        tokio::task::spawn_blocking(move || {
+            crate::mcp::tools::acemcp::index_admin::reindex_project(&body.project_root)
+        })
+        .await,
+    )
+}
+
+/// Cancel-indexing endpoint (same as the `cancel_indexing` Tauri command)
+async fn cancel_indexing(Json(body): Json<IndexAdminBody>) -> impl IntoResponse {
+    index_admin_response(
+        tokio::task::spawn_blocking(move || {
+            crate::mcp::tools::acemcp::index_admin::cancel_project_indexing(&body.project_root)
+        })
+        .await,
+    )
+}
+
+fn index_admin_response(
+    result: Result<Result<crate::mcp::tools::acemcp::index_admin::IndexAdminResponse, String>, tokio::task::JoinError>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    match result {
+        Ok(Ok(response)) => (StatusCode::OK, Json(serde_json::json!(response))),
+        Ok(Err(e)) => (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": e }))),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": format!("Index admin task failed: {}", e) })),
+        ),
+    }
+}
+
+/// Request body for the record-commit endpoint
+#[derive(Debug, serde::Deserialize)]
+struct RecordCommitBody {
+    project_root: String,
+    /// Commit to record (any git revision, defaults to HEAD)
+    #[serde(default = "default_commit_rev")]
+    commit: String,
+}
+
+fn default_commit_rev() -> String {
+    "HEAD".to_string()
+}
+
+/// Record a change memory for a commit (called by the post-commit hook)
+async fn record_commit(Json(body): Json<RecordCommitBody>) -> impl IntoResponse {
+    let result = tokio::task::spawn_blocking(move || {
+        crate::mcp::tools::memory::capture_commit(std::path::Path::new(&body.project_root), &body.commit)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(capture)) => (StatusCode::OK, Json(serde_json::json!(capture))),
+        Ok(Err(e)) => (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": e.to_string() }))),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": format!("Record commit task failed: {}", e) })),
+        ),
+    }
+}
+
 /// Execute MCP tool endpoint
+#[tracing::instrument(name = "daemon.request", skip_all, fields(tool = request.tool_name()))]
 async fn execute_tool(
     State(state): State<Arc<DaemonAppState>>,
     Json(request): Json<DaemonRequest>,
@@ -90,10 +207,10 @@ async fn execute_tool(
             if interact_req.message.len() > MAX_MESSAGE_SIZE {
                 return (
                     StatusCode::OK,
-                    Json(DaemonResponse::error(format!(
+                    Json(DaemonResponse::failure(invalid_params(format!(
                         "Message size exceeds maximum allowed size of {} bytes",
                         MAX_MESSAGE_SIZE
-                    )))
+                    ))))
                 );
             }
             
@@ -101,16 +218,20 @@ async fn execute_tool(
             if interact_req.predefined_options.len() > MAX_OPTIONS {
                 return (
                     StatusCode::OK,
-                    Json(DaemonResponse::error(format!(
+                    Json(DaemonResponse::failure(invalid_params(format!(
                         "Number of options ({}) exceeds maximum allowed ({})",
                         interact_req.predefined_options.len(),
                         MAX_OPTIONS
-                    )))
+                    ))))
                 );
             }
             
             // Use app handle if available for GUI popup
             if let Some(app_handle) = &state.app_handle {
+                if let Some(ref picker) = interact_req.picker {
+                    return (StatusCode::OK, Json(run_path_picker(app_handle, &interact_req.message, picker, interact_req.timeout_secs).await));
+                }
+
                 use crate::mcp::types::PopupRequest;
                 use crate::daemon::show_popup_and_wait;
                 use crate::mcp::handlers::parse_mcp_response;
@@ -124,11 +245,20 @@ async fn execute_tool(
                         Some(interact_req.predefined_options)
                     },
                     is_markdown: interact_req.is_markdown,
+                    multi_select: interact_req.multi_select,
+                    input: interact_req.input,
+                    preview: interact_req.preview,
+                    picker: None,
+                    timeout_secs: interact_req.timeout_secs,
+                    default_option: interact_req.default_option,
+                    form: interact_req.form,
+                    language: interact_req.language,
                 };
                 
                 match show_popup_and_wait(app_handle, &popup_request).await {
                     Ok(response_str) => {
-                        match parse_mcp_response(&response_str) {
+                        let lang = crate::mcp::utils::resolve_output_language(popup_request.language);
+                        match parse_mcp_response(&response_str, lang) {
                             Ok(content) => {
                                 let result = crate::mcp::create_success_result(content);
                                 match serde_json::to_value(&result) {
@@ -136,18 +266,19 @@ async fn execute_tool(
                                     Err(e) => DaemonResponse::error(format!("Failed to serialize result: {}", e)),
                                 }
                             }
-                            Err(e) => DaemonResponse::error(format!("Failed to parse response: {}", e)),
+                            Err(e) => DaemonResponse::failure(ToolError::new(ToolErrorCode::InvalidResponse, format!("Failed to parse response: {}", e))),
                         }
                     }
-                    Err(e) => DaemonResponse::error(format!("Popup failed: {}", e)),
+                    Err(e) => popup_failure(e),
                 }
             } else {
                 // Fail explicitly if no app handle (Headless Daemon)
                 // Do NOT call InteractionTool::interact here as it would cause infinite recursion
-                DaemonResponse::error(
+                DaemonResponse::failure(ToolError::new(
+                    ToolErrorCode::PopupFailed,
                     "Cannot show popup: Daemon running in headless mode or AppHandle missing. \
-                    GUI interaction requires the main application window."
-                )
+                    GUI interaction requires the main application window.",
+                ))
             }
         }
         DaemonRequest::Memory(memory_req) => {
@@ -158,7 +289,7 @@ async fn execute_tool(
                         Err(e) => DaemonResponse::error(format!("Failed to serialize result: {}", e)),
                     }
                 }
-                Err(e) => DaemonResponse::error(format!("Tool execution failed: {}", e)),
+                Err(e) => tool_failure(e),
             }
         }
         DaemonRequest::Search(search_req) => {
@@ -169,24 +300,98 @@ async fn execute_tool(
                         Err(e) => DaemonResponse::error(format!("Failed to serialize result: {}", e)),
                     }
                 }
-                Err(e) => DaemonResponse::error(format!("Tool execution failed: {}", e)),
+                Err(e) => tool_failure(e),
             }
         }
         DaemonRequest::EnhanceContext(enhance_req) => {
             // 使用 context_orchestrator 增强消息
-            let enhanced = enhance_message_with_context(&enhance_req.message);
+            let enhanced = enhance_message_with_context(&enhance_req.message).await;
             DaemonResponse::success(serde_json::json!({
                 "original": enhance_req.message,
                 "enhanced": enhanced,
             }))
         }
+        DaemonRequest::Notify(notify_req) => run_notification(state.app_handle.as_ref(), &notify_req),
     };
     
     (StatusCode::OK, Json(result))
 }
 
+/// 打开原生文件/目录选择器，结果以 JSON 文本返回
+async fn run_path_picker(
+    app_handle: &AppHandle,
+    title: &str,
+    picker: &crate::mcp::types::PathPickerRequest,
+    timeout_secs: Option<u64>,
+) -> DaemonResponse {
+    let timeout_secs = super::popup_handler::resolve_popup_timeout_secs(timeout_secs);
+    let picked = match super::pick_paths(app_handle, title, picker, timeout_secs).await {
+        Ok(picked) => picked,
+        Err(e) => {
+            let code = match e.downcast_ref::<McpToolError>() {
+                Some(McpToolError::PopupTimeout(_)) => ToolErrorCode::PopupTimeout,
+                _ => ToolErrorCode::PopupFailed,
+            };
+            return DaemonResponse::failure(ToolError::new(code, format!("Path picker failed: {}", e)));
+        }
+    };
+    let text = match serde_json::to_string_pretty(&picked) {
+        Ok(text) => text,
+        Err(e) => return DaemonResponse::error(format!("Failed to serialize result: {}", e)),
+    };
+    let result = crate::mcp::create_success_result(vec![rmcp::model::Content::text(text)]);
+    match serde_json::to_value(&result) {
+        Ok(json) => DaemonResponse::success(json),
+        Err(e) => DaemonResponse::error(format!("Failed to serialize result: {}", e)),
+    }
+}
+
+/// 显示非阻塞通知，不等待用户
+fn run_notification(app_handle: Option<&AppHandle>, request: &crate::mcp::NotifyRequest) -> DaemonResponse {
+    if request.message.len() > MAX_MESSAGE_SIZE {
+        return DaemonResponse::failure(invalid_params(format!(
+            "Message size exceeds maximum allowed size of {} bytes",
+            MAX_MESSAGE_SIZE
+        )));
+    }
+    let Some(app_handle) = app_handle else {
+        return DaemonResponse::failure(ToolError::new(
+            ToolErrorCode::PopupFailed,
+            "Cannot show notification: Daemon running in headless mode or AppHandle missing.",
+        ));
+    };
+    match super::show_notification(app_handle, request) {
+        Ok(delivery) => DaemonResponse::success(serde_json::json!({ "delivery": delivery })),
+        Err(e) => DaemonResponse::error(format!("Notification failed: {}", e)),
+    }
+}
+
+/// 参数校验失败
+fn invalid_params(message: String) -> ToolError {
+    ToolError::new(ToolErrorCode::InvalidParams, message)
+}
+
+/// 弹窗失败：区分等待超时与其他错误
+fn popup_failure(error: anyhow::Error) -> DaemonResponse {
+    let message = format!("Popup failed: {}", error);
+    let code = if message.contains(crate::constants::mcp::POPUP_TIMEOUT_ERROR) {
+        ToolErrorCode::PopupTimeout
+    } else {
+        ToolErrorCode::PopupFailed
+    };
+    DaemonResponse::failure(ToolError::new(code, message))
+}
+
+/// 工具执行失败：保留工具返回的错误码
+fn tool_failure(error: McpToolError) -> DaemonResponse {
+    let mut error = ToolError::from(error);
+    error.message = format!("Tool execution failed: {}", error.message);
+    DaemonResponse::failure(error)
+}
+
 /// Process daemon request - shared logic for HTTP and WebSocket handlers
 /// This is the core request processing function, extracted for reuse
+#[tracing::instrument(name = "daemon.request", skip_all, fields(tool = request.tool_name()))]
 pub async fn process_daemon_request(
     request: DaemonRequest,
     state: &Arc<DaemonAppState>,
@@ -195,23 +400,27 @@ pub async fn process_daemon_request(
         DaemonRequest::Interact(interact_req) => {
             // Validate message size
             if interact_req.message.len() > MAX_MESSAGE_SIZE {
-                return DaemonResponse::error(format!(
+                return DaemonResponse::failure(invalid_params(format!(
                     "Message size exceeds maximum allowed size of {} bytes",
                     MAX_MESSAGE_SIZE
-                ));
+                )));
             }
             
             // Validate options count
             if interact_req.predefined_options.len() > MAX_OPTIONS {
-                return DaemonResponse::error(format!(
+                return DaemonResponse::failure(invalid_params(format!(
                     "Number of options ({}) exceeds maximum allowed ({})",
                     interact_req.predefined_options.len(),
                     MAX_OPTIONS
-                ));
+                )));
             }
             
             // Use app handle if available for GUI popup
             if let Some(app_handle) = &state.app_handle {
+                if let Some(ref picker) = interact_req.picker {
+                    return run_path_picker(app_handle, &interact_req.message, picker, interact_req.timeout_secs).await;
+                }
+
                 use crate::mcp::types::PopupRequest;
                 use crate::daemon::show_popup_and_wait;
                 use crate::mcp::handlers::parse_mcp_response;
@@ -225,11 +434,20 @@ pub async fn process_daemon_request(
                         Some(interact_req.predefined_options)
                     },
                     is_markdown: interact_req.is_markdown,
+                    multi_select: interact_req.multi_select,
+                    input: interact_req.input,
+                    preview: interact_req.preview,
+                    picker: None,
+                    timeout_secs: interact_req.timeout_secs,
+                    default_option: interact_req.default_option,
+                    form: interact_req.form,
+                    language: interact_req.language,
                 };
                 
                 match show_popup_and_wait(app_handle, &popup_request).await {
                     Ok(response_str) => {
-                        match parse_mcp_response(&response_str) {
+                        let lang = crate::mcp::utils::resolve_output_language(popup_request.language);
+                        match parse_mcp_response(&response_str, lang) {
                             Ok(content) => {
                                 let result = crate::mcp::create_success_result(content);
                                 match serde_json::to_value(&result) {
@@ -237,15 +455,16 @@ pub async fn process_daemon_request(
                                     Err(e) => DaemonResponse::error(format!("Failed to serialize result: {}", e)),
                                 }
                             }
-                            Err(e) => DaemonResponse::error(format!("Failed to parse response: {}", e)),
+                            Err(e) => DaemonResponse::failure(ToolError::new(ToolErrorCode::InvalidResponse, format!("Failed to parse response: {}", e))),
                         }
                     }
-                    Err(e) => DaemonResponse::error(format!("Popup failed: {}", e)),
+                    Err(e) => popup_failure(e),
                 }
             } else {
-                DaemonResponse::error(
-                    "Cannot show popup: Daemon running in headless mode or AppHandle missing."
-                )
+                DaemonResponse::failure(ToolError::new(
+                    ToolErrorCode::PopupFailed,
+                    "Cannot show popup: Daemon running in headless mode or AppHandle missing.",
+                ))
             }
         }
         DaemonRequest::Memory(memory_req) => {
@@ -256,7 +475,7 @@ pub async fn process_daemon_request(
                         Err(e) => DaemonResponse::error(format!("Failed to serialize result: {}", e)),
                     }
                 }
-                Err(e) => DaemonResponse::error(format!("Tool execution failed: {}", e)),
+                Err(e) => tool_failure(e),
             }
         }
         DaemonRequest::Search(search_req) => {
@@ -267,15 +486,16 @@ pub async fn process_daemon_request(
                         Err(e) => DaemonResponse::error(format!("Failed to serialize result: {}", e)),
                     }
                 }
-                Err(e) => DaemonResponse::error(format!("Tool execution failed: {}", e)),
+                Err(e) => tool_failure(e),
             }
         }
         DaemonRequest::EnhanceContext(enhance_req) => {
-            let enhanced = enhance_message_with_context(&enhance_req.message);
+            let enhanced = enhance_message_with_context(&enhance_req.message).await;
             DaemonResponse::success(serde_json::json!({
                 "original": enhance_req.message,
                 "enhanced": enhanced,
             }))
         }
+        DaemonRequest::Notify(notify_req) => run_notification(state.app_handle.as_ref(), &notify_req),
     }
 }
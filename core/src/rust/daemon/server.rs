@@ -20,6 +20,9 @@ pub async fn start_daemon_server_with_app(app_handle: AppHandle, port: Option<u1
     
     // 初始化全局统一存储
     init_unified_store();
+
+    // 定期为已索引项目补算代码块和记忆的向量
+    crate::mcp::tools::acemcp::local_engine::embedding_queue::start_backfill_worker();
     
     log_important!(info, "Starting daemon HTTP server on {}", addr);
     
@@ -115,10 +118,8 @@ pub async fn wait_for_daemon(port: Option<u16>, timeout_secs: u64) -> Result<()>
 
 /// 初始化全局统一存储、搜索引擎和文件监听器
 fn init_unified_store() {
-    // 获取缓存目录
-    let base_cache_dir = dirs::cache_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("neurospec");
+    // 获取缓存目录（按当前配置档案隔离）
+    let base_cache_dir = crate::config::profile_cache_dir();
     
     let store_cache_dir = base_cache_dir.join("unified_store");
     let index_cache_dir = base_cache_dir.join("search_index");
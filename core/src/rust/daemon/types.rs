@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::mcp::utils::{ToolError, ToolErrorCode};
+
 /// Generic daemon request wrapper
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "tool", content = "params")]
@@ -15,6 +17,33 @@ pub enum DaemonRequest {
     
     #[serde(rename = "enhance_context")]
     EnhanceContext(EnhanceContextRequest),
+
+    #[serde(rename = "notify")]
+    Notify(crate::mcp::NotifyRequest),
+}
+
+impl DaemonRequest {
+    /// 请求的工具名（与序列化的 `tool` 字段一致）
+    pub fn tool_name(&self) -> &'static str {
+        match self {
+            DaemonRequest::Interact(_) => "interact",
+            DaemonRequest::Memory(_) => "memory",
+            DaemonRequest::Search(_) => "search",
+            DaemonRequest::EnhanceContext(_) => "enhance_context",
+            DaemonRequest::Notify(_) => "notify",
+        }
+    }
+
+    /// 客户端等待响应的超时时间（秒）：交互请求需覆盖弹窗超时
+    pub fn client_timeout_secs(&self, default_secs: u64) -> u64 {
+        match self {
+            DaemonRequest::Interact(req) => default_secs.max(
+                super::popup_handler::resolve_popup_timeout_secs(req.timeout_secs)
+                    + crate::constants::mcp::POPUP_CLIENT_TIMEOUT_MARGIN_SECS,
+            ),
+            _ => default_secs,
+        }
+    }
 }
 
 /// Request to enhance a message with context
@@ -29,6 +58,9 @@ pub struct DaemonResponse {
     pub success: bool,
     pub data: Option<serde_json::Value>,
     pub error: Option<String>,
+    /// 失败时的稳定错误码
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<ToolErrorCode>,
 }
 
 impl DaemonResponse {
@@ -37,16 +69,30 @@ impl DaemonResponse {
             success: true,
             data: Some(data),
             error: None,
+            code: None,
         }
     }
     
     pub fn error(message: impl Into<String>) -> Self {
+        Self::failure(ToolError::new(ToolErrorCode::InternalError, message))
+    }
+
+    /// 带错误码的失败响应
+    pub fn failure(error: ToolError) -> Self {
         Self {
             success: false,
             data: None,
-            error: Some(message.into()),
+            error: Some(error.message),
+            code: Some(error.code),
         }
     }
+
+    /// 失败响应转换为错误，错误码随 [`ToolError`] 保留在错误链中
+    pub fn into_error(self, fallback: &str) -> anyhow::Error {
+        let code = self.code.unwrap_or(ToolErrorCode::InternalError);
+        let message = self.error.unwrap_or_else(|| fallback.to_string());
+        anyhow::Error::new(ToolError::new(code, message))
+    }
 }
 
 /// Health check response
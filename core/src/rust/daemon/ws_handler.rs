@@ -11,7 +11,10 @@ use axum::{
 };
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::Instrument;
 
 use super::types::{DaemonRequest, DaemonResponse};
 use super::routes::DaemonAppState;
@@ -26,6 +29,9 @@ pub enum WsMessage {
     Request {
         id: String,
         payload: DaemonRequest,
+        /// 调用方的链路上下文（W3C `traceparent`，未启用链路追踪时省略）
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        trace_context: HashMap<String, String>,
     },
     /// 响应消息
     #[serde(rename = "response")]
@@ -45,6 +51,11 @@ pub enum WsMessage {
         id: Option<String>,
         message: String,
     },
+    /// 服务端推送的索引事件（索引进度、状态变化）
+    #[serde(rename = "event")]
+    Event {
+        payload: serde_json::Value,
+    },
 }
 
 /// 最大消息大小（10MB）- 支持大图片响应
@@ -86,6 +97,10 @@ async fn handle_ws_connection(socket: WebSocket, state: Arc<DaemonAppState>) {
     
     // 心跳定时器 - 15秒间隔，与客户端更同步
     let mut heartbeat_interval = tokio::time::interval(std::time::Duration::from_secs(15));
+
+    // 订阅索引事件，推送给客户端
+    let mut store_events = crate::mcp::tools::unified_store::subscribe_store_events();
+    let mut store_events_open = true;
     
     // 主消息处理循环
     loop {
@@ -182,6 +197,29 @@ async fn handle_ws_connection(socket: WebSocket, state: Arc<DaemonAppState>) {
                 }
             }
             
+            // 推送索引事件（落后时跳过，客户端只关心最新进度）
+            event = store_events.recv(), if store_events_open => {
+                let event = match event {
+                    Ok(event) if event.is_index_event() => event,
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(skipped)) => {
+                        log_debug!("[WebSocket][Conn#{}] Event forwarder lagged, skipped {} events", conn_id, skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => {
+                        store_events_open = false;
+                        continue;
+                    }
+                };
+                let message = WsMessage::Event {
+                    payload: serde_json::to_value(&event).unwrap_or_default(),
+                };
+                if let Err(e) = sender.send(Message::Text(serde_json::to_string(&message).unwrap_or_default())).await {
+                    log_important!(error, "[WebSocket][Conn#{}] Failed to push event: {}", conn_id, e);
+                    break;
+                }
+            }
+
             // 发送心跳
             _ = heartbeat_interval.tick() => {
                 let ping = WsMessage::Ping;
@@ -201,11 +239,16 @@ async fn handle_ws_connection(socket: WebSocket, state: Arc<DaemonAppState>) {
 /// 处理 WebSocket 消息
 async fn handle_ws_message(msg: WsMessage, state: &Arc<DaemonAppState>) -> Option<WsMessage> {
     match msg {
-        WsMessage::Request { id, payload } => {
+        WsMessage::Request { id, payload, trace_context } => {
             log_important!(info, "[WebSocket] Processing request: {}", id);
             
+            let span = tracing::info_span!("daemon.ws", request_id = %id);
+            crate::app::otel::set_parent(&span, &trace_context);
+
             // 使用抽取的公共请求处理逻辑
-            let response = super::routes::process_daemon_request(payload, state).await;
+            let response = super::routes::process_daemon_request(payload, state)
+                .instrument(span)
+                .await;
             
             Some(WsMessage::Response {
                 id,
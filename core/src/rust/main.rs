@@ -13,6 +13,9 @@ fn main() -> Result<()> {
         eprintln!("Failed to initialize logging system: {}", e);
     }
 
+    // 崩溃时写入崩溃报告
+    neurospec::app::crash::install_panic_hook();
+
     // Handle CLI arguments
     handle_cli_args()
 }
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use tauri::{AppHandle, State};
 
-use crate::config::{AppState, save_config};
+use crate::config::{AppState, OutputLanguage, save_config};
 use crate::constants::mcp;
 // use crate::mcp::tools::acemcp; // 已迁移到独立模块
 
@@ -133,6 +133,33 @@ pub async fn reset_mcp_tools_config(
     Ok(())
 }
 
+/// 获取工具输出语言
+#[tauri::command]
+pub async fn get_output_language(state: State<'_, AppState>) -> Result<OutputLanguage, String> {
+    let config = state.config.lock().map_err(|e| format!("获取配置失败: {}", e))?;
+    Ok(config.mcp_config.output_language)
+}
+
+/// 设置工具输出语言
+#[tauri::command]
+pub async fn set_output_language(
+    language: OutputLanguage,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    {
+        let mut config = state.config.lock().map_err(|e| format!("获取配置失败: {}", e))?;
+        config.mcp_config.output_language = language;
+    }
+
+    save_config(&state, &app).await
+        .map_err(|e| format!("保存配置失败: {}", e))?;
+    crate::mcp::utils::set_cached_output_language(language);
+
+    log::info!("工具输出语言已更新为: {:?}", language);
+    Ok(())
+}
+
 // acemcp 相关命令已迁移
 
 // 已移除 Python Web 服务相关函数，完全使用 Rust 实现
@@ -1,4 +1,7 @@
-use rmcp::{model::CallToolResult, ErrorData as McpError};
+use rmcp::{
+    model::{CallToolResult, ErrorCode},
+    ErrorData as McpError,
+};
 use std::sync::Once;
 
 use crate::mcp::tools::{AcemcpTool, InteractionTool, MemoryTool};
@@ -9,26 +12,27 @@ use crate::mcp::tools::unified_store::{init_global_search_config, init_global_st
 /// 确保搜索系统只初始化一次
 static SEARCH_INIT: Once = Once::new();
 
+/// 不依赖搜索系统的工具（工具子集只含这些时跳过搜索系统初始化）
+const TOOLS_WITHOUT_SEARCH: &[&str] = &["interact", "memory", "notify"];
+
 /// 初始化 MCP 搜索系统
 /// 
 /// 在 MCP stdio 模式下，daemon 服务器可能未启动，
 /// 因此需要在 dispatcher 中也进行初始化。
-fn ensure_search_system_initialized() {
+pub(crate) fn ensure_search_system_initialized() {
     SEARCH_INIT.call_once(|| {
         if is_search_initialized() {
             return; // 已由 daemon 初始化
         }
         
         // 使用与 LocalEngineConfig::default() 一致的路径，复用已有索引
-        // 索引路径: ~/.acemcp/local_index
+        // 索引路径: ~/.acemcp/local_index（每个项目位于 projects/<路径哈希> 子目录）
         // 存储路径: %LOCALAPPDATA%/neurospec/unified_store
+        // 命名配置档案下两者均位于 profiles/<档案名> 子目录
         let default_config = crate::mcp::tools::acemcp::local_engine::LocalEngineConfig::default();
         let index_cache_dir = default_config.index_path;
         
-        let store_cache_dir = dirs::cache_dir()
-            .unwrap_or_else(|| std::path::PathBuf::from("."))
-            .join("neurospec")
-            .join("unified_store");
+        let store_cache_dir = crate::config::profile_cache_dir().join("unified_store");
         
         // 初始化全局存储
         if let Err(e) = init_global_store(&store_cache_dir) {
@@ -72,6 +76,23 @@ impl ToolDispatcher {
         Self { registered_tools }
     }
 
+    /// 只注册指定的工具子集（`--mcp --tools`），未注册的工具名会被忽略
+    ///
+    /// 子集不含搜索类工具时不初始化符号存储、搜索索引和文件监听
+    pub fn with_tools(tools: &[String]) -> Self {
+        let registered_tools: std::collections::HashSet<String> = crate::mcp::tool_registry::get_all_tool_names()
+            .into_iter()
+            .filter(|name| tools.iter().any(|t| t == name))
+            .map(String::from)
+            .collect();
+
+        if registered_tools.iter().any(|name| !TOOLS_WITHOUT_SEARCH.contains(&name.as_str())) {
+            ensure_search_system_initialized();
+        }
+
+        Self { registered_tools }
+    }
+
     /// Check if a tool is registered (O(1))
     pub fn has_tool(&self, tool_name: &str) -> bool {
         self.registered_tools.contains(tool_name)
@@ -85,6 +106,7 @@ impl ToolDispatcher {
     /// Dispatch a tool call
     ///
     /// This uses match instead of HashMap<closure> to avoid async lifetime issues
+    #[tracing::instrument(name = "mcp.tool", skip(self, args), fields(ok = tracing::field::Empty))]
     pub async fn dispatch(
         &self,
         tool_name: &str,
@@ -99,11 +121,21 @@ impl ToolDispatcher {
         }
 
         // Dispatch to handlers
-        match tool_name {
+        let result = match tool_name {
             "interact" => Self::handle_interact(args).await,
             "memory" => Self::handle_memory(args).await,
+            "change_intent" => Self::handle_change_intent(args).await,
+            "file_history" => Self::handle_file_history(args).await,
             "search" => Self::handle_search(args).await,
             "health" => Self::handle_health(args).await,
+            "symbols" => Self::handle_symbols(args).await,
+            "read_symbol" => Self::handle_read_symbol(args).await,
+            "store_stats" => Self::handle_store_stats(args).await,
+            "index_admin" => Self::handle_index_admin(args).await,
+            "projects" => Self::handle_projects(args).await,
+            "perf_profile" => Self::handle_perf_profile(args).await,
+            "interact_history" => Self::handle_interact_history(args).await,
+            "notify" => Self::handle_notify(args).await,
 
             #[cfg(feature = "experimental-neurospec")]
             name if name.starts_with("neurospec_") => Self::handle_neurospec(name, args).await,
@@ -112,7 +144,15 @@ impl ToolDispatcher {
                 format!("Unknown tool: {}", tool_name),
                 None,
             )),
+        };
+
+        let failed = result.is_err() || result.as_ref().is_ok_and(|r| r.is_error == Some(true));
+        crate::app::telemetry::record_tool_call(tool_name, !failed);
+        if failed {
+            crate::app::telemetry::record_error(error_category(&result));
         }
+        tracing::Span::current().record("ok", !failed);
+        result
     }
 
     /// Handle interact tool
@@ -214,6 +254,82 @@ impl ToolDispatcher {
         Ok(crate::mcp::tools::acemcp::health::check_health(req).await?)
     }
 
+    /// Handle symbols tool
+    async fn handle_symbols(args: serde_json::Value) -> Result<CallToolResult, McpError> {
+        let req: crate::mcp::tools::acemcp::symbols::SymbolsRequest = serde_json::from_value(args)
+            .map_err(|e| invalid_params_error(format!("Failed to parse parameters: {}", e)))?;
+        Ok(crate::mcp::tools::acemcp::symbols::query_symbols(req).await?)
+    }
+
+    /// Handle read_symbol tool
+    async fn handle_read_symbol(args: serde_json::Value) -> Result<CallToolResult, McpError> {
+        let req: crate::mcp::tools::acemcp::read_symbol::ReadSymbolRequest = serde_json::from_value(args)
+            .map_err(|e| invalid_params_error(format!("Failed to parse parameters: {}", e)))?;
+        Ok(crate::mcp::tools::acemcp::read_symbol::read_symbol(req).await?)
+    }
+
+    /// Handle store_stats tool
+    async fn handle_store_stats(args: serde_json::Value) -> Result<CallToolResult, McpError> {
+        let req: crate::mcp::tools::acemcp::store_stats::StoreStatsRequest = serde_json::from_value(args)
+            .map_err(|e| invalid_params_error(format!("Failed to parse parameters: {}", e)))?;
+        Ok(crate::mcp::tools::acemcp::store_stats::store_stats(req).await?)
+    }
+
+    /// Handle change_intent tool
+    async fn handle_change_intent(args: serde_json::Value) -> Result<CallToolResult, McpError> {
+        let req: crate::mcp::tools::memory::mcp::ChangeIntentRequest = serde_json::from_value(args)
+            .map_err(|e| invalid_params_error(format!("Failed to parse parameters: {}", e)))?;
+        Ok(MemoryTool::change_intent(req).await?)
+    }
+
+    /// Handle file_history tool
+    async fn handle_file_history(args: serde_json::Value) -> Result<CallToolResult, McpError> {
+        let req: crate::mcp::tools::memory::mcp::FileHistoryRequest = serde_json::from_value(args)
+            .map_err(|e| invalid_params_error(format!("Failed to parse parameters: {}", e)))?;
+        Ok(MemoryTool::file_history(req).await?)
+    }
+
+    /// Handle index_admin tool
+    async fn handle_index_admin(args: serde_json::Value) -> Result<CallToolResult, McpError> {
+        let req: crate::mcp::tools::acemcp::index_admin::IndexAdminRequest = serde_json::from_value(args)
+            .map_err(|e| invalid_params_error(format!("Failed to parse parameters: {}", e)))?;
+        Ok(crate::mcp::tools::acemcp::index_admin::index_admin(req).await?)
+    }
+
+    /// Handle projects tool
+    async fn handle_projects(args: serde_json::Value) -> Result<CallToolResult, McpError> {
+        let req: crate::mcp::tools::acemcp::projects::ProjectsRequest = serde_json::from_value(args)
+            .map_err(|e| invalid_params_error(format!("Failed to parse parameters: {}", e)))?;
+        Ok(crate::mcp::tools::acemcp::projects::projects(req).await?)
+    }
+
+    /// Handle perf_profile tool
+    async fn handle_perf_profile(args: serde_json::Value) -> Result<CallToolResult, McpError> {
+        let req: crate::mcp::tools::acemcp::perf_profile::PerfProfileRequest = serde_json::from_value(args)
+            .map_err(|e| invalid_params_error(format!("Failed to parse parameters: {}", e)))?;
+        Ok(crate::mcp::tools::acemcp::perf_profile::perf_profile(req).await?)
+    }
+
+    /// Handle interact_history tool
+    async fn handle_interact_history(args: serde_json::Value) -> Result<CallToolResult, McpError> {
+        let req: crate::mcp::tools::interaction::mcp::InteractHistoryRequest = serde_json::from_value(args)
+            .map_err(|e| invalid_params_error(format!("Failed to parse parameters: {}", e)))?;
+        InteractionTool::query_history(req).await
+    }
+
+    /// Handle notify tool
+    async fn handle_notify(args: serde_json::Value) -> Result<CallToolResult, McpError> {
+        let req: crate::mcp::types::NotifyRequest = serde_json::from_value(args)
+            .map_err(|e| invalid_params_error(format!("Failed to parse parameters: {}", e)))?;
+        let delivery = crate::mcp::handlers::send_notification(&req)
+            .await
+            .map_err(|e| McpError::internal_error(format!("Notification failed: {}", e), None))?;
+        Ok(crate::mcp::create_success_result(vec![rmcp::model::Content::text(format!(
+            "Notification shown ({})",
+            delivery
+        ))]))
+    }
+
     /// 预处理 search 参数，修复 profile 字段可能被序列化为字符串的问题
     fn preprocess_search_args(mut args: serde_json::Value) -> serde_json::Value {
         if let serde_json::Value::Object(ref mut map) = args {
@@ -263,3 +379,17 @@ impl Default for ToolDispatcher {
         Self::new()
     }
 }
+
+/// 工具调用失败的统计类别（固定字符串，不含错误信息）
+fn error_category(result: &Result<CallToolResult, McpError>) -> &'static str {
+    match result {
+        Ok(_) => "tool_error",
+        Err(e) => match e.code {
+            ErrorCode::INVALID_PARAMS => "invalid_params",
+            ErrorCode::INVALID_REQUEST => "invalid_request",
+            ErrorCode::METHOD_NOT_FOUND => "method_not_found",
+            ErrorCode::INTERNAL_ERROR => "internal_error",
+            _ => "other",
+        },
+    }
+}
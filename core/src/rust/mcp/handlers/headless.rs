@@ -0,0 +1,301 @@
+//! 无 GUI 交互回退
+//!
+//! 纯 stdio 部署（如服务器上）没有 daemon/GUI 可以弹窗时，交互工具按
+//! `headless_policy` 通过 MCP elicitation 向客户端提问，或自动使用默认答案。
+//! 两种方式都返回与 daemon 相同格式的响应 JSON，后续解析与历史记录保持不变。
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use anyhow::Result;
+use rmcp::{model::*, service::Peer, RoleServer};
+use serde_json::{json, Map, Value};
+
+use crate::config::HeadlessPolicy;
+use crate::constants::mcp::{
+    ELICITATION_SOURCE, HEADLESS_DEFAULT_SOURCE, POPUP_TIMEOUT_DEFAULT_SOURCE, POPUP_TIMEOUT_ERROR,
+};
+use crate::daemon::popup_handler::resolve_popup_timeout_secs;
+use crate::log_important;
+use crate::mcp::types::{build_mcp_response, FormFieldKind, PopupForm, PopupPreview, PopupRequest};
+use crate::mcp::utils::errors::{McpToolError, ToolError, ToolErrorCode};
+use crate::mcp::utils::resolve_output_language;
+use crate::tr;
+
+/// elicitation 中单选选项的字段名
+const CHOICE_FIELD: &str = "choice";
+
+/// elicitation 中自由文本回答的字段名
+const REPLY_FIELD: &str = "reply";
+
+/// 当前 MCP 客户端（stdio 服务只对应一个客户端）
+static CLIENT_PEER: OnceLock<Peer<RoleServer>> = OnceLock::new();
+
+/// 记录客户端连接，供无 GUI 时发起 elicitation
+pub fn register_client_peer(peer: Peer<RoleServer>) {
+    let _ = CLIENT_PEER.set(peer);
+}
+
+/// 弹窗错误是否因为 daemon/GUI 不可用
+///
+/// 包括无法连接 daemon，以及 daemon 以 headless 模式运行（没有窗口）时返回的弹窗失败
+pub fn is_daemon_unavailable(error: &anyhow::Error) -> bool {
+    if matches!(
+        error.downcast_ref::<McpToolError>(),
+        Some(McpToolError::DaemonConnection(_))
+    ) {
+        return true;
+    }
+    matches!(
+        error.downcast_ref::<ToolError>().map(|e| e.code),
+        Some(ToolErrorCode::DaemonUnavailable | ToolErrorCode::PopupFailed)
+    )
+}
+
+/// 按配置的策略在无 GUI 时回答弹窗
+///
+/// 返回与 daemon 相同格式的响应；策略不允许回退时返回 None
+pub async fn answer_headless(request: &PopupRequest) -> Result<Option<String>> {
+    let policy = crate::config::load_standalone_config()
+        .map(|config| config.mcp_config.headless_policy)
+        .unwrap_or_default();
+
+    match policy {
+        HeadlessPolicy::Disabled => Ok(None),
+        HeadlessPolicy::DefaultAnswer => default_answer(request).map(Some),
+        HeadlessPolicy::Auto | HeadlessPolicy::Elicitation => match elicitation_peer() {
+            Some(peer) => elicit(peer, request).await.map(Some),
+            // 自动模式下没有可用的默认答案时报错，不编造空回答
+            None if policy == HeadlessPolicy::Auto => {
+                if request.default_option.is_none() && request.form.is_none() {
+                    return Err(anyhow::anyhow!(
+                        "No interactive UI is available, the client does not support elicitation, \
+                         and the request has no default_option"
+                    ));
+                }
+                default_answer(request).map(Some)
+            }
+            None => {
+                log_important!(warn, "[Headless] Client does not support elicitation");
+                Ok(None)
+            }
+        },
+    }
+}
+
+/// 支持 elicitation 的客户端连接
+fn elicitation_peer() -> Option<&'static Peer<RoleServer>> {
+    CLIENT_PEER.get().filter(|peer| {
+        peer.peer_info()
+            .is_some_and(|info| info.capabilities.elicitation.is_some())
+    })
+}
+
+/// 自动作答：default_option 作为选中项，表单使用字段默认值
+fn default_answer(request: &PopupRequest) -> Result<String> {
+    let form_values = match request.form {
+        Some(ref form) => {
+            let values = form_defaults(form);
+            form.validate(&values)
+                .map_err(|e| anyhow::anyhow!("Cannot fill in the form without a UI: {}", e))?;
+            Some(values)
+        }
+        None => None,
+    };
+
+    log_important!(info, "[Headless] Answering {} with the default answer", request.id);
+    Ok(build_response(
+        None,
+        request.default_option.clone().into_iter().collect(),
+        form_values,
+        &request.id,
+        HEADLESS_DEFAULT_SOURCE,
+    ))
+}
+
+/// 表单字段的默认值（未指定默认值的勾选框视为未勾选）
+fn form_defaults(form: &PopupForm) -> Map<String, Value> {
+    form.fields
+        .iter()
+        .filter_map(|field| match (&field.default, field.kind) {
+            (Some(default), _) => Some((field.name.clone(), default.clone())),
+            (None, FormFieldKind::Checkbox) => Some((field.name.clone(), Value::Bool(false))),
+            (None, _) => None,
+        })
+        .collect()
+}
+
+/// 通过 MCP elicitation 让客户端向用户提问
+async fn elicit(peer: &Peer<RoleServer>, request: &PopupRequest) -> Result<String> {
+    let options = request.predefined_options.clone().unwrap_or_default();
+
+    // 模式按弹窗内容动态生成，经 JSON 转换为请求参数
+    let params: CreateElicitationRequestParam = serde_json::from_value(json!({
+        "message": elicitation_message(request),
+        "requestedSchema": elicitation_schema(request, &options),
+    }))?;
+
+    let timeout_secs = resolve_popup_timeout_secs(request.timeout_secs);
+    log_important!(info, "[Headless] Asking the client via elicitation: {}", request.id);
+
+    let result = match tokio::time::timeout(Duration::from_secs(timeout_secs), peer.create_elicitation(params)).await {
+        Ok(result) => result?,
+        // 与 daemon 一致：超时有默认选项时使用默认选项，否则返回超时错误
+        Err(_) => {
+            return match request.default_option {
+                Some(ref default_option) => Ok(build_response(
+                    None,
+                    vec![default_option.clone()],
+                    None,
+                    &request.id,
+                    POPUP_TIMEOUT_DEFAULT_SOURCE,
+                )),
+                None => Err(anyhow::anyhow!("{} ({} seconds)", POPUP_TIMEOUT_ERROR, timeout_secs)),
+            };
+        }
+    };
+
+    if !matches!(result.action, ElicitationAction::Accept) {
+        return Ok("CANCELLED".to_string());
+    }
+
+    let content = match result.content {
+        Some(Value::Object(content)) => content,
+        _ => Map::new(),
+    };
+    response_from_elicitation(request, &options, content)
+}
+
+/// 提问文本：图片预览无法展示，diff 预览以代码块附在消息后
+fn elicitation_message(request: &PopupRequest) -> String {
+    match request.preview {
+        Some(PopupPreview::Diff { ref diff, .. }) => format!("{}\n\n```diff\n{}\n```", request.message, diff.trim_end()),
+        _ => request.message.clone(),
+    }
+}
+
+/// 生成 elicitation 的字段模式
+///
+/// 有表单时只收集表单字段；否则单选为一个枚举字段，多选为每个选项一个勾选字段，
+/// 另附自由文本回答
+fn elicitation_schema(request: &PopupRequest, options: &[String]) -> Value {
+    let lang = resolve_output_language(request.language);
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+
+    if let Some(ref form) = request.form {
+        for field in &form.fields {
+            let mut property = match field.kind {
+                FormFieldKind::Text => json!({ "type": "string" }),
+                FormFieldKind::Select => json!({ "type": "string", "enum": field.options }),
+                FormFieldKind::Checkbox => json!({ "type": "boolean" }),
+            };
+            property["title"] = json!(field.label.as_deref().unwrap_or(&field.name));
+            if let Some(ref placeholder) = field.placeholder {
+                property["description"] = json!(placeholder);
+            }
+            if let Some(ref default) = field.default {
+                property["default"] = default.clone();
+            }
+            if field.required && field.kind != FormFieldKind::Checkbox {
+                required.push(field.name.clone());
+            }
+            properties.insert(field.name.clone(), property);
+        }
+        return json!({ "type": "object", "properties": properties, "required": required });
+    }
+
+    if request.multi_select {
+        for option in options {
+            properties.insert(option.clone(), json!({
+                "type": "boolean",
+                "title": option,
+                "default": request.default_option.as_ref() == Some(option),
+            }));
+        }
+    } else if !options.is_empty() {
+        let mut choice = json!({
+            "type": "string",
+            "title": tr!(lang, "选择", "Choice"),
+            "enum": options,
+        });
+        if let Some(ref default_option) = request.default_option {
+            choice["default"] = json!(default_option);
+        }
+        properties.insert(CHOICE_FIELD.to_string(), choice);
+    }
+
+    let mut reply = json!({ "type": "string", "title": tr!(lang, "回复", "Reply") });
+    if let Some(ref input) = request.input {
+        if let Some(ref placeholder) = input.placeholder {
+            reply["description"] = json!(placeholder);
+        }
+        if let Some(ref pattern) = input.pattern {
+            reply["pattern"] = json!(format!("^(?:{})$", pattern));
+        }
+        if let Some(min_length) = input.min_length {
+            reply["minLength"] = json!(min_length);
+        }
+        if let Some(max_length) = input.max_length {
+            reply["maxLength"] = json!(max_length);
+        }
+    }
+    properties.insert(REPLY_FIELD.to_string(), reply);
+    if options.is_empty() {
+        required.push(REPLY_FIELD.to_string());
+    }
+
+    json!({ "type": "object", "properties": properties, "required": required })
+}
+
+/// 把 elicitation 的回答转换为弹窗响应
+fn response_from_elicitation(
+    request: &PopupRequest,
+    options: &[String],
+    mut content: Map<String, Value>,
+) -> Result<String> {
+    if let Some(ref form) = request.form {
+        form.validate(&content).map_err(|e| anyhow::anyhow!("Invalid form values: {}", e))?;
+        return Ok(build_response(None, vec![], Some(content), &request.id, ELICITATION_SOURCE));
+    }
+
+    let user_input = content
+        .remove(REPLY_FIELD)
+        .and_then(|v| v.as_str().map(|s| s.to_string()))
+        .filter(|s| !s.trim().is_empty());
+    if let (Some(ref input), Some(ref text)) = (&request.input, &user_input) {
+        input.validate(text).map_err(|e| anyhow::anyhow!("Invalid reply: {}", e))?;
+    }
+
+    let selected_options = if request.multi_select {
+        options
+            .iter()
+            .filter(|option| content.get(option.as_str()).and_then(Value::as_bool) == Some(true))
+            .cloned()
+            .collect()
+    } else {
+        content
+            .get(CHOICE_FIELD)
+            .and_then(Value::as_str)
+            .filter(|choice| options.iter().any(|o| o == choice))
+            .map(|choice| vec![choice.to_string()])
+            .unwrap_or_default()
+    };
+
+    Ok(build_response(user_input, selected_options, None, &request.id, ELICITATION_SOURCE))
+}
+
+/// 构建与 daemon 相同格式的响应 JSON
+fn build_response(
+    user_input: Option<String>,
+    selected_options: Vec<String>,
+    form_values: Option<Map<String, Value>>,
+    request_id: &str,
+    source: &str,
+) -> String {
+    let mut response = build_mcp_response(user_input, selected_options, vec![], Some(request_id.to_string()), source);
+    if let Some(form_values) = form_values {
+        response["form_values"] = Value::Object(form_values);
+    }
+    response.to_string()
+}
@@ -1,6 +1,8 @@
+pub mod headless;
 pub mod popup;
 pub mod response;
 pub mod ws_client;
 
+pub use headless::{answer_headless, is_daemon_unavailable, register_client_peer};
 pub use popup::*;
 pub use response::*;
@@ -1,6 +1,6 @@
 use anyhow::Result;
 
-use crate::mcp::types::{PopupRequest, InteractRequest};
+use crate::mcp::types::{PopupRequest, InteractRequest, NotifyRequest};
 use crate::daemon::{DaemonClient, DaemonRequest, DaemonResponse};
 use super::ws_client;
 use crate::{log_important, log_debug};
@@ -23,34 +23,23 @@ pub async fn create_tauri_popup(request: &PopupRequest) -> Result<String> {
         message: request.message.clone(),
         predefined_options: request.predefined_options.clone().unwrap_or_default(),
         is_markdown: request.is_markdown,
+        multi_select: request.multi_select,
+        input: request.input.clone(),
+        preview: request.preview.clone(),
+        picker: request.picker.clone(),
+        timeout_secs: request.timeout_secs,
+        default_option: request.default_option.clone(),
+        form: request.form.clone(),
+        language: request.language,
     };
     
-    let daemon_request = DaemonRequest::Interact(interact_request);
-    
-    // 策略：WS 优先，HTTP 降级
-    let ws_enabled = is_websocket_enabled();
-    log_important!(info, "[Popup] WebSocket enabled: {}", ws_enabled);
-    
-    let response = if ws_enabled {
-        // 尝试 WebSocket
-        log_important!(info, "[Popup] Attempting WebSocket connection to daemon...");
-        match ws_client::execute_via_ws(daemon_request.clone()).await {
-            Ok(resp) => {
-                log_important!(info, "[Popup] WebSocket request successful!");
-                resp
-            }
-            Err(ws_err) => {
-                // WS 失败，降级到 HTTP
-                log_important!(warn, "[Popup] WebSocket failed: {}, falling back to HTTP", ws_err);
-                execute_via_http(daemon_request).await?
-            }
-        }
-    } else {
-        // WebSocket 禁用，直接使用 HTTP
-        log_important!(info, "[Popup] WebSocket disabled, using HTTP");
-        execute_via_http(daemon_request).await?
-    };
+    let response = execute_daemon_request(DaemonRequest::Interact(interact_request)).await?;
     
+    // daemon 返回的错误（如弹窗超时）直接上抛，不能当作用户取消
+    if !response.success && response.error.is_some() {
+        return Err(response.into_error("Popup failed"));
+    }
+
     // 从响应中提取结果
     if let Some(result) = response.data {
         // 结果是 CallToolResult 的 JSON 表示
@@ -74,6 +63,46 @@ pub async fn create_tauri_popup(request: &PopupRequest) -> Result<String> {
     }
 }
 
+/// 发送非阻塞通知（daemon 显示后立即返回，不等待用户）
+pub async fn send_notification(request: &NotifyRequest) -> Result<String> {
+    let response = execute_daemon_request(DaemonRequest::Notify(request.clone())).await?;
+    if !response.success {
+        return Err(response.into_error("Notification failed"));
+    }
+    Ok(response
+        .data
+        .and_then(|d| d.get("delivery").and_then(|v| v.as_str()).map(|s| s.to_string()))
+        .unwrap_or_default())
+}
+
+/// 执行 Daemon 请求
+///
+/// 策略：WS 优先，HTTP 降级
+async fn execute_daemon_request(daemon_request: DaemonRequest) -> Result<DaemonResponse> {
+    let ws_enabled = is_websocket_enabled();
+    log_important!(info, "[Popup] WebSocket enabled: {}", ws_enabled);
+    
+    if ws_enabled {
+        // 尝试 WebSocket
+        log_important!(info, "[Popup] Attempting WebSocket connection to daemon...");
+        match ws_client::execute_via_ws(daemon_request.clone()).await {
+            Ok(resp) => {
+                log_important!(info, "[Popup] WebSocket request successful!");
+                Ok(resp)
+            }
+            Err(ws_err) => {
+                // WS 失败，降级到 HTTP
+                log_important!(warn, "[Popup] WebSocket failed: {}, falling back to HTTP", ws_err);
+                execute_via_http(daemon_request).await
+            }
+        }
+    } else {
+        // WebSocket 禁用，直接使用 HTTP
+        log_important!(info, "[Popup] WebSocket disabled, using HTTP");
+        execute_via_http(daemon_request).await
+    }
+}
+
 /// 通过 HTTP 执行 Daemon 请求
 async fn execute_via_http(request: DaemonRequest) -> Result<DaemonResponse> {
     log_debug!("Executing request via HTTP client");
@@ -91,8 +120,11 @@ async fn execute_via_http(request: DaemonRequest) -> Result<DaemonResponse> {
             }
         }
 
-        // Fallback: 字符串匹配检查连接错误
+        // Fallback: 字符串匹配检查连接错误（弹窗超时不属于连接错误）
         let error_msg = e.to_string();
+        if error_msg.contains(crate::constants::mcp::POPUP_TIMEOUT_ERROR) {
+            return e;
+        }
         if error_msg.contains("connect") || error_msg.contains("refused") || error_msg.contains("timeout") {
             log_important!(error, "Daemon HTTP connection failed (msg match): {}", error_msg);
             return anyhow::Error::new(daemon_connection_error(
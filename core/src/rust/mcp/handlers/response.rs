@@ -4,7 +4,9 @@ use std::fs;
 use std::path::PathBuf;
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 
+use crate::config::OutputLanguage;
 use crate::mcp::types::{McpResponse, McpResponseContent};
+use crate::tr;
 
 /// 获取图片保存目录
 /// 
@@ -63,17 +65,70 @@ fn save_image_to_temp(base64_data: &str, media_type: &str, index: usize) -> Opti
     }
 }
 
+/// 响应文本中选中选项的标签（中文、英文）
+const SELECTED_OPTIONS_LABELS: [&str; 2] = ["选择的选项", "Selected options"];
+
+/// 用户取消时 daemon 返回的原始文本
+const CANCELLED_RESPONSES: [&str; 2] = ["CANCELLED", "用户取消了操作"];
+
+fn selected_options_label(lang: OutputLanguage) -> &'static str {
+    match lang {
+        OutputLanguage::Zh => SELECTED_OPTIONS_LABELS[0],
+        OutputLanguage::En => SELECTED_OPTIONS_LABELS[1],
+    }
+}
+
+/// 超时使用默认选项时的提示
+pub fn popup_timeout_notice(lang: OutputLanguage) -> &'static str {
+    match lang {
+        OutputLanguage::Zh => crate::constants::mcp::POPUP_TIMEOUT_NOTICE,
+        OutputLanguage::En => crate::constants::mcp::POPUP_TIMEOUT_NOTICE_EN,
+    }
+}
+
+/// 无 GUI 自动作答时的提示
+fn headless_default_notice(lang: OutputLanguage) -> &'static str {
+    match lang {
+        OutputLanguage::Zh => crate::constants::mcp::HEADLESS_DEFAULT_NOTICE,
+        OutputLanguage::En => crate::constants::mcp::HEADLESS_DEFAULT_NOTICE_EN,
+    }
+}
+
+/// 响应文本是否以超时提示开头（任一语言）
+pub fn starts_with_timeout_notice(text: &str) -> bool {
+    text.starts_with(crate::constants::mcp::POPUP_TIMEOUT_NOTICE)
+        || text.starts_with(crate::constants::mcp::POPUP_TIMEOUT_NOTICE_EN)
+}
+
+/// 从解析后的响应文本中取回选中的选项（单选与多选两种格式，任一语言）
+pub fn extract_selected_options(text: &str) -> Vec<String> {
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        let Some(rest) = SELECTED_OPTIONS_LABELS.iter().find_map(|label| line.strip_prefix(label)) else {
+            continue;
+        };
+        if let Some(option) = rest.strip_prefix(": ") {
+            return vec![option.to_string()];
+        }
+        return lines
+            .map_while(|l| l.strip_prefix("- "))
+            .map(|o| o.to_string())
+            .collect();
+    }
+    Vec::new()
+}
+
 /// 解析 MCP 响应内容
 ///
-/// 支持新的结构化格式和旧格式的兼容性，并生成适当的 Content 对象
-pub fn parse_mcp_response(response: &str) -> Result<Vec<Content>, McpError> {
-    if response.trim() == "CANCELLED" || response.trim() == "用户取消了操作" {
-        return Ok(vec![Content::text("用户取消了操作".to_string())]);
+/// 支持新的结构化格式和旧格式的兼容性，并按输出语言生成适当的 Content 对象
+pub fn parse_mcp_response(response: &str, lang: OutputLanguage) -> Result<Vec<Content>, McpError> {
+    if CANCELLED_RESPONSES.contains(&response.trim()) {
+        return Ok(vec![Content::text(tr!(lang, "用户取消了操作", "The user cancelled the operation"))]);
     }
 
     // 首先尝试解析为新的结构化格式
     if let Ok(structured_response) = serde_json::from_str::<McpResponse>(response) {
-        return parse_structured_response(structured_response);
+        return parse_structured_response(structured_response, lang);
     }
 
     // 回退到旧格式兼容性解析
@@ -119,8 +174,10 @@ pub fn parse_mcp_response(response: &str) -> Result<Vec<Content>, McpError> {
                                     format!("{:.1} MB", estimated_size as f64 / (1024.0 * 1024.0))
                                 };
 
-                                let image_info = format!(
+                                let image_info = tr!(
+                                    lang,
                                     "=== 图片 {} ===\n类型: {}\n大小: {}\nBase64 预览: {}\n完整 Base64 长度: {} 字符",
+                                    "=== Image {} ===\nType: {}\nSize: {}\nBase64 preview: {}\nFull Base64 length: {} chars",
                                     image_count, source.media_type, size_str, preview, base64_len
                                 );
                                 image_info_parts.push(image_info);
@@ -151,10 +208,7 @@ pub fn parse_mcp_response(response: &str) -> Result<Vec<Content>, McpError> {
 
             // 3. 兼容性说明
             if image_count > 0 {
-                all_text_parts.push(format!(
-                    "💡 注意：用户提供了 {} 张图片。如果 AI 助手无法显示图片，图片数据已包含在上述 Base64 信息中。",
-                    image_count
-                ));
+                all_text_parts.push(images_fallback_note(lang, image_count));
             }
 
             // 将所有文本内容合并并添加到结果末尾（图片后面）
@@ -164,7 +218,7 @@ pub fn parse_mcp_response(response: &str) -> Result<Vec<Content>, McpError> {
             }
 
             if result.is_empty() {
-                result.push(Content::text("用户未提供任何内容".to_string()));
+                result.push(Content::text(tr!(lang, "用户未提供任何内容", "The user did not provide any content")));
             }
 
             Ok(result)
@@ -179,13 +233,36 @@ pub fn parse_mcp_response(response: &str) -> Result<Vec<Content>, McpError> {
 /// 解析新的结构化响应格式
 /// 
 /// 优化：避免在栈上构建超大字符串，使用堆分配和大小限制
-fn parse_structured_response(response: McpResponse) -> Result<Vec<Content>, McpError> {
+fn parse_structured_response(response: McpResponse, lang: OutputLanguage) -> Result<Vec<Content>, McpError> {
     let mut result = Vec::new();
     let mut text_parts = Vec::new();
 
-    // 1. 处理选择的选项
-    if !response.selected_options.is_empty() {
-        text_parts.push(format!("选择的选项: {}", response.selected_options.join(", ")));
+    // 超时或无 GUI 时自动使用默认答案要注明，避免被当作用户的真实选择
+    match response.metadata.source.as_deref() {
+        Some(crate::constants::mcp::POPUP_TIMEOUT_DEFAULT_SOURCE) => {
+            text_parts.push(popup_timeout_notice(lang).to_string());
+        }
+        Some(crate::constants::mcp::HEADLESS_DEFAULT_SOURCE) => {
+            text_parts.push(headless_default_notice(lang).to_string());
+        }
+        _ => {}
+    }
+
+    // 1. 处理选择的选项（多选时逐行列出，避免选项中的逗号造成歧义）
+    let label = selected_options_label(lang);
+    match response.selected_options.as_slice() {
+        [] => {}
+        [option] => text_parts.push(format!("{}: {}", label, option)),
+        options => {
+            let list = options.iter().map(|o| format!("- {}", o)).collect::<Vec<_>>().join("\n");
+            text_parts.push(tr!(lang, "{}（共 {} 项）:\n{}", "{} ({}):\n{}", label, options.len(), list));
+        }
+    }
+
+    // 表单字段值以 JSON 对象返回，便于调用方直接解析
+    if let Some(ref form_values) = response.form_values {
+        let json = serde_json::to_string_pretty(form_values).unwrap_or_default();
+        text_parts.push(tr!(lang, "表单填写结果:\n```json\n{}\n```", "Form values:\n```json\n{}\n```", json));
     }
 
     // 2. 处理用户输入文本
@@ -217,12 +294,14 @@ fn parse_structured_response(response: McpResponse) -> Result<Vec<Content>, McpE
 
         // 使用堆分配构建 Markdown 内联图片（避免栈溢出）
         let mut markdown_image = String::with_capacity(base64_len + 100);
-        markdown_image.push_str(&format!("![图片 {}](data:{};base64,", index + 1, image.media_type));
+        markdown_image.push_str(&tr!(lang, "![图片 {}](data:{};base64,", "![Image {}](data:{};base64,", index + 1, image.media_type));
         markdown_image.push_str(&image.data);
         markdown_image.push(')');
         
-        let image_info = format!(
+        let image_info = tr!(
+            lang,
             "=== 图片 {} ===\n类型: {}\n大小: {}\n\n{}",
+            "=== Image {} ===\nType: {}\nSize: {}\n\n{}",
             index + 1, image.media_type, size_str, markdown_image
         );
         image_info_parts.push(image_info);
@@ -237,21 +316,20 @@ fn parse_structured_response(response: McpResponse) -> Result<Vec<Content>, McpE
         let mut saved_paths = Vec::new();
         for (index, image) in response.images.iter().enumerate() {
             if let Some(path) = save_image_to_temp(&image.data, &image.media_type, index + 1) {
-                saved_paths.push(format!("📁 图片 {}: {}", index + 1, path.display()));
+                saved_paths.push(tr!(lang, "📁 图片 {}: {}", "📁 Image {}: {}", index + 1, path.display()));
             }
         }
         
         if !saved_paths.is_empty() {
-            all_text_parts.push(format!(
+            all_text_parts.push(tr!(
+                lang,
                 "⚠️ **用户上传了 {} 张图片，请立即使用 read_file 工具查看！**\n{}",
+                "⚠️ **The user uploaded {} image(s); view them with the read_file tool now!**\n{}",
                 saved_paths.len(),
                 saved_paths.join("\n")
             ));
         } else {
-            all_text_parts.push(format!(
-                "💡 注意：用户提供了 {} 张图片。如果 AI 助手无法显示图片，图片数据已包含在上述 Base64 信息中。",
-                response.images.len()
-            ));
+            all_text_parts.push(images_fallback_note(lang, response.images.len()));
         }
     }
 
@@ -263,8 +341,18 @@ fn parse_structured_response(response: McpResponse) -> Result<Vec<Content>, McpE
 
     // 7. 如果没有任何内容，添加默认响应
     if result.is_empty() {
-        result.push(Content::text("用户未提供任何内容".to_string()));
+        result.push(Content::text(tr!(lang, "用户未提供任何内容", "The user did not provide any content")));
     }
 
     Ok(result)
 }
+
+/// 客户端可能无法显示图片时的说明
+fn images_fallback_note(lang: OutputLanguage, count: usize) -> String {
+    tr!(
+        lang,
+        "💡 注意：用户提供了 {} 张图片。如果 AI 助手无法显示图片，图片数据已包含在上述 Base64 信息中。",
+        "💡 Note: the user provided {} image(s). If the assistant cannot display images, the data is included in the Base64 information above.",
+        count
+    )
+}
@@ -12,6 +12,7 @@ use tokio::sync::{mpsc, oneshot, RwLock};
 use tokio_tungstenite::{connect_async_with_config, tungstenite::{Message, protocol::WebSocketConfig}};
 
 use crate::daemon::types::{DaemonRequest, DaemonResponse};
+use crate::mcp::utils::{ToolError, ToolErrorCode};
 use crate::daemon::server::DEFAULT_DAEMON_PORT;
 use crate::{log_important, log_debug};
 
@@ -23,6 +24,8 @@ enum WsMessage {
     Request {
         id: String,
         payload: DaemonRequest,
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        trace_context: HashMap<String, String>,
     },
     #[serde(rename = "response")]
     Response {
@@ -40,6 +43,8 @@ enum WsMessage {
         id: Option<String>,
         message: String,
     },
+    #[serde(rename = "event")]
+    Event { payload: serde_json::Value },
 }
 
 /// 待处理的请求
@@ -149,7 +154,7 @@ async fn ws_connection_loop() {
                         log_important!(warn, "[WsClient] Cleaning up {} pending requests due to disconnection", pending_count);
                         for (id, pending) in state.pending.drain() {
                             log_important!(warn, "[WsClient] Canceling pending request: {}", id);
-                            let _ = pending.sender.send(DaemonResponse::error("WebSocket disconnected".to_string()));
+                            let _ = pending.sender.send(DaemonResponse::failure(ToolError::new(ToolErrorCode::DaemonUnavailable, "WebSocket disconnected")));
                         }
                     }
                 }
@@ -332,6 +337,10 @@ async fn handle_message(text: &str) {
                 }
             }
         }
+        Ok(WsMessage::Event { payload }) => {
+            // 索引进度等推送事件，MCP 侧只记录
+            log_debug!("[WsClient] Received event: {}", payload);
+        }
         Ok(other) => {
             log_important!(warn, "[WsClient] Received unexpected message type: {:?}", other);
         }
@@ -354,6 +363,7 @@ pub async fn execute_via_ws(request: DaemonRequest) -> Result<DaemonResponse> {
     
     let request_id = uuid::Uuid::new_v4().to_string();
     log_important!(info, "[WsClient] Request ID: {}", request_id);
+    let timeout_secs = request.client_timeout_secs(REQUEST_TIMEOUT_SECS);
     
     let (tx, rx) = oneshot::channel();
     
@@ -384,6 +394,7 @@ pub async fn execute_via_ws(request: DaemonRequest) -> Result<DaemonResponse> {
     let msg = WsMessage::Request {
         id: request_id.clone(),
         payload: request,
+        trace_context: crate::app::otel::current_context(),
     };
     let msg_text = serde_json::to_string(&msg)?;
     log_important!(info, "[WsClient] Sending message, length={}", msg_text.len());
@@ -397,10 +408,10 @@ pub async fn execute_via_ws(request: DaemonRequest) -> Result<DaemonResponse> {
         return Err(anyhow::anyhow!("Failed to send: {}", e));
     }
     
-    log_important!(info, "[WsClient] Message sent, waiting for response (timeout={}s)...", REQUEST_TIMEOUT_SECS);
+    log_important!(info, "[WsClient] Message sent, waiting for response (timeout={}s)...", timeout_secs);
     
     // 等待响应
-    match tokio::time::timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS), rx).await {
+    match tokio::time::timeout(Duration::from_secs(timeout_secs), rx).await {
         Ok(Ok(response)) => {
             log_important!(info, "[WsClient] Response received successfully");
             // 检查是否是错误响应（连接断开导致的）
@@ -417,7 +428,7 @@ pub async fn execute_via_ws(request: DaemonRequest) -> Result<DaemonResponse> {
             Err(anyhow::anyhow!("Response channel closed - connection dropped"))
         }
         Err(_) => {
-            log_important!(error, "[WsClient] Request timeout after {}s", REQUEST_TIMEOUT_SECS);
+            log_important!(error, "[WsClient] Request timeout after {}s", timeout_secs);
             let mut state = WS_CLIENT.write().await;
             state.pending.remove(&request_id);
             Err(anyhow::anyhow!("Request timeout"))
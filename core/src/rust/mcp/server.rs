@@ -4,7 +4,7 @@ use rmcp::{
     model::*, service::RequestContext, transport::stdio, ErrorData as McpError, RoleServer,
     ServerHandler, ServiceExt,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use super::dispatcher::ToolDispatcher;
 use crate::config::load_standalone_config;
@@ -12,6 +12,8 @@ use crate::{log_debug, log_important};
 
 pub struct ZhiServer {
     enabled_tools: HashMap<String, bool>,
+    /// 命令行指定的工具子集（优先于配置中的启用状态）
+    tool_subset: Option<HashSet<String>>,
     dispatcher: std::sync::Arc<ToolDispatcher>,
 }
 
@@ -34,13 +36,26 @@ impl ZhiServer {
 
         Self {
             enabled_tools,
+            tool_subset: None,
             dispatcher: std::sync::Arc::new(ToolDispatcher::new()),
         }
     }
 
+    /// 只提供指定工具的服务器，其余工具既不列出也不可调用
+    pub fn with_tool_subset(tools: Vec<String>) -> Self {
+        Self {
+            enabled_tools: HashMap::new(),
+            dispatcher: std::sync::Arc::new(ToolDispatcher::with_tools(&tools)),
+            tool_subset: Some(tools.into_iter().collect()),
+        }
+    }
+
     /// 检查工具是否启用 - 使用缓存配置
     /// 注意：配置更新通过配置监听器自动重新加载
     fn is_tool_enabled(&self, tool_name: &str) -> bool {
+        if let Some(subset) = &self.tool_subset {
+            return subset.contains(tool_name);
+        }
         let enabled = self.enabled_tools.get(tool_name).copied().unwrap_or(true);
         log_debug!("工具 {} 当前状态: {}", tool_name, enabled);
         enabled
@@ -63,8 +78,10 @@ impl ServerHandler for ZhiServer {
     async fn initialize(
         &self,
         _request: InitializeRequestParam,
-        _context: RequestContext<RoleServer>,
+        context: RequestContext<RoleServer>,
     ) -> Result<ServerInfo, McpError> {
+        // 无 GUI 时交互工具通过该连接向客户端发起 elicitation
+        crate::mcp::handlers::register_client_peer(context.peer.clone());
         Ok(self.get_info())
     }
 
@@ -112,12 +129,35 @@ impl ServerHandler for ZhiServer {
 
 /// 启动MCP服务器
 pub async fn run_server() -> Result<(), Box<dyn std::error::Error>> {
+    run_server_with_tools(None).await
+}
+
+/// 启动MCP服务器，`tools` 为 Some 时只提供指定的工具
+///
+/// stdin 关闭后服务结束，退出前按 GUI 退出流程清理各子系统
+pub async fn run_server_with_tools(tools: Option<Vec<String>>) -> Result<(), Box<dyn std::error::Error>> {
+    let server = match tools {
+        Some(tools) => {
+            let available = crate::mcp::tool_registry::get_all_tool_names();
+            if let Some(unknown) = tools.iter().find(|t| !available.contains(&t.as_str())) {
+                return Err(format!("未知工具: {}（可用工具: {}）", unknown, available.join(", ")).into());
+            }
+            log_important!(info, "MCP 服务器工具子集: {}", tools.join(", "));
+            ZhiServer::with_tool_subset(tools)
+        }
+        None => ZhiServer::new(),
+    };
+
     // 创建并运行服务器
-    let service = ZhiServer::new().serve(stdio()).await.inspect_err(|e| {
+    let service = server.serve(stdio()).await.inspect_err(|e| {
         log_important!(error, "启动服务器失败: {}", e);
     })?;
 
-    // 等待服务器关闭
-    service.waiting().await?;
+    // 等待服务器关闭（客户端关闭 stdin）
+    let result = service.waiting().await;
+
+    crate::ui::exit_handler::shutdown_subsystems();
+
+    result?;
     Ok(())
 }
@@ -5,12 +5,20 @@
 use rmcp::model::Tool;
 use schemars::schema_for;
 
-use crate::mcp::types::{InteractRequest, MemoryRequest};
+use crate::mcp::types::{InteractRequest, MemoryRequest, NotifyRequest};
 use crate::mcp::tools::acemcp::types::SearchRequest;
 use crate::mcp::tools::acemcp::health::HealthRequest;
+use crate::mcp::tools::acemcp::symbols::SymbolsRequest;
+use crate::mcp::tools::acemcp::read_symbol::ReadSymbolRequest;
+use crate::mcp::tools::acemcp::store_stats::StoreStatsRequest;
+use crate::mcp::tools::acemcp::index_admin::IndexAdminRequest;
+use crate::mcp::tools::acemcp::projects::ProjectsRequest;
+use crate::mcp::tools::acemcp::perf_profile::PerfProfileRequest;
+use crate::mcp::tools::interaction::mcp::InteractHistoryRequest;
+use crate::mcp::tools::memory::mcp::{ChangeIntentRequest, FileHistoryRequest};
 
 #[cfg(feature = "experimental-neurospec")]
-use crate::neurospec::tools::{ImpactAnalysisArgs, RenameArgs};
+use crate::neurospec::tools::{ArchitectureCheckArgs, GraphCallersArgs, GraphCyclesArgs, GraphExportArgs, ImpactAnalysisArgs, ImpactChangesetArgs, PlanAssessRiskArgs, PlanDryRunArgs, PlanExecuteArgs, PlanResumeArgs, PlanSchemaArgs, PlanValidateArgs, PlansArgs, RenameArgs};
 
 /// 工具定义条目
 pub struct ToolDefinition {
@@ -28,7 +36,7 @@ pub struct ToolDefinition {
 pub const CORE_TOOLS: &[ToolDefinition] = &[
     ToolDefinition {
         name: "interact",
-        description: "Interactive dialogue tool with support for predefined options, free-text input, and image uploads",
+        description: "Interactive dialogue tool with support for single or multi-select predefined options, free-text input, image uploads, diff/image previews, multi-field forms (text/select/checkbox) and a native file/folder picker",
         is_core: true,
         feature: None,
     },
@@ -38,15 +46,75 @@ pub const CORE_TOOLS: &[ToolDefinition] = &[
         is_core: true,
         feature: None,
     },
+    ToolDefinition {
+        name: "change_intent",
+        description: "Explain why code looks the way it does: pass `commit` (any git revision) or `file_path` + `line` (resolved via git blame) to get the commit and the recorded change memories (summary, user intent, symbols) behind it; `file_path` alone lists change memories touching that file",
+        is_core: false,
+        feature: None,
+    },
+    ToolDefinition {
+        name: "file_history",
+        description: "Chronological timeline of one file: recent git commits touching it merged with the recorded change memories (summary, user intent, symbols), oldest first, so you can understand why the code looks the way it does before changing it",
+        is_core: false,
+        feature: None,
+    },
     ToolDefinition {
         name: "search",
-        description: "🔍 PRIORITY TOOL: Always use this FIRST before reading files! Structure-first smart search for relevant code context in a project. Recommended usage: set `profile` to `smart_structure` or `structure_only` and use natural language queries. Low-level `mode` (`text`/`symbol`/`structure`) is kept for backward compatibility.",
+        description: "🔍 PRIORITY TOOL: Always use this FIRST before reading files! Structure-first smart search for relevant code context in a project. Recommended usage: set `profile` to `smart_structure` or `structure_only` and use natural language queries; `tests_for` pairs a symbol with the tests that exercise it. Low-level `mode` (`text`/`symbol`/`structure`) is kept for backward compatibility; `mode: references` finds all call sites and usages of a symbol via the code graph.",
         is_core: false,
         feature: None,
     },
     ToolDefinition {
         name: "health",
-        description: "Check Neurospec search engine health status, including index state, engine availability, and embedding readiness",
+        description: "Check Neurospec search engine health status, including index state, engine availability, and embedding readiness. Set verify=true to cross-check the symbol store, tantivy documents and index state, or repair=true to also fix any drift found",
+        is_core: false,
+        feature: None,
+    },
+    ToolDefinition {
+        name: "symbols",
+        description: "Fast symbol lookup from the unified symbol store. Filter by file path prefix/substring, kind (file/module/class/function/variable), name prefix/substring and language instead of dumping all symbols. Set fuzzy_name to match names by edit distance; empty name lookups return did_you_mean suggestions",
+        is_core: false,
+        feature: None,
+    },
+    ToolDefinition {
+        name: "read_symbol",
+        description: "Read only the source of one definition instead of the whole file. Pass `file` + `symbol` (optionally qualified as `Type::method`), or a symbol ID `path::name`; the range comes from tree-sitter and includes leading doc comments, attributes and decorators",
+        is_core: false,
+        feature: None,
+    },
+    ToolDefinition {
+        name: "store_stats",
+        description: "Show unified symbol store statistics per project: file and symbol counts, per-language file counts, estimated memory usage, last update time, plus eviction/reload metrics",
+        is_core: false,
+        feature: None,
+    },
+    ToolDefinition {
+        name: "index_admin",
+        description: "Index maintenance for a project: action=reindex wipes and rebuilds the search index in the background (the old index keeps serving until the rebuild finishes); action=cancel stops a running indexing job and restores the previous index state",
+        is_core: false,
+        feature: None,
+    },
+    ToolDefinition {
+        name: "projects",
+        description: "List every project Neurospec has indexed or searched: path, last used time, index state, indexed file count, memory count and whether file watching is active. Pass project_root with auto_index and/or watch to change that project's settings",
+        is_core: false,
+        feature: None,
+    },
+    ToolDefinition {
+        name: "perf_profile",
+        description: "Run a standard benchmark against a project: full index rebuild time, p50/p95 text and symbol search latency for a query set, and memory recall latency. Keep queries and iterations fixed to compare reports across versions",
+        is_core: false,
+        feature: None,
+    },
+    ToolDefinition {
+        name: "interact_history",
+        description: "Query past interact popups and the user's answers by date range, response type (option/text/timed_out/empty), project and keyword, newest first. Pass export_path to write the matching records to a new JSON file inside the project for auditing",
+        is_core: false,
+        feature: None,
+    },
+    ToolDefinition {
+        name: "notify",
+        description: "Show a non-blocking notification (in-app toast, or an OS notification when the window is in the background) and return immediately without waiting for the user. Use for progress/status updates where an interact popup is overkill",
         is_core: false,
         feature: None,
     },
@@ -57,7 +125,37 @@ pub const CORE_TOOLS: &[ToolDefinition] = &[
 pub const NEUROSPEC_TOOLS: &[ToolDefinition] = &[
     ToolDefinition {
         name: "neurospec_graph_impact_analysis",
-        description: "分析符号的依赖影响范围，用于重构前评估",
+        description: "分析符号的依赖影响范围，按文件给出 blast radius 风险分数和审查清单，用于重构前评估",
+        is_core: false,
+        feature: Some("experimental-neurospec"),
+    },
+    ToolDefinition {
+        name: "neurospec_impact_changeset",
+        description: "分析一组修改文件（如 git diff --name-only 的输出）涉及的符号，汇总下游影响和受影响的测试，适合提交/PR 前检查",
+        is_core: false,
+        feature: Some("experimental-neurospec"),
+    },
+    ToolDefinition {
+        name: "neurospec_graph_callers",
+        description: "查询谁调用了某个函数/符号（带 文件:行号），可按深度展开传递依赖方，以及该符号自身的依赖，适合修改前确认影响",
+        is_core: false,
+        feature: Some("experimental-neurospec"),
+    },
+    ToolDefinition {
+        name: "neurospec_graph_cycles",
+        description: "用强连通分量检测代码图谱中的循环依赖，分别报告文件级（模块）和函数级的环及其依赖边（带 文件:行号），用于发现架构问题",
+        is_core: false,
+        feature: Some("experimental-neurospec"),
+    },
+    ToolDefinition {
+        name: "neurospec_graph_export",
+        description: "将代码图谱导出为 GraphML、Graphviz DOT 或 Mermaid，可按路径前缀筛选并写入文件，便于用外部工具可视化项目结构",
+        is_core: false,
+        feature: Some("experimental-neurospec"),
+    },
+    ToolDefinition {
+        name: "neurospec_architecture_check",
+        description: "按 .neurospec/architecture.toml 中的分层规则检查模块间的导入/调用依赖，报告违规的依赖边",
         is_core: false,
         feature: Some("experimental-neurospec"),
     },
@@ -67,6 +165,48 @@ pub const NEUROSPEC_TOOLS: &[ToolDefinition] = &[
         is_core: false,
         feature: Some("experimental-neurospec"),
     },
+    ToolDefinition {
+        name: "neurospec_plan_validate",
+        description: "校验 NSP 计划的结构（步骤引用的文件存在、操作类型已知、无冲突修改），返回机器可读的问题列表",
+        is_core: false,
+        feature: Some("experimental-neurospec"),
+    },
+    ToolDefinition {
+        name: "neurospec_plan_assess_risk",
+        description: "根据代码图谱（被引用数、跨语言引用、测试覆盖）自动计算 NSP 计划的风险级别，高风险时补充需要确认的问题",
+        is_core: false,
+        feature: Some("experimental-neurospec"),
+    },
+    ToolDefinition {
+        name: "neurospec_plan_dry_run",
+        description: "在内存副本上预演整个 NSP 计划，返回每一步的统一 diff 和冲突，执行前让用户确认具体修改",
+        is_core: false,
+        feature: Some("experimental-neurospec"),
+    },
+    ToolDefinition {
+        name: "neurospec_plan_execute",
+        description: "按依赖顺序逐步执行 NSP 计划（CREATE/MODIFY 使用提供的文件内容），在检查点弹窗确认，并把每一步结果记录为代码修改记忆",
+        is_core: false,
+        feature: Some("experimental-neurospec"),
+    },
+    ToolDefinition {
+        name: "neurospec_plan_resume",
+        description: "继续执行被中断（崩溃、检查点中止、步骤失败）的 NSP 计划，跳过已完成的步骤",
+        is_core: false,
+        feature: Some("experimental-neurospec"),
+    },
+    ToolDefinition {
+        name: "neurospec_plans",
+        description: "列出项目中保存的 NSP 计划及其执行状态和进度",
+        is_core: false,
+        feature: Some("experimental-neurospec"),
+    },
+    ToolDefinition {
+        name: "neurospec_plan_schema",
+        description: "导出 NSP 计划（plan / step / meta）的 JSON Schema，供外部 agent 和 CI 校验计划",
+        is_core: false,
+        feature: Some("experimental-neurospec"),
+    },
 ];
 
 /// 获取所有已注册的工具名称
@@ -122,6 +262,14 @@ pub fn get_tool_schema(name: &str) -> Option<serde_json::Map<String, serde_json:
             let schema = schema_for!(MemoryRequest);
             root_schema_to_json(schema)
         }
+        "change_intent" => {
+            let schema = schema_for!(ChangeIntentRequest);
+            root_schema_to_json(schema)
+        }
+        "file_history" => {
+            let schema = schema_for!(FileHistoryRequest);
+            root_schema_to_json(schema)
+        }
         "search" => {
             let schema = schema_for!(SearchRequest);
             root_schema_to_json(schema)
@@ -130,16 +278,108 @@ pub fn get_tool_schema(name: &str) -> Option<serde_json::Map<String, serde_json:
             let schema = schema_for!(HealthRequest);
             root_schema_to_json(schema)
         }
+        "symbols" => {
+            let schema = schema_for!(SymbolsRequest);
+            root_schema_to_json(schema)
+        }
+        "read_symbol" => {
+            let schema = schema_for!(ReadSymbolRequest);
+            root_schema_to_json(schema)
+        }
+        "store_stats" => {
+            let schema = schema_for!(StoreStatsRequest);
+            root_schema_to_json(schema)
+        }
+        "index_admin" => {
+            let schema = schema_for!(IndexAdminRequest);
+            root_schema_to_json(schema)
+        }
+        "projects" => {
+            let schema = schema_for!(ProjectsRequest);
+            root_schema_to_json(schema)
+        }
+        "perf_profile" => {
+            let schema = schema_for!(PerfProfileRequest);
+            root_schema_to_json(schema)
+        }
+        "interact_history" => {
+            let schema = schema_for!(InteractHistoryRequest);
+            root_schema_to_json(schema)
+        }
+        "notify" => {
+            let schema = schema_for!(NotifyRequest);
+            root_schema_to_json(schema)
+        }
         #[cfg(feature = "experimental-neurospec")]
         "neurospec_graph_impact_analysis" => {
             let schema = schema_for!(ImpactAnalysisArgs);
             root_schema_to_json(schema)
         }
         #[cfg(feature = "experimental-neurospec")]
+        "neurospec_impact_changeset" => {
+            let schema = schema_for!(ImpactChangesetArgs);
+            root_schema_to_json(schema)
+        }
+        #[cfg(feature = "experimental-neurospec")]
+        "neurospec_graph_callers" => {
+            let schema = schema_for!(GraphCallersArgs);
+            root_schema_to_json(schema)
+        }
+        #[cfg(feature = "experimental-neurospec")]
+        "neurospec_graph_cycles" => {
+            let schema = schema_for!(GraphCyclesArgs);
+            root_schema_to_json(schema)
+        }
+        #[cfg(feature = "experimental-neurospec")]
+        "neurospec_graph_export" => {
+            let schema = schema_for!(GraphExportArgs);
+            root_schema_to_json(schema)
+        }
+        #[cfg(feature = "experimental-neurospec")]
+        "neurospec_architecture_check" => {
+            let schema = schema_for!(ArchitectureCheckArgs);
+            root_schema_to_json(schema)
+        }
+        #[cfg(feature = "experimental-neurospec")]
         "neurospec_refactor_rename" => {
             let schema = schema_for!(RenameArgs);
             root_schema_to_json(schema)
         }
+        #[cfg(feature = "experimental-neurospec")]
+        "neurospec_plan_validate" => {
+            let schema = schema_for!(PlanValidateArgs);
+            root_schema_to_json(schema)
+        }
+        #[cfg(feature = "experimental-neurospec")]
+        "neurospec_plan_assess_risk" => {
+            let schema = schema_for!(PlanAssessRiskArgs);
+            root_schema_to_json(schema)
+        }
+        #[cfg(feature = "experimental-neurospec")]
+        "neurospec_plan_dry_run" => {
+            let schema = schema_for!(PlanDryRunArgs);
+            root_schema_to_json(schema)
+        }
+        #[cfg(feature = "experimental-neurospec")]
+        "neurospec_plan_execute" => {
+            let schema = schema_for!(PlanExecuteArgs);
+            root_schema_to_json(schema)
+        }
+        #[cfg(feature = "experimental-neurospec")]
+        "neurospec_plan_resume" => {
+            let schema = schema_for!(PlanResumeArgs);
+            root_schema_to_json(schema)
+        }
+        #[cfg(feature = "experimental-neurospec")]
+        "neurospec_plans" => {
+            let schema = schema_for!(PlansArgs);
+            root_schema_to_json(schema)
+        }
+        #[cfg(feature = "experimental-neurospec")]
+        "neurospec_plan_schema" => {
+            let schema = schema_for!(PlanSchemaArgs);
+            root_schema_to_json(schema)
+        }
         _ => None,
     }
 }
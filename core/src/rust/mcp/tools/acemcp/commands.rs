@@ -22,6 +22,14 @@ pub async fn debug_acemcp_search(
         query,
         mode: None,
         profile: None,
+        language: None,
+        languages: None,
+        path_glob: None,
+        symbol_kinds: None,
+        rank_explain: None,
+        fuzzy: None,
+        max_edit_distance: None,
+        dedupe: None,
     };
 
     let search_result = AcemcpTool::search_context(req).await;
@@ -79,6 +87,14 @@ pub async fn execute_acemcp_tool(
                 query,
                 mode: None,
                 profile: None,
+                language: None,
+                languages: None,
+                path_glob: None,
+                symbol_kinds: None,
+                rank_explain: None,
+                fuzzy: None,
+                max_edit_distance: None,
+                dedupe: None,
             };
 
             match AcemcpTool::search_context(req).await {
@@ -102,11 +118,82 @@ pub async fn execute_acemcp_tool(
     }
 }
 
+/// 获取统一符号存储统计（索引面板）
+#[tauri::command]
+pub async fn get_store_stats(
+    project_root: Option<String>,
+) -> Result<super::store_stats::StoreStatsResponse, String> {
+    tokio::task::spawn_blocking(move || {
+        super::store_stats::collect_store_stats(project_root.as_deref())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+/// 获取索引健康面板数据（不填 project_root 时返回所有已知项目）
+#[tauri::command]
+pub async fn get_index_dashboard(
+    project_root: Option<String>,
+) -> Result<super::dashboard::IndexDashboard, String> {
+    tokio::task::spawn_blocking(move || super::dashboard::collect_index_dashboard(project_root.as_deref()))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 强制重建项目索引（后台执行）
+#[tauri::command]
+pub async fn reindex_project(
+    project_root: String,
+) -> Result<super::index_admin::IndexAdminResponse, String> {
+    tokio::task::spawn_blocking(move || super::index_admin::reindex_project(&project_root))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 取消项目正在进行的索引
+#[tauri::command]
+pub async fn cancel_indexing(
+    project_root: String,
+) -> Result<super::index_admin::IndexAdminResponse, String> {
+    tokio::task::spawn_blocking(move || super::index_admin::cancel_project_indexing(&project_root))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 获取项目注册表（项目面板）
+#[tauri::command]
+pub async fn get_projects() -> Result<Vec<super::projects::ProjectSummary>, String> {
+    tokio::task::spawn_blocking(|| super::projects::collect_projects(None))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 修改项目设置（打开时自动索引、文件监听）
+#[tauri::command]
+pub async fn set_project_settings(
+    project_root: String,
+    auto_index: Option<bool>,
+    watch: Option<bool>,
+) -> Result<crate::config::ProjectSettings, String> {
+    tokio::task::spawn_blocking(move || {
+        super::projects::update_project_settings(std::path::Path::new(&project_root), auto_index, watch)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+/// 从项目注册表移除项目（索引与记忆数据保留）
+#[tauri::command]
+pub async fn remove_project(project_root: String) -> Result<bool, String> {
+    crate::config::remove_project_record(std::path::Path::new(&project_root)).map_err(|e| e.to_string())
+}
+
 /// 清除本地索引缓存
 #[tauri::command]
 pub async fn clear_acemcp_cache() -> Result<String, String> {
-    let home = dirs::home_dir().ok_or("无法获取 HOME 目录")?;
-    let cache_dir = home.join(".acemcp").join("local_index");
+    let cache_dir = crate::config::profile_local_index_dir();
     
     if cache_dir.exists() {
         std::fs::remove_dir_all(&cache_dir).map_err(|e| e.to_string())?;
@@ -0,0 +1,160 @@
+//! 索引健康面板数据
+//!
+//! 汇总每个项目的索引状态、健康评估、向量覆盖率和最近一次错误，
+//! 由 Tauri 命令 `get_index_dashboard` 与 daemon 的 `/index/dashboard` 路由共用
+
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use crate::mcp::tools::unified_store::{
+    assess_index_health, get_index_state, is_project_watched, is_search_initialized, known_projects,
+    EmbeddingStatus, IndexErrorSource, IndexHealth, IndexState,
+};
+use crate::neurospec::services::embedding::is_embedding_available;
+use super::local_engine::CodeVectorStore;
+
+/// 面板整体数据
+#[derive(Debug, Serialize)]
+pub struct IndexDashboard {
+    /// 生成时间（ISO 8601）
+    pub generated_at: String,
+    /// 搜索引擎（符号存储 + Tantivy）是否已初始化
+    pub search_initialized: bool,
+    /// 嵌入服务当前是否可用
+    pub embedding_available: bool,
+    pub projects: Vec<ProjectDashboard>,
+}
+
+/// 单个项目的面板数据
+#[derive(Debug, Serialize)]
+pub struct ProjectDashboard {
+    pub path: String,
+    pub name: String,
+    /// 目录是否仍然存在
+    pub exists: bool,
+    /// 统一状态机
+    pub state: IndexState,
+    pub health: HealthSummary,
+    pub indexing: bool,
+    /// 索引已超过有效期，需要重建
+    pub expired: bool,
+    pub file_count: usize,
+    /// 上次索引完成时间（ISO 8601）
+    pub last_indexed_at: Option<String>,
+    pub watching: bool,
+    pub embedding: EmbeddingCoverage,
+    pub last_error: Option<LastError>,
+}
+
+/// 健康评估结果
+#[derive(Debug, Serialize)]
+pub struct HealthSummary {
+    /// healthy / degraded / unhealthy
+    pub status: &'static str,
+    pub reason: Option<String>,
+}
+
+/// 向量覆盖率
+#[derive(Debug, Default, Serialize)]
+pub struct EmbeddingCoverage {
+    /// 索引状态中记录的嵌入状态（索引未就绪时为 not_available）
+    pub status: EmbeddingStatus,
+    pub total_files: usize,
+    pub files_with_vectors: usize,
+    pub total_chunks: usize,
+    pub chunks_with_vectors: usize,
+    /// 已有向量的代码块比例（0.0 ~ 1.0，没有代码块时为 0）
+    pub coverage: f64,
+}
+
+/// 最近一次错误
+#[derive(Debug, Serialize)]
+pub struct LastError {
+    pub source: IndexErrorSource,
+    pub message: String,
+    /// 发生时间（ISO 8601）
+    pub at: Option<String>,
+}
+
+/// 收集面板数据（不填 project_root 时返回所有有索引状态记录的项目）
+pub fn collect_index_dashboard(project_root: Option<&str>) -> IndexDashboard {
+    let roots = match project_root {
+        Some(root) => vec![PathBuf::from(root)],
+        None => known_projects(),
+    };
+
+    IndexDashboard {
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        search_initialized: is_search_initialized(),
+        embedding_available: is_embedding_available(),
+        projects: roots.iter().map(PathBuf::as_path).map(project_dashboard).collect(),
+    }
+}
+
+fn project_dashboard(root: &Path) -> ProjectDashboard {
+    let state = get_index_state(root).unwrap_or_default();
+
+    let health = match assess_index_health(root) {
+        IndexHealth::Healthy => HealthSummary { status: "healthy", reason: None },
+        IndexHealth::Degraded { reason } => HealthSummary { status: "degraded", reason: Some(reason) },
+        IndexHealth::Unhealthy { reason } => HealthSummary { status: "unhealthy", reason: Some(reason) },
+    };
+
+    let mut embedding = embedding_coverage(root);
+    if let IndexState::Ready { embedding_status, .. } = &state.state {
+        embedding.status = embedding_status.clone();
+    }
+
+    ProjectDashboard {
+        path: root.to_string_lossy().replace('\\', "/"),
+        name: root
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| root.to_string_lossy().to_string()),
+        exists: root.is_dir(),
+        health,
+        indexing: state.is_indexing(),
+        expired: state.is_ready() && state.is_expired(),
+        file_count: state.get_file_count(),
+        last_indexed_at: state.last_indexed_ts.and_then(format_timestamp),
+        watching: is_project_watched(root),
+        embedding,
+        last_error: state.last_error.as_ref().map(|error| LastError {
+            source: error.source,
+            message: error.message.clone(),
+            at: format_timestamp(error.at),
+        }),
+        state: state.state,
+    }
+}
+
+/// 从项目的代码向量库读取覆盖率（库不存在时不创建，返回全 0）
+fn embedding_coverage(root: &Path) -> EmbeddingCoverage {
+    if !root.join(".neurospec").join("code_vectors.db").exists() {
+        return EmbeddingCoverage::default();
+    }
+
+    let stats = match CodeVectorStore::new(&root.to_path_buf()).and_then(|store| store.stats()) {
+        Ok(stats) => stats,
+        Err(e) => {
+            crate::log_debug!("Vector store stats unavailable for {}: {}", root.display(), e);
+            return EmbeddingCoverage::default();
+        }
+    };
+
+    EmbeddingCoverage {
+        status: EmbeddingStatus::default(),
+        coverage: if stats.total_chunks == 0 {
+            0.0
+        } else {
+            stats.chunks_with_vectors as f64 / stats.total_chunks as f64
+        },
+        total_files: stats.total_files,
+        files_with_vectors: stats.files_with_vectors,
+        total_chunks: stats.total_chunks,
+        chunks_with_vectors: stats.chunks_with_vectors,
+    }
+}
+
+fn format_timestamp(ts: u64) -> Option<String> {
+    chrono::DateTime::<chrono::Utc>::from_timestamp(ts as i64, 0).map(|dt| dt.to_rfc3339())
+}
@@ -0,0 +1,65 @@
+//! 未提交修改的文件（scope.kind = dirty）
+//!
+//! 通过 `git status` 列出工作区中已修改、已暂存和未跟踪的文件，
+//! 搜索时把它们作为路径限定下推到索引和 ripgrep 中
+
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result};
+
+/// 列出项目中有未提交修改的文件（相对项目根目录，已排序）
+///
+/// 包含已修改、已暂存、重命名后的新路径和未跟踪文件；已删除的文件不再存在，不返回。
+/// 项目根目录可以是仓库的子目录，此时只返回该目录下的文件
+pub fn dirty_files(project_root: &Path) -> Result<Vec<String>> {
+    let prefix = git(project_root, &["rev-parse", "--show-prefix"])
+        .context("Project is not inside a git repository")?;
+    let prefix = prefix.trim();
+
+    let status = git(project_root, &["status", "--porcelain=v1", "-z", "--untracked-files=all", "--", "."])?;
+
+    let mut files = Vec::new();
+    let mut entries = status.split('\0');
+    while let Some(entry) = entries.next() {
+        if entry.len() < 4 {
+            continue;
+        }
+        let (code, path) = entry.split_at(3);
+        // 重命名 / 复制的条目后面跟着原路径，跳过
+        if code.starts_with('R') || code.starts_with('C') {
+            entries.next();
+        }
+
+        // git status 的路径相对仓库根目录
+        let Some(rel_path) = path.strip_prefix(prefix) else {
+            continue;
+        };
+        if project_root.join(rel_path).is_file() {
+            files.push(rel_path.to_string());
+        }
+    }
+
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// 在项目目录中执行 git 命令并返回标准输出
+fn git(project_root: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(project_root)
+        .output()
+        .context("Failed to run git. Is 'git' installed?")?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -7,7 +7,7 @@ use rmcp::model::{CallToolResult, Content};
 use crate::mcp::utils::errors::McpToolError;
 use crate::mcp::tools::unified_store::{
     is_search_initialized, assess_index_health, IndexHealth,
-    get_index_state, is_project_indexing,
+    get_index_state, is_project_indexing, verify_project, ConsistencyReport,
 };
 use super::local_engine::ripgrep::RipgrepSearcher;
 
@@ -16,6 +16,12 @@ use super::local_engine::ripgrep::RipgrepSearcher;
 pub struct HealthRequest {
     /// 项目根目录（可选，默认当前目录）
     pub project_root: Option<String>,
+    /// 是否交叉校验符号存储、Tantivy 文档与索引状态（较慢）
+    #[serde(default)]
+    pub verify: Option<bool>,
+    /// 校验发现漂移时是否自动修复（隐含 verify）
+    #[serde(default)]
+    pub repair: Option<bool>,
 }
 
 /// 健康检查响应
@@ -35,6 +41,12 @@ pub struct HealthResponse {
     pub is_indexing: bool,
     /// 可用引擎列表
     pub engines: EngineStatus,
+    /// 一致性检查结果（仅在 verify/repair 时返回）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consistency: Option<ConsistencyReport>,
+    /// 一致性检查失败原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consistency_error: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -59,6 +71,19 @@ pub async fn check_health(request: HealthRequest) -> Result<CallToolResult, McpT
         )));
     }
     
+    // 一致性检查需在读取状态前完成，修复会更新状态
+    let repair = request.repair.unwrap_or(false);
+    let (consistency, consistency_error) = if repair || request.verify.unwrap_or(false) {
+        let root = project_root.clone();
+        match tokio::task::spawn_blocking(move || verify_project(&root, repair)).await {
+            Ok(Ok(report)) => (Some(report), None),
+            Ok(Err(e)) => (None, Some(e.to_string())),
+            Err(e) => (None, Some(format!("Consistency check task failed: {}", e))),
+        }
+    } else {
+        (None, None)
+    };
+
     // 收集健康信息
     let index_state_info = get_index_state(&project_root);
     let health = assess_index_health(&project_root);
@@ -102,6 +127,8 @@ pub async fn check_health(request: HealthRequest) -> Result<CallToolResult, McpT
             ripgrep: RipgrepSearcher::is_available(),
             ctags: super::local_engine::ctags::CtagsIndexer::is_available(),
         },
+        consistency,
+        consistency_error,
     };
     
     let json = serde_json::to_string_pretty(&response)?;
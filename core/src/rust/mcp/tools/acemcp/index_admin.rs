@@ -0,0 +1,99 @@
+//! 索引管理：强制重建与取消索引
+//!
+//! 由 MCP 工具 `index_admin`、Tauri 命令 `reindex_project` / `cancel_indexing`
+//! 与 daemon 的 `/index/reindex`、`/index/cancel` 路由共用
+
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use rmcp::model::{CallToolResult, Content};
+use crate::mcp::utils::errors::McpToolError;
+use crate::mcp::tools::unified_store::{cancel_indexing, get_index_state, IndexState};
+use super::mcp::AcemcpTool;
+
+/// index_admin 工具请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct IndexAdminRequest {
+    /// 项目根目录
+    #[schemars(description = "Project root directory")]
+    pub project_root: String,
+    /// 操作类型
+    #[schemars(description = "reindex: wipe and rebuild the project index in the background; cancel: stop the running indexing")]
+    pub action: IndexAdminAction,
+}
+
+/// 索引管理操作
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexAdminAction {
+    /// 丢弃现有索引并在后台全量重建
+    Reindex,
+    /// 取消正在进行的索引
+    Cancel,
+}
+
+/// 索引管理操作结果
+#[derive(Debug, Serialize)]
+pub struct IndexAdminResponse {
+    pub project_root: String,
+    pub action: IndexAdminAction,
+    /// 操作是否已生效（重建已开始 / 已发出取消信号）
+    pub accepted: bool,
+    pub message: String,
+    /// 操作后的索引状态
+    pub state: IndexState,
+}
+
+/// 强制重建项目索引（后台执行，立即返回）
+pub fn reindex_project(project_root: &str) -> Result<IndexAdminResponse, String> {
+    let root = existing_root(project_root)?;
+    let (accepted, message) = match AcemcpTool::start_reindex(&root) {
+        Ok(()) => (true, "Reindex started".to_string()),
+        Err(e) => (false, e),
+    };
+    Ok(response(&root, IndexAdminAction::Reindex, accepted, message))
+}
+
+/// 取消项目正在进行的索引
+///
+/// 索引线程在下一批文件前退出，状态回到取消前的 Ready 或 NotIndexed
+pub fn cancel_project_indexing(project_root: &str) -> Result<IndexAdminResponse, String> {
+    let root = existing_root(project_root)?;
+    let (accepted, message) = if cancel_indexing(&root) {
+        (true, "Cancellation requested".to_string())
+    } else {
+        (false, "Project is not being indexed".to_string())
+    };
+    Ok(response(&root, IndexAdminAction::Cancel, accepted, message))
+}
+
+/// 执行索引管理操作
+pub async fn index_admin(request: IndexAdminRequest) -> Result<CallToolResult, McpToolError> {
+    let result = match request.action {
+        IndexAdminAction::Reindex => reindex_project(&request.project_root),
+        IndexAdminAction::Cancel => cancel_project_indexing(&request.project_root),
+    };
+    let response = result.map_err(McpToolError::InvalidParams)?;
+
+    let json = serde_json::to_string_pretty(&response)?;
+
+    Ok(crate::mcp::create_success_result(vec![Content::text(json)]))
+}
+
+fn existing_root(project_root: &str) -> Result<PathBuf, String> {
+    let root = PathBuf::from(project_root);
+    if !root.is_dir() {
+        return Err(format!("Project root does not exist: {}", project_root));
+    }
+    Ok(root)
+}
+
+fn response(root: &PathBuf, action: IndexAdminAction, accepted: bool, message: String) -> IndexAdminResponse {
+    IndexAdminResponse {
+        project_root: root.to_string_lossy().replace('\\', "/"),
+        action,
+        accepted,
+        message,
+        state: get_index_state(root).map(|s| s.state).unwrap_or_default(),
+    }
+}
@@ -0,0 +1,209 @@
+//! 搜索结果去重与片段合并
+//!
+//! 同一函数内的多个命中合并为一个结果：保留最高分命中的上下文，记录全部匹配行号，
+//! 按行号顺序拼接未被覆盖的片段。函数范围来自统一符号存储；无法确定所在函数时，
+//! 同一文件中相距不超过 [`NEARBY_LINES`] 行的命中视为同一处。
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::types::{SearchResult, SnippetContext};
+use crate::mcp::tools::unified_store::{with_global_store, SymbolFilter, SymbolKind};
+
+/// 无法确定所在函数时，合并相距不超过该行数的命中
+pub const NEARBY_LINES: usize = 10;
+
+/// 合并片段之间的分隔行
+const SNIPPET_SEPARATOR: &str = "  ⋮\n";
+
+/// 函数 / 类的行范围
+#[derive(Debug, Clone)]
+struct Enclosing {
+    name: String,
+    kind: SymbolKind,
+    start: usize,
+    end: usize,
+}
+
+/// 分组依据
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Scope {
+    /// 所在的（最内层）函数或类
+    Symbol { start: usize, end: usize },
+    /// 未知所在函数，以首个命中的行号为锚点
+    Nearby { anchor: usize },
+}
+
+/// 合并同一函数内的命中，结果保持各组首个命中的原始顺序
+pub fn merge_results(results: Vec<SearchResult>, project_root: &Path) -> Vec<SearchResult> {
+    let mut per_file: HashMap<&str, usize> = HashMap::new();
+    for result in &results {
+        *per_file.entry(result.path.as_str()).or_insert(0) += 1;
+    }
+    let repeated: Vec<String> = per_file
+        .into_iter()
+        .filter(|&(_, count)| count > 1)
+        .map(|(path, _)| path.to_string())
+        .collect();
+    if repeated.is_empty() {
+        return results;
+    }
+
+    let ranges = enclosing_ranges(project_root, &repeated);
+
+    let mut groups: Vec<(String, Scope, Vec<SearchResult>)> = Vec::new();
+    for result in results {
+        let enclosing = ranges
+            .get(&result.path)
+            .and_then(|ranges| innermost(ranges, result.line_number));
+        let scope = match enclosing {
+            Some(symbol) => Scope::Symbol { start: symbol.start, end: symbol.end },
+            None => Scope::Nearby { anchor: result.line_number },
+        };
+
+        let existing = groups.iter_mut().find(|(path, group_scope, _)| {
+            *path == result.path
+                && match (group_scope, &scope) {
+                    (Scope::Nearby { anchor }, Scope::Nearby { .. }) => {
+                        anchor.abs_diff(result.line_number) <= NEARBY_LINES
+                    }
+                    (a, b) => a == b,
+                }
+        });
+        match existing {
+            Some((_, _, hits)) => hits.push(result),
+            None => groups.push((result.path.clone(), scope, vec![result])),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(path, scope, hits)| {
+            let enclosing = match scope {
+                Scope::Symbol { start, end } => ranges
+                    .get(&path)
+                    .and_then(|ranges| ranges.iter().find(|r| r.start == start && r.end == end)),
+                Scope::Nearby { .. } => None,
+            };
+            merge_group(hits, enclosing)
+        })
+        .collect()
+}
+
+/// 把一组命中合并为一个结果
+fn merge_group(mut hits: Vec<SearchResult>, enclosing: Option<&Enclosing>) -> SearchResult {
+    if hits.len() == 1 {
+        return hits.remove(0);
+    }
+
+    let best = hits
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.score.partial_cmp(&b.score).unwrap_or(std::cmp::Ordering::Equal))
+        .map_or(0, |(i, _)| i);
+    let mut merged = hits.swap_remove(best);
+
+    let mut lines: Vec<usize> = std::iter::once(merged.line_number)
+        .chain(merged.matched_lines.iter().copied())
+        .collect();
+    let mut pieces: Vec<(usize, String)> = vec![(merged.line_number, std::mem::take(&mut merged.snippet))];
+
+    for hit in hits {
+        lines.push(hit.line_number);
+        lines.extend(hit.matched_lines.iter().copied());
+        pieces.push((hit.line_number, hit.snippet));
+
+        if let Some(other) = hit.match_info {
+            match merged.match_info.as_mut() {
+                Some(info) => {
+                    for term in other.matched_terms {
+                        if !info.matched_terms.contains(&term) {
+                            info.matched_terms.push(term);
+                        }
+                    }
+                }
+                None => merged.match_info = Some(other),
+            }
+        }
+    }
+
+    lines.sort_unstable();
+    lines.dedup();
+    merged.matched_lines = lines;
+
+    // 按行号顺序拼接，已被其他片段包含的片段跳过
+    pieces.sort_by_key(|(line, _)| *line);
+    let mut snippet = String::new();
+    for (_, piece) in pieces {
+        let trimmed = piece.trim_end();
+        if trimmed.is_empty() || snippet.contains(trimmed) {
+            continue;
+        }
+        if !snippet.is_empty() {
+            snippet.push_str(SNIPPET_SEPARATOR);
+        }
+        snippet.push_str(trimmed);
+        snippet.push('\n');
+    }
+    merged.snippet = snippet;
+
+    if let Some(symbol) = enclosing {
+        let context = merged.context.get_or_insert_with(SnippetContext::default);
+        if context.parent_symbol.is_none() {
+            context.parent_symbol = Some(symbol.name.clone());
+        }
+        if context.symbol_kind.is_none() {
+            context.symbol_kind = Some(format!("{:?}", symbol.kind).to_lowercase());
+        }
+    }
+
+    merged
+}
+
+/// 包含该行的最内层范围
+fn innermost(ranges: &[Enclosing], line: usize) -> Option<&Enclosing> {
+    ranges
+        .iter()
+        .filter(|r| r.start <= line && line <= r.end)
+        .min_by_key(|r| r.end - r.start)
+}
+
+/// 从统一符号存储读取文件中函数和类的行范围（存储不可用时为空）
+fn enclosing_ranges(project_root: &Path, paths: &[String]) -> HashMap<String, Vec<Enclosing>> {
+    let lookup = with_global_store(|store| {
+        let mut ranges = HashMap::new();
+        for path in paths {
+            // 存储以相对路径为键；ripgrep 结果可能带 `./` 前缀或为绝对路径
+            let rel_path = Path::new(path)
+                .strip_prefix(project_root)
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|_| path.trim_start_matches("./").to_string())
+                .replace('\\', "/");
+            let filter = SymbolFilter {
+                path_prefix: Some(rel_path.clone()),
+                kinds: vec![SymbolKind::Function, SymbolKind::Class],
+                ..Default::default()
+            };
+            let symbols: Vec<Enclosing> = store
+                .query_symbols(project_root, &filter)?
+                .into_iter()
+                .filter(|s| Path::new(&s.path).ends_with(&rel_path))
+                .filter_map(|s| {
+                    Some(Enclosing {
+                        start: s.start_line? as usize,
+                        end: s.end_line? as usize,
+                        name: s.name,
+                        kind: s.kind,
+                    })
+                })
+                .collect();
+            ranges.insert(path.clone(), symbols);
+        }
+        Ok(ranges)
+    });
+
+    lookup.unwrap_or_else(|e| {
+        crate::log_debug!("Symbol ranges unavailable for result merging: {}", e);
+        HashMap::new()
+    })
+}
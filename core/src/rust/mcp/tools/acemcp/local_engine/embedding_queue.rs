@@ -0,0 +1,334 @@
+//! 后台向量嵌入队列
+//!
+//! 索引完成后、以及 daemon 的定期补算任务把项目加入队列，文件监听批次只加入变化的文件，
+//! 由单个后台任务依次处理：先为新增或修改的文件按函数 / 类切分代码块（在阻塞线程池中遍历和读取文件），再按小批量为未嵌入的代码块和代码修改记忆
+//! 计算向量并限速，每批完成后在项目索引状态中更新 `EmbeddingStatus` 进度。
+//! 待嵌入条目持久化在向量存储 / 记忆库中，进程退出或嵌入服务停用后，下次入队时从剩余条目继续。
+//! 查询时只嵌入查询文本本身，候选的向量都来自这里预先计算的结果
+
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::Result;
+use lazy_static::lazy_static;
+
+use super::file_limits::FileLimits;
+use super::indexer::LocalIndexer;
+use super::types::LocalEngineConfig;
+use super::vector_store::CodeVectorStore;
+use crate::mcp::tools::memory::{ChangeTracker, CodeChangeMemory};
+use crate::mcp::tools::unified_store::{
+    get_global_search_config, get_index_state, known_projects, update_embedding_status, EmbeddingStatus,
+    IndexState,
+};
+use crate::neurospec::services::embedding::{get_global_embedding_service, is_embedding_available};
+
+/// 每批嵌入的代码块 / 记忆数
+const BATCH_SIZE: usize = 32;
+
+/// 两批之间的间隔，避免触发嵌入服务的速率限制
+const BATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// daemon 启动后首次补算前的等待时间（避开启动时的索引高峰）
+const BACKFILL_INITIAL_DELAY: Duration = Duration::from_secs(30);
+
+/// 定期补算的间隔
+const BACKFILL_INTERVAL: Duration = Duration::from_secs(300);
+
+/// 需要重新切分代码块的范围
+#[derive(Clone)]
+enum ChunkScope {
+    /// 遍历整个项目（索引完成或定期补算）
+    Project,
+    /// 只处理文件监听批次中变化的文件（绝对路径）
+    Files(Vec<PathBuf>),
+}
+
+impl ChunkScope {
+    /// 合并同一项目的两次入队，任一次需要遍历整个项目时结果也遍历整个项目
+    fn merge(&mut self, other: ChunkScope) {
+        match other {
+            ChunkScope::Project => *self = ChunkScope::Project,
+            ChunkScope::Files(more) => {
+                if let ChunkScope::Files(files) = self {
+                    for path in more {
+                        if !files.contains(&path) {
+                            files.push(path);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 等待嵌入的项目
+struct EmbeddingJob {
+    project_root: PathBuf,
+    exclude_patterns: Vec<String>,
+    file_limits: FileLimits,
+    scope: ChunkScope,
+}
+
+lazy_static! {
+    static ref EMBEDDING_QUEUE: Mutex<VecDeque<EmbeddingJob>> = Mutex::new(VecDeque::new());
+}
+
+/// 后台任务是否在运行
+static WORKER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// 定期补算任务是否已启动
+static BACKFILL_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// 把项目加入嵌入队列，处理时遍历整个项目（已在队列中的项目不重复加入）
+///
+/// 可以在后台索引线程中调用，处理任务运行在共享的异步运行时上
+pub fn enqueue_embedding(project_root: &Path, config: &LocalEngineConfig) {
+    enqueue(project_root, config, ChunkScope::Project);
+}
+
+/// 把文件监听批次中变化的文件（绝对路径，含已删除的文件）加入嵌入队列
+pub fn enqueue_changed_files(project_root: &Path, config: &LocalEngineConfig, paths: Vec<PathBuf>) {
+    enqueue(project_root, config, ChunkScope::Files(paths));
+}
+
+fn enqueue(project_root: &Path, config: &LocalEngineConfig, scope: ChunkScope) {
+    if !is_embedding_available() {
+        return;
+    }
+
+    if let Ok(mut queue) = EMBEDDING_QUEUE.lock() {
+        match queue.iter_mut().find(|job| job.project_root == project_root) {
+            Some(job) => job.scope.merge(scope),
+            None => queue.push_back(EmbeddingJob {
+                project_root: project_root.to_path_buf(),
+                exclude_patterns: config.exclude_patterns.clone(),
+                file_limits: config.file_limits.clone(),
+                scope,
+            }),
+        }
+    }
+
+    if !WORKER_RUNNING.swap(true, Ordering::AcqRel) {
+        tauri::async_runtime::spawn(run_worker());
+    }
+}
+
+/// 启动定期补算任务（daemon 启动时调用，重复调用无效）
+///
+/// 索引后的入队只覆盖当次索引；嵌入服务后来才配置、补算中途被停用，或有新记录的记忆时，
+/// 由这里每隔 [`BACKFILL_INTERVAL`] 把所有就绪的项目重新入队
+pub fn start_backfill_worker() {
+    if BACKFILL_STARTED.swap(true, Ordering::AcqRel) {
+        return;
+    }
+
+    tauri::async_runtime::spawn(async {
+        tokio::time::sleep(BACKFILL_INITIAL_DELAY).await;
+        while !crate::utils::is_shutting_down() {
+            if is_embedding_available() {
+                enqueue_ready_projects();
+            }
+            tokio::time::sleep(BACKFILL_INTERVAL).await;
+        }
+    });
+}
+
+/// 把索引就绪的已知项目加入嵌入队列
+fn enqueue_ready_projects() {
+    let Ok(config) = get_global_search_config() else {
+        return;
+    };
+
+    for project_root in known_projects() {
+        let ready = get_index_state(&project_root)
+            .is_some_and(|state| matches!(state.state, IndexState::Ready { .. }));
+        if ready && project_root.exists() {
+            enqueue_embedding(&project_root, &config);
+        }
+    }
+}
+
+/// 依次处理队列中的项目，队列为空时退出
+async fn run_worker() {
+    loop {
+        let job = EMBEDDING_QUEUE.lock().ok().and_then(|mut queue| queue.pop_front());
+        let Some(job) = job else {
+            WORKER_RUNNING.store(false, Ordering::Release);
+            // 退出前有新项目入队且没有其他任务接手时继续处理
+            let has_pending = EMBEDDING_QUEUE.lock().map(|queue| !queue.is_empty()).unwrap_or(false);
+            if has_pending && !WORKER_RUNNING.swap(true, Ordering::AcqRel) {
+                continue;
+            }
+            return;
+        };
+
+        if let Err(e) = embed_project(&job).await {
+            crate::log_important!(warn, "Failed to embed {}: {}", job.project_root.display(), e);
+            update_embedding_status(&job.project_root, EmbeddingStatus::Failed {
+                reason: e.to_string(),
+            });
+        }
+    }
+}
+
+/// 为项目的待嵌入代码块和记忆分批计算向量
+async fn embed_project(job: &EmbeddingJob) -> Result<()> {
+    let (store, queued) = queue_chunks(job).await?;
+
+    let chunks = store.get_pending_chunks()?;
+    let (tracker, memories) = pending_memories(&job.project_root);
+
+    let total = chunks.len() + memories.len();
+    if total == 0 {
+        return Ok(());
+    }
+    crate::log_important!(
+        info,
+        "Embedding {} chunks and {} memories for {} ({} chunks newly queued)",
+        chunks.len(),
+        memories.len(),
+        job.project_root.display(),
+        queued
+    );
+
+    let mut processed = 0;
+    update_embedding_status(&job.project_root, EmbeddingStatus::InProgress { processed, total });
+
+    let mut deleted: HashSet<String> = HashSet::new();
+    for batch in chunks.chunks(BATCH_SIZE) {
+        let mut entries = Vec::with_capacity(batch.len());
+        for entry in batch {
+            if deleted.contains(&entry.file_path) {
+                continue;
+            }
+            // 文件已删除，丢弃它的全部代码块
+            if !job.project_root.join(&entry.file_path).exists() {
+                store.delete(&entry.file_path)?;
+                deleted.insert(entry.file_path.clone());
+                continue;
+            }
+            entries.push(entry);
+        }
+
+        if !entries.is_empty() {
+            let texts: Vec<String> = entries
+                .iter()
+                .map(|e| format!("{} {}", e.summary, e.symbols.join(" ")))
+                .collect();
+            // 嵌入服务被停用或进程退出：剩余条目保留，下次入队时继续
+            let Some((embeddings, _)) = embed_texts(&texts).await? else {
+                crate::log_important!(info, "Embedding paused for {}", job.project_root.display());
+                return report_available(&job.project_root, &store);
+            };
+
+            for (entry, embedding) in entries.iter().zip(embeddings) {
+                if !embedding.is_empty() {
+                    store.update_embedding(entry, &embedding)?;
+                }
+            }
+            tokio::time::sleep(BATCH_INTERVAL).await;
+        }
+
+        processed += batch.len();
+        update_embedding_status(&job.project_root, EmbeddingStatus::InProgress { processed, total });
+    }
+
+    if let Some(tracker) = &tracker {
+        for batch in memories.chunks(BATCH_SIZE) {
+            let texts: Vec<String> = batch.iter().map(ChangeTracker::embedding_text).collect();
+            let Some((embeddings, model)) = embed_texts(&texts).await? else {
+                crate::log_important!(info, "Embedding paused for {}", job.project_root.display());
+                return report_available(&job.project_root, &store);
+            };
+
+            for (memory, embedding) in batch.iter().zip(embeddings) {
+                if !embedding.is_empty() {
+                    tracker.save_change_embedding(&memory.id, &embedding, &model)?;
+                }
+            }
+
+            processed += batch.len();
+            update_embedding_status(&job.project_root, EmbeddingStatus::InProgress { processed, total });
+            tokio::time::sleep(BATCH_INTERVAL).await;
+        }
+    }
+
+    report_available(&job.project_root, &store)
+}
+
+/// 在阻塞线程池中遍历并读取文件、切分代码块，返回向量存储和新增的待嵌入条目数
+async fn queue_chunks(job: &EmbeddingJob) -> Result<(CodeVectorStore, usize)> {
+    let project_root = job.project_root.clone();
+    let exclude_patterns = job.exclude_patterns.clone();
+    let file_limits = job.file_limits.clone();
+    let scope = job.scope.clone();
+
+    tokio::task::spawn_blocking(move || -> Result<(CodeVectorStore, usize)> {
+        let store = CodeVectorStore::new(&project_root)?;
+        let queued = match &scope {
+            ChunkScope::Project => {
+                LocalIndexer::queue_vector_entries(&project_root, &exclude_patterns, &file_limits, &store)?
+            }
+            ChunkScope::Files(paths) => {
+                LocalIndexer::queue_vector_files(&project_root, paths, &exclude_patterns, &file_limits, &store)?
+            }
+        };
+        Ok((store, queued))
+    })
+    .await?
+}
+
+/// 补算结束（或暂停）时把项目的嵌入状态更新为可用
+fn report_available(project_root: &Path, store: &CodeVectorStore) -> Result<()> {
+    let stats = store.stats()?;
+    update_embedding_status(project_root, EmbeddingStatus::Available {
+        files_with_vectors: stats.files_with_vectors,
+    });
+    crate::log_important!(
+        info,
+        "Vector store updated: {}/{} chunks in {} files have embeddings",
+        stats.chunks_with_vectors,
+        stats.total_chunks,
+        stats.total_files
+    );
+    Ok(())
+}
+
+/// 项目中还没有向量的代码修改记忆（项目没有记忆库时为空，不会创建记忆库）
+fn pending_memories(project_root: &Path) -> (Option<ChangeTracker>, Vec<CodeChangeMemory>) {
+    if !crate::config::project_memory_dir(project_root).exists() {
+        return (None, Vec::new());
+    }
+
+    let tracker = match ChangeTracker::new(&project_root.to_string_lossy()) {
+        Ok(tracker) => tracker,
+        Err(e) => {
+            crate::log_debug!("Memory store unavailable for embedding: {}", e);
+            return (None, Vec::new());
+        }
+    };
+    let memories = tracker.changes_without_embedding().unwrap_or_default();
+    (Some(tracker), memories)
+}
+
+/// 用全局嵌入服务计算一批文本的向量，同时返回模型名
+///
+/// 嵌入服务不可用或进程正在退出时返回 None
+async fn embed_texts(texts: &[String]) -> Result<Option<(Vec<Vec<f32>>, String)>> {
+    if crate::utils::is_shutting_down() {
+        return Ok(None);
+    }
+    let Some(lock) = get_global_embedding_service().filter(|_| is_embedding_available()) else {
+        return Ok(None);
+    };
+
+    let guard = lock.read().await;
+    match guard.as_ref() {
+        Some(service) => Ok(Some((service.embed_batch(texts).await?, service.model().to_string()))),
+        None => Ok(None),
+    }
+}
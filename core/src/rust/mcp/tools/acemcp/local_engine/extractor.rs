@@ -1,3 +1,4 @@
+use std::ops::Range;
 use std::path::Path;
 use anyhow::Result;
 use tree_sitter::{Parser, Node};
@@ -21,18 +22,98 @@ pub fn detect_language(path: &Path) -> Language {
 }
 
 pub fn extract_symbols(path: &Path, content: &str) -> Result<Vec<Symbol>> {
+    let (definitions, _) = parse_definitions(path, content)?;
+    Ok(definitions.into_iter().map(|d| d.symbol).collect())
+}
+
+/// 单个代码块最多保留的源码字符数（超出部分截断，避免超过嵌入模型的输入上限）
+const MAX_CHUNK_CHARS: usize = 1500;
+
+/// 单个文件最多切分的代码块数
+const MAX_CHUNKS_PER_FILE: usize = 200;
+
+/// 按 AST 边界切分出的代码块（函数、方法、类、结构体、接口）
+#[derive(Debug, Clone)]
+pub struct CodeChunk {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// 在整个文件中的字节区间 [start_byte, end_byte)
+    pub start_byte: usize,
+    pub end_byte: usize,
+    /// 行范围（1-based，含两端）
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+impl CodeChunk {
+    /// 块的源码，超过 [`MAX_CHUNK_CHARS`] 时在字符边界处截断
+    pub fn text<'a>(&self, content: &'a str) -> &'a str {
+        let code = content.get(self.start_byte..self.end_byte).unwrap_or("");
+        match code.char_indices().nth(MAX_CHUNK_CHARS) {
+            Some((end, _)) => &code[..end],
+            None => code,
+        }
+    }
+}
+
+/// 按函数 / 类级别切分文件，用于逐块计算嵌入向量
+///
+/// Rust 的 `impl` 块不单独成块（其中的方法各自成块）；嵌套定义（类与其方法）各自成块
+pub fn extract_chunks(path: &Path, content: &str) -> Result<Vec<CodeChunk>> {
+    let (definitions, offset) = parse_definitions(path, content)?;
+
+    let chunks = definitions
+        .into_iter()
+        .filter(|d| !matches!(d.symbol.kind, SymbolKind::Other))
+        .take(MAX_CHUNKS_PER_FILE)
+        .map(|d| {
+            let start_byte = offset + d.range.start;
+            let end_byte = offset + d.range.end;
+            CodeChunk {
+                name: d.symbol.name,
+                kind: d.symbol.kind,
+                start_byte,
+                end_byte,
+                start_line: line_at(content, start_byte),
+                end_line: line_at(content, end_byte.saturating_sub(1).max(start_byte)),
+            }
+        })
+        .collect();
+    Ok(chunks)
+}
+
+/// 字节偏移所在的行号（1-based）
+fn line_at(content: &str, byte: usize) -> usize {
+    content.as_bytes()[..byte.min(content.len())]
+        .iter()
+        .filter(|&&b| b == b'\n')
+        .count()
+        + 1
+}
+
+/// 解析出的定义及其字节区间（相对于解析的内容）
+struct Definition {
+    symbol: Symbol,
+    range: Range<usize>,
+}
+
+/// 解析文件中的定义，同时返回被解析内容在文件中的起始偏移（Vue/Svelte 为 script 部分）
+fn parse_definitions(path: &Path, content: &str) -> Result<(Vec<Definition>, usize)> {
     let lang = detect_language(path);
     if let Language::Unknown = lang {
-        return Ok(Vec::new());
+        return Ok((Vec::new(), 0));
     }
 
     // 对于 Vue/Svelte 文件，提取 script 部分
-    let effective_content = if path.extension().and_then(|s| s.to_str()) == Some("vue")
+    let (offset, effective_content) = if path.extension().and_then(|s| s.to_str()) == Some("vue")
         || path.extension().and_then(|s| s.to_str()) == Some("svelte")
     {
-        extract_script_content(content)
+        match script_range(content) {
+            Some(range) => (range.start, &content[range]),
+            None => (0, content),
+        }
     } else {
-        content.to_string()
+        (0, content)
     };
 
     let mut parser = Parser::new();
@@ -45,46 +126,38 @@ pub fn extract_symbols(path: &Path, content: &str) -> Result<Vec<Symbol>> {
             parser.set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())?
         }
         Language::Python => parser.set_language(&tree_sitter_python::LANGUAGE.into())?,
-        _ => return Ok(Vec::new()),
+        _ => return Ok((Vec::new(), 0)),
     };
 
-    let tree = match parser.parse(&effective_content, None) {
+    let tree = match parser.parse(effective_content, None) {
         Some(t) => t,
-        None => return Ok(Vec::new()),
+        None => return Ok((Vec::new(), 0)),
     };
 
-    let mut symbols = Vec::new();
-    walk_tree(&tree.root_node(), &effective_content, &lang, &mut symbols);
-    Ok(symbols)
+    let mut definitions = Vec::new();
+    walk_tree(&tree.root_node(), effective_content, &lang, &mut definitions);
+    Ok((definitions, offset))
 }
 
-/// 从 Vue/Svelte SFC 中提取 script 内容
-fn extract_script_content(content: &str) -> String {
-    // 简单的正则匹配 <script> 标签内容
-    let script_start = content.find("<script");
-    let script_end = content.find("</script>");
-
-    if let (Some(start), Some(end)) = (script_start, script_end) {
-        // 找到 > 的位置
-        if let Some(tag_end) = content[start..].find('>') {
-            let content_start = start + tag_end + 1;
-            if content_start < end {
-                return content[content_start..end].to_string();
-            }
-        }
-    }
+/// Vue/Svelte SFC 中 script 内容的字节区间
+fn script_range(content: &str) -> Option<Range<usize>> {
+    // 简单的文本匹配 <script> 标签内容
+    let start = content.find("<script")?;
+    let end = content.find("</script>")?;
 
-    // 如果没找到 script 标签，返回原内容
-    content.to_string()
+    // 找到 > 的位置
+    let tag_end = content[start..].find('>')?;
+    let content_start = start + tag_end + 1;
+    (content_start < end).then_some(content_start..end)
 }
 
-fn walk_tree(node: &Node, source: &str, lang: &Language, symbols: &mut Vec<Symbol>) {
+fn walk_tree(node: &Node, source: &str, lang: &Language, definitions: &mut Vec<Definition>) {
     let mut cursor = node.walk();
     for child in node.children(&mut cursor) {
         if let Some(symbol) = map_node_to_symbol(&child, source, lang) {
-            symbols.push(symbol);
+            definitions.push(Definition { symbol, range: child.byte_range() });
         }
-        walk_tree(&child, source, lang, symbols);
+        walk_tree(&child, source, lang, definitions);
     }
 }
 
@@ -0,0 +1,119 @@
+//! 索引文件的大小与内容限制
+//!
+//! 过大的文件、二进制文件和压缩 / 打包生成的文件（如多兆字节的 bundle）不进入搜索索引和符号存储，
+//! 避免索引膨胀和无意义的 snippet。文件仍记录元数据，内容变化后重新检查
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// 二进制检测读取的前缀长度（与 git 相同，前缀中含 NUL 字节即视为二进制）
+const BINARY_SNIFF_LEN: usize = 8000;
+
+/// 按平均行长判断压缩文件时要求的最小文件大小，避免误判很短的单行文件
+const MINIFIED_MIN_SIZE: usize = 2048;
+
+/// 平均行长超过此值视为压缩文件
+const MINIFIED_AVG_LINE_LEN: usize = 300;
+
+/// 文件限制
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileLimits {
+    /// 单个文件最大字节数（0 表示不限制）
+    pub max_file_size: u64,
+    /// 跳过二进制文件
+    pub skip_binary: bool,
+    /// 跳过压缩文件（文件名含 `.min.`，或平均行长超过 [`MINIFIED_AVG_LINE_LEN`]）
+    pub skip_minified: bool,
+}
+
+impl Default for FileLimits {
+    fn default() -> Self {
+        Self {
+            max_file_size: 1024 * 1024,
+            skip_binary: true,
+            skip_minified: true,
+        }
+    }
+}
+
+impl FileLimits {
+    /// 按配置中的 KB 数构建
+    pub fn from_config(search_config: &crate::config::SearchConfig) -> Self {
+        Self {
+            max_file_size: search_config.max_file_size_kb.saturating_mul(1024),
+            skip_binary: search_config.skip_binary_files,
+            skip_minified: search_config.skip_minified_files,
+        }
+    }
+
+    /// 按文件大小检查（读取内容之前）
+    pub fn check_size(&self, size: u64) -> Option<SkipReason> {
+        (self.max_file_size > 0 && size > self.max_file_size).then_some(SkipReason::TooLarge)
+    }
+
+    /// 按文件内容检查
+    pub fn check_content(&self, path: &Path, content: &[u8]) -> Option<SkipReason> {
+        if let Some(reason) = self.check_size(content.len() as u64) {
+            return Some(reason);
+        }
+        if self.skip_binary && is_binary(content) {
+            return Some(SkipReason::Binary);
+        }
+        if self.skip_minified && is_minified(path, content) {
+            return Some(SkipReason::Minified);
+        }
+        None
+    }
+}
+
+/// 文件被跳过的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    TooLarge,
+    Binary,
+    Minified,
+}
+
+/// 按原因统计的跳过文件数
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SkippedFiles {
+    pub too_large: usize,
+    pub binary: usize,
+    pub minified: usize,
+}
+
+impl SkippedFiles {
+    pub fn record(&mut self, reason: SkipReason) {
+        match reason {
+            SkipReason::TooLarge => self.too_large += 1,
+            SkipReason::Binary => self.binary += 1,
+            SkipReason::Minified => self.minified += 1,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.too_large + self.binary + self.minified
+    }
+}
+
+fn is_binary(content: &[u8]) -> bool {
+    content[..content.len().min(BINARY_SNIFF_LEN)].contains(&0)
+}
+
+fn is_minified(path: &Path, content: &[u8]) -> bool {
+    let is_min_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.contains(".min."));
+    if is_min_name {
+        return true;
+    }
+
+    if content.len() < MINIFIED_MIN_SIZE {
+        return false;
+    }
+    let lines = content.iter().filter(|&&b| b == b'\n').count() + 1;
+    content.len() / lines > MINIFIED_AVG_LINE_LEN
+}
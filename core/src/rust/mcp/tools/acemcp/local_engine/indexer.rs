@@ -4,41 +4,316 @@ use std::path::{Path, PathBuf};
 use std::time::UNIX_EPOCH;
 
 use anyhow::Result;
+use fs2::FileExt;
+use ignore::gitignore::GitignoreBuilder;
+use ignore::overrides::{Override, OverrideBuilder};
 use ignore::WalkBuilder;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use tantivy::schema::*;
 use tantivy::{Document, Index, IndexWriter, Term};
 
-use super::extractor;
-use super::types::LocalEngineConfig;
+use super::extractor::{self, CodeChunk};
+use super::file_limits::{FileLimits, SkipReason, SkippedFiles};
+use super::neurospec_ignore::{filter_walker, is_neurospec_ignored, load_neurospec_ignore};
+use super::tokenizer::{register_code_tokenizers, CODE_TOKENIZER};
+use super::types::{language_of_path, LocalEngineConfig};
 use super::vector_store::{CodeVectorStore, CodeVectorEntry};
+use crate::mcp::tools::unified_store::{indexing_cancel_token, report_indexing_progress};
+
+/// 索引被 [`cancel_indexing`](crate::mcp::tools::unified_store::cancel_indexing) 取消或因进程退出中断
+///
+/// 取消时未提交的写入已回滚，调用方可用 `err.is::<IndexingCancelled>()` 与真正的失败区分
+#[derive(Debug)]
+pub struct IndexingCancelled;
+
+impl std::fmt::Display for IndexingCancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Indexing cancelled")
+    }
+}
+
+impl std::error::Error for IndexingCancelled {}
 
 /// 文件元数据缓存条目
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct FileMetadata {
     mtime: u64,
     size: u64,
+    /// 因文件限制未入索引的原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    skipped: Option<SkipReason>,
 }
 
+/// 索引 schema 版本
+///
+/// 字段或分词方式变化时递增；版本不一致的索引在打开时清空并重新建立
+/// - 2: path / content / symbols 改用标识符感知分词（camelCase / snake_case 拆分）
+pub const INDEX_SCHEMA_VERSION: u32 = 2;
+
 /// 索引元数据
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct IndexMetadata {
+    /// 写入时的 schema 版本（旧版本文件没有该字段，视为 0）
+    #[serde(default)]
+    schema_version: u32,
     /// 项目根路径 -> 文件路径 -> 元数据
     projects: HashMap<String, HashMap<String, FileMetadata>>,
 }
 
+impl Default for IndexMetadata {
+    fn default() -> Self {
+        Self {
+            schema_version: INDEX_SCHEMA_VERSION,
+            projects: HashMap::new(),
+        }
+    }
+}
+
+/// 索引目录中已有的索引是否由旧版本 schema 建立（需要重建）
+///
+/// 目录为空或不存在时返回 false
+pub fn index_schema_outdated(index_path: &Path) -> bool {
+    if !index_path.join("meta.json").exists() {
+        return false;
+    }
+    let version = fs::read_to_string(index_path.join("index_metadata.json"))
+        .ok()
+        .and_then(|data| serde_json::from_str::<serde_json::Value>(&data).ok())
+        .and_then(|meta| meta.get("schema_version").and_then(|v| v.as_u64()))
+        .unwrap_or(0);
+    version != INDEX_SCHEMA_VERSION as u64
+}
+
+/// 使用代码分词器的文本字段选项
+fn code_text_options() -> TextOptions {
+    TextOptions::default().set_indexing_options(
+        TextFieldIndexing::default()
+            .set_tokenizer(CODE_TOKENIZER)
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+    )
+}
+
+/// 构建项目文件遍历器：遵守 .gitignore 与 .neurospecignore，并跳过配置的排除模式
+fn project_walker(root: &Path, exclude_patterns: &[String]) -> ignore::Walk {
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .hidden(false)
+        .git_ignore(true)
+        .git_global(true)
+        .git_exclude(true);
+
+    if let Some(overrides) = exclude_overrides(root, exclude_patterns) {
+        builder.overrides(overrides);
+    }
+    filter_walker(&mut builder, root);
+    builder.build()
+}
+
+/// 单个文件是否会被 [`project_walker`] 遍历到（.gitignore、.neurospecignore 与排除模式），用于按变化事件增量更新
+pub(crate) fn is_indexable(root: &Path, path: &Path, exclude_patterns: &[String]) -> bool {
+    let Ok(rel_path) = path.strip_prefix(root) else {
+        return false;
+    };
+
+    if load_neurospec_ignore(root).is_some_and(|ignore| is_neurospec_ignored(&ignore, path, false)) {
+        return false;
+    }
+
+    // 遍历时排除的目录会被整体跳过，因此也要检查文件所在的各级目录
+    if let Some(overrides) = exclude_overrides(root, exclude_patterns) {
+        let excluded = path
+            .ancestors()
+            .take_while(|p| *p != root)
+            .any(|p| overrides.matched(p, p != path).is_ignore());
+        if excluded {
+            return false;
+        }
+    }
+
+    // 从项目根目录到文件所在目录逐级应用 .gitignore（规则相对于所在目录），深层规则优先
+    let mut rule_files = vec![(root.to_path_buf(), root.join(".git").join("info").join("exclude"))];
+    let mut dir = root.to_path_buf();
+    rule_files.push((dir.clone(), dir.join(".gitignore")));
+    for component in rel_path.parent().into_iter().flat_map(|p| p.components()) {
+        dir.push(component);
+        rule_files.push((dir.clone(), dir.join(".gitignore")));
+    }
+
+    let mut ignored = false;
+    for (base, rule_file) in rule_files.iter().filter(|(_, file)| file.is_file()) {
+        let mut builder = GitignoreBuilder::new(base);
+        builder.add(rule_file);
+        let Ok(gitignore) = builder.build() else {
+            continue;
+        };
+        let matched = gitignore.matched_path_or_any_parents(path, false);
+        if matched.is_ignore() {
+            ignored = true;
+        } else if matched.is_whitelist() {
+            ignored = false;
+        }
+    }
+    !ignored
+}
+
+/// 将排除模式转换为 ignore 覆盖规则（无效模式跳过并记录警告）
+pub(crate) fn exclude_overrides(root: &Path, patterns: &[String]) -> Option<Override> {
+    if patterns.is_empty() {
+        return None;
+    }
+
+    let mut builder = OverrideBuilder::new(root);
+    for pattern in patterns {
+        // 覆盖规则中 `!` 前缀表示排除
+        if let Err(e) = builder.add(&format!("!{}", pattern.trim_start_matches('!'))) {
+            crate::log_important!(warn, "Invalid exclude pattern '{}': {}", pattern, e);
+        }
+    }
+
+    match builder.build() {
+        Ok(overrides) => Some(overrides),
+        Err(e) => {
+            crate::log_important!(warn, "Failed to build exclude patterns: {}", e);
+            None
+        }
+    }
+}
+
 /// Snippet 最大长度（字符）
 const MAX_SNIPPET_LENGTH: usize = 500;
 
+/// 每批并行解析的文件数，每批结束后写入索引并上报一次进度
+const INDEX_BATCH_SIZE: usize = 256;
+
+/// 全量重建时的临时目录后缀
+const REBUILD_DIR_SUFFIX: &str = "rebuild";
+/// 交换时旧索引的备份目录后缀
+const BACKUP_DIR_SUFFIX: &str = "old";
+/// 索引锁文件后缀（与索引目录同级，全量重建交换目录时不受影响）
+const INDEXING_LOCK_SUFFIX: &str = "indexing.lock";
+
+/// 索引锁文件路径，如 `projects/<hash>` -> `projects/<hash>.indexing.lock`
+pub fn indexing_lock_path(index_path: &Path) -> PathBuf {
+    sibling_dir(index_path, INDEXING_LOCK_SUFFIX)
+}
+
+/// 项目索引的进程间排他锁
+///
+/// 对锁文件加操作系统的咨询锁（Unix 为 `flock`，Windows 为 `LockFileEx`），在整个索引过程中持有，
+/// daemon、stdio MCP 与命令行进程共用同一把锁。释放该值时解锁；进程退出（包括崩溃）时由系统自动释放，
+/// 不会遗留失效的锁
+#[derive(Debug)]
+pub struct IndexingLock {
+    file: fs::File,
+}
+
+impl IndexingLock {
+    /// 尝试获取锁，已被其他进程或本进程的其他索引任务持有时返回 `Ok(None)`
+    pub fn try_acquire(index_path: &Path) -> Result<Option<Self>> {
+        let lock_path = indexing_lock_path(index_path);
+        if let Some(parent) = lock_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&lock_path)?;
+        match file.try_lock_exclusive() {
+            Ok(()) => {}
+            Err(e) if e.raw_os_error() == fs2::lock_contended_error().raw_os_error() => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+
+        // 记录持有者 PID，仅用于排查
+        use std::io::Write;
+        let _ = file.set_len(0);
+        let _ = write!(file, "{}", std::process::id());
+
+        Ok(Some(Self { file }))
+    }
+}
+
+impl Drop for IndexingLock {
+    fn drop(&mut self) {
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+/// 一致性报告中最多列出的示例路径数
+const MAX_CONSISTENCY_SAMPLES: usize = 20;
+
+/// Tantivy 索引与索引元数据的一致性检查结果
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct IndexConsistency {
+    /// 项目索引中存活的文档总数
+    pub total_docs: usize,
+    /// 元数据中该项目的文件数
+    pub metadata_files: usize,
+    /// 元数据记录但磁盘上已删除的文件数
+    pub stale_files: usize,
+    /// 元数据记录但索引中没有文档的文件数
+    pub missing_docs: usize,
+    /// 同一文件存在多份文档的文件数
+    pub duplicate_docs: usize,
+    /// 不属于项目元数据的文档数
+    pub orphan_docs: usize,
+    /// 部分问题路径示例
+    pub samples: Vec<String>,
+}
+
+impl IndexConsistency {
+    /// 是否无漂移
+    pub fn is_consistent(&self) -> bool {
+        self.stale_files == 0
+            && self.missing_docs == 0
+            && self.duplicate_docs == 0
+            && self.orphan_docs == 0
+    }
+
+    fn add_sample(&mut self, kind: &str, path: &str) {
+        if self.samples.len() < MAX_CONSISTENCY_SAMPLES {
+            self.samples.push(format!("{}: {}", kind, path));
+        }
+    }
+}
+
+/// 读取文件构建文档的结果
+enum BuiltDocument {
+    Document(Document),
+    /// 超出大小限制、二进制或压缩文件
+    Skipped(SkipReason),
+    /// 不可读或非 UTF-8
+    Unreadable,
+}
+
+/// 构建文档所需的字段句柄（可在解析线程间共享）
+#[derive(Clone, Copy)]
+struct DocFields {
+    path: Field,
+    path_exact: Field,
+    content: Field,
+    symbols: Field,
+    symbol_kinds: Field,
+    language: Field,
+    snippet: Field,
+}
+
 pub struct LocalIndexer {
-    #[allow(dead_code)] // 保留用于未来查询优化
     index: Index,
     writer: IndexWriter,
     config: LocalEngineConfig,
     // Field handles
     field_path: Field,
+    /// 不分词的完整相对路径，用于按文件精确删除文档
+    field_path_exact: Field,
     field_content: Field,
     field_symbols: Field,
+    /// 文件中出现的符号种类（不分词，用于按种类过滤）
+    field_symbol_kinds: Field,
     field_language: Field,
     field_snippet: Field,
 }
@@ -48,9 +323,11 @@ impl LocalIndexer {
         // 1. Define Schema
         let mut schema_builder = Schema::builder();
 
-        let field_path = schema_builder.add_text_field("path", TEXT | STORED);
-        let field_content = schema_builder.add_text_field("content", TEXT);
-        let field_symbols = schema_builder.add_text_field("symbols", TEXT | STORED);
+        let field_path = schema_builder.add_text_field("path", code_text_options().set_stored());
+        let field_path_exact = schema_builder.add_text_field("path_exact", STRING);
+        let field_content = schema_builder.add_text_field("content", code_text_options());
+        let field_symbols = schema_builder.add_text_field("symbols", code_text_options().set_stored());
+        let field_symbol_kinds = schema_builder.add_text_field("symbol_kinds", STRING);
         let field_language = schema_builder.add_text_field("language", STRING);
         let field_snippet = schema_builder.add_text_field("snippet", STORED);  // 预存 snippet
 
@@ -58,8 +335,28 @@ impl LocalIndexer {
 
         // 2. Open or Create Index
         fs::create_dir_all(&config.index_path)?;
+        if index_schema_outdated(&config.index_path) {
+            crate::log_important!(
+                warn,
+                "Index schema is older than version {}, rebuilding {:?}",
+                INDEX_SCHEMA_VERSION,
+                config.index_path
+            );
+            clear_index_dir(&config.index_path)?;
+        }
         let dir = tantivy::directory::MmapDirectory::open(&config.index_path)?;
-        let index = Index::open_or_create(dir, schema)?;
+        let index = match Index::open_or_create(dir, schema.clone()) {
+            Ok(index) => index,
+            // 旧版本 schema 的索引无法增量更新，清空后重新建立
+            Err(tantivy::TantivyError::SchemaError(e)) => {
+                crate::log_important!(warn, "Index schema changed ({}), recreating {:?}", e, config.index_path);
+                clear_index_dir(&config.index_path)?;
+                let dir = tantivy::directory::MmapDirectory::open(&config.index_path)?;
+                Index::open_or_create(dir, schema)?
+            }
+            Err(e) => return Err(e.into()),
+        };
+        register_code_tokenizers(&index);
 
         // 3. Create Writer (heap size 50MB)
         let writer = index.writer(50_000_000)?;
@@ -69,8 +366,10 @@ impl LocalIndexer {
             writer,
             config: config.clone(),
             field_path,
+            field_path_exact,
             field_content,
             field_symbols,
+            field_symbol_kinds,
             field_language,
             field_snippet,
         })
@@ -86,8 +385,14 @@ impl LocalIndexer {
         let path = self.metadata_path();
         if path.exists() {
             if let Ok(data) = fs::read_to_string(&path) {
-                if let Ok(meta) = serde_json::from_str(&data) {
-                    return meta;
+                if let Ok(meta) = serde_json::from_str::<IndexMetadata>(&data) {
+                    // 旧版本以原始路径字符串作为项目键，统一为规范化的键
+                    let projects = meta
+                        .projects
+                        .into_iter()
+                        .map(|(key, files)| (crate::utils::rekey(&key), files))
+                        .collect();
+                    return IndexMetadata { projects, ..Default::default() };
                 }
             }
         }
@@ -117,7 +422,7 @@ impl LocalIndexer {
             .as_secs();
         let size = metadata.len();
 
-        let current = FileMetadata { mtime, size };
+        let current = FileMetadata { mtime, size, skipped: None };
 
         match cached {
             Some(cached) if cached.mtime == mtime && cached.size == size => None,
@@ -125,42 +430,114 @@ impl LocalIndexer {
         }
     }
 
+    /// 全量重建索引
+    ///
+    /// 在临时目录中构建新索引，成功后再与现有索引目录交换，
+    /// 重建期间旧索引持续提供搜索；构建失败时旧索引保持不变。
+    ///
+    /// 索引目录只属于当前项目（见 [`LocalEngineConfig::for_project`]），重建不影响其他项目
     pub fn rebuild_index(&mut self, root: &Path) -> Result<usize> {
+        let live_dir = self.config.index_path.clone();
+        let staging_dir = sibling_dir(&live_dir, REBUILD_DIR_SUFFIX);
+        let backup_dir = sibling_dir(&live_dir, BACKUP_DIR_SUFFIX);
+
+        // 清理上次中断遗留的目录
+        let _ = fs::remove_dir_all(&staging_dir);
+        let _ = fs::remove_dir_all(&backup_dir);
+
+        let staging_config = LocalEngineConfig {
+            index_path: staging_dir.clone(),
+            ..self.config.clone()
+        };
+        let total_files = {
+            let mut staging = LocalIndexer::new(&staging_config)?;
+            match staging.index_directory(root) {
+                Ok(count) => count,
+                Err(e) => {
+                    drop(staging);
+                    let _ = fs::remove_dir_all(&staging_dir);
+                    return Err(e);
+                }
+            }
+        };
+
+        if let Err(e) = self.swap_in(&staging_dir, &backup_dir) {
+            // 部分平台（如 Windows）在索引文件被映射时无法重命名目录
+            crate::log_important!(warn, "Index swap failed ({}), rebuilding in place", e);
+            return self.rebuild_in_place(root);
+        }
+
+        crate::log_important!(info, "Index rebuilt and swapped in: {} files", total_files);
+        Ok(total_files)
+    }
+
+    /// 在当前索引目录中原地重建（删除与新增在提交前对搜索不可见）
+    fn rebuild_in_place(&mut self, root: &Path) -> Result<usize> {
         self.writer.delete_all_documents()?;
-        
-        // 清除该项目的元数据缓存
+
         let mut metadata = self.load_metadata();
-        let root_key = root.to_string_lossy().to_string();
-        metadata.projects.remove(&root_key);
+        metadata.projects.clear();
         self.save_metadata(&metadata)?;
-        
+
         self.index_directory(root)
     }
 
+    /// 用已构建好的目录替换当前索引目录，并重新打开索引与写入器
+    fn swap_in(&mut self, staging_dir: &Path, backup_dir: &Path) -> Result<()> {
+        let live_dir = self.config.index_path.clone();
+
+        if let Err(e) = fs::rename(&live_dir, backup_dir) {
+            let _ = fs::remove_dir_all(staging_dir);
+            return Err(anyhow::anyhow!("Failed to move live index aside: {}", e));
+        }
+        if let Err(e) = fs::rename(staging_dir, &live_dir) {
+            // 回滚：恢复旧索引
+            let _ = fs::rename(backup_dir, &live_dir);
+            let _ = fs::remove_dir_all(staging_dir);
+            return Err(anyhow::anyhow!("Failed to swap in rebuilt index: {}", e));
+        }
+
+        let dir = tantivy::directory::MmapDirectory::open(&live_dir)?;
+        let index = Index::open(dir)?;
+        register_code_tokenizers(&index);
+        let writer = index.writer(50_000_000)?;
+
+        // 旧写入器指向备份目录，替换后随之释放
+        self.writer = writer;
+        self.index = index;
+
+        if let Err(e) = fs::remove_dir_all(backup_dir) {
+            crate::log_important!(warn, "Failed to remove old index at {:?}: {}", backup_dir, e);
+        }
+        Ok(())
+    }
+
     /// 增量索引目录
     pub fn index_directory(&mut self, root: &Path) -> Result<usize> {
-        let root_key = root.to_string_lossy().to_string();
+        let root_key = crate::utils::path_key(root);
         
         crate::log_important!(info, "Starting index for: {}", root_key);
         crate::log_important!(info, "Index path: {:?}", self.config.index_path);
         
+        let cancel = indexing_cancel_token(root);
         let mut metadata = self.load_metadata();
-        let project_cache = metadata.projects.entry(root_key.clone()).or_default();
+        let project_cache = metadata.projects.remove(&root_key).unwrap_or_default();
 
         let mut indexed_count = 0;
         let mut skipped_count = 0;
+        let mut skipped_files = SkippedFiles::default();
         let mut current_files: HashMap<String, FileMetadata> = HashMap::new();
+        let mut to_index: Vec<(PathBuf, String, FileMetadata)> = Vec::new();
         let mut total_walked = 0;
 
-        // 使用 ignore crate 遵守 .gitignore 规则
-        let walker = WalkBuilder::new(root)
-            .hidden(false)
-            .git_ignore(true)
-            .git_global(true)
-            .git_exclude(true)
-            .build();
+        // 1. 遍历项目（遵守 .gitignore 及配置的排除模式），找出需要重新索引的文件
+        let walker = project_walker(root, &self.config.exclude_patterns);
         
         for entry in walker.filter_map(|e| e.ok()) {
+            // 退出时与手动取消一样处理，索引不会因此被标记为损坏
+            if crate::utils::is_shutting_down() || cancel.is_cancelled() {
+                return Err(anyhow::Error::new(IndexingCancelled));
+            }
             total_walked += 1;
             
             // ignore::DirEntry 的 file_type() 返回 Option<FileType>
@@ -179,27 +556,9 @@ impl LocalIndexer {
                 .to_string_lossy()
                 .replace('\\', "/");
 
-            // 检查是否需要重新索引
             let cached = project_cache.get(&rel_path);
             match self.should_reindex(path, cached) {
-                Some(new_meta) => {
-                    // 需要重新索引：先删除旧文档
-                    let term = Term::from_field_text(self.field_path, &rel_path);
-                    self.writer.delete_term(term);
-
-                    // 索引新内容
-                    if let Err(e) = self.index_file(path, root) {
-                        crate::log_important!(error, "Failed to index file {:?}: {}", path, e);
-                    } else {
-                        indexed_count += 1;
-                        current_files.insert(rel_path.clone(), new_meta);
-                        
-                        // 每 100 个文件输出一次进度
-                        if indexed_count % 100 == 0 {
-                            crate::log_important!(info, "Indexed {} files...", indexed_count);
-                        }
-                    }
-                }
+                Some(new_meta) => to_index.push((path.to_path_buf(), rel_path, new_meta)),
                 None => {
                     // 文件未变化，跳过
                     skipped_count += 1;
@@ -210,6 +569,55 @@ impl LocalIndexer {
             }
         }
 
+        // 2. 分批在 rayon 线程池中读取文件、提取符号并构建文档，再由写入器依次加入索引
+        let total_to_index = to_index.len();
+        let fields = self.doc_fields();
+        let limits = self.config.file_limits.clone();
+        let mut processed = 0;
+        report_indexing_progress(root, processed, total_to_index);
+
+        for batch in to_index.chunks(INDEX_BATCH_SIZE) {
+            // 退出或取消时放弃未提交的写入，下次启动按增量重新索引
+            if crate::utils::is_shutting_down() || cancel.is_cancelled() {
+                let _ = self.writer.rollback();
+                return Err(anyhow::Error::new(IndexingCancelled));
+            }
+
+            let documents: Vec<Result<Option<Document>>> = batch
+                .par_iter()
+                .map(|(path, rel_path, _)| Self::build_document(fields, &limits, path, rel_path))
+                .collect();
+
+            for ((path, rel_path, new_meta), document) in batch.iter().zip(documents) {
+                // 先删除旧文档
+                self.delete_file_docs(rel_path);
+
+                let added = match document {
+                    Ok(BuiltDocument::Document(doc)) => self.writer.add_document(doc).map(|_| ()).map_err(anyhow::Error::from),
+                    // 非 UTF-8 或不可读的文件不入索引，但记录元数据避免反复尝试
+                    Ok(BuiltDocument::Unreadable) => Ok(()),
+                    // 超出限制的文件同样只记录元数据，内容变化后重新检查
+                    Ok(BuiltDocument::Skipped(reason)) => {
+                        skipped_files.record(reason);
+                        current_files.insert(rel_path.clone(), FileMetadata { skipped: Some(reason), ..new_meta.clone() });
+                        continue;
+                    }
+                    Err(e) => Err(e),
+                };
+                match added {
+                    Ok(()) => {
+                        indexed_count += 1;
+                        current_files.insert(rel_path.clone(), new_meta.clone());
+                    }
+                    Err(e) => crate::log_important!(error, "Failed to index file {:?}: {}", path, e),
+                }
+            }
+
+            processed += batch.len();
+            crate::log_important!(info, "Indexed {}/{} files...", processed, total_to_index);
+            report_indexing_progress(root, processed, total_to_index);
+        }
+
         // 更新元数据缓存
         let total_files = current_files.len();
         metadata.projects.insert(root_key, current_files);
@@ -218,144 +626,220 @@ impl LocalIndexer {
         self.commit()?;
         crate::log_important!(
             info,
-            "Index complete: {} indexed, {} skipped (unchanged), {} total files, {} entries walked",
+            "Index complete: {} indexed, {} skipped (unchanged), {} skipped by limits \
+             ({} too large, {} binary, {} minified), {} total files, {} entries walked",
             indexed_count,
             skipped_count,
+            skipped_files.total(),
+            skipped_files.too_large,
+            skipped_files.binary,
+            skipped_files.minified,
             total_files,
             total_walked
         );
 
-        // 异步更新向量存储（仅在有 Tokio runtime 时执行）
+        // 后台分批计算新文件的向量
         if indexed_count > 0 {
-            let root_path = root.to_path_buf();
-            // 使用 try_current() 检测是否在 Tokio runtime 上下文中
-            // 避免在 std::thread::spawn 的后台线程中调用 tokio::spawn 导致 panic
-            if let Ok(handle) = tokio::runtime::Handle::try_current() {
-                handle.spawn(async move {
-                    if let Err(e) = Self::update_vector_store(&root_path).await {
-                        crate::log_important!(warn, "Failed to update vector store: {}", e);
-                    }
-                });
-            } else {
-                crate::log_important!(info, "Skipping vector store update (no async runtime available)");
-            }
+            super::embedding_queue::enqueue_embedding(root, &self.config);
         }
 
         // 返回总文件数（而非本次新索引数），用于正确显示索引状态
         Ok(total_files)
     }
 
-    /// 异步更新向量存储
-    async fn update_vector_store(root: &PathBuf) -> Result<()> {
-        use crate::neurospec::services::embedding::{is_embedding_available, get_global_embedding_service};
-        
-        // 检查嵌入服务是否可用
-        if !is_embedding_available() {
-            crate::log_important!(info, "Embedding service not available, skipping vector store update");
-            return Ok(());
-        }
+    /// 按文件变化增量更新索引，只删除并重新加入受影响文件的文档，返回项目的总文件数
+    ///
+    /// `changed` 为新建或修改的文件，`removed` 为已删除的文件（均为绝对路径）；
+    /// 被 .gitignore 或排除模式忽略的文件视为已删除
+    pub fn update_files(&mut self, root: &Path, changed: &[PathBuf], removed: &[PathBuf]) -> Result<usize> {
+        let root_key = crate::utils::path_key(root);
+        let mut metadata = self.load_metadata();
+        let mut project_files = metadata.projects.remove(&root_key).unwrap_or_default();
 
-        // 创建向量存储
-        let store = CodeVectorStore::new(root)?;
-        
-        // 遍历所有代码文件（遵守 .gitignore）
-        let walker = WalkBuilder::new(root)
-            .hidden(false)
-            .git_ignore(true)
-            .git_global(true)
-            .git_exclude(true)
-            .build();
-        let mut entries_to_update = Vec::new();
-        
-        for entry in walker.filter_map(|e| e.ok()) {
-            if !entry.file_type().map(|t| t.is_file()).unwrap_or(false) {
+        for path in removed {
+            let Some(rel_path) = relative_path(root, path) else {
                 continue;
-            }
+            };
+            self.delete_file_docs(&rel_path);
+            project_files.remove(&rel_path);
+        }
 
-            let path = entry.path();
-            
-            // 只处理代码文件
-            if !is_code_file(path) {
+        let fields = self.doc_fields();
+        let limits = self.config.file_limits.clone();
+        for path in changed {
+            let Some(rel_path) = relative_path(root, path) else {
                 continue;
-            }
-
-            let rel_path = path
-                .strip_prefix(root)
-                .unwrap_or(path)
-                .to_string_lossy()
-                .replace('\\', "/");
+            };
+            self.delete_file_docs(&rel_path);
 
-            // 检查是否已有向量
-            if let Ok(Some(_)) = store.get(&rel_path) {
-                continue; // 已有向量，跳过
-            }
+            let indexable = path.is_file() && is_indexable(root, path, &self.config.exclude_patterns);
+            let Some(mut new_meta) = indexable.then(|| self.should_reindex(path, None)).flatten() else {
+                project_files.remove(&rel_path);
+                continue;
+            };
 
-            // 读取文件并提取符号
-            if let Ok(content) = fs::read_to_string(path) {
-                if let Ok(symbols) = super::extractor::extract_symbols(path, &content) {
-                    let symbol_names: Vec<String> = symbols.iter().map(|s| s.name.clone()).collect();
-                    let summary = generate_file_summary(path, &symbol_names);
-                    
-                    entries_to_update.push(CodeVectorEntry {
-                        file_path: rel_path,
-                        symbols: symbol_names,
-                        summary,
-                        embedding: vec![], // 稍后填充
-                        updated_at: chrono::Utc::now().timestamp(),
-                    });
+            match Self::build_document(fields, &limits, path, &rel_path) {
+                Ok(document) => {
+                    match document {
+                        BuiltDocument::Document(doc) => self.writer.add_document(doc)?,
+                        BuiltDocument::Skipped(reason) => new_meta.skipped = Some(reason),
+                        BuiltDocument::Unreadable => {}
+                    }
+                    project_files.insert(rel_path, new_meta);
+                }
+                Err(e) => {
+                    crate::log_important!(error, "Failed to index file {:?}: {}", path, e);
+                    project_files.remove(&rel_path);
                 }
             }
         }
 
-        if entries_to_update.is_empty() {
-            return Ok(());
+        let total_files = project_files.len();
+        metadata.projects.insert(root_key, project_files);
+        self.save_metadata(&metadata)?;
+        self.commit()?;
+
+        // 只为本批变化的文件重新切分代码块，不遍历整个项目
+        if !changed.is_empty() || !removed.is_empty() {
+            let paths: Vec<PathBuf> = changed.iter().chain(removed).cloned().collect();
+            super::embedding_queue::enqueue_changed_files(root, &self.config, paths);
         }
+        Ok(total_files)
+    }
 
-        crate::log_important!(info, "Updating vector store: {} files to embed", entries_to_update.len());
+    /// 删除文件的全部文档（提交前对搜索不可见）
+    fn delete_file_docs(&mut self, rel_path: &str) {
+        self.writer.delete_term(Term::from_field_text(self.field_path_exact, rel_path));
+    }
 
-        // 获取嵌入服务
-        let lock = match get_global_embedding_service() {
-            Some(l) => l,
-            None => return Ok(()),
-        };
+    /// 为新增或修改过的代码文件重新切分代码块并写入待嵌入条目，返回新增条目数
+    ///
+    /// 每个文件一个文件级块加上每个函数 / 类一个块；文件修改时间未变的跳过。
+    /// 待嵌入条目的向量为空，中断后由嵌入队列从 `get_pending_chunks` 继续
+    pub(super) fn queue_vector_entries(
+        root: &Path,
+        exclude_patterns: &[String],
+        limits: &FileLimits,
+        store: &CodeVectorStore,
+    ) -> Result<usize> {
+        // 遍历所有代码文件（遵守 .gitignore 及配置的排除模式）
+        let walker = project_walker(root, exclude_patterns);
+        let mut queued = 0;
 
-        // 批量计算嵌入（每次最多 10 个）
-        for chunk in entries_to_update.chunks(10) {
-            let texts: Vec<String> = chunk.iter()
-                .map(|e| format!("{} {}", e.summary, e.symbols.join(" ")))
-                .collect();
+        for entry in walker.filter_map(|e| e.ok()) {
+            if !entry.file_type().map(|t| t.is_file()).unwrap_or(false) {
+                continue;
+            }
+            let path = entry.path();
+            if let Some(rel_path) = relative_path(root, path) {
+                queued += Self::queue_vector_file(path, &rel_path, limits, store)?;
+            }
+        }
 
-            // 获取锁并计算嵌入
-            let embeddings = {
-                let guard = lock.read().await;
-                if let Some(service) = guard.as_ref() {
-                    service.embed_batch(&texts).await.ok()
-                } else {
-                    None
-                }
-            };
+        Ok(queued)
+    }
 
-            if let Some(embeddings) = embeddings {
-                for (entry, embedding) in chunk.iter().zip(embeddings.into_iter()) {
-                    let mut updated_entry = entry.clone();
-                    updated_entry.embedding = embedding;
-                    let _ = store.save(&updated_entry);
-                }
+    /// 只为指定文件（文件监听批次中变化的文件，绝对路径）重新切分代码块，返回新增条目数
+    ///
+    /// 已删除、被忽略或不再是代码文件的文件删除其全部代码块
+    pub(super) fn queue_vector_files(
+        root: &Path,
+        paths: &[PathBuf],
+        exclude_patterns: &[String],
+        limits: &FileLimits,
+        store: &CodeVectorStore,
+    ) -> Result<usize> {
+        let mut queued = 0;
+        for path in paths {
+            let Some(rel_path) = relative_path(root, path) else {
+                continue;
+            };
+            if path.is_file() && is_indexable(root, path, exclude_patterns) {
+                queued += Self::queue_vector_file(path, &rel_path, limits, store)?;
+            } else {
+                store.delete(&rel_path)?;
             }
         }
+        Ok(queued)
+    }
 
-        let stats = store.stats()?;
-        crate::log_important!(info, "Vector store updated: {}/{} files have embeddings", 
-            stats.files_with_vectors, stats.total_files);
+    /// 切分单个代码文件并写入待嵌入条目，文件修改时间未变或超出限制时跳过
+    fn queue_vector_file(path: &Path, rel_path: &str, limits: &FileLimits, store: &CodeVectorStore) -> Result<usize> {
+        // 只处理代码文件
+        if !is_code_file(path) {
+            return Ok(0);
+        }
 
-        Ok(())
+        let mtime = fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_secs() as i64);
+
+        // 切分后文件未修改（已嵌入或已在队列中），跳过
+        if store.file_mtime(rel_path)? == Some(mtime) {
+            return Ok(0);
+        }
+
+        // 读取文件并按定义切分（超出限制的文件不入索引，也不切分）
+        let Ok(bytes) = fs::read(path) else {
+            return Ok(0);
+        };
+        if limits.check_content(path, &bytes).is_some() {
+            return Ok(0);
+        }
+        let Ok(content) = String::from_utf8(bytes) else {
+            return Ok(0);
+        };
+        let Ok(chunks) = extractor::extract_chunks(path, &content) else {
+            return Ok(0);
+        };
+        let entries = build_chunk_entries(path, rel_path, &content, &chunks, mtime);
+        store.replace_file(rel_path, &entries)
     }
 
     pub fn index_file(&mut self, path: &Path, root: &Path) -> Result<()> {
+        let rel_path = path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/");
+
+        let document = Self::build_document(self.doc_fields(), &self.config.file_limits, path, &rel_path)?;
+        if let BuiltDocument::Document(doc) = document {
+            self.writer.add_document(doc)?;
+        }
+        Ok(())
+    }
+
+    fn doc_fields(&self) -> DocFields {
+        DocFields {
+            path: self.field_path,
+            path_exact: self.field_path_exact,
+            content: self.field_content,
+            symbols: self.field_symbols,
+            symbol_kinds: self.field_symbol_kinds,
+            language: self.field_language,
+            snippet: self.field_snippet,
+        }
+    }
+
+    /// 读取文件并构建文档（检查文件限制，不可读或非 UTF-8 的文件不构建），可在多个线程中并行调用
+    fn build_document(fields: DocFields, limits: &FileLimits, path: &Path, rel_path: &str) -> Result<BuiltDocument> {
+        // 先按元数据检查大小，避免读入过大的文件
+        if let Some(reason) = fs::metadata(path).ok().and_then(|m| limits.check_size(m.len())) {
+            return Ok(BuiltDocument::Skipped(reason));
+        }
+
         // Read content
-        let content = match fs::read_to_string(path) {
-            Ok(c) => c,
-            Err(_) => return Ok(()), // Skip non-utf8 or unreadable files
+        let Ok(bytes) = fs::read(path) else {
+            return Ok(BuiltDocument::Unreadable);
+        };
+        if let Some(reason) = limits.check_content(path, &bytes) {
+            return Ok(BuiltDocument::Skipped(reason));
+        }
+        let Ok(content) = String::from_utf8(bytes) else {
+            return Ok(BuiltDocument::Unreadable); // Skip non-utf8 files
         };
 
         // Extract symbols
@@ -366,28 +850,29 @@ impl LocalIndexer {
             .collect::<Vec<_>>()
             .join(" ");
 
-        // Detect Language
-        let lang_str = format!("{:?}", extractor::detect_language(path));
+        let mut symbol_kinds: Vec<&str> = symbols.iter().filter_map(|s| s.kind.filter_name()).collect();
+        symbol_kinds.sort_unstable();
+        symbol_kinds.dedup();
+
+        // Detect Language（与查询过滤使用同一组语言名）
+        let lang_str = language_of_path(path).unwrap_or("unknown");
 
         // Generate preview snippet (first N characters with line numbers)
         let snippet = Self::generate_preview_snippet(&content);
 
         // Create Document
         let mut doc = Document::default();
-        let rel_path = path
-            .strip_prefix(root)
-            .unwrap_or(path)
-            .to_string_lossy()
-            .replace('\\', "/");
-
-        doc.add_text(self.field_path, &rel_path);
-        doc.add_text(self.field_content, &content);
-        doc.add_text(self.field_symbols, &symbol_text);
-        doc.add_text(self.field_language, &lang_str);
-        doc.add_text(self.field_snippet, &snippet);
+        doc.add_text(fields.path, rel_path);
+        doc.add_text(fields.path_exact, rel_path);
+        doc.add_text(fields.content, &content);
+        doc.add_text(fields.symbols, &symbol_text);
+        for kind in symbol_kinds {
+            doc.add_text(fields.symbol_kinds, kind);
+        }
+        doc.add_text(fields.language, lang_str);
+        doc.add_text(fields.snippet, &snippet);
 
-        self.writer.add_document(doc)?;
-        Ok(())
+        Ok(BuiltDocument::Document(doc))
     }
 
     /// 生成预览 snippet（跳过 imports，返回有意义的代码）
@@ -440,16 +925,123 @@ impl LocalIndexer {
         Ok(())
     }
 
+    /// 提交并在代价较低时合并段，然后等待合并线程结束（关闭前调用）
+    ///
+    /// 文档数不超过 `max_merge_docs` 时才合并，避免拖慢退出
+    pub fn compact(mut self, max_merge_docs: u64) -> Result<()> {
+        self.writer.commit()?;
+
+        let segments = self.index.searchable_segment_metas()?;
+        let total_docs: u64 = segments.iter().map(|s| s.num_docs() as u64).sum();
+        if segments.len() > 1 && total_docs <= max_merge_docs {
+            let segment_ids: Vec<_> = segments.iter().map(|s| s.id()).collect();
+            self.writer.merge(&segment_ids).wait()?;
+            crate::log_important!(info, "Merged {} index segments ({} docs)", segment_ids.len(), total_docs);
+        }
+
+        self.writer.wait_merging_threads()?;
+        Ok(())
+    }
+
+    /// 获取元数据中项目已索引的文件（相对路径）
+    pub fn project_file_paths(&self, root: &Path) -> Vec<String> {
+        let root_key = crate::utils::path_key(root);
+        self.load_metadata()
+            .projects
+            .remove(&root_key)
+            .map(|files| files.into_keys().collect())
+            .unwrap_or_default()
+    }
+
+    /// 统计索引中每个路径的存活文档数
+    fn doc_path_counts(&self) -> Result<HashMap<String, usize>> {
+        let reader = self.index.reader()?;
+        let searcher = reader.searcher();
+
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for segment_reader in searcher.segment_readers() {
+            let store_reader = segment_reader.get_store_reader(1)?;
+            for doc in store_reader.iter(segment_reader.alive_bitset()) {
+                let doc = doc?;
+                if let Some(path) = doc.get_first(self.field_path).and_then(|v| v.as_text()) {
+                    *counts.entry(path.to_string()).or_insert(0) += 1;
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// 校验项目的索引元数据与 Tantivy 文档是否一致
+    ///
+    /// 发现漂移后通过 `rebuild_index` 修复
+    pub fn verify_project(&self, root: &Path) -> Result<IndexConsistency> {
+        let metadata = self.load_metadata();
+        let root_key = crate::utils::path_key(root);
+        let doc_counts = self.doc_path_counts()?;
+
+        let mut report = IndexConsistency {
+            total_docs: doc_counts.values().sum(),
+            ..Default::default()
+        };
+
+        if let Some(project_files) = metadata.projects.get(&root_key) {
+            report.metadata_files = project_files.len();
+
+            let mut rel_paths: Vec<&String> = project_files.keys().collect();
+            rel_paths.sort();
+            for rel_path in rel_paths {
+                if !root.join(rel_path).exists() {
+                    report.stale_files += 1;
+                    report.add_sample("stale", rel_path);
+                    continue;
+                }
+                match doc_counts.get(rel_path).copied().unwrap_or(0) {
+                    0 => {
+                        report.missing_docs += 1;
+                        report.add_sample("missing", rel_path);
+                    }
+                    1 => {}
+                    _ => {
+                        report.duplicate_docs += 1;
+                        report.add_sample("duplicate", rel_path);
+                    }
+                }
+            }
+        }
+
+        // 孤儿文档：不属于任何项目的元数据（如文件删除后未清理的旧文档）
+        let mut orphans: Vec<(&String, &usize)> = doc_counts
+            .iter()
+            .filter(|(path, _)| !metadata.projects.values().any(|files| files.contains_key(*path)))
+            .collect();
+        orphans.sort();
+        for (path, count) in orphans {
+            report.orphan_docs += count;
+            report.add_sample("orphan", path);
+        }
+
+        Ok(report)
+    }
+
     /// 获取索引统计信息
     pub fn get_stats(&self, root: &Path) -> Result<IndexStats> {
         let metadata = self.load_metadata();
-        let root_key = root.to_string_lossy().to_string();
+        let root_key = crate::utils::path_key(root);
         
         let project_files = metadata.projects.get(&root_key);
-        let indexed_count = project_files.map(|m| m.len()).unwrap_or(0);
+        let mut indexed_count = 0;
+        let mut skipped_files = SkippedFiles::default();
+        for meta in project_files.into_iter().flat_map(|m| m.values()) {
+            match meta.skipped {
+                Some(reason) => skipped_files.record(reason),
+                None => indexed_count += 1,
+            }
+        }
         
         Ok(IndexStats {
             indexed_files: indexed_count,
+            skipped_files,
             index_path: self.config.index_path.clone(),
             last_updated: project_files
                 .and_then(|m| m.values().map(|v| v.mtime).max()),
@@ -461,10 +1053,43 @@ impl LocalIndexer {
 #[derive(Debug)]
 pub struct IndexStats {
     pub indexed_files: usize,
+    /// 因文件限制未入索引的文件数
+    pub skipped_files: SkippedFiles,
     pub index_path: PathBuf,
     pub last_updated: Option<u64>,
 }
 
+/// 文件相对于项目根目录的路径（统一使用 `/` 分隔），不在项目内时返回 None
+fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    path.strip_prefix(root)
+        .ok()
+        .map(|rel| rel.to_string_lossy().replace('\\', "/"))
+}
+
+/// 清空索引目录
+fn clear_index_dir(dir: &Path) -> Result<()> {
+    for entry in fs::read_dir(dir)?.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            fs::remove_dir_all(&path)?;
+        } else {
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// 索引目录的同级路径，如 `local_index` -> `local_index.rebuild`
+fn sibling_dir(dir: &Path, suffix: &str) -> PathBuf {
+    let mut name = dir
+        .file_name()
+        .map(|n| n.to_os_string())
+        .unwrap_or_else(|| "index".into());
+    name.push(".");
+    name.push(suffix);
+    dir.with_file_name(name)
+}
+
 #[allow(dead_code)]
 fn is_ignored(entry: &walkdir::DirEntry) -> bool {
     entry
@@ -491,6 +1116,51 @@ fn is_code_file(path: &Path) -> bool {
         .unwrap_or(false)
 }
 
+/// 文件的待嵌入代码块：文件级摘要块加上每个定义一个块
+fn build_chunk_entries(
+    path: &Path,
+    rel_path: &str,
+    content: &str,
+    chunks: &[CodeChunk],
+    mtime: i64,
+) -> Vec<CodeVectorEntry> {
+    let now = chrono::Utc::now().timestamp();
+    let symbol_names: Vec<String> = chunks.iter().map(|c| c.name.clone()).collect();
+
+    let file_chunk = CodeVectorEntry {
+        file_path: rel_path.to_string(),
+        summary: generate_file_summary(path, &symbol_names),
+        symbols: symbol_names,
+        embedding: vec![], // 由嵌入队列填充
+        updated_at: now,
+        start_byte: 0,
+        end_byte: content.len(),
+        start_line: 1,
+        end_line: content.lines().count().max(1),
+        kind: None,
+        file_mtime: mtime,
+    };
+
+    std::iter::once(file_chunk)
+        .chain(chunks.iter().map(|chunk| {
+            let kind = format!("{:?}", chunk.kind).to_lowercase();
+            CodeVectorEntry {
+                file_path: rel_path.to_string(),
+                symbols: vec![chunk.name.clone()],
+                summary: format!("{} {} {}\n{}", rel_path, kind, chunk.name, chunk.text(content)),
+                embedding: vec![],
+                updated_at: now,
+                start_byte: chunk.start_byte,
+                end_byte: chunk.end_byte,
+                start_line: chunk.start_line,
+                end_line: chunk.end_line,
+                kind: Some(kind),
+                file_mtime: mtime,
+            }
+        }))
+        .collect()
+}
+
 /// 生成文件摘要
 fn generate_file_summary(path: &Path, symbols: &[String]) -> String {
     let file_name = path.file_name()
@@ -506,3 +1176,37 @@ fn generate_file_summary(path: &Path, symbols: &[String]) -> String {
     
     format!("{}/{} contains: {}", parent, file_name, top_symbols)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::tools::unified_store::{finish_indexing, get_index_state, transition_index_state, IndexState};
+
+    #[test]
+    fn shutdown_cancels_indexing_without_marking_corrupted() {
+        let dir = std::env::temp_dir().join(format!("neurospec-indexer-shutdown-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let project = dir.join("project");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(project.join("main.rs"), "fn main() {}\n").unwrap();
+
+        let config = LocalEngineConfig {
+            index_path: dir.join("index"),
+            ..LocalEngineConfig::default()
+        };
+        let mut indexer = LocalIndexer::new(&config).unwrap();
+        transition_index_state(&project, IndexState::Indexing { started_at: 0, progress: 0.0 });
+
+        // 退出标志是进程级的，本模块之外的测试不依赖它
+        crate::utils::request_shutdown();
+        let result = indexer.index_directory(&project);
+        assert!(result.as_ref().is_err_and(|e| e.is::<IndexingCancelled>()));
+
+        finish_indexing(&project, &result);
+        let state = get_index_state(&project).unwrap().state;
+        assert!(matches!(state, IndexState::NotIndexed), "unexpected state: {:?}", state);
+
+        drop(indexer);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
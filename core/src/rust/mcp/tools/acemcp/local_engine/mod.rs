@@ -1,15 +1,27 @@
 pub mod ctags;
+pub mod dedupe;
+pub mod embedding_queue;
 pub mod extractor;
+pub mod file_limits;
 pub mod indexer;
+pub mod neurospec_ignore;
+pub mod query_syntax;
 pub mod ripgrep;
 pub mod searcher;
+pub mod snippet_cache;
+pub mod source_file;
+pub mod symbol_patterns;
+pub mod tokenizer;
 pub mod types;
 pub mod vector_store;
 
 // 重新导出常用类型
 pub use ctags::CtagsIndexer;
-pub use indexer::LocalIndexer;
+pub use indexer::{
+    LocalIndexer, IndexConsistency, IndexingCancelled, IndexingLock, indexing_lock_path, index_schema_outdated, INDEX_SCHEMA_VERSION,
+};
 pub use ripgrep::RipgrepSearcher;
 pub use searcher::LocalSearcher;
-pub use types::{LocalEngineConfig, SearchResult, SnippetContext, MatchInfo};
+pub use types::{LocalEngineConfig, RankingWeights, ScoreBreakdown, SearchFilters, SearchResult, SnippetContext, MatchInfo};
+pub use file_limits::{FileLimits, SkipReason, SkippedFiles};
 pub use vector_store::{CodeVectorStore, CodeVectorEntry, VectorStoreStats};
@@ -0,0 +1,59 @@
+//! 项目级忽略文件 `.neurospecignore`
+//!
+//! 位于项目根目录，语法与 `.gitignore` 相同，用于排除生成代码、测试夹具或第三方大文件。
+//! 在 `.gitignore` 与配置的排除模式之外额外生效，索引、ripgrep 回退、符号存储、
+//! 项目概览和文件监听统一使用同一份规则。规则只能追加排除，`!` 不能恢复被 `.gitignore` 忽略的文件
+
+use std::path::Path;
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::WalkBuilder;
+
+/// 忽略文件名
+pub const NEUROSPEC_IGNORE_FILE: &str = ".neurospecignore";
+
+/// 加载项目根目录下的忽略规则，文件不存在或没有有效规则时返回 None
+pub fn load_neurospec_ignore(root: &Path) -> Option<Gitignore> {
+    let file = root.join(NEUROSPEC_IGNORE_FILE);
+    if !file.is_file() {
+        return None;
+    }
+
+    let mut builder = GitignoreBuilder::new(root);
+    if let Some(e) = builder.add(&file) {
+        crate::log_important!(warn, "Invalid rules in {}: {}", file.display(), e);
+    }
+    match builder.build() {
+        Ok(ignore) if !ignore.is_empty() => Some(ignore),
+        Ok(_) => None,
+        Err(e) => {
+            crate::log_important!(warn, "Failed to load {}: {}", file.display(), e);
+            None
+        }
+    }
+}
+
+/// 路径本身或其所在的任一目录是否被忽略（不在项目根目录下的路径不受影响）
+pub fn is_neurospec_ignored(ignore: &Gitignore, path: &Path, is_dir: bool) -> bool {
+    path.starts_with(ignore.path()) && ignore.matched_path_or_any_parents(path, is_dir).is_ignore()
+}
+
+/// 让遍历器跳过被忽略的文件和目录
+pub fn filter_walker(builder: &mut WalkBuilder, root: &Path) {
+    if let Some(ignore) = load_neurospec_ignore(root) {
+        builder.filter_entry(move |entry| {
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            !is_neurospec_ignored(&ignore, entry.path(), is_dir)
+        });
+    }
+}
+
+/// ripgrep 参数（忽略文件存在时追加 `--ignore-file`，ripgrep 需在项目根目录下执行）
+pub fn ripgrep_ignore_args(root: &Path) -> Vec<String> {
+    let file = root.join(NEUROSPEC_IGNORE_FILE);
+    if file.is_file() {
+        vec!["--ignore-file".to_string(), file.to_string_lossy().into_owned()]
+    } else {
+        Vec::new()
+    }
+}
@@ -0,0 +1,305 @@
+//! 搜索查询语法
+//!
+//! 支持的写法：
+//! - 引号短语：`"token refresh"`
+//! - 布尔运算：`AND` / `OR` / `NOT`（大写，或 `&&` / `||`），以及 `+词`（必须）/ `-词`（排除）
+//! - 括号分组：`(login OR auth) AND NOT test`
+//! - 字段限定：`path:handlers`、`symbol:refresh`、`content:"retry loop"`、`lang:rust`、`kind:function`
+//!
+//! 解析是宽松的：未知字段（如 `std::io`）、不成对的引号和括号都按普通文本处理，不会报错。
+//! 没有运算符的多个词仍是「任一匹配」，与之前的词袋行为一致。
+
+use tantivy::query::Occur;
+
+/// 可在查询中限定的字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryField {
+    /// 相对路径（分词）
+    Path,
+    /// 文件内容
+    Content,
+    /// 符号名
+    Symbols,
+    /// 语言（与 `languages` 过滤使用同一组名称）
+    Language,
+    /// 文件中定义的符号种类
+    Kind,
+}
+
+impl QueryField {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "path" | "file" => Some(Self::Path),
+            "content" | "body" => Some(Self::Content),
+            "symbol" | "symbols" => Some(Self::Symbols),
+            "lang" | "language" => Some(Self::Language),
+            "kind" => Some(Self::Kind),
+            _ => None,
+        }
+    }
+
+    /// 是否为匹配文本的字段（语言和种类只用于过滤）
+    pub fn is_text(self) -> bool {
+        matches!(self, Self::Path | Self::Content | Self::Symbols)
+    }
+}
+
+/// 查询树节点
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryNode {
+    /// 单个词（可能被分词器拆成多个词项，此时按短语匹配）
+    Term { field: Option<QueryField>, text: String },
+    /// 引号短语
+    Phrase { field: Option<QueryField>, text: String },
+    /// 括号分组
+    Group(Vec<QueryClause>),
+}
+
+/// 带出现要求的子句
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryClause {
+    pub occur: Occur,
+    pub node: QueryNode,
+}
+
+/// 解析后的查询
+#[derive(Debug, Clone, Default)]
+pub struct ParsedQuery {
+    pub clauses: Vec<QueryClause>,
+    /// 是否使用了短语、运算符或字段限定
+    pub uses_syntax: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+    /// 前缀修饰（`+` / `-`）与原子节点
+    Atom(Option<char>, QueryNode),
+}
+
+impl ParsedQuery {
+    pub fn parse(query: &str) -> Self {
+        let tokens = tokenize(query);
+        let uses_syntax = tokens.iter().any(|token| match token {
+            Token::Atom(Some(_), _) | Token::And | Token::Or | Token::Not | Token::LParen => true,
+            Token::Atom(None, QueryNode::Phrase { .. }) => true,
+            Token::Atom(None, QueryNode::Term { field, .. }) => field.is_some(),
+            _ => false,
+        });
+        let clauses = parse_clauses(&mut tokens.into_iter(), false);
+        Self { clauses, uses_syntax }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+    }
+
+    /// 去掉语法后的正向文本（用于 snippet 定位、高亮和语义匹配）
+    pub fn plain_text(&self) -> String {
+        let mut words = Vec::new();
+        collect_positive_text(&self.clauses, &mut words);
+        words.join(" ")
+    }
+
+    /// ripgrep 回退使用的正则：正向的词和短语任一匹配
+    ///
+    /// 未使用查询语法时返回 None，原样使用查询（保持正则搜索的行为）
+    pub fn ripgrep_pattern(&self) -> Option<String> {
+        if !self.uses_syntax {
+            return None;
+        }
+        let mut words = Vec::new();
+        collect_positive_text(&self.clauses, &mut words);
+        let alternatives: Vec<String> = words
+            .iter()
+            .map(|text| {
+                text.split_whitespace()
+                    .map(regex::escape)
+                    .collect::<Vec<_>>()
+                    .join(r"\s+")
+            })
+            .filter(|pattern| !pattern.is_empty())
+            .collect();
+        (!alternatives.is_empty()).then(|| alternatives.join("|"))
+    }
+}
+
+fn collect_positive_text(clauses: &[QueryClause], words: &mut Vec<String>) {
+    for clause in clauses.iter().filter(|clause| clause.occur != Occur::MustNot) {
+        match &clause.node {
+            QueryNode::Term { field, text } | QueryNode::Phrase { field, text } => {
+                if field.map_or(true, QueryField::is_text) {
+                    words.push(text.clone());
+                }
+            }
+            QueryNode::Group(inner) => collect_positive_text(inner, words),
+        }
+    }
+}
+
+fn tokenize(query: &str) -> Vec<Token> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        if c == '(' {
+            tokens.push(Token::LParen);
+            i += 1;
+            continue;
+        }
+        if c == ')' {
+            tokens.push(Token::RParen);
+            i += 1;
+            continue;
+        }
+
+        // `+` / `-` 只有紧跟内容时才是修饰符
+        let mut prefix = None;
+        if (c == '+' || c == '-') && chars.get(i + 1).map_or(false, |next| !next.is_whitespace()) {
+            prefix = Some(c);
+            i += 1;
+        }
+
+        // `-(...)` 等同于 `NOT (...)`
+        if chars[i] == '(' {
+            if prefix == Some('-') {
+                tokens.push(Token::Not);
+            }
+            continue;
+        }
+
+        if chars[i] == '"' {
+            let (text, next) = read_quoted(&chars, i + 1);
+            i = next;
+            if !text.trim().is_empty() {
+                tokens.push(Token::Atom(prefix, QueryNode::Phrase { field: None, text }));
+            }
+            continue;
+        }
+
+        let start = i;
+        while i < chars.len() && !chars[i].is_whitespace() && !matches!(chars[i], '(' | ')' | '"') {
+            i += 1;
+        }
+        let word: String = chars[start..i].iter().collect();
+
+        if prefix.is_none() {
+            let operator = match word.as_str() {
+                "AND" | "&&" => Some(Token::And),
+                "OR" | "||" => Some(Token::Or),
+                "NOT" => Some(Token::Not),
+                _ => None,
+            };
+            if let Some(operator) = operator {
+                tokens.push(operator);
+                continue;
+            }
+        }
+
+        // 字段限定：`field:value` 或 `field:"phrase"`
+        if let Some((name, value)) = word.split_once(':') {
+            if let Some(field) = QueryField::from_name(name) {
+                if value.is_empty() && chars.get(i) == Some(&'"') {
+                    let (text, next) = read_quoted(&chars, i + 1);
+                    i = next;
+                    if !text.trim().is_empty() {
+                        tokens.push(Token::Atom(prefix, QueryNode::Phrase { field: Some(field), text }));
+                    }
+                    continue;
+                }
+                if !value.is_empty() && !value.contains(':') {
+                    let text = value.to_string();
+                    tokens.push(Token::Atom(prefix, QueryNode::Term { field: Some(field), text }));
+                    continue;
+                }
+            }
+        }
+
+        if !word.is_empty() {
+            tokens.push(Token::Atom(prefix, QueryNode::Term { field: None, text: word }));
+        }
+    }
+
+    tokens
+}
+
+/// 读取引号内的文本，返回文本和结束引号之后的位置（缺少结束引号时读到末尾）
+fn read_quoted(chars: &[char], start: usize) -> (String, usize) {
+    let end = chars[start..]
+        .iter()
+        .position(|&c| c == '"')
+        .map_or(chars.len(), |offset| start + offset);
+    let text = chars[start..end].iter().collect();
+    (text, (end + 1).min(chars.len()))
+}
+
+/// 解析子句列表，直到输入结束或（在分组内时）遇到右括号
+///
+/// 规则与 Lucene 相同：默认任一匹配；`AND` 使两侧变为必须；`NOT` / `-` 排除；`+` 必须
+fn parse_clauses(tokens: &mut impl Iterator<Item = Token>, in_group: bool) -> Vec<QueryClause> {
+    let mut clauses: Vec<QueryClause> = Vec::new();
+    let mut conjunction = false;
+    let mut negate = false;
+
+    while let Some(token) = tokens.next() {
+        let (prefix, node) = match token {
+            Token::RParen if in_group => break,
+            // 多余的右括号忽略
+            Token::RParen => continue,
+            Token::And => {
+                conjunction = true;
+                continue;
+            }
+            Token::Or => {
+                conjunction = false;
+                continue;
+            }
+            Token::Not => {
+                negate = true;
+                continue;
+            }
+            Token::LParen => {
+                let inner = parse_clauses(tokens, true);
+                if inner.is_empty() {
+                    negate = false;
+                    continue;
+                }
+                (None, QueryNode::Group(inner))
+            }
+            Token::Atom(prefix, node) => (prefix, node),
+        };
+
+        let mut occur = match prefix {
+            _ if negate => Occur::MustNot,
+            Some('-') => Occur::MustNot,
+            Some('+') => Occur::Must,
+            _ => Occur::Should,
+        };
+        if conjunction {
+            if let Some(previous) = clauses.last_mut() {
+                if previous.occur == Occur::Should {
+                    previous.occur = Occur::Must;
+                }
+            }
+            if occur == Occur::Should {
+                occur = Occur::Must;
+            }
+        }
+
+        clauses.push(QueryClause { occur, node });
+        conjunction = false;
+        negate = false;
+    }
+
+    clauses
+}
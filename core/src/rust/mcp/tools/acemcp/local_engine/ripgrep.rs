@@ -12,7 +12,9 @@ const RIPGREP_TIMEOUT_SECS: u64 = 5;
 
 use anyhow::{Result, Context};
 
-use super::types::SearchResult;
+use super::neurospec_ignore::ripgrep_ignore_args;
+use super::query_syntax::ParsedQuery;
+use super::types::{SearchFilters, SearchResult};
 
 /// Ripgrep 搜索器
 pub struct RipgrepSearcher {
@@ -20,6 +22,8 @@ pub struct RipgrepSearcher {
     max_results: usize,
     /// 上下文行数
     context_lines: usize,
+    /// 语言 / 路径过滤（转为 `-t` / `-g` 参数）
+    filters: SearchFilters,
 }
 
 impl RipgrepSearcher {
@@ -27,23 +31,36 @@ impl RipgrepSearcher {
         Self {
             max_results,
             context_lines,
+            filters: SearchFilters::default(),
         }
     }
 
+    /// 设置过滤条件（符号种类无法在 ripgrep 中下推，会被忽略）
+    pub fn with_filters(mut self, filters: SearchFilters) -> Self {
+        self.filters = filters;
+        self
+    }
+
     /// 执行 ripgrep 搜索（带超时和流式结果限制）
+    #[tracing::instrument(name = "search.ripgrep", skip_all)]
     pub fn search(&self, project_root: &Path, query: &str) -> Result<Vec<SearchResult>> {
+        // 限定的文件列表为空（如没有未提交修改）时，不能让 ripgrep 退回到搜索整个项目
+        if self.filters.paths().is_some_and(|paths| paths.is_empty()) {
+            return Ok(Vec::new());
+        }
+
         let rg_cmd = if cfg!(windows) { "rg.exe" } else { "rg" };
+
+        // 使用了短语/布尔/字段语法时，搜索其中任一正向词或短语；否则原样作为正则
+        let pattern = ParsedQuery::parse(query).ripgrep_pattern();
+        let query = pattern.as_deref().unwrap_or(query);
         
         let mut child = Command::new(rg_cmd)
             .current_dir(project_root)
-            .args([
-                "--json",
-                "-C", &self.context_lines.to_string(),
-                "--type-add", "code:*.{rs,ts,tsx,js,jsx,py,go,java,c,cpp,h,hpp,vue,svelte}",
-                "--type", "code",
-                "--ignore-case",
-                query,
-            ])
+            .args(["--json", "-C", &self.context_lines.to_string()])
+            .args(self.filters.ripgrep_args())
+            .args(ripgrep_ignore_args(project_root))
+            .args(["--ignore-case", "-e", query])
             .stdout(Stdio::piped())
             .stderr(Stdio::null())
             .spawn()
@@ -60,6 +77,7 @@ impl RipgrepSearcher {
         let mut current_file: Option<String> = None;
         let mut current_lines: Vec<String> = Vec::new();
         let mut match_line: Option<usize> = None;
+        let mut matched_lines: Vec<usize> = Vec::new();
         let mut file_count = 0;
         
         for line_result in reader.lines() {
@@ -99,6 +117,8 @@ impl RipgrepSearcher {
                                     line_number: match_line.unwrap_or(1),
                                     context: None,
                                     match_info: None,
+                                    score_breakdown: None,
+                                    matched_lines: std::mem::take(&mut matched_lines),
                                 });
                                 file_count += 1;
                             }
@@ -112,6 +132,7 @@ impl RipgrepSearcher {
                             current_file = Some(path.to_string());
                             current_lines.clear();
                             match_line = None;
+                            matched_lines.clear();
                         }
                     }
                     Some("match") => {
@@ -120,6 +141,7 @@ impl RipgrepSearcher {
                                 if match_line.is_none() {
                                     match_line = Some(line_num as usize);
                                 }
+                                matched_lines.push(line_num as usize);
                             }
                             if let Some(text) = data.get("lines")
                                 .and_then(|l| l.get("text"))
@@ -155,12 +177,15 @@ impl RipgrepSearcher {
                                     line_number: match_line.unwrap_or(1),
                                     context: None,
                                     match_info: None,
+                                    score_breakdown: None,
+                                    matched_lines: std::mem::take(&mut matched_lines),
                                 });
                                 file_count += 1;
                             }
                         }
                         current_lines.clear();
                         match_line = None;
+                        matched_lines.clear();
                     }
                     _ => {}
                 }
@@ -177,6 +202,8 @@ impl RipgrepSearcher {
                     line_number: match_line.unwrap_or(1),
                     context: None,
                     match_info: None,
+                    score_breakdown: None,
+                    matched_lines,
                 });
             }
         }
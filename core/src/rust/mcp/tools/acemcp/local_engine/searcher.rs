@@ -1,18 +1,30 @@
-use std::fs;
 use std::path::PathBuf;
 
 use anyhow::{Context, Result};
 use tantivy::collector::TopDocs;
-use tantivy::query::{QueryParser, PhraseQuery};
-use tantivy::schema::Field;
-use tantivy::{Index, ReloadPolicy, Term};
-
-use super::types::{LocalEngineConfig, SearchResult, SnippetContext, MatchInfo};
-use super::vector_store::CodeVectorStore;
-use crate::neurospec::services::embedding::{find_similar, is_embedding_available};
+use tantivy::query::{
+    AllQuery, BooleanQuery, BoostQuery, ConstScoreQuery, Occur, PhraseQuery, Query, RegexQuery, TermQuery,
+    TermSetQuery,
+};
+use tantivy::schema::{Field, IndexRecordOption};
+use tantivy::tokenizer::TokenStream;
+use tantivy::{Index, IndexReader, ReloadPolicy, Term};
+
+use super::query_syntax::{ParsedQuery, QueryClause, QueryField, QueryNode};
+
+use super::snippet_cache::{self, FileFingerprint};
+use super::source_file::SourceFile;
+use super::tokenizer::{query_analyzer, register_code_tokenizers};
+use super::types::{
+    normalize_language, normalize_symbol_kind, LocalEngineConfig, RankingWeights, ScoreBreakdown, SearchFilters,
+    SearchResult, SnippetContext, MatchInfo,
+};
+use super::vector_store::{CodeVectorEntry, CodeVectorStore};
+use crate::neurospec::services::embedding::{cosine_similarity, embed_text, is_embedding_available};
 
 /// 增强的 Snippet 提取结果
-struct EnhancedSnippet {
+#[derive(Debug, Clone)]
+pub(super) struct EnhancedSnippet {
     code: String,
     line_number: usize,
     context: SnippetContext,
@@ -21,6 +33,8 @@ struct EnhancedSnippet {
 
 pub struct LocalSearcher {
     index: Index,
+    /// 复用的 reader，索引提交后自动重新加载
+    reader: IndexReader,
     project_root: PathBuf,
     config: LocalEngineConfig,
 }
@@ -28,23 +42,53 @@ pub struct LocalSearcher {
 impl LocalSearcher {
     pub fn new(config: LocalEngineConfig, project_root: PathBuf) -> Result<Self> {
         let index = Index::open_in_dir(&config.index_path)?;
+        register_code_tokenizers(&index);
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommit)
+            .try_into()?;
 
         Ok(Self {
             index,
+            reader,
             project_root,
             config,
         })
     }
 
+    /// 立即重新加载 reader（不等待提交通知）
+    pub fn reload(&self) -> Result<()> {
+        self.reader.reload()?;
+        Ok(())
+    }
+
     /// 全文搜索
     pub fn search(&self, query_str: &str) -> Result<Vec<SearchResult>> {
-        let reader = self
-            .index
-            .reader_builder()
-            .reload_policy(ReloadPolicy::OnCommit)
-            .try_into()?;
+        self.search_filtered(query_str, &SearchFilters::default(), false)
+    }
+
+    /// 带过滤条件的全文搜索
+    ///
+    /// 查询支持短语、布尔运算和字段限定（见 [`super::query_syntax`]）。
+    /// `explain` 为 true 时在分数构成中附带各字段单独匹配的分数
+    #[tracing::instrument(name = "search.text", skip_all)]
+    pub fn search_filtered(&self, query_str: &str, filters: &SearchFilters, explain: bool) -> Result<Vec<SearchResult>> {
+        self.lexical_search(query_str, filters, &self.ranking(), explain)
+    }
 
-        let searcher = reader.searcher();
+    /// 当前生效的排序权重（全局配置叠加项目的 `.neurospec/search.toml`，每次查询重新读取）
+    pub fn ranking(&self) -> RankingWeights {
+        self.config.ranking.for_project(&self.project_root)
+    }
+
+    fn lexical_search(
+        &self,
+        query_str: &str,
+        filters: &SearchFilters,
+        ranking: &RankingWeights,
+        explain: bool,
+    ) -> Result<Vec<SearchResult>> {
+        let searcher = self.reader.searcher();
         let schema = self.index.schema();
 
         let field_path = schema.get_field("path").context("Missing path field")?;
@@ -52,22 +96,39 @@ impl LocalSearcher {
         let field_symbols = schema.get_field("symbols").context("Missing symbols field")?;
         let field_snippet = schema.get_field("snippet").ok();
 
-        // 预处理查询：扩展常见术语
-        let expanded_query = Self::expand_query(query_str);
+        // 预处理查询：扩展常见术语后解析查询语法
+        let parsed = ParsedQuery::parse(&Self::expand_query(query_str));
+        // snippet 定位和高亮只使用原查询中的正向词
+        let plain_query = ParsedQuery::parse(query_str).plain_text();
+        let query_str = plain_query.as_str();
 
-        // 配置多字段查询解析器，优化权重策略：
+        // 未限定字段的词在多个字段中匹配，默认权重策略：
         // - 符号名匹配最重要 (5.0)
         // - 路径包含关键词也重要 (2.0) - 如 auth/login.rs
         // - 内容兜底 (1.0)
-        let mut query_parser = QueryParser::for_index(
-            &self.index, 
-            vec![field_symbols, field_path, field_content]
-        );
-        query_parser.set_field_boost(field_symbols, 5.0);
-        query_parser.set_field_boost(field_path, 2.0);
-        query_parser.set_field_boost(field_content, 1.0);
+        let default_fields = [
+            (field_symbols, ranking.symbols_boost),
+            (field_path, ranking.path_boost),
+            (field_content, ranking.content_boost),
+        ];
 
-        let query = query_parser.parse_query(&expanded_query)?;
+        let Some(query) = self.build_clauses(&parsed.clauses, &default_fields)? else {
+            return Ok(Vec::new());
+        };
+        let query = self.apply_filters(query, filters)?;
+
+        // rank_explain：每个字段单独构建一次查询，用于拆分各字段的得分
+        let field_queries = if explain {
+            let mut field_queries = Vec::new();
+            for (name, field) in [("symbols", default_fields[0]), ("path", default_fields[1]), ("content", default_fields[2])] {
+                if let Some(query) = self.build_clauses(&parsed.clauses, &[field])? {
+                    field_queries.push((name, self.apply_filters(query, filters)?));
+                }
+            }
+            field_queries
+        } else {
+            Vec::new()
+        };
 
         // Execute Search
         let top_docs = searcher.search(&query, &TopDocs::with_limit(self.config.max_results))?;
@@ -77,33 +138,50 @@ impl LocalSearcher {
         for (score, doc_address) in top_docs {
             let retrieved_doc = searcher.doc(doc_address)?;
 
+            // 文档不匹配某个字段的查询时该字段得分为 0
+            let fields = field_queries
+                .iter()
+                .map(|(name, query)| {
+                    let value = query.explain(&searcher, doc_address).map(|e| e.value()).unwrap_or(0.0);
+                    (name.to_string(), value)
+                })
+                .collect();
+
             let path_val = retrieved_doc
                 .get_first(field_path)
                 .and_then(|v| v.as_text())
                 .unwrap_or("");
 
-            // 优先使用预存 snippet，否则回退到读文件
-            let (snippet, line) = if let Some(field) = field_snippet {
-                if let Some(stored_snippet) = retrieved_doc.get_first(field).and_then(|v| v.as_text()) {
-                    (stored_snippet.to_string(), 1)
-                } else {
-                    self.fallback_snippet(path_val, query_str)
-                }
-            } else {
-                self.fallback_snippet(path_val, query_str)
-            };
+            let stored_snippet = field_snippet
+                .and_then(|field| retrieved_doc.get_first(field))
+                .and_then(|v| v.as_text());
 
-            // 提取增强上下文
+            // 每个结果文件只读取一次；有预存 snippet 时从文件开头提取，否则按查询定位
             let full_path = self.project_root.join(path_val);
-            let enhanced = if let Ok(content) = fs::read_to_string(&full_path) {
-                self.extract_enhanced_snippet(&content, path_val, query_str, line)
-            } else {
-                EnhancedSnippet {
-                    code: snippet.clone(),
-                    line_number: line,
-                    context: SnippetContext::default(),
-                    matched_terms: vec![],
-                }
+            let match_line = if stored_snippet.is_some() { 1 } else { 0 };
+            let context_lines = self.config.snippet_context;
+            let fingerprint = FileFingerprint::of(&full_path);
+            let cached = fingerprint.and_then(|fingerprint| {
+                snippet_cache::get(&full_path, fingerprint, match_line, context_lines, query_str)
+            });
+
+            let enhanced = match cached {
+                Some(cached) => cached,
+                None => match SourceFile::open(&full_path) {
+                    Ok(source) => {
+                        let enhanced = self.extract_enhanced_snippet(&source.lines(), path_val, query_str, match_line);
+                        if let Some(fingerprint) = fingerprint {
+                            snippet_cache::insert(&full_path, fingerprint, match_line, context_lines, query_str, enhanced.clone());
+                        }
+                        enhanced
+                    }
+                    Err(_) => EnhancedSnippet {
+                        code: stored_snippet.unwrap_or("(file not readable)").to_string(),
+                        line_number: match_line,
+                        context: SnippetContext::default(),
+                        matched_terms: vec![],
+                    },
+                },
             };
 
             results.push(SearchResult {
@@ -117,6 +195,14 @@ impl LocalSearcher {
                     match_type: "content".to_string(),
                     match_quality: "partial".to_string(),
                 }),
+                score_breakdown: Some(ScoreBreakdown {
+                    lexical: score,
+                    fields,
+                    semantic: None,
+                    weights: *ranking,
+                    total: score,
+                }),
+                matched_lines: Vec::new(),
             });
         }
 
@@ -128,8 +214,25 @@ impl LocalSearcher {
     /// 如果嵌入服务可用，会对 TF-IDF 结果进行语义重排序
     /// 如果 TF-IDF 无结果，会尝试纯向量搜索
     pub async fn search_with_embedding(&self, query_str: &str) -> Result<Vec<SearchResult>> {
+        self.search_with_embedding_filtered(query_str, &SearchFilters::default(), false).await
+    }
+
+    /// 带过滤条件的语义增强搜索
+    #[tracing::instrument(name = "search.semantic", skip_all)]
+    pub async fn search_with_embedding_filtered(
+        &self,
+        query_str: &str,
+        filters: &SearchFilters,
+        explain: bool,
+    ) -> Result<Vec<SearchResult>> {
+        let ranking = self.ranking();
+
         // 先执行普通搜索
-        let mut results = self.search(query_str)?;
+        let mut results = self.lexical_search(query_str, filters, &ranking, explain)?;
+        // 语义匹配使用去掉查询语法后的文本
+        let parsed = ParsedQuery::parse(query_str);
+        let plain_query = parsed.plain_text();
+        let query_str = plain_query.as_str();
         
         // 检查嵌入服务是否可用
         if !is_embedding_available() {
@@ -137,25 +240,27 @@ impl LocalSearcher {
         }
         
         // 如果 TF-IDF 无结果，尝试纯向量搜索
+        // 向量搜索无法满足短语/布尔条件，也不记录符号种类，这两种情况下跳过
         if results.is_empty() {
-            return self.search_by_vector(query_str).await;
+            if parsed.uses_syntax || !filters.symbol_kinds.is_empty() {
+                return Ok(results);
+            }
+            return self.search_by_vector(query_str, filters, &ranking).await;
         }
         
-        // 构建候选文本列表（使用路径 + snippet 的组合）
-        let candidates: Vec<String> = results.iter()
-            .map(|r| format!("{} {}", r.path, r.snippet))
-            .collect();
-        
-        // 使用嵌入进行语义匹配
-        if let Some(similar) = find_similar(query_str, &candidates, results.len()).await {
-            // 创建语义分数映射
-            let semantic_scores: std::collections::HashMap<usize, f32> = similar.into_iter().collect();
-            
-            // 混合排序：TF-IDF (60%) + Embedding (40%)
+        // 语义分数来自后台预先计算的代码块向量，查询时只嵌入查询文本
+        let semantic_scores = self.stored_semantic_scores(query_str, &results).await;
+        if !semantic_scores.is_empty() {
+            // 混合排序：默认 TF-IDF (60%) + Embedding (40%)
             for (i, result) in results.iter_mut().enumerate() {
                 let semantic_score = semantic_scores.get(&i).copied().unwrap_or(0.0);
-                let combined = result.score * 0.6 + semantic_score * 10.0 * 0.4; // 归一化
+                let combined = result.score * ranking.lexical_weight
+                    + semantic_score * 10.0 * ranking.semantic_weight; // 归一化
                 result.score = combined;
+                if let Some(breakdown) = result.score_breakdown.as_mut() {
+                    breakdown.semantic = Some(semantic_score);
+                    breakdown.total = combined;
+                }
             }
             
             // 重新排序
@@ -167,46 +272,109 @@ impl LocalSearcher {
         Ok(results)
     }
 
+    /// 结果的语义分数：取结果所在代码块（包含命中行的最内层定义，否则文件级块）的存储向量与查询比较
+    ///
+    /// 还没有向量的结果不在返回值中；所有结果都没有向量时不调用嵌入服务
+    async fn stored_semantic_scores(
+        &self,
+        query_str: &str,
+        results: &[SearchResult],
+    ) -> std::collections::HashMap<usize, f32> {
+        let Ok(vector_store) = CodeVectorStore::new(&self.project_root) else {
+            return Default::default();
+        };
+
+        let mut chunks_by_file: std::collections::HashMap<&str, Vec<CodeVectorEntry>> = Default::default();
+        let mut vectors: Vec<(usize, Vec<f32>)> = Vec::new();
+        for (i, result) in results.iter().enumerate() {
+            let path = result.path.trim_start_matches("./");
+            let chunks = chunks_by_file
+                .entry(path)
+                .or_insert_with(|| vector_store.get_file_chunks(path).unwrap_or_default());
+            let chunk = chunks
+                .iter()
+                .filter(|c| !c.embedding.is_empty())
+                .filter(|c| {
+                    c.is_file_chunk() || (c.start_line <= result.line_number && result.line_number <= c.end_line)
+                })
+                .min_by_key(|c| if c.is_file_chunk() { usize::MAX } else { c.end_line - c.start_line });
+            if let Some(chunk) = chunk {
+                vectors.push((i, chunk.embedding.clone()));
+            }
+        }
+        if vectors.is_empty() {
+            return Default::default();
+        }
+
+        let Some(query_vector) = embed_text(query_str).await else {
+            return Default::default();
+        };
+        vectors
+            .into_iter()
+            .map(|(i, vector)| (i, cosine_similarity(&query_vector, &vector)))
+            .collect()
+    }
+
     /// 纯向量搜索（当 TF-IDF 无结果时使用）
-    async fn search_by_vector(&self, query_str: &str) -> Result<Vec<SearchResult>> {
+    ///
+    /// 查询只嵌入一次，与存储中每个代码块（函数 / 类或文件摘要）的向量比较，
+    /// 结果定位到块的起始行；同一文件已有定义块命中时不再返回文件级块
+    async fn search_by_vector(
+        &self,
+        query_str: &str,
+        filters: &SearchFilters,
+        ranking: &RankingWeights,
+    ) -> Result<Vec<SearchResult>> {
         // 尝试加载向量存储
         let vector_store = match CodeVectorStore::new(&self.project_root) {
             Ok(store) => store,
             Err(_) => return Ok(vec![]),
         };
 
-        // 获取所有有向量的代码
-        let entries = vector_store.get_all_with_vectors()?;
+        // 获取所有有向量的代码块
+        let mut entries = vector_store.get_all_with_vectors()?;
+        entries.retain(|entry| filters.matches_path(&entry.file_path));
         if entries.is_empty() {
             return Ok(vec![]);
         }
 
-        // 构建候选文本
-        let candidates: Vec<String> = entries.iter()
-            .map(|e| format!("{} {}", e.summary, e.symbols.join(" ")))
-            .collect();
-
-        // 使用嵌入计算相似度
-        let similar = match find_similar(query_str, &candidates, self.config.max_results).await {
-            Some(s) => s,
-            None => return Ok(vec![]),
+        let Some(query_vector) = embed_text(query_str).await else {
+            return Ok(vec![]);
         };
 
+        let mut scored: Vec<(f32, &CodeVectorEntry)> = entries
+            .iter()
+            .map(|entry| (cosine_similarity(&query_vector, &entry.embedding), entry))
+            .filter(|(score, _)| *score >= 0.3) // 过滤低相似度
+            .collect();
+        scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
         // 构建搜索结果
-        let mut results = Vec::new();
-        for (idx, score) in similar {
-            if score < 0.3 {
-                continue; // 过滤低相似度
+        let mut results: Vec<SearchResult> = Vec::new();
+        for (score, entry) in scored {
+            if results.len() >= self.config.max_results {
+                break;
+            }
+            if entry.is_file_chunk() && results.iter().any(|r| r.path == entry.file_path) {
+                continue;
             }
 
-            let entry = &entries[idx];
             let full_path = self.project_root.join(&entry.file_path);
-            
-            // 读取文件生成 snippet
-            let (snippet, line_number) = if let Ok(content) = fs::read_to_string(&full_path) {
-                self.generate_snippet(&content, query_str)
+            let Ok(source) = SourceFile::open(&full_path) else {
+                continue;
+            };
+            let lines = source.lines();
+
+            let (snippet, line_number, context) = if entry.is_file_chunk() {
+                let (snippet, line_number) = self.generate_snippet(&lines, query_str);
+                (snippet, line_number, SnippetContext::default())
             } else {
-                ("(file not readable)".to_string(), 0)
+                let context = SnippetContext {
+                    parent_symbol: entry.symbols.first().cloned(),
+                    symbol_kind: entry.kind.clone(),
+                    ..Default::default()
+                };
+                (self.chunk_snippet(&lines, entry.start_line, entry.end_line), entry.start_line, context)
             };
 
             results.push(SearchResult {
@@ -214,36 +382,54 @@ impl LocalSearcher {
                 score: score * 10.0, // 归一化到类似 TF-IDF 的范围
                 snippet,
                 line_number,
-                context: Some(SnippetContext::default()),
+                context: Some(context),
                 match_info: Some(MatchInfo {
                     matched_terms: entry.symbols.clone(),
                     match_type: "semantic".to_string(),
-                    match_quality: "vector".to_string(),
+                    match_quality: if entry.is_file_chunk() { "vector" } else { "chunk" }.to_string(),
                 }),
+                score_breakdown: Some(ScoreBreakdown {
+                    lexical: 0.0,
+                    fields: Vec::new(),
+                    semantic: Some(score),
+                    weights: *ranking,
+                    total: score * 10.0,
+                }),
+                matched_lines: Vec::new(),
             });
         }
 
         Ok(results)
     }
 
-    /// 回退方案：读取文件生成 snippet
-    fn fallback_snippet(&self, path: &str, query: &str) -> (String, usize) {
-        let full_path = self.project_root.join(path);
-        match fs::read_to_string(&full_path) {
-            Ok(content) => self.generate_snippet(&content, query),
-            Err(_) => ("(file not readable)".to_string(), 0),
+    /// 代码块开头的片段（最多 `2 * snippet_context + 1` 行），标记块的起始行
+    fn chunk_snippet(&self, lines: &[&str], start_line: usize, end_line: usize) -> String {
+        let start = start_line.saturating_sub(1).min(lines.len());
+        let end = end_line
+            .min(lines.len())
+            .min(start + 2 * self.config.snippet_context + 1)
+            .max(start);
+
+        let mut snippet = String::new();
+        for (idx, l) in lines[start..end].iter().enumerate() {
+            let current_line_num = start + idx + 1;
+            let marker = if current_line_num == start_line { ">" } else { " " };
+            snippet.push_str(&format!("{} {:4} | {}\n", marker, current_line_num, l));
+        }
+        if end < end_line.min(lines.len()) {
+            snippet.push_str("  ⋮\n");
         }
+        snippet
     }
 
     /// 提取增强的 snippet 上下文
     fn extract_enhanced_snippet(
         &self, 
-        content: &str, 
+        lines: &[&str], 
         path: &str, 
         query: &str, 
         match_line: usize
     ) -> EnhancedSnippet {
-        let lines: Vec<&str> = content.lines().collect();
         let query_terms: Vec<String> = query
             .split_whitespace()
             .map(|s| s.to_lowercase())
@@ -251,16 +437,16 @@ impl LocalSearcher {
         
         // 1. 生成基础 snippet
         let (code, line_num) = if match_line > 0 && match_line <= lines.len() {
-            self.extract_snippet(&lines, match_line - 1)
+            self.extract_snippet(lines, match_line - 1)
         } else {
-            self.generate_snippet(content, query)
+            self.generate_snippet(lines, query)
         };
         
         // 2. 提取结构化上下文
-        let context = self.extract_context(&lines, line_num.saturating_sub(1), path);
+        let context = self.extract_context(lines, line_num.saturating_sub(1), path);
         
         // 3. 识别匹配的词项
-        let matched_terms = self.find_matched_terms(&lines, line_num.saturating_sub(1), &query_terms);
+        let matched_terms = self.find_matched_terms(lines, line_num.saturating_sub(1), &query_terms);
         
         EnhancedSnippet {
             code,
@@ -489,13 +675,15 @@ impl LocalSearcher {
 
     /// 符号搜索 - 精确匹配
     pub fn search_symbol(&self, symbol_name: &str) -> Result<Vec<SearchResult>> {
-        let reader = self
-            .index
-            .reader_builder()
-            .reload_policy(ReloadPolicy::OnCommit)
-            .try_into()?;
+        self.search_symbol_filtered(symbol_name, &SearchFilters::default())
+    }
 
-        let searcher = reader.searcher();
+    /// 带过滤条件的符号搜索
+    #[tracing::instrument(name = "search.symbol", skip_all)]
+    pub fn search_symbol_filtered(&self, symbol_name: &str, filters: &SearchFilters) -> Result<Vec<SearchResult>> {
+        let searcher = self.reader.searcher();
+        // 分数构成中记录当前生效的权重（符号查询只匹配符号字段，不使用字段权重）
+        let ranking = self.ranking();
         let schema = self.index.schema();
 
         let field_path = schema.get_field("path").context("Missing path field")?;
@@ -503,7 +691,7 @@ impl LocalSearcher {
         let field_snippet = schema.get_field("snippet").ok();
 
         // 使用 PhraseQuery 进行更精确的符号匹配
-        let query = self.build_symbol_query(field_symbols, symbol_name);
+        let query = self.apply_filters(self.build_symbol_query(field_symbols, symbol_name), filters)?;
 
         let top_docs = searcher.search(&query, &TopDocs::with_limit(self.config.max_results))?;
 
@@ -517,33 +705,23 @@ impl LocalSearcher {
                 .and_then(|v| v.as_text())
                 .unwrap_or("");
 
-            // 符号搜索仍需读取文件来定位符号位置，但可优先使用预存 snippet 作为回退
-            let (snippet, line) = {
-                let full_path = self.project_root.join(path_val);
-                match fs::read_to_string(&full_path) {
-                    Ok(content) => self.find_symbol_definition(&content, symbol_name),
-                    Err(_) => {
-                        // 回退到预存 snippet
-                        if let Some(field) = field_snippet {
-                            if let Some(s) = retrieved_doc.get_first(field).and_then(|v| v.as_text()) {
-                                (s.to_string(), 1)
-                            } else {
-                                ("(file not readable)".to_string(), 0)
-                            }
-                        } else {
-                            ("(file not readable)".to_string(), 0)
-                        }
-                    }
-                }
-            };
-
-            // 提取上下文信息 (符号搜索专用)
+            // 符号搜索仍需读取文件来定位符号位置，定位与上下文提取共用一次读取
             let full_path = self.project_root.join(path_val);
-            let context = if let Ok(content) = fs::read_to_string(&full_path) {
-                let lines: Vec<&str> = content.lines().collect();
-                Some(self.extract_context(&lines, line.saturating_sub(1), path_val))
-            } else {
-                None
+            let (snippet, line, context) = match SourceFile::open(&full_path) {
+                Ok(source) => {
+                    let lines = source.lines();
+                    let (snippet, line) = self.find_symbol_definition(&lines, symbol_name);
+                    let context = self.extract_context(&lines, line.saturating_sub(1), path_val);
+                    (snippet, line, Some(context))
+                }
+                // 回退到预存 snippet
+                Err(_) => match field_snippet
+                    .and_then(|field| retrieved_doc.get_first(field))
+                    .and_then(|v| v.as_text())
+                {
+                    Some(s) => (s.to_string(), 1, None),
+                    None => ("(file not readable)".to_string(), 0, None),
+                },
             };
 
             results.push(SearchResult {
@@ -557,12 +735,154 @@ impl LocalSearcher {
                     match_type: "symbol".to_string(),
                     match_quality: "exact".to_string(),
                 }),
+                score_breakdown: Some(ScoreBreakdown {
+                    lexical: score,
+                    fields: Vec::new(),
+                    semantic: None,
+                    weights: ranking,
+                    total: score,
+                }),
+                matched_lines: Vec::new(),
             });
         }
 
         Ok(results)
     }
 
+    /// 将解析后的子句转为布尔查询（没有可匹配词项时返回 None）
+    fn build_clauses(&self, clauses: &[QueryClause], default_fields: &[(Field, f32)]) -> Result<Option<Box<dyn Query>>> {
+        let mut built: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        for clause in clauses {
+            let query = match &clause.node {
+                QueryNode::Group(inner) => self.build_clauses(inner, default_fields)?,
+                QueryNode::Term { field, text } | QueryNode::Phrase { field, text } => {
+                    self.build_leaf(*field, text, default_fields)?
+                }
+            };
+            // 分词后为空的子句（如只有标点）忽略
+            if let Some(query) = query {
+                built.push((clause.occur, query));
+            }
+        }
+
+        if built.is_empty() {
+            return Ok(None);
+        }
+        // 只有排除条件时从全部文档中排除
+        if built.iter().all(|(occur, _)| *occur == Occur::MustNot) {
+            built.push((Occur::Must, Box::new(AllQuery)));
+        }
+        Ok(Some(Box::new(BooleanQuery::new(built))))
+    }
+
+    /// 构建单个词或短语的查询
+    ///
+    /// 文本按字段的分词器拆分，多个词项时按短语匹配；未限定字段时在所有默认字段中匹配
+    fn build_leaf(
+        &self,
+        field: Option<QueryField>,
+        text: &str,
+        default_fields: &[(Field, f32)],
+    ) -> Result<Option<Box<dyn Query>>> {
+        let schema = self.index.schema();
+        let field_name = match field {
+            None => None,
+            Some(QueryField::Path) => Some("path"),
+            Some(QueryField::Content) => Some("content"),
+            Some(QueryField::Symbols) => Some("symbols"),
+            // 语言和符号种类是不分词的过滤字段，不参与评分
+            Some(QueryField::Language) | Some(QueryField::Kind) => {
+                let (name, value) = if field == Some(QueryField::Language) {
+                    ("language", normalize_language(text))
+                } else {
+                    ("symbol_kinds", normalize_symbol_kind(text))
+                };
+                let field = schema.get_field(name).with_context(|| format!("Missing {} field", name))?;
+                let value = value.map(str::to_string).unwrap_or_else(|| text.to_lowercase());
+                let term = TermQuery::new(Term::from_field_text(field, &value), IndexRecordOption::Basic);
+                return Ok(Some(Box::new(ConstScoreQuery::new(Box::new(term), 0.0))));
+            }
+        };
+        let restrict = field_name
+            .map(|name| schema.get_field(name).with_context(|| format!("Missing {} field", name)))
+            .transpose()?;
+
+        let mut per_field: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        for &(field, boost) in default_fields {
+            if restrict.map_or(false, |restrict| restrict != field) {
+                continue;
+            }
+            let mut analyzer = query_analyzer(&self.index, field)?;
+            let mut terms = Vec::new();
+            analyzer
+                .token_stream(text)
+                .process(&mut |token| terms.push(Term::from_field_text(field, &token.text)));
+
+            let query: Box<dyn Query> = match terms.len() {
+                0 => continue,
+                1 => Box::new(TermQuery::new(terms.remove(0), IndexRecordOption::WithFreqs)),
+                _ => Box::new(PhraseQuery::new(terms)),
+            };
+            per_field.push((Occur::Should, Box::new(BoostQuery::new(query, boost))));
+        }
+
+        Ok(match per_field.len() {
+            0 => None,
+            1 => per_field.pop().map(|(_, query)| query),
+            _ => Some(Box::new(BooleanQuery::new(per_field))),
+        })
+    }
+
+    /// 将过滤条件作为不计分的必需子句附加到查询上
+    ///
+    /// 语言和符号种类是不分词字段上的词项过滤，path_glob 转为 `path_exact` 上的正则过滤，
+    /// 限定的文件列表转为 `path_exact` 上的词项集合过滤
+    fn apply_filters(&self, query: Box<dyn Query>, filters: &SearchFilters) -> Result<Box<dyn Query>> {
+        if filters.is_empty() {
+            return Ok(query);
+        }
+
+        let schema = self.index.schema();
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = vec![(Occur::Must, query)];
+
+        let any_term = |field: Field, values: &[&str]| -> Box<dyn Query> {
+            let terms = values
+                .iter()
+                .map(|value| {
+                    let term = Term::from_field_text(field, value);
+                    (Occur::Should, Box::new(TermQuery::new(term, IndexRecordOption::Basic)) as Box<dyn Query>)
+                })
+                .collect();
+            Box::new(ConstScoreQuery::new(Box::new(BooleanQuery::new(terms)), 0.0))
+        };
+
+        if !filters.languages.is_empty() {
+            let field = schema.get_field("language").context("Missing language field")?;
+            clauses.push((Occur::Must, any_term(field, &filters.languages)));
+        }
+
+        if !filters.symbol_kinds.is_empty() {
+            let field = schema
+                .get_field("symbol_kinds")
+                .context("Missing symbol_kinds field, the index is rebuilt on the next indexing run")?;
+            clauses.push((Occur::Must, any_term(field, &filters.symbol_kinds)));
+        }
+
+        if let Some(path_regex) = filters.path_regex() {
+            let field = schema.get_field("path_exact").context("Missing path_exact field")?;
+            let regex = RegexQuery::from_pattern(path_regex, field)?;
+            clauses.push((Occur::Must, Box::new(ConstScoreQuery::new(Box::new(regex), 0.0))));
+        }
+
+        if let Some(paths) = filters.paths() {
+            let field = schema.get_field("path_exact").context("Missing path_exact field")?;
+            let terms = paths.iter().map(|path| Term::from_field_text(field, path));
+            clauses.push((Occur::Must, Box::new(ConstScoreQuery::new(Box::new(TermSetQuery::new(terms)), 0.0))));
+        }
+
+        Ok(Box::new(BooleanQuery::new(clauses)))
+    }
+
     /// 构建符号查询
     fn build_symbol_query(&self, field: Field, symbol_name: &str) -> Box<dyn tantivy::query::Query> {
         // 将符号名转为小写进行匹配
@@ -584,8 +904,7 @@ impl LocalSearcher {
     }
 
     /// 查找符号定义位置
-    fn find_symbol_definition(&self, content: &str, symbol_name: &str) -> (String, usize) {
-        let lines: Vec<&str> = content.lines().collect();
+    fn find_symbol_definition(&self, lines: &[&str], symbol_name: &str) -> (String, usize) {
         let symbol_lower = symbol_name.to_lowercase();
 
         // 查找包含符号定义的行
@@ -603,14 +922,14 @@ impl LocalSearcher {
                 && line_lower.contains(&symbol_lower);
 
             if is_definition {
-                return self.extract_snippet(&lines, i);
+                return self.extract_snippet(lines, i);
             }
         }
 
         // 回退：查找任何包含符号的行
         for (i, line) in lines.iter().enumerate() {
             if line.to_lowercase().contains(&symbol_lower) {
-                return self.extract_snippet(&lines, i);
+                return self.extract_snippet(lines, i);
             }
         }
 
@@ -625,8 +944,7 @@ impl LocalSearcher {
     /// 1. 支持驼峰命名拆分（SearchProfile → search, profile）
     /// 2. 支持下划线拆分（search_profile → search, profile）
     /// 3. 多轮匹配：先精确匹配，再宽松匹配，最后模糊匹配
-    fn generate_snippet(&self, content: &str, query: &str) -> (String, usize) {
-        let lines: Vec<&str> = content.lines().collect();
+    fn generate_snippet(&self, lines: &[&str], query: &str) -> (String, usize) {
         
         // 扩展查询词：原词 + 拆分后的词
         let mut terms: Vec<String> = query
@@ -648,7 +966,7 @@ impl LocalSearcher {
         for (i, line) in lines.iter().enumerate() {
             let lower_line = line.to_lowercase();
             if lower_line.contains(&query_lower) {
-                return self.extract_snippet(&lines, i);
+                return self.extract_snippet(lines, i);
             }
         }
 
@@ -656,7 +974,7 @@ impl LocalSearcher {
         for (i, line) in lines.iter().enumerate() {
             let lower_line = line.to_lowercase();
             if terms.iter().any(|t| lower_line.contains(t)) {
-                return self.extract_snippet(&lines, i);
+                return self.extract_snippet(lines, i);
             }
         }
 
@@ -665,14 +983,14 @@ impl LocalSearcher {
             let lower_line = line.to_lowercase();
             for term in &terms {
                 if term.len() >= 4 && lower_line.contains(&term[..term.len()-1]) {
-                    return self.extract_snippet(&lines, i);
+                    return self.extract_snippet(lines, i);
                 }
             }
         }
 
         // 改进的默认行为：返回文件中有意义的部分（跳过 imports）
-        let meaningful_start = Self::find_meaningful_start(&lines);
-        self.extract_snippet(&lines, meaningful_start)
+        let meaningful_start = Self::find_meaningful_start(lines);
+        self.extract_snippet(lines, meaningful_start)
     }
 
     /// 查找文件中有意义的起始位置（跳过 imports 和注释）
@@ -800,3 +1118,4 @@ impl LocalSearcher {
         expanded
     }
 }
+
@@ -0,0 +1,128 @@
+//! 片段提取缓存
+//!
+//! 短时间内对同一批热点文件的连续搜索会反复读取和扫描这些文件。
+//! 按（文件指纹、匹配行、查询词）缓存提取出的片段：文件变化事件到达时移除该文件的条目，
+//! 指纹（大小 + 修改时间）与当前文件不一致的条目同样视为失效
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime};
+
+use lazy_static::lazy_static;
+
+use super::searcher::EnhancedSnippet;
+
+/// 最多缓存的片段数
+const MAX_ENTRIES: usize = 512;
+
+/// 文件指纹：只需一次 stat，无需读取内容
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct FileFingerprint {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl FileFingerprint {
+    pub(super) fn of(path: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        Some(Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    path: PathBuf,
+    match_line: usize,
+    /// 片段上下文行数（配置变化后不复用旧片段）
+    context_lines: usize,
+    /// 归一化后的查询（小写、合并空白）
+    query: String,
+}
+
+impl CacheKey {
+    fn new(path: &Path, match_line: usize, context_lines: usize, query: &str) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            match_line,
+            context_lines,
+            query: query.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase(),
+        }
+    }
+}
+
+struct CacheEntry {
+    fingerprint: FileFingerprint,
+    snippet: EnhancedSnippet,
+    last_used: Instant,
+}
+
+lazy_static! {
+    static ref SNIPPET_CACHE: Mutex<HashMap<CacheKey, CacheEntry>> = Mutex::new(HashMap::new());
+}
+
+/// 查找指纹一致的缓存片段
+pub(super) fn get(
+    path: &Path,
+    fingerprint: FileFingerprint,
+    match_line: usize,
+    context_lines: usize,
+    query: &str,
+) -> Option<EnhancedSnippet> {
+    let key = CacheKey::new(path, match_line, context_lines, query);
+    let mut cache = SNIPPET_CACHE.lock().ok()?;
+    match cache.get_mut(&key) {
+        Some(entry) if entry.fingerprint == fingerprint => {
+            entry.last_used = Instant::now();
+            Some(entry.snippet.clone())
+        }
+        Some(_) => {
+            cache.remove(&key);
+            None
+        }
+        None => None,
+    }
+}
+
+/// 写入片段，超出容量时淘汰最久未使用的条目
+pub(super) fn insert(
+    path: &Path,
+    fingerprint: FileFingerprint,
+    match_line: usize,
+    context_lines: usize,
+    query: &str,
+    snippet: EnhancedSnippet,
+) {
+    let Ok(mut cache) = SNIPPET_CACHE.lock() else {
+        return;
+    };
+
+    if cache.len() >= MAX_ENTRIES {
+        let oldest = cache
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(key, _)| key.clone());
+        if let Some(oldest) = oldest {
+            cache.remove(&oldest);
+        }
+    }
+
+    cache.insert(
+        CacheKey::new(path, match_line, context_lines, query),
+        CacheEntry {
+            fingerprint,
+            snippet,
+            last_used: Instant::now(),
+        },
+    );
+}
+
+/// 文件变化后移除该文件的所有缓存片段
+pub fn invalidate_file(path: &Path) {
+    if let Ok(mut cache) = SNIPPET_CACHE.lock() {
+        cache.retain(|key, _| key.path != path);
+    }
+}
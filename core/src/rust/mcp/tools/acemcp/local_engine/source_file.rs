@@ -0,0 +1,114 @@
+//! 搜索结果的源文件读取
+//!
+//! 提取片段和上下文需要按行访问源文件。文件一次性读入，
+//! 打开时建立行偏移索引，同一结果的多次提取共用一份，减少系统调用和内存分配
+
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use anyhow::Result;
+
+/// 只读源文件与行偏移索引
+pub struct SourceFile {
+    /// 文件全文（读入后与磁盘上的文件无关，编辑器改写或截断文件不影响已打开的内容）
+    content: String,
+    /// 每行起始字节偏移
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    /// 打开文件；非 UTF-8 文件与 `fs::read_to_string` 一样返回错误
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+
+        let mut content = String::with_capacity(len as usize);
+        BufReader::new(file).read_to_string(&mut content)?;
+
+        Ok(Self::from_text(content))
+    }
+
+    fn from_text(content: String) -> Self {
+        let line_starts = index_lines(&content);
+        Self { content, line_starts }
+    }
+
+    /// 文件全文
+    pub fn text(&self) -> &str {
+        &self.content
+    }
+
+    /// 按行切分（与 `str::lines` 一致，去掉行尾的 `\n` / `\r\n`）
+    pub fn lines(&self) -> Vec<&str> {
+        let text = self.text();
+        self.line_starts
+            .iter()
+            .enumerate()
+            .map(|(i, &start)| {
+                let end = self.line_starts.get(i + 1).copied().unwrap_or(text.len());
+                let line = &text[start..end];
+                let line = line.strip_suffix('\n').unwrap_or(line);
+                line.strip_suffix('\r').unwrap_or(line)
+            })
+            .collect()
+    }
+}
+
+/// 行起始偏移；末尾换行之后不再计一行
+fn index_lines(text: &str) -> Vec<usize> {
+    if text.is_empty() {
+        return Vec::new();
+    }
+    std::iter::once(0)
+        .chain(
+            text.bytes()
+                .enumerate()
+                .filter(|&(i, b)| b == b'\n' && i + 1 < text.len())
+                .map(|(i, _)| i + 1),
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines_of(text: &str) -> Vec<String> {
+        SourceFile::from_text(text.to_string())
+            .lines()
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn index_lines_handles_empty_and_trailing_newline() {
+        assert!(index_lines("").is_empty());
+        assert_eq!(index_lines("a"), vec![0]);
+        assert_eq!(index_lines("a\nb"), vec![0, 2]);
+        // 末尾换行之后不再计一行
+        assert_eq!(index_lines("a\nb\n"), vec![0, 2]);
+        assert_eq!(index_lines("\n\n"), vec![0, 1]);
+    }
+
+    #[test]
+    fn lines_match_str_lines() {
+        for text in ["", "one", "one\ntwo", "one\ntwo\n", "one\r\ntwo\r\n", "a\r\n\r\nb", "\n"] {
+            let expected: Vec<String> = text.lines().map(str::to_string).collect();
+            assert_eq!(lines_of(text), expected, "text: {:?}", text);
+        }
+    }
+
+    #[test]
+    fn open_reads_crlf_file() {
+        let path = std::env::temp_dir().join(format!("neurospec-source-file-{}.rs", std::process::id()));
+        std::fs::write(&path, "fn a() {}\r\nfn b() {}\r\n").unwrap();
+
+        let file = SourceFile::open(&path).unwrap();
+        assert_eq!(file.lines(), vec!["fn a() {}", "fn b() {}"]);
+        assert_eq!(file.text(), "fn a() {}\r\nfn b() {}\r\n");
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,329 @@
+//! 正则符号定义模式（ctags 不可用时的符号搜索回退）
+//!
+//! 按语言维护符号定义行的正则模板，`{name}` 为符号名占位（代入前会转义）。
+//! 搜索时先把所有模板合并为一个正则交给 ripgrep 筛选候选行，再按候选文件的语言
+//! 用该语言自己的模板确认并判断符号种类，避免一种语言的写法在另一种语言中误报。
+//!
+//! 项目可在 `.neurospec/search.toml` 中追加模式（见 [`SymbolPatternConfig`]），
+//! 用于内置表未覆盖的语言或写法。
+
+use std::path::Path;
+
+use regex::Regex;
+use serde::Deserialize;
+
+use super::types::{language_of_path, normalize_language, normalize_symbol_kind, project_symbol_patterns};
+
+/// 符号名占位符
+const NAME_PLACEHOLDER: &str = "{name}";
+
+/// 内置模式：适用的语言（可过滤语言的规范名），以及（符号种类, 正则模板）列表
+///
+/// 同一行匹配多个模板时取第一个的种类，因此更具体的模板放在前面（如 `enum class` 在 `class` 之前）
+const BUILTIN_PATTERNS: &[(&[&str], &[(Option<&str>, &str)])] = &[
+    (
+        &["rust"],
+        &[
+            (Some("function"), r"fn\s+{name}\s*[(<]"),
+            (Some("struct"), r"struct\s+{name}\s*[{(<;]"),
+            (Some("enum"), r"enum\s+{name}\s*[{<]"),
+            (Some("interface"), r"trait\s+{name}\s*[{<:]"),
+            (None, r"type\s+{name}\s*[=<]"),
+            (None, r"(?:const|static)\s+{name}\s*:"),
+        ],
+    ),
+    (
+        &["typescript", "javascript", "vue", "svelte"],
+        &[
+            (Some("function"), r"function\*?\s+{name}\s*[(<]"),
+            (Some("class"), r"class\s+{name}\b"),
+            (Some("interface"), r"interface\s+{name}\b"),
+            (Some("enum"), r"enum\s+{name}\b"),
+            (None, r"type\s+{name}\s*[=<]"),
+            (None, r"export\s+(?:const|let|var)\s+{name}\s*[=:]"),
+        ],
+    ),
+    (
+        &["python"],
+        &[
+            (Some("function"), r"def\s+{name}\s*\("),
+            (Some("class"), r"class\s+{name}\s*[(:]"),
+        ],
+    ),
+    (
+        &["go"],
+        &[
+            (Some("function"), r"func\s+(?:\([^)]*\)\s*)?{name}\s*[(\[]"),
+            (Some("struct"), r"type\s+{name}\s+struct\b"),
+            (Some("interface"), r"type\s+{name}\s+interface\b"),
+            (None, r"type\s+{name}\b"),
+        ],
+    ),
+    (
+        &["java"],
+        &[
+            (Some("enum"), r"enum\s+{name}\b"),
+            (Some("class"), r"(?:class|record)\s+{name}\b"),
+            (Some("interface"), r"interface\s+{name}\b"),
+            (
+                Some("method"),
+                r"(?:(?:public|private|protected|static|final|abstract|synchronized|native|default)\s+)+[\w<>\[\],.?]+\s+{name}\s*\(",
+            ),
+        ],
+    ),
+    (
+        &["c", "cpp"],
+        &[
+            (Some("enum"), r"enum\s+(?:class\s+)?{name}\b"),
+            (Some("struct"), r"struct\s+{name}\s*[{:]"),
+            (Some("class"), r"class\s+{name}\s*[{:]"),
+            (Some("function"), r"^[\w:*&<>,\s]*[\s*&]{name}\s*\([^;]*$"),
+            (None, r"typedef\s.*\b{name}\s*;"),
+            (None, r"#define\s+{name}\b"),
+        ],
+    ),
+    (
+        &["kotlin"],
+        &[
+            (Some("function"), r"fun\s+(?:<[^>]+>\s*)?(?:[\w.]+\.)?{name}\s*\("),
+            (Some("enum"), r"enum\s+class\s+{name}\b"),
+            (Some("interface"), r"interface\s+{name}\b"),
+            (Some("class"), r"(?:class|object)\s+{name}\b"),
+            (None, r"typealias\s+{name}\b"),
+        ],
+    ),
+    (
+        &["swift"],
+        &[
+            (Some("function"), r"func\s+{name}\s*[(<]"),
+            (Some("class"), r"(?:class|actor)\s+{name}\b"),
+            (Some("struct"), r"struct\s+{name}\b"),
+            (Some("enum"), r"enum\s+{name}\b"),
+            (Some("interface"), r"protocol\s+{name}\b"),
+            (None, r"typealias\s+{name}\b"),
+        ],
+    ),
+    (
+        &["csharp"],
+        &[
+            (Some("class"), r"(?:class|record)\s+{name}\b"),
+            (Some("struct"), r"struct\s+{name}\b"),
+            (Some("interface"), r"interface\s+{name}\b"),
+            (Some("enum"), r"enum\s+{name}\b"),
+            (
+                Some("method"),
+                r"(?:(?:public|private|protected|internal|static|async|override|virtual|abstract|sealed|extern|unsafe|new)\s+)+[\w<>\[\],.?]+\s+{name}\s*(?:<[^>]*>)?\s*\(",
+            ),
+        ],
+    ),
+    (
+        &["ruby"],
+        &[
+            (Some("function"), r"def\s+(?:self\.)?{name}(?:[\s(;]|$)"),
+            (Some("class"), r"class\s+{name}(?:[\s<;]|$)"),
+            (None, r"module\s+{name}(?:[\s;]|$)"),
+        ],
+    ),
+    (
+        &["php"],
+        &[
+            (Some("function"), r"function\s+&?{name}\s*\("),
+            (Some("enum"), r"enum\s+{name}\b"),
+            (Some("class"), r"class\s+{name}\b"),
+            (Some("interface"), r"(?:interface|trait)\s+{name}\b"),
+        ],
+    ),
+    (
+        &["scala"],
+        &[
+            (Some("function"), r"def\s+{name}\b"),
+            (Some("enum"), r"enum\s+{name}\b"),
+            (Some("class"), r"(?:class|object)\s+{name}\b"),
+            (Some("interface"), r"trait\s+{name}\b"),
+            (None, r"type\s+{name}\b"),
+        ],
+    ),
+];
+
+/// `.neurospec/search.toml` 中追加的符号模式
+///
+/// ```toml
+/// [[symbol_patterns]]
+/// language = "elixir"
+/// extensions = ["ex", "exs"]
+/// kind = "function"
+/// pattern = 'def\s+{name}\b'
+/// ```
+///
+/// `language` 为可过滤的语言时追加到该语言，否则按 `extensions` 识别文件；
+/// `kind` 可省略（不参与符号种类过滤）
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SymbolPatternConfig {
+    pub language: String,
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    #[serde(default)]
+    pub kind: Option<String>,
+    pub pattern: String,
+}
+
+/// 单条模式
+#[derive(Debug, Clone)]
+struct SymbolPattern {
+    /// 可过滤语言的规范名，或自定义语言名
+    language: String,
+    /// 自定义语言的扩展名（内置语言为空，按 [`language_of_path`] 识别）
+    extensions: Vec<String>,
+    kind: Option<String>,
+    template: String,
+}
+
+/// 一次搜索使用的模式表（内置 + 项目追加）
+#[derive(Debug, Clone)]
+pub struct SymbolPatterns {
+    patterns: Vec<SymbolPattern>,
+}
+
+/// 为某个符号名实例化的模式
+pub struct SymbolMatcher {
+    /// 交给 ripgrep 的合并正则
+    combined: String,
+    /// （语言, 扩展名, 种类, 正则）
+    compiled: Vec<(String, Vec<String>, Option<String>, Regex)>,
+}
+
+impl SymbolPatterns {
+    /// 内置模式加上项目配置中追加的模式
+    pub fn for_project(project_root: &Path) -> Self {
+        let mut patterns = Self::builtin();
+        for config in project_symbol_patterns(project_root) {
+            if !config.pattern.contains(NAME_PLACEHOLDER) {
+                crate::log_important!(
+                    warn,
+                    "Ignoring symbol pattern for {} without {}: {}",
+                    config.language,
+                    NAME_PLACEHOLDER,
+                    config.pattern
+                );
+                continue;
+            }
+            let language = normalize_language(&config.language)
+                .map(str::to_string)
+                .unwrap_or_else(|| config.language.trim().to_lowercase());
+            patterns.patterns.push(SymbolPattern {
+                language,
+                extensions: config
+                    .extensions
+                    .iter()
+                    .map(|ext| ext.trim_start_matches('.').to_lowercase())
+                    .collect(),
+                kind: config.kind.as_deref().map(|kind| normalize_symbol_kind(kind).unwrap_or(kind).to_string()),
+                template: config.pattern,
+            });
+        }
+        patterns
+    }
+
+    fn builtin() -> Self {
+        let patterns = BUILTIN_PATTERNS
+            .iter()
+            .flat_map(|(languages, patterns)| {
+                languages.iter().flat_map(move |language| {
+                    patterns.iter().map(move |(kind, template)| SymbolPattern {
+                        language: language.to_string(),
+                        extensions: Vec::new(),
+                        kind: kind.map(str::to_string),
+                        template: template.to_string(),
+                    })
+                })
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    /// 自定义语言的扩展名（需要追加到 ripgrep 的文件类型中）
+    ///
+    /// `languages` 非空时只返回其中语言的扩展名
+    pub fn extra_extensions(&self, languages: &[&str]) -> Vec<String> {
+        let mut extensions: Vec<String> = self
+            .patterns
+            .iter()
+            .filter(|p| languages.is_empty() || languages.contains(&p.language.as_str()))
+            .flat_map(|p| p.extensions.iter().cloned())
+            .collect();
+        extensions.sort();
+        extensions.dedup();
+        extensions
+    }
+
+    /// 代入符号名，只保留 `kinds` 中的种类（为空时保留全部）和 `languages` 中的语言（为空时保留全部）
+    ///
+    /// 没有可用模式时返回 None
+    pub fn matcher(&self, name: &str, kinds: &[&str], languages: &[&str]) -> Option<SymbolMatcher> {
+        let escaped = regex::escape(name);
+        let mut compiled = Vec::new();
+        let mut alternatives: Vec<String> = Vec::new();
+
+        for pattern in &self.patterns {
+            if !kinds.is_empty() && !pattern.kind.as_deref().is_some_and(|kind| kinds.contains(&kind)) {
+                continue;
+            }
+            if !languages.is_empty() && !languages.contains(&pattern.language.as_str()) {
+                continue;
+            }
+
+            let source = pattern.template.replace(NAME_PLACEHOLDER, &escaped);
+            let regex = match Regex::new(&source) {
+                Ok(regex) => regex,
+                Err(e) => {
+                    crate::log_important!(warn, "Ignoring invalid symbol pattern {}: {}", pattern.template, e);
+                    continue;
+                }
+            };
+            if !alternatives.contains(&source) {
+                alternatives.push(source);
+            }
+            compiled.push((pattern.language.clone(), pattern.extensions.clone(), pattern.kind.clone(), regex));
+        }
+
+        if compiled.is_empty() {
+            return None;
+        }
+        Some(SymbolMatcher {
+            combined: alternatives.iter().map(|source| format!("(?:{})", source)).collect::<Vec<_>>().join("|"),
+            compiled,
+        })
+    }
+}
+
+impl SymbolMatcher {
+    /// 交给 ripgrep 筛选候选行的合并正则
+    pub fn combined_pattern(&self) -> &str {
+        &self.combined
+    }
+
+    /// 按文件语言确认候选行是符号定义，返回符号种类（模式未标注种类时为 `Some(None)`）
+    ///
+    /// 文件语言不在模式表中时，任一语言的模式匹配即可
+    pub fn classify(&self, path: &str, line: &str) -> Option<Option<&str>> {
+        let path = Path::new(path);
+        let extension = path.extension().and_then(|ext| ext.to_str()).map(str::to_lowercase);
+        let builtin_language = language_of_path(path);
+
+        let in_language = |language: &str, extensions: &[String]| {
+            builtin_language == Some(language)
+                || extension.as_ref().is_some_and(|ext| extensions.contains(ext))
+        };
+        let known = self
+            .compiled
+            .iter()
+            .any(|(language, extensions, _, _)| in_language(language, extensions));
+
+        self.compiled
+            .iter()
+            .filter(|(language, extensions, _, _)| !known || in_language(language, extensions))
+            .find(|(_, _, _, regex)| regex.is_match(line))
+            .map(|(_, _, kind, _)| kind.as_deref())
+    }
+}
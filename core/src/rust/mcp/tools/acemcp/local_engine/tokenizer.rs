@@ -0,0 +1,178 @@
+//! 标识符感知的分词器
+//!
+//! 在 `SimpleTokenizer` 的基础上把标识符按 camelCase / PascalCase / snake_case 拆成子词：
+//! `getUserById`、`get_user_by_id` 都被拆为 `get user by id`，因此查询 "user id" 能同时命中两者。
+//!
+//! - 索引时（[`CODE_TOKENIZER`]）同时保留完整标识符，位置与第一个子词相同，整词查询仍可命中
+//! - 查询时（[`CODE_QUERY_TOKENIZER`]）只输出子词，多个子词按短语匹配
+
+use tantivy::schema::{Field, FieldType};
+use tantivy::tokenizer::{LowerCaser, RemoveLongFilter, TextAnalyzer, Token, TokenStream, Tokenizer};
+use tantivy::Index;
+
+/// 索引时使用的分词器名（写入 schema）
+pub const CODE_TOKENIZER: &str = "code";
+/// 查询时使用的分词器名
+pub const CODE_QUERY_TOKENIZER: &str = "code_query";
+
+/// 与 tantivy 默认分词器一致的超长词项上限
+const MAX_TOKEN_LEN: usize = 40;
+
+/// 在索引上注册代码分词器
+///
+/// 分词器不随索引持久化，每次打开索引（写入或查询）都必须注册
+pub fn register_code_tokenizers(index: &Index) {
+    index.tokenizers().register(CODE_TOKENIZER, code_analyzer(true));
+    index.tokenizers().register(CODE_QUERY_TOKENIZER, code_analyzer(false));
+}
+
+fn code_analyzer(keep_original: bool) -> TextAnalyzer {
+    TextAnalyzer::builder(CodeTokenizer { keep_original })
+        .filter(RemoveLongFilter::limit(MAX_TOKEN_LEN))
+        .filter(LowerCaser)
+        .build()
+}
+
+/// 查询某个字段时使用的分析器
+///
+/// 使用代码分词器的字段改用只输出子词的查询分词器，其余字段与索引时一致
+pub fn query_analyzer(index: &Index, field: Field) -> tantivy::Result<TextAnalyzer> {
+    let schema = index.schema();
+    let uses_code_tokenizer = match schema.get_field_entry(field).field_type() {
+        FieldType::Str(options) => options
+            .get_indexing_options()
+            .is_some_and(|indexing| indexing.tokenizer() == CODE_TOKENIZER),
+        _ => false,
+    };
+
+    if uses_code_tokenizer {
+        if let Some(analyzer) = index.tokenizers().get(CODE_QUERY_TOKENIZER) {
+            return Ok(analyzer);
+        }
+    }
+    index.tokenizer_for_field(field)
+}
+
+/// 按标识符拆分的分词器
+#[derive(Clone, Default)]
+pub struct CodeTokenizer {
+    /// 是否在子词之外保留完整标识符
+    keep_original: bool,
+}
+
+pub struct CodeTokenStream {
+    tokens: Vec<Token>,
+    index: usize,
+}
+
+impl Tokenizer for CodeTokenizer {
+    type TokenStream<'a> = CodeTokenStream;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> CodeTokenStream {
+        let mut tokens = Vec::new();
+        let mut position = 0;
+
+        for (start, word) in words(text) {
+            let parts = split_identifier(word);
+            if self.keep_original && parts.len() > 1 {
+                tokens.push(Token {
+                    offset_from: start,
+                    offset_to: start + word.len(),
+                    position,
+                    text: word.to_string(),
+                    position_length: parts.len(),
+                });
+            }
+            for (from, to) in parts {
+                tokens.push(Token {
+                    offset_from: start + from,
+                    offset_to: start + to,
+                    position,
+                    text: word[from..to].to_string(),
+                    position_length: 1,
+                });
+                position += 1;
+            }
+        }
+
+        CodeTokenStream { tokens, index: 0 }
+    }
+}
+
+impl TokenStream for CodeTokenStream {
+    fn advance(&mut self) -> bool {
+        if self.index < self.tokens.len() {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.tokens[self.index - 1]
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.tokens[self.index - 1]
+    }
+}
+
+/// 文本中的标识符（字母、数字、下划线组成的连续片段）及其字节偏移
+fn words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    let mut rest = text.char_indices().peekable();
+
+    std::iter::from_fn(move || {
+        let (start, _) = rest.by_ref().find(|&(_, c)| is_word_char(c))?;
+        let mut end = text.len();
+        while let Some(&(offset, c)) = rest.peek() {
+            if !is_word_char(c) {
+                end = offset;
+                break;
+            }
+            rest.next();
+        }
+        Some((start, &text[start..end]))
+    })
+}
+
+/// 把标识符拆成子词的字节区间
+///
+/// 在下划线处、小写或数字到大写处、连续大写到「大写 + 小写」处断开：
+/// `parseHTTPResponse_v2` -> `parse` `HTTP` `Response` `v2`
+pub fn split_identifier(word: &str) -> Vec<(usize, usize)> {
+    let chars: Vec<(usize, char)> = word.char_indices().collect();
+    let mut parts = Vec::new();
+    let mut start: Option<usize> = None;
+
+    for (i, &(offset, c)) in chars.iter().enumerate() {
+        if c == '_' {
+            if let Some(from) = start.take() {
+                parts.push((from, offset));
+            }
+            continue;
+        }
+
+        let Some(from) = start else {
+            start = Some(offset);
+            continue;
+        };
+
+        let prev = chars[i - 1].1;
+        let next = chars.get(i + 1).map(|&(_, c)| c);
+        let boundary = c.is_uppercase()
+            && (prev.is_lowercase()
+                || prev.is_numeric()
+                || (prev.is_uppercase() && next.is_some_and(|n| n.is_lowercase())));
+        if boundary {
+            parts.push((from, offset));
+            start = Some(offset);
+        }
+    }
+
+    if let Some(from) = start {
+        parts.push((from, word.len()));
+    }
+    parts
+}
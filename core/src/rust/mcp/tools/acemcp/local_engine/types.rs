@@ -1,6 +1,9 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use serde::{Serialize, Deserialize};
 
+use super::file_limits::FileLimits;
+use super::symbol_patterns::SymbolPatternConfig;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SymbolKind {
     Function,
@@ -45,6 +48,28 @@ pub struct SearchResult {
     /// 匹配信息 (增强)
     #[serde(default)]
     pub match_info: Option<MatchInfo>,
+    /// 分数构成（索引搜索结果）
+    #[serde(default)]
+    pub score_breakdown: Option<ScoreBreakdown>,
+    /// 全部匹配行号（升序；ripgrep 的多处匹配或合并后的命中）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matched_lines: Vec<usize>,
+}
+
+/// 单个结果的分数构成（`rank_explain` 调试输出）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScoreBreakdown {
+    /// Tantivy BM25 分数（已乘字段权重）
+    pub lexical: f32,
+    /// 各字段单独匹配时的分数（字段名, 分数），只在请求 rank_explain 时计算
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<(String, f32)>,
+    /// 嵌入相似度（0-1），未做语义重排序时为 None
+    pub semantic: Option<f32>,
+    /// 计算最终分数时使用的权重
+    pub weights: RankingWeights,
+    /// 最终分数
+    pub total: f32,
 }
 
 /// Snippet 结构化上下文
@@ -75,23 +100,480 @@ pub struct MatchInfo {
     pub match_quality: String,
 }
 
+/// 索引根目录下存放各项目索引的子目录
+const PROJECT_INDEX_DIR: &str = "projects";
+
 #[derive(Debug, Clone)]
 pub struct LocalEngineConfig {
+    /// 索引目录：全局配置中为索引根目录，经 [`LocalEngineConfig::for_project`] 后为项目自己的索引目录
     pub index_path: PathBuf,
     pub max_results: usize,
     pub snippet_context: usize,
+    /// 额外排除的路径模式（gitignore 语法）
+    pub exclude_patterns: Vec<String>,
+    /// 文件大小与内容限制
+    pub file_limits: FileLimits,
+    /// 排序权重（项目可在 `.neurospec/search.toml` 中覆盖，见 [`RankingWeights::for_project`]）
+    pub ranking: RankingWeights,
 }
 
 impl Default for LocalEngineConfig {
     fn default() -> Self {
-        let mut path = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
-        path.push(".acemcp");
-        path.push("local_index");
-        
         Self {
-            index_path: path,
+            index_path: crate::config::profile_local_index_dir(),
             max_results: 10,
             snippet_context: 3,
+            exclude_patterns: Vec::new(),
+            file_limits: FileLimits::default(),
+            ranking: RankingWeights::default(),
+        }
+    }
+}
+
+impl LocalEngineConfig {
+    /// 项目专用的配置：每个项目使用独立的索引目录 `projects/<规范化路径哈希>`，
+    /// 避免不同项目的文档和元数据互相混入
+    pub fn for_project(&self, project_root: &Path) -> Self {
+        let key = crate::utils::path_key(project_root);
+        let name = &crate::mcp::tools::unified_store::persistence::content_hash(key.as_bytes())[..16];
+        Self {
+            index_path: self.index_path.join(PROJECT_INDEX_DIR).join(name),
+            ..self.clone()
+        }
+    }
+}
+
+/// 项目搜索配置文件（相对项目根目录）
+pub const PROJECT_SEARCH_CONFIG: &str = ".neurospec/search.toml";
+
+/// 排序权重
+///
+/// 未限定字段的查询词在各字段上的得分乘以对应权重后相加；嵌入服务可用时，
+/// 最终分数为 `lexical * lexical_weight + semantic * 10 * semantic_weight`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RankingWeights {
+    /// 符号名字段权重
+    pub symbols_boost: f32,
+    /// 路径字段权重（如 auth/login.rs 中的 auth、login）
+    pub path_boost: f32,
+    /// 内容字段权重
+    pub content_boost: f32,
+    /// 语义重排序时 TF-IDF 分数的占比
+    pub lexical_weight: f32,
+    /// 语义重排序时嵌入相似度的占比
+    pub semantic_weight: f32,
+}
+
+impl Default for RankingWeights {
+    fn default() -> Self {
+        Self {
+            symbols_boost: 5.0,
+            path_boost: 2.0,
+            content_boost: 1.0,
+            lexical_weight: 0.6,
+            semantic_weight: 0.4,
+        }
+    }
+}
+
+/// `.neurospec/search.toml`，只覆盖写出的项
+///
+/// ```toml
+/// [ranking]
+/// symbols_boost = 8.0
+/// semantic_weight = 0.5
+///
+/// [[symbol_patterns]]
+/// language = "elixir"
+/// extensions = ["ex", "exs"]
+/// kind = "function"
+/// pattern = 'def\s+{name}\b'
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ProjectSearchConfig {
+    ranking: RankingOverrides,
+    /// 追加的正则符号模式，见 [`super::symbol_patterns`]
+    symbol_patterns: Vec<SymbolPatternConfig>,
+}
+
+/// 项目配置文件中追加的正则符号模式（文件不存在时为空，无效时记录警告并忽略）
+pub fn project_symbol_patterns(project_root: &Path) -> Vec<SymbolPatternConfig> {
+    let path = project_root.join(PROJECT_SEARCH_CONFIG);
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    match toml::from_str::<ProjectSearchConfig>(&content) {
+        Ok(config) => config.symbol_patterns,
+        Err(e) => {
+            crate::log_important!(warn, "Ignoring invalid {}: {}", path.display(), e);
+            Vec::new()
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RankingOverrides {
+    symbols_boost: Option<f32>,
+    path_boost: Option<f32>,
+    content_boost: Option<f32>,
+    lexical_weight: Option<f32>,
+    semantic_weight: Option<f32>,
+}
+
+impl RankingWeights {
+    /// 叠加项目配置文件中的覆盖项；文件不存在时原样返回，无效时记录警告并忽略
+    pub fn for_project(&self, project_root: &Path) -> Self {
+        let path = project_root.join(PROJECT_SEARCH_CONFIG);
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            return *self;
+        };
+        match toml::from_str::<ProjectSearchConfig>(&content) {
+            Ok(config) => self.with_overrides(&config.ranking, &path),
+            Err(e) => {
+                crate::log_important!(warn, "Ignoring invalid {}: {}", path.display(), e);
+                *self
+            }
+        }
+    }
+
+    fn with_overrides(&self, overrides: &RankingOverrides, path: &Path) -> Self {
+        let pick = |name: &str, value: Option<f32>, current: f32| match value {
+            Some(value) if value.is_finite() && value >= 0.0 => value,
+            Some(value) => {
+                crate::log_important!(warn, "Ignoring {} = {} in {}: must be a non-negative number", name, value, path.display());
+                current
+            }
+            None => current,
+        };
+        Self {
+            symbols_boost: pick("symbols_boost", overrides.symbols_boost, self.symbols_boost),
+            path_boost: pick("path_boost", overrides.path_boost, self.path_boost),
+            content_boost: pick("content_boost", overrides.content_boost, self.content_boost),
+            lexical_weight: pick("lexical_weight", overrides.lexical_weight, self.lexical_weight),
+            semantic_weight: pick("semantic_weight", overrides.semantic_weight, self.semantic_weight),
         }
     }
-}
\ No newline at end of file
+}
+
+/// 可过滤的语言：规范名、别名、扩展名
+const FILTER_LANGUAGES: &[(&str, &[&str], &[&str])] = &[
+    ("rust", &["rs"], &["rs"]),
+    ("typescript", &["ts", "tsx"], &["ts", "tsx", "mts", "cts"]),
+    ("javascript", &["js", "jsx"], &["js", "jsx", "mjs", "cjs"]),
+    ("python", &["py"], &["py", "pyi"]),
+    ("go", &["golang"], &["go"]),
+    ("java", &[], &["java"]),
+    ("c", &[], &["c", "h"]),
+    ("cpp", &["c++", "cxx"], &["cpp", "cc", "cxx", "hpp", "hh", "hxx"]),
+    ("vue", &[], &["vue"]),
+    ("svelte", &[], &["svelte"]),
+    ("kotlin", &["kt"], &["kt", "kts"]),
+    ("swift", &[], &["swift"]),
+    ("csharp", &["cs", "c#"], &["cs"]),
+    ("ruby", &["rb"], &["rb"]),
+    ("php", &[], &["php"]),
+    ("scala", &[], &["scala", "sc"]),
+];
+
+/// 未指定语言时 ripgrep 搜索的扩展名
+const DEFAULT_CODE_EXTENSIONS: &str =
+    "rs,ts,tsx,js,jsx,py,go,java,c,cpp,h,hpp,vue,svelte,kt,kts,swift,cs,rb,php,scala,sc";
+
+/// 可过滤的符号种类：规范名与别名（含 ctags 和 snippet 上下文中出现的写法）
+const FILTER_SYMBOL_KINDS: &[(&str, &[&str])] = &[
+    ("function", &["fn", "func", "def", "async function"]),
+    ("method", &["member"]),
+    ("class", &[]),
+    ("struct", &["structure"]),
+    ("interface", &["trait"]),
+    ("enum", &["enumeration"]),
+];
+
+/// 规范化语言名（大小写不敏感，支持 `rs`、`ts` 等别名）
+pub fn normalize_language(name: &str) -> Option<&'static str> {
+    let name = name.trim().to_lowercase();
+    FILTER_LANGUAGES
+        .iter()
+        .find(|(canonical, aliases, _)| *canonical == name || aliases.contains(&name.as_str()))
+        .map(|(canonical, _, _)| *canonical)
+}
+
+/// 按扩展名判断文件所属的可过滤语言
+pub fn language_of_path(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    FILTER_LANGUAGES
+        .iter()
+        .find(|(_, _, extensions)| extensions.contains(&ext.as_str()))
+        .map(|(canonical, _, _)| *canonical)
+}
+
+/// 规范化符号种类（大小写不敏感，`fn`、`trait` 等归入对应种类）
+pub fn normalize_symbol_kind(kind: &str) -> Option<&'static str> {
+    let kind = kind.trim().to_lowercase();
+    FILTER_SYMBOL_KINDS
+        .iter()
+        .find(|(canonical, aliases)| *canonical == kind || aliases.contains(&kind.as_str()))
+        .map(|(canonical, _)| *canonical)
+}
+
+impl SymbolKind {
+    /// 索引中使用的种类名（`Other` 不参与过滤）
+    pub fn filter_name(&self) -> Option<&'static str> {
+        match self {
+            Self::Function => Some("function"),
+            Self::Method => Some("method"),
+            Self::Class => Some("class"),
+            Self::Struct => Some("struct"),
+            Self::Interface => Some("interface"),
+            Self::Enum => Some("enum"),
+            Self::Other => None,
+        }
+    }
+}
+
+/// 查询时过滤条件
+///
+/// 由索引查询（词项/正则过滤）和 ripgrep 回退（`-t` / `-g` 参数）共同使用，
+/// 无法下推的结果（如 ctags 符号）用 [`SearchFilters::matches`] 过滤
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilters {
+    /// 规范化后的语言名，任一匹配即可
+    pub languages: Vec<&'static str>,
+    /// 相对项目根目录的路径 glob（如 `core/src/**/*.rs`）
+    pub path_glob: Option<String>,
+    /// 规范化后的符号种类，文件中含有任一种类即可
+    pub symbol_kinds: Vec<&'static str>,
+    /// path_glob 对应的正则（不含锚点，可直接用于 Tantivy 的 RegexQuery）
+    path_regex: Option<String>,
+    /// 编译后的整路径匹配器
+    path_matcher: Option<regex::Regex>,
+    /// 限定的文件列表（相对路径，如 scope = dirty 时的未提交文件），None 为不限定
+    paths: Option<Vec<String>>,
+}
+
+impl SearchFilters {
+    /// 校验并规范化请求中的过滤条件，未知的语言或符号种类返回错误说明
+    pub fn new(
+        languages: &[String],
+        path_glob: Option<&str>,
+        symbol_kinds: &[String],
+    ) -> std::result::Result<Self, String> {
+        let mut filters = Self::default();
+
+        for language in languages {
+            let canonical = normalize_language(language).ok_or_else(|| {
+                let known: Vec<&str> = FILTER_LANGUAGES.iter().map(|(name, _, _)| *name).collect();
+                format!("Unknown language '{}', expected one of: {}", language, known.join(", "))
+            })?;
+            if !filters.languages.contains(&canonical) {
+                filters.languages.push(canonical);
+            }
+        }
+
+        for kind in symbol_kinds {
+            let canonical = normalize_symbol_kind(kind).ok_or_else(|| {
+                let known: Vec<&str> = FILTER_SYMBOL_KINDS.iter().map(|(name, _)| *name).collect();
+                format!("Unknown symbol kind '{}', expected one of: {}", kind, known.join(", "))
+            })?;
+            if !filters.symbol_kinds.contains(&canonical) {
+                filters.symbol_kinds.push(canonical);
+            }
+        }
+
+        if let Some(glob) = path_glob.map(str::trim).filter(|glob| !glob.is_empty()) {
+            let path_regex = glob_to_regex(glob);
+            let matcher = regex::Regex::new(&format!("^{}$", path_regex))
+                .map_err(|e| format!("Invalid path_glob '{}': {}", glob, e))?;
+            filters.path_glob = Some(glob.to_string());
+            filters.path_regex = Some(path_regex);
+            filters.path_matcher = Some(matcher);
+        }
+
+        Ok(filters)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.languages.is_empty()
+            && self.path_glob.is_none()
+            && self.symbol_kinds.is_empty()
+            && self.paths.is_none()
+    }
+
+    /// 把搜索限定在给定文件中，只保留满足语言和 path_glob 条件的文件
+    pub fn restrict_to_paths(&mut self, paths: Vec<String>) {
+        let mut paths: Vec<String> = paths
+            .into_iter()
+            .map(|path| path.trim_start_matches("./").replace('\\', "/"))
+            .filter(|path| self.matches_path(path))
+            .collect();
+        paths.sort();
+        paths.dedup();
+        self.paths = Some(paths);
+    }
+
+    /// 限定的文件列表（None 为不限定；为空时不会匹配任何文件）
+    pub fn paths(&self) -> Option<&[String]> {
+        self.paths.as_deref()
+    }
+
+    /// path_glob 对应的正则（不含锚点）
+    pub fn path_regex(&self) -> Option<&str> {
+        self.path_regex.as_deref()
+    }
+
+    /// 检查相对路径是否满足语言、path_glob 和文件列表条件
+    pub fn matches_path(&self, rel_path: &str) -> bool {
+        let rel_path = rel_path.trim_start_matches("./").replace('\\', "/");
+        if let Some(paths) = &self.paths {
+            if paths.binary_search(&rel_path).is_err() {
+                return false;
+            }
+        }
+        if !self.languages.is_empty() {
+            match language_of_path(Path::new(&rel_path)) {
+                Some(language) if self.languages.contains(&language) => {}
+                _ => return false,
+            }
+        }
+        self.path_matcher.as_ref().map_or(true, |matcher| matcher.is_match(&rel_path))
+    }
+
+    /// 检查结果是否满足全部条件；未指定符号种类时不要求结果带种类
+    pub fn matches(&self, rel_path: &str, symbol_kind: Option<&str>) -> bool {
+        if !self.matches_path(rel_path) {
+            return false;
+        }
+        self.symbol_kinds.is_empty()
+            || symbol_kind
+                .and_then(normalize_symbol_kind)
+                .map_or(false, |kind| self.symbol_kinds.contains(&kind))
+    }
+
+    /// ripgrep 的文件类型和 glob 参数（`--type-add` / `-t` / `-g`）
+    pub fn ripgrep_args(&self) -> Vec<String> {
+        let extensions = if self.languages.is_empty() {
+            DEFAULT_CODE_EXTENSIONS.to_string()
+        } else {
+            FILTER_LANGUAGES
+                .iter()
+                .filter(|(name, _, _)| self.languages.contains(name))
+                .flat_map(|(_, _, extensions)| extensions.iter().copied())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        let mut args = vec![
+            "--type-add".to_string(),
+            format!("code:*.{{{}}}", extensions),
+            "-t".to_string(),
+            "code".to_string(),
+        ];
+        // 多个 `-g` 之间是“或”的关系，限定文件列表时只传文件（列表已按 path_glob 筛选过）
+        if let Some(paths) = &self.paths {
+            for path in paths {
+                args.push("-g".to_string());
+                args.push(format!("/{}", escape_glob(path)));
+            }
+        } else if let Some(glob) = &self.path_glob {
+            args.push("-g".to_string());
+            args.push(glob.clone());
+        }
+        args
+    }
+}
+
+/// 转义 glob 元字符，使路径按字面匹配
+fn escape_glob(path: &str) -> String {
+    let mut escaped = String::with_capacity(path.len());
+    for c in path.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '{' | '}' | '\\' | '!') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// 将 gitignore 风格的 glob 转为正则（与 ripgrep `-g` 的语义保持一致）
+///
+/// - 不含 `/` 的 glob 匹配任意目录下的文件名
+/// - `**/` 匹配零或多级目录，`*` 和 `?` 不跨越 `/`
+/// - 匹配到目录时包含其下的所有文件
+fn glob_to_regex(glob: &str) -> String {
+    let glob = glob.trim_start_matches("./").trim_start_matches('/');
+    let mut re = String::new();
+    if !glob.trim_end_matches('/').contains('/') {
+        re.push_str("(.*/)?");
+    }
+
+    let mut chars = glob.trim_end_matches('/').chars().peekable();
+    let mut in_braces = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    re.push_str("(.*/)?");
+                } else {
+                    re.push_str(".*");
+                }
+            }
+            '*' => re.push_str("[^/]*"),
+            '?' => re.push_str("[^/]"),
+            '{' if !in_braces => {
+                in_braces = true;
+                re.push('(');
+            }
+            '}' if in_braces => {
+                in_braces = false;
+                re.push(')');
+            }
+            ',' if in_braces => re.push('|'),
+            '[' => {
+                let mut class = String::new();
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == ']' && !class.is_empty() {
+                        closed = true;
+                        break;
+                    }
+                    class.push(c);
+                }
+                if closed {
+                    re.push('[');
+                    match class.strip_prefix('!') {
+                        Some(rest) => {
+                            re.push('^');
+                            re.push_str(&class_escape(rest));
+                        }
+                        None => re.push_str(&class_escape(&class)),
+                    }
+                    re.push(']');
+                } else {
+                    re.push_str(&regex::escape(&format!("[{}", class)));
+                }
+            }
+            c => re.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    if in_braces {
+        re.push(')');
+    }
+
+    re.push_str("(/.*)?");
+    re
+}
+
+/// 转义字符类中除范围符 `-` 以外的特殊字符
+fn class_escape(class: &str) -> String {
+    class
+        .chars()
+        .map(|c| match c {
+            '\\' | '[' | ']' | '^' | '&' | '~' => format!("\\{}", c),
+            c => c.to_string(),
+        })
+        .collect()
+}
@@ -1,20 +1,44 @@
 //! 代码向量存储
 //!
-//! 存储代码文件的嵌入向量，用于语义搜索
+//! 按代码块存储嵌入向量，用于语义搜索。每个文件有一个文件级块（文件摘要），
+//! 另外每个函数 / 方法 / 类各一个块，记录字节区间和行范围，语义搜索可以直接定位到定义
 
 use anyhow::Result;
-use rusqlite::{Connection, params};
+use rusqlite::{Connection, Row, params};
 use std::path::PathBuf;
 use std::sync::Mutex;
 
-/// 代码向量条目
+/// 查询代码块时读取的列（顺序与 [`CodeVectorStore::row_to_entry`] 对应）
+const ENTRY_COLUMNS: &str = "file_path, symbols, summary, embedding, dimension, updated_at, \
+     start_byte, end_byte, start_line, end_line, kind, file_mtime";
+
+/// 代码向量条目（一个代码块）
 #[derive(Debug, Clone)]
 pub struct CodeVectorEntry {
     pub file_path: String,
+    /// 块中定义的符号名（文件级块为文件中的全部符号）
     pub symbols: Vec<String>,
+    /// 用于嵌入的文本（文件级块为文件摘要，定义块为位置、名称和截断的源码）
     pub summary: String,
     pub embedding: Vec<f32>,
     pub updated_at: i64,
+    /// 块在文件中的字节区间 [start_byte, end_byte)，文件级块覆盖整个文件
+    pub start_byte: usize,
+    pub end_byte: usize,
+    /// 块的行范围（1-based，含两端）
+    pub start_line: usize,
+    pub end_line: usize,
+    /// 定义种类（function / class 等），文件级块为 None
+    pub kind: Option<String>,
+    /// 切分时文件的修改时间（Unix 秒），变化后重新切分
+    pub file_mtime: i64,
+}
+
+impl CodeVectorEntry {
+    /// 是否为文件级块
+    pub fn is_file_chunk(&self) -> bool {
+        self.kind.is_none()
+    }
 }
 
 /// 代码向量存储
@@ -27,12 +51,12 @@ impl CodeVectorStore {
     pub fn new(project_root: &PathBuf) -> Result<Self> {
         let store_dir = project_root.join(".neurospec");
         std::fs::create_dir_all(&store_dir)?;
-        
+
         let db_path = store_dir.join("code_vectors.db");
         let conn = Connection::open(&db_path)?;
-        
+
         Self::initialize_schema(&conn)?;
-        
+
         Ok(Self {
             conn: Mutex::new(conn),
         })
@@ -40,228 +64,236 @@ impl CodeVectorStore {
 
     /// 初始化数据库 schema
     fn initialize_schema(conn: &Connection) -> Result<()> {
+        // 旧版按文件存储的向量表，已由 code_chunks 取代（向量会按块重新计算）
+        conn.execute("DROP TABLE IF EXISTS code_vectors", [])?;
+
         conn.execute(
-            "CREATE TABLE IF NOT EXISTS code_vectors (
-                file_path TEXT PRIMARY KEY,
+            "CREATE TABLE IF NOT EXISTS code_chunks (
+                file_path TEXT NOT NULL,
+                start_byte INTEGER NOT NULL,
+                end_byte INTEGER NOT NULL,
+                start_line INTEGER NOT NULL,
+                end_line INTEGER NOT NULL,
+                kind TEXT,
                 symbols TEXT NOT NULL,
                 summary TEXT NOT NULL,
                 embedding BLOB,
                 dimension INTEGER DEFAULT 0,
-                updated_at INTEGER NOT NULL
+                file_mtime INTEGER NOT NULL DEFAULT 0,
+                updated_at INTEGER NOT NULL,
+                PRIMARY KEY (file_path, start_byte, end_byte)
             )",
             [],
         )?;
-        
+
         conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_vectors_updated ON code_vectors(updated_at)",
+            "CREATE INDEX IF NOT EXISTS idx_chunks_updated ON code_chunks(updated_at)",
             [],
         )?;
-        
+
         Ok(())
     }
 
-    /// 保存代码向量
+    /// 保存代码块
     pub fn save(&self, entry: &CodeVectorEntry) -> Result<()> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
-        
-        let symbols_json = serde_json::to_string(&entry.symbols)?;
-        let embedding_blob = Self::vector_to_bytes(&entry.embedding);
-        
-        conn.execute(
-            "INSERT OR REPLACE INTO code_vectors (file_path, symbols, summary, embedding, dimension, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![
-                entry.file_path,
-                symbols_json,
-                entry.summary,
-                embedding_blob,
-                entry.embedding.len() as i64,
-                entry.updated_at
-            ],
-        )?;
-        
-        Ok(())
+        Self::insert_entry(&conn, entry)
     }
 
     /// 批量保存
     pub fn save_batch(&self, entries: &[CodeVectorEntry]) -> Result<usize> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
-        
-        let mut count = 0;
+
         for entry in entries {
-            let symbols_json = serde_json::to_string(&entry.symbols)?;
-            let embedding_blob = Self::vector_to_bytes(&entry.embedding);
-            
-            conn.execute(
-                "INSERT OR REPLACE INTO code_vectors (file_path, symbols, summary, embedding, dimension, updated_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-                params![
-                    entry.file_path,
-                    symbols_json,
-                    entry.summary,
-                    embedding_blob,
-                    entry.embedding.len() as i64,
-                    entry.updated_at
-                ],
-            )?;
-            count += 1;
+            Self::insert_entry(&conn, entry)?;
         }
-        
-        Ok(count)
+
+        Ok(entries.len())
     }
 
-    /// 获取代码向量
-    pub fn get(&self, file_path: &str) -> Result<Option<CodeVectorEntry>> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
-        
-        let result = conn.query_row(
-            "SELECT file_path, symbols, summary, embedding, dimension, updated_at FROM code_vectors WHERE file_path = ?1",
-            params![file_path],
-            |row| {
-                let symbols_json: String = row.get(1)?;
-                let blob: Vec<u8> = row.get(3)?;
-                let dim: i64 = row.get(4)?;
-                
-                Ok((
-                    row.get::<_, String>(0)?,
-                    symbols_json,
-                    row.get::<_, String>(2)?,
-                    blob,
-                    dim,
-                    row.get::<_, i64>(5)?,
-                ))
-            },
-        );
-
-        match result {
-            Ok((file_path, symbols_json, summary, blob, dim, updated_at)) => {
-                let symbols: Vec<String> = serde_json::from_str(&symbols_json).unwrap_or_default();
-                let embedding = Self::bytes_to_vector(&blob, dim as usize);
-                
-                Ok(Some(CodeVectorEntry {
-                    file_path,
-                    symbols,
-                    summary,
-                    embedding,
-                    updated_at,
-                }))
-            }
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e.into()),
+    /// 用新的切分结果替换文件的全部代码块（旧块及其向量一并删除）
+    pub fn replace_file(&self, file_path: &str, entries: &[CodeVectorEntry]) -> Result<usize> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let tx = conn.transaction()?;
+
+        tx.execute("DELETE FROM code_chunks WHERE file_path = ?1", params![file_path])?;
+        for entry in entries {
+            Self::insert_entry(&tx, entry)?;
         }
+        tx.commit()?;
+
+        Ok(entries.len())
     }
 
-    /// 获取所有有向量的条目
-    pub fn get_all_with_vectors(&self) -> Result<Vec<CodeVectorEntry>> {
+    /// 文件切分时记录的修改时间（没有记录时为 None）
+    pub fn file_mtime(&self, file_path: &str) -> Result<Option<i64>> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
-        
-        let mut stmt = conn.prepare(
-            "SELECT file_path, symbols, summary, embedding, dimension, updated_at 
-             FROM code_vectors 
-             WHERE embedding IS NOT NULL AND dimension > 0"
+
+        let mtime = conn.query_row(
+            "SELECT MIN(file_mtime) FROM code_chunks WHERE file_path = ?1",
+            params![file_path],
+            |row| row.get::<_, Option<i64>>(0),
         )?;
-        
-        let rows = stmt.query_map([], |row| {
-            let symbols_json: String = row.get(1)?;
-            let blob: Vec<u8> = row.get(3)?;
-            let dim: i64 = row.get(4)?;
-            
-            Ok((
-                row.get::<_, String>(0)?,
-                symbols_json,
-                row.get::<_, String>(2)?,
-                blob,
-                dim,
-                row.get::<_, i64>(5)?,
-            ))
-        })?;
-        
-        let mut entries = Vec::new();
-        for row in rows {
-            if let Ok((file_path, symbols_json, summary, blob, dim, updated_at)) = row {
-                let symbols: Vec<String> = serde_json::from_str(&symbols_json).unwrap_or_default();
-                let embedding = Self::bytes_to_vector(&blob, dim as usize);
-                
-                entries.push(CodeVectorEntry {
-                    file_path,
-                    symbols,
-                    summary,
-                    embedding,
-                    updated_at,
-                });
-            }
-        }
-        
-        Ok(entries)
+
+        Ok(mtime)
     }
 
-    /// 获取需要计算向量的文件
-    pub fn get_files_without_vectors(&self) -> Result<Vec<String>> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
-        
-        let mut stmt = conn.prepare(
-            "SELECT file_path FROM code_vectors WHERE embedding IS NULL OR dimension = 0"
-        )?;
-        
-        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
-        
-        let mut paths = Vec::new();
-        for row in rows {
-            if let Ok(path) = row {
-                paths.push(path);
-            }
-        }
-        
-        Ok(paths)
+    /// 获取文件的全部代码块（按起始位置排序）
+    pub fn get_file_chunks(&self, file_path: &str) -> Result<Vec<CodeVectorEntry>> {
+        self.query_entries(
+            &format!(
+                "SELECT {} FROM code_chunks WHERE file_path = ?1 ORDER BY start_byte, end_byte DESC",
+                ENTRY_COLUMNS
+            ),
+            params![file_path],
+        )
+    }
+
+    /// 获取所有有向量的代码块
+    pub fn get_all_with_vectors(&self) -> Result<Vec<CodeVectorEntry>> {
+        self.query_entries(
+            &format!(
+                "SELECT {} FROM code_chunks WHERE embedding IS NOT NULL AND dimension > 0",
+                ENTRY_COLUMNS
+            ),
+            [],
+        )
     }
 
-    /// 更新文件的向量
-    pub fn update_embedding(&self, file_path: &str, embedding: &[f32]) -> Result<()> {
+    /// 获取需要计算向量的代码块
+    pub fn get_pending_chunks(&self) -> Result<Vec<CodeVectorEntry>> {
+        self.query_entries(
+            &format!(
+                "SELECT {} FROM code_chunks WHERE embedding IS NULL OR dimension = 0 \
+                 ORDER BY file_path, start_byte",
+                ENTRY_COLUMNS
+            ),
+            [],
+        )
+    }
+
+    /// 更新代码块的向量
+    pub fn update_embedding(&self, entry: &CodeVectorEntry, embedding: &[f32]) -> Result<()> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
-        
+
         let blob = Self::vector_to_bytes(embedding);
         let now = chrono::Utc::now().timestamp();
-        
+
         conn.execute(
-            "UPDATE code_vectors SET embedding = ?1, dimension = ?2, updated_at = ?3 WHERE file_path = ?4",
-            params![blob, embedding.len() as i64, now, file_path],
+            "UPDATE code_chunks SET embedding = ?1, dimension = ?2, updated_at = ?3
+             WHERE file_path = ?4 AND start_byte = ?5 AND end_byte = ?6",
+            params![
+                blob,
+                embedding.len() as i64,
+                now,
+                entry.file_path,
+                entry.start_byte as i64,
+                entry.end_byte as i64
+            ],
         )?;
-        
+
         Ok(())
     }
 
-    /// 删除文件的记录
+    /// 删除文件的全部代码块
     pub fn delete(&self, file_path: &str) -> Result<()> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
-        
-        conn.execute("DELETE FROM code_vectors WHERE file_path = ?1", params![file_path])?;
-        
+
+        conn.execute("DELETE FROM code_chunks WHERE file_path = ?1", params![file_path])?;
+
         Ok(())
     }
 
     /// 清空所有记录
     pub fn clear(&self) -> Result<()> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
-        
-        conn.execute("DELETE FROM code_vectors", [])?;
-        
+
+        conn.execute("DELETE FROM code_chunks", [])?;
+
         Ok(())
     }
 
     /// 获取统计信息
     pub fn stats(&self) -> Result<VectorStoreStats> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
-        
-        let total: i64 = conn.query_row("SELECT COUNT(*) FROM code_vectors", [], |row| row.get(0))?;
-        let with_vectors: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM code_vectors WHERE embedding IS NOT NULL AND dimension > 0", 
-            [], 
-            |row| row.get(0)
+
+        let (total_files, total_chunks): (i64, i64) = conn.query_row(
+            "SELECT COUNT(DISTINCT file_path), COUNT(*) FROM code_chunks",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let (files_with_vectors, chunks_with_vectors): (i64, i64) = conn.query_row(
+            "SELECT COUNT(DISTINCT file_path), COUNT(*) FROM code_chunks
+             WHERE embedding IS NOT NULL AND dimension > 0",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
         )?;
-        
+
         Ok(VectorStoreStats {
-            total_files: total as usize,
-            files_with_vectors: with_vectors as usize,
+            total_files: total_files as usize,
+            files_with_vectors: files_with_vectors as usize,
+            total_chunks: total_chunks as usize,
+            chunks_with_vectors: chunks_with_vectors as usize,
+        })
+    }
+
+    /// 写入（或覆盖）一个代码块
+    fn insert_entry(conn: &Connection, entry: &CodeVectorEntry) -> Result<()> {
+        let symbols_json = serde_json::to_string(&entry.symbols)?;
+        let embedding_blob = Self::vector_to_bytes(&entry.embedding);
+
+        conn.execute(
+            "INSERT OR REPLACE INTO code_chunks (
+                file_path, start_byte, end_byte, start_line, end_line, kind,
+                symbols, summary, embedding, dimension, file_mtime, updated_at
+             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                entry.file_path,
+                entry.start_byte as i64,
+                entry.end_byte as i64,
+                entry.start_line as i64,
+                entry.end_line as i64,
+                entry.kind,
+                symbols_json,
+                entry.summary,
+                embedding_blob,
+                entry.embedding.len() as i64,
+                entry.file_mtime,
+                entry.updated_at
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// 执行查询并转换为代码块（无法解析的行跳过）
+    fn query_entries(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<CodeVectorEntry>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map(params, Self::row_to_entry)?;
+
+        Ok(rows.filter_map(|row| row.ok()).collect())
+    }
+
+    /// 把 [`ENTRY_COLUMNS`] 顺序的查询行转换为代码块
+    fn row_to_entry(row: &Row) -> rusqlite::Result<CodeVectorEntry> {
+        let symbols_json: String = row.get(1)?;
+        let blob: Option<Vec<u8>> = row.get(3)?;
+        let dim: i64 = row.get(4)?;
+
+        Ok(CodeVectorEntry {
+            file_path: row.get(0)?,
+            symbols: serde_json::from_str(&symbols_json).unwrap_or_default(),
+            summary: row.get(2)?,
+            embedding: Self::bytes_to_vector(&blob.unwrap_or_default(), dim as usize),
+            updated_at: row.get(5)?,
+            start_byte: row.get::<_, i64>(6)? as usize,
+            end_byte: row.get::<_, i64>(7)? as usize,
+            start_line: row.get::<_, i64>(8)? as usize,
+            end_line: row.get::<_, i64>(9)? as usize,
+            kind: row.get(10)?,
+            file_mtime: row.get(11)?,
         })
     }
 
@@ -289,4 +321,6 @@ impl CodeVectorStore {
 pub struct VectorStoreStats {
     pub total_files: usize,
     pub files_with_vectors: usize,
+    pub total_chunks: usize,
+    pub chunks_with_vectors: usize,
 }
@@ -4,17 +4,29 @@ use std::path::{Path, PathBuf};
 use std::collections::HashMap;
 use chrono::{DateTime, Utc};
 
-use super::types::{SearchRequest, SearchMode, SearchProfile, SearchScope, SearchScopeKind, SearchError};
-use super::local_engine::{LocalIndexer, LocalEngineConfig, RipgrepSearcher, CtagsIndexer};
+use super::types::{SearchRequest, SearchMode, SearchProfile, SearchScope, SearchScopeKind};
+use super::local_engine::{LocalEngineConfig, IndexingCancelled, IndexingLock, RipgrepSearcher, CtagsIndexer, ScoreBreakdown, SearchFilters};
+use super::local_engine::dedupe::merge_results;
+use super::local_engine::query_syntax::ParsedQuery;
+use super::local_engine::symbol_patterns::SymbolPatterns;
+use super::local_engine::neurospec_ignore::{filter_walker, ripgrep_ignore_args};
+use super::dirty::dirty_files;
+use crate::config::OutputLanguage;
 use crate::log_important;
-use crate::mcp::utils::errors::McpToolError;
+use crate::mcp::utils::errors::{McpToolError, ToolError, ToolErrorCode};
+use crate::mcp::utils::resolve_output_language;
+use crate::tr;
 use crate::mcp::tools::memory::{ChangeTracker, CodeChangeMemory};
 use crate::mcp::tools::unified_store::{
-    create_searcher_for_project, is_search_initialized, get_global_search_config,
-    is_project_indexed, is_project_indexing, mark_indexing_started, mark_indexing_complete,
-    get_index_state, assess_index_health, IndexHealth,
+    acquire_searcher, average_searcher_open_ms, create_searcher_for_project, is_search_initialized, get_global_search_config,
+    is_project_indexed, is_project_indexing, mark_indexing_started, finish_indexing,
+    get_index_state, assess_index_health, IndexHealth, with_global_store, with_live_indexer, default_edit_distance,
+    SymbolFilter, MAX_EDIT_DISTANCE,
 };
 
+/// 模糊符号搜索最多返回的结果数
+const FUZZY_SYMBOL_LIMIT: usize = 20;
+
 // ============================================================================
 // Structure Mode: Project Insight 相关类型和辅助函数
 // ============================================================================
@@ -22,34 +34,34 @@ use crate::mcp::tools::unified_store::{
 /// 项目洞察结果
 #[derive(Debug)]
 #[allow(dead_code)]
-struct ProjectInsight {
+pub(crate) struct ProjectInsight {
     /// 项目名称
-    name: String,
+    pub(crate) name: String,
     /// 项目类型 (e.g., "Rust Library", "TypeScript Web App")
-    project_type: Option<String>,
+    pub(crate) project_type: Option<String>,
     /// 语言分布
-    lang_stats: Vec<(String, usize)>,
+    pub(crate) lang_stats: Vec<(String, usize)>,
     /// 总文件数
-    total_files: usize,
+    pub(crate) total_files: usize,
     /// 模块映射 (路径 -> 描述)
-    module_map: Vec<ModuleEntry>,
+    pub(crate) module_map: Vec<ModuleEntry>,
     /// 依赖关系
     dependencies: Vec<DependencyEdge>,
     /// 核心符号/入口点
     key_symbols: Vec<KeySymbol>,
     /// 外部依赖
-    external_deps: Vec<String>,
+    pub(crate) external_deps: Vec<String>,
 }
 
 /// 模块条目
 #[derive(Debug)]
 #[allow(dead_code)]
-struct ModuleEntry {
-    path: String,
-    depth: usize,
-    is_dir: bool,
+pub(crate) struct ModuleEntry {
+    pub(crate) path: String,
+    pub(crate) depth: usize,
+    pub(crate) is_dir: bool,
     symbol_count: usize,
-    description: Option<String>,
+    pub(crate) description: Option<String>,
 }
 
 /// 依赖边
@@ -85,6 +97,7 @@ impl AcemcpTool {
     /// 2. mode 只作为底层搜索引擎的 hint（Text / Symbol）
     /// 3. StructureOnly 走纯结构路径，不再看 mode
     /// 4. mode = Structure 仅在 profile.is_none() 时兼容旧行为
+    #[tracing::instrument(name = "search.context", skip_all, fields(mode = ?request.mode, profile = ?request.profile))]
     pub async fn search_context(request: SearchRequest) -> Result<CallToolResult, McpToolError> {
         // ====== 阶段 1: 请求预处理 ======
         let project_root = match &request.project_root_path {
@@ -93,7 +106,7 @@ impl AcemcpTool {
                 match detect_project_root() {
                     Some(path) => path,
                     None => {
-                        let err = SearchError::invalid_project_path("<auto-detect failed>");
+                        let err = ToolError::invalid_project_path("<auto-detect failed>");
                         return Ok(crate::mcp::create_error_result(err.to_json()));
                     }
                 }
@@ -102,6 +115,7 @@ impl AcemcpTool {
 
         let project_root_str = project_root.to_string_lossy().to_string();
         let profile = request.profile.clone();
+        let lang = resolve_output_language(request.language);
         
         crate::ui::agents_commands::update_project_path_cache(&project_root_str);
         
@@ -115,10 +129,42 @@ impl AcemcpTool {
         );
         
         if !project_root.exists() {
-            let err = SearchError::invalid_project_path(&project_root_str);
+            let err = ToolError::invalid_project_path(&project_root_str);
             return Ok(crate::mcp::create_error_result(err.to_json()));
         }
 
+        let mut filters = match request.filters() {
+            Ok(filters) => filters,
+            Err(message) => {
+                let err = ToolError::new(ToolErrorCode::InvalidParams, message);
+                return Ok(crate::mcp::create_error_result(err.to_json()));
+            }
+        };
+
+        // scope = dirty：把未提交修改的文件作为路径限定下推到各个引擎
+        if let Some(SearchProfile::SmartStructure { scope: Some(scope), .. }) = &profile {
+            if matches!(scope.kind, SearchScopeKind::Dirty) {
+                match dirty_files(&project_root) {
+                    Ok(files) => filters.restrict_to_paths(files),
+                    Err(e) => {
+                        let err = ToolError::new(
+                            ToolErrorCode::InvalidParams,
+                            format!("scope.kind = dirty requires a git repository: {:#}", e),
+                        );
+                        return Ok(crate::mcp::create_error_result(err.to_json()));
+                    }
+                }
+                if filters.paths().is_some_and(|paths| paths.is_empty()) {
+                    let message = tr!(
+                        lang,
+                        "工作区没有符合条件的未提交修改，scope = dirty 无可搜索的文件",
+                        "No uncommitted changes match the filters; nothing to search with scope = dirty"
+                    );
+                    return Ok(crate::mcp::create_success_result(vec![Content::text(message)]));
+                }
+            }
+        }
+
         // ====== 阶段 2: Profile 决策层（profile 优先生效）======
         
         // 2.1 StructureOnly：直接返回结构概览，不看 mode
@@ -126,8 +172,24 @@ impl AcemcpTool {
             return Self::get_project_structure(&project_root, *max_depth, *max_nodes).await;
         }
 
-        // 2.2 SmartStructure：走独立的 orchestrator 路径
+        // 2.1.1 TestsFor：基于代码图谱和命名约定查找测试，不走文本索引
+        if let Some(SearchProfile::TestsFor { symbol }) = &profile {
+            let symbol = symbol.as_deref().filter(|s| !s.trim().is_empty()).unwrap_or(&request.query);
+            return Self::tests_for_search(&project_root, symbol, &filters, lang).await;
+        }
+
+        // 2.2 模糊符号搜索：直接查询符号存储（SmartStructure 的 scope / max_results 仍然生效）
         let mode = request.mode.clone().unwrap_or(SearchMode::Text);
+        if request.fuzzy.unwrap_or(false) && matches!(mode, SearchMode::Symbol) {
+            return Self::fuzzy_symbol_search(&project_root, &project_root_str, &request, &filters, lang).await;
+        }
+
+        // 2.3 查找引用：基于代码图谱，不走文本索引（SmartStructure 的 scope / max_results 仍然生效）
+        if matches!(mode, SearchMode::References) {
+            return Self::references_search(&project_root, &project_root_str, &request, &filters, lang).await;
+        }
+
+        // 2.4 SmartStructure：走独立的 orchestrator 路径
         if let Some(ref smart_profile) = profile {
             if matches!(smart_profile, SearchProfile::SmartStructure { .. }) {
                 return Self::smart_structure_search(
@@ -136,17 +198,19 @@ impl AcemcpTool {
                     &request,
                     mode,
                     smart_profile,
+                    &filters,
+                    lang,
                 ).await;
             }
         }
 
-        // 2.3 兼容旧调用：仅当 profile 为空时才使用 mode=Structure
+        // 2.5 兼容旧调用：仅当 profile 为空时才使用 mode=Structure
         if profile.is_none() && matches!(mode, SearchMode::Structure) {
             return Self::get_project_structure(&project_root, None, None).await;
         }
         
         // ====== 阶段 3: 旧模式（profile = None）的简单搜索 ======
-        Self::legacy_search(&project_root, &project_root_str, &request, mode).await
+        Self::legacy_search(&project_root, &project_root_str, &request, mode, &filters, lang).await
     }
 
     // ========================================================================
@@ -166,6 +230,8 @@ impl AcemcpTool {
         request: &SearchRequest,
         mode: SearchMode,
         profile: &SearchProfile,
+        filters: &SearchFilters,
+        lang: OutputLanguage,
     ) -> Result<CallToolResult, McpToolError> {
         use crate::mcp::tools::acemcp::types::SearchTrace;
         use std::time::Instant;
@@ -181,10 +247,24 @@ impl AcemcpTool {
         log_important!(info, "SmartStructure orchestrator: mode={:?}", mode);
 
         // 1. 调用统一引擎获取原始结果
-        let raw_results = Self::run_search_engine(project_root, &request.query, mode.clone()).await;
+        let rank_explain = request.rank_explain.unwrap_or(false);
+        let raw_results = Self::run_search_engine(
+            project_root,
+            &request.query,
+            mode.clone(),
+            filters,
+            rank_explain,
+            Some(&mut trace),
+        ).await;
 
         match raw_results {
             Ok(results) => {
+                // 同一函数内的多个命中合并为一个结果
+                let results = if request.dedupe.unwrap_or(true) {
+                    merge_results(results, project_root)
+                } else {
+                    results
+                };
                 trace.result_count = results.len();
                 trace.engine_used = if is_search_initialized() && is_project_indexed(project_root) {
                     "tantivy".to_string()
@@ -201,7 +281,7 @@ impl AcemcpTool {
                     log_important!(info, "SmartStructure search returned no results, trying fallback strategies");
                     trace.duration_ms = start.elapsed().as_millis() as u64;
                     trace.log();
-                    return Self::handle_empty_results(project_root, &request.query, mode).await;
+                    return Self::handle_empty_results(project_root, &request.query, mode, filters, lang).await;
                 }
 
                 trace.result_count = filtered.len();
@@ -215,6 +295,8 @@ impl AcemcpTool {
                     project_root_str,
                     &request.query,
                     mode,
+                    rank_explain,
+                    lang,
                 );
 
                 Ok(crate::mcp::create_success_result(vec![Content::text(formatted)]))
@@ -223,7 +305,7 @@ impl AcemcpTool {
                 trace.engine_used = "failed".to_string();
                 trace.duration_ms = start.elapsed().as_millis() as u64;
                 trace.log();
-                let err = SearchError::search_engine_error(&e);
+                let err = ToolError::search_engine_error(&e);
                 Ok(crate::mcp::create_error_result(err.to_json()))
             }
         }
@@ -236,26 +318,30 @@ impl AcemcpTool {
         project_root: &PathBuf,
         query: &str,
         mode: SearchMode,
+        filters: &SearchFilters,
+        lang: OutputLanguage,
     ) -> Result<CallToolResult, McpToolError> {
-        let mut suggestions = Vec::new();
-        
-        // Step 1: 尝试模糊匹配（简单拼写纠错）
-        if let Some(fuzzy_query) = Self::generate_fuzzy_query(query) {
+        // Step 1: 尝试模糊匹配（按项目符号名纠正拼写）
+        let spelling = Self::spelling_suggestions(project_root, query).await;
+        if let Some(fuzzy_query) = Self::generate_fuzzy_query(query, &spelling) {
             log_important!(info, "Trying fuzzy match: '{}' -> '{}'", query, fuzzy_query);
             
-            let fuzzy_results = Self::run_search_engine(project_root, &fuzzy_query, mode.clone()).await;
+            let fuzzy_results = Self::run_search_engine(project_root, &fuzzy_query, mode.clone(), filters, false, None).await;
             if let Ok(results) = fuzzy_results {
                 if !results.is_empty() {
-                    suggestions.push(format!("未找到 `{}`，您是否要搜索 `{}`？", query, fuzzy_query));
-                    
-                    let formatted = format!(
+                    let formatted = tr!(
+                        lang,
                         "⚠️ **未找到精确匹配，以下是相似结果**\n\n\
                          💡 原查询：`{}`\n\
                          🔍 建议查询：`{}`\n\n\
                          ---\n\n{}",
+                        "⚠️ **No exact match, showing similar results**\n\n\
+                         💡 Original query: `{}`\n\
+                         🔍 Suggested query: `{}`\n\n\
+                         ---\n\n{}",
                         query,
                         fuzzy_query,
-                        Self::format_simple_results(&results, project_root, 5)
+                        Self::format_simple_results(&results, project_root, 5, lang)
                     );
                     return Ok(crate::mcp::create_success_result(vec![Content::text(formatted)]));
                 }
@@ -268,10 +354,14 @@ impl AcemcpTool {
             
             if let Ok(file_results) = Self::search_by_filename(project_root, query).await {
                 if !file_results.is_empty() {
-                    let formatted = format!(
+                    let formatted = tr!(
+                        lang,
                         "⚠️ **未找到内容匹配，但找到了相似文件名**\n\n\
                          💡 查询：`{}`\n\n\
                          ---\n\n{}",
+                        "⚠️ **No content match, but found similar file names**\n\n\
+                         💡 Query: `{}`\n\n\
+                         ---\n\n{}",
                         query,
                         file_results.join("\n")
                     );
@@ -297,57 +387,110 @@ impl AcemcpTool {
             .join("\n");
         
         // 生成搜索建议
-        let query_suggestions = Self::generate_search_suggestions(query, &mode);
+        let query_suggestions = Self::generate_search_suggestions(query, &mode, lang);
         let suggestions_text = if query_suggestions.is_empty() {
             String::new()
         } else {
-            format!("\n💡 **搜索建议**：\n{}\n", query_suggestions.iter()
+            let list = query_suggestions.iter()
                 .map(|s| format!("   - {}", s))
                 .collect::<Vec<_>>()
-                .join("\n"))
+                .join("\n");
+            tr!(lang, "\n💡 **搜索建议**：\n{}\n", "\n💡 **Search tips**:\n{}\n", list)
+        };
+        let did_you_mean_text = if spelling.is_empty() {
+            String::new()
+        } else {
+            let list = spelling.iter()
+                .map(|(word, names)| {
+                    let names: Vec<String> = names.iter().map(|n| format!("`{}`", n)).collect();
+                    format!("   - `{}` → {}", word, names.join(", "))
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            tr!(lang, "\n🔤 **你是不是要找**：\n{}\n", "\n🔤 **Did you mean**:\n{}\n", list)
         };
         
-        let wrapped = format!(
+        let wrapped = tr!(
+            lang,
             "⚠️ **搜索无结果**\n\n\
              查询：`{}`\n\
              模式：{:?}\n{}\
              \n---\n\n\
              📁 **项目结构概览**（供参考）：\n\n{}",
+            "⚠️ **No search results**\n\n\
+             Query: `{}`\n\
+             Mode: {:?}\n{}\
+             \n---\n\n\
+             📁 **Project structure overview** (for reference):\n\n{}",
             query,
             mode,
-            suggestions_text,
+            format!("{}{}", did_you_mean_text, suggestions_text),
             structure_text
         );
         
         Ok(crate::mcp::create_success_result(vec![Content::text(wrapped)]))
     }
     
-    /// 生成模糊查询（简单拼写纠错）
-    fn generate_fuzzy_query(query: &str) -> Option<String> {
-        // 常见拼写错误纠正词典
-        let corrections = [
-            ("strucutre", "structure"),
-            ("fucntion", "function"),
-            ("calss", "class"),
-            ("inteface", "interface"),
-            ("pubic", "public"),
-            ("privte", "private"),
-            ("consturctor", "constructor"),
-            ("retrun", "return"),
-            ("imoprt", "import"),
-            ("exprot", "export"),
-        ];
-        
-        let lower = query.to_lowercase();
-        for (typo, correct) in &corrections {
-            if lower.contains(typo) {
-                return Some(lower.replace(typo, correct));
-            }
-        }
-        
-        None
+    /// 按项目符号名为查询中的词给出拼写纠错建议（词 -> 相近的符号名）
+    ///
+    /// 只检查 4 个字符以上、本身不是符号名的词；符号存储未初始化或项目未索引时返回空
+    async fn spelling_suggestions(project_root: &PathBuf, query: &str) -> Vec<(String, Vec<String>)> {
+        let mut seen = std::collections::HashSet::new();
+        let words: Vec<String> = ParsedQuery::parse(query)
+            .plain_text()
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .filter(|word| word.chars().count() >= 4 && seen.insert(word.to_lowercase()))
+            .map(str::to_string)
+            .collect();
+        if words.is_empty() {
+            return Vec::new();
+        }
+
+        let root = project_root.clone();
+        let suggestions = tokio::task::spawn_blocking(move || {
+            with_global_store(|store| {
+                let mut suggestions = Vec::new();
+                for word in words {
+                    let names: Vec<String> = store
+                        .did_you_mean(&root, &word, 3)?
+                        .into_iter()
+                        .map(|suggestion| suggestion.name)
+                        .collect();
+                    if !names.is_empty() {
+                        suggestions.push((word, names));
+                    }
+                }
+                Ok(suggestions)
+            })
+        })
+        .await;
+
+        match suggestions {
+            Ok(Ok(suggestions)) => suggestions,
+            Ok(Err(e)) => {
+                crate::log_debug!("Spelling suggestions unavailable: {}", e);
+                Vec::new()
+            }
+            Err(_) => Vec::new(),
+        }
     }
-    
+
+    /// 生成模糊查询：把每个拼错的词替换为最接近的符号名
+    fn generate_fuzzy_query(query: &str, spelling: &[(String, Vec<String>)]) -> Option<String> {
+        let mut corrected = query.to_string();
+        for (word, names) in spelling {
+            let Some(best) = names.first() else {
+                continue;
+            };
+            let Ok(pattern) = regex::Regex::new(&format!(r"\b{}\b", regex::escape(word))) else {
+                continue;
+            };
+            corrected = pattern.replace_all(&corrected, regex::NoExpand(best)).into_owned();
+        }
+
+        (corrected != query).then_some(corrected)
+    }
+
     /// 检查查询是否像路径
     fn looks_like_path(query: &str) -> bool {
         query.contains('/') || query.contains('\\') || query.contains(".rs") 
@@ -387,28 +530,36 @@ impl AcemcpTool {
     }
     
     /// 生成搜索建议
-    fn generate_search_suggestions(query: &str, mode: &SearchMode) -> Vec<String> {
+    fn generate_search_suggestions(query: &str, mode: &SearchMode, lang: OutputLanguage) -> Vec<String> {
         let mut suggestions = Vec::new();
         
         // 基于查询长度的建议
         if query.len() < 3 {
-            suggestions.push("查询词过短，建议使用至少 3 个字符".to_string());
+            suggestions.push(tr!(
+                lang,
+                "查询词过短，建议使用至少 3 个字符",
+                "The query is too short; use at least 3 characters"
+            ));
         }
         
         // 基于模式的建议
         match mode {
             SearchMode::Symbol if !query.chars().next().map(|c| c.is_alphanumeric()).unwrap_or(false) => {
-                suggestions.push("符号搜索建议使用字母或数字开头".to_string());
+                suggestions.push(tr!(
+                    lang,
+                    "符号搜索建议使用字母或数字开头",
+                    "Symbol queries should start with a letter or digit"
+                ));
             }
             SearchMode::Text if query.split_whitespace().count() == 1 => {
-                suggestions.push("尝试使用多个关键词或相关术语".to_string());
+                suggestions.push(tr!(lang, "尝试使用多个关键词或相关术语", "Try several keywords or related terms"));
             }
             _ => {}
         }
         
         // 通用建议
         if query.chars().all(|c| c.is_lowercase()) {
-            suggestions.push("尝试使用驼峰命名或首字母大写".to_string());
+            suggestions.push(tr!(lang, "尝试使用驼峰命名或首字母大写", "Try camelCase or capitalized names"));
         }
         
         suggestions
@@ -419,11 +570,12 @@ impl AcemcpTool {
         results: &[crate::mcp::tools::acemcp::local_engine::types::SearchResult],
         _project_root: &PathBuf,
         limit: usize,
+        lang: OutputLanguage,
     ) -> String {
         let mut formatted = String::new();
         
         for (i, res) in results.iter().take(limit).enumerate() {
-            formatted.push_str(&format!("{}. **{}** (行 {})\n", i + 1, res.path, res.line_number));
+            formatted.push_str(&tr!(lang, "{}. **{}** (行 {})\n", "{}. **{}** (line {})\n", i + 1, res.path, res.line_number));
             formatted.push_str("```\n");
             formatted.push_str(&res.snippet.lines().take(5).collect::<Vec<_>>().join("\n"));
             formatted.push_str("\n```\n\n");
@@ -439,6 +591,8 @@ impl AcemcpTool {
         project_root_str: &str,
         query: &str,
         mode: SearchMode,
+        rank_explain: bool,
+        lang: OutputLanguage,
     ) -> String {
         let mut formatted = String::new();
 
@@ -454,8 +608,13 @@ impl AcemcpTool {
             formatted.push_str(&format!("[Index: {} | Files: {}]\n", status, state.file_count));
         }
 
-        let mode_str = match mode { SearchMode::Text => "Text", SearchMode::Symbol => "Symbol", SearchMode::Structure => "Structure" };
-        formatted.push_str(&format!("Found {} relevant snippets (Mode: {} | Profile: SmartStructure):\n\n", results.len(), mode_str));
+        let mode_str = match mode { SearchMode::Text => "Text", SearchMode::Symbol => "Symbol", SearchMode::Structure => "Structure", SearchMode::References => "References" };
+        formatted.push_str(&tr!(
+            lang,
+            "找到 {} 个相关片段（模式: {} | 策略: SmartStructure）:\n\n",
+            "Found {} relevant snippets (Mode: {} | Profile: SmartStructure):\n\n",
+            results.len(), mode_str
+        ));
 
         // 批量查询修改历史
         let all_paths: Vec<String> = results.iter().map(|r| r.path.clone()).collect();
@@ -463,10 +622,11 @@ impl AcemcpTool {
 
         for res in results {
             formatted.push_str(&format!("### 📄 `{}` (Score: {:.2})\n", res.path, res.score));
+            formatted.push_str(&Self::format_matched_lines(res, lang));
             
             if let Some(changes) = changes_by_file.get(&res.path) {
                 for change in changes.iter().take(3) {
-                    let ago = Self::format_time_ago(change.created_at);
+                    let ago = Self::format_time_ago(change.created_at, lang);
                     formatted.push_str(&format!("  📝 {} ({})\n", change.summary, ago));
                 }
             }
@@ -502,7 +662,13 @@ impl AcemcpTool {
                     ));
                 }
             }
+            if rank_explain {
+                if let Some(ref breakdown) = res.score_breakdown {
+                    formatted.push_str(&Self::format_score_breakdown(breakdown));
+                }
+            }
             
+
             formatted.push_str("```\n");
             formatted.push_str(&res.snippet);
             formatted.push_str("```\n\n");
@@ -524,8 +690,8 @@ impl AcemcpTool {
         let mut dir_list: Vec<_> = dir_counts.into_iter().collect();
         dir_list.sort_by(|a, b| b.1.cmp(&a.1));
         
-        formatted.push_str("## 📁 匹配分布\n\n");
-        formatted.push_str("| 目录 | 匹配数 |\n");
+        formatted.push_str(&tr!(lang, "## 📁 匹配分布\n\n", "## 📁 Match Distribution\n\n"));
+        formatted.push_str(&tr!(lang, "| 目录 | 匹配数 |\n", "| Directory | Matches |\n"));
         formatted.push_str("|------|--------|\n");
         for (dir, count) in dir_list.iter().take(5) {
             formatted.push_str(&format!("| `{}` | {} |\n", dir, count));
@@ -545,7 +711,7 @@ impl AcemcpTool {
         symbols.dedup_by(|a, b| a.0 == b.0);
         
         if !symbols.is_empty() {
-            formatted.push_str("## 🔗 关键符号\n\n");
+            formatted.push_str(&tr!(lang, "## 🔗 关键符号\n\n", "## 🔗 Key Symbols\n\n"));
             for (name, path, line) in symbols.iter().take(10) {
                 formatted.push_str(&format!("- `{}` (`{}`:{})\n", name, path, line));
             }
@@ -571,6 +737,9 @@ impl AcemcpTool {
         project_root: &PathBuf,
         query: &str,
         mode: SearchMode,
+        filters: &SearchFilters,
+        rank_explain: bool,
+        trace: Option<&mut crate::mcp::tools::acemcp::types::SearchTrace>,
     ) -> Result<Vec<crate::mcp::tools::acemcp::local_engine::types::SearchResult>, String> {
         let is_indexing = is_project_indexing(project_root);
         
@@ -585,37 +754,43 @@ impl AcemcpTool {
         );
 
         if use_tantivy {
-            // Tantivy 路径
-            let searcher = match create_searcher_for_project(project_root) {
-                Ok(s) => s,
+            // Tantivy 路径：优先复用池中的 reader
+            let searcher = match acquire_searcher(project_root) {
+                Ok(lease) => {
+                    if let Some(trace) = trace {
+                        trace.record_reader(lease.pooled, lease.acquire_ms, average_searcher_open_ms());
+                    }
+                    lease.searcher
+                }
                 Err(e) => {
                     log_important!(warn, "Failed to create Tantivy searcher: {}, falling back to ripgrep", e);
-                    return Self::search_with_ripgrep_raw_async(project_root, query, mode).await;
+                    return Self::search_with_ripgrep_raw_async(project_root, query, mode, filters).await;
                 }
             };
 
             let result = match mode {
-                SearchMode::Text => searcher.search_with_embedding(query).await.map_err(|e| e.to_string()),
-                SearchMode::Symbol => searcher.search_symbol(query).map_err(|e| e.to_string()),
-                SearchMode::Structure => unreachable!("Structure mode handled earlier"),
+                SearchMode::Text => searcher.search_with_embedding_filtered(query, filters, rank_explain).await.map_err(|e| e.to_string()),
+                SearchMode::Symbol => searcher.search_symbol_filtered(query, filters).map_err(|e| e.to_string()),
+                SearchMode::Structure | SearchMode::References => unreachable!("Structure and references modes handled earlier"),
             };
             
             // 如果 Tantivy 返回空结果且索引状态为 Degraded，尝试 ripgrep 补充
             match &result {
                 Ok(results) if results.is_empty() && matches!(health, IndexHealth::Degraded { .. }) => {
                     log_important!(info, "Tantivy returned empty, trying ripgrep supplement due to degraded index");
-                    Self::search_with_ripgrep_raw_async(project_root, query, mode).await
+                    Self::search_with_ripgrep_raw_async(project_root, query, mode, filters).await
                 }
                 _ => result,
             }
         } else {
             // Ripgrep 回退路径
-            if !is_indexing {
+            // 项目设置关闭了自动索引时只用 ripgrep
+            if !is_indexing && crate::config::project_settings(project_root).auto_index {
                 Self::ensure_search_initialized();
                 // 触发后台索引（带锁保护）
-                Self::trigger_background_indexing_safe(project_root);
+                Self::trigger_background_indexing_safe(project_root, false);
             }
-            Self::search_with_ripgrep_raw_async(project_root, query, mode).await
+            Self::search_with_ripgrep_raw_async(project_root, query, mode, filters).await
         }
     }
 
@@ -624,12 +799,17 @@ impl AcemcpTool {
         project_root: &PathBuf,
         query: &str,
         mode: SearchMode,
+        filters: &SearchFilters,
     ) -> Result<Vec<crate::mcp::tools::acemcp::local_engine::types::SearchResult>, String> {
         let project_root = project_root.clone();
         let query = query.to_string();
+        let filters = filters.clone();
         
+        // 阻塞线程中没有当前 span，显式带上以保持链路完整
+        let span = tracing::Span::current();
         tokio::task::spawn_blocking(move || {
-            Self::search_with_ripgrep_raw(&project_root, &query, mode)
+            let _entered = span.enter();
+            Self::search_with_ripgrep_raw(&project_root, &query, mode, &filters)
         })
         .await
         .map_err(|e| format!("Task join error: {}", e))?
@@ -642,17 +822,18 @@ impl AcemcpTool {
         project_root: &PathBuf,
         query: &str,
         mode: SearchMode,
+        filters: &SearchFilters,
     ) -> Result<Vec<crate::mcp::tools::acemcp::local_engine::types::SearchResult>, String> {
         // 符号搜索优先使用 ctags
         if matches!(mode, SearchMode::Symbol) && CtagsIndexer::is_available() {
             log_important!(info, "Using ctags for symbol search (raw)");
-            return Self::search_with_ctags_raw(project_root, query);
+            return Self::search_with_ctags_raw(project_root, query, filters);
         }
         
         // 符号模式下，无 ctags 时使用正则符号搜索
         if matches!(mode, SearchMode::Symbol) {
             log_important!(info, "Using regex-based symbol search (ctags not available)");
-            return Self::search_symbols_with_regex(project_root, query);
+            return Self::search_symbols_with_regex(project_root, query, filters);
         }
 
         log_important!(info, "Using ripgrep fallback (raw)");
@@ -661,48 +842,45 @@ impl AcemcpTool {
             return Err("Ripgrep not available and index not ready".to_string());
         }
 
-        let rg_searcher = RipgrepSearcher::new(10, 3);
+        let rg_searcher = RipgrepSearcher::new(10, 3).with_filters(filters.clone());
         rg_searcher.search(project_root, query).map_err(|e| e.to_string())
     }
     
     /// 使用正则表达式搜索符号定义
     /// 
-    /// 当 ctags 不可用时的回退方案，使用 ripgrep + 正则匹配符号定义行
+    /// 当 ctags 不可用时的回退方案：ripgrep 用所有语言的定义模式合并后的正则筛选候选行，
+    /// 再按候选文件的语言确认（模式表见 `local_engine::symbol_patterns`，项目可在 search.toml 中追加）
     fn search_symbols_with_regex(
         project_root: &PathBuf,
         symbol_name: &str,
+        filters: &SearchFilters,
     ) -> Result<Vec<crate::mcp::tools::acemcp::local_engine::types::SearchResult>, String> {
         use std::process::{Command, Stdio};
         use std::io::{BufRead, BufReader};
         
         let rg_cmd = if cfg!(windows) { "rg.exe" } else { "rg" };
         
-        // 构建符号定义正则表达式
-        // 匹配常见符号定义：fn, struct, class, def, func, interface, trait, enum, type
-        let patterns = vec![
-            format!(r"fn\s+{}\s*[(<]", symbol_name),          // Rust function
-            format!(r"struct\s+{}\s*[{{<]", symbol_name),      // Rust struct
-            format!(r"enum\s+{}\s*[{{<]", symbol_name),        // Rust enum
-            format!(r"trait\s+{}\s*[{{<:]", symbol_name),      // Rust trait
-            format!(r"type\s+{}\s*=", symbol_name),            // Rust type alias
-            format!(r"class\s+{}\s*[{{(<:]", symbol_name),     // Class (TS/JS/Python/Java)
-            format!(r"interface\s+{}\s*[{{<]", symbol_name),   // TypeScript interface
-            format!(r"def\s+{}\s*\(", symbol_name),            // Python function
-            format!(r"func\s+{}\s*\(", symbol_name),           // Go function
-            format!(r"function\s+{}\s*\(", symbol_name),       // JavaScript function
-            format!(r"export\s+(const|let|var)\s+{}\s*=", symbol_name), // JS/TS export
-        ];
-        
-        let combined_pattern = patterns.join("|");
+        let patterns = SymbolPatterns::for_project(project_root);
+        let Some(matcher) = patterns.matcher(symbol_name, &filters.symbol_kinds, &filters.languages) else {
+            return Ok(Vec::new());
+        };
+        if filters.paths().is_some_and(|paths| paths.is_empty()) {
+            return Ok(Vec::new());
+        }
+        
+        // 项目追加的语言不在默认的文件类型中，补充其扩展名
+        let mut type_args = filters.ripgrep_args();
+        type_args.extend(ripgrep_ignore_args(project_root));
+        let extra_extensions = patterns.extra_extensions(&filters.languages);
+        if !extra_extensions.is_empty() {
+            type_args.push("--type-add".to_string());
+            type_args.push(format!("code:*.{{{}}}", extra_extensions.join(",")));
+        }
         
         let mut child = Command::new(rg_cmd)
             .current_dir(project_root)
-            .args([
-                "--json",
-                "-e", &combined_pattern,
-                "--type-add", "code:*.{rs,ts,tsx,js,jsx,py,go,java,c,cpp,h,hpp,vue,svelte}",
-                "--type", "code",
-            ])
+            .args(["--json", "-e", matcher.combined_pattern()])
+            .args(&type_args)
             .stdout(Stdio::piped())
             .stderr(Stdio::null())
             .spawn()
@@ -713,101 +891,53 @@ impl AcemcpTool {
         
         let reader = BufReader::new(stdout);
         let mut results: Vec<crate::mcp::tools::acemcp::local_engine::types::SearchResult> = Vec::new();
-        let mut current_file: Option<String> = None;
-        let mut current_line: Option<(usize, String)> = None;
         
-        for line_result in reader.lines() {
-            let line = match line_result {
-                Ok(l) => l,
-                Err(_) => continue,
+        for line in reader.lines().map_while(|l| l.ok()) {
+            let Ok(json) = serde_json::from_str::<serde_json::Value>(&line) else {
+                continue;
             };
-            
-            if line.is_empty() {
+            if json.get("type").and_then(|t| t.as_str()) != Some("match") {
                 continue;
             }
+            let Some(data) = json.get("data") else {
+                continue;
+            };
+            let path = data.get("path").and_then(|p| p.get("text")).and_then(|t| t.as_str());
+            let text = data.get("lines").and_then(|l| l.get("text")).and_then(|t| t.as_str());
+            let (Some(path), Some(text)) = (path, text) else {
+                continue;
+            };
             
-            if let Ok(json) = serde_json::from_str::<serde_json::Value>(&line) {
-                match json.get("type").and_then(|t| t.as_str()) {
-                    Some("begin") => {
-                        // 保存上一个匹配
-                        if let (Some(file), Some((line_num, text))) = (current_file.take(), current_line.take()) {
-                            results.push(crate::mcp::tools::acemcp::local_engine::types::SearchResult {
-                                path: file,
-                                score: 1.0,
-                                snippet: text,
-                                line_number: line_num,
-                                context: None,
-                                match_info: Some(crate::mcp::tools::acemcp::local_engine::types::MatchInfo {
-                                    matched_terms: vec![symbol_name.to_string()],
-                                    match_type: "symbol".to_string(),
-                                    match_quality: "regex_symbol".to_string(),
-                                }),
-                            });
-                        }
-                        
-                        if let Some(path) = json.get("data")
-                            .and_then(|d| d.get("path"))
-                            .and_then(|p| p.get("text"))
-                            .and_then(|t| t.as_str())
-                        {
-                            current_file = Some(path.to_string());
-                        }
-                    }
-                    Some("match") => {
-                        if let Some(data) = json.get("data") {
-                            let line_num = data.get("line_number")
-                                .and_then(|n| n.as_u64())
-                                .unwrap_or(0) as usize;
-                            
-                            if let Some(text) = data.get("lines")
-                                .and_then(|l| l.get("text"))
-                                .and_then(|t| t.as_str())
-                            {
-                                current_line = Some((line_num, format!("{:4} | {}", line_num, text.trim())));
-                            }
-                        }
-                    }
-                    Some("end") => {
-                        if let (Some(file), Some((line_num, text))) = (current_file.take(), current_line.take()) {
-                            results.push(crate::mcp::tools::acemcp::local_engine::types::SearchResult {
-                                path: file,
-                                score: 1.0,
-                                snippet: text,
-                                line_number: line_num,
-                                context: None,
-                                match_info: Some(crate::mcp::tools::acemcp::local_engine::types::MatchInfo {
-                                    matched_terms: vec![symbol_name.to_string()],
-                                    match_type: "symbol".to_string(),
-                                    match_quality: "regex_symbol".to_string(),
-                                }),
-                            });
-                        }
-                    }
-                    _ => {}
-                }
-            }
+            // 合并正则可能命中其他语言的写法，按文件语言确认
+            let Some(kind) = matcher.classify(path, text) else {
+                continue;
+            };
+            let line_num = data.get("line_number").and_then(|n| n.as_u64()).unwrap_or(0) as usize;
             
-            if results.len() >= 10 {
-                break;
-            }
-        }
-        
-        // 处理最后一个
-        if let (Some(file), Some((line_num, text))) = (current_file, current_line) {
             results.push(crate::mcp::tools::acemcp::local_engine::types::SearchResult {
-                path: file,
+                path: path.to_string(),
                 score: 1.0,
-                snippet: text,
+                snippet: format!("{:4} | {}", line_num, text.trim()),
                 line_number: line_num,
-                context: None,
+                context: kind.map(|kind| crate::mcp::tools::acemcp::local_engine::types::SnippetContext {
+                    symbol_kind: Some(kind.to_string()),
+                    ..Default::default()
+                }),
                 match_info: Some(crate::mcp::tools::acemcp::local_engine::types::MatchInfo {
                     matched_terms: vec![symbol_name.to_string()],
                     match_type: "symbol".to_string(),
                     match_quality: "regex_symbol".to_string(),
                 }),
+                score_breakdown: None,
+                matched_lines: Vec::new(),
             });
+            
+            if results.len() >= 10 {
+                break;
+            }
         }
         
+        let _ = child.kill();
         let _ = child.wait();
         Ok(results)
     }
@@ -816,20 +946,22 @@ impl AcemcpTool {
     fn search_with_ctags_raw(
         project_root: &PathBuf,
         query: &str,
+        filters: &SearchFilters,
     ) -> Result<Vec<crate::mcp::tools::acemcp::local_engine::types::SearchResult>, String> {
         let mut indexer = CtagsIndexer::new(project_root);
         
         if let Err(e) = indexer.load_tags() {
             log_important!(warn, "Failed to load ctags: {}, falling back to ripgrep", e);
-            let rg_searcher = RipgrepSearcher::new(10, 3);
+            let rg_searcher = RipgrepSearcher::new(10, 3).with_filters(filters.clone());
             return rg_searcher.search(project_root, query).map_err(|e| e.to_string());
         }
 
         let symbols = indexer.search_symbol(query);
         
-        // 将 ctags 结果转换为 SearchResult 格式
+        // 将 ctags 结果转换为 SearchResult 格式（ctags 无法下推过滤条件，在这里过滤）
         let results: Vec<crate::mcp::tools::acemcp::local_engine::types::SearchResult> = symbols
             .into_iter()
+            .filter(|sym| filters.matches(&sym.file, Some(sym.kind.as_str())))
             .map(|sym| {
                 let sig_clone = sym.signature.clone();
                 crate::mcp::tools::acemcp::local_engine::types::SearchResult {
@@ -850,6 +982,8 @@ impl AcemcpTool {
                         match_type: "symbol".to_string(),
                         match_quality: "exact".to_string(),
                     }),
+                    score_breakdown: None,
+                    matched_lines: Vec::new(),
                 }
             })
             .collect();
@@ -857,15 +991,380 @@ impl AcemcpTool {
         Ok(results)
     }
 
+    /// 模糊符号搜索（`fuzzy` + `mode = symbol`）
+    ///
+    /// 在统一符号存储中按编辑距离匹配符号名，距离越小分数越高
+    async fn fuzzy_symbol_search(
+        project_root: &PathBuf,
+        project_root_str: &str,
+        request: &SearchRequest,
+        filters: &SearchFilters,
+        lang: OutputLanguage,
+    ) -> Result<CallToolResult, McpToolError> {
+        let name = request.query.trim().to_string();
+        if name.is_empty() {
+            let err = ToolError::new(ToolErrorCode::InvalidParams, "Fuzzy symbol search requires a symbol name in `query`");
+            return Ok(crate::mcp::create_error_result(err.to_json()));
+        }
+        let max_distance = match request.max_edit_distance.map(usize::from) {
+            Some(distance) if distance > MAX_EDIT_DISTANCE => {
+                let err = ToolError::new(
+                    ToolErrorCode::InvalidParams,
+                    format!("max_edit_distance must be between 0 and {}", MAX_EDIT_DISTANCE),
+                );
+                return Ok(crate::mcp::create_error_result(err.to_json()));
+            }
+            Some(distance) => distance,
+            None => default_edit_distance(&name),
+        };
+
+        log_important!(info, "Fuzzy symbol search: name={}, max_distance={}", name, max_distance);
+
+        let root = project_root.clone();
+        let matches = tokio::task::spawn_blocking(move || {
+            with_global_store(|store| {
+                // 未索引过的项目先做一次增量索引（已持久化的文件不会重新解析）
+                if !store.has_project_symbols(&root)? {
+                    store.index_project(&root)?;
+                }
+                store.fuzzy_symbols(&root, &name, max_distance, &SymbolFilter::default())
+            })
+        })
+        .await
+        .map_err(|e| McpToolError::Generic(anyhow::anyhow!("Fuzzy symbol search task failed: {}", e)))?;
+
+        let matches = match matches {
+            Ok(matches) => matches,
+            Err(e) => {
+                let err = ToolError::search_engine_error(&e.to_string());
+                return Ok(crate::mcp::create_error_result(err.to_json()));
+            }
+        };
+
+        let results: Vec<crate::mcp::tools::acemcp::local_engine::types::SearchResult> = matches
+            .into_iter()
+            .filter_map(|m| {
+                let symbol = m.symbol;
+                let rel_path = Path::new(&symbol.path)
+                    .strip_prefix(project_root)
+                    .map(|p| p.to_string_lossy().replace('\\', "/"))
+                    .unwrap_or_else(|_| symbol.path.clone());
+                let kind = format!("{:?}", symbol.kind).to_lowercase();
+                if !filters.matches(&rel_path, Some(&kind)) {
+                    return None;
+                }
+
+                let line = symbol.start_line.unwrap_or(1) as usize;
+                let end = symbol.end_line.map_or(line, |end| end as usize).clamp(line, line + 4);
+                let snippet = std::fs::read_to_string(project_root.join(&rel_path))
+                    .ok()
+                    .map(|content| {
+                        content
+                            .lines()
+                            .skip(line.saturating_sub(1))
+                            .take(end + 1 - line)
+                            .map(|l| format!("{}\n", l))
+                            .collect::<String>()
+                    })
+                    .filter(|snippet| !snippet.is_empty())
+                    .unwrap_or_else(|| format!("{}\n", symbol.signature.clone().unwrap_or_else(|| symbol.name.clone())));
+
+                Some(crate::mcp::tools::acemcp::local_engine::types::SearchResult {
+                    path: rel_path,
+                    score: 1.0 / (1.0 + m.distance as f32),
+                    snippet,
+                    line_number: line,
+                    context: Some(crate::mcp::tools::acemcp::local_engine::types::SnippetContext {
+                        module: None,
+                        parent_symbol: None,
+                        symbol_kind: Some(kind),
+                        visibility: None,
+                        doc_comment: None,
+                        signature: symbol.signature,
+                    }),
+                    match_info: Some(crate::mcp::tools::acemcp::local_engine::types::MatchInfo {
+                        matched_terms: vec![symbol.name],
+                        match_type: "symbol".to_string(),
+                        match_quality: if m.distance == 0 { "exact" } else { "fuzzy" }.to_string(),
+                    }),
+                    score_breakdown: None,
+                    matched_lines: Vec::new(),
+                })
+            })
+            .take(FUZZY_SYMBOL_LIMIT)
+            .collect();
+
+        let results = Self::apply_smart_profile_filters(results, project_root, &request.profile);
+        if results.is_empty() {
+            return Ok(crate::mcp::create_success_result(vec![Content::text(no_results_text(lang))]));
+        }
+
+        let formatted = Self::format_legacy_results(
+            &results,
+            project_root,
+            project_root_str,
+            &request.query,
+            SearchMode::Symbol,
+            false,
+            lang,
+        );
+        Ok(crate::mcp::create_success_result(vec![Content::text(formatted)]))
+    }
+
+    /// 查找引用（mode = references）
+    ///
+    /// 在代码图谱中定位符号定义，返回调用、导入、继承它的位置及所在的函数 / 类
+    #[cfg(feature = "experimental-neurospec")]
+    async fn references_search(
+        project_root: &PathBuf,
+        project_root_str: &str,
+        request: &SearchRequest,
+        filters: &SearchFilters,
+        lang: OutputLanguage,
+    ) -> Result<CallToolResult, McpToolError> {
+        use super::local_engine::SearchResult;
+        use super::references::{find_definitions, find_references, relative_path};
+        use crate::neurospec::services::graph::builder::GraphBuilder;
+        use crate::neurospec::services::graph::cache;
+
+        let name = request.query.trim().to_string();
+        if name.is_empty() {
+            let err = ToolError::new(
+                ToolErrorCode::InvalidParams,
+                "References search requires a symbol name or ID (`path::name`) in `query`",
+            );
+            return Ok(crate::mcp::create_error_result(err.to_json()));
+        }
+
+        log_important!(info, "References search: symbol={}", name);
+
+        let root = project_root.clone();
+        let task_filters = filters.clone();
+        let lookup = tokio::task::spawn_blocking(move || -> Result<Option<(Vec<String>, Vec<SearchResult>)>> {
+            // 与 graph 工具一致：优先用全局符号存储增量构建，否则使用持久化图谱
+            let graph = if is_search_initialized() {
+                std::sync::Arc::new(with_global_store(|store| {
+                    GraphBuilder::build_from_store(&root.to_string_lossy(), store)
+                })?)
+            } else {
+                cache::load_graph(&root)
+            };
+
+            let definitions = find_definitions(&graph, &name);
+            if definitions.is_empty() {
+                return Ok(None);
+            }
+            let locations = definitions
+                .iter()
+                .map(|definition| {
+                    let path = relative_path(&definition.file_path, &root);
+                    match definition.start_line {
+                        Some(line) => format!("{}:{} ({:?})", path, line, definition.kind),
+                        None => format!("{} ({:?})", path, definition.kind),
+                    }
+                })
+                .collect();
+            let references = find_references(&graph, &definitions, &root, &task_filters);
+            Ok(Some((locations, references)))
+        })
+        .await
+        .map_err(|e| McpToolError::Generic(anyhow::anyhow!("References search task failed: {}", e)))?;
+
+        let (definitions, results) = match lookup {
+            Ok(Some(found)) => found,
+            Ok(None) => {
+                let suggestions: Vec<String> = with_global_store(|store| store.did_you_mean(project_root, &name, 5))
+                    .map(|names| names.into_iter().map(|s| s.name).collect())
+                    .unwrap_or_default();
+                let mut message = format!("Symbol '{}' not found in the code graph", name);
+                if !suggestions.is_empty() {
+                    message.push_str(&format!("; did you mean: {}", suggestions.join(", ")));
+                }
+                let err = ToolError::new(ToolErrorCode::NotFound, message);
+                return Ok(crate::mcp::create_error_result(err.to_json()));
+            }
+            Err(e) => {
+                let err = ToolError::search_engine_error(&e.to_string());
+                return Ok(crate::mcp::create_error_result(err.to_json()));
+            }
+        };
+
+        let results = if request.dedupe.unwrap_or(true) {
+            merge_results(results, project_root)
+        } else {
+            results
+        };
+        let results = Self::apply_smart_profile_filters(results, project_root, &request.profile);
+
+        let mut formatted = String::new();
+        for definition in &definitions {
+            formatted.push_str(&tr!(lang, "🎯 定义: {}\n", "🎯 Definition: {}\n", definition));
+        }
+        if results.is_empty() {
+            formatted.push_str(&tr!(
+                lang,
+                "\n未找到 `{}` 的引用\n",
+                "\nNo references to `{}` found\n",
+                name
+            ));
+            return Ok(crate::mcp::create_success_result(vec![Content::text(formatted)]));
+        }
+        formatted.push('\n');
+        formatted.push_str(&Self::format_legacy_results(
+            &results,
+            project_root,
+            project_root_str,
+            &request.query,
+            SearchMode::References,
+            false,
+            lang,
+        ));
+        Ok(crate::mcp::create_success_result(vec![Content::text(formatted)]))
+    }
+
+    #[cfg(not(feature = "experimental-neurospec"))]
+    async fn references_search(
+        _project_root: &PathBuf,
+        _project_root_str: &str,
+        _request: &SearchRequest,
+        _filters: &SearchFilters,
+        _lang: OutputLanguage,
+    ) -> Result<CallToolResult, McpToolError> {
+        let err = ToolError::new(
+            ToolErrorCode::InvalidParams,
+            "References search requires the experimental-neurospec feature",
+        );
+        Ok(crate::mcp::create_error_result(err.to_json()))
+    }
+
+    /// 查找覆盖符号的测试（profile = tests_for）
+    ///
+    /// 对每个同名定义列出调用图和命名约定关联到的测试函数 / 测试文件
+    #[cfg(feature = "experimental-neurospec")]
+    async fn tests_for_search(
+        project_root: &PathBuf,
+        symbol: &str,
+        filters: &SearchFilters,
+        lang: OutputLanguage,
+    ) -> Result<CallToolResult, McpToolError> {
+        use super::references::find_definitions;
+        use super::tests_for::{find_tests, TestPair};
+        use crate::neurospec::services::graph::builder::GraphBuilder;
+        use crate::neurospec::services::graph::cache;
+
+        let name = symbol.trim().to_string();
+        if name.is_empty() {
+            let err = ToolError::new(
+                ToolErrorCode::InvalidParams,
+                "tests_for requires a symbol name or ID (`path::name`) in `profile.tests_for.symbol` or `query`",
+            );
+            return Ok(crate::mcp::create_error_result(err.to_json()));
+        }
+
+        log_important!(info, "Tests-for search: symbol={}", name);
+
+        let root = project_root.clone();
+        let task_filters = filters.clone();
+        let lookup = tokio::task::spawn_blocking(move || -> Result<Option<Vec<TestPair>>> {
+            // 与 references 一致：优先用全局符号存储增量构建，否则使用持久化图谱
+            let graph = if is_search_initialized() {
+                std::sync::Arc::new(with_global_store(|store| {
+                    GraphBuilder::build_from_store(&root.to_string_lossy(), store)
+                })?)
+            } else {
+                cache::load_graph(&root)
+            };
+
+            let definitions = find_definitions(&graph, &name);
+            if definitions.is_empty() {
+                return Ok(None);
+            }
+            Ok(Some(find_tests(&graph, &definitions, &root, &task_filters)))
+        })
+        .await
+        .map_err(|e| McpToolError::Generic(anyhow::anyhow!("Tests-for search task failed: {}", e)))?;
+
+        let pairs = match lookup {
+            Ok(Some(pairs)) => pairs,
+            Ok(None) => {
+                let suggestions: Vec<String> = with_global_store(|store| store.did_you_mean(project_root, symbol.trim(), 5))
+                    .map(|names| names.into_iter().map(|s| s.name).collect())
+                    .unwrap_or_default();
+                let mut message = format!("Symbol '{}' not found in the code graph", symbol.trim());
+                if !suggestions.is_empty() {
+                    message.push_str(&format!("; did you mean: {}", suggestions.join(", ")));
+                }
+                let err = ToolError::new(ToolErrorCode::NotFound, message);
+                return Ok(crate::mcp::create_error_result(err.to_json()));
+            }
+            Err(e) => {
+                let err = ToolError::search_engine_error(&e.to_string());
+                return Ok(crate::mcp::create_error_result(err.to_json()));
+            }
+        };
+
+        if pairs.is_empty() {
+            let message = tr!(
+                lang,
+                "`{}` 只在测试文件中定义，没有对应的生产代码\n",
+                "`{}` is only defined in test files; there is no production code to pair with\n",
+                symbol.trim()
+            );
+            return Ok(crate::mcp::create_success_result(vec![Content::text(message)]));
+        }
+
+        let mut formatted = String::new();
+        for pair in &pairs {
+            formatted.push_str(&tr!(lang, "🎯 定义: {}\n", "🎯 Definition: {}\n", pair.definition));
+            if pair.tests.is_empty() {
+                formatted.push_str(&tr!(lang, "   ⚠️ 未找到相关测试\n", "   ⚠️ No related tests found\n"));
+            }
+            for test in &pair.tests {
+                let location = match test.line {
+                    Some(line) => format!("{}:{}", test.path, line),
+                    None => test.path.clone(),
+                };
+                match &test.name {
+                    Some(test_name) => formatted.push_str(&format!(
+                        "   ↔ {} `{}` [{}]\n",
+                        location,
+                        test_name,
+                        test.reason.label()
+                    )),
+                    None => formatted.push_str(&format!("   ↔ {} [{}]\n", location, test.reason.label())),
+                }
+            }
+            formatted.push('\n');
+        }
+        Ok(crate::mcp::create_success_result(vec![Content::text(formatted)]))
+    }
+
+    #[cfg(not(feature = "experimental-neurospec"))]
+    async fn tests_for_search(
+        _project_root: &PathBuf,
+        _symbol: &str,
+        _filters: &SearchFilters,
+        _lang: OutputLanguage,
+    ) -> Result<CallToolResult, McpToolError> {
+        let err = ToolError::new(
+            ToolErrorCode::InvalidParams,
+            "The tests_for profile requires the experimental-neurospec feature",
+        );
+        Ok(crate::mcp::create_error_result(err.to_json()))
+    }
+
     /// 旧模式搜索（profile = None 时的兼容路径）
     async fn legacy_search(
         project_root: &PathBuf,
         project_root_str: &str,
         request: &SearchRequest,
         mode: SearchMode,
+        filters: &SearchFilters,
+        lang: OutputLanguage,
     ) -> Result<CallToolResult, McpToolError> {
         let use_tantivy = is_search_initialized() && is_project_indexed(project_root);
         let is_indexing = is_project_indexing(project_root);
+        let rank_explain = request.rank_explain.unwrap_or(false);
 
         log_important!(
             info,
@@ -878,39 +1377,50 @@ impl AcemcpTool {
                 Ok(s) => s,
                 Err(e) => {
                     log_important!(warn, "Failed to create Tantivy searcher: {}, falling back to ripgrep", e);
-                    return Self::search_with_ripgrep(project_root, &request.query, mode).await;
+                    return Self::search_with_ripgrep(project_root, &request.query, mode, filters, lang).await;
                 }
             };
 
             let search_result = match mode {
-                SearchMode::Text => searcher.search_with_embedding(&request.query).await,
-                SearchMode::Symbol => searcher.search_symbol(&request.query),
-                SearchMode::Structure => unreachable!("Structure mode handled earlier"),
+                SearchMode::Text => searcher.search_with_embedding_filtered(&request.query, filters, rank_explain).await,
+                SearchMode::Symbol => searcher.search_symbol_filtered(&request.query, filters),
+                SearchMode::Structure | SearchMode::References => unreachable!("Structure and references modes handled earlier"),
             };
 
             match search_result {
                 Ok(results) => {
                     if results.is_empty() {
-                        return Ok(crate::mcp::create_success_result(vec![Content::text(
-                            "No relevant code context found."
-                        )]));
+                        return Ok(crate::mcp::create_success_result(vec![Content::text(no_results_text(lang))]));
                     }
-                    let formatted = Self::format_legacy_results(&results, project_root, project_root_str, &request.query, mode);
+                    let results = if request.dedupe.unwrap_or(true) {
+                        merge_results(results, project_root)
+                    } else {
+                        results
+                    };
+                    let formatted = Self::format_legacy_results(
+                        &results,
+                        project_root,
+                        project_root_str,
+                        &request.query,
+                        mode,
+                        rank_explain,
+                        lang,
+                    );
                     Ok(crate::mcp::create_success_result(vec![Content::text(formatted)]))
                 }
                 Err(e) => {
-                    let err = SearchError::search_engine_error(&e.to_string());
+                    let err = ToolError::search_engine_error(&e.to_string());
                     Ok(crate::mcp::create_error_result(err.to_json()))
                 }
             }
         } else {
-            if !is_indexing {
+            if !is_indexing && crate::config::project_settings(project_root).auto_index {
                 Self::ensure_search_initialized();
                 if is_search_initialized() {
-                    Self::trigger_background_indexing(project_root);
+                    Self::trigger_background_indexing_safe(project_root, false);
                 }
             }
-            Self::search_with_ripgrep(project_root, &request.query, mode).await
+            Self::search_with_ripgrep(project_root, &request.query, mode, filters, lang).await
         }
     }
 
@@ -921,6 +1431,8 @@ impl AcemcpTool {
         project_root_str: &str,
         query: &str,
         mode: SearchMode,
+        rank_explain: bool,
+        lang: OutputLanguage,
     ) -> String {
         let mut formatted = String::new();
 
@@ -935,18 +1447,24 @@ impl AcemcpTool {
             formatted.push_str(&format!("[Index: {} | Files: {}]\n", status, state.file_count));
         }
 
-        let mode_str = match mode { SearchMode::Text => "Text", SearchMode::Symbol => "Symbol", SearchMode::Structure => "Structure" };
-        formatted.push_str(&format!("Found {} relevant snippets (Mode: {}):\n\n", results.len(), mode_str));
+        let mode_str = match mode { SearchMode::Text => "Text", SearchMode::Symbol => "Symbol", SearchMode::Structure => "Structure", SearchMode::References => "References" };
+        formatted.push_str(&tr!(
+            lang,
+            "找到 {} 个相关片段（模式: {}）:\n\n",
+            "Found {} relevant snippets (Mode: {}):\n\n",
+            results.len(), mode_str
+        ));
 
         let all_paths: Vec<String> = results.iter().map(|r| r.path.clone()).collect();
         let changes_by_file = Self::get_changes_for_files(project_root_str, &all_paths, query);
 
         for res in results {
             formatted.push_str(&format!("### 📄 `{}` (Score: {:.2})\n", res.path, res.score));
+            formatted.push_str(&Self::format_matched_lines(res, lang));
             
             if let Some(changes) = changes_by_file.get(&res.path) {
                 for change in changes.iter().take(3) {
-                    let ago = Self::format_time_ago(change.created_at);
+                    let ago = Self::format_time_ago(change.created_at, lang);
                     formatted.push_str(&format!("  📝 {} ({})\n", change.summary, ago));
                 }
             }
@@ -982,7 +1500,13 @@ impl AcemcpTool {
                     ));
                 }
             }
+            if rank_explain {
+                if let Some(ref breakdown) = res.score_breakdown {
+                    formatted.push_str(&Self::format_score_breakdown(breakdown));
+                }
+            }
             
+
             formatted.push_str("```\n");
             formatted.push_str(&res.snippet);
             formatted.push_str("```\n\n");
@@ -991,40 +1515,92 @@ impl AcemcpTool {
         formatted
     }
 
+    /// 格式化多处命中的行号（单处命中时为空）
+    fn format_matched_lines(
+        res: &crate::mcp::tools::acemcp::local_engine::types::SearchResult,
+        lang: OutputLanguage,
+    ) -> String {
+        if res.matched_lines.len() < 2 {
+            return String::new();
+        }
+        let lines: Vec<String> = res.matched_lines.iter().map(|line| line.to_string()).collect();
+        tr!(
+            lang,
+            "📌 {} 处命中，行 {}\n",
+            "📌 {} hits on lines {}\n",
+            res.matched_lines.len(),
+            lines.join(", ")
+        )
+    }
+
+    /// 格式化单个结果的分数构成（rank_explain）
+    fn format_score_breakdown(breakdown: &ScoreBreakdown) -> String {
+        let weights = &breakdown.weights;
+        let mut line = format!("📊 Rank: lexical {:.3}", breakdown.lexical);
+        if !breakdown.fields.is_empty() {
+            let fields: Vec<String> = breakdown
+                .fields
+                .iter()
+                .map(|(name, score)| format!("{} {:.3}", name, score))
+                .collect();
+            line.push_str(&format!(" [{}]", fields.join(", ")));
+        }
+        if let Some(semantic) = breakdown.semantic {
+            line.push_str(&format!(
+                " × {} + semantic {:.3} × 10 × {}",
+                weights.lexical_weight, semantic, weights.semantic_weight
+            ));
+        }
+        line.push_str(&format!(
+            " = {:.3} (boosts: symbols {}, path {}, content {})\n",
+            breakdown.total, weights.symbols_boost, weights.path_boost, weights.content_boost
+        ));
+        line
+    }
+
     /// 使用 ripgrep/ctags 进行搜索（回退方案）
     async fn search_with_ripgrep(
         project_root: &PathBuf,
         query: &str,
         mode: SearchMode,
+        filters: &SearchFilters,
+        lang: OutputLanguage,
     ) -> Result<CallToolResult, McpToolError> {
         // 符号搜索优先使用 ctags
         if matches!(mode, SearchMode::Symbol) && CtagsIndexer::is_available() {
             log_important!(info, "Using ctags for symbol search");
-            return Self::search_with_ctags(project_root, query).await;
+            return Self::search_with_ctags(project_root, query, filters, lang).await;
         }
 
         log_important!(info, "Using ripgrep fallback for search");
         
         // 检查 ripgrep 是否可用
         if !RipgrepSearcher::is_available() {
-            let err = SearchError::index_not_ready();
+            let err = ToolError::index_not_ready();
             return Ok(crate::mcp::create_error_result(err.to_json()));
         }
 
-        let rg_searcher = RipgrepSearcher::new(10, 3);
+        let rg_searcher = RipgrepSearcher::new(10, 3).with_filters(filters.clone());
         
         match rg_searcher.search(project_root, query) {
             Ok(results) => {
                 if results.is_empty() {
-                    return Ok(crate::mcp::create_success_result(vec![Content::text(
-                        "No relevant code context found."
-                    )]));
+                    return Ok(crate::mcp::create_success_result(vec![Content::text(no_results_text(lang))]));
                 }
                 
                 let mut formatted = String::new();
-                let mode_str = match mode { SearchMode::Text => "Text", SearchMode::Symbol => "Symbol", SearchMode::Structure => "Structure" };
-                formatted.push_str(&format!("Found {} snippets via ripgrep (Mode: {}):\n", results.len(), mode_str));
-                formatted.push_str("💡 Note: Using ripgrep fallback. Index building in background for faster future searches.\n\n");
+                let mode_str = match mode { SearchMode::Text => "Text", SearchMode::Symbol => "Symbol", SearchMode::Structure => "Structure", SearchMode::References => "References" };
+                formatted.push_str(&tr!(
+                    lang,
+                    "通过 ripgrep 找到 {} 个片段（模式: {}）:\n",
+                    "Found {} snippets via ripgrep (Mode: {}):\n",
+                    results.len(), mode_str
+                ));
+                formatted.push_str(&tr!(
+                    lang,
+                    "💡 注意：当前使用 ripgrep 回退搜索，索引正在后台建立，之后的搜索会更快。\n\n",
+                    "💡 Note: Using ripgrep fallback. Index building in background for faster future searches.\n\n"
+                ));
                 
                 for res in results {
                     formatted.push_str(&format!("--- {} ---\n", res.path));
@@ -1035,7 +1611,7 @@ impl AcemcpTool {
                 Ok(crate::mcp::create_success_result(vec![Content::text(formatted)]))
             }
             Err(e) => {
-                let err = SearchError::io_error(&e.to_string());
+                let err = ToolError::io_error(&e.to_string());
                 Ok(crate::mcp::create_error_result(err.to_json()))
             }
         }
@@ -1045,6 +1621,8 @@ impl AcemcpTool {
     async fn search_with_ctags(
         project_root: &PathBuf,
         query: &str,
+        filters: &SearchFilters,
+        lang: OutputLanguage,
     ) -> Result<CallToolResult, McpToolError> {
         let mut indexer = CtagsIndexer::new(project_root);
         
@@ -1052,32 +1630,40 @@ impl AcemcpTool {
         if let Err(e) = indexer.load_tags() {
             log_important!(warn, "Failed to load ctags: {}, falling back to ripgrep", e);
             // 回退到 ripgrep
-            let rg_searcher = RipgrepSearcher::new(10, 3);
+            let rg_searcher = RipgrepSearcher::new(10, 3).with_filters(filters.clone());
             return match rg_searcher.search(project_root, query) {
                 Ok(results) => {
-                    let mut formatted = format!("Found {} snippets via ripgrep (Symbol mode, ctags unavailable):\n\n", results.len());
+                    let mut formatted = tr!(
+                        lang,
+                        "通过 ripgrep 找到 {} 个片段（符号模式，ctags 不可用）:\n\n",
+                        "Found {} snippets via ripgrep (Symbol mode, ctags unavailable):\n\n",
+                        results.len()
+                    );
                     for res in results {
                         formatted.push_str(&format!("--- {} ---\n{}\n\n", res.path, res.snippet));
                     }
                     Ok(crate::mcp::create_success_result(vec![Content::text(formatted)]))
                 }
                 Err(e) => {
-                    let err = SearchError::io_error(&e.to_string());
+                    let err = ToolError::io_error(&e.to_string());
                     Ok(crate::mcp::create_error_result(err.to_json()))
                 }
             };
         }
 
-        let symbols = indexer.search_symbol(query);
+        let mut symbols = indexer.search_symbol(query);
+        symbols.retain(|sym| filters.matches(&sym.file, Some(sym.kind.as_str())));
         
         if symbols.is_empty() {
-            return Ok(crate::mcp::create_success_result(vec![Content::text(
+            return Ok(crate::mcp::create_success_result(vec![Content::text(tr!(
+                lang,
+                "未找到匹配的符号。",
                 "No matching symbols found."
-            )]));
+            ))]));
         }
 
         let mut formatted = String::new();
-        formatted.push_str(&format!("Found {} symbols via ctags:\n\n", symbols.len()));
+        formatted.push_str(&tr!(lang, "通过 ctags 找到 {} 个符号:\n\n", "Found {} symbols via ctags:\n\n", symbols.len()));
 
         for symbol in symbols {
             formatted.push_str(&format!(
@@ -1088,7 +1674,7 @@ impl AcemcpTool {
                 symbol.line
             ));
             if let Some(sig) = &symbol.signature {
-                formatted.push_str(&format!("   Signature: {}\n", sig));
+                formatted.push_str(&tr!(lang, "   签名: {}\n", "   Signature: {}\n", sig));
             }
             formatted.push('\n');
         }
@@ -1108,10 +1694,8 @@ impl AcemcpTool {
             return;
         }
         
-        // 获取缓存目录
-        let base_cache_dir = dirs::cache_dir()
-            .unwrap_or_else(|| std::path::PathBuf::from("."))
-            .join("neurospec");
+        // 获取缓存目录（按当前配置档案隔离）
+        let base_cache_dir = crate::config::profile_cache_dir();
         
         let store_cache_dir = base_cache_dir.join("unified_store");
         let index_cache_dir = base_cache_dir.join("search_index");
@@ -1130,104 +1714,71 @@ impl AcemcpTool {
         let _ = init_global_watcher();
     }
 
-    /// 安全触发后台索引（带文件锁保护）
+    /// 强制全量重建项目索引（`index_admin` / `reindex_project`）
+    ///
+    /// 在后台丢弃现有索引重新构建并刷新符号存储，重建期间旧索引继续提供搜索。
+    /// 项目正在索引时返回错误，需要先取消
+    pub(crate) fn start_reindex(project_root: &PathBuf) -> Result<(), String> {
+        if is_project_indexing(project_root) {
+            return Err(format!(
+                "Project is already being indexed: {}. Cancel it first.",
+                project_root.display()
+            ));
+        }
+
+        Self::ensure_search_initialized();
+        if !is_search_initialized() {
+            return Err("Search engine is not initialized".to_string());
+        }
+
+        if !Self::trigger_background_indexing_safe(project_root, true) {
+            return Err(format!(
+                "Background indexing for {} was not started: the index lock is held by another indexing task or unavailable",
+                project_root.display()
+            ));
+        }
+        Ok(())
+    }
+
+    /// 安全触发后台索引（带进程间索引锁保护）
     /// 
-    /// 使用简单的文件锁机制防止并发触发多个索引任务
-    fn trigger_background_indexing_safe(project_root: &PathBuf) {
-        use std::fs::{File, OpenOptions};
-        use std::io::{Read, Write};
-        
-        // 获取锁文件路径
-        let lock_path = match get_global_search_config() {
-            Ok(config) => config.index_path.join(".indexing.lock"),
-            Err(_) => {
-                log_important!(warn, "Cannot get config for lock file, falling back to unsafe indexing");
-                Self::trigger_background_indexing(project_root);
-                return;
+    /// 锁在整个索引过程中持有，防止 daemon 与 stdio MCP 进程同时索引同一项目；
+    /// 锁被其他索引任务持有或无法获取时跳过本次索引并返回 false，不会在无锁状态下索引
+    fn trigger_background_indexing_safe(project_root: &PathBuf, force_rebuild: bool) -> bool {
+        let index_path = match get_global_search_config() {
+            Ok(config) => config.for_project(project_root).index_path,
+            Err(e) => {
+                log_important!(warn, "Cannot get config for lock file, skipping background indexing: {}", e);
+                return false;
             }
         };
-        
-        // 确保锁文件目录存在
-        if let Some(parent) = lock_path.parent() {
-            let _ = std::fs::create_dir_all(parent);
-        }
-        
-        // 检查锁文件是否存在且有效（包含正在运行的 PID）
-        if lock_path.exists() {
-            if let Ok(mut file) = File::open(&lock_path) {
-                let mut content = String::new();
-                if file.read_to_string(&mut content).is_ok() {
-                    if let Ok(pid) = content.trim().parse::<u32>() {
-                        // 检查进程是否还在运行
-                        if Self::is_process_running(pid) {
-                            log_important!(info, "Index lock held by PID {}, skipping duplicate indexing", pid);
-                            return;
-                        }
-                    }
-                }
-            }
-        }
-        
-        // 写入当前进程 PID 到锁文件
-        let current_pid = std::process::id();
-        match OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(&lock_path)
-        {
-            Ok(mut file) => {
-                if write!(file, "{}", current_pid).is_err() {
-                    log_important!(warn, "Failed to write lock file");
-                }
+
+        let lock = match IndexingLock::try_acquire(&index_path) {
+            Ok(Some(lock)) => lock,
+            Ok(None) => {
+                log_important!(info, "Index lock held by another indexing task, skipping duplicate indexing");
+                return false;
             }
             Err(e) => {
-                log_important!(warn, "Cannot create lock file: {}", e);
+                log_important!(warn, "Cannot acquire index lock ({}), skipping background indexing", e);
+                return false;
             }
-        }
-        
-        log_important!(info, "Acquired index lock (PID: {}), triggering background indexing", current_pid);
-        
-        let root = project_root.clone();
-        let lock_path_clone = lock_path.clone();
-        std::thread::spawn(move || {
-            Self::do_background_indexing(&root);
-            // 索引完成后删除锁文件
-            let _ = std::fs::remove_file(&lock_path_clone);
-        });
-    }
-    
-    /// 检查进程是否正在运行
-    #[cfg(windows)]
-    fn is_process_running(pid: u32) -> bool {
-        use std::process::Command;
-        Command::new("tasklist")
-            .args(["/FI", &format!("PID eq {}", pid), "/NH"])
-            .output()
-            .map(|output| {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                stdout.contains(&pid.to_string())
-            })
-            .unwrap_or(false)
-    }
-    
-    #[cfg(not(windows))]
-    fn is_process_running(pid: u32) -> bool {
-        std::path::Path::new(&format!("/proc/{}", pid)).exists()
-    }
+        };
 
-    /// 在后台触发索引
-    /// 
-    /// 如果索引文件数 < 10，则执行重建索引；否则执行增量索引
-    fn trigger_background_indexing(project_root: &PathBuf) {
+        log_important!(info, "Acquired index lock, triggering background indexing");
+
+        // 如果索引文件数 < 10 或 `force_rebuild`，则执行重建索引；否则执行增量索引
         let root = project_root.clone();
         std::thread::spawn(move || {
-            Self::do_background_indexing(&root);
+            Self::do_background_indexing(&root, force_rebuild);
+            // 索引完成后释放锁
+            drop(lock);
         });
+        true
     }
     
     /// 执行后台索引的实际逻辑
-    fn do_background_indexing(project_root: &PathBuf) {
+    fn do_background_indexing(project_root: &PathBuf, force_rebuild: bool) {
         use crate::mcp::tools::unified_store::get_indexed_file_count;
         
         // 检查是否正在索引
@@ -1238,6 +1789,10 @@ impl AcemcpTool {
         
         // 检查索引文件数，如果 < 10 则重建
         let should_rebuild = match get_indexed_file_count(project_root) {
+            _ if force_rebuild => {
+                log_important!(info, "Forced rebuild requested");
+                true
+            }
             Some(count) if count < 10 => {
                 log_important!(info, "Index has only {} files, will rebuild", count);
                 true
@@ -1256,43 +1811,42 @@ impl AcemcpTool {
         let config = match get_global_search_config() {
             Ok(c) => c,
             Err(_) => LocalEngineConfig::default(),
-        };
+        }
+        .for_project(project_root);
         
         mark_indexing_started(project_root);
         
         log_important!(info, "Starting background indexing for: {} (index_path: {:?})", project_root.display(), config.index_path);
         
-        match LocalIndexer::new(&config) {
-            Ok(mut indexer) => {
-                let result = if should_rebuild {
-                    log_important!(info, "Executing full index rebuild...");
-                    indexer.rebuild_index(project_root)
-                } else {
-                    log_important!(info, "Executing incremental indexing...");
-                    indexer.index_directory(project_root)
-                };
-                
-                match result {
-                    Ok(count) => {
-                        mark_indexing_complete(project_root, count);
-                        log_important!(info, "Background indexing complete: {} files indexed", count);
-                        
-                        // 启动文件变化监听循环
-                        Self::start_file_change_loop(project_root.clone(), config);
-                    }
-                    Err(e) => {
-                        use crate::mcp::tools::unified_store::mark_index_corrupted;
-                        mark_index_corrupted(project_root, &format!("Indexing failed: {}", e));
-                        log_important!(error, "Background indexing failed: {}", e);
+        let result = with_live_indexer(&config, project_root, |indexer| {
+            if should_rebuild {
+                log_important!(info, "Executing full index rebuild...");
+                indexer.rebuild_index(project_root)
+            } else {
+                log_important!(info, "Executing incremental indexing...");
+                indexer.index_directory(project_root)
+            }
+        });
+
+        match &result {
+            Ok(count) => {
+                // 强制重建时一并刷新符号存储，使图谱类查询也基于最新文件
+                if force_rebuild {
+                    if let Err(e) = with_global_store(|store| store.index_project(project_root)) {
+                        log_important!(warn, "Failed to refresh symbol store after rebuild: {}", e);
                     }
                 }
+                log_important!(info, "Background indexing complete: {} files indexed", count);
+            }
+            Err(e) if e.is::<IndexingCancelled>() => {
+                log_important!(info, "Background indexing cancelled: {}", project_root.display());
             }
             Err(e) => {
-                use crate::mcp::tools::unified_store::mark_index_corrupted;
-                mark_index_corrupted(project_root, &format!("Failed to create indexer: {}", e));
-                log_important!(error, "Failed to create indexer: {}", e);
+                log_important!(error, "Background indexing failed: {}", e);
             }
         }
+        // 索引就绪后开始监听，文件变化由对应的变化处理任务增量更新
+        finish_indexing(project_root, &result);
     }
 
     /// 根据 SmartStructure profile 对搜索结果进行 scope / max_results 过滤
@@ -1305,7 +1859,7 @@ impl AcemcpTool {
             return results;
         };
 
-        // 作用域过滤（目前只对 Folder/File 生效，Project/Symbol 不做额外限制）
+        // 作用域过滤（目前只对 Folder/File 生效，Project/Symbol 不做额外限制，Dirty 已在引擎中过滤）
         if let Some(scope) = scope.as_ref() {
             let root_str = project_root.to_string_lossy().to_string();
 
@@ -1355,54 +1909,11 @@ impl AcemcpTool {
             }
             // 暂不根据符号名做进一步过滤，后续可以结合 SnippetContext/MatchInfo 增强
             SearchScopeKind::Symbol => true,
+            // 未提交文件已作为路径限定下推到引擎（SearchFilters::restrict_to_paths）
+            SearchScopeKind::Dirty => true,
         }
     }
 
-    /// 启动文件变化监听循环
-    /// 
-    /// 使用自适应休眠策略：
-    /// - 有文件变化时，快速响应（500ms）
-    /// - 无文件变化时，逐渐延长间隔（最大 10s）
-    fn start_file_change_loop(project_root: PathBuf, config: LocalEngineConfig) {
-        use crate::mcp::tools::unified_store::process_file_changes;
-        
-        std::thread::spawn(move || {
-            log_important!(info, "Starting file change loop for: {}", project_root.display());
-            
-            let mut idle_cycles = 0u32;
-            const MIN_SLEEP_MS: u64 = 500;
-            const MAX_SLEEP_MS: u64 = 10000;
-            
-            loop {
-                // 自适应休眠：无变化时逐渐延长，有变化时重置
-                let sleep_ms = MIN_SLEEP_MS.saturating_mul(1 + idle_cycles as u64).min(MAX_SLEEP_MS);
-                std::thread::sleep(std::time::Duration::from_millis(sleep_ms));
-                
-                // 处理文件变化
-                match process_file_changes() {
-                    Ok(count) if count > 0 => {
-                        idle_cycles = 0; // 重置空闲计数
-                        log_important!(info, "Detected {} file changes, updating index...", count);
-                        
-                        // 增量更新索引
-                        if let Ok(mut indexer) = LocalIndexer::new(&config) {
-                            if let Err(e) = indexer.index_directory(&project_root) {
-                                log_important!(error, "Failed to update index: {}", e);
-                            }
-                        }
-                    }
-                    Ok(_) => {
-                        // 无变化，增加空闲计数
-                        idle_cycles = idle_cycles.saturating_add(1).min(20);
-                    }
-                    Err(e) => {
-                        log_important!(error, "Error processing file changes: {}", e);
-                    }
-                }
-            }
-        });
-    }
-
     /// Get project structure overview (structure mode)
     /// 
     /// 升级版：生成 Project Insight，包含：
@@ -1418,22 +1929,32 @@ impl AcemcpTool {
     ) -> Result<CallToolResult, McpToolError> {
         log_important!(info, "Generating Project Insight for: {}", project_root.display());
         
-        // 🚀 优化：单次遍历收集基础信息和模块映射
-        let (lang_stats, total_files, mut module_map) = Self::collect_project_data(project_root);
+        let mut insight = Self::collect_project_insight(project_root);
 
         // 按深度和节点数量进行裁剪（如果配置了）
         if let Some(limit_depth) = max_depth {
             let limit = limit_depth as usize;
-            module_map.retain(|m| m.depth <= limit);
+            insight.module_map.retain(|m| m.depth <= limit);
         }
 
         if let Some(max_nodes) = max_nodes {
             let limit = max_nodes as usize;
-            if module_map.len() > limit {
-                module_map.truncate(limit);
+            if insight.module_map.len() > limit {
+                insight.module_map.truncate(limit);
             }
         }
         
+        // 格式化输出
+        let output = Self::format_project_insight(&insight, project_root);
+        
+        Ok(crate::mcp::create_success_result(vec![Content::text(output)]))
+    }
+
+    /// 收集 Project Insight 数据（不裁剪模块映射）
+    pub(crate) fn collect_project_insight(project_root: &Path) -> ProjectInsight {
+        // 🚀 优化：单次遍历收集基础信息和模块映射
+        let (lang_stats, total_files, module_map) = Self::collect_project_data(project_root);
+        
         // 生成依赖图谱 (使用 CodeGraph)
         let dependencies = Self::generate_dependency_graph(project_root);
         
@@ -1454,7 +1975,7 @@ impl AcemcpTool {
             .to_string();
         
         // 构建 ProjectInsight
-        let insight = ProjectInsight {
+        ProjectInsight {
             name: project_name,
             project_type,
             lang_stats,
@@ -1463,12 +1984,7 @@ impl AcemcpTool {
             dependencies,
             key_symbols,
             external_deps,
-        };
-        
-        // 格式化输出
-        let output = Self::format_project_insight(&insight, project_root);
-        
-        Ok(crate::mcp::create_success_result(vec![Content::text(output)]))
+        }
     }
 
     /// 🚀 单次遍历收集项目数据
@@ -1479,12 +1995,14 @@ impl AcemcpTool {
         use ignore::WalkBuilder;
         use std::collections::HashSet;
         
-        let walker = WalkBuilder::new(project_root)
+        let mut builder = WalkBuilder::new(project_root);
+        builder
             .hidden(false)
             .git_ignore(true)
             .git_global(true)
-            .git_exclude(true)
-            .build();
+            .git_exclude(true);
+        filter_walker(&mut builder, project_root);
+        let walker = builder.build();
         
         let mut lang_stats: HashMap<String, usize> = HashMap::new();
         let mut total_files = 0;
@@ -1656,9 +2174,10 @@ impl AcemcpTool {
         // 尝试使用现有的 CodeGraph 基础设施
         #[cfg(feature = "experimental-neurospec")]
         {
-            use crate::neurospec::services::graph::builder::GraphBuilder;
+            use crate::neurospec::services::graph::cache;
             
-            let graph = GraphBuilder::build_from_project(&project_root.to_string_lossy());
+            // 持久化图谱只重新解析变化的文件
+            let graph = cache::load_graph(project_root);
             
             let mut edges = Vec::new();
             
@@ -2045,7 +2564,7 @@ impl AcemcpTool {
     }
 
     /// 格式化时间为相对时间（如 "3天前"、"1周前"）
-    fn format_time_ago(time: DateTime<Utc>) -> String {
+    fn format_time_ago(time: DateTime<Utc>, lang: OutputLanguage) -> String {
         let now = Utc::now();
         let duration = now.signed_duration_since(time);
         
@@ -2054,21 +2573,26 @@ impl AcemcpTool {
         let minutes = duration.num_minutes();
         
         if days > 30 {
-            format!("{}个月前", days / 30)
+            tr!(lang, "{}个月前", "{}mo ago", days / 30)
         } else if days > 7 {
-            format!("{}周前", days / 7)
+            tr!(lang, "{}周前", "{}w ago", days / 7)
         } else if days > 0 {
-            format!("{}天前", days)
+            tr!(lang, "{}天前", "{}d ago", days)
         } else if hours > 0 {
-            format!("{}小时前", hours)
+            tr!(lang, "{}小时前", "{}h ago", hours)
         } else if minutes > 0 {
-            format!("{}分钟前", minutes)
+            tr!(lang, "{}分钟前", "{}m ago", minutes)
         } else {
-            "刚刚".to_string()
+            tr!(lang, "刚刚", "just now")
         }
     }
 }
 
+/// 没有搜索结果时的提示
+fn no_results_text(lang: OutputLanguage) -> String {
+    tr!(lang, "未找到相关代码上下文。", "No relevant code context found.")
+}
+
 /// 自动检测项目根目录
 fn detect_project_root() -> Option<PathBuf> {
     // 1. 优先使用缓存的项目路径
@@ -6,6 +6,18 @@ pub mod types;
 pub mod commands;
 pub mod local_engine;
 pub mod health;
+pub mod symbols;
+pub mod store_stats;
+pub mod projects;
+pub mod dashboard;
+pub mod index_admin;
+pub mod perf_profile;
+pub mod read_symbol;
+pub mod dirty;
+#[cfg(feature = "experimental-neurospec")]
+pub mod references;
+#[cfg(feature = "experimental-neurospec")]
+pub mod tests_for;
 
 // 重新导出工具以便访问
 pub use mcp::AcemcpTool;
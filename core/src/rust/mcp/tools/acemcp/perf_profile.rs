@@ -0,0 +1,54 @@
+//! 性能基准工具
+//!
+//! 对项目运行标准基准（重建索引耗时、搜索与记忆召回的 p50/p95 延迟），
+//! 输出可跨版本对比的报告。与 `perf` 子命令共用同一实现
+
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use rmcp::model::{CallToolResult, Content};
+use crate::app::perf::{run_profile, PerfOptions};
+use crate::mcp::utils::errors::McpToolError;
+
+/// perf_profile 工具请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PerfProfileRequest {
+    /// 项目根目录
+    pub project_root: String,
+    /// 查询集（可选，默认使用内置查询集，便于不同版本间对比）
+    pub queries: Option<Vec<String>>,
+    /// 每个查询的重复次数（默认 5）
+    pub iterations: Option<usize>,
+    /// 是否测量全量重建索引（默认 true，大项目耗时较长）
+    pub rebuild: Option<bool>,
+}
+
+/// 执行性能基准
+pub async fn perf_profile(request: PerfProfileRequest) -> Result<CallToolResult, McpToolError> {
+    let root = PathBuf::from(&request.project_root);
+    if !root.is_dir() {
+        return Err(McpToolError::InvalidParams(format!(
+            "Project root does not exist: {}",
+            request.project_root
+        )));
+    }
+
+    let mut options = PerfOptions::default();
+    if let Some(queries) = request.queries.filter(|q| !q.is_empty()) {
+        options.queries = queries;
+    }
+    if let Some(iterations) = request.iterations {
+        options.iterations = iterations.max(1);
+    }
+    if let Some(rebuild) = request.rebuild {
+        options.rebuild = rebuild;
+    }
+
+    let report = tokio::task::spawn_blocking(move || run_profile(&root, &options))
+        .await
+        .map_err(|e| McpToolError::Generic(anyhow::anyhow!("Perf profile task failed: {}", e)))??;
+
+    let json = serde_json::to_string_pretty(&report)?;
+
+    Ok(crate::mcp::create_success_result(vec![Content::text(json)]))
+}
@@ -0,0 +1,144 @@
+//! 项目注册表工具
+//!
+//! 列出索引或查询过的项目（路径、最近使用时间、索引状态、记忆条数、是否监听），
+//! 并修改按项目的设置（打开时自动索引、文件监听）。UI 项目面板与 MCP 调用方共用
+
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use rmcp::model::{CallToolResult, Content};
+use crate::config::{list_project_records, project_settings, set_project_settings, ProjectRecord, ProjectSettings};
+use crate::mcp::utils::errors::McpToolError;
+use crate::mcp::tools::memory::MemoryManager;
+use crate::mcp::tools::unified_store::{
+    get_index_state, is_project_watched, known_projects, touch_project, unwatch_project, IndexState,
+};
+
+/// projects 工具请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ProjectsRequest {
+    /// 项目根目录（可选，不填则返回所有已知项目）
+    pub project_root: Option<String>,
+    /// 设置是否在首次搜索时自动索引该项目（需要 project_root）
+    pub auto_index: Option<bool>,
+    /// 设置是否监听该项目的文件变化（需要 project_root）
+    pub watch: Option<bool>,
+}
+
+/// 项目概况
+#[derive(Debug, Serialize)]
+pub struct ProjectSummary {
+    pub path: String,
+    pub name: String,
+    /// 目录是否仍然存在
+    pub exists: bool,
+    pub first_seen: Option<String>,
+    pub last_used: Option<String>,
+    pub index_state: IndexState,
+    pub file_count: usize,
+    /// 记忆条数（没有记忆目录时为 None）
+    pub memory_count: Option<usize>,
+    pub watching: bool,
+    pub settings: ProjectSettings,
+}
+
+fn summarize(path: &str, record: Option<&ProjectRecord>) -> ProjectSummary {
+    let root = PathBuf::from(path);
+    let index = get_index_state(&root);
+    let memory_count = crate::config::project_memory_dir(&root)
+        .exists()
+        .then(|| MemoryManager::new(path).and_then(|m| m.count_memories()).ok())
+        .flatten();
+
+    ProjectSummary {
+        path: path.to_string(),
+        name: root
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string()),
+        exists: root.is_dir(),
+        first_seen: record.map(|r| r.first_seen.clone()),
+        last_used: record.map(|r| r.last_used.clone()),
+        index_state: index.as_ref().map(|s| s.state.clone()).unwrap_or_default(),
+        file_count: index.map(|s| s.file_count).unwrap_or(0),
+        memory_count,
+        watching: is_project_watched(&root),
+        settings: record.map(|r| r.settings.clone()).unwrap_or_default(),
+    }
+}
+
+/// 收集项目概况：注册表中的项目在前（按最近使用倒序），
+/// 其后是只有索引状态记录的旧项目（供 MCP 工具与 Tauri 命令共用）
+pub fn collect_projects(project_root: Option<&str>) -> Vec<ProjectSummary> {
+    let records = list_project_records();
+
+    if let Some(root) = project_root {
+        let key = crate::utils::path_key(Path::new(root));
+        return vec![summarize(&key, records.iter().find(|r| r.path == key))];
+    }
+
+    let mut projects: Vec<ProjectSummary> = records.iter().map(|r| summarize(&r.path, Some(r))).collect();
+    for root in known_projects() {
+        let key = crate::utils::path_key(&root);
+        if !records.iter().any(|r| r.path == key) {
+            projects.push(summarize(&key, None));
+        }
+    }
+    projects
+}
+
+/// 修改项目设置并立即应用监听开关
+pub fn update_project_settings(
+    project_root: &Path,
+    auto_index: Option<bool>,
+    watch: Option<bool>,
+) -> anyhow::Result<ProjectSettings> {
+    let mut settings = project_settings(project_root);
+    if let Some(auto_index) = auto_index {
+        settings.auto_index = auto_index;
+    }
+    if let Some(watch) = watch {
+        settings.watch = watch;
+    }
+    let record = set_project_settings(project_root, settings)?;
+
+    if record.settings.watch {
+        // 已索引的项目恢复监听
+        touch_project(project_root);
+    } else if is_project_watched(project_root) {
+        unwatch_project(project_root)?;
+    }
+    Ok(record.settings)
+}
+
+/// 执行项目查询，附带设置修改时先修改
+pub async fn projects(request: ProjectsRequest) -> Result<CallToolResult, McpToolError> {
+    let updates_settings = request.auto_index.is_some() || request.watch.is_some();
+    match request.project_root {
+        Some(ref root) if !Path::new(root).is_dir() => {
+            return Err(McpToolError::InvalidParams(format!(
+                "Project root does not exist: {}",
+                root
+            )));
+        }
+        None if updates_settings => {
+            return Err(McpToolError::InvalidParams(
+                "project_root is required when changing auto_index or watch".to_string(),
+            ));
+        }
+        _ => {}
+    }
+
+    let response = tokio::task::spawn_blocking(move || -> anyhow::Result<Vec<ProjectSummary>> {
+        if let (true, Some(root)) = (updates_settings, request.project_root.as_deref()) {
+            update_project_settings(Path::new(root), request.auto_index, request.watch)?;
+        }
+        Ok(collect_projects(request.project_root.as_deref()))
+    })
+    .await
+    .map_err(|e| McpToolError::Generic(anyhow::anyhow!("Projects task failed: {}", e)))??;
+
+    let json = serde_json::to_string_pretty(&response)?;
+
+    Ok(crate::mcp::create_success_result(vec![Content::text(json)]))
+}
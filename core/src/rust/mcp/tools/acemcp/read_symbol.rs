@@ -0,0 +1,353 @@
+//! 符号源码读取工具
+//!
+//! 按 `file + symbol` 或符号 ID（`path::name`）返回单个定义的源码，范围来自 tree-sitter 解析，
+//! 代替「先搜索再读取整个文件」，只把需要的定义交给调用方
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use rmcp::model::{CallToolResult, Content};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::OutputLanguage;
+use crate::mcp::tools::unified_store::fuzzy::SymbolNameIndex;
+use crate::mcp::tools::unified_store::store::detect_language;
+use crate::mcp::tools::unified_store::{touch_project, with_global_store, SymbolFilter};
+use crate::mcp::utils::errors::{coded_error, McpToolError, ToolErrorCode};
+use crate::mcp::utils::resolve_output_language;
+use crate::neurospec::models::{Symbol, SymbolKind};
+use crate::neurospec::services::analyzer::analyze_file_thread_local;
+use crate::tr;
+
+/// 每个定义默认最多返回的行数
+const DEFAULT_MAX_LINES: usize = 400;
+/// 同名定义最多返回的数量
+const MAX_DEFINITIONS: usize = 5;
+
+/// read_symbol 工具请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReadSymbolRequest {
+    /// 项目根目录（可选，默认当前目录）
+    pub project_root: Option<String>,
+    /// 符号所在文件（相对项目根目录或绝对路径）；省略时在符号存储中按名称查找
+    #[serde(default)]
+    pub file: Option<String>,
+    /// 符号名，可用 `Type::method` 或 `Type.method` 限定所属类型
+    #[serde(default)]
+    pub symbol: Option<String>,
+    /// 完整符号 ID `path::name`（与 references 搜索和 graph 工具一致），设置后忽略 file / symbol
+    #[serde(default)]
+    pub symbol_id: Option<String>,
+    /// 是否包含定义前的文档注释、属性和装饰器（默认 true）
+    #[serde(default)]
+    pub include_comments: Option<bool>,
+    /// 每个定义最多返回的行数（默认 400）
+    #[serde(default)]
+    pub max_lines: Option<usize>,
+    /// 结果文本的语言：zh / en（默认使用配置）
+    #[serde(default)]
+    pub language: Option<OutputLanguage>,
+}
+
+/// 找到的定义
+struct Definition {
+    path: String,
+    language: &'static str,
+    symbol: Symbol,
+    /// 返回的起止行（含前置注释，1-based）
+    start: usize,
+    end: usize,
+    body: String,
+    /// 定义总行数超过 max_lines 时被截断
+    total_lines: Option<usize>,
+}
+
+/// 读取符号定义的源码
+pub async fn read_symbol(request: ReadSymbolRequest) -> Result<CallToolResult, McpToolError> {
+    let project_root = if let Some(ref root) = request.project_root {
+        PathBuf::from(root)
+    } else {
+        std::env::current_dir()?
+    };
+
+    if !project_root.exists() {
+        return Err(McpToolError::InvalidParams(format!(
+            "Project root does not exist: {}",
+            project_root.display()
+        )));
+    }
+
+    let (file, qualified) = match (&request.symbol_id, &request.symbol) {
+        (Some(id), _) => {
+            let (path, name) = id
+                .split_once("::")
+                .filter(|(path, name)| !path.is_empty() && !name.is_empty())
+                .ok_or_else(|| {
+                    McpToolError::InvalidParams(format!("symbol_id must look like `path::name`, got: {}", id))
+                })?;
+            (Some(path.to_string()), name.to_string())
+        }
+        (None, Some(symbol)) if !symbol.trim().is_empty() => (request.file.clone(), symbol.trim().to_string()),
+        _ => {
+            return Err(McpToolError::InvalidParams(
+                "Pass `symbol_id` (`path::name`) or `symbol` with an optional `file`".to_string(),
+            ))
+        }
+    };
+    let (container, name) = split_qualified(&qualified);
+    let include_comments = request.include_comments.unwrap_or(true);
+    let max_lines = request.max_lines.unwrap_or(DEFAULT_MAX_LINES).max(1);
+    let lang = resolve_output_language(request.language);
+
+    touch_project(&project_root);
+
+    let root = project_root.clone();
+    let (definitions, suggestions) = tokio::task::spawn_blocking(move || -> Result<_, McpToolError> {
+        let files = match file {
+            Some(file) => vec![relative_to_root(&file, &root)],
+            None => files_defining(&root, &name)?,
+        };
+        if files.is_empty() {
+            let suggestions = with_global_store(|store| store.did_you_mean(&root, &name, 5))
+                .map(|names| names.into_iter().map(|s| s.name).collect())
+                .unwrap_or_default();
+            return Ok((Vec::new(), suggestions));
+        }
+
+        let mut definitions = Vec::new();
+        let mut names_in_files: Vec<String> = Vec::new();
+        for file in &files {
+            let path = root.join(file);
+            let Some(language) = detect_language(&path) else {
+                return Err(McpToolError::InvalidParams(format!(
+                    "Unsupported language for {}: only rust, typescript, javascript, python, c and cpp are parsed",
+                    file
+                )));
+            };
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| McpToolError::InvalidParams(format!("Failed to read {}: {}", file, e)))?;
+            let lines: Vec<&str> = content.lines().collect();
+
+            let symbols = analyze_file_thread_local(Path::new(file), &content, language);
+            for symbol in &symbols {
+                if matches!(symbol.kind, SymbolKind::File) {
+                    continue;
+                }
+                if symbol.name != name {
+                    names_in_files.push(symbol.name.clone());
+                    continue;
+                }
+                if let Some(container) = container.as_deref() {
+                    if !is_member_of(symbol, container, &symbols, &lines, language) {
+                        continue;
+                    }
+                }
+                if let Some(definition) = extract(file, language, symbol, &lines, include_comments, max_lines) {
+                    definitions.push(definition);
+                }
+            }
+        }
+
+        let suggestions = if definitions.is_empty() {
+            SymbolNameIndex::build(names_in_files.iter().map(String::as_str))
+                .did_you_mean(&name, 5)
+                .into_iter()
+                .map(|s| s.name)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        Ok((definitions, suggestions))
+    })
+    .await
+    .map_err(|e| McpToolError::Generic(anyhow::anyhow!("Read symbol task failed: {}", e)))??;
+
+    if definitions.is_empty() {
+        let mut message = match (&request.symbol_id, &request.file) {
+            (Some(id), _) => format!("Symbol '{}' not found", id),
+            (None, Some(file)) => format!("Symbol '{}' not found in {}", qualified, file),
+            (None, None) => format!("Symbol '{}' not found in project", qualified),
+        };
+        if !suggestions.is_empty() {
+            message.push_str(&format!("; did you mean: {}", suggestions.join(", ")));
+        }
+        return Err(coded_error(ToolErrorCode::NotFound, message));
+    }
+
+    let total = definitions.len();
+    let mut output = String::new();
+    if total > MAX_DEFINITIONS {
+        output.push_str(&tr!(
+            lang,
+            "共 {} 个同名定义，仅显示前 {} 个；可用 `file` 或 `Type::method` 缩小范围\n\n",
+            "{} definitions share this name, showing the first {}; narrow down with `file` or `Type::method`\n\n",
+            total,
+            MAX_DEFINITIONS
+        ));
+    }
+    for definition in definitions.iter().take(MAX_DEFINITIONS) {
+        output.push_str(&format_definition(definition, lang));
+    }
+
+    Ok(crate::mcp::create_success_result(vec![Content::text(output.trim_end().to_string())]))
+}
+
+/// 拆分限定名：`Type::method` / `Type.method` -> (Some("Type"), "method")
+fn split_qualified(qualified: &str) -> (Option<String>, String) {
+    let split = qualified.rsplit_once("::").or_else(|| qualified.rsplit_once('.'));
+    match split {
+        Some((container, name)) if !container.is_empty() && !name.is_empty() => {
+            let container = container.rsplit(['.', ':']).next().unwrap_or(container);
+            (Some(container.to_string()), name.to_string())
+        }
+        _ => (None, qualified.to_string()),
+    }
+}
+
+/// 转为相对项目根目录的路径（项目外的绝对路径原样保留）
+fn relative_to_root(path: &str, project_root: &Path) -> String {
+    let canonical_root = project_root.canonicalize().ok();
+    let path = Path::new(path);
+    path.strip_prefix(project_root)
+        .ok()
+        .or_else(|| canonical_root.as_deref().and_then(|root| path.strip_prefix(root).ok()))
+        .unwrap_or(path)
+        .to_string_lossy()
+        .trim_start_matches("./")
+        .replace('\\', "/")
+}
+
+/// 在符号存储中查找定义了该名称的文件
+fn files_defining(project_root: &Path, name: &str) -> Result<Vec<String>, McpToolError> {
+    let paths = with_global_store(|store| {
+        // 未索引过的项目先做一次增量索引（已持久化的文件不会重新解析）
+        if !store.has_project_symbols(project_root)? {
+            store.index_project(project_root)?;
+        }
+        let filter = SymbolFilter {
+            name_prefix: Some(name.to_string()),
+            ..Default::default()
+        };
+        Ok(store
+            .query_symbols(project_root, &filter)?
+            .into_iter()
+            .filter(|symbol| symbol.name == name)
+            .map(|symbol| symbol.path)
+            .collect::<Vec<String>>())
+    })?;
+
+    let files: BTreeSet<String> = paths.iter().map(|path| relative_to_root(path, project_root)).collect();
+    Ok(files.into_iter().collect())
+}
+
+/// 定义是否属于 `container` 类型
+///
+/// 类定义范围包含该定义即可；Rust 的方法定义在 `impl` 块中（不产生符号），
+/// 向上找到第一个顶格的行，它是提到 `container` 的 `impl` 时成立
+fn is_member_of(symbol: &Symbol, container: &str, symbols: &[Symbol], lines: &[&str], language: &str) -> bool {
+    let (Some(start), Some(end)) = (symbol.start_line, symbol.end_line) else {
+        return false;
+    };
+    let in_class = symbols.iter().any(|class| {
+        matches!(class.kind, SymbolKind::Class)
+            && class.name == container
+            && class.start_line.is_some_and(|s| s <= start)
+            && class.end_line.is_some_and(|e| end <= e)
+    });
+    if in_class || language != "rust" {
+        return in_class;
+    }
+
+    lines[..(start as usize).saturating_sub(1).min(lines.len())]
+        .iter()
+        .rev()
+        .find(|line| !line.trim().is_empty() && !line.starts_with(char::is_whitespace))
+        .is_some_and(|line| line.starts_with("impl") && contains_identifier(line, container))
+}
+
+/// 截取定义源码（可选包含前置注释、属性和装饰器）
+fn extract(
+    file: &str,
+    language: &'static str,
+    symbol: &Symbol,
+    lines: &[&str],
+    include_comments: bool,
+    max_lines: usize,
+) -> Option<Definition> {
+    let def_start = symbol.start_line? as usize;
+    let end = (symbol.end_line? as usize).min(lines.len());
+    if def_start == 0 || def_start > end {
+        return None;
+    }
+
+    let mut start = def_start;
+    if include_comments {
+        while start > 1 && is_leading_line(lines[start - 2], language) {
+            start -= 1;
+        }
+    }
+
+    let total = end + 1 - start;
+    let shown_end = if total > max_lines { start + max_lines - 1 } else { end };
+    let body: String = lines[start - 1..shown_end].iter().map(|line| format!("{}\n", line)).collect();
+
+    Some(Definition {
+        path: file.to_string(),
+        language,
+        symbol: symbol.clone(),
+        start,
+        end: shown_end,
+        body,
+        total_lines: (total > max_lines).then_some(total),
+    })
+}
+
+/// 定义前可归属于它的行：文档注释、普通注释、属性和装饰器
+fn is_leading_line(line: &str, language: &str) -> bool {
+    let trimmed = line.trim_start();
+    if trimmed.is_empty() {
+        return false;
+    }
+    match language {
+        "python" => trimmed.starts_with('#') || trimmed.starts_with('@'),
+        "rust" => trimmed.starts_with("//") || trimmed.starts_with("#[") || is_block_comment(trimmed),
+        "typescript" | "javascript" => trimmed.starts_with("//") || trimmed.starts_with('@') || is_block_comment(trimmed),
+        _ => trimmed.starts_with("//") || is_block_comment(trimmed),
+    }
+}
+
+fn is_block_comment(trimmed: &str) -> bool {
+    trimmed.starts_with("/*") || trimmed.starts_with('*')
+}
+
+/// 行中是否含有完整的标识符
+fn contains_identifier(line: &str, name: &str) -> bool {
+    let is_identifier_char = |c: char| c.is_alphanumeric() || c == '_';
+    line.match_indices(name).any(|(start, _)| {
+        let end = start + name.len();
+        !line[..start].chars().next_back().is_some_and(is_identifier_char)
+            && !line[end..].chars().next().is_some_and(is_identifier_char)
+    })
+}
+
+fn format_definition(definition: &Definition, lang: OutputLanguage) -> String {
+    let kind = format!("{:?}", definition.symbol.kind).to_lowercase();
+    let mut output = format!(
+        "### 📄 `{}:{}-{}` {} `{}`\n",
+        definition.path, definition.start, definition.end, kind, definition.symbol.name
+    );
+    output.push_str(&format!("```{}\n", definition.language));
+    output.push_str(&definition.body);
+    output.push_str("```\n");
+    if let Some(total) = definition.total_lines {
+        output.push_str(&tr!(
+            lang,
+            "⚠️ 定义共 {} 行，已截断；可调大 max_lines\n",
+            "⚠️ Truncated: the definition has {} lines; raise max_lines to see more\n",
+            total
+        ));
+    }
+    output.push('\n');
+    output
+}
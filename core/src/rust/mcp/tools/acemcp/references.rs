@@ -0,0 +1,164 @@
+//! 基于代码图谱的引用查找（mode = references）
+//!
+//! 先在图谱中找到符号定义，再沿反向边（调用、导入、继承、引用）得到使用它的符号，
+//! 只在这些符号的定义范围内（导入则在所有定义之外的顶层代码中）定位标识符出现的行。
+//! 同名局部变量、无关函数中的同名文本不在图谱的引用者范围内，不会返回。
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::local_engine::{MatchInfo, SearchFilters, SearchResult, SnippetContext};
+use crate::neurospec::models::SymbolKind;
+use crate::neurospec::services::graph::{CodeGraph, RelationType, SymbolNode};
+
+/// 每处引用前后附带的上下文行数
+const CONTEXT_LINES: usize = 1;
+
+/// 按名称或 ID（`path::name`）查找图谱中的符号定义（不含文件节点），按 ID 排序
+pub fn find_definitions<'a>(graph: &'a CodeGraph, query: &str) -> Vec<&'a SymbolNode> {
+    let mut definitions: Vec<&SymbolNode> = graph
+        .graph
+        .node_weights()
+        .filter(|node| !matches!(node.kind, SymbolKind::File))
+        .filter(|node| node.id == query || node.name == query)
+        .collect();
+    definitions.sort_by(|a, b| a.id.cmp(&b.id));
+    definitions
+}
+
+/// 查找定义的全部引用位置，每处出现一个结果，按文件和行号排序
+///
+/// `definitions` 应同名（[`find_definitions`] 的结果）；定义所在行本身不算引用
+pub fn find_references(
+    graph: &CodeGraph,
+    definitions: &[&SymbolNode],
+    project_root: &Path,
+    filters: &SearchFilters,
+) -> Vec<SearchResult> {
+    let Some(name) = definitions.first().map(|definition| definition.name.as_str()) else {
+        return Vec::new();
+    };
+
+    // 文件 -> 该文件中使用符号的图谱节点及关系
+    let mut users: BTreeMap<&str, Vec<(&SymbolNode, RelationType)>> = BTreeMap::new();
+    for definition in definitions {
+        for (user, relation) in graph.references_to(&definition.id) {
+            let entries = users.entry(user.file_path.as_str()).or_default();
+            if !entries.iter().any(|(existing, _)| existing.id == user.id) {
+                entries.push((user, relation));
+            }
+        }
+    }
+
+    let mut results = Vec::new();
+    for (file_path, file_users) in users {
+        let rel_path = relative_path(file_path, project_root);
+        if !filters.matches_path(&rel_path) {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(project_root.join(&rel_path)) else {
+            continue;
+        };
+        let lines: Vec<&str> = content.lines().collect();
+        let imported = file_users.iter().any(|(user, _)| matches!(user.kind, SymbolKind::File));
+
+        for (index, line) in lines.iter().enumerate() {
+            let line_number = index + 1;
+            if !contains_identifier(line, name) {
+                continue;
+            }
+            let is_definition = definitions.iter().any(|definition| {
+                definition.file_path == file_path && definition.start_line == Some(line_number as u32)
+            });
+            if is_definition {
+                continue;
+            }
+
+            // 包含该行的最内层引用者；不在任何定义内的行只可能是导入
+            let user = file_users
+                .iter()
+                .filter(|(user, _)| !matches!(user.kind, SymbolKind::File))
+                .filter_map(|&(user, relation)| {
+                    let start = user.start_line? as usize;
+                    let end = user.end_line? as usize;
+                    (start <= line_number && line_number <= end).then_some((user, relation, end - start))
+                })
+                .min_by_key(|&(_, _, span)| span)
+                .map(|(user, relation, _)| (user, relation));
+            let (parent, relation) = match user {
+                Some((user, relation)) => (Some(user), relation),
+                None if imported && graph.enclosing_symbol(file_path, line_number as u32).is_none() => {
+                    (None, RelationType::Imports)
+                }
+                None => continue,
+            };
+
+            let kind = parent.map(|user| format!("{:?}", user.kind).to_lowercase());
+            if !filters.matches(&rel_path, kind.as_deref()) {
+                continue;
+            }
+
+            let from = index.saturating_sub(CONTEXT_LINES);
+            let to = (index + CONTEXT_LINES + 1).min(lines.len());
+            let snippet: String = lines[from..to].iter().map(|l| format!("{}\n", l)).collect();
+
+            results.push(SearchResult {
+                path: rel_path.clone(),
+                score: 1.0,
+                snippet,
+                line_number,
+                context: Some(SnippetContext {
+                    module: None,
+                    parent_symbol: parent.map(|user| user.name.clone()),
+                    symbol_kind: kind,
+                    visibility: None,
+                    doc_comment: None,
+                    signature: parent.and_then(|user| user.signature.clone()),
+                }),
+                match_info: Some(MatchInfo {
+                    matched_terms: vec![name.to_string()],
+                    match_type: "reference".to_string(),
+                    match_quality: relation_label(relation).to_string(),
+                }),
+                score_breakdown: None,
+                matched_lines: Vec::new(),
+            });
+        }
+    }
+
+    results
+}
+
+/// 引用关系的显示名
+pub fn relation_label(relation: RelationType) -> &'static str {
+    match relation {
+        RelationType::Calls => "call",
+        RelationType::Imports => "import",
+        RelationType::Inherits => "inherit",
+        RelationType::References => "usage",
+        RelationType::Defines => "definition",
+    }
+}
+
+/// 图谱中的路径转为相对项目根目录的路径（符号存储中的路径可能是绝对路径）
+pub fn relative_path(path: &str, project_root: &Path) -> String {
+    let canonical_root = project_root.canonicalize().ok();
+    let path = Path::new(path);
+    path.strip_prefix(project_root)
+        .ok()
+        .or_else(|| canonical_root.as_deref().and_then(|root| path.strip_prefix(root).ok()))
+        .unwrap_or(path)
+        .to_string_lossy()
+        .trim_start_matches("./")
+        .replace('\\', "/")
+}
+
+/// 行中是否含有完整的标识符 `name`（`load` 不匹配 `load_all` 或 `reload`）
+fn contains_identifier(line: &str, name: &str) -> bool {
+    let is_identifier_char = |c: char| c.is_alphanumeric() || c == '_';
+    line.match_indices(name).any(|(start, _)| {
+        let end = start + name.len();
+        !line[..start].chars().next_back().is_some_and(is_identifier_char)
+            && !line[end..].chars().next().is_some_and(is_identifier_char)
+    })
+}
@@ -0,0 +1,63 @@
+//! 统一符号存储统计工具
+//!
+//! 为 UI 索引面板与 MCP 调用方提供文件数、符号数、语言分布、内存占用等真实数据
+
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use rmcp::model::{CallToolResult, Content};
+use crate::mcp::utils::errors::McpToolError;
+use crate::mcp::tools::unified_store::{with_global_store, IndexStats, StoreMetrics};
+
+/// store_stats 工具请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StoreStatsRequest {
+    /// 项目根目录（可选，不填则返回所有已知项目）
+    pub project_root: Option<String>,
+}
+
+/// 存储统计响应
+#[derive(Debug, Serialize)]
+pub struct StoreStatsResponse {
+    /// 存储整体运行指标
+    pub metrics: StoreMetrics,
+    /// 各项目统计
+    pub projects: Vec<IndexStats>,
+}
+
+/// 收集存储统计（供 MCP 工具与 Tauri 命令共用）
+pub fn collect_store_stats(project_root: Option<&str>) -> anyhow::Result<StoreStatsResponse> {
+    with_global_store(|store| {
+        let projects = match project_root {
+            Some(root) => vec![store.project_stats(&PathBuf::from(root))?],
+            None => store.all_project_stats()?,
+        };
+
+        Ok(StoreStatsResponse {
+            metrics: store.metrics(),
+            projects,
+        })
+    })
+}
+
+/// 执行存储统计查询
+pub async fn store_stats(request: StoreStatsRequest) -> Result<CallToolResult, McpToolError> {
+    if let Some(ref root) = request.project_root {
+        if !PathBuf::from(root).exists() {
+            return Err(McpToolError::InvalidParams(format!(
+                "Project root does not exist: {}",
+                root
+            )));
+        }
+    }
+
+    let response = tokio::task::spawn_blocking(move || {
+        collect_store_stats(request.project_root.as_deref())
+    })
+    .await
+    .map_err(|e| McpToolError::Generic(anyhow::anyhow!("Store stats task failed: {}", e)))??;
+
+    let json = serde_json::to_string_pretty(&response)?;
+
+    Ok(crate::mcp::create_success_result(vec![Content::text(json)]))
+}
@@ -0,0 +1,188 @@
+//! 符号查询工具
+//!
+//! 直接查询统一符号存储，按路径/类型/名称/语言过滤，避免消费者自行扫描全量符号；
+//! 支持按编辑距离模糊匹配名称，名称条件无结果时给出拼写建议
+
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use rmcp::model::{CallToolResult, Content};
+use crate::mcp::utils::errors::McpToolError;
+use crate::mcp::tools::unified_store::{
+    default_edit_distance, touch_project, with_global_store, SymbolFilter, SymbolKind, UnifiedSymbol, MAX_EDIT_DISTANCE,
+};
+
+/// 默认最大返回数量
+const DEFAULT_LIMIT: usize = 200;
+/// 最大返回数量上限
+const MAX_LIMIT: usize = 2000;
+
+/// symbols 工具请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SymbolsRequest {
+    /// 项目根目录（可选，默认当前目录）
+    pub project_root: Option<String>,
+    /// 文件相对路径前缀，如 `src/mcp/`
+    #[serde(default)]
+    pub path_prefix: Option<String>,
+    /// 文件相对路径子串
+    #[serde(default)]
+    pub path_contains: Option<String>,
+    /// 符号类型：file / module / class / function / variable
+    #[serde(default)]
+    pub kinds: Option<Vec<String>>,
+    /// 名称前缀（大小写不敏感）
+    #[serde(default)]
+    pub name_prefix: Option<String>,
+    /// 名称子串（大小写不敏感）
+    #[serde(default)]
+    pub name_contains: Option<String>,
+    /// 语言，如 rust / typescript / python
+    #[serde(default)]
+    pub language: Option<String>,
+    /// 模糊名称：按编辑距离匹配（大小写不敏感），结果按距离排序；设置后忽略名称前缀/子串
+    #[serde(default)]
+    pub fuzzy_name: Option<String>,
+    /// 模糊匹配的最大编辑距离（0-3，默认按名称长度取 1-3）
+    #[serde(default)]
+    pub max_edit_distance: Option<usize>,
+    /// 最大返回数量（默认 200，上限 2000）
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// symbols 工具响应
+#[derive(Debug, Serialize)]
+pub struct SymbolsResponse {
+    pub total: usize,
+    pub truncated: bool,
+    pub symbols: Vec<SymbolHit>,
+    /// 名称条件无结果时，与之相近的符号名
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub did_you_mean: Vec<String>,
+}
+
+/// 查询结果中的单个符号
+#[derive(Debug, Serialize)]
+pub struct SymbolHit {
+    #[serde(flatten)]
+    pub symbol: UnifiedSymbol,
+    /// 模糊匹配时名称与查询的编辑距离
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distance: Option<usize>,
+}
+
+impl SymbolsRequest {
+    /// 转换为存储层过滤条件
+    fn to_filter(&self) -> Result<SymbolFilter, McpToolError> {
+        let mut kinds = Vec::new();
+        for kind in self.kinds.iter().flatten() {
+            let parsed = SymbolKind::parse(kind).ok_or_else(|| {
+                McpToolError::InvalidParams(format!(
+                    "Unknown symbol kind: {}. Supported kinds: file, module, class, function, variable",
+                    kind
+                ))
+            })?;
+            kinds.push(parsed);
+        }
+
+        if let Some(distance) = self.max_edit_distance {
+            if distance > MAX_EDIT_DISTANCE {
+                return Err(McpToolError::InvalidParams(format!(
+                    "max_edit_distance must be between 0 and {}",
+                    MAX_EDIT_DISTANCE
+                )));
+            }
+        }
+
+        let limit = self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+        Ok(SymbolFilter {
+            path_prefix: self.path_prefix.clone(),
+            path_contains: self.path_contains.clone(),
+            kinds,
+            name_prefix: self.name_prefix.clone(),
+            name_contains: self.name_contains.clone(),
+            language: self.language.clone(),
+            // 多取一条用于判断是否截断
+            limit: Some(limit + 1),
+        })
+    }
+}
+
+/// 执行符号查询
+pub async fn query_symbols(request: SymbolsRequest) -> Result<CallToolResult, McpToolError> {
+    let project_root = if let Some(ref root) = request.project_root {
+        PathBuf::from(root)
+    } else {
+        std::env::current_dir()?
+    };
+
+    if !project_root.exists() {
+        return Err(McpToolError::InvalidParams(format!(
+            "Project root does not exist: {}",
+            project_root.display()
+        )));
+    }
+
+    let filter = request.to_filter()?;
+    touch_project(&project_root);
+    let limit = filter.limit.unwrap_or(DEFAULT_LIMIT + 1) - 1;
+    let fuzzy_name = request.fuzzy_name.clone().filter(|name| !name.trim().is_empty());
+    let max_distance = request.max_edit_distance;
+    // 名称条件无结果时用于拼写建议的词
+    let name_term = request.name_prefix.clone().or_else(|| request.name_contains.clone());
+
+    let (mut symbols, total, did_you_mean) = tokio::task::spawn_blocking(move || {
+        with_global_store(|store| {
+            // 未索引过的项目先做一次增量索引（已持久化的文件不会重新解析）
+            if !store.has_project_symbols(&project_root)? {
+                store.index_project(&project_root)?;
+            }
+
+            if let Some(name) = fuzzy_name {
+                let distance = max_distance.unwrap_or_else(|| default_edit_distance(&name));
+                let unlimited = SymbolFilter { limit: None, ..filter };
+                let hits: Vec<SymbolHit> = store
+                    .fuzzy_symbols(&project_root, &name, distance, &unlimited)?
+                    .into_iter()
+                    .map(|m| SymbolHit { symbol: m.symbol, distance: Some(m.distance) })
+                    .collect();
+                let total = hits.len();
+                return Ok((hits, total, Vec::new()));
+            }
+
+            let total = store.count_symbols(&project_root, &filter)?;
+            let hits: Vec<SymbolHit> = store
+                .query_symbols(&project_root, &filter)?
+                .into_iter()
+                .map(|symbol| SymbolHit { symbol, distance: None })
+                .collect();
+            let did_you_mean = match name_term {
+                Some(term) if hits.is_empty() => store
+                    .did_you_mean(&project_root, &term, 5)?
+                    .into_iter()
+                    .map(|suggestion| suggestion.name)
+                    .collect(),
+                _ => Vec::new(),
+            };
+            Ok((hits, total, did_you_mean))
+        })
+    })
+    .await
+    .map_err(|e| McpToolError::Generic(anyhow::anyhow!("Symbol query task failed: {}", e)))??;
+
+    let truncated = symbols.len() > limit;
+    symbols.truncate(limit);
+
+    let response = SymbolsResponse {
+        total,
+        truncated,
+        symbols,
+        did_you_mean,
+    };
+
+    let json = serde_json::to_string_pretty(&response)?;
+
+    Ok(crate::mcp::create_success_result(vec![Content::text(json)]))
+}
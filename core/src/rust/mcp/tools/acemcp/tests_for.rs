@@ -0,0 +1,209 @@
+//! 查找覆盖某个符号的测试（profile = tests_for）
+//!
+//! 三类线索，按可信度从高到低：
+//! 1. 调用图：沿反向边（调用、导入、继承）在 [`MAX_CALL_DEPTH`] 跳内到达的测试函数或测试文件
+//! 2. 测试函数命名：名称包含符号名的测试函数（`test_parse_config`、`TestParseConfig` 等）
+//! 3. 测试文件命名：与定义所在文件同名的测试文件（`config_test.go`、`config.spec.ts`、`test_config.py` 等）
+//!
+//! 同一个测试只保留可信度最高的线索；测试文件中已有函数级结果时不再列出文件本身。
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use super::local_engine::SearchFilters;
+use super::references::relative_path;
+use crate::neurospec::models::SymbolKind;
+use crate::neurospec::services::graph::{CodeGraph, SymbolNode};
+use crate::neurospec::services::risk::is_test_path;
+
+/// 沿调用图查找测试的最大跳数（测试通常直接调用，或经过一层测试辅助函数）
+const MAX_CALL_DEPTH: usize = 2;
+
+/// 按名称匹配测试函数时，符号名（规范化后）的最短长度，避免 `new`、`run` 之类的名字误报
+const MIN_NAME_MATCH_LEN: usize = 4;
+
+/// 每个定义最多列出的测试数
+const MAX_TESTS_PER_DEFINITION: usize = 20;
+
+/// 测试与定义之间的关联依据
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TestLinkReason {
+    /// 测试经 `depth` 跳调用 / 导入 / 继承到达定义
+    CallGraph { depth: usize },
+    /// 测试函数名包含符号名
+    TestName,
+    /// 测试文件与定义所在文件同名
+    FileName,
+}
+
+impl TestLinkReason {
+    /// 显示名
+    pub fn label(&self) -> String {
+        match self {
+            Self::CallGraph { depth: 1 } => "call graph".to_string(),
+            Self::CallGraph { depth } => format!("call graph, {} hops", depth),
+            Self::TestName => "test name".to_string(),
+            Self::FileName => "test file name".to_string(),
+        }
+    }
+}
+
+/// 一个测试（函数，或无法定位到函数时的整个测试文件）
+#[derive(Debug, Clone)]
+pub struct TestLink {
+    /// 相对项目根目录的路径
+    pub path: String,
+    /// 测试函数名，文件级结果为 None
+    pub name: Option<String>,
+    pub line: Option<u32>,
+    pub reason: TestLinkReason,
+}
+
+/// 生产代码定义及覆盖它的测试
+#[derive(Debug, Clone)]
+pub struct TestPair {
+    /// 定义位置（`path:line (Kind)`）
+    pub definition: String,
+    pub tests: Vec<TestLink>,
+}
+
+/// 为每个定义查找测试，测试路径需满足 `filters` 中的语言和路径条件
+///
+/// 定义本身位于测试文件中时跳过（查找的是生产代码的测试）
+pub fn find_tests(
+    graph: &CodeGraph,
+    definitions: &[&SymbolNode],
+    project_root: &Path,
+    filters: &SearchFilters,
+) -> Vec<TestPair> {
+    let test_files: Vec<String> = graph
+        .graph
+        .node_weights()
+        .filter(|node| matches!(node.kind, SymbolKind::File))
+        .map(|node| relative_path(&node.file_path, project_root))
+        .filter(|path| is_test_path(path) && filters.matches_path(path))
+        .collect();
+
+    definitions
+        .iter()
+        .filter(|definition| !is_test_path(&relative_path(&definition.file_path, project_root)))
+        .map(|definition| {
+            let rel_path = relative_path(&definition.file_path, project_root);
+            let location = match definition.start_line {
+                Some(line) => format!("{}:{} ({:?})", rel_path, line, definition.kind),
+                None => format!("{} ({:?})", rel_path, definition.kind),
+            };
+
+            let mut tests = Vec::new();
+            let mut seen: HashSet<(String, Option<String>)> = HashSet::new();
+            let mut push = |tests: &mut Vec<TestLink>, link: TestLink| {
+                if seen.insert((link.path.clone(), link.name.clone())) {
+                    tests.push(link);
+                }
+            };
+
+            // 1. 调用图
+            for (node, depth) in graph.transitive_dependents(&definition.id, MAX_CALL_DEPTH) {
+                let path = relative_path(&node.file_path, project_root);
+                if !filters.matches_path(&path) {
+                    continue;
+                }
+                let reason = TestLinkReason::CallGraph { depth };
+                if matches!(node.kind, SymbolKind::File) {
+                    if is_test_path(&path) {
+                        push(&mut tests, TestLink { path, name: None, line: None, reason });
+                    }
+                } else if is_test_name(&node.name) || is_test_path(&path) {
+                    push(&mut tests, test_link(node, path, reason));
+                }
+            }
+
+            // 2. 测试函数命名
+            let key = normalize_name(&definition.name);
+            if key.len() >= MIN_NAME_MATCH_LEN {
+                let mut named: Vec<&SymbolNode> = graph
+                    .graph
+                    .node_weights()
+                    .filter(|node| matches!(node.kind, SymbolKind::Function | SymbolKind::Class))
+                    .filter(|node| is_test_name(&node.name) && normalize_name(&node.name).contains(&key))
+                    .collect();
+                named.sort_by(|a, b| a.id.cmp(&b.id));
+                for node in named {
+                    let path = relative_path(&node.file_path, project_root);
+                    if filters.matches_path(&path) {
+                        push(&mut tests, test_link(node, path, TestLinkReason::TestName));
+                    }
+                }
+            }
+
+            // 3. 测试文件命名（文件中已有函数级结果时不再列出）
+            let stem = file_stem(&rel_path);
+            for path in &test_files {
+                if tested_stem(path) != stem || tests.iter().any(|test| test.path == *path) {
+                    continue;
+                }
+                push(
+                    &mut tests,
+                    TestLink { path: path.clone(), name: None, line: None, reason: TestLinkReason::FileName },
+                );
+            }
+
+            tests.sort_by(|a, b| {
+                a.reason
+                    .cmp(&b.reason)
+                    .then_with(|| a.path.cmp(&b.path))
+                    .then_with(|| a.line.cmp(&b.line))
+            });
+            tests.truncate(MAX_TESTS_PER_DEFINITION);
+
+            TestPair { definition: location, tests }
+        })
+        .collect()
+}
+
+fn test_link(node: &SymbolNode, path: String, reason: TestLinkReason) -> TestLink {
+    TestLink {
+        path,
+        name: Some(node.name.clone()),
+        line: node.start_line,
+        reason,
+    }
+}
+
+/// 按常见命名约定判断是否为测试函数 / 测试类
+/// （`test_foo`、`testFoo`、`TestFoo`、`foo_test`、`FooTest`、`FooSpec`）
+fn is_test_name(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    lower.starts_with("test")
+        || lower.ends_with("_test")
+        || lower.ends_with("_spec")
+        || name.ends_with("Test")
+        || name.ends_with("Tests")
+        || name.ends_with("Spec")
+}
+
+/// 去掉下划线并转小写，使 `parse_config`、`parseConfig`、`ParseConfig` 一致
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// 文件名去掉所有扩展名后的部分（规范化）
+fn file_stem(path: &str) -> String {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    normalize_name(file_name.split('.').next().unwrap_or(file_name))
+}
+
+/// 测试文件对应的被测文件名（规范化）：去掉 `test_` 前缀、`_test` / `_spec` / `Test` 后缀和 `.test` / `.spec`
+fn tested_stem(path: &str) -> String {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    let stem = file_name.split('.').next().unwrap_or(file_name);
+    let stem = stem.strip_prefix("test_").unwrap_or(stem);
+    let stem = ["_test", "_spec", "Tests", "Test", "Spec"]
+        .iter()
+        .find_map(|suffix| stem.strip_suffix(suffix))
+        .unwrap_or(stem);
+    normalize_name(stem)
+}
@@ -34,6 +34,7 @@ fn profile_schema(gen: &mut SchemaGenerator) -> Schema {
 /// - text: 全文搜索（自然语言）
 /// - symbol: 符号定义搜索
 /// - structure: 仅项目结构概览（老模式）
+/// - references: 基于代码图谱查找符号的调用点和使用位置
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
 #[serde(rename_all = "lowercase")]
 #[schemars(rename_all = "lowercase")]
@@ -41,6 +42,7 @@ pub enum SearchMode {
     Text,
     Symbol,
     Structure,
+    References,
 }
 
 /// 搜索范围类型
@@ -55,13 +57,15 @@ pub enum SearchScopeKind {
     File,
     /// 聚焦某个符号（函数 / 类等）
     Symbol,
+    /// 仅 `git status` 中有未提交修改的文件（含未跟踪文件）
+    Dirty,
 }
 
 /// 搜索范围配置
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct SearchScope {
-    /// 范围类型：project / folder / file / symbol
-    #[schemars(description = "Scope kind: project/folder/file/symbol")]
+    /// 范围类型：project / folder / file / symbol / dirty
+    #[schemars(description = "Scope kind: project/folder/file/symbol/dirty. `dirty` limits the search to files with uncommitted changes in `git status` (including untracked files).")]
     pub kind: SearchScopeKind,
 
     /// 当 kind = folder/file 时的路径（相对或绝对，后端会规范化）
@@ -93,7 +97,7 @@ pub enum SearchProfile {
     SmartStructure {
         /// 搜索范围（默认整个项目）
         #[serde(default)]
-        #[schemars(description = "Optional search scope: project/folder/file/symbol.")]
+        #[schemars(description = "Optional search scope: project/folder/file/symbol/dirty.")]
         scope: Option<SearchScope>,
 
         /// 期望的最大结果数（soft limit）
@@ -114,6 +118,16 @@ pub enum SearchProfile {
         #[schemars(description = "Optional max number of modules/nodes to include.")]
         max_nodes: Option<u32>,
     },
+
+    /// 查找覆盖某个符号的测试，返回「生产代码 ↔ 测试」配对
+    ///
+    /// 依据调用图（测试直接或经测试辅助函数调用该符号）以及测试函数 / 测试文件的命名约定
+    TestsFor {
+        /// 符号名或 ID（`path::name`），为空则回退到 query
+        #[serde(default)]
+        #[schemars(description = "Symbol name or ID (`path::name`) to find tests for. Falls back to `query` if omitted.")]
+        symbol: Option<String>,
+    },
 }
 
 /// Code search request parameters
@@ -128,13 +142,14 @@ pub struct SearchRequest {
     ///
     /// - For SmartStructure: natural language, e.g. "fix search JSON error"
     /// - For StructureOnly: may be empty, meaning "just show structure"
+    /// - Text search understands phrases, boolean operators and `field:value`
     #[serde(default)]
-    #[schemars(description = "Primary search query. For smart structure search, use natural language. For structure-only mode, may be empty.")]
+    #[schemars(description = "Primary search query. For smart structure search, use natural language. For structure-only mode, may be empty. Text search supports \"exact phrases\", AND/OR/NOT (or +term/-term), parentheses and field:value (path:, symbol:, content:, lang:, kind:), e.g. path:handlers \"token refresh\".")]
     pub query: String,
 
     /// 低层搜索模式（兼容旧调用，不推荐 LLM 直接设置）
     #[serde(default)]
-    #[schemars(description = "Legacy low-level mode. Prefer using `profile` for new callers.")]
+    #[schemars(description = "Legacy low-level mode. Prefer using `profile` for new callers. Use `references` with a symbol name or ID (`path::name`) in `query` to find all call sites and usages.")]
     pub mode: Option<SearchMode>,
 
     /// 高层搜索策略（推荐）
@@ -146,79 +161,62 @@ pub struct SearchRequest {
     #[serde(default)]
     #[schemars(schema_with = "profile_schema")]
     pub profile: Option<SearchProfile>,
-}
 
-/// Legacy alias for backward compatibility
-pub type AcemcpRequest = SearchRequest;
+    /// 结果文本的语言（None 时使用配置）
+    #[serde(default)]
+    #[schemars(description = "Language of the formatted results: 'zh' or 'en' (optional, defaults to the output language in settings)")]
+    pub language: Option<crate::config::OutputLanguage>,
 
-/// 搜索错误码（机器可解析）
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum SearchErrorCode {
-    /// 索引尚未就绪，正在后台构建
-    IndexNotReady,
-    /// 项目路径无效或不存在
-    InvalidProjectPath,
-    /// 文件读取/写入错误
-    IoError,
-    /// 搜索引擎内部错误
-    SearchEngineError,
-    /// 未知错误
-    UnknownError,
-}
+    /// 只搜索这些语言的文件
+    #[serde(default)]
+    #[schemars(description = "Optional: only search files of these languages, e.g. [\"rust\", \"typescript\"]. Accepts rust, typescript, javascript, python, go, java, c, cpp, vue, svelte, kotlin, swift, csharp, ruby, php, scala and common aliases (rs, ts, js, py, kt, cs, rb).")]
+    pub languages: Option<Vec<String>>,
 
-/// 结构化搜索错误响应
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SearchError {
-    /// 机器可解析的错误码
-    pub code: SearchErrorCode,
-    /// 人类可读的错误消息
-    pub message: String,
-    /// 是否可重试
-    pub retryable: bool,
-}
+    /// 相对项目根目录的路径 glob
+    #[serde(default)]
+    #[schemars(description = "Optional: only search paths matching this glob, relative to the project root, e.g. \"core/src/**/*.rs\". A glob without '/' matches file names in any directory.")]
+    pub path_glob: Option<String>,
 
-impl SearchError {
-    pub fn index_not_ready() -> Self {
-        Self {
-            code: SearchErrorCode::IndexNotReady,
-            message: "索引尚未就绪，正在后台构建中。请稍后重试，或使用 ripgrep 回退搜索。".to_string(),
-            retryable: true,
-        }
-    }
+    /// 只返回包含这些种类符号的文件
+    #[serde(default)]
+    #[schemars(description = "Optional: only return files defining symbols of these kinds: function, method, class, struct, interface (includes traits), enum. Not applied by the plain-text ripgrep fallback used while the index is building.")]
+    pub symbol_kinds: Option<Vec<String>>,
 
-    pub fn invalid_project_path(path: &str) -> Self {
-        Self {
-            code: SearchErrorCode::InvalidProjectPath,
-            message: format!("项目路径无效或不存在: {}", path),
-            retryable: false,
-        }
-    }
+    /// 调试：在每个结果下输出分数构成
+    #[serde(default)]
+    #[schemars(description = "Optional debug flag: show a score breakdown for each hit (per-field lexical scores, semantic similarity, ranking weights). Ranking weights can be overridden per project in .neurospec/search.toml.")]
+    pub rank_explain: Option<bool>,
 
-    pub fn io_error(detail: &str) -> Self {
-        Self {
-            code: SearchErrorCode::IoError,
-            message: format!("文件读取/写入错误: {}", detail),
-            retryable: true,
-        }
-    }
+    /// 符号模式下按编辑距离模糊匹配符号名
+    #[serde(default)]
+    #[schemars(description = "Optional: with mode 'symbol', match symbol names by edit distance instead of exactly, e.g. 'fetchUsr' finds 'fetchUser'. Uses the symbol store; path_glob, languages and symbol_kinds still apply.")]
+    pub fuzzy: Option<bool>,
 
-    pub fn search_engine_error(detail: &str) -> Self {
-        Self {
-            code: SearchErrorCode::SearchEngineError,
-            message: format!("搜索引擎内部错误: {}", detail),
-            retryable: true,
-        }
-    }
+    /// 模糊符号匹配的最大编辑距离
+    #[serde(default)]
+    #[schemars(description = "Optional: maximum edit distance for fuzzy symbol matching (0-3). Defaults to 1 for names up to 4 characters, 2 up to 8, 3 beyond.")]
+    pub max_edit_distance: Option<u8>,
 
-    /// 格式化为 JSON 字符串（用于 MCP 返回）
-    pub fn to_json(&self) -> String {
-        serde_json::to_string(self).unwrap_or_else(|_| {
-            format!(r#"{{"code":"UNKNOWN_ERROR","message":"{}","retryable":false}}"#, self.message)
-        })
+    /// 合并同一函数内的多个命中（默认开启）
+    #[serde(default)]
+    #[schemars(description = "Optional: merge hits inside the same function into one result listing all matched line numbers (default true). Set false to get every hit separately.")]
+    pub dedupe: Option<bool>,
+}
+
+impl SearchRequest {
+    /// 校验并规范化请求中的过滤条件
+    pub fn filters(&self) -> Result<super::local_engine::SearchFilters, String> {
+        super::local_engine::SearchFilters::new(
+            self.languages.as_deref().unwrap_or_default(),
+            self.path_glob.as_deref(),
+            self.symbol_kinds.as_deref().unwrap_or_default(),
+        )
     }
 }
 
+/// Legacy alias for backward compatibility
+pub type AcemcpRequest = SearchRequest;
+
 /// 搜索追踪信息（用于结构化日志和调试）
 #[derive(Debug, Clone, Serialize)]
 pub struct SearchTrace {
@@ -242,6 +240,12 @@ pub struct SearchTrace {
     pub fallback_chain: Vec<String>,
     /// 是否触发了索引
     pub triggered_indexing: bool,
+    /// Tantivy reader 是否复用自 Searcher 池（未使用 Tantivy 时为 None）
+    pub reader_pooled: Option<bool>,
+    /// 获取 reader 的耗时（毫秒）
+    pub reader_acquire_ms: Option<f64>,
+    /// 复用 reader 节省的耗时估计（按新打开索引的平均耗时计算，毫秒）
+    pub reader_saved_ms: Option<f64>,
 }
 
 impl SearchTrace {
@@ -257,8 +261,22 @@ impl SearchTrace {
             duration_ms: 0,
             fallback_chain: Vec::new(),
             triggered_indexing: false,
+            reader_pooled: None,
+            reader_acquire_ms: None,
+            reader_saved_ms: None,
         }
     }
+
+    /// 记录 reader 的获取方式与耗时
+    pub fn record_reader(&mut self, pooled: bool, acquire_ms: f64, average_open_ms: Option<f64>) {
+        self.reader_pooled = Some(pooled);
+        self.reader_acquire_ms = Some(acquire_ms);
+        self.reader_saved_ms = if pooled {
+            average_open_ms.map(|open_ms| (open_ms - acquire_ms).max(0.0))
+        } else {
+            None
+        };
+    }
     
     fn generate_request_id() -> String {
         use std::time::{SystemTime, UNIX_EPOCH};
@@ -7,7 +7,8 @@ use std::path::PathBuf;
 use std::sync::OnceLock;
 
 use anyhow::Result;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// 历史记录文件名
@@ -35,6 +36,75 @@ pub struct InteractRecord {
     pub selected_options: Vec<String>,
     /// 项目路径
     pub project_path: Option<String>,
+    /// 用户未在超时前响应（选中的选项为默认选项，或没有响应）
+    #[serde(default)]
+    pub timed_out: bool,
+}
+
+/// 响应类型（用于过滤）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseKind {
+    /// 选择了预定义选项
+    Option,
+    /// 输入了文本
+    Text,
+    /// 超时未响应（含使用默认选项）
+    TimedOut,
+    /// 没有任何响应内容
+    Empty,
+}
+
+impl InteractRecord {
+    /// 记录是否属于指定的响应类型（同时选择选项并输入文本的记录同时属于两类）
+    pub fn has_response_kind(&self, kind: ResponseKind) -> bool {
+        let has_text = self.user_response.as_deref().is_some_and(|s| !s.trim().is_empty());
+        match kind {
+            ResponseKind::Option => !self.selected_options.is_empty(),
+            ResponseKind::Text => has_text,
+            ResponseKind::TimedOut => self.timed_out,
+            ResponseKind::Empty => !has_text && self.selected_options.is_empty(),
+        }
+    }
+}
+
+/// 历史查询条件
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct HistoryQuery {
+    #[schemars(description = "Keyword matched against the request message, the answer and the selected options (optional)")]
+    #[serde(default)]
+    pub keyword: Option<String>,
+    #[schemars(description = "Only records at or after this time: RFC 3339 timestamp or YYYY-MM-DD (optional)")]
+    #[serde(default)]
+    pub since: Option<String>,
+    #[schemars(description = "Only records at or before this time: RFC 3339 timestamp or YYYY-MM-DD, a date includes the whole day (optional)")]
+    #[serde(default)]
+    pub until: Option<String>,
+    #[schemars(description = "Response type: 'option', 'text', 'timed_out' or 'empty' (optional)")]
+    #[serde(default)]
+    pub response_type: Option<ResponseKind>,
+    #[schemars(description = "Only records from this project path (optional)")]
+    #[serde(default)]
+    pub project_path: Option<String>,
+    #[schemars(description = "Maximum number of records to return, newest first (default: 20)")]
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// 解析时间边界：RFC 3339 时间戳，或日期（起始取当天零点，结束取当天末尾）
+fn parse_time_bound(value: &str, end_of_day: bool) -> Result<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| anyhow::anyhow!("Invalid date '{}', expected RFC 3339 or YYYY-MM-DD", value))?;
+    let time = if end_of_day {
+        date.and_hms_milli_opt(23, 59, 59, 999)
+    } else {
+        date.and_hms_opt(0, 0, 0)
+    };
+    time.map(|t| t.and_utc())
+        .ok_or_else(|| anyhow::anyhow!("Invalid date '{}'", value))
 }
 
 /// 历史记录存储
@@ -53,12 +123,10 @@ impl InteractHistory {
             return Ok(path.clone());
         }
         
-        // 使用应用数据目录 (跨平台)
-        let app_data = dirs::data_dir()
-            .or_else(|| dirs::home_dir())
+        // 使用应用数据目录 (跨平台，便携模式下位于可执行文件旁)
+        let history_dir = crate::config::base_data_dir()
             .ok_or_else(|| anyhow::anyhow!("Cannot find data directory"))?;
         
-        let history_dir = app_data.join("neurospec");
         let path = history_dir.join(HISTORY_FILE);
         
         // 缓存路径
@@ -162,6 +230,7 @@ pub fn save_interact_record(
     user_response: Option<&str>,
     selected_options: &[String],
     project_path: Option<&str>,
+    timed_out: bool,
 ) -> Result<()> {
     log::debug!("Saving interact record: {}", request_id);
     
@@ -175,6 +244,7 @@ pub fn save_interact_record(
         user_response: user_response.map(|s| s.to_string()),
         selected_options: selected_options.to_vec(),
         project_path: project_path.map(|s| s.to_string()),
+        timed_out,
     };
     
     history.add_record(record);
@@ -223,6 +293,52 @@ pub fn search_interact_history(query: &str) -> Result<Vec<InteractRecord>> {
     Ok(history.search(query).into_iter().cloned().collect())
 }
 
+/// 按条件查询交互历史（最新在前），`limit` 为 None 时不限制条数
+pub fn filter_interact_history(query: &HistoryQuery, limit: Option<usize>) -> Result<Vec<InteractRecord>> {
+    let since = query.since.as_deref().map(|s| parse_time_bound(s, false)).transpose()?;
+    let until = query.until.as_deref().map(|s| parse_time_bound(s, true)).transpose()?;
+    let keyword = query
+        .keyword
+        .as_deref()
+        .map(|k| k.trim().to_lowercase())
+        .filter(|k| !k.is_empty());
+    let project = query.project_path.as_deref().map(|p| p.replace('\\', "/"));
+
+    let history = InteractHistory::load()?;
+    let records = history
+        .records
+        .into_iter()
+        .filter(|r| since.map_or(true, |since| r.timestamp >= since))
+        .filter(|r| until.map_or(true, |until| r.timestamp <= until))
+        .filter(|r| query.response_type.map_or(true, |kind| r.has_response_kind(kind)))
+        .filter(|r| {
+            project.as_deref().map_or(true, |project| {
+                r.project_path.as_deref().map(|p| p.replace('\\', "/")).as_deref() == Some(project)
+            })
+        })
+        .filter(|r| {
+            keyword.as_deref().map_or(true, |keyword| {
+                r.request_message.to_lowercase().contains(keyword)
+                    || r.user_response.as_deref().is_some_and(|s| s.to_lowercase().contains(keyword))
+                    || r.selected_options.iter().any(|o| o.to_lowercase().contains(keyword))
+            })
+        })
+        .take(limit.unwrap_or(usize::MAX))
+        .collect();
+    Ok(records)
+}
+
+/// 按条件导出交互历史为 JSON 文件，返回导出的记录数
+pub fn export_interact_history(query: &HistoryQuery, path: &std::path::Path) -> Result<usize> {
+    let records = filter_interact_history(query, query.limit)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(&records)?)?;
+    log::info!("Exported {} interact records to {}", records.len(), path.display());
+    Ok(records.len())
+}
+
 /// 清空交互历史
 pub fn clear_interact_history() -> Result<()> {
     let mut history = InteractHistory::load().unwrap_or_default();
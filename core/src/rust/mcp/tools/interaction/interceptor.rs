@@ -3,7 +3,7 @@
 //! 在交互流程中自动召回和记录代码修改记忆
 
 use crate::mcp::tools::memory::{ChangeTracker, CodeChangeMemory};
-use crate::neurospec::services::embedding::{find_similar, is_embedding_available};
+use crate::neurospec::services::embedding::{cosine_similarity, embed_text, is_embedding_available};
 
 /// 记忆拦截器
 /// 
@@ -84,22 +84,11 @@ impl MemoryInterceptor {
 
         // 检查嵌入服务是否可用
         if is_embedding_available() {
-            // 构建候选摘要列表
-            let summaries: Vec<String> = all_memories.iter()
-                .map(|m| format!("{} {}", m.summary, m.user_intent))
-                .collect();
-            
-            // 使用嵌入进行语义匹配
-            if let Some(similar) = find_similar(user_message, &summaries, limit).await {
-                let matched_memories: Vec<&CodeChangeMemory> = similar.iter()
-                    .filter(|(_, score)| *score > 0.5) // 相似度阈值
-                    .map(|(idx, _)| &all_memories[*idx])
-                    .collect();
-                
-                if !matched_memories.is_empty() {
-                    let owned: Vec<CodeChangeMemory> = matched_memories.into_iter().cloned().collect();
-                    return Some(self.format_memories_as_context(&owned));
-                }
+            // 记忆向量由后台嵌入任务预先计算
+            let embeddings = tracker.change_embeddings().unwrap_or_default();
+            let matched = semantic_matches(user_message, &all_memories, &embeddings, limit).await;
+            if !matched.is_empty() {
+                return Some(self.format_memories_as_context(&matched));
             }
         }
         
@@ -287,44 +276,45 @@ pub fn auto_recall(user_message: &str) -> Option<String> {
 }
 
 /// 自动召回相关记忆（便捷函数，异步版本，使用嵌入模型）
+///
+/// 读取记忆库是同步 IO，在阻塞线程池中执行，只有嵌入计算留在异步上下文
 pub async fn auto_recall_async(user_message: &str) -> Option<String> {
+    let message = user_message.to_string();
+
     // 检查嵌入服务是否可用
     if is_embedding_available() {
-        // 先获取所有记忆（在锁内，立即释放）
-        let (all_memories, fallback_result) = {
+        // 先获取所有记忆及其向量（在锁内，立即释放）
+        let (all_memories, embeddings, fallback_result) = tokio::task::spawn_blocking(move || {
             let interceptor = get_interceptor().lock().ok()?;
             let tracker = interceptor.tracker.as_ref()?;
             let memories = tracker.get_all_changes().ok()?;
-            let fallback = interceptor.recall_relevant_memories(user_message, 3);
-            (memories, fallback)
-        }; // 锁在这里释放
+            let embeddings = tracker.change_embeddings().unwrap_or_default();
+            let fallback = interceptor.recall_relevant_memories(&message, 3);
+            Some((memories, embeddings, fallback))
+        })
+        .await
+        .ok()??; // 锁在这里释放
         
         if all_memories.is_empty() {
             return fallback_result;
         }
         
-        // 异步调用嵌入服务（锁已释放）
-        let summaries: Vec<String> = all_memories.iter()
-            .map(|m| format!("{} {}", m.summary, m.user_intent))
-            .collect();
-        
-        if let Some(similar) = find_similar(user_message, &summaries, 3).await {
-            let matched: Vec<CodeChangeMemory> = similar.iter()
-                .filter(|(_, score)| *score > 0.5)
-                .map(|(idx, _)| all_memories[*idx].clone())
-                .collect();
-            
-            if !matched.is_empty() {
-                // 直接格式化，不需要锁
-                return Some(format_memories_standalone(&matched));
-            }
+        // 异步计算查询向量（锁已释放）
+        let matched = semantic_matches(user_message, &all_memories, &embeddings, 3).await;
+        if !matched.is_empty() {
+            // 直接格式化，不需要锁
+            return Some(format_memories_standalone(&matched));
         }
         
         // 回退到关键词匹配结果
         fallback_result
     } else {
-        let interceptor = get_interceptor().lock().ok()?;
-        interceptor.recall_relevant_memories(user_message, 3)
+        tokio::task::spawn_blocking(move || {
+            let interceptor = get_interceptor().lock().ok()?;
+            interceptor.recall_relevant_memories(&message, 3)
+        })
+        .await
+        .ok()?
     }
 }
 
@@ -352,3 +342,32 @@ pub fn auto_record(ai_response: &str, user_intent: &str) -> Option<String> {
     let interceptor = get_interceptor().lock().ok()?;
     interceptor.detect_and_record_change(ai_response, user_intent)
 }
+
+/// 用预先计算的记忆向量做语义匹配，查询时只嵌入用户消息
+///
+/// 还没有向量的记忆（后台尚未补算）不参与语义匹配，只能通过关键词召回
+async fn semantic_matches(
+    user_message: &str,
+    memories: &[CodeChangeMemory],
+    embeddings: &[(String, Vec<f32>)],
+    limit: usize,
+) -> Vec<CodeChangeMemory> {
+    if embeddings.is_empty() {
+        return Vec::new();
+    }
+    let Some(query) = embed_text(user_message).await else {
+        return Vec::new();
+    };
+
+    let by_id: std::collections::HashMap<&str, &CodeChangeMemory> =
+        memories.iter().map(|m| (m.id.as_str(), m)).collect();
+    let mut scored: Vec<(f32, &CodeChangeMemory)> = embeddings
+        .iter()
+        .filter_map(|(id, vector)| Some((cosine_similarity(&query, vector), *by_id.get(id.as_str())?)))
+        .filter(|(score, _)| *score > 0.5) // 相似度阈值
+        .collect();
+    scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+    scored.truncate(limit);
+
+    scored.into_iter().map(|(_, memory)| memory.clone()).collect()
+}
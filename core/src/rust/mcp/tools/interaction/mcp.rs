@@ -1,19 +1,47 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 
 use anyhow::Result;
 use rmcp::{ErrorData as McpError, model::*};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
+use crate::config::OutputLanguage;
 use crate::mcp::{InteractRequest, PopupRequest};
-use crate::mcp::handlers::{create_tauri_popup, parse_mcp_response};
-use crate::mcp::utils::popup_error;
+use crate::tr;
+use crate::mcp::types::{PathPickerRequest, PickedPaths, PopupPreview};
+use crate::mcp::handlers::{
+    answer_headless, create_tauri_popup, extract_selected_options, is_daemon_unavailable, parse_mcp_response,
+    starts_with_timeout_notice,
+};
+use crate::daemon::popup_handler::resolve_popup_timeout_secs;
+use crate::mcp::utils::{
+    detect_project_root, invalid_params_error, popup_error, popup_timeout_error, resolve_output_language, ToolError,
+    ToolErrorCode,
+};
 
-use super::history::save_interact_record;
+use super::history::{export_interact_history, filter_interact_history, save_interact_record, HistoryQuery};
 use super::interceptor::auto_recall_async;
 
+/// interact_history 工具默认返回条数
+const DEFAULT_HISTORY_LIMIT: usize = 20;
+
+/// interact_history 工具请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct InteractHistoryRequest {
+    #[serde(flatten)]
+    pub query: HistoryQuery,
+    #[schemars(description = "Write all matching records (up to limit if given) to this new JSON file instead of returning them; must be inside the project or app data directory, relative paths are resolved against the project (optional)")]
+    #[serde(default)]
+    pub export_path: Option<String>,
+}
+
 /// 标记是否已经提示过创建 AGENTS.md（避免重复提示）
 static AGENTS_PROMPT_SHOWN: AtomicBool = AtomicBool::new(false);
 
+/// AGENTS.md 草稿确认选项：写入
+const AGENTS_DRAFT_WRITE: &str = "✅ 写入 AGENTS.md";
+
 /// Interactive dialogue tool
 ///
 /// 智能交互入口，支持弹窗交互（确认/选择/输入）
@@ -27,14 +55,94 @@ impl InteractionTool {
     ) -> Result<CallToolResult, McpError> {
         // 首次调用时检测 AGENTS.md
         Self::check_agents_md_on_first_call().await;
+
+        if let Some(ref picker) = request.picker {
+            return Self::handle_path_picker(&request, picker).await;
+        }
         
         Self::handle_normal_interaction(&request).await
     }
 
+    /// 打开原生文件/目录选择器，返回项目内的相对路径
+    async fn handle_path_picker(
+        request: &InteractRequest,
+        picker: &PathPickerRequest,
+    ) -> Result<CallToolResult, McpError> {
+        let mut picker = picker.clone();
+        if picker.project_root.is_none() {
+            picker.project_root = detect_project_root().map(|p| p.to_string_lossy().to_string());
+        }
+        let project_root = picker
+            .project_root
+            .clone()
+            .ok_or_else(|| invalid_params_error("Cannot detect project root for the path picker"))?;
+
+        let request_id = uuid::Uuid::new_v4().to_string();
+        let popup_request = PopupRequest {
+            id: request_id.clone(),
+            message: request.message.clone(),
+            predefined_options: None,
+            is_markdown: false,
+            multi_select: false,
+            input: None,
+            preview: None,
+            picker: Some(picker),
+            timeout_secs: request.timeout_secs,
+            default_option: None,
+            form: None,
+            language: None,
+        };
+
+        let response = create_tauri_popup(&popup_request)
+            .await
+            .map_err(|e| McpError::from(popup_error(e.to_string())))?;
+
+        let picked = serde_json::from_str::<PickedPaths>(&response).ok();
+        if let Err(e) = save_interact_record(
+            &request_id,
+            &request.message,
+            &[],
+            None,
+            picked.as_ref().map(|p| p.paths.as_slice()).unwrap_or_default(),
+            Some(&project_root),
+            false,
+        ) {
+            log::warn!("Failed to save interact record: {}", e);
+        }
+
+        Ok(crate::mcp::create_success_result(vec![Content::text(response)]))
+    }
+
     /// 处理普通交互流程
     async fn handle_normal_interaction(
         request: &InteractRequest,
     ) -> Result<CallToolResult, McpError> {
+        if let Some(ref default_option) = request.default_option {
+            if !request.predefined_options.is_empty() && !request.predefined_options.contains(default_option) {
+                return Err(invalid_params_error(format!(
+                    "default_option '{}' is not one of predefined_options",
+                    default_option
+                ))
+                .into());
+            }
+        }
+
+        if let Some(ref input) = request.input {
+            input.check().map_err(invalid_params_error)?;
+        }
+
+        if let Some(ref form) = request.form {
+            form.check().map_err(invalid_params_error)?;
+        }
+
+        // 图片路径在本进程读取（daemon 不在项目目录下运行）
+        let preview = request
+            .preview
+            .clone()
+            .map(PopupPreview::resolve)
+            .transpose()
+            .map_err(invalid_params_error)?;
+
         let request_id = uuid::Uuid::new_v4().to_string();
         
         // 🔮 前置拦截：自动召回相关的代码修改记忆（使用嵌入模型语义匹配）
@@ -54,16 +162,23 @@ impl InteractionTool {
                 Some(request.predefined_options.clone())
             },
             is_markdown: request.is_markdown,
+            multi_select: request.multi_select,
+            input: request.input.clone(),
+            preview,
+            picker: None,
+            timeout_secs: request.timeout_secs,
+            default_option: request.default_option.clone(),
+            form: request.form.clone(),
+            language: request.language,
         };
 
-        match create_tauri_popup(&popup_request).await {
+        let project_path = Self::detect_project_root()
+            .map(|p| p.to_string_lossy().to_string());
+
+        match Self::show_popup_or_headless(&popup_request).await {
             Ok(response) => {
-                // 保存历史记录
-                let project_path = Self::detect_project_root()
-                    .map(|p| p.to_string_lossy().to_string());
-                
                 // 尝试解析 JSON 格式的响应（兼容两种格式）
-                let (user_input, selected) = if let Ok(resp_json) = serde_json::from_str::<serde_json::Value>(&response) {
+                let (user_input, selected, timed_out) = if let Ok(resp_json) = serde_json::from_str::<serde_json::Value>(&response) {
                     // JSON 格式：提取 user_input 和 selected_options
                     let input = resp_json.get("user_input")
                         .and_then(|v| v.as_str())
@@ -74,11 +189,20 @@ impl InteractionTool {
                             .filter_map(|v| v.as_str().map(|s| s.to_string()))
                             .collect::<Vec<_>>())
                         .unwrap_or_default();
-                    (input, opts)
+                    let timed_out = resp_json.pointer("/metadata/source").and_then(|v| v.as_str())
+                        == Some(crate::constants::mcp::POPUP_TIMEOUT_DEFAULT_SOURCE);
+                    (input, opts, timed_out)
+                } else if starts_with_timeout_notice(&response) {
+                    // 超时后 daemon 使用了默认选项
+                    (None, extract_selected_options(&response), true)
                 } else {
-                    // 纯文本格式：直接使用响应作为 user_input
-                    (Some(response.clone()), vec![])
+                    // 纯文本格式：直接使用响应作为 user_input，并取回其中的选项
+                    (Some(response.clone()), extract_selected_options(&response), false)
                 };
+
+                if timed_out {
+                    log::info!("Interact request {} timed out, using default option", request_id);
+                }
                 
                 // 保存历史记录（无论哪种格式都会执行）
                 if let Err(e) = save_interact_record(
@@ -88,23 +212,130 @@ impl InteractionTool {
                     user_input.as_deref(),
                     &selected,
                     project_path.as_deref(),
+                    timed_out,
                 ) {
                     log::warn!("Failed to save interact record: {}", e);
                 }
                 
-                let mut content = parse_mcp_response(&response)?;
+                let lang = resolve_output_language(request.language);
+                let mut content = parse_mcp_response(&response, lang)?;
                 
                 // 🔔 在返回内容末尾添加 CHANGE_REPORT 提醒
-                content = Self::append_change_report_reminder(content);
+                content = Self::append_change_report_reminder(content, lang);
                 
                 Ok(crate::mcp::create_success_result(content))
             }
-            Err(e) => {
-                Err(popup_error(e.to_string()).into())
+            Err(e) if e.to_string().contains(crate::constants::mcp::POPUP_TIMEOUT_ERROR) => {
+                // 超时且没有默认选项：记录到历史后返回结构化的超时错误
+                let timeout_secs = resolve_popup_timeout_secs(request.timeout_secs);
+                log::info!("Interact request {} timed out after {} seconds", request_id, timeout_secs);
+                if let Err(e) = save_interact_record(
+                    &request_id,
+                    &request.message,
+                    &request.predefined_options,
+                    None,
+                    &[],
+                    project_path.as_deref(),
+                    true,
+                ) {
+                    log::warn!("Failed to save interact record: {}", e);
+                }
+                Err(popup_timeout_error(timeout_secs).into())
             }
+            // daemon 返回的错误码（如参数校验失败）原样保留
+            Err(e) => match e.downcast::<ToolError>() {
+                Ok(error) if error.code != ToolErrorCode::InternalError => Err(error.into()),
+                Ok(error) => Err(popup_error(error.message).into()),
+                Err(e) => Err(popup_error(e.to_string()).into()),
+            },
         }
     }
     
+    /// 弹窗；daemon/GUI 不可用时按 headless 策略回退
+    pub(crate) async fn show_popup_or_headless(popup_request: &PopupRequest) -> Result<String> {
+        match create_tauri_popup(popup_request).await {
+            Err(e) if is_daemon_unavailable(&e) => {
+                log::info!("Daemon unavailable, trying headless fallback: {}", e);
+                answer_headless(popup_request).await?.ok_or(e)
+            }
+            result => result,
+        }
+    }
+
+    /// 查询或导出交互历史
+    pub async fn query_history(request: InteractHistoryRequest) -> Result<CallToolResult, McpError> {
+        let result = tokio::task::spawn_blocking(move || -> anyhow::Result<serde_json::Value> {
+            let query = request.query;
+            match request.export_path {
+                Some(path) => {
+                    let path = Self::resolve_export_path(&path, query.project_path.as_deref())?;
+                    let exported = export_interact_history(&query, &path)?;
+                    Ok(serde_json::json!({ "exported": exported, "path": path.to_string_lossy() }))
+                }
+                None => {
+                    let records = filter_interact_history(&query, Some(query.limit.unwrap_or(DEFAULT_HISTORY_LIMIT)))?;
+                    Ok(serde_json::json!({ "count": records.len(), "records": records }))
+                }
+            }
+        })
+        .await
+        .map_err(|e| McpError::internal_error(format!("History task failed: {}", e), None))?
+        .map_err(|e| McpError::from(invalid_params_error(e.to_string())))?;
+
+        let json = serde_json::to_string_pretty(&result)
+            .map_err(|e| McpError::internal_error(format!("JSON 错误: {}", e), None))?;
+        Ok(crate::mcp::create_success_result(vec![Content::text(json)]))
+    }
+
+    /// 解析 interact_history 的导出路径
+    ///
+    /// 只允许写入项目目录或应用数据目录内的新文件：相对路径相对项目目录（没有项目时为数据目录），
+    /// 已存在的文件不覆盖
+    fn resolve_export_path(path: &str, project_path: Option<&str>) -> anyhow::Result<PathBuf> {
+        let project_root = project_path
+            .map(PathBuf::from)
+            .or_else(detect_project_root)
+            .and_then(|p| p.canonicalize().ok());
+        let data_dir = crate::config::base_data_dir().and_then(|d| d.canonicalize().ok());
+
+        let path = Path::new(path);
+        if path.components().any(|c| matches!(c, std::path::Component::ParentDir)) {
+            return Err(anyhow::anyhow!("export_path must not contain '..'"));
+        }
+        let path = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            project_root
+                .as_ref()
+                .or(data_dir.as_ref())
+                .ok_or_else(|| anyhow::anyhow!("Cannot resolve relative export_path without a project"))?
+                .join(path)
+        };
+
+        // 按已存在的最深一级目录解析符号链接，防止借助链接写到允许的目录之外
+        let mut existing = path.as_path();
+        while !existing.exists() {
+            existing = existing
+                .parent()
+                .ok_or_else(|| anyhow::anyhow!("Invalid export_path: {}", path.display()))?;
+        }
+        let resolved = existing.canonicalize()?.join(path.strip_prefix(existing)?);
+
+        let allowed = [project_root, data_dir]
+            .into_iter()
+            .flatten()
+            .any(|root| resolved.starts_with(root));
+        if !allowed {
+            return Err(anyhow::anyhow!(
+                "export_path must be inside the project directory or the application data directory"
+            ));
+        }
+        if resolved.exists() {
+            return Err(anyhow::anyhow!("Refusing to overwrite existing file: {}", resolved.display()));
+        }
+        Ok(resolved)
+    }
+
     // Legacy method name for backward compatibility
     pub async fn zhi(request: InteractRequest) -> Result<CallToolResult, McpError> {
         Self::interact(request).await
@@ -135,31 +366,73 @@ impl InteractionTool {
 
         // 不存在 AGENTS.md，发送提示
         log::info!("No AGENTS.md found in project: {}", project_root.display());
-        
+
+        // 根据 Project Insight 生成草稿，用户确认后才写入
+        #[cfg(feature = "experimental-neurospec")]
+        let draft = Some(crate::neurospec::services::agents_parser::generate_agents_draft(&project_root));
+        #[cfg(not(feature = "experimental-neurospec"))]
+        let draft: Option<String> = None;
+
+        let mut message = format!(
+            "## 📋 未检测到 AGENTS.md\n\n\
+            在项目 `{}` 中未找到 AGENTS.md 配置文件。\n\n\
+            AGENTS.md 可以定义 AI 的行为规范，包括：\n\
+            - 🎭 角色定义\n\
+            - 🔧 可用工具\n\
+            - ⚠️ 最高原则\n\
+            - 📝 自定义规则\n\n",
+            project_root.display()
+        );
+        let (options, preview) = match draft {
+            Some(ref draft) => {
+                message.push_str("已根据项目结构生成草稿（见下方预览），确认后写入项目根目录，也可以稍后在 **AGENTS** 标签页中编辑。");
+                let diff = similar::TextDiff::from_lines("", draft.as_str())
+                    .unified_diff()
+                    .header("/dev/null", "b/AGENTS.md")
+                    .to_string();
+                let preview = PopupPreview::Diff { diff, language: Some("markdown".to_string()) }
+                    .resolve()
+                    .inspect_err(|e| log::warn!("AGENTS.md draft preview unavailable: {}", e))
+                    .ok();
+                (vec![AGENTS_DRAFT_WRITE.to_string(), "🚀 稍后创建".to_string()], preview)
+            }
+            None => {
+                message.push_str("建议在应用的 **AGENTS** 标签页中创建配置。");
+                (vec!["✅ 知道了".to_string(), "🚀 稍后创建".to_string()], None)
+            }
+        };
+
         // 创建提示弹窗
         let popup_request = PopupRequest {
             id: uuid::Uuid::new_v4().to_string(),
-            message: format!(
-                "## 📋 未检测到 AGENTS.md\n\n\
-                在项目 `{}` 中未找到 AGENTS.md 配置文件。\n\n\
-                AGENTS.md 可以定义 AI 的行为规范，包括：\n\
-                - 🎭 角色定义\n\
-                - 🔧 可用工具\n\
-                - ⚠️ 最高原则\n\
-                - 📝 自定义规则\n\n\
-                建议在应用的 **AGENTS** 标签页中创建配置。",
-                project_root.display()
-            ),
-            predefined_options: Some(vec![
-                "✅ 知道了".to_string(),
-                "🚀 稍后创建".to_string(),
-            ]),
+            message,
+            predefined_options: Some(options),
             is_markdown: true,
+            multi_select: false,
+            input: None,
+            preview,
+            picker: None,
+            timeout_secs: None,
+            default_option: None,
+            form: None,
+            language: None,
         };
 
-        // 发送提示（异步，不阻塞主流程）
-        if let Err(e) = create_tauri_popup(&popup_request).await {
-            log::warn!("Failed to show AGENTS.md prompt: {}", e);
+        let response = match create_tauri_popup(&popup_request).await {
+            Ok(response) => response,
+            Err(e) => {
+                log::warn!("Failed to show AGENTS.md prompt: {}", e);
+                return;
+            }
+        };
+
+        if let Some(draft) = draft {
+            if extract_selected_options(&response).iter().any(|o| o == AGENTS_DRAFT_WRITE) {
+                match std::fs::write(&agents_path, draft) {
+                    Ok(()) => log::info!("AGENTS.md draft written to: {}", agents_path.display()),
+                    Err(e) => log::warn!("Failed to write AGENTS.md draft: {}", e),
+                }
+            }
         }
     }
 
@@ -178,11 +451,18 @@ impl InteractionTool {
     }
 
     /// 在返回内容末尾添加 CHANGE_REPORT 提醒（精简版）
-    fn append_change_report_reminder(content: Vec<rmcp::model::Content>) -> Vec<rmcp::model::Content> {
+    fn append_change_report_reminder(
+        content: Vec<rmcp::model::Content>,
+        lang: OutputLanguage,
+    ) -> Vec<rmcp::model::Content> {
         use rmcp::model::Content;
         
         // 精简版提醒，减少 token 消耗
-        let reminder = "\n\n---\n💡 代码修改后请添加 `[CHANGE_REPORT]` 标记";
+        let reminder = tr!(
+            lang,
+            "\n\n---\n💡 代码修改后请添加 `[CHANGE_REPORT]` 标记",
+            "\n\n---\n💡 Add a `[CHANGE_REPORT]` marker after modifying code"
+        );
 
         let mut result = content;
         result.push(Content::text(reminder));
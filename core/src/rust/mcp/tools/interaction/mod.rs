@@ -10,5 +10,8 @@ pub mod history;
 pub mod interceptor;
 
 pub use mcp::InteractionTool;
-pub use history::{InteractRecord, InteractHistory, get_interact_history, search_interact_history, clear_interact_history, init_interact_history};
+pub use history::{
+    InteractRecord, InteractHistory, HistoryQuery, ResponseKind, get_interact_history, search_interact_history,
+    filter_interact_history, export_interact_history, clear_interact_history, init_interact_history,
+};
 pub use interceptor::{MemoryInterceptor, auto_recall, auto_recall_async, auto_record, get_interceptor};
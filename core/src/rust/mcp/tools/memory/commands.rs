@@ -56,7 +56,7 @@ impl From<MemoryListResult> for MemoryListResponse {
     }
 }
 
-fn parse_category(category: &str) -> Option<MemoryCategory> {
+pub(crate) fn parse_category(category: &str) -> Option<MemoryCategory> {
     match category {
         "rule" => Some(MemoryCategory::Rule),
         "preference" => Some(MemoryCategory::Preference),
@@ -181,7 +181,7 @@ pub async fn detect_project_path() -> Result<String, String> {
 
 /// 从配置文件加载已保存的项目路径
 fn load_saved_project_path() -> Option<String> {
-    let config_path = dirs::data_dir()?.join("neurospec").join("project_config.json");
+    let config_path = crate::config::base_data_dir()?.join("project_config.json");
     
     if !config_path.exists() {
         return None;
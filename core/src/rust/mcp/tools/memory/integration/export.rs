@@ -1,9 +1,10 @@
 //! 记忆导入/导出
 //!
-//! 支持 JSON 和 Markdown 格式
+//! 支持 JSON 和 Markdown 格式，以及与 AGENTS.md / Cursor 规则文件双向同步规则类记忆
 
 use anyhow::Result;
 use chrono::Utc;
+use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 
 use crate::mcp::tools::memory::types::{MemoryEntry, MemoryCategory};
@@ -15,6 +16,46 @@ pub enum ExportFormat {
     Markdown,
 }
 
+/// 规则文件中由 NeuroSpec 维护的区块起止标记，标记之外的内容保持不变
+const RULES_BEGIN_MARKER: &str = "<!-- neurospec:rules:begin -->";
+const RULES_END_MARKER: &str = "<!-- neurospec:rules:end -->";
+
+/// 规则文件类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RuleFileTarget {
+    /// 项目根目录的 AGENTS.md
+    AgentsMd,
+    /// Cursor 项目规则 `.cursor/rules/neurospec.mdc`
+    CursorRules,
+}
+
+impl RuleFileTarget {
+    /// 解析规则文件类型名称
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "agents" | "agents.md" | "agents_md" => Some(Self::AgentsMd),
+            "cursor" | "cursor_rules" | ".cursor/rules" => Some(Self::CursorRules),
+            _ => None,
+        }
+    }
+
+    /// 相对项目根目录的文件路径
+    pub fn relative_path(&self) -> &'static str {
+        match self {
+            Self::AgentsMd => "AGENTS.md",
+            Self::CursorRules => ".cursor/rules/neurospec.mdc",
+        }
+    }
+
+    /// 新建文件时写在规则区块之前的内容
+    fn new_file_header(&self) -> &'static str {
+        match self {
+            Self::AgentsMd => "# AGENTS.md\n\n",
+            Self::CursorRules => "---\ndescription: Project rules maintained by NeuroSpec\nalwaysApply: true\n---\n\n",
+        }
+    }
+}
+
 /// 导出数据结构
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ExportData {
@@ -151,4 +192,183 @@ impl MemoryExporter {
         let content = std::fs::read_to_string(file_path)?;
         Self::import_json(&content)
     }
+
+    /// 生成规则区块（只包含 Rule 分类的记忆，带起止标记）
+    pub fn render_rules_section(memories: &[MemoryEntry]) -> String {
+        let mut section = String::new();
+        section.push_str(RULES_BEGIN_MARKER);
+        section.push_str("\n## Project Rules\n\n");
+        for mem in memories.iter().filter(|m| m.category == MemoryCategory::Rule) {
+            // 多行规则的后续行缩进，保持为同一个列表项
+            section.push_str(&format!("- {}\n", mem.content.trim().replace('\n', "\n  ")));
+        }
+        section.push_str(RULES_END_MARKER);
+        section.push('\n');
+        section
+    }
+
+    /// 把规则区块写入已有文件内容：替换已有区块，没有区块时追加到末尾
+    pub fn merge_rules_section(existing: &str, section: &str) -> String {
+        if let Some((before, after)) = Self::split_around_section(existing) {
+            return format!("{}{}{}", before, section, after);
+        }
+
+        let mut merged = existing.trim_end().to_string();
+        if !merged.is_empty() {
+            merged.push_str("\n\n");
+        }
+        merged.push_str(section);
+        merged
+    }
+
+    /// 从规则文件内容解析规则
+    ///
+    /// 文件含 NeuroSpec 区块时只解析区块内的列表项；否则只解析标题含 "Rules"/"规则" 的章节中的
+    /// 顶层列表项（`-` / `*` / `+` / `1.`），避免把构建步骤、目录等普通列表当成规则。
+    /// 代码块中的内容不算规则
+    pub fn parse_rules(content: &str) -> Vec<String> {
+        match Self::section_body(content) {
+            Some(body) => Self::parse_list_items(body),
+            None => Self::parse_list_items(&Self::rules_headed_sections(content)),
+        }
+    }
+
+    /// 内容中是否已有 NeuroSpec 规则区块
+    pub fn has_rules_section(content: &str) -> bool {
+        Self::section_body(content).is_some()
+    }
+
+    /// 解析顶层列表项（含缩进的续行）
+    fn parse_list_items(body: &str) -> Vec<String> {
+        let mut rules: Vec<String> = Vec::new();
+        let mut in_item = false;
+        let mut in_code_block = false;
+        for line in body.lines() {
+            if line.trim_start().starts_with("```") {
+                in_code_block = !in_code_block;
+                in_item = false;
+                continue;
+            }
+            if in_code_block {
+                continue;
+            }
+
+            if let Some(item) = Self::list_item_text(line) {
+                rules.push(item.to_string());
+                in_item = true;
+            } else if in_item && line.starts_with("  ") && !line.trim().is_empty() {
+                // 列表项的续行
+                if let Some(last) = rules.last_mut() {
+                    last.push('\n');
+                    last.push_str(line.trim());
+                }
+            } else {
+                in_item = false;
+            }
+        }
+
+        rules.retain(|rule| !rule.trim().is_empty());
+        rules
+    }
+
+    /// 标题含 "Rules"/"规则" 的章节内容（到下一个同级或更高级标题为止）
+    fn rules_headed_sections(content: &str) -> String {
+        let mut body = String::new();
+        let mut rules_level: Option<usize> = None;
+        let mut in_code_block = false;
+        for line in content.lines() {
+            if line.trim_start().starts_with("```") {
+                in_code_block = !in_code_block;
+            }
+            let level = line.chars().take_while(|&c| c == '#').count();
+            if !in_code_block && level > 0 && line[level..].starts_with(' ') {
+                let title = line[level..].trim().to_lowercase();
+                if title.contains("rule") || title.contains("规则") {
+                    rules_level = Some(level);
+                    continue;
+                }
+                if rules_level.is_some_and(|rules| level <= rules) {
+                    rules_level = None;
+                }
+            }
+            if rules_level.is_some() {
+                body.push_str(line);
+                body.push('\n');
+            }
+        }
+        body
+    }
+
+    /// 把规则类记忆写入项目的规则文件，返回文件路径
+    pub fn export_rules_to_file(
+        memories: &[MemoryEntry],
+        project_root: &Path,
+        target: RuleFileTarget,
+    ) -> Result<PathBuf> {
+        let path = project_root.join(target.relative_path());
+        let existing = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => target.new_file_header().to_string(),
+            Err(e) => return Err(e.into()),
+        };
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let section = Self::render_rules_section(memories);
+        std::fs::write(&path, Self::merge_rules_section(&existing, &section))?;
+        Ok(path)
+    }
+
+    /// 从项目的规则文件读取规则（文件不存在时返回空列表）
+    pub fn import_rules_from_file(project_root: &Path, target: RuleFileTarget) -> Result<Vec<String>> {
+        let path = project_root.join(target.relative_path());
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Ok(Self::parse_rules(&content)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// 项目的规则文件中是否已有 NeuroSpec 规则区块（文件不存在时返回 false）
+    pub fn rule_file_has_section(project_root: &Path, target: RuleFileTarget) -> Result<bool> {
+        let path = project_root.join(target.relative_path());
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Ok(Self::has_rules_section(&content)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// 按起止标记拆分文件内容，返回区块之前和之后的部分
+    fn split_around_section(content: &str) -> Option<(&str, &str)> {
+        let begin = content.find(RULES_BEGIN_MARKER)?;
+        let end = begin + content[begin..].find(RULES_END_MARKER)? + RULES_END_MARKER.len();
+        let after = &content[end..];
+        Some((&content[..begin], after.strip_prefix('\n').unwrap_or(after)))
+    }
+
+    /// 区块标记之间的内容
+    fn section_body(content: &str) -> Option<&str> {
+        let begin = content.find(RULES_BEGIN_MARKER)? + RULES_BEGIN_MARKER.len();
+        let end = begin + content[begin..].find(RULES_END_MARKER)?;
+        Some(&content[begin..end])
+    }
+
+    /// 顶层列表项的文本（不是列表项时返回 None）
+    fn list_item_text(line: &str) -> Option<&str> {
+        for bullet in ["- ", "* ", "+ "] {
+            if let Some(text) = line.strip_prefix(bullet) {
+                return Some(text.trim());
+            }
+        }
+
+        let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+        if digits > 0 {
+            if let Some(text) = line[digits..].strip_prefix(". ") {
+                return Some(text.trim());
+            }
+        }
+        None
+    }
 }
@@ -1,10 +1,11 @@
 //! Git 集成
 //!
-//! 从 Git commit message 提取项目规范
+//! 从 Git commit message 提取项目规范，并为代码修改记忆查找对应的提交
 
 use std::path::Path;
 use std::process::Command;
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 
 use serde::{Deserialize, Serialize};
 
@@ -38,6 +39,21 @@ pub struct GitSuggestion {
     pub reason: String,
 }
 
+/// `git log` 输出格式：每个提交以 0x1e 开头，哈希、提交时间和标题之间用 0x1f 分隔，
+/// 配合 `--name-only` 之后是改动的文件列表
+const LOG_FORMAT: &str = "--format=%x1e%H%x1f%ct%x1f%s";
+
+/// 一次提交的概要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitCommit {
+    pub hash: String,
+    /// 提交说明的第一行
+    pub summary: String,
+    pub committed_at: DateTime<Utc>,
+    /// 提交改动的文件（相对仓库根目录）
+    pub files: Vec<String>,
+}
+
 impl GitIntegration {
     pub fn new(project_path: &str) -> Self {
         Self {
@@ -45,6 +61,119 @@ impl GitIntegration {
         }
     }
 
+    /// 执行 git 命令并返回标准输出
+    fn git(&self, args: &[&str]) -> Result<String> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(&self.project_path)
+            .output()?;
+
+        if !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "git {} failed: {}",
+                args.first().copied().unwrap_or_default(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// 把分支名、短哈希等解析为完整的提交哈希（无法解析时返回 None）
+    pub fn resolve_commit(&self, rev: &str) -> Result<Option<String>> {
+        let spec = format!("{}^{{commit}}", rev.trim());
+        match self.git(&["rev-parse", "--verify", "--quiet", &spec]) {
+            Ok(out) => Ok(Some(out.trim().to_string()).filter(|hash| !hash.is_empty())),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// 获取指定时间之后的提交（按时间升序），包含每个提交改动的文件
+    pub fn commits_since(&self, since: DateTime<Utc>) -> Result<Vec<GitCommit>> {
+        let since = format!("--since={}", since.format("%Y-%m-%d %H:%M:%S +0000"));
+        let out = self.git(&["log", "--reverse", "--name-only", LOG_FORMAT, &since])?;
+        Ok(Self::parse_log(&out))
+    }
+
+    /// 获取改动过某个文件的最近提交（按时间倒序，跟随重命名）
+    pub fn file_commits(&self, file_path: &str, limit: usize) -> Result<Vec<GitCommit>> {
+        let limit = limit.to_string();
+        let out = self.git(&["log", "--follow", "--name-only", LOG_FORMAT, "-n", &limit, "--", file_path])?;
+        Ok(Self::parse_log(&out))
+    }
+
+    /// 解析 [`LOG_FORMAT`] 格式的 `git log --name-only` 输出
+    fn parse_log(out: &str) -> Vec<GitCommit> {
+        out.split('\x1e')
+            .filter_map(|record| {
+                let mut lines = record.lines();
+                let mut fields = lines.next()?.split('\x1f');
+                let hash = fields.next()?.to_string();
+                let timestamp: i64 = fields.next()?.parse().ok()?;
+                let summary = fields.next().unwrap_or_default().to_string();
+                Some(GitCommit {
+                    hash,
+                    summary,
+                    committed_at: DateTime::from_timestamp(timestamp, 0)?,
+                    files: lines.filter(|l| !l.trim().is_empty()).map(str::to_string).collect(),
+                })
+            })
+            .collect()
+    }
+
+    /// 获取单个提交的概要（提交不存在时返回 None）
+    pub fn commit_info(&self, rev: &str) -> Result<Option<GitCommit>> {
+        let Some(hash) = self.resolve_commit(rev)? else {
+            return Ok(None);
+        };
+        let out = self.git(&["show", "--name-only", "--format=%ct%x1f%s", &hash])?;
+
+        let mut lines = out.lines();
+        let Some(header) = lines.next() else {
+            return Ok(None);
+        };
+        let mut fields = header.split('\x1f');
+        let timestamp: i64 = fields.next().and_then(|ts| ts.parse().ok()).unwrap_or_default();
+
+        Ok(Some(GitCommit {
+            summary: fields.next().unwrap_or_default().to_string(),
+            committed_at: DateTime::from_timestamp(timestamp, 0).unwrap_or_else(Utc::now),
+            files: lines.filter(|l| !l.trim().is_empty()).map(str::to_string).collect(),
+            hash,
+        }))
+    }
+
+    /// 提交的完整说明
+    pub fn commit_message(&self, hash: &str) -> Result<String> {
+        Ok(self.git(&["log", "-1", "--format=%B", hash])?.trim().to_string())
+    }
+
+    /// 提交的改动（不含上下文行）
+    pub fn commit_diff(&self, hash: &str) -> Result<String> {
+        self.git(&["show", "--format=", "--unified=0", "--no-color", "--no-ext-diff", hash])
+    }
+
+    /// 钩子目录（遵循 core.hooksPath 配置）
+    pub fn hooks_dir(&self) -> Result<std::path::PathBuf> {
+        let dir = std::path::PathBuf::from(self.git(&["rev-parse", "--git-path", "hooks"])?.trim());
+        Ok(if dir.is_absolute() { dir } else { Path::new(&self.project_path).join(dir) })
+    }
+
+    /// 查找最后修改某一行的提交（行尚未提交时返回 None）
+    pub fn blame_line(&self, file_path: &str, line: u32) -> Result<Option<String>> {
+        let range = format!("{},{}", line, line);
+        let out = self.git(&["blame", "--porcelain", "-L", &range, "--", file_path])?;
+
+        let hash = out
+            .lines()
+            .next()
+            .and_then(|header| header.split_whitespace().next())
+            .filter(|hash| !hash.chars().all(|c| c == '0'))
+            .map(str::to_string);
+
+        Ok(hash)
+    }
+
     /// 获取最近的 commit messages
     pub fn get_recent_commits(&self, limit: usize) -> Result<Vec<String>> {
         let output = Command::new("git")
@@ -0,0 +1,283 @@
+//! Git 提交钩子
+//!
+//! `hook install` 在仓库中安装 post-commit 钩子，每次提交后在后台调用 `hook record`，
+//! 由 daemon 根据提交说明、改动和涉及的符号自动记录一条代码修改记忆，无需 AI 手动记录
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use super::git::GitIntegration;
+use crate::mcp::tools::memory::tracker::{infer_change_type, ChangeTracker};
+use crate::mcp::tools::unified_store::{with_global_store, SymbolFilter, SymbolKind};
+
+/// 钩子文件中由 NeuroSpec 维护的片段起止标记，片段之外的已有钩子内容保持不变
+const HOOK_BEGIN_MARKER: &str = "# >>> neurospec post-commit >>>";
+const HOOK_END_MARKER: &str = "# <<< neurospec post-commit <<<";
+
+/// 记录到记忆中的改动片段上限（字符）
+const MAX_DIFF_SNIPPET_CHARS: usize = 4000;
+
+/// 每次提交最多记录的符号数量
+const MAX_COMMIT_SYMBOLS: usize = 20;
+
+/// 提交记录结果
+#[derive(Debug, Serialize)]
+pub struct CommitCapture {
+    pub commit: String,
+    /// 新记录的修改记忆 ID（提交已有关联的记忆时为 None）
+    pub memory_id: Option<String>,
+    pub files: Vec<String>,
+    pub symbols: Vec<String>,
+}
+
+/// 在项目仓库中安装 post-commit 钩子，返回钩子文件路径
+///
+/// 已有的钩子保留，NeuroSpec 片段追加在末尾（末尾为 `exit`/`exec` 时插在其前面）；
+/// 重复安装只替换该片段。已有钩子不是 sh/bash 脚本时拒绝安装
+pub fn install_post_commit_hook(project_root: &Path) -> Result<PathBuf> {
+    let hooks_dir = GitIntegration::new(&project_root.to_string_lossy()).hooks_dir()?;
+    std::fs::create_dir_all(&hooks_dir)?;
+    let path = hooks_dir.join("post-commit");
+
+    let existing = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => "#!/bin/sh\n".to_string(),
+        Err(e) => return Err(e.into()),
+    };
+    if !is_shell_hook(&existing) {
+        return Err(anyhow::anyhow!(
+            "Existing post-commit hook at {} is not a sh/bash script; add `hook record` to it manually",
+            path.display()
+        ));
+    }
+
+    let content = insert_hook_block(&remove_hook_block(&existing), &hook_block()?);
+    std::fs::write(&path, content)?;
+    make_executable(&path)?;
+
+    Ok(path)
+}
+
+/// 移除 post-commit 钩子中的 NeuroSpec 片段（钩子只剩 shebang 时删除文件）
+///
+/// 返回钩子文件路径，没有安装过时返回 None
+pub fn uninstall_post_commit_hook(project_root: &Path) -> Result<Option<PathBuf>> {
+    let path = GitIntegration::new(&project_root.to_string_lossy()).hooks_dir()?.join("post-commit");
+    let existing = match std::fs::read_to_string(&path) {
+        Ok(content) if content.contains(HOOK_BEGIN_MARKER) => content,
+        Ok(_) => return Ok(None),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    let remaining = remove_hook_block(&existing);
+    let is_empty = remaining
+        .lines()
+        .all(|line| line.trim().is_empty() || line.starts_with("#!"));
+    if is_empty {
+        std::fs::remove_file(&path)?;
+    } else {
+        std::fs::write(&path, remaining.trim_end().to_string() + "\n")?;
+    }
+
+    Ok(Some(path))
+}
+
+/// 为提交记录一条代码修改记忆
+///
+/// 先把已有的修改记忆关联到提交；提交已有关联的记忆（AI 在提交前手动记录过）时不再重复记录
+pub fn capture_commit(project_root: &Path, rev: &str) -> Result<CommitCapture> {
+    let root = project_root.to_string_lossy();
+    let git = GitIntegration::new(&root);
+    let commit = git
+        .commit_info(rev)?
+        .ok_or_else(|| anyhow::anyhow!("Unknown commit: {}", rev))?;
+
+    let tracker = ChangeTracker::new(&root)?;
+    if let Err(e) = tracker.link_commits() {
+        crate::log_debug!("Failed to link change memories to commits: {}", e);
+    }
+    if !tracker.changes_for_commit(&commit.hash)?.is_empty() {
+        return Ok(CommitCapture {
+            commit: commit.hash,
+            memory_id: None,
+            files: commit.files,
+            symbols: Vec::new(),
+        });
+    }
+
+    let diff = git.commit_diff(&commit.hash)?;
+    let symbols = touched_symbols(project_root, &diff);
+    let message = git.commit_message(&commit.hash).unwrap_or_else(|_| commit.summary.clone());
+
+    let id = tracker.record_change_with_diff(
+        infer_change_type(&commit.summary, &message),
+        commit.files.clone(),
+        symbols.clone(),
+        commit.summary.clone(),
+        message,
+        truncate_chars(&diff, MAX_DIFF_SNIPPET_CHARS),
+    )?;
+    tracker.link_commit(&id, &commit.hash)?;
+
+    Ok(CommitCapture {
+        commit: commit.hash,
+        memory_id: Some(id),
+        files: commit.files,
+        symbols,
+    })
+}
+
+/// 钩子片段：在后台调用当前程序的 `hook record`，不拖慢提交
+fn hook_block() -> Result<String> {
+    // Git for Windows 的 sh 也接受正斜杠形式的 Windows 路径
+    let exe = std::env::current_exe()?.to_string_lossy().replace('\\', "/");
+    Ok(format!(
+        "{}\n\"{}\" hook record --project \"$(git rev-parse --show-toplevel)\" >/dev/null 2>&1 &\n{}\n",
+        HOOK_BEGIN_MARKER, exe, HOOK_END_MARKER
+    ))
+}
+
+/// 钩子是否由 sh/bash 执行（没有 shebang 时 Git 以 sh 执行）
+fn is_shell_hook(content: &str) -> bool {
+    let Some(shebang) = content.lines().next().and_then(|line| line.strip_prefix("#!")) else {
+        return true;
+    };
+    let mut parts = shebang.split_whitespace();
+    let mut interpreter = parts.next().unwrap_or_default();
+    // #!/usr/bin/env bash
+    if interpreter.ends_with("/env") {
+        interpreter = parts.next().unwrap_or_default();
+    }
+    matches!(interpreter.rsplit('/').next(), Some("sh" | "bash"))
+}
+
+/// 把片段加入钩子内容：末尾的 `exit`/`exec` 之后的内容不会执行，片段插在其前面
+fn insert_hook_block(content: &str, block: &str) -> String {
+    let lines: Vec<&str> = content.trim_end().lines().collect();
+    let last_command = lines.iter().rposition(|line| {
+        let line = line.trim();
+        !line.is_empty() && !line.starts_with('#')
+    });
+    let insert_at = match last_command {
+        Some(i) if matches!(lines[i].split_whitespace().next(), Some("exit" | "exec")) => i,
+        _ => lines.len(),
+    };
+
+    let mut out = lines[..insert_at].join("\n").trim_end().to_string();
+    out.push_str("\n\n");
+    out.push_str(block);
+    if insert_at < lines.len() {
+        out.push('\n');
+        out.push_str(&lines[insert_at..].join("\n"));
+        out.push('\n');
+    }
+    out
+}
+
+/// 去掉钩子内容中的 NeuroSpec 片段
+fn remove_hook_block(content: &str) -> String {
+    let Some(begin) = content.find(HOOK_BEGIN_MARKER) else {
+        return content.to_string();
+    };
+    let end = content[begin..]
+        .find(HOOK_END_MARKER)
+        .map(|i| begin + i + HOOK_END_MARKER.len())
+        .unwrap_or(content.len());
+    let after = &content[end..];
+    format!("{}{}", &content[..begin], after.strip_prefix('\n').unwrap_or(after))
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut permissions = std::fs::metadata(path)?.permissions();
+    permissions.set_mode(permissions.mode() | 0o755);
+    std::fs::set_permissions(path, permissions)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// 改动涉及的符号：符号定义的行范围与提交后文件中改动的行有重叠
+///
+/// 使用全局符号存储，存储未初始化时返回空列表
+fn touched_symbols(project_root: &Path, diff: &str) -> Vec<String> {
+    let ranges = changed_line_ranges(diff);
+    if ranges.is_empty() {
+        return Vec::new();
+    }
+
+    let result = with_global_store(|store| {
+        let mut names: Vec<String> = Vec::new();
+        for (file, file_ranges) in &ranges {
+            let filter = SymbolFilter {
+                path_prefix: Some(file.clone()),
+                ..SymbolFilter::default()
+            };
+            for symbol in store.query_symbols(project_root, &filter)? {
+                if symbol.path != *file || matches!(symbol.kind, SymbolKind::File | SymbolKind::Module) {
+                    continue;
+                }
+                let (Some(start), Some(end)) = (symbol.start_line, symbol.end_line) else {
+                    continue;
+                };
+                let touched = file_ranges.iter().any(|&(from, to)| start <= to && end >= from);
+                if touched && !names.contains(&symbol.name) {
+                    names.push(symbol.name);
+                }
+            }
+        }
+        Ok(names)
+    });
+
+    match result {
+        Ok(mut names) => {
+            names.truncate(MAX_COMMIT_SYMBOLS);
+            names
+        }
+        Err(e) => {
+            crate::log_debug!("Touched symbols unavailable for {}: {}", project_root.display(), e);
+            Vec::new()
+        }
+    }
+}
+
+/// 从 `--unified=0` 的改动中解析每个文件在提交后版本中改动的行范围（闭区间）
+fn changed_line_ranges(diff: &str) -> HashMap<String, Vec<(u32, u32)>> {
+    let mut ranges: HashMap<String, Vec<(u32, u32)>> = HashMap::new();
+    let mut current: Option<String> = None;
+
+    for line in diff.lines() {
+        if let Some(path) = line.strip_prefix("+++ ") {
+            // 删除的文件为 /dev/null
+            current = path.strip_prefix("b/").map(str::to_string);
+        } else if let (Some(file), Some(hunk)) = (&current, line.strip_prefix("@@ ")) {
+            // @@ -a,b +c,d @@：新版本从第 c 行起 d 行（d 省略为 1，为 0 表示只删除了内容）
+            let Some(new_range) = hunk.split_whitespace().find_map(|part| part.strip_prefix('+')) else {
+                continue;
+            };
+            let mut parts = new_range.split(',');
+            let start: u32 = parts.next().and_then(|n| n.parse().ok()).unwrap_or(0);
+            let count: u32 = parts.next().and_then(|n| n.parse().ok()).unwrap_or(1);
+            let end = start + count.saturating_sub(1);
+            ranges.entry(file.clone()).or_default().push((start.max(1), end.max(1)));
+        }
+    }
+
+    ranges
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((idx, _)) => format!("{}\n...", &text[..idx]),
+        None => text.to_string(),
+    }
+}
@@ -4,6 +4,8 @@
 
 pub mod git;
 pub mod export;
+pub mod hook;
 
-pub use git::{GitIntegration, GitSuggestion};
-pub use export::{MemoryExporter, ExportFormat};
+pub use git::{GitCommit, GitIntegration, GitSuggestion};
+pub use export::{MemoryExporter, ExportFormat, RuleFileTarget};
+pub use hook::{capture_commit, install_post_commit_hook, uninstall_post_commit_hook, CommitCapture};
@@ -7,9 +7,24 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use super::integration::{MemoryExporter, RuleFileTarget};
 use super::storage::{MemoryStorage, SqliteStorage, FileStorage, MigrationManager};
 use super::types::{MemoryEntry, MemoryCategory, MemoryListResult};
 
+/// 记忆数超过此值时，智能召回先用全文检索筛选候选，再做 TF-IDF 排序
+const RECALL_CANDIDATE_LIMIT: usize = 200;
+
+/// 规则文件同步结果
+#[derive(Debug)]
+pub struct RuleFileSync {
+    /// 规则文件路径
+    pub path: PathBuf,
+    /// 从规则文件新导入的规则
+    pub imported: Vec<String>,
+    /// 写入规则文件的规则数（只导入时为 0）
+    pub exported: usize,
+}
+
 /// 存储后端类型
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum StorageBackend {
@@ -20,11 +35,14 @@ pub enum StorageBackend {
 }
 
 /// 记忆管理器
+///
+/// 存储后端都是同步 IO。异步上下文（MCP 处理器、daemon）应通过 [`MemoryManager::open`]
+/// 和 [`MemoryManager::run`] 在阻塞线程池中访问，避免占用 tokio 工作线程
+#[derive(Clone)]
 pub struct MemoryManager {
     storage: Arc<dyn MemoryStorage>,
     #[allow(dead_code)] // 保留用于未来诊断/调试
     memory_dir: PathBuf,
-    #[allow(dead_code)] // 保留用于未来诊断/调试
     project_path: String,
     backend: StorageBackend,
 }
@@ -38,7 +56,7 @@ impl MemoryManager {
     /// 使用指定后端创建记忆管理器
     pub fn with_backend(project_path: &str, backend: StorageBackend) -> Result<Self> {
         let normalized_path = Self::normalize_project_path(project_path)?;
-        let memory_dir = normalized_path.join(".neurospec-memory");
+        let memory_dir = crate::config::project_memory_dir(&normalized_path);
 
         fs::create_dir_all(&memory_dir)
             .map_err(|e| anyhow::anyhow!(
@@ -46,7 +64,7 @@ impl MemoryManager {
                 memory_dir.display(), e
             ))?;
 
-        let project_path_str = normalized_path.to_string_lossy().to_string();
+        let project_path_str = crate::utils::path_key(&normalized_path);
 
         // 检查是否需要迁移
         let migration_manager = MigrationManager::new(
@@ -81,6 +99,27 @@ impl MemoryManager {
         })
     }
 
+    /// 在阻塞线程池中创建记忆管理器（打开数据库、必要时执行迁移）
+    pub async fn open(project_path: &str) -> Result<Self> {
+        let project_path = project_path.to_string();
+        tokio::task::spawn_blocking(move || Self::new(&project_path))
+            .await
+            .map_err(|e| anyhow::anyhow!("Memory task failed: {}", e))?
+    }
+
+    /// 在阻塞线程池中执行一组记忆操作
+    pub async fn run<T, E, F>(&self, f: F) -> std::result::Result<T, E>
+    where
+        F: FnOnce(&MemoryManager) -> std::result::Result<T, E> + Send + 'static,
+        T: Send + 'static,
+        E: From<anyhow::Error> + Send + 'static,
+    {
+        let manager = self.clone();
+        tokio::task::spawn_blocking(move || f(&manager))
+            .await
+            .map_err(|e| E::from(anyhow::anyhow!("Memory task failed: {}", e)))?
+    }
+
     /// 使用文件存储创建（内部方法）
     fn create_with_file_storage(memory_dir: PathBuf, project_path: String) -> Result<Self> {
         let storage = Arc::new(FileStorage::new(memory_dir.clone(), project_path.clone())?);
@@ -134,6 +173,11 @@ impl MemoryManager {
         self.storage.get_all()
     }
 
+    /// 记忆条目总数
+    pub fn count_memories(&self) -> Result<usize> {
+        self.storage.count(None)
+    }
+
     /// 获取指定分类的记忆
     pub fn get_memories_by_category(&self, category: MemoryCategory) -> Result<Vec<MemoryEntry>> {
         self.storage.get_by_category(category)
@@ -144,6 +188,11 @@ impl MemoryManager {
         self.storage.record_usage(memory_id)
     }
 
+    /// 全文搜索记忆
+    pub fn search_memories(&self, query: &str, limit: usize) -> Result<Vec<MemoryEntry>> {
+        self.storage.search(query, limit)
+    }
+
     /// 智能召回：基于上下文返回相关记忆
     pub fn smart_recall(
         &self,
@@ -153,7 +202,18 @@ impl MemoryManager {
     ) -> Result<Vec<super::retrieval::ScoredMemory>> {
         use super::retrieval::MemoryRanker;
 
-        let all_memories = self.storage.get_all()?;
+        // 记忆较多时只对全文检索命中的候选排序，没有命中时仍按全部记忆排序
+        let all_memories = match context.filter(|c| !c.trim().is_empty()) {
+            Some(ctx) if self.storage.count(None)? > RECALL_CANDIDATE_LIMIT => {
+                let candidates = self.storage.search(ctx, RECALL_CANDIDATE_LIMIT)?;
+                if candidates.is_empty() {
+                    self.storage.get_all()?
+                } else {
+                    candidates
+                }
+            }
+            _ => self.storage.get_all()?,
+        };
         if all_memories.is_empty() {
             return Ok(Vec::new());
         }
@@ -278,6 +338,67 @@ impl MemoryManager {
         }
     }
 
+    // ========== 规则文件同步 ==========
+
+    /// 从规则文件（AGENTS.md / Cursor 规则）导入规则，已存在的相同规则跳过
+    pub fn import_rule_file(&self, target: RuleFileTarget) -> Result<RuleFileSync> {
+        let root = PathBuf::from(&self.project_path);
+        let rules = MemoryExporter::import_rules_from_file(&root, target)?;
+
+        let mut known: std::collections::HashSet<String> = self.storage
+            .get_by_category(MemoryCategory::Rule)?
+            .iter()
+            .map(|m| Self::rule_key(&m.content))
+            .collect();
+
+        let mut imported = Vec::new();
+        for rule in rules {
+            if known.insert(Self::rule_key(&rule)) {
+                self.add_memory(&rule, MemoryCategory::Rule)?;
+                imported.push(rule);
+            }
+        }
+
+        Ok(RuleFileSync {
+            path: root.join(target.relative_path()),
+            imported,
+            exported: 0,
+        })
+    }
+
+    /// 把规则类记忆写入规则文件的 NeuroSpec 区块
+    pub fn export_rule_file(&self, target: RuleFileTarget) -> Result<RuleFileSync> {
+        let rules = self.storage.get_by_category(MemoryCategory::Rule)?;
+        let path = MemoryExporter::export_rules_to_file(&rules, Path::new(&self.project_path), target)?;
+
+        Ok(RuleFileSync {
+            path,
+            imported: Vec::new(),
+            exported: rules.len(),
+        })
+    }
+
+    /// 双向同步：先导入规则文件 NeuroSpec 区块中新增的规则，再把全部规则写回文件
+    ///
+    /// 只合并新增内容，从文件中删掉的规则不会删除对应记忆。文件还没有区块（首次同步）时
+    /// 只写入，不导入手写内容，避免把文件中的普通列表复制进区块；需要时先显式调用 `import_rules`
+    pub fn sync_rule_file(&self, target: RuleFileTarget) -> Result<RuleFileSync> {
+        let root = PathBuf::from(&self.project_path);
+        let imported = if MemoryExporter::rule_file_has_section(&root, target)? {
+            self.import_rule_file(target)?.imported
+        } else {
+            Vec::new()
+        };
+        let exported = self.export_rule_file(target)?;
+
+        Ok(RuleFileSync { imported, ..exported })
+    }
+
+    /// 规则比较键：忽略大小写和空白差异
+    fn rule_key(content: &str) -> String {
+        content.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+    }
+
     // ========== 路径处理方法 ==========
 
     fn normalize_project_path(project_path: &str) -> Result<PathBuf> {
@@ -291,8 +412,7 @@ impl MemoryManager {
             std::env::current_dir()?.join(path)
         };
 
-        let canonical_path = absolute_path.canonicalize()
-            .unwrap_or_else(|_| Self::manual_canonicalize(&absolute_path).unwrap_or(absolute_path));
+        let canonical_path = crate::utils::canonical_path(&absolute_path);
 
         if !canonical_path.exists() {
             return Err(anyhow::anyhow!(
@@ -315,22 +435,6 @@ impl MemoryManager {
         }
     }
 
-    fn manual_canonicalize(path: &Path) -> Result<PathBuf> {
-        let mut components = Vec::new();
-        for component in path.components() {
-            match component {
-                std::path::Component::CurDir => {}
-                std::path::Component::ParentDir => { components.pop(); }
-                _ => { components.push(component); }
-            }
-        }
-        let mut result = PathBuf::new();
-        for component in components {
-            result.push(component);
-        }
-        Ok(result)
-    }
-
     fn find_git_root(start_path: &Path) -> Option<PathBuf> {
         let mut current_path = start_path;
         loop {
@@ -4,16 +4,20 @@ use std::collections::HashMap;
 use std::sync::Mutex;
 use std::path::PathBuf;
 use lazy_static::lazy_static;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-use super::{MemoryCategory, MemoryManager, MemoryEntry, MemorySuggester, ConversationContext, MemoryListResult, ScoredMemory};
+use super::{format_file_history, ChangeTracker, MemoryCategory, MemoryManager, MemoryEntry, MemorySuggester, ConversationContext, MemoryListResult, ScoredMemory};
+use crate::config::OutputLanguage;
 use crate::mcp::{
     utils::{
         errors::{invalid_params_error, memory_error, McpToolError},
-        project_path_error, validate_project_path,
+        project_path_error, resolve_output_language, validate_project_path,
     },
     MemoryRequest, InteractRequest,
 };
 use crate::mcp::tools::interaction::InteractionTool;
+use crate::tr;
 
 // Simple LRU-like Path Cache (Global)
 lazy_static! {
@@ -21,6 +25,45 @@ lazy_static! {
     static ref MEMORY_SUGGESTER: Mutex<MemorySuggester> = Mutex::new(MemorySuggester::new());
 }
 
+/// change_intent 工具默认返回的记忆数量（只按文件查询时）
+const DEFAULT_CHANGE_INTENT_LIMIT: usize = 20;
+
+/// change_intent 工具请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ChangeIntentRequest {
+    #[schemars(description = "Project path (optional, auto-detects the Git root from the current working directory if omitted)")]
+    #[serde(default)]
+    pub project_path: String,
+    #[schemars(description = "Commit to explain: full or short hash, branch or any git revision (optional)")]
+    #[serde(default)]
+    pub commit: Option<String>,
+    #[schemars(description = "File path relative to the project root. With `line`, explains the commit that last changed that line; alone, lists change memories touching the file")]
+    #[serde(default)]
+    pub file_path: Option<String>,
+    #[schemars(description = "1-based line number in `file_path` (optional)")]
+    #[serde(default)]
+    pub line: Option<u32>,
+    #[schemars(description = "Maximum number of change memories when querying by file only (default: 20)")]
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// file_history 工具默认返回的条目数量
+const DEFAULT_FILE_HISTORY_LIMIT: usize = 20;
+
+/// file_history 工具请求参数
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FileHistoryRequest {
+    #[schemars(description = "Project path (optional, auto-detects the Git root from the current working directory if omitted)")]
+    #[serde(default)]
+    pub project_path: String,
+    #[schemars(description = "File path relative to the project root")]
+    pub file_path: String,
+    #[schemars(description = "Maximum number of timeline entries, most recent kept (default: 20)")]
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
 /// Global memory management tool
 ///
 /// For storing and managing development rules, user preferences, and best practices
@@ -28,6 +71,16 @@ lazy_static! {
 pub struct MemoryTool;
 
 impl MemoryTool {
+    /// 清空路径校验缓存和建议状态（MCP 工具配置变化后调用）
+    pub fn reset_caches() {
+        if let Ok(mut cache) = PATH_CACHE.lock() {
+            cache.clear();
+        }
+        if let Ok(mut suggester) = MEMORY_SUGGESTER.lock() {
+            *suggester = MemorySuggester::new();
+        }
+    }
+
     /// 自动推断项目路径
     /// 如果 project_path 为空，从当前工作目录向上查找 .git 目录
     fn resolve_project_path(project_path: &str) -> Result<String, McpToolError> {
@@ -87,9 +140,28 @@ impl MemoryTool {
             }
         }
 
-        let manager = MemoryManager::new(&project_path)
+        let manager = MemoryManager::open(&project_path)
+            .await
             .map_err(|e| memory_error(format!("Failed to create memory manager: {}", e)))?;
+        let lang = resolve_output_language(request.language);
 
+        // 存储读写、Git 扫描和代码分析都是同步 IO，放到阻塞线程池执行
+        let result = manager
+            .run(move |manager| Self::execute_action(manager, &request, &project_path, lang))
+            .await?;
+
+        Ok(crate::mcp::create_success_result(vec![Content::text(
+            result,
+        )]))
+    }
+
+    /// 执行记忆操作（同步，在阻塞线程池中调用）
+    fn execute_action(
+        manager: &MemoryManager,
+        request: &MemoryRequest,
+        project_path: &str,
+        lang: OutputLanguage,
+    ) -> Result<String, McpToolError> {
         let result = match request.action.as_str() {
             "remember" | "记忆" => {
                 if request.content.trim().is_empty() {
@@ -108,7 +180,9 @@ impl MemoryTool {
                     .add_memory(&request.content, category)
                     .map_err(|e| memory_error(format!("Failed to add memory: {}", e)))?;
 
-                format!(
+                tr!(
+                    lang,
+                    "✅ 记忆已添加\nID: {}\n内容: {}\n分类: {:?}",
                     "✅ Memory added successfully\nID: {}\nContent: {}\nCategory: {:?}",
                     id, request.content, category
                 )
@@ -123,9 +197,9 @@ impl MemoryTool {
                             .map_err(|e| memory_error(format!("Smart recall failed: {}", e)))?;
                         
                         if scored.is_empty() {
-                            "📭 未找到相关记忆".to_string()
+                            tr!(lang, "📭 未找到相关记忆", "📭 No related memories found")
                         } else {
-                            Self::format_smart_recall_result(&scored)
+                            Self::format_smart_recall_result(&scored, lang)
                         }
                     } else {
                         manager
@@ -149,9 +223,9 @@ impl MemoryTool {
                     .map_err(|e| memory_error(format!("Failed to delete memory: {}", e)))?;
 
                 if deleted {
-                    format!("✅ Memory deleted successfully\nID: {}", id)
+                    tr!(lang, "✅ 记忆已删除\nID: {}", "✅ Memory deleted successfully\nID: {}", id)
                 } else {
-                    format!("⚠️ Memory not found\nID: {}", id)
+                    Self::memory_not_found(lang, id)
                 }
             }
 
@@ -169,9 +243,14 @@ impl MemoryTool {
                     .map_err(|e| memory_error(format!("Failed to update memory: {}", e)))?;
 
                 if updated {
-                    format!("✅ Memory updated successfully\nID: {}\nNew content: {}", id, request.content)
+                    tr!(
+                        lang,
+                        "✅ 记忆已更新\nID: {}\n新内容: {}",
+                        "✅ Memory updated successfully\nID: {}\nNew content: {}",
+                        id, request.content
+                    )
                 } else {
-                    format!("⚠️ Memory not found\nID: {}", id)
+                    Self::memory_not_found(lang, id)
                 }
             }
 
@@ -189,7 +268,7 @@ impl MemoryTool {
                     .list_memories(category, request.page, request.page_size)
                     .map_err(|e| memory_error(format!("Failed to list memories: {}", e)))?;
 
-                Self::format_list_result(&result)
+                Self::format_list_result(&result, lang)
             }
 
             "get" | "获取" => {
@@ -202,11 +281,13 @@ impl MemoryTool {
                     .map_err(|e| memory_error(format!("Failed to get memory: {}", e)))?;
 
                 match memory {
-                    Some(m) => format!(
+                    Some(m) => tr!(
+                        lang,
+                        "📝 记忆详情\nID: {}\n分类: {:?}\n内容: {}\n创建时间: {}\n更新时间: {}",
                         "📝 Memory Details\nID: {}\nCategory: {:?}\nContent: {}\nCreated: {}\nUpdated: {}",
                         m.id, m.category, m.content, m.created_at, m.updated_at
                     ),
-                    None => format!("⚠️ Memory not found\nID: {}", id),
+                    None => Self::memory_not_found(lang, id),
                 }
             }
 
@@ -231,7 +312,12 @@ impl MemoryTool {
                     }
                 };
 
-                format!("📤 导出成功 ({} 条记忆)\n\n{}", memories.len(), content)
+                tr!(
+                    lang,
+                    "📤 导出成功 ({} 条记忆)\n\n{}",
+                    "📤 Exported {} memories\n\n{}",
+                    memories.len(), content
+                )
             }
 
             "import" | "导入" => {
@@ -249,7 +335,38 @@ impl MemoryTool {
                     }
                 }
 
-                format!("📥 导入成功: {} 条记忆", success_count)
+                tr!(lang, "📥 导入成功: {} 条记忆", "📥 Imported {} memories", success_count)
+            }
+
+            "export_rules" | "import_rules" | "sync_rules" => {
+                // category 指定规则文件：cursor -> .cursor/rules/neurospec.mdc，未指定 -> AGENTS.md
+                let target = match request.category.trim() {
+                    "" | "context" => super::RuleFileTarget::AgentsMd,
+                    name => super::RuleFileTarget::parse(name).ok_or_else(|| {
+                        invalid_params_error(format!(
+                            "Unknown rule file target '{}': use 'agents' (AGENTS.md) or 'cursor' (.cursor/rules/neurospec.mdc)",
+                            name
+                        ))
+                    })?,
+                };
+
+                let sync = match request.action.as_str() {
+                    "export_rules" => manager.export_rule_file(target),
+                    "import_rules" => manager.import_rule_file(target),
+                    _ => manager.sync_rule_file(target),
+                }
+                .map_err(|e| memory_error(format!("Rule file sync failed: {}", e)))?;
+
+                let mut output = tr!(
+                    lang,
+                    "📄 规则文件: {}\n导入 {} 条新规则，写入 {} 条规则\n",
+                    "📄 Rule file: {}\nImported {} new rules, wrote {} rules\n",
+                    sync.path.display(), sync.imported.len(), sync.exported
+                );
+                for rule in &sync.imported {
+                    output.push_str(&format!("+ {}\n", rule));
+                }
+                output
             }
 
             "git_scan" | "扫描git" => {
@@ -258,11 +375,25 @@ impl MemoryTool {
                     .map_err(|e| memory_error(format!("Git scan failed: {}", e)))?;
 
                 if suggestions.is_empty() {
-                    "📭 未从 Git 历史中发现可记忆的模式".to_string()
+                    tr!(
+                        lang,
+                        "📭 未从 Git 历史中发现可记忆的模式",
+                        "📭 No memorable patterns found in the Git history"
+                    )
                 } else {
-                    let mut output = format!("🔍 从 Git 历史发现 {} 条建议:\n\n", suggestions.len());
+                    let mut output = tr!(
+                        lang,
+                        "🔍 从 Git 历史发现 {} 条建议:\n\n",
+                        "🔍 Found {} suggestions in the Git history:\n\n",
+                        suggestions.len()
+                    );
                     for (i, s) in suggestions.iter().enumerate() {
-                        output.push_str(&format!("{}. {} (置信度: {:.0}%)\n", i + 1, s.content, s.confidence * 100.0));
+                        output.push_str(&tr!(
+                            lang,
+                            "{}. {} (置信度: {:.0}%)\n",
+                            "{}. {} (confidence: {:.0}%)\n",
+                            i + 1, s.content, s.confidence * 100.0
+                        ));
                     }
                     output
                 }
@@ -270,14 +401,14 @@ impl MemoryTool {
 
             "context" | "上下文" | "project_context" => {
                 // 智能上下文注入：获取项目背景信息
-                Self::get_project_context(&project_path, &manager)?
+                Self::get_project_context(project_path, manager, lang)?
             }
 
             "analyze" | "分析" | "analyze_patterns" => {
                 // 代码模式分析
                 use super::ai_suggester::CodePatternAnalyzer;
                 
-                let analysis = CodePatternAnalyzer::analyze_project(&project_path)
+                let analysis = CodePatternAnalyzer::analyze_project(project_path)
                     .map_err(|e| memory_error(format!("代码分析失败: {}", e)))?;
                 
                 CodePatternAnalyzer::format_analysis(&analysis)
@@ -285,15 +416,75 @@ impl MemoryTool {
 
             _ => {
                 return Err(invalid_params_error(format!(
-                    "Unknown action type: {}. Supported actions: 'remember', 'recall', 'delete', 'update', 'list', 'get', 'export', 'import', 'git_scan', 'context', 'analyze'",
+                    "Unknown action type: {}. Supported actions: 'remember', 'recall', 'delete', 'update', 'list', 'get', 'export', 'import', 'export_rules', 'import_rules', 'sync_rules', 'git_scan', 'context', 'analyze'",
                     request.action
                 )));
             }
         };
 
-        Ok(crate::mcp::create_success_result(vec![Content::text(
-            result,
-        )]))
+        Ok(result)
+    }
+
+    /// 查询提交 / 文件 / 行背后的修改意图
+    ///
+    /// 先把尚未关联的修改记忆关联到之后的提交，再按提交哈希查找
+    pub async fn change_intent(request: ChangeIntentRequest) -> Result<CallToolResult, McpToolError> {
+        let project_path = Self::resolve_project_path(&request.project_path)?;
+        validate_project_path(&project_path)
+            .map_err(|e| project_path_error(format!("Path validation failed: {}", e)))?;
+
+        let result = tokio::task::spawn_blocking(move || -> Result<serde_json::Value, McpToolError> {
+            let tracker = ChangeTracker::new(&project_path)
+                .map_err(|e| memory_error(format!("Failed to open change memories: {}", e)))?;
+
+            let intent = match (&request.commit, &request.file_path, request.line) {
+                (Some(commit), _, _) => tracker.intent_for_commit(commit),
+                (None, Some(file), Some(line)) => tracker.intent_for_line(file, line),
+                (None, Some(file), None) => {
+                    let limit = request.limit.unwrap_or(DEFAULT_CHANGE_INTENT_LIMIT);
+                    let memories = tracker
+                        .changes_for_file(file, limit)
+                        .map_err(|e| memory_error(format!("Change lookup failed: {}", e)))?;
+                    return Ok(serde_json::json!({ "file_path": file, "count": memories.len(), "memories": memories }));
+                }
+                (None, None, _) => {
+                    return Err(invalid_params_error("Either `commit` or `file_path` is required"));
+                }
+            }
+            .map_err(|e| memory_error(format!("Change lookup failed: {}", e)))?;
+
+            Ok(serde_json::to_value(intent)?)
+        })
+        .await
+        .map_err(|e| memory_error(format!("Change lookup task failed: {}", e)))??;
+
+        let json = serde_json::to_string_pretty(&result)?;
+        Ok(crate::mcp::create_success_result(vec![Content::text(json)]))
+    }
+
+    /// 文件的修改时间线：提交历史与修改记忆合并，按时间升序
+    pub async fn file_history(request: FileHistoryRequest) -> Result<CallToolResult, McpToolError> {
+        if request.file_path.trim().is_empty() {
+            return Err(invalid_params_error("file_path is required"));
+        }
+        let project_path = Self::resolve_project_path(&request.project_path)?;
+        validate_project_path(&project_path)
+            .map_err(|e| project_path_error(format!("Path validation failed: {}", e)))?;
+
+        let text = tokio::task::spawn_blocking(move || -> Result<String, McpToolError> {
+            let tracker = ChangeTracker::new(&project_path)
+                .map_err(|e| memory_error(format!("Failed to open change memories: {}", e)))?;
+            let limit = request.limit.unwrap_or(DEFAULT_FILE_HISTORY_LIMIT).max(1);
+            let entries = tracker
+                .file_history(&request.file_path, limit)
+                .map_err(|e| memory_error(format!("File history lookup failed: {}", e)))?;
+
+            Ok(format_file_history(&request.file_path, &entries))
+        })
+        .await
+        .map_err(|e| memory_error(format!("File history task failed: {}", e)))??;
+
+        Ok(crate::mcp::create_success_result(vec![Content::text(text)]))
     }
 
     // Legacy method name for backward compatibility
@@ -349,6 +540,14 @@ impl MemoryTool {
                 "📝 修改计划细节".to_string(),
             ],
             is_markdown: true,
+            multi_select: false,
+            input: None,
+            preview: None,
+            picker: None,
+            timeout_secs: None,
+            default_option: None,
+            form: None,
+            language: None,
         };
 
         let response = InteractionTool::interact(interact_request)
@@ -458,13 +657,17 @@ impl MemoryTool {
 
     /// 获取项目上下文信息
     /// 自动检测项目类型、依赖、并召回相关记忆
-    fn get_project_context(project_path: &str, manager: &MemoryManager) -> Result<String, McpToolError> {
+    fn get_project_context(
+        project_path: &str,
+        manager: &MemoryManager,
+        lang: OutputLanguage,
+    ) -> Result<String, McpToolError> {
         use std::fs;
         use std::path::Path;
 
         let root = Path::new(project_path);
         let mut context = String::new();
-        context.push_str("# 📋 项目上下文\n\n");
+        context.push_str(&tr!(lang, "# 📋 项目上下文\n\n", "# 📋 Project Context\n\n"));
 
         // 1. 检测项目类型和依赖
         let mut project_type = "Unknown";
@@ -540,22 +743,22 @@ impl MemoryTool {
             }
         }
 
-        context.push_str(&format!("## 项目信息\n"));
-        context.push_str(&format!("- **名称**: {}\n", project_name));
-        context.push_str(&format!("- **类型**: {}\n", project_type));
-        context.push_str(&format!("- **路径**: {}\n", project_path));
+        context.push_str(&tr!(lang, "## 项目信息\n", "## Project Info\n"));
+        context.push_str(&tr!(lang, "- **名称**: {}\n", "- **Name**: {}\n", project_name));
+        context.push_str(&tr!(lang, "- **类型**: {}\n", "- **Type**: {}\n", project_type));
+        context.push_str(&tr!(lang, "- **路径**: {}\n", "- **Path**: {}\n", project_path));
 
         if !key_deps.is_empty() {
-            context.push_str(&format!("- **主要依赖**: {}\n", key_deps.join(", ")));
+            context.push_str(&tr!(lang, "- **主要依赖**: {}\n", "- **Key dependencies**: {}\n", key_deps.join(", ")));
         }
 
         // 2. 召回相关记忆
-        context.push_str("\n## 项目记忆\n");
+        context.push_str(&tr!(lang, "\n## 项目记忆\n", "\n## Project Memories\n"));
         let memories = manager.list_memories(None, 1, 10)
             .map_err(|e| memory_error(format!("Failed to list memories: {}", e)))?;
 
         if memories.memories.is_empty() {
-            context.push_str("暂无项目记忆\n");
+            context.push_str(&tr!(lang, "暂无项目记忆\n", "No project memories yet\n"));
         } else {
             for mem in &memories.memories {
                 let icon = match mem.category {
@@ -567,23 +770,37 @@ impl MemoryTool {
                 context.push_str(&format!("- {} {}\n", icon, mem.content));
             }
             if memories.total > 10 {
-                context.push_str(&format!("\n_...还有 {} 条记忆_\n", memories.total - 10));
+                context.push_str(&tr!(
+                    lang,
+                    "\n_...还有 {} 条记忆_\n",
+                    "\n_...{} more memories_\n",
+                    memories.total - 10
+                ));
             }
         }
 
         Ok(context)
     }
 
+    /// 记忆不存在时的提示
+    fn memory_not_found(lang: OutputLanguage, id: &str) -> String {
+        tr!(lang, "⚠️ 记忆不存在\nID: {}", "⚠️ Memory not found\nID: {}", id)
+    }
+
     /// 格式化列表结果
-    fn format_list_result(result: &MemoryListResult) -> String {
+    fn format_list_result(result: &MemoryListResult, lang: OutputLanguage) -> String {
         if result.memories.is_empty() {
-            return format!(
+            return tr!(
+                lang,
+                "📭 暂无记忆\n页码: {}/{}\n总数: {}",
                 "📭 No memories found\nPage: {}/{}\nTotal: {}",
                 result.page, result.total_pages.max(1), result.total
             );
         }
 
-        let mut output = format!(
+        let mut output = tr!(
+            lang,
+            "📚 记忆列表 (第 {}/{} 页)\n共 {} 条记忆\n\n",
             "📚 Memory List (Page {}/{})\nTotal: {} memories\n\n",
             result.page, result.total_pages, result.total
         );
@@ -607,7 +824,9 @@ impl MemoryTool {
         }
 
         if result.page < result.total_pages {
-            output.push_str(&format!(
+            output.push_str(&tr!(
+                lang,
+                "---\n💡 使用 page={} 查看更多",
                 "---\n💡 Use page={} to see more",
                 result.page + 1
             ));
@@ -617,8 +836,13 @@ impl MemoryTool {
     }
 
     /// 格式化智能召回结果
-    fn format_smart_recall_result(scored: &[ScoredMemory]) -> String {
-        let mut output = format!("📚 相关记忆 (共 {} 条):\n\n", scored.len());
+    fn format_smart_recall_result(scored: &[ScoredMemory], lang: OutputLanguage) -> String {
+        let mut output = tr!(
+            lang,
+            "📚 相关记忆 (共 {} 条):\n\n",
+            "📚 Related memories ({}):\n\n",
+            scored.len()
+        );
 
         for (i, sm) in scored.iter().enumerate() {
             let category_icon = match sm.memory.category {
@@ -628,8 +852,10 @@ impl MemoryTool {
                 MemoryCategory::Context => "⚪",
             };
 
-            output.push_str(&format!(
+            output.push_str(&tr!(
+                lang,
                 "{}. {} {} (相关度: {:.0}%)\n",
+                "{}. {} {} (relevance: {:.0}%)\n",
                 i + 1,
                 category_icon,
                 sm.memory.content,
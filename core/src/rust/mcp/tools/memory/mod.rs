@@ -15,8 +15,11 @@ pub mod types;
 // 重新导出主要类型和功能
 pub use ai_suggester::{MemorySuggester, MemorySuggestion, MemoryUsageStats, ConversationContext};
 pub use commands::{memory_list, memory_add, memory_update, memory_delete};
-pub use integration::{GitIntegration, GitSuggestion, MemoryExporter, ExportFormat};
-pub use manager::{MemoryManager, StorageBackend};
+pub use integration::{
+    GitCommit, GitIntegration, GitSuggestion, MemoryExporter, ExportFormat, RuleFileTarget,
+    capture_commit, install_post_commit_hook, uninstall_post_commit_hook, CommitCapture,
+};
+pub use manager::{MemoryManager, RuleFileSync, StorageBackend};
 pub use mcp::MemoryTool;
 pub use retrieval::{MemoryRanker, ScoredMemory, RankingConfig, TfIdfEngine};
 pub use storage::{MemoryStorage, SqliteStorage, FileStorage, MigrationManager};
@@ -24,5 +27,7 @@ pub use types::{
     MemoryEntry, MemoryCategory, MemoryMetadata, MemoryListResult,
     // 代码修改轨迹记忆
     CodeChangeMemory, ChangeType, ChangeMemoryListResult,
+    // NSP 计划执行记录
+    PlanRunStatus, StoredPlan,
 };
-pub use tracker::{ChangeTracker, infer_change_type, format_change_memory};
+pub use tracker::{ChangeTracker, ChangeIntent, FileHistoryEntry, infer_change_type, format_change_memory, format_file_history};
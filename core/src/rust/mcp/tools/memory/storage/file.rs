@@ -237,6 +237,30 @@ impl MemoryStorage for FileStorage {
         Ok(memories)
     }
 
+    fn search(&self, query: &str, limit: usize) -> Result<Vec<MemoryEntry>> {
+        // 文件存储没有索引，逐条按子串匹配，命中词数多的排在前面
+        let terms: Vec<String> = query
+            .split_whitespace()
+            .filter(|term| term.chars().count() >= 3)
+            .map(str::to_lowercase)
+            .collect();
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut matched: Vec<(usize, MemoryEntry)> = self.get_all()?
+            .into_iter()
+            .filter_map(|m| {
+                let content = m.content.to_lowercase();
+                let hits = terms.iter().filter(|term| content.contains(term.as_str())).count();
+                (hits > 0).then_some((hits, m))
+            })
+            .collect();
+        matched.sort_by(|a, b| b.0.cmp(&a.0));
+
+        Ok(matched.into_iter().take(limit).map(|(_, m)| m).collect())
+    }
+
     fn get_by_category(&self, category: MemoryCategory) -> Result<Vec<MemoryEntry>> {
         let filename = Self::get_category_filename(&category);
         let file_path = self.memory_dir.join(filename);
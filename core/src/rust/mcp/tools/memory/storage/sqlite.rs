@@ -2,22 +2,39 @@
 
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+use r2d2::{Pool, PooledConnection};
+use r2d2_sqlite::SqliteConnectionManager;
 use rusqlite::{Connection, params};
 use std::path::PathBuf;
-use std::sync::Mutex;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
 
 use super::traits::{MemoryStorage, MemoryUsageStat};
 use crate::mcp::tools::memory::types::{
     MemoryEntry, MemoryCategory, MemoryListResult, MemoryMetadata,
-    CodeChangeMemory, ChangeType,
+    CodeChangeMemory, ChangeType, PlanRunStatus, StoredPlan,
 };
 
 const DB_FILENAME: &str = "memory.db";
-const SCHEMA_VERSION: i32 = 3; // 升级到 v3 以支持向量存储
+const SCHEMA_VERSION: i32 = 5; // 升级到 v5 以关联 Git 提交
+
+/// 全文检索使用 trigram 分词：中文没有空格分词，按三字符切分后可做任意子串匹配（与原 LIKE 语义一致），
+/// 查询词至少需要 3 个字符
+const FTS_MIN_TERM_CHARS: usize = 3;
+
+/// 只读连接池上限
+const MAX_READERS: u32 = 4;
+
+/// 数据库被其他连接锁定时的等待时间
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// SQLite 存储实现
+///
+/// WAL 模式下读写互不阻塞：查询从只读连接池取连接，可以并发执行；
+/// 写入统一经过唯一的写连接，避免多个写者互相等待数据库锁
 pub struct SqliteStorage {
-    conn: Mutex<Connection>,
+    readers: Pool<SqliteConnectionManager>,
+    writer: Mutex<Connection>,
     project_path: String,
 }
 
@@ -25,20 +42,97 @@ impl SqliteStorage {
     /// 创建新的 SQLite 存储
     pub fn new(memory_dir: &PathBuf, project_path: &str) -> Result<Self> {
         let db_path = memory_dir.join(DB_FILENAME);
-        let conn = Connection::open(&db_path)?;
-        
+        let writer = Connection::open(&db_path)?;
+        writer.pragma_update(None, "journal_mode", "WAL")?;
+        writer.busy_timeout(BUSY_TIMEOUT)?;
+
+        // 读连接按需建立，短生命周期的实例不会预先打开多余的连接
+        let manager = SqliteConnectionManager::file(&db_path).with_init(|conn| {
+            conn.busy_timeout(BUSY_TIMEOUT)?;
+            conn.pragma_update(None, "query_only", true)
+        });
+        let readers = Pool::builder()
+            .max_size(MAX_READERS)
+            .min_idle(Some(0))
+            .build(manager)?;
+
         let storage = Self {
-            conn: Mutex::new(conn),
-            project_path: project_path.to_string(),
+            readers,
+            writer: Mutex::new(writer),
+            project_path: crate::utils::rekey(project_path),
         };
         
         storage.initialize_schema()?;
+        storage.rekey_project_rows()?;
         Ok(storage)
     }
 
+    /// 旧版本以其他路径形式（反斜杠、`\\?\` 前缀等）写入的本项目记录统一为当前项目键
+    fn rekey_project_rows(&self) -> Result<()> {
+        let conn = self.writer()?;
+
+        for table in ["memories", "change_memories", "nsp_plans"] {
+            let legacy: Vec<String> = {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT DISTINCT project_path FROM {} WHERE project_path != ?1",
+                    table
+                ))?;
+                let rows = stmt.query_map(params![self.project_path], |row| row.get(0))?
+                    .filter_map(|r| r.ok())
+                    .collect();
+                rows
+            };
+
+            for path in legacy {
+                if crate::utils::rekey(&path) == self.project_path {
+                    conn.execute(
+                        &format!("UPDATE {} SET project_path = ?1 WHERE project_path = ?2", table),
+                        params![self.project_path, path],
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 从连接池取一个只读连接
+    fn reader(&self) -> Result<PooledConnection<SqliteConnectionManager>> {
+        Ok(self.readers.get()?)
+    }
+
+    /// 取得唯一的写连接
+    fn writer(&self) -> Result<MutexGuard<'_, Connection>> {
+        self.writer.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))
+    }
+
+    /// 对目录中的记忆数据库执行 WAL 检查点，把日志内容写回主文件（数据库不存在时跳过）
+    pub fn checkpoint(memory_dir: &PathBuf) -> Result<()> {
+        let db_path = memory_dir.join(DB_FILENAME);
+        if !db_path.exists() {
+            return Ok(());
+        }
+        let conn = Connection::open(&db_path)?;
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+        Ok(())
+    }
+
+    /// 执行 SQLite 完整性检查，返回发现的问题（为空表示完好）
+    pub fn integrity_check(&self) -> Result<Vec<String>> {
+        let conn = self.reader()?;
+
+        let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+        let problems = stmt.query_map([], |row| row.get::<_, String>(0))?
+            .filter_map(|r| r.ok())
+            .filter(|line| line != "ok")
+            .collect();
+
+        Ok(problems)
+    }
+
     /// 初始化数据库 schema
     fn initialize_schema(&self) -> Result<()> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let conn = self.writer()?;
         
         // 创建 memories 表
         conn.execute(
@@ -84,7 +178,25 @@ impl SqliteStorage {
                 relevance_score REAL DEFAULT 1.0,
                 is_deleted INTEGER DEFAULT 0,
                 summary_embedding BLOB,
-                embedding_model TEXT
+                embedding_model TEXT,
+                commit_hash TEXT
+            )",
+            [],
+        )?;
+
+        // 创建 nsp_plans 表 (NSP 计划执行记录)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS nsp_plans (
+                id TEXT PRIMARY KEY,
+                intent_summary TEXT NOT NULL,
+                status TEXT NOT NULL,
+                plan TEXT NOT NULL,
+                edits TEXT NOT NULL,
+                checkpoints TEXT NOT NULL,
+                outcomes TEXT NOT NULL,
+                project_path TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
             )",
             [],
         )?;
@@ -115,6 +227,12 @@ impl SqliteStorage {
             "CREATE INDEX IF NOT EXISTS idx_change_memories_type ON change_memories(project_path, change_type)",
             [],
         )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_nsp_plans_project ON nsp_plans(project_path, updated_at)",
+            [],
+        )?;
+
+        Self::create_fts_tables(&conn)?;
 
         // 检查并更新 schema 版本
         let current_version: i32 = conn
@@ -127,6 +245,12 @@ impl SqliteStorage {
             conn.execute("INSERT OR REPLACE INTO schema_version (version) VALUES (?1)", [SCHEMA_VERSION])?;
         }
 
+        // commit_hash 列可能由迁移添加，索引在迁移之后创建
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_change_memories_commit ON change_memories(project_path, commit_hash)",
+            [],
+        )?;
+
         Ok(())
     }
 
@@ -151,9 +275,130 @@ impl SqliteStorage {
             }
         }
 
+        // v4 -> v5: 添加 commit_hash 字段
+        if from_version < 5 {
+            let has_commit: bool = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM pragma_table_info('change_memories') WHERE name='commit_hash'",
+                    [],
+                    |row| row.get::<_, i32>(0),
+                )
+                .map(|c| c > 0)
+                .unwrap_or(false);
+
+            if !has_commit {
+                conn.execute("ALTER TABLE change_memories ADD COLUMN commit_hash TEXT", [])?;
+                log::info!("Migrated change_memories table to v5 (added commit_hash column)");
+            }
+        }
+
+        // v3 -> v4: 为已有记录建立全文索引
+        if from_version < 4 {
+            conn.execute("INSERT INTO memories_fts(memories_fts) VALUES ('rebuild')", [])?;
+            conn.execute("INSERT INTO change_memories_fts(change_memories_fts) VALUES ('rebuild')", [])?;
+            log::info!("Migrated memory database to v4 (built full-text indexes)");
+        }
+
+        Ok(())
+    }
+
+    /// 创建全文索引表
+    ///
+    /// 外部内容表只保存索引，正文仍在 memories / change_memories 中，由触发器保持同步。
+    /// 软删除的记录保留在索引中，查询时按 is_deleted 过滤
+    fn create_fts_tables(conn: &Connection) -> Result<()> {
+        conn.execute_batch(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS memories_fts USING fts5(
+                content,
+                content='memories', content_rowid='rowid', tokenize='trigram'
+            );
+            CREATE TRIGGER IF NOT EXISTS memories_fts_insert AFTER INSERT ON memories BEGIN
+                INSERT INTO memories_fts(rowid, content) VALUES (new.rowid, new.content);
+            END;
+            CREATE TRIGGER IF NOT EXISTS memories_fts_delete AFTER DELETE ON memories BEGIN
+                INSERT INTO memories_fts(memories_fts, rowid, content) VALUES ('delete', old.rowid, old.content);
+            END;
+            CREATE TRIGGER IF NOT EXISTS memories_fts_update AFTER UPDATE OF content ON memories BEGIN
+                INSERT INTO memories_fts(memories_fts, rowid, content) VALUES ('delete', old.rowid, old.content);
+                INSERT INTO memories_fts(rowid, content) VALUES (new.rowid, new.content);
+            END;
+
+            CREATE VIRTUAL TABLE IF NOT EXISTS change_memories_fts USING fts5(
+                summary, user_intent, keywords,
+                content='change_memories', content_rowid='rowid', tokenize='trigram'
+            );
+            CREATE TRIGGER IF NOT EXISTS change_memories_fts_insert AFTER INSERT ON change_memories BEGIN
+                INSERT INTO change_memories_fts(rowid, summary, user_intent, keywords)
+                VALUES (new.rowid, new.summary, new.user_intent, new.keywords);
+            END;
+            CREATE TRIGGER IF NOT EXISTS change_memories_fts_delete AFTER DELETE ON change_memories BEGIN
+                INSERT INTO change_memories_fts(change_memories_fts, rowid, summary, user_intent, keywords)
+                VALUES ('delete', old.rowid, old.summary, old.user_intent, old.keywords);
+            END;
+            CREATE TRIGGER IF NOT EXISTS change_memories_fts_update
+            AFTER UPDATE OF summary, user_intent, keywords ON change_memories BEGIN
+                INSERT INTO change_memories_fts(change_memories_fts, rowid, summary, user_intent, keywords)
+                VALUES ('delete', old.rowid, old.summary, old.user_intent, old.keywords);
+                INSERT INTO change_memories_fts(rowid, summary, user_intent, keywords)
+                VALUES (new.rowid, new.summary, new.user_intent, new.keywords);
+            END;",
+        )?;
+
         Ok(())
     }
 
+    /// 把查询词转换为 FTS5 MATCH 表达式（各词之间为 OR，作为短语匹配避免 FTS 语法字符生效）
+    ///
+    /// 没有足够长的查询词时返回 None
+    fn fts_match_expr<'a>(terms: impl IntoIterator<Item = &'a str>) -> Option<String> {
+        let phrases: Vec<String> = terms
+            .into_iter()
+            .map(str::trim)
+            .filter(|term| term.chars().count() >= FTS_MIN_TERM_CHARS)
+            .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+            .collect();
+
+        (!phrases.is_empty()).then(|| phrases.join(" OR "))
+    }
+
+    /// 按 LIKE 子串匹配搜索记忆（查询词过短时使用），各词之间为 OR
+    fn search_like(&self, query: &str, limit: usize) -> Result<Vec<MemoryEntry>> {
+        let terms: Vec<&str> = query.split_whitespace().collect();
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.reader()?;
+
+        let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = vec![
+            Box::new(self.project_path.clone()),
+            Box::new(limit as i64),
+        ];
+        let mut conditions = Vec::new();
+        for (i, term) in terms.iter().enumerate() {
+            conditions.push(format!("content LIKE ?{}", i + 3));
+            params_vec.push(Box::new(format!("%{}%", term)));
+        }
+
+        let query = format!(
+            "SELECT id, content, category, created_at, updated_at
+             FROM memories
+             WHERE project_path = ?1 AND is_deleted = 0 AND ({})
+             ORDER BY updated_at DESC
+             LIMIT ?2",
+            conditions.join(" OR ")
+        );
+
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|b| b.as_ref()).collect();
+
+        let mut stmt = conn.prepare(&query)?;
+        let entries = stmt.query_map(params_refs.as_slice(), Self::row_to_entry)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(entries)
+    }
+
     /// 将 MemoryCategory 转换为字符串
     fn category_to_str(category: &MemoryCategory) -> &'static str {
         match category {
@@ -200,7 +445,7 @@ impl SqliteStorage {
 
 impl MemoryStorage for SqliteStorage {
     fn add(&self, entry: &MemoryEntry) -> Result<String> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let conn = self.writer()?;
         
         conn.execute(
             "INSERT INTO memories (id, content, category, project_path, created_at, updated_at)
@@ -226,7 +471,7 @@ impl MemoryStorage for SqliteStorage {
     }
 
     fn delete(&self, id: &str) -> Result<bool> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let conn = self.writer()?;
         
         // 软删除
         let rows = conn.execute(
@@ -239,7 +484,7 @@ impl MemoryStorage for SqliteStorage {
     }
 
     fn update(&self, id: &str, new_content: &str) -> Result<bool> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let conn = self.writer()?;
         
         let rows = conn.execute(
             "UPDATE memories SET content = ?1, updated_at = ?2 
@@ -251,7 +496,7 @@ impl MemoryStorage for SqliteStorage {
     }
 
     fn get_by_id(&self, id: &str) -> Result<Option<MemoryEntry>> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let conn = self.reader()?;
         
         let mut stmt = conn.prepare(
             "SELECT id, content, category, created_at, updated_at 
@@ -264,7 +509,7 @@ impl MemoryStorage for SqliteStorage {
     }
 
     fn get_all(&self) -> Result<Vec<MemoryEntry>> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let conn = self.reader()?;
         
         let mut stmt = conn.prepare(
             "SELECT id, content, category, created_at, updated_at 
@@ -280,8 +525,35 @@ impl MemoryStorage for SqliteStorage {
         Ok(entries)
     }
 
+    /// 查询词都短于 3 个字符（如两字中文词）时无法使用 trigram 索引，回退到 LIKE 扫描
+    fn search(&self, query: &str, limit: usize) -> Result<Vec<MemoryEntry>> {
+        let Some(match_expr) = Self::fts_match_expr(query.split_whitespace()) else {
+            return self.search_like(query, limit);
+        };
+
+        let conn = self.reader()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT m.id, m.content, m.category, m.created_at, m.updated_at
+             FROM memories_fts
+             JOIN memories m ON m.rowid = memories_fts.rowid
+             WHERE memories_fts MATCH ?1 AND m.project_path = ?2 AND m.is_deleted = 0
+             ORDER BY bm25(memories_fts), m.updated_at DESC
+             LIMIT ?3"
+        )?;
+
+        let entries = stmt.query_map(
+            params![match_expr, self.project_path, limit as i64],
+            Self::row_to_entry
+        )?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(entries)
+    }
+
     fn get_by_category(&self, category: MemoryCategory) -> Result<Vec<MemoryEntry>> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let conn = self.reader()?;
         
         let mut stmt = conn.prepare(
             "SELECT id, content, category, created_at, updated_at 
@@ -306,7 +578,7 @@ impl MemoryStorage for SqliteStorage {
         let page = page.max(1);
         let offset = (page - 1) * page_size;
 
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let conn = self.reader()?;
 
         let memories: Vec<MemoryEntry> = if let Some(cat) = category {
             let mut stmt = conn.prepare(
@@ -346,7 +618,7 @@ impl MemoryStorage for SqliteStorage {
     }
 
     fn count(&self, category: Option<MemoryCategory>) -> Result<usize> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let conn = self.reader()?;
 
         let count: i64 = if let Some(cat) = category {
             conn.query_row(
@@ -366,7 +638,7 @@ impl MemoryStorage for SqliteStorage {
     }
 
     fn record_usage(&self, memory_id: &str) -> Result<()> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let conn = self.writer()?;
         
         conn.execute(
             "UPDATE memory_stats 
@@ -381,7 +653,7 @@ impl MemoryStorage for SqliteStorage {
     }
 
     fn get_usage_stats(&self, memory_id: &str) -> Result<Option<MemoryUsageStat>> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let conn = self.reader()?;
         
         let stat = conn.query_row(
             "SELECT memory_id, usage_count, last_used_at, contributed_count 
@@ -448,13 +720,13 @@ impl SqliteStorage {
 
     /// 添加代码修改记忆
     pub fn add_change_memory(&self, memory: &CodeChangeMemory) -> Result<String> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let conn = self.writer()?;
         
         conn.execute(
             "INSERT INTO change_memories (
                 id, change_type, file_paths, symbols, summary, diff_snippet,
-                user_intent, keywords, project_path, created_at, recall_count, relevance_score
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                user_intent, keywords, project_path, created_at, recall_count, relevance_score, commit_hash
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
             params![
                 memory.id,
                 Self::change_type_to_str(&memory.change_type),
@@ -468,6 +740,7 @@ impl SqliteStorage {
                 memory.created_at.timestamp(),
                 memory.recall_count,
                 memory.relevance_score,
+                memory.commit_hash,
             ],
         )?;
 
@@ -476,11 +749,11 @@ impl SqliteStorage {
 
     /// 获取所有代码修改记忆
     pub fn get_all_change_memories(&self) -> Result<Vec<CodeChangeMemory>> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let conn = self.reader()?;
         
         let mut stmt = conn.prepare(
             "SELECT id, change_type, file_paths, symbols, summary, diff_snippet,
-                    user_intent, keywords, created_at, last_recalled, recall_count, relevance_score
+                    user_intent, keywords, created_at, last_recalled, recall_count, relevance_score, commit_hash
              FROM change_memories 
              WHERE project_path = ?1 AND is_deleted = 0
              ORDER BY created_at DESC"
@@ -496,8 +769,39 @@ impl SqliteStorage {
     }
 
     /// 根据关键词搜索代码修改记忆
+    ///
+    /// 在 summary / user_intent / keywords 的全文索引上匹配任一关键词，按 bm25 排序
+    /// （keywords 权重最高），得分相同时按相关性分数和时间排序。
+    /// 关键词都短于 3 个字符时无法使用 trigram 索引，回退到 LIKE 扫描
     pub fn search_change_memories(&self, keywords: &[String], limit: usize) -> Result<Vec<CodeChangeMemory>> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let Some(match_expr) = Self::fts_match_expr(keywords.iter().map(String::as_str)) else {
+            return self.search_change_memories_like(keywords, limit);
+        };
+
+        let conn = self.reader()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT c.id, c.change_type, c.file_paths, c.symbols, c.summary, c.diff_snippet,
+                    c.user_intent, c.keywords, c.created_at, c.last_recalled, c.recall_count, c.relevance_score, c.commit_hash
+             FROM change_memories_fts
+             JOIN change_memories c ON c.rowid = change_memories_fts.rowid
+             WHERE change_memories_fts MATCH ?1 AND c.project_path = ?2 AND c.is_deleted = 0
+             ORDER BY bm25(change_memories_fts, 2.0, 1.0, 3.0), c.relevance_score DESC, c.created_at DESC
+             LIMIT ?3"
+        )?;
+
+        let memories = stmt.query_map(params![match_expr, self.project_path, limit as i64], |row| {
+            Ok(self.row_to_change_memory(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(memories)
+    }
+
+    /// 按 LIKE 子串匹配搜索代码修改记忆（关键词过短时使用）
+    fn search_change_memories_like(&self, keywords: &[String], limit: usize) -> Result<Vec<CodeChangeMemory>> {
+        let conn = self.reader()?;
         
         // 构建 LIKE 查询条件
         let mut conditions = Vec::new();
@@ -522,7 +826,7 @@ impl SqliteStorage {
 
         let query = format!(
             "SELECT id, change_type, file_paths, symbols, summary, diff_snippet,
-                    user_intent, keywords, created_at, last_recalled, recall_count, relevance_score
+                    user_intent, keywords, created_at, last_recalled, recall_count, relevance_score, commit_hash
              FROM change_memories 
              WHERE project_path = ?1 AND is_deleted = 0 {}
              ORDER BY relevance_score DESC, created_at DESC
@@ -544,13 +848,13 @@ impl SqliteStorage {
 
     /// 根据文件路径搜索相关记忆
     pub fn search_by_file_path(&self, file_path: &str, limit: usize) -> Result<Vec<CodeChangeMemory>> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let conn = self.reader()?;
         
         let pattern = format!("%{}%", file_path);
         
         let mut stmt = conn.prepare(
             "SELECT id, change_type, file_paths, symbols, summary, diff_snippet,
-                    user_intent, keywords, created_at, last_recalled, recall_count, relevance_score
+                    user_intent, keywords, created_at, last_recalled, recall_count, relevance_score, commit_hash
              FROM change_memories 
              WHERE project_path = ?1 AND is_deleted = 0 AND file_paths LIKE ?2
              ORDER BY relevance_score DESC, created_at DESC
@@ -566,9 +870,63 @@ impl SqliteStorage {
         Ok(memories)
     }
 
+    /// 关联代码修改记忆与 Git 提交
+    pub fn set_change_commit(&self, memory_id: &str, commit_hash: &str) -> Result<bool> {
+        let conn = self.writer()?;
+
+        let rows = conn.execute(
+            "UPDATE change_memories SET commit_hash = ?1 WHERE id = ?2 AND project_path = ?3",
+            params![commit_hash, memory_id, self.project_path],
+        )?;
+
+        Ok(rows > 0)
+    }
+
+    /// 获取关联到指定提交的代码修改记忆
+    pub fn get_change_memories_by_commit(&self, commit_hash: &str) -> Result<Vec<CodeChangeMemory>> {
+        let conn = self.reader()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT id, change_type, file_paths, symbols, summary, diff_snippet,
+                    user_intent, keywords, created_at, last_recalled, recall_count, relevance_score, commit_hash
+             FROM change_memories
+             WHERE project_path = ?1 AND is_deleted = 0 AND commit_hash = ?2
+             ORDER BY created_at ASC"
+        )?;
+
+        let memories = stmt.query_map(params![self.project_path, commit_hash], |row| {
+            Ok(self.row_to_change_memory(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(memories)
+    }
+
+    /// 获取尚未关联提交的代码修改记忆（按创建时间升序）
+    pub fn get_unlinked_change_memories(&self) -> Result<Vec<CodeChangeMemory>> {
+        let conn = self.reader()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT id, change_type, file_paths, symbols, summary, diff_snippet,
+                    user_intent, keywords, created_at, last_recalled, recall_count, relevance_score, commit_hash
+             FROM change_memories
+             WHERE project_path = ?1 AND is_deleted = 0 AND commit_hash IS NULL
+             ORDER BY created_at ASC"
+        )?;
+
+        let memories = stmt.query_map(params![self.project_path], |row| {
+            Ok(self.row_to_change_memory(row))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(memories)
+    }
+
     /// 记录代码修改记忆被召回
     pub fn record_change_recall(&self, memory_id: &str) -> Result<()> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let conn = self.writer()?;
         
         conn.execute(
             "UPDATE change_memories 
@@ -584,7 +942,7 @@ impl SqliteStorage {
 
     /// 应用记忆衰减（批量更新）
     pub fn apply_memory_decay(&self, decay_rate: f32) -> Result<usize> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let conn = self.writer()?;
         
         // 计算衰减因子：每 30 天降低 decay_rate
         let affected = conn.execute(
@@ -599,7 +957,7 @@ impl SqliteStorage {
 
     /// 清理低分记忆（软删除）
     pub fn cleanup_low_score_memories(&self, threshold: f32) -> Result<usize> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let conn = self.writer()?;
         
         let affected = conn.execute(
             "UPDATE change_memories 
@@ -611,6 +969,62 @@ impl SqliteStorage {
         Ok(affected)
     }
 
+    /// 将代码修改记忆中引用的符号名替换为新名称（符号重命名后保持关联）
+    ///
+    /// 只更新涉及符号原所在文件 `file_path`（相对路径）的记忆，其他文件中的同名符号不受影响。
+    /// 返回被更新的记忆数量
+    pub fn rename_change_memory_symbol(&self, old_name: &str, new_name: &str, file_path: &str) -> Result<usize> {
+        let conn = self.writer()?;
+
+        let file_path = file_path.replace('\\', "/");
+        let candidates: Vec<(String, String, String)> = {
+            let mut stmt = conn.prepare(
+                "SELECT id, symbols, file_paths FROM change_memories
+                 WHERE project_path = ?1 AND is_deleted = 0 AND symbols LIKE ?2 AND file_paths LIKE ?3"
+            )?;
+            let rows = stmt.query_map(
+                params![self.project_path, format!("%{}%", old_name), format!("%{}%", file_path)],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )?
+            .filter_map(|r| r.ok())
+            .collect();
+            rows
+        };
+
+        let mut updated = 0;
+        for (id, symbols_json, file_paths_json) in candidates {
+            let file_paths: Vec<String> = serde_json::from_str(&file_paths_json).unwrap_or_default();
+            if !file_paths.iter().any(|path| Self::same_file_path(path, &file_path)) {
+                continue;
+            }
+
+            let mut symbols: Vec<String> = serde_json::from_str(&symbols_json).unwrap_or_default();
+            let mut changed = false;
+            for symbol in symbols.iter_mut() {
+                if symbol == old_name {
+                    *symbol = new_name.to_string();
+                    changed = true;
+                }
+            }
+            if changed {
+                conn.execute(
+                    "UPDATE change_memories SET symbols = ?1 WHERE id = ?2",
+                    params![serde_json::to_string(&symbols).unwrap_or_default(), id],
+                )?;
+                updated += 1;
+            }
+        }
+
+        Ok(updated)
+    }
+
+    /// 记忆中记录的文件路径（相对或绝对）是否指向相对路径 `rel_path`
+    fn same_file_path(recorded: &str, rel_path: &str) -> bool {
+        let recorded = recorded.replace('\\', "/");
+        let recorded = recorded.trim_start_matches("./");
+        recorded == rel_path || recorded.ends_with(&format!("/{}", rel_path))
+    }
+
     /// 从数据库行构建 CodeChangeMemory
     fn row_to_change_memory(&self, row: &rusqlite::Row) -> CodeChangeMemory {
         let file_paths: Vec<String> = serde_json::from_str(row.get::<_, String>(2).unwrap_or_default().as_str())
@@ -637,6 +1051,7 @@ impl SqliteStorage {
             last_recalled: last_recalled_ts.and_then(|ts| DateTime::from_timestamp(ts, 0)),
             recall_count: row.get(10).unwrap_or(0),
             relevance_score: row.get(11).unwrap_or(1.0),
+            commit_hash: row.get::<_, Option<String>>(12).ok().flatten(),
         }
     }
 
@@ -646,7 +1061,7 @@ impl SqliteStorage {
 
     /// 保存记忆的向量
     pub fn save_embedding(&self, memory_id: &str, embedding: &[f32], model: &str) -> Result<()> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let conn = self.writer()?;
         
         let blob = Self::vector_to_bytes(embedding);
         
@@ -660,7 +1075,7 @@ impl SqliteStorage {
 
     /// 获取记忆的向量
     pub fn get_embedding(&self, memory_id: &str) -> Result<Option<(Vec<f32>, String)>> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let conn = self.reader()?;
         
         let result: Option<(Vec<u8>, String)> = conn.query_row(
             "SELECT summary_embedding, embedding_model FROM change_memories WHERE id = ?1 AND summary_embedding IS NOT NULL",
@@ -678,7 +1093,7 @@ impl SqliteStorage {
 
     /// 获取所有带向量的记忆 ID
     pub fn get_memories_with_embedding(&self) -> Result<Vec<(String, Vec<f32>)>> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let conn = self.reader()?;
         
         let mut stmt = conn.prepare(
             "SELECT id, summary_embedding FROM change_memories 
@@ -704,11 +1119,11 @@ impl SqliteStorage {
 
     /// 获取没有向量的记忆
     pub fn get_memories_without_embedding(&self) -> Result<Vec<CodeChangeMemory>> {
-        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let conn = self.reader()?;
         
         let mut stmt = conn.prepare(
             "SELECT id, change_type, file_paths, symbols, summary, diff_snippet, user_intent, keywords,
-                    created_at, last_recalled, recall_count, relevance_score
+                    created_at, last_recalled, recall_count, relevance_score, commit_hash
              FROM change_memories 
              WHERE project_path = ?1 AND summary_embedding IS NULL AND is_deleted = 0"
         )?;
@@ -744,3 +1159,250 @@ impl SqliteStorage {
             .collect()
     }
 }
+
+// ============================================================================
+// NSP 计划存储方法
+// ============================================================================
+
+impl SqliteStorage {
+    /// 将 PlanRunStatus 转换为字符串
+    fn plan_status_to_str(status: PlanRunStatus) -> &'static str {
+        match status {
+            PlanRunStatus::Running => "running",
+            PlanRunStatus::Paused => "paused",
+            PlanRunStatus::Failed => "failed",
+            PlanRunStatus::Completed => "completed",
+        }
+    }
+
+    /// 从字符串解析 PlanRunStatus
+    fn str_to_plan_status(s: &str) -> PlanRunStatus {
+        match s {
+            "paused" => PlanRunStatus::Paused,
+            "failed" => PlanRunStatus::Failed,
+            "completed" => PlanRunStatus::Completed,
+            _ => PlanRunStatus::Running,
+        }
+    }
+
+    /// 保存计划（已存在时更新状态与进度，保留创建时间）
+    pub fn save_plan(&self, plan: &StoredPlan) -> Result<()> {
+        let conn = self.writer()?;
+
+        conn.execute(
+            "INSERT INTO nsp_plans (
+                id, intent_summary, status, plan, edits, checkpoints, outcomes,
+                project_path, created_at, updated_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+            ON CONFLICT(id) DO UPDATE SET
+                intent_summary = excluded.intent_summary,
+                status = excluded.status,
+                plan = excluded.plan,
+                edits = excluded.edits,
+                checkpoints = excluded.checkpoints,
+                outcomes = excluded.outcomes,
+                updated_at = excluded.updated_at",
+            params![
+                plan.id,
+                plan.intent_summary,
+                Self::plan_status_to_str(plan.status),
+                plan.plan.to_string(),
+                plan.edits.to_string(),
+                serde_json::to_string(&plan.checkpoints).unwrap_or_default(),
+                plan.outcomes.to_string(),
+                self.project_path,
+                plan.created_at.timestamp(),
+                plan.updated_at.timestamp(),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// 根据 ID 获取计划
+    pub fn get_plan(&self, id: &str) -> Result<Option<StoredPlan>> {
+        let conn = self.reader()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT id, intent_summary, status, plan, edits, checkpoints, outcomes, created_at, updated_at
+             FROM nsp_plans
+             WHERE id = ?1 AND project_path = ?2",
+        )?;
+
+        let mut rows = stmt.query_map(params![id, self.project_path], |row| Ok(Self::row_to_plan(row)))?;
+        let plan = rows.next().transpose()?;
+        Ok(plan)
+    }
+
+    /// 获取最近更新的计划
+    pub fn list_plans(&self, limit: usize) -> Result<Vec<StoredPlan>> {
+        let conn = self.reader()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT id, intent_summary, status, plan, edits, checkpoints, outcomes, created_at, updated_at
+             FROM nsp_plans
+             WHERE project_path = ?1
+             ORDER BY updated_at DESC
+             LIMIT ?2",
+        )?;
+
+        let rows = stmt.query_map(params![self.project_path, limit as i64], |row| Ok(Self::row_to_plan(row)))?;
+        Ok(rows.filter_map(|r| r.ok()).collect())
+    }
+
+    /// 将数据库行转换为 StoredPlan
+    fn row_to_plan(row: &rusqlite::Row) -> StoredPlan {
+        let json = |idx: usize| {
+            serde_json::from_str(row.get::<_, String>(idx).unwrap_or_default().as_str())
+                .unwrap_or(serde_json::Value::Null)
+        };
+        let timestamp = |idx: usize| {
+            DateTime::from_timestamp(row.get::<_, i64>(idx).unwrap_or(0), 0).unwrap_or_else(Utc::now)
+        };
+
+        StoredPlan {
+            id: row.get(0).unwrap_or_default(),
+            intent_summary: row.get(1).unwrap_or_default(),
+            status: Self::str_to_plan_status(&row.get::<_, String>(2).unwrap_or_default()),
+            plan: json(3),
+            edits: json(4),
+            checkpoints: serde_json::from_str(row.get::<_, String>(5).unwrap_or_default().as_str())
+                .unwrap_or_default(),
+            outcomes: json(6),
+            created_at: timestamp(7),
+            updated_at: timestamp(8),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROJECT: &str = "/tmp/neurospec-sqlite-test-project";
+
+    fn temp_memory_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("neurospec-sqlite-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn short_terms_fall_back_to_substring_search() {
+        let dir = temp_memory_dir("short-terms");
+        let storage = SqliteStorage::new(&dir, PROJECT).unwrap();
+        storage.add(&MemoryEntry::new("数据库连接使用连接池".to_string(), MemoryCategory::Rule)).unwrap();
+        storage.add(&MemoryEntry::new("界面统一使用深色主题".to_string(), MemoryCategory::Preference)).unwrap();
+
+        let results = storage.search("数据", 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].content.contains("数据库"));
+
+        // 足够长的查询词仍走全文索引
+        let results = storage.search("深色主题", 10).unwrap();
+        assert_eq!(results.len(), 1);
+
+        assert!(storage.search("  ", 10).unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn symbol_rename_only_touches_memories_of_the_renamed_file() {
+        let dir = temp_memory_dir("rename");
+        let storage = SqliteStorage::new(&dir, PROJECT).unwrap();
+        let in_a = storage.add_change_memory(&CodeChangeMemory::new(
+            ChangeType::Refactor,
+            vec!["src/a.rs".to_string()],
+            vec!["foo".to_string()],
+            "tidy foo in a".to_string(),
+            "cleanup".to_string(),
+        )).unwrap();
+        let in_b = storage.add_change_memory(&CodeChangeMemory::new(
+            ChangeType::Refactor,
+            vec!["src/b.rs".to_string()],
+            vec!["foo".to_string()],
+            "tidy foo in b".to_string(),
+            "cleanup".to_string(),
+        )).unwrap();
+
+        assert_eq!(storage.rename_change_memory_symbol("foo", "bar", "src/a.rs").unwrap(), 1);
+
+        let memories = storage.get_all_change_memories().unwrap();
+        let symbols_of = |id: &str| memories.iter().find(|m| m.id == id).unwrap().symbols.clone();
+        assert_eq!(symbols_of(&in_a), vec!["bar".to_string()]);
+        assert_eq!(symbols_of(&in_b), vec!["foo".to_string()]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn migration_from_v3_indexes_existing_rows() {
+        let dir = temp_memory_dir("migrate-v3");
+        {
+            // v3 数据库：没有全文索引表，change_memories 没有 commit_hash 列
+            let conn = Connection::open(dir.join(DB_FILENAME)).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE memories (
+                    id TEXT PRIMARY KEY,
+                    content TEXT NOT NULL,
+                    category TEXT NOT NULL,
+                    project_path TEXT NOT NULL,
+                    created_at INTEGER NOT NULL,
+                    updated_at INTEGER NOT NULL,
+                    is_deleted INTEGER DEFAULT 0
+                );
+                CREATE TABLE change_memories (
+                    id TEXT PRIMARY KEY,
+                    change_type TEXT NOT NULL,
+                    file_paths TEXT NOT NULL,
+                    symbols TEXT NOT NULL,
+                    summary TEXT NOT NULL,
+                    diff_snippet TEXT,
+                    user_intent TEXT NOT NULL,
+                    keywords TEXT NOT NULL,
+                    project_path TEXT NOT NULL,
+                    created_at INTEGER NOT NULL,
+                    last_recalled INTEGER,
+                    recall_count INTEGER DEFAULT 0,
+                    relevance_score REAL DEFAULT 1.0,
+                    is_deleted INTEGER DEFAULT 0,
+                    summary_embedding BLOB,
+                    embedding_model TEXT
+                );
+                CREATE TABLE schema_version (version INTEGER PRIMARY KEY);
+                INSERT INTO schema_version (version) VALUES (3);",
+            )
+            .unwrap();
+
+            let project = crate::utils::rekey(PROJECT);
+            conn.execute(
+                "INSERT INTO memories (id, content, category, project_path, created_at, updated_at)
+                 VALUES ('mem_old', '迁移前写入的缓存失效规则', 'rule', ?1, 0, 0)",
+                [&project],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO change_memories (id, change_type, file_paths, symbols, summary, user_intent,
+                                              keywords, project_path, created_at)
+                 VALUES ('chg_old', 'bug-fix', '[]', '[]', 'fix cache invalidation', 'stale entries',
+                         '[\"cache\"]', ?1, 0)",
+                [&project],
+            )
+            .unwrap();
+        }
+
+        let storage = SqliteStorage::new(&dir, PROJECT).unwrap();
+
+        let results = storage.search("缓存失效", 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "mem_old");
+
+        let changes = storage.search_change_memories(&["invalidation".to_string()], 10).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].id, "chg_old");
+        assert!(changes[0].commit_hash.is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
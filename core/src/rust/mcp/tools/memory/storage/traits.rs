@@ -31,6 +31,11 @@ pub trait MemoryStorage: Send + Sync {
     /// 获取所有记忆
     fn get_all(&self) -> Result<Vec<MemoryEntry>>;
     
+    /// 全文搜索记忆（匹配任一查询词，按相关度排序）
+    ///
+    /// 查询词过短无法检索时返回空列表
+    fn search(&self, query: &str, limit: usize) -> Result<Vec<MemoryEntry>>;
+
     /// 按分类获取记忆
     fn get_by_category(&self, category: MemoryCategory) -> Result<Vec<MemoryEntry>>;
     
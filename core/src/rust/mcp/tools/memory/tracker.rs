@@ -3,10 +3,35 @@
 //! 自动记录 AI 的代码修改，并在相似场景时召回相关记忆
 
 use anyhow::Result;
-use std::path::PathBuf;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::path::Path;
 
+use super::integration::{GitCommit, GitIntegration};
 use super::storage::SqliteStorage;
-use super::types::{CodeChangeMemory, ChangeType};
+use super::types::{CodeChangeMemory, ChangeType, StoredPlan};
+use crate::mcp::tools::unified_store::SymbolRename;
+
+/// 修改记录后多久之内的提交可以关联到该记忆
+const COMMIT_LINK_WINDOW_DAYS: i64 = 7;
+
+/// 文件历史中的一项：一次提交及其修改记忆，或尚未关联提交的修改记忆
+#[derive(Debug, Serialize)]
+pub struct FileHistoryEntry {
+    /// 提交时间（没有提交时为记忆的记录时间）
+    pub at: DateTime<Utc>,
+    pub commit: Option<GitCommit>,
+    pub memories: Vec<CodeChangeMemory>,
+}
+
+/// 提交与其背后的修改意图
+#[derive(Debug, Serialize)]
+pub struct ChangeIntent {
+    /// 提交概要（找不到提交时为 None）
+    pub commit: Option<GitCommit>,
+    /// 关联到该提交的修改记忆
+    pub memories: Vec<CodeChangeMemory>,
+}
 
 /// 代码修改追踪器
 /// 
@@ -16,7 +41,6 @@ use super::types::{CodeChangeMemory, ChangeType};
 /// - 管理记忆衰减
 pub struct ChangeTracker {
     storage: SqliteStorage,
-    #[allow(dead_code)]
     project_path: String,
 }
 
@@ -24,7 +48,7 @@ impl ChangeTracker {
     /// 创建新的追踪器
     pub fn new(project_path: &str) -> Result<Self> {
         let normalized = Self::normalize_path(project_path);
-        let memory_dir = PathBuf::from(&normalized).join(".neurospec-memory");
+        let memory_dir = crate::config::project_memory_dir(Path::new(&normalized));
         
         std::fs::create_dir_all(&memory_dir)?;
         
@@ -36,14 +60,9 @@ impl ChangeTracker {
         })
     }
 
-    /// 规范化路径
+    /// 规范化路径（与记忆管理器使用相同的项目键）
     fn normalize_path(path: &str) -> String {
-        let p = PathBuf::from(path);
-        if let Ok(canonical) = p.canonicalize() {
-            canonical.to_string_lossy().to_string()
-        } else {
-            path.to_string()
-        }
+        crate::utils::path_key(Path::new(path))
     }
 
     // ========================================================================
@@ -171,6 +190,147 @@ impl ChangeTracker {
         self.storage.get_all_change_memories()
     }
 
+    /// 获取还没有向量的修改记忆（由后台嵌入任务补算）
+    pub fn changes_without_embedding(&self) -> Result<Vec<CodeChangeMemory>> {
+        self.storage.get_memories_without_embedding()
+    }
+
+    /// 获取所有已计算的记忆向量（记忆 ID, 向量）
+    pub fn change_embeddings(&self) -> Result<Vec<(String, Vec<f32>)>> {
+        self.storage.get_memories_with_embedding()
+    }
+
+    /// 保存记忆的向量
+    pub fn save_change_embedding(&self, memory_id: &str, embedding: &[f32], model: &str) -> Result<()> {
+        self.storage.save_embedding(memory_id, embedding, model)
+    }
+
+    /// 记忆用于嵌入的文本
+    pub fn embedding_text(memory: &CodeChangeMemory) -> String {
+        format!("{} {}", memory.summary, memory.user_intent)
+    }
+
+    // ========================================================================
+    // Git 提交关联
+    // ========================================================================
+
+    /// 为尚未关联提交的修改记忆查找对应提交
+    ///
+    /// 取记录之后 7 天内第一个改动了记忆中任一文件的提交。返回新关联的记忆数量
+    pub fn link_commits(&self) -> Result<usize> {
+        let unlinked = self.storage.get_unlinked_change_memories()?;
+        let Some(earliest) = unlinked.first().map(|m| m.created_at) else {
+            return Ok(0);
+        };
+
+        let commits = GitIntegration::new(&self.project_path).commits_since(earliest)?;
+        let window = Duration::days(COMMIT_LINK_WINDOW_DAYS);
+
+        let mut linked = 0;
+        for memory in &unlinked {
+            let commit = commits.iter().find(|commit| {
+                commit.committed_at >= memory.created_at
+                    && commit.committed_at - memory.created_at <= window
+                    && self.touches_any(commit, &memory.file_paths)
+            });
+            if let Some(commit) = commit {
+                if self.storage.set_change_commit(&memory.id, &commit.hash)? {
+                    linked += 1;
+                }
+            }
+        }
+
+        Ok(linked)
+    }
+
+    /// 关联指定记忆与提交（提交后立即知道哈希时使用）
+    pub fn link_commit(&self, memory_id: &str, commit_hash: &str) -> Result<bool> {
+        self.storage.set_change_commit(memory_id, commit_hash)
+    }
+
+    /// 查询提交背后的修改意图（支持短哈希、分支名等任意 revision）
+    pub fn intent_for_commit(&self, rev: &str) -> Result<ChangeIntent> {
+        self.link_commits()?;
+
+        let commit = GitIntegration::new(&self.project_path).commit_info(rev)?;
+        let memories = match &commit {
+            Some(commit) => self.storage.get_change_memories_by_commit(&commit.hash)?,
+            None => Vec::new(),
+        };
+
+        Ok(ChangeIntent { commit, memories })
+    }
+
+    /// 查询最后修改文件某一行的提交及其修改意图
+    pub fn intent_for_line(&self, file_path: &str, line: u32) -> Result<ChangeIntent> {
+        match GitIntegration::new(&self.project_path).blame_line(file_path, line)? {
+            Some(hash) => self.intent_for_commit(&hash),
+            None => Ok(ChangeIntent { commit: None, memories: Vec::new() }),
+        }
+    }
+
+    /// 获取关联到指定提交（完整哈希）的修改记忆
+    pub fn changes_for_commit(&self, commit_hash: &str) -> Result<Vec<CodeChangeMemory>> {
+        self.storage.get_change_memories_by_commit(commit_hash)
+    }
+
+    /// 获取涉及某个文件的修改记忆
+    pub fn changes_for_file(&self, file_path: &str, limit: usize) -> Result<Vec<CodeChangeMemory>> {
+        self.storage.search_by_file_path(file_path, limit)
+    }
+
+    /// 文件的修改时间线（按时间升序）
+    ///
+    /// 合并 `git log` 中改动过该文件的最近 `limit` 个提交与涉及该文件的修改记忆：
+    /// 关联到提交的记忆归入对应提交，其余记忆单独成项。结果保留最近的 `limit` 项
+    pub fn file_history(&self, file_path: &str, limit: usize) -> Result<Vec<FileHistoryEntry>> {
+        if let Err(e) = self.link_commits() {
+            crate::log_debug!("Failed to link change memories to commits: {}", e);
+        }
+
+        let file_path = self.project_relative(file_path);
+        let commits = GitIntegration::new(&self.project_path)
+            .file_commits(&file_path, limit)
+            .unwrap_or_else(|e| {
+                crate::log_debug!("Git history unavailable for {}: {}", file_path, e);
+                Vec::new()
+            });
+        let memories: Vec<CodeChangeMemory> = self.storage
+            .search_by_file_path(&file_path, usize::MAX)?
+            .into_iter()
+            .filter(|m| m.file_paths.iter().any(|path| self.same_file(path, &file_path)))
+            .collect();
+
+        Ok(merge_file_history(commits, memories, limit))
+    }
+
+    /// 提交是否改动了任一文件
+    fn touches_any(&self, commit: &GitCommit, file_paths: &[String]) -> bool {
+        file_paths
+            .iter()
+            .any(|path| commit.files.iter().any(|file| self.same_file(path, file)))
+    }
+
+    /// 两个路径是否指向项目中的同一文件（记忆中的路径可能是绝对路径或相对路径）
+    fn same_file(&self, a: &str, b: &str) -> bool {
+        self.project_relative(a) == self.project_relative(b)
+    }
+
+    /// 转换为相对项目根目录、以 `/` 分隔的路径；项目外的绝对路径保持不变
+    fn project_relative(&self, path: &str) -> String {
+        let path = path.replace('\\', "/");
+        if !Path::new(&path).is_absolute() {
+            return path.trim_start_matches("./").to_string();
+        }
+
+        let key = crate::utils::path_key(Path::new(&path));
+        let root = format!("{}/", self.project_path.trim_end_matches('/'));
+        match key.strip_prefix(&root) {
+            Some(relative) => relative.to_string(),
+            None => key,
+        }
+    }
+
     // ========================================================================
     // 记忆管理
     // ========================================================================
@@ -189,12 +349,42 @@ impl ChangeTracker {
         self.storage.cleanup_low_score_memories(threshold)
     }
 
+    /// 同步符号重命名，使涉及原文件的历史修改记忆继续关联到新名称
+    ///
+    /// 返回被更新的记忆数量
+    pub fn apply_symbol_renames(&self, renames: &[SymbolRename]) -> Result<usize> {
+        let mut updated = 0;
+        for rename in renames.iter().filter(|r| r.old_name != r.new_name) {
+            updated += self.storage.rename_change_memory_symbol(&rename.old_name, &rename.new_name, &rename.old_path)?;
+        }
+        Ok(updated)
+    }
+
     /// 执行完整的维护（衰减 + 清理）
     pub fn maintenance(&self) -> Result<(usize, usize)> {
         let decayed = self.apply_decay()?;
         let cleaned = self.cleanup(0.1)?; // 清理分数低于 0.1 的记忆
         Ok((decayed, cleaned))
     }
+
+    // ========================================================================
+    // NSP 计划执行记录
+    // ========================================================================
+
+    /// 保存计划及执行进度
+    pub fn save_plan(&self, plan: &StoredPlan) -> Result<()> {
+        self.storage.save_plan(plan)
+    }
+
+    /// 根据 ID 获取计划
+    pub fn get_plan(&self, id: &str) -> Result<Option<StoredPlan>> {
+        self.storage.get_plan(id)
+    }
+
+    /// 获取最近更新的计划
+    pub fn list_plans(&self, limit: usize) -> Result<Vec<StoredPlan>> {
+        self.storage.list_plans(limit)
+    }
 }
 
 // ============================================================================
@@ -220,6 +410,85 @@ pub fn infer_change_type(summary: &str, user_intent: &str) -> ChangeType {
     }
 }
 
+/// 合并提交与修改记忆为按时间升序的时间线，只保留最近的 `limit` 项
+///
+/// `commits` 为改动过文件的提交（按时间倒序）；关联到其中某个提交的记忆归入该提交，
+/// 未关联或关联的提交不在 `commits` 中的记忆按记录时间单独成项
+fn merge_file_history(
+    commits: Vec<GitCommit>,
+    mut memories: Vec<CodeChangeMemory>,
+    limit: usize,
+) -> Vec<FileHistoryEntry> {
+    let mut entries: Vec<FileHistoryEntry> = commits
+        .into_iter()
+        .map(|commit| {
+            let (linked, rest): (Vec<_>, Vec<_>) = std::mem::take(&mut memories)
+                .into_iter()
+                .partition(|m| m.commit_hash.as_deref() == Some(commit.hash.as_str()));
+            memories = rest;
+            FileHistoryEntry {
+                at: commit.committed_at,
+                commit: Some(commit),
+                memories: linked,
+            }
+        })
+        .collect();
+    entries.extend(memories.into_iter().map(|memory| FileHistoryEntry {
+        at: memory.created_at,
+        commit: None,
+        memories: vec![memory],
+    }));
+
+    entries.sort_by_key(|entry| entry.at);
+    if entries.len() > limit {
+        entries.drain(..entries.len() - limit);
+    }
+    entries
+}
+
+/// 格式化文件时间线为可读文本（供 AI 了解代码为何是现在的样子）
+pub fn format_file_history(file_path: &str, entries: &[FileHistoryEntry]) -> String {
+    let mut output = format!("## History of {} ({} entries, oldest first)\n\n", file_path, entries.len());
+    if entries.is_empty() {
+        output.push_str("No commits or change memories touch this file.\n");
+        return output;
+    }
+
+    for entry in entries {
+        let date = entry.at.format("%Y-%m-%d %H:%M");
+        match &entry.commit {
+            Some(commit) => output.push_str(&format!(
+                "### {} · {} · {}\n",
+                date,
+                &commit.hash[..commit.hash.len().min(7)],
+                commit.summary
+            )),
+            // 关联的提交早于列出的提交范围时只显示记录的哈希
+            None => match entry.memories.first().and_then(|m| m.commit_hash.as_deref()) {
+                Some(hash) => output.push_str(&format!(
+                    "### {} · {} · (older commit)\n",
+                    date,
+                    &hash[..hash.len().min(7)]
+                )),
+                None => output.push_str(&format!("### {} · (not committed yet)\n", date)),
+            },
+        }
+
+        for memory in &entry.memories {
+            output.push_str(&format!("- [{}] {}\n", memory.change_type, memory.summary));
+            if !memory.user_intent.is_empty() && memory.user_intent != memory.summary {
+                output.push_str(&format!("  Intent: {}\n", memory.user_intent));
+            }
+            if !memory.symbols.is_empty() {
+                output.push_str(&format!("  Symbols: {}\n", memory.symbols.join(", ")));
+            }
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
 /// 格式化修改记忆为可读文本
 pub fn format_change_memory(memory: &CodeChangeMemory) -> String {
     let mut output = String::new();
@@ -245,3 +514,75 @@ pub fn format_change_memory(memory: &CodeChangeMemory) -> String {
     
     output
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(hash: &str, at: i64) -> GitCommit {
+        GitCommit {
+            hash: hash.to_string(),
+            summary: format!("commit {}", hash),
+            committed_at: DateTime::from_timestamp(at, 0).unwrap(),
+            files: vec!["src/lib.rs".to_string()],
+        }
+    }
+
+    fn memory(summary: &str, at: i64, commit_hash: Option<&str>) -> CodeChangeMemory {
+        let mut memory = CodeChangeMemory::new(
+            ChangeType::Feature,
+            vec!["src/lib.rs".to_string()],
+            Vec::new(),
+            summary.to_string(),
+            String::new(),
+        );
+        memory.created_at = DateTime::from_timestamp(at, 0).unwrap();
+        memory.commit_hash = commit_hash.map(str::to_string);
+        memory
+    }
+
+    #[test]
+    fn test_merge_groups_memories_under_commits() {
+        let entries = merge_file_history(
+            vec![commit("bbb", 300), commit("aaa", 100)],
+            vec![
+                memory("linked to aaa", 90, Some("aaa")),
+                memory("pending", 400, None),
+                memory("older commit", 50, Some("zzz")),
+            ],
+            10,
+        );
+
+        let order: Vec<(Option<&str>, usize)> = entries
+            .iter()
+            .map(|e| (e.commit.as_ref().map(|c| c.hash.as_str()), e.memories.len()))
+            .collect();
+        assert_eq!(order, vec![(None, 1), (Some("aaa"), 1), (Some("bbb"), 0), (None, 1)]);
+        assert_eq!(entries[0].memories[0].summary, "older commit");
+        assert_eq!(entries[1].memories[0].summary, "linked to aaa");
+        assert_eq!(entries[3].memories[0].summary, "pending");
+    }
+
+    #[test]
+    fn test_merge_keeps_most_recent_entries() {
+        let entries = merge_file_history(
+            vec![commit("ccc", 300), commit("bbb", 200), commit("aaa", 100)],
+            vec![memory("pending", 400, None)],
+            2,
+        );
+
+        let hashes: Vec<Option<&str>> = entries
+            .iter()
+            .map(|e| e.commit.as_ref().map(|c| c.hash.as_str()))
+            .collect();
+        assert_eq!(hashes, vec![Some("ccc"), None]);
+    }
+
+    #[test]
+    fn test_format_shows_hash_of_older_commit() {
+        let entries = merge_file_history(Vec::new(), vec![memory("old", 50, Some("0123456789abcdef"))], 10);
+        let text = format_file_history("src/lib.rs", &entries);
+        assert!(text.contains("0123456 · (older commit)"));
+        assert!(!text.contains("not committed yet"));
+    }
+}
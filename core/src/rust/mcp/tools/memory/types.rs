@@ -151,6 +151,9 @@ pub struct CodeChangeMemory {
     pub recall_count: u32,
     /// 相关性分数 (0.0 - 1.0)，会随时间衰减
     pub relevance_score: f32,
+    /// 修改最终所在的 Git 提交（提交后关联，尚未提交时为 None）
+    #[serde(default)]
+    pub commit_hash: Option<String>,
 }
 
 impl CodeChangeMemory {
@@ -190,6 +193,7 @@ impl CodeChangeMemory {
             last_recalled: None,
             recall_count: 0,
             relevance_score: 1.0, // 新记忆初始分数为 1.0
+            commit_hash: None,
         }
     }
 
@@ -262,3 +266,43 @@ pub struct ChangeMemoryListResult {
     pub page: usize,
     pub page_size: usize,
 }
+
+// ============================================================================
+// NSP 计划执行记录
+// ============================================================================
+
+/// 计划执行状态
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanRunStatus {
+    /// 正在执行；进程崩溃后保持此状态，可继续执行
+    Running,
+    /// 在检查点中止，可继续执行
+    Paused,
+    /// 某一步执行失败，修复后可继续执行
+    Failed,
+    /// 所有步骤都已执行
+    Completed,
+}
+
+/// 持久化的 NSP 计划及执行进度
+///
+/// 计划、步骤内容和步骤结果以 JSON 保存，记忆模块不依赖 NSP 类型定义
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredPlan {
+    /// 计划 ID
+    pub id: String,
+    /// 计划意图摘要
+    pub intent_summary: String,
+    pub status: PlanRunStatus,
+    /// NSP 计划
+    pub plan: serde_json::Value,
+    /// 各步骤要写入的内容
+    pub edits: serde_json::Value,
+    /// 检查点步骤
+    pub checkpoints: Vec<i32>,
+    /// 各步骤的执行结果
+    pub outcomes: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
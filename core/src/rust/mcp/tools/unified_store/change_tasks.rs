@@ -0,0 +1,103 @@
+//! 文件变化处理任务
+//!
+//! 每个被监听的项目对应一个异步任务：平时挂起等待该项目的变化通知，
+//! 被唤醒后按批次的剩余防抖时间等待就绪，再消费该项目的事件，只更新受影响文件的符号和搜索索引文档。
+//! 任务统一登记在注册表中，取消监听、暂停监听或退出时停止，不会遗留后台线程
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use tokio::sync::{oneshot, watch};
+
+use super::global::{next_file_change_delay, process_project_file_changes};
+
+lazy_static! {
+    /// 项目根目录 -> 停止信号（发送或丢弃都会让任务退出）
+    static ref CHANGE_TASKS: Mutex<HashMap<PathBuf, oneshot::Sender<()>>> = Mutex::new(HashMap::new());
+}
+
+/// 为项目启动变化处理任务（已有运行中的任务时不重复启动）
+pub(super) fn start(project_root: &Path, changes: watch::Receiver<u64>) {
+    let Ok(mut tasks) = CHANGE_TASKS.lock() else {
+        return;
+    };
+    if tasks.get(project_root).is_some_and(|stop| !stop.is_closed()) {
+        return;
+    }
+
+    let (stop_tx, stop_rx) = oneshot::channel();
+    tasks.insert(project_root.to_path_buf(), stop_tx);
+    tauri::async_runtime::spawn(run_change_task(project_root.to_path_buf(), changes, stop_rx));
+}
+
+/// 停止项目的变化处理任务
+pub(super) fn stop(project_root: &Path) {
+    if let Ok(mut tasks) = CHANGE_TASKS.lock() {
+        if let Some(stop) = tasks.remove(project_root) {
+            let _ = stop.send(());
+        }
+    }
+}
+
+/// 停止所有变化处理任务
+pub(super) fn stop_all() {
+    if let Ok(mut tasks) = CHANGE_TASKS.lock() {
+        for (_, stop) in tasks.drain() {
+            let _ = stop.send(());
+        }
+    }
+}
+
+async fn run_change_task(
+    project_root: PathBuf,
+    mut changes: watch::Receiver<u64>,
+    mut stop: oneshot::Receiver<()>,
+) {
+    crate::log_important!(info, "Starting file change task for: {}", project_root.display());
+
+    'wait: loop {
+        tokio::select! {
+            changed = changes.changed() => {
+                // 项目已取消监听或监听器已停止
+                if changed.is_err() {
+                    break;
+                }
+            }
+            _ = &mut stop => break,
+        }
+
+        // 防抖：等到批次就绪，期间到达的事件合并进同一批次并顺延就绪时间；
+        // 处理期间到达的事件留在下一批次，直到没有待处理的变化再回去等待通知
+        while let Some(delay) = next_file_change_delay(&project_root) {
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = &mut stop => break 'wait,
+            }
+            if crate::utils::is_shutting_down() {
+                break 'wait;
+            }
+
+            let root = project_root.clone();
+            if tokio::task::spawn_blocking(move || apply_changes(&root)).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    crate::log_important!(info, "Stopped file change task for: {}", project_root.display());
+}
+
+/// 消费项目已就绪的变化批次并增量更新索引
+fn apply_changes(project_root: &Path) {
+    match process_project_file_changes(project_root) {
+        Ok(count) if count > 0 => {
+            crate::log_important!(info, "Applied {} file changes to index", count);
+        }
+        Ok(_) => {}
+        Err(e) => {
+            crate::log_important!(error, "Error processing file changes: {}", e);
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! 索引一致性检查
+//!
+//! 交叉校验 UnifiedSymbolStore、Tantivy 文档与 ProjectIndexState，
+//! 发现漂移（文件数不符、已删除文件的残留文档等）时可选择修复
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use super::global::{
+    get_global_search_config, get_index_state, invalidate_project_searcher, is_project_indexing,
+    transition_index_state,
+    with_global_store, with_live_indexer, EmbeddingStatus, IndexState, ProjectIndexState,
+};
+use super::store::detect_language;
+use crate::mcp::tools::acemcp::local_engine::{IndexConsistency, IndexingLock};
+
+/// 一致性检查报告
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsistencyReport {
+    pub project_root: String,
+    /// Tantivy 索引与元数据的对比结果
+    pub tantivy: IndexConsistency,
+    /// 符号存储中磁盘上已不存在的文件数
+    pub store_stale_files: usize,
+    /// 已进入 Tantivy 索引、但符号存储中缺失的代码文件数
+    pub store_missing_files: usize,
+    /// 索引状态记录的文件数
+    pub state_file_count: Option<usize>,
+    /// 索引状态与元数据文件数是否不一致
+    pub state_drift: bool,
+    /// 是否执行了修复
+    pub repaired: bool,
+}
+
+impl ConsistencyReport {
+    /// 是否完全一致
+    pub fn is_consistent(&self) -> bool {
+        self.tantivy.is_consistent()
+            && self.store_stale_files == 0
+            && self.store_missing_files == 0
+            && !self.state_drift
+    }
+}
+
+/// 校验项目索引的一致性
+///
+/// `repair` 为 true 时：
+/// - Tantivy 漂移 → 重建索引
+/// - 符号存储漂移 → 增量重新索引
+/// - 状态文件数漂移 → 按实际文件数修正状态
+pub fn verify_project(project_root: &Path, repair: bool) -> Result<ConsistencyReport> {
+    if is_project_indexing(project_root) {
+        return Err(anyhow::anyhow!("Indexing in progress, try again later"));
+    }
+
+    let config = get_global_search_config()?.for_project(project_root);
+    let Some(_lock) = IndexingLock::try_acquire(&config.index_path)? else {
+        return Err(anyhow::anyhow!("Indexing in progress, try again later"));
+    };
+    let (mut report, state, file_count) = with_live_indexer(&config, project_root, |indexer| {
+        let tantivy = indexer.verify_project(project_root)?;
+
+        // 符号存储 vs 磁盘 / Tantivy 元数据
+        let store_files = with_global_store(|store| store.project_file_paths(project_root))?;
+        let store_set: HashSet<&str> = store_files.iter().map(|s| s.as_str()).collect();
+        let store_stale_files = store_files
+            .iter()
+            .filter(|rel| !project_root.join(rel).exists())
+            .count();
+        let indexed_files = indexer.project_file_paths(project_root);
+        let store_missing_files = indexed_files
+            .iter()
+            .filter(|rel| {
+                let path = project_root.join(rel);
+                path.exists() && detect_language(&path).is_some() && !store_set.contains(rel.as_str())
+            })
+            .count();
+
+        let state = get_index_state(project_root);
+        let state_file_count = state.as_ref().map(|s| s.get_file_count());
+        let state_drift = match &state {
+            Some(s) if s.is_ready() => s.get_file_count() != tantivy.metadata_files,
+            _ => false,
+        };
+
+        let report = ConsistencyReport {
+            project_root: crate::utils::path_key(project_root),
+            tantivy,
+            store_stale_files,
+            store_missing_files,
+            state_file_count,
+            state_drift,
+            repaired: false,
+        };
+
+        if !repair || report.is_consistent() {
+            return Ok((report, state, None));
+        }
+
+        crate::log_important!(
+            warn,
+            "Index drift detected for {}: {:?}, repairing",
+            report.project_root,
+            report
+        );
+
+        let mut file_count = report.tantivy.metadata_files;
+        if !report.tantivy.is_consistent() {
+            file_count = indexer.rebuild_index(project_root)?;
+            invalidate_project_searcher(project_root);
+        }
+        Ok((report, state, Some(file_count)))
+    })?;
+
+    // 无需修复
+    let Some(file_count) = file_count else {
+        return Ok(report);
+    };
+
+    if report.store_stale_files > 0 || report.store_missing_files > 0 {
+        with_global_store(|store| store.index_project(project_root))?;
+    }
+
+    if report.state_drift || !report.tantivy.is_consistent() {
+        let embedding_status = match state.map(|s| s.state) {
+            Some(IndexState::Ready { embedding_status, .. }) => embedding_status,
+            _ => EmbeddingStatus::NotAvailable,
+        };
+        let new_state = if file_count > 0 {
+            IndexState::Ready {
+                file_count,
+                indexed_at: ProjectIndexState::current_timestamp(),
+                embedding_status,
+            }
+        } else {
+            IndexState::NotIndexed
+        };
+        transition_index_state(project_root, new_state);
+    }
+
+    report.repaired = true;
+    Ok(report)
+}
@@ -0,0 +1,79 @@
+//! 存储/索引更新事件
+//!
+//! 通过广播通道分发，图谱、向量嵌入任务和 UI 订阅后即可响应更新，
+//! 无需各自轮询 `process_file_changes`
+
+use lazy_static::lazy_static;
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use super::global::IndexState;
+
+/// 广播通道容量（订阅者落后超过该数量时会收到 Lagged 错误）
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// 存储/索引更新事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StoreEvent {
+    /// 文件的符号条目已更新
+    FileIndexed {
+        project_root: String,
+        /// 相对路径
+        path: String,
+        symbol_count: usize,
+    },
+    /// 文件已从存储中移除
+    FileRemoved {
+        project_root: String,
+        /// 相对路径
+        path: String,
+    },
+    /// 项目索引构建完成，可供搜索
+    IndexReady {
+        project_root: String,
+        file_count: usize,
+    },
+    /// 项目索引状态变化（开始索引、完成、损坏、过期）
+    IndexStateChanged {
+        project_root: String,
+        state: IndexState,
+    },
+    /// 项目索引进度（每处理完一批文件发布一次）
+    IndexProgress {
+        project_root: String,
+        /// 本轮已处理的文件数
+        processed: usize,
+        /// 本轮需要重新索引的文件数
+        total: usize,
+        progress: f32,
+    },
+}
+
+impl StoreEvent {
+    /// 是否为项目级的索引事件（不含逐文件的符号更新）
+    pub fn is_index_event(&self) -> bool {
+        matches!(
+            self,
+            Self::IndexReady { .. } | Self::IndexStateChanged { .. } | Self::IndexProgress { .. }
+        )
+    }
+}
+
+lazy_static! {
+    static ref STORE_EVENTS: broadcast::Sender<StoreEvent> = broadcast::channel(EVENT_CHANNEL_CAPACITY).0;
+}
+
+/// 订阅存储/索引更新事件
+///
+/// 只会收到订阅之后发生的事件
+pub fn subscribe_store_events() -> broadcast::Receiver<StoreEvent> {
+    STORE_EVENTS.subscribe()
+}
+
+/// 发布事件（没有订阅者时直接丢弃）
+pub(crate) fn emit_store_event(event: StoreEvent) {
+    if STORE_EVENTS.receiver_count() > 0 {
+        let _ = STORE_EVENTS.send(event);
+    }
+}
@@ -0,0 +1,228 @@
+//! 符号名模糊匹配
+//!
+//! 对项目中的去重符号名建立三元组（trigram）倒排索引：先用共享三元组数量筛出候选，
+//! 再计算编辑距离（含相邻字符交换）确认。用于拼写纠错建议和模糊符号搜索。
+
+use std::collections::HashMap;
+
+/// 单个查询最多计算编辑距离的候选数
+const MAX_CANDIDATES: usize = 512;
+
+/// 编辑距离上限（更大的距离几乎总是噪音，且候选筛选失效）
+pub const MAX_EDIT_DISTANCE: usize = 3;
+
+/// 按查询长度给出默认的最大编辑距离
+///
+/// 短词只容忍一处错误，避免 `get` 匹配到 `set` / `let` 之类的无关名称
+pub fn default_edit_distance(term: &str) -> usize {
+    match term.chars().count() {
+        0..=4 => 1,
+        5..=8 => 2,
+        _ => 3,
+    }
+}
+
+/// 模糊匹配到的符号名
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyName {
+    /// 原始符号名（保留大小写）
+    pub name: String,
+    /// 与查询的编辑距离（大小写不敏感）
+    pub distance: usize,
+    /// 项目中同名符号的数量
+    pub occurrences: usize,
+}
+
+#[derive(Debug)]
+struct NameEntry {
+    name: String,
+    lower: Vec<char>,
+    occurrences: usize,
+}
+
+/// 符号名三元组索引
+#[derive(Debug, Default)]
+pub struct SymbolNameIndex {
+    names: Vec<NameEntry>,
+    /// 三元组 -> 名称下标（升序、去重）
+    trigrams: HashMap<[char; 3], Vec<u32>>,
+}
+
+impl SymbolNameIndex {
+    /// 从符号名构建索引（名称可重复，重复次数记为出现次数）
+    pub fn build<'a>(names: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut positions: HashMap<&'a str, usize> = HashMap::new();
+        let mut index = Self::default();
+
+        for name in names {
+            if name.is_empty() {
+                continue;
+            }
+            if let Some(&i) = positions.get(name) {
+                index.names[i].occurrences += 1;
+                continue;
+            }
+            positions.insert(name, index.names.len());
+            index.names.push(NameEntry {
+                name: name.to_string(),
+                lower: name.to_lowercase().chars().collect(),
+                occurrences: 1,
+            });
+        }
+
+        for (i, entry) in index.names.iter().enumerate() {
+            let mut grams = trigrams_of(&entry.lower);
+            grams.sort_unstable();
+            grams.dedup();
+            for gram in grams {
+                index.trigrams.entry(gram).or_default().push(i as u32);
+            }
+        }
+
+        index
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// 查找编辑距离不超过 `max_distance` 的符号名
+    ///
+    /// 结果按（距离, 出现次数降序, 名称）排序，最多 `limit` 个
+    pub fn search(&self, query: &str, max_distance: usize, limit: usize) -> Vec<FuzzyName> {
+        let query: Vec<char> = query.trim().to_lowercase().chars().collect();
+        if query.is_empty() || limit == 0 {
+            return Vec::new();
+        }
+        let max_distance = max_distance.min(MAX_EDIT_DISTANCE);
+
+        let mut matches: Vec<FuzzyName> = self
+            .candidates(&query, max_distance)
+            .into_iter()
+            .filter_map(|i| {
+                let entry = &self.names[i];
+                let distance = edit_distance(&query, &entry.lower, max_distance)?;
+                Some(FuzzyName {
+                    name: entry.name.clone(),
+                    distance,
+                    occurrences: entry.occurrences,
+                })
+            })
+            .collect();
+
+        matches.sort_by(|a, b| {
+            a.distance
+                .cmp(&b.distance)
+                .then(b.occurrences.cmp(&a.occurrences))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        matches.truncate(limit);
+        matches
+    }
+
+    /// 拼写纠错建议：与 `term` 相近的符号名
+    ///
+    /// `term` 本身就是符号名（大小写不敏感）时不给建议
+    pub fn did_you_mean(&self, term: &str, limit: usize) -> Vec<FuzzyName> {
+        let mut suggestions = self.search(term, default_edit_distance(term), limit + 1);
+        if suggestions.first().is_some_and(|s| s.distance == 0) {
+            return Vec::new();
+        }
+        suggestions.truncate(limit);
+        suggestions
+    }
+
+    /// 用三元组筛选候选名称下标
+    ///
+    /// 每处编辑最多破坏 3 个三元组，据此得到候选必须共享的最少三元组数；
+    /// 查询太短无法保证共享三元组时退化为按长度筛选
+    fn candidates(&self, query: &[char], max_distance: usize) -> Vec<usize> {
+        let grams = {
+            let mut grams = trigrams_of(query);
+            grams.sort_unstable();
+            grams.dedup();
+            grams
+        };
+        let min_shared = grams.len().saturating_sub(3 * max_distance);
+
+        let within_length = |entry: &NameEntry| entry.lower.len().abs_diff(query.len()) <= max_distance;
+
+        if min_shared == 0 {
+            return self
+                .names
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| within_length(entry))
+                .map(|(i, _)| i)
+                .collect();
+        }
+
+        let mut shared: HashMap<u32, usize> = HashMap::new();
+        for gram in &grams {
+            if let Some(postings) = self.trigrams.get(gram) {
+                for &i in postings {
+                    *shared.entry(i).or_insert(0) += 1;
+                }
+            }
+        }
+
+        let mut candidates: Vec<(usize, usize)> = shared
+            .into_iter()
+            .filter(|&(i, count)| count >= min_shared && within_length(&self.names[i as usize]))
+            .map(|(i, count)| (i as usize, count))
+            .collect();
+        // 共享三元组多的优先，候选过多时截断
+        candidates.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        candidates.truncate(MAX_CANDIDATES);
+        candidates.into_iter().map(|(i, _)| i).collect()
+    }
+}
+
+/// 名称的三元组（两端补空格，使首尾字符也参与匹配）
+fn trigrams_of(chars: &[char]) -> Vec<[char; 3]> {
+    let padded: Vec<char> = std::iter::once(' ')
+        .chain(chars.iter().copied())
+        .chain(std::iter::once(' '))
+        .collect();
+    padded.windows(3).map(|w| [w[0], w[1], w[2]]).collect()
+}
+
+/// 受限编辑距离（插入、删除、替换、相邻交换各计 1）
+///
+/// 超过 `max_distance` 时提前返回 None
+pub fn edit_distance(a: &[char], b: &[char], max_distance: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max_distance {
+        return None;
+    }
+
+    let width = b.len() + 1;
+    let mut before_prev: Vec<usize> = vec![0; width];
+    let mut prev: Vec<usize> = (0..width).collect();
+    let mut current: Vec<usize> = vec![0; width];
+
+    for i in 1..=a.len() {
+        current[0] = i;
+        let mut row_min = current[0];
+        for j in 1..width {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut value = (prev[j] + 1).min(current[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                value = value.min(before_prev[j - 2] + 1);
+            }
+            current[j] = value;
+            row_min = row_min.min(value);
+        }
+        if row_min > max_distance {
+            return None;
+        }
+        std::mem::swap(&mut before_prev, &mut prev);
+        std::mem::swap(&mut prev, &mut current);
+    }
+
+    let distance = prev[b.len()];
+    (distance <= max_distance).then_some(distance)
+}
@@ -3,17 +3,22 @@
 //! 提供 UnifiedSymbolStore 和 LocalSearcher 的全局访问点
 
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 
 use anyhow::Result;
 use lazy_static::lazy_static;
 
-use super::store::UnifiedSymbolStore;
+use super::change_tasks;
+use super::events::{emit_store_event, StoreEvent};
+use super::store::{StoreLimits, UnifiedSymbolStore};
 use super::watcher::{FileWatcher, FileChangeEvent};
-use crate::mcp::tools::acemcp::local_engine::{LocalSearcher, LocalEngineConfig};
+use crate::mcp::tools::acemcp::local_engine::{
+    index_schema_outdated, snippet_cache, FileLimits, IndexingCancelled, IndexingLock, LocalIndexer, LocalSearcher, LocalEngineConfig, INDEX_SCHEMA_VERSION,
+};
 
 use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use serde::{Deserialize, Serialize};
 
 /// 索引过期时间（秒）- 默认 24 小时
@@ -22,6 +27,15 @@ const INDEX_EXPIRY_SECS: u64 = 86400;
 /// 索引状态文件名
 const INDEX_STATE_FILE: &str = "index_state.json";
 
+/// 项目多久未被查询后自动取消文件监听（秒）- 默认 6 小时
+const WATCH_IDLE_TIMEOUT_SECS: u64 = 6 * 3600;
+
+/// Searcher 池最多缓存的项目数
+const SEARCHER_POOL_SIZE: usize = 8;
+
+/// 退出时仅在索引文档数不超过该值时合并段
+const SHUTDOWN_MERGE_MAX_DOCS: u64 = 50_000;
+
 /// 统一索引状态机
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "status", rename_all = "snake_case")]
@@ -64,6 +78,11 @@ impl Default for IndexState {
 pub enum EmbeddingStatus {
     #[default]
     NotAvailable,
+    /// 后台正在补算向量（代码块和记忆）
+    InProgress {
+        processed: usize,
+        total: usize,
+    },
     Available {
         files_with_vectors: usize,
     },
@@ -72,6 +91,25 @@ pub enum EmbeddingStatus {
     },
 }
 
+/// 最近一次索引 / 嵌入错误的来源
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexErrorSource {
+    /// 索引损坏或构建失败
+    Index,
+    /// 向量计算失败
+    Embedding,
+}
+
+/// 最近一次错误（状态恢复后仍保留，供索引面板展示）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IndexErrorRecord {
+    pub source: IndexErrorSource,
+    pub message: String,
+    /// 发生时间 (Unix timestamp)
+    pub at: u64,
+}
+
 /// 项目索引状态（可持久化）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectIndexState {
@@ -88,6 +126,9 @@ pub struct ProjectIndexState {
     pub last_indexed_ts: Option<u64>,
     /// 索引文件数
     pub file_count: usize,
+    /// 最近一次错误
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<IndexErrorRecord>,
 }
 
 impl Default for ProjectIndexState {
@@ -98,6 +139,7 @@ impl Default for ProjectIndexState {
             indexing: false,
             last_indexed_ts: None,
             file_count: 0,
+            last_error: None,
         }
     }
 }
@@ -149,22 +191,59 @@ impl ProjectIndexState {
     }
 }
 
+/// 池中的 Searcher
+struct PooledSearcher {
+    searcher: Arc<LocalSearcher>,
+    last_used: SystemTime,
+}
+
+/// 获取到的 Searcher 及其来源
+pub struct SearcherLease {
+    pub searcher: Arc<LocalSearcher>,
+    /// 是否复用池中已打开的 reader
+    pub pooled: bool,
+    /// 获取耗时（毫秒）
+    pub acquire_ms: f64,
+}
+
+/// 新打开索引的次数与累计耗时（微秒），用于估算复用 reader 节省的时间
+static SEARCHER_OPENS: AtomicU64 = AtomicU64::new(0);
+static SEARCHER_OPEN_MICROS: AtomicU64 = AtomicU64::new(0);
+
+/// 登记中的索引写入器；退出时取出并提交，之后不再可用
+type LiveIndexer = Arc<std::sync::Mutex<Option<LocalIndexer>>>;
+
 /// 持久化的索引状态存储
 #[derive(Debug, Default, Serialize, Deserialize)]
 struct PersistedIndexState {
     projects: HashMap<String, ProjectIndexState>,
 }
 
+/// 符号图谱代数：文件变化或全量重建后递增，基于图谱的缓存以此判断是否过期
+static GRAPH_GENERATION: AtomicU64 = AtomicU64::new(0);
+
 lazy_static! {
     /// 全局统一符号存储
     static ref GLOBAL_STORE: Arc<RwLock<Option<UnifiedSymbolStore>>> = Arc::new(RwLock::new(None));
     
     /// 全局文件监听器（使用 Mutex 因为 Receiver 不是 Sync）
     static ref GLOBAL_WATCHER: Arc<std::sync::Mutex<Option<FileWatcher>>> = Arc::new(std::sync::Mutex::new(None));
+
+    /// 暂停监听时记录的项目（Some 表示已暂停，恢复时重新监听这些项目）
+    static ref PAUSED_WATCH_PATHS: std::sync::Mutex<Option<Vec<PathBuf>>> = std::sync::Mutex::new(None);
     
     /// 全局搜索引擎配置
     static ref GLOBAL_SEARCH_CONFIG: Arc<RwLock<Option<LocalEngineConfig>>> = Arc::new(RwLock::new(None));
+
+    /// 按项目缓存的 Searcher（规范化项目键 -> Searcher），避免每次请求重新打开索引
+    static ref SEARCHER_POOL: RwLock<HashMap<String, PooledSearcher>> = RwLock::new(HashMap::new());
     
+    /// 正在使用的项目索引写入器（规范化项目键 -> 写入器），退出时通过这些句柄提交
+    static ref LIVE_INDEXERS: std::sync::Mutex<HashMap<String, LiveIndexer>> = std::sync::Mutex::new(HashMap::new());
+
+    /// 各项目后台索引的取消标记（规范化项目键 -> 标记），每次开始索引时重置
+    static ref INDEXING_CANCEL_TOKENS: std::sync::Mutex<HashMap<String, IndexingCancelToken>> = std::sync::Mutex::new(HashMap::new());
+
     /// 项目索引状态（项目路径 -> 状态）
     static ref PROJECT_INDEX_STATE: Arc<RwLock<HashMap<String, ProjectIndexState>>> = {
         // 尝试从文件加载持久化状态
@@ -175,9 +254,12 @@ lazy_static! {
 
 /// 初始化全局存储
 ///
-/// 应在应用启动时调用一次
+/// 应在应用启动时调用一次，内存上限读取自 `search_config`
 pub fn init_global_store(cache_dir: &std::path::Path) -> Result<()> {
-    let store = UnifiedSymbolStore::new(cache_dir)?;
+    let limits = crate::config::load_standalone_config()
+        .map(|c| StoreLimits::from_config(&c.search_config))
+        .unwrap_or_default();
+    let store = UnifiedSymbolStore::with_limits(cache_dir, limits)?;
     
     let mut global = GLOBAL_STORE.write().map_err(|e| anyhow::anyhow!("{}", e))?;
     *global = Some(store);
@@ -213,6 +295,16 @@ pub fn init_global_watcher() -> Result<()> {
     Ok(())
 }
 
+/// 停止全局文件监听器与所有变化处理任务（退出时调用）
+pub fn stop_global_watcher() {
+    change_tasks::stop_all();
+    if let Ok(mut global) = GLOBAL_WATCHER.lock() {
+        if global.take().is_some() {
+            crate::log_important!(info, "File watcher stopped");
+        }
+    }
+}
+
 /// 开始监听项目目录
 pub fn watch_project(project_root: &std::path::Path) -> Result<()> {
     let mut guard = GLOBAL_WATCHER.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
@@ -226,11 +318,128 @@ pub fn watch_project(project_root: &std::path::Path) -> Result<()> {
     Ok(())
 }
 
+/// 停止监听项目目录
+pub fn unwatch_project(project_root: &std::path::Path) -> Result<()> {
+    // 与 start_watching_project 使用同一规范化根目录，变化处理任务才能对应上
+    let project_root = &crate::utils::canonical_path(project_root);
+    let mut guard = GLOBAL_WATCHER.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+    
+    if let Some(ref mut watcher) = *guard {
+        watcher.unwatch(project_root)?;
+        change_tasks::stop(project_root);
+        crate::log_important!(info, "Stopped watching project: {}", project_root.display());
+    }
+    
+    Ok(())
+}
+
+/// 检查项目是否正在被监听
+pub fn is_project_watched(project_root: &std::path::Path) -> bool {
+    GLOBAL_WATCHER.lock()
+        .map(|guard| guard.as_ref().map(|w| w.is_watching(project_root)).unwrap_or(false))
+        .unwrap_or(false)
+}
+
+/// 暂停或恢复文件监听，返回受影响的项目数
+///
+/// 暂停期间新索引的项目不会开始监听，恢复时统一重新监听
+pub fn set_file_watching_paused(paused: bool) -> Result<usize> {
+    let mut paused_paths = PAUSED_WATCH_PATHS.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+    if paused == paused_paths.is_some() {
+        return Ok(0);
+    }
+
+    if paused {
+        let mut guard = GLOBAL_WATCHER.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let paths = match guard.as_mut() {
+            Some(watcher) => {
+                let paths = watcher.watched_paths();
+                for path in &paths {
+                    if let Err(e) = watcher.unwatch(path) {
+                        crate::log_important!(warn, "Failed to unwatch {}: {}", path.display(), e);
+                    }
+                    change_tasks::stop(path);
+                }
+                paths
+            }
+            None => Vec::new(),
+        };
+        let count = paths.len();
+        *paused_paths = Some(paths);
+        crate::log_important!(info, "File watching paused ({} projects)", count);
+        Ok(count)
+    } else {
+        let paths = paused_paths.take().unwrap_or_default();
+        drop(paused_paths);
+        let mut count = 0;
+        for path in &paths {
+            match start_watching_project(path) {
+                Ok(()) => count += 1,
+                Err(e) => crate::log_important!(warn, "Failed to resume watching {}: {}", path.display(), e),
+            }
+        }
+        crate::log_important!(info, "File watching resumed ({} projects)", count);
+        Ok(count)
+    }
+}
+
+/// 文件监听是否已暂停
+pub fn is_file_watching_paused() -> bool {
+    PAUSED_WATCH_PATHS.lock().map(|p| p.is_some()).unwrap_or(false)
+}
+
+/// 记录项目被查询
+///
+/// 刷新空闲计时与项目注册表中的最近使用时间；若项目已索引但因空闲被取消监听，则恢复监听
+pub fn touch_project(project_root: &std::path::Path) {
+    crate::config::record_project_used(project_root);
+
+    let watching = match GLOBAL_WATCHER.lock() {
+        Ok(guard) => match guard.as_ref() {
+            Some(watcher) => {
+                watcher.touch(project_root);
+                watcher.is_watching(project_root)
+            }
+            None => return,
+        },
+        Err(_) => return,
+    };
+
+    if !watching && is_project_indexed(project_root) {
+        if let Err(e) = start_watching_project(project_root) {
+            crate::log_important!(warn, "Failed to resume file watching: {}", e);
+        }
+    }
+}
+
+/// 取消监听长时间未被查询的项目，返回取消数量
+pub fn unwatch_idle_projects() -> usize {
+    let idle = match GLOBAL_WATCHER.lock() {
+        Ok(guard) => match guard.as_ref() {
+            Some(watcher) => watcher.idle_projects(Duration::from_secs(WATCH_IDLE_TIMEOUT_SECS)),
+            None => return 0,
+        },
+        Err(_) => return 0,
+    };
+
+    let mut count = 0;
+    for project_root in idle {
+        if unwatch_project(&project_root).is_ok() {
+            count += 1;
+        }
+    }
+    count
+}
+
 /// 处理文件变化事件
 ///
-/// 应定期调用以处理待处理的文件变化
+/// 应定期调用以处理待处理的文件变化（所有项目）
 pub fn process_file_changes() -> Result<usize> {
-    let events = {
+    unwatch_idle_projects();
+
+    // 事件按用户打开的项目根目录分组（而不是 .git 所在目录），
+    // 以子目录打开的项目使用自己的设置和索引
+    let by_project = {
         let guard = GLOBAL_WATCHER.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
         if let Some(ref watcher) = *guard {
             watcher.poll_events()
@@ -239,52 +448,119 @@ pub fn process_file_changes() -> Result<usize> {
         }
     };
 
-    if events.is_empty() {
-        return Ok(0);
+    let mut processed = 0;
+    for (project_root, events) in by_project {
+        for event in &events {
+            snippet_cache::invalidate_file(event.path());
+        }
+        processed += apply_file_events(&project_root, events);
     }
+    Ok(processed)
+}
 
-    let mut store_guard = GLOBAL_STORE.write().map_err(|e| anyhow::anyhow!("{}", e))?;
-    let store = store_guard.as_mut().ok_or_else(|| anyhow::anyhow!("Global store not initialized"))?;
+/// 处理单个项目的文件变化事件
+///
+/// 只消费该项目队列中的事件，不会影响其他项目
+pub fn process_project_file_changes(project_root: &std::path::Path) -> Result<usize> {
+    unwatch_idle_projects();
 
-    let mut processed = 0;
+    let events = {
+        let guard = GLOBAL_WATCHER.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        if let Some(ref watcher) = *guard {
+            watcher.poll_project_events(project_root)
+        } else {
+            return Ok(0);
+        }
+    };
+
+    for event in &events {
+        snippet_cache::invalidate_file(event.path());
+    }
+    Ok(apply_file_events(project_root, events))
+}
+
+/// 距离项目的文件变化批次就绪还需等待多久，没有待处理的变化时返回 None
+pub(super) fn next_file_change_delay(project_root: &std::path::Path) -> Option<Duration> {
+    GLOBAL_WATCHER.lock()
+        .ok()
+        .and_then(|guard| guard.as_ref().and_then(|w| w.next_batch_delay(project_root)))
+}
+
+/// 当前符号图谱代数
+pub fn graph_generation() -> u64 {
+    GRAPH_GENERATION.load(Ordering::Acquire)
+}
+
+/// 使基于图谱的缓存失效
+fn bump_graph_generation() {
+    GRAPH_GENERATION.fetch_add(1, Ordering::AcqRel);
+}
+
+/// 将项目的变化事件直接应用到符号存储和搜索索引，返回处理的文件数
+///
+/// 只重新解析变化的文件、只替换其 Tantivy 文档，不遍历整个项目
+fn apply_file_events(project_root: &std::path::Path, events: Vec<FileChangeEvent>) -> usize {
+    if events.is_empty() {
+        return 0;
+    }
+    bump_graph_generation();
+
+    let mut changed = Vec::new();
+    let mut removed = Vec::new();
     for event in events {
         match event {
-            FileChangeEvent::Created(path) | FileChangeEvent::Modified(path) => {
-                // 找到项目根目录并使文件失效
-                if let Some(project_root) = find_project_root(&path) {
-                    let rel_path = path.strip_prefix(&project_root)
-                        .map(|p| p.to_string_lossy().replace('\\', "/"))
-                        .unwrap_or_default();
-                    let _ = store.invalidate_file(&project_root, &rel_path);
-                    processed += 1;
-                }
-            }
-            FileChangeEvent::Removed(path) => {
-                if let Some(project_root) = find_project_root(&path) {
-                    let rel_path = path.strip_prefix(&project_root)
-                        .map(|p| p.to_string_lossy().replace('\\', "/"))
-                        .unwrap_or_default();
-                    let _ = store.invalidate_file(&project_root, &rel_path);
-                    processed += 1;
-                }
-            }
+            FileChangeEvent::Created(path) | FileChangeEvent::Modified(path) => changed.push(path),
+            FileChangeEvent::Removed(path) => removed.push(path),
         }
     }
 
-    Ok(processed)
-}
+    let relative = |paths: &[PathBuf]| -> Vec<String> {
+        paths
+            .iter()
+            .filter_map(|path| path.strip_prefix(project_root).ok())
+            .map(|rel| rel.to_string_lossy().replace('\\', "/"))
+            .collect()
+    };
+    if let Err(e) = with_global_store(|store| store.update_files(project_root, &relative(&changed), &relative(&removed))) {
+        crate::log_important!(warn, "Failed to update symbols for {}: {}", project_root.display(), e);
+    }
 
-/// 查找文件所属的项目根目录（通过 .git 目录）
-fn find_project_root(path: &std::path::Path) -> Option<PathBuf> {
-    let mut current = path.parent()?;
-    
-    loop {
-        if current.join(".git").exists() {
-            return Some(current.to_path_buf());
+    // 与后台索引共用进程间索引锁；锁被占用时跳过，进行中的索引会扫描到这些变化
+    if let Ok(config) = get_global_search_config() {
+        let config = config.for_project(project_root);
+        let _lock = match IndexingLock::try_acquire(&config.index_path) {
+            Ok(Some(lock)) => lock,
+            Ok(None) => {
+                crate::log_debug!(
+                    "Index lock held by another indexing task, skipping search index update for {}",
+                    project_root.display()
+                );
+                return changed.len() + removed.len();
+            }
+            Err(e) => {
+                crate::log_important!(
+                    warn,
+                    "Cannot acquire index lock for {} ({}), skipping search index update",
+                    project_root.display(),
+                    e
+                );
+                return changed.len() + removed.len();
+            }
+        };
+        match with_live_indexer(&config, project_root, |indexer| indexer.update_files(project_root, &changed, &removed)) {
+            Ok(_) => {
+                reload_project_searcher(project_root);
+                crate::log_debug!(
+                    "Applied {} file changes to search index for {}",
+                    changed.len() + removed.len(),
+                    project_root.display()
+                );
+            }
+            Err(e) => crate::log_important!(warn, "Failed to update search index for {}: {}", project_root.display(), e),
         }
-        
-        current = current.parent()?;
     }
+
+    changed.len() + removed.len()
 }
 
 // ============================================================================
@@ -293,32 +569,197 @@ fn find_project_root(path: &std::path::Path) -> Option<PathBuf> {
 
 /// 初始化全局搜索配置
 /// 
-/// 应在应用启动时与 init_global_store 一起调用
+/// 应在应用启动时与 init_global_store 一起调用。`index_dir` 是索引根目录，
+/// 每个项目的索引位于其下独立的子目录（见 [`LocalEngineConfig::for_project`]）
 pub fn init_global_search_config(index_dir: &std::path::Path) -> Result<()> {
+    remove_legacy_shared_index(index_dir);
+
+    let search_config = crate::config::load_standalone_config()
+        .map(|c| c.search_config)
+        .unwrap_or_else(|_| crate::config::default_search_config());
+
     let config = LocalEngineConfig {
         index_path: index_dir.to_path_buf(),
-        max_results: 10,
+        max_results: search_config.max_results,
         snippet_context: 3,
+        file_limits: FileLimits::from_config(&search_config),
+        exclude_patterns: search_config.exclude_patterns,
+        ranking: Default::default(),
     };
     
     let mut global = GLOBAL_SEARCH_CONFIG.write().map_err(|e| anyhow::anyhow!("{}", e))?;
     *global = Some(config);
+    drop(global);
+
+    // 配置变化后旧 Searcher 可能指向旧索引目录
+    invalidate_searcher_pool();
     
     Ok(())
 }
 
+/// 删除旧版本所有项目共享的索引文件（直接位于索引根目录下）
+///
+/// 项目子目录保持不变；旧索引中的项目在下次访问时按新布局全量索引
+fn remove_legacy_shared_index(index_dir: &std::path::Path) {
+    if !index_dir.join("meta.json").exists() {
+        return;
+    }
+
+    let removed = std::fs::read_dir(index_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().map(|t| t.is_file()).unwrap_or(false))
+                .filter(|e| std::fs::remove_file(e.path()).is_ok())
+                .count()
+        })
+        .unwrap_or(0);
+    crate::log_important!(info, "Removed legacy shared search index ({} files)", removed);
+}
+
+/// 应用新的搜索配置（配置热重载）
+///
+/// 结果数量立即生效；排除模式和文件限制从下一次索引开始生效，
+/// 已索引的文件需重建索引后才会移除
+pub fn apply_search_config(search_config: &crate::config::SearchConfig) -> Result<()> {
+    let patterns_changed = {
+        let mut global = GLOBAL_SEARCH_CONFIG.write().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let Some(config) = global.as_mut() else {
+            return Ok(());
+        };
+        let patterns_changed = config.exclude_patterns != search_config.exclude_patterns;
+        config.max_results = search_config.max_results;
+        config.exclude_patterns = search_config.exclude_patterns.clone();
+        config.file_limits = FileLimits::from_config(search_config);
+        patterns_changed
+    };
+
+    // 池中的 Searcher 持有旧配置的副本
+    invalidate_searcher_pool();
+
+    if patterns_changed {
+        crate::log_important!(
+            info,
+            "Search exclude patterns updated ({}), effective from next indexing",
+            search_config.exclude_patterns.len()
+        );
+    }
+    Ok(())
+}
+
 /// 获取全局搜索配置
 pub fn get_global_search_config() -> Result<LocalEngineConfig> {
     let guard = GLOBAL_SEARCH_CONFIG.read().map_err(|e| anyhow::anyhow!("{}", e))?;
     guard.clone().ok_or_else(|| anyhow::anyhow!("Global search config not initialized"))
 }
 
-/// 为项目创建 Searcher
+/// 获取项目的 Searcher
 /// 
-/// 使用全局配置创建针对特定项目的 Searcher 实例
-pub fn create_searcher_for_project(project_root: &std::path::Path) -> Result<LocalSearcher> {
-    let config = get_global_search_config()?;
-    LocalSearcher::new(config, project_root.to_path_buf())
+/// 每个项目打开自己的索引目录，并按项目复用池中的实例（reader 在索引提交后自动重新加载），
+/// 池满时淘汰最久未使用的项目
+pub fn create_searcher_for_project(project_root: &std::path::Path) -> Result<Arc<LocalSearcher>> {
+    acquire_searcher(project_root).map(|lease| lease.searcher)
+}
+
+/// 获取项目的 Searcher，并记录是否命中池及耗时（供 SearchTrace 使用）
+pub fn acquire_searcher(project_root: &std::path::Path) -> Result<SearcherLease> {
+    touch_project(project_root);
+    let key = normalize_project_key(project_root);
+    let start = std::time::Instant::now();
+    let lease = |searcher, pooled| SearcherLease {
+        searcher,
+        pooled,
+        acquire_ms: start.elapsed().as_secs_f64() * 1000.0,
+    };
+
+    {
+        let mut pool = SEARCHER_POOL.write().map_err(|e| anyhow::anyhow!("{}", e))?;
+        if let Some(entry) = pool.get_mut(&key) {
+            entry.last_used = SystemTime::now();
+            return Ok(lease(entry.searcher.clone(), true));
+        }
+    }
+
+    // 在锁外打开索引，避免阻塞其他项目的查询
+    let config = get_global_search_config()?.for_project(project_root);
+    // 旧版本 schema 的索引分词方式不同：标记为损坏，由下次搜索触发后台重建
+    if index_schema_outdated(&config.index_path) {
+        mark_index_corrupted(
+            project_root,
+            &format!("Index schema older than version {}, rebuild required", INDEX_SCHEMA_VERSION),
+        );
+        anyhow::bail!("Index schema outdated, rebuild required");
+    }
+    let searcher = Arc::new(LocalSearcher::new(config, project_root.to_path_buf())?);
+    SEARCHER_OPENS.fetch_add(1, Ordering::Relaxed);
+    SEARCHER_OPEN_MICROS.fetch_add(start.elapsed().as_micros() as u64, Ordering::Relaxed);
+
+    let mut pool = SEARCHER_POOL.write().map_err(|e| anyhow::anyhow!("{}", e))?;
+    if let Some(entry) = pool.get_mut(&key) {
+        // 并发请求已先一步创建
+        entry.last_used = SystemTime::now();
+        return Ok(lease(entry.searcher.clone(), true));
+    }
+
+    if pool.len() >= SEARCHER_POOL_SIZE {
+        let oldest = pool
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(key, _)| key.clone());
+        if let Some(oldest) = oldest {
+            pool.remove(&oldest);
+        }
+    }
+
+    pool.insert(key, PooledSearcher {
+        searcher: searcher.clone(),
+        last_used: SystemTime::now(),
+    });
+
+    Ok(lease(searcher, false))
+}
+
+/// 新打开索引的平均耗时（毫秒），尚未打开过时为 None
+pub fn average_searcher_open_ms() -> Option<f64> {
+    let opens = SEARCHER_OPENS.load(Ordering::Relaxed);
+    (opens > 0).then(|| SEARCHER_OPEN_MICROS.load(Ordering::Relaxed) as f64 / opens as f64 / 1000.0)
+}
+
+/// 清空 Searcher 池
+///
+/// 配置变化后调用，后续请求会重新打开索引
+pub fn invalidate_searcher_pool() {
+    if let Ok(mut pool) = SEARCHER_POOL.write() {
+        pool.clear();
+    }
+}
+
+/// 丢弃项目池中的 Searcher
+///
+/// 项目的索引目录被替换（全量重建、修复）后调用，其他项目的 reader 不受影响
+pub fn invalidate_project_searcher(project_root: &std::path::Path) {
+    let key = normalize_project_key(project_root);
+    if let Ok(mut pool) = SEARCHER_POOL.write() {
+        pool.remove(&key);
+    }
+}
+
+/// 让池中项目的 Searcher 立即看到最新提交
+///
+/// reader 的自动重新加载依赖 meta.json 的文件通知，有延迟；增量更新提交后调用，
+/// 保证紧随其后的查询能搜到刚改动的文件。池中没有该项目时无操作
+pub fn reload_project_searcher(project_root: &std::path::Path) {
+    let key = normalize_project_key(project_root);
+    let searcher = match SEARCHER_POOL.read() {
+        Ok(pool) => pool.get(&key).map(|entry| entry.searcher.clone()),
+        Err(_) => return,
+    };
+    if let Some(searcher) = searcher {
+        if let Err(e) = searcher.reload() {
+            crate::log_debug!("Failed to reload searcher for {}: {}", project_root.display(), e);
+            invalidate_project_searcher(project_root);
+        }
+    }
 }
 
 /// 检查全局搜索系统是否已初始化
@@ -328,13 +769,75 @@ pub fn is_search_initialized() -> bool {
         .unwrap_or(false)
 }
 
+/// 打开项目的索引写入器，并在 `f` 执行期间登记，使退出时 [`flush_on_shutdown`] 能通过同一句柄提交
+///
+/// 所有写入 Tantivy 索引的入口都应先持有项目的 [`IndexingLock`]，再通过该函数获取写入器
+pub fn with_live_indexer<R>(
+    config: &LocalEngineConfig,
+    project_root: &std::path::Path,
+    f: impl FnOnce(&mut LocalIndexer) -> Result<R>,
+) -> Result<R> {
+    let key = normalize_project_key(project_root);
+    let handle: LiveIndexer = Arc::new(std::sync::Mutex::new(Some(LocalIndexer::new(config)?)));
+    if let Ok(mut live) = LIVE_INDEXERS.lock() {
+        live.insert(key.clone(), handle.clone());
+    }
+
+    let result = match handle.lock() {
+        Ok(mut guard) => match guard.as_mut() {
+            Some(indexer) => f(indexer),
+            None => Err(anyhow::anyhow!("Index writer closed on shutdown")),
+        },
+        Err(e) => Err(anyhow::anyhow!("{}", e)),
+    };
+
+    if let Ok(mut live) = LIVE_INDEXERS.lock() {
+        if live.get(&key).is_some_and(|h| Arc::ptr_eq(h, &handle)) {
+            live.remove(&key);
+        }
+    }
+    result
+}
+
+/// 退出前刷新缓存
+///
+/// - 符号存储：WAL 检查点
+/// - Tantivy：通过登记的句柄提交正在使用的写入器，段较少时顺带合并
+///
+/// 调用前应已设置退出标志：进行中的索引任务在当前批次结束后放弃未提交的写入并返回，
+/// 这里等待其释放写入器后再提交。没有登记写入器的项目没有待提交的内容，无需处理
+pub fn flush_on_shutdown() {
+    if let Err(e) = with_global_store(|store| store.flush()) {
+        crate::log_important!(warn, "Failed to flush symbol store on shutdown: {}", e);
+    }
+
+    let live: Vec<(String, LiveIndexer)> = match LIVE_INDEXERS.lock() {
+        Ok(live) => live.iter().map(|(key, handle)| (key.clone(), handle.clone())).collect(),
+        Err(_) => return,
+    };
+    if live.is_empty() {
+        return;
+    }
+
+    invalidate_searcher_pool();
+    for (key, handle) in live {
+        let Some(indexer) = handle.lock().ok().and_then(|mut guard| guard.take()) else {
+            continue;
+        };
+        match indexer.compact(SHUTDOWN_MERGE_MAX_DOCS) {
+            Ok(()) => crate::log_important!(info, "Search index flushed on shutdown: {}", key),
+            Err(e) => crate::log_important!(warn, "Failed to flush search index for {} on shutdown: {}", key, e),
+        }
+    }
+}
+
 // ============================================================================
 // 索引状态管理
 // ============================================================================
 
-/// 规范化项目路径键（统一使用正斜杠，用于跨平台兼容）
+/// 规范化项目路径键（见 [`crate::utils::path_key`]）
 fn normalize_project_key(project_root: &std::path::Path) -> String {
-    project_root.to_string_lossy().replace('\\', "/")
+    crate::utils::path_key(project_root)
 }
 
 /// 索引健康状态
@@ -463,9 +966,14 @@ pub fn transition_index_state(project_root: &std::path::Path, new_state: IndexSt
                 project_state.file_count = *file_count;
                 project_state.last_indexed_ts = Some(*indexed_at);
             }
-            IndexState::Corrupted { .. } => {
+            IndexState::Corrupted { reason } => {
                 project_state.ready = false;
                 project_state.indexing = false;
+                project_state.last_error = Some(IndexErrorRecord {
+                    source: IndexErrorSource::Index,
+                    message: reason.clone(),
+                    at: ProjectIndexState::current_timestamp(),
+                });
             }
             IndexState::Stale { file_count, last_indexed_at } => {
                 project_state.ready = false;
@@ -482,6 +990,39 @@ pub fn transition_index_state(project_root: &std::path::Path, new_state: IndexSt
         
         crate::log_important!(info, "Index state transition: {} -> {:?}", key, new_state);
     }
+
+    emit_store_event(StoreEvent::IndexStateChanged {
+        project_root: key,
+        state: new_state,
+    });
+}
+
+/// 更新项目的索引进度并发布 `IndexProgress` 事件
+///
+/// 只修改内存中的 `IndexState::Indexing`（不持久化、不记录状态转换），项目未在索引中时忽略
+pub fn report_indexing_progress(project_root: &std::path::Path, processed: usize, total: usize) {
+    let key = normalize_project_key(project_root);
+    let progress = if total == 0 { 1.0 } else { processed as f32 / total as f32 };
+
+    let updated = PROJECT_INDEX_STATE
+        .write()
+        .map(|mut guard| match guard.get_mut(&key).map(|s| &mut s.state) {
+            Some(IndexState::Indexing { progress: current, .. }) => {
+                *current = progress;
+                true
+            }
+            _ => false,
+        })
+        .unwrap_or(false);
+
+    if updated {
+        emit_store_event(StoreEvent::IndexProgress {
+            project_root: key,
+            processed,
+            total,
+            progress,
+        });
+    }
 }
 
 /// 检查项目索引是否就绪
@@ -507,7 +1048,7 @@ pub fn is_project_indexed(project_root: &std::path::Path) -> bool {
     }
     
     // 运行时状态没有记录，尝试从 index_metadata.json 恢复
-    if let Some(file_count) = check_index_metadata_exists(&key) {
+    if let Some(file_count) = check_index_metadata_exists(project_root, &key) {
         // 验证索引完整性
         if verify_index_integrity(project_root) {
             let now = ProjectIndexState::current_timestamp();
@@ -530,9 +1071,12 @@ pub fn is_project_indexed(project_root: &std::path::Path) -> bool {
 }
 
 /// 验证 Tantivy 索引完整性
-fn verify_index_integrity(_project_root: &std::path::Path) -> bool {
+///
+/// 只做快速检查（目录结构 + 索引可打开且非空）；
+/// 文档级别的交叉校验见 `consistency::verify_project`
+fn verify_index_integrity(project_root: &std::path::Path) -> bool {
     let config = match get_global_search_config() {
-        Ok(c) => c,
+        Ok(c) => c.for_project(project_root),
         Err(_) => return false,
     };
     
@@ -552,12 +1096,25 @@ fn verify_index_integrity(_project_root: &std::path::Path) -> bool {
         })
         .unwrap_or(false);
     
-    has_meta || has_segments
+    if !(has_meta || has_segments) {
+        return false;
+    }
+
+    // 旧版本 schema 的索引需要重建
+    if index_schema_outdated(index_dir) {
+        return false;
+    }
+
+    // meta.json 存在但内容损坏时无法打开
+    tantivy::Index::open_in_dir(index_dir)
+        .and_then(|index| index.reader())
+        .map(|reader| reader.searcher().num_docs() > 0)
+        .unwrap_or(false)
 }
 
-/// 检查 index_metadata.json 中是否有该项目的记录
-fn check_index_metadata_exists(project_key: &str) -> Option<usize> {
-    let config = get_global_search_config().ok()?;
+/// 检查项目索引目录的 index_metadata.json 中是否有该项目的记录
+fn check_index_metadata_exists(project_root: &std::path::Path, project_key: &str) -> Option<usize> {
+    let config = get_global_search_config().ok()?.for_project(project_root);
     let metadata_path = config.index_path.join("index_metadata.json");
     
     if !metadata_path.exists() {
@@ -583,8 +1140,72 @@ pub fn is_project_indexing(project_root: &std::path::Path) -> bool {
         .unwrap_or(false)
 }
 
+/// 后台索引的取消标记
+///
+/// 索引线程在遍历文件和每批写入之间检查，被取消时丢弃未提交的写入
+#[derive(Debug, Clone, Default)]
+pub struct IndexingCancelToken(Arc<AtomicBool>);
+
+impl IndexingCancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// 获取项目当前索引的取消标记
+pub fn indexing_cancel_token(project_root: &std::path::Path) -> IndexingCancelToken {
+    let key = normalize_project_key(project_root);
+    INDEXING_CANCEL_TOKENS
+        .lock()
+        .map(|mut tokens| tokens.entry(key).or_default().clone())
+        .unwrap_or_default()
+}
+
+/// 请求取消项目正在进行的索引
+///
+/// 只设置取消标记，索引线程在下一次检查时退出；项目未在索引时返回 false
+pub fn cancel_indexing(project_root: &std::path::Path) -> bool {
+    if !is_project_indexing(project_root) {
+        return false;
+    }
+    indexing_cancel_token(project_root).cancel();
+    crate::log_important!(info, "Indexing cancellation requested: {}", project_root.display());
+    true
+}
+
+/// 标记项目索引已取消
+///
+/// 取消不会修改已有的索引（增量写入未提交，全量重建的临时目录已删除）：
+/// 之前索引过的项目回到 Ready，否则回到 NotIndexed
+pub fn mark_indexing_cancelled(project_root: &std::path::Path) {
+    let key = normalize_project_key(project_root);
+    if let Ok(mut tokens) = INDEXING_CANCEL_TOKENS.lock() {
+        tokens.remove(&key);
+    }
+
+    let new_state = match get_index_state(project_root) {
+        Some(ProjectIndexState { last_indexed_ts: Some(indexed_at), file_count, .. }) if file_count > 0 => {
+            IndexState::Ready {
+                file_count,
+                indexed_at,
+                embedding_status: EmbeddingStatus::NotAvailable,
+            }
+        }
+        _ => IndexState::NotIndexed,
+    };
+    transition_index_state(project_root, new_state);
+}
+
 /// 标记项目开始索引
 pub fn mark_indexing_started(project_root: &std::path::Path) {
+    crate::config::record_project_used(project_root);
+    if let Ok(mut tokens) = INDEXING_CANCEL_TOKENS.lock() {
+        tokens.insert(normalize_project_key(project_root), IndexingCancelToken::default());
+    }
     let now = ProjectIndexState::current_timestamp();
     transition_index_state(project_root, IndexState::Indexing {
         started_at: now,
@@ -596,12 +1217,21 @@ pub fn mark_indexing_started(project_root: &std::path::Path) {
 /// 
 /// 同时启动文件监听（如果全局 watcher 已初始化）
 pub fn mark_indexing_complete(project_root: &std::path::Path, file_count: usize) {
+    // 全量重建会替换项目的索引目录，旧 reader 不再可用
+    invalidate_project_searcher(project_root);
+    bump_graph_generation();
+
     let now = ProjectIndexState::current_timestamp();
     transition_index_state(project_root, IndexState::Ready {
         file_count,
         indexed_at: now,
         embedding_status: EmbeddingStatus::NotAvailable,
     });
+
+    emit_store_event(StoreEvent::IndexReady {
+        project_root: normalize_project_key(project_root),
+        file_count,
+    });
     
     // 自动启动文件监听
     if let Err(e) = start_watching_project(project_root) {
@@ -609,6 +1239,17 @@ pub fn mark_indexing_complete(project_root: &std::path::Path, file_count: usize)
     }
 }
 
+/// 按索引结果更新项目状态
+///
+/// 取消（包括退出时中断）回到取消前的状态，其他错误才标记为损坏
+pub fn finish_indexing(project_root: &std::path::Path, result: &Result<usize>) {
+    match result {
+        Ok(count) => mark_indexing_complete(project_root, *count),
+        Err(e) if e.is::<IndexingCancelled>() => mark_indexing_cancelled(project_root),
+        Err(e) => mark_index_corrupted(project_root, &format!("Indexing failed: {}", e)),
+    }
+}
+
 /// 标记索引为损坏状态
 pub fn mark_index_corrupted(project_root: &std::path::Path, reason: &str) {
     transition_index_state(project_root, IndexState::Corrupted {
@@ -622,29 +1263,61 @@ pub fn update_embedding_status(project_root: &std::path::Path, status: Embedding
     
     if let Ok(mut guard) = PROJECT_INDEX_STATE.write() {
         if let Some(project_state) = guard.get_mut(&key) {
+            let mut changed = false;
+            if let EmbeddingStatus::Failed { reason } = &status {
+                project_state.last_error = Some(IndexErrorRecord {
+                    source: IndexErrorSource::Embedding,
+                    message: reason.clone(),
+                    at: ProjectIndexState::current_timestamp(),
+                });
+                changed = true;
+            }
             if let IndexState::Ready { file_count, indexed_at, .. } = &project_state.state {
                 project_state.state = IndexState::Ready {
                     file_count: *file_count,
                     indexed_at: *indexed_at,
                     embedding_status: status,
                 };
+                changed = true;
+            }
+            if changed {
                 let _ = save_persisted_state(&guard);
             }
         }
     }
 }
 
-/// 启动项目文件监听
+/// 启动项目文件监听及其变化处理任务
 fn start_watching_project(project_root: &std::path::Path) -> Result<()> {
+    // 监听器以规范化根目录为键，事件路径也在其下，变化处理任务使用同一根目录
+    let project_root = &crate::utils::canonical_path(project_root);
+
+    // 项目设置中关闭了监听
+    if !crate::config::project_settings(project_root).watch {
+        return Ok(());
+    }
+
+    // 监听已暂停：记下项目，恢复时再监听
+    if let Ok(mut paused) = PAUSED_WATCH_PATHS.lock() {
+        if let Some(paths) = paused.as_mut() {
+            if !paths.iter().any(|p| p == project_root) {
+                paths.push(project_root.to_path_buf());
+            }
+            return Ok(());
+        }
+    }
+
     let mut guard = GLOBAL_WATCHER.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
     
     if let Some(ref mut watcher) = *guard {
         // 检查是否已在监听
-        let watched = watcher.watched_paths();
-        if !watched.iter().any(|p| p == project_root) {
+        if !watcher.is_watching(project_root) {
             watcher.watch(project_root)?;
             crate::log_important!(info, "Started watching project: {}", project_root.display());
         }
+        if let Some(changes) = watcher.subscribe_changes(project_root) {
+            change_tasks::start(project_root, changes);
+        }
     }
     
     Ok(())
@@ -659,6 +1332,51 @@ pub fn get_index_state(project_root: &std::path::Path) -> Option<ProjectIndexSta
         .and_then(|guard| guard.get(&key).cloned())
 }
 
+/// 列出所有有索引状态记录的项目（已排序）
+pub fn known_projects() -> Vec<PathBuf> {
+    let mut projects: Vec<PathBuf> = PROJECT_INDEX_STATE
+        .read()
+        .map(|guard| guard.keys().map(PathBuf::from).collect())
+        .unwrap_or_default();
+    projects.sort();
+    projects
+}
+
+/// 所有项目索引状态的快照（不阻塞：锁被占用时返回 None，用于崩溃报告等场景）
+pub fn index_state_snapshot() -> Option<HashMap<String, IndexState>> {
+    PROJECT_INDEX_STATE
+        .try_read()
+        .ok()
+        .map(|guard| guard.iter().map(|(k, v)| (k.clone(), v.state.clone())).collect())
+}
+
+/// 退出时持久化索引状态
+///
+/// 未完成的索引不会在下次启动时继续，按已有文件数标记为过期或未索引，
+/// 避免下次启动误以为仍在索引
+pub fn persist_index_states_on_shutdown() {
+    let Ok(mut guard) = PROJECT_INDEX_STATE.write() else {
+        return;
+    };
+
+    for state in guard.values_mut() {
+        if matches!(state.state, IndexState::Indexing { .. }) {
+            state.state = match state.last_indexed_ts {
+                Some(last_indexed_at) if state.file_count > 0 => IndexState::Stale {
+                    file_count: state.file_count,
+                    last_indexed_at,
+                },
+                _ => IndexState::NotIndexed,
+            };
+        }
+        state.indexing = false;
+    }
+
+    if let Err(e) = save_persisted_state(&guard) {
+        crate::log_important!(warn, "Failed to persist index states on shutdown: {}", e);
+    }
+}
+
 /// 获取项目已索引的文件数量
 pub fn get_indexed_file_count(project_root: &std::path::Path) -> Option<usize> {
     get_index_state(project_root).map(|s| s.file_count)
@@ -669,8 +1387,15 @@ pub fn get_indexed_file_count(project_root: &std::path::Path) -> Option<usize> {
 // ============================================================================
 
 /// 获取索引状态文件路径
+#[cfg(not(test))]
+fn get_state_file_path() -> Option<PathBuf> {
+    crate::config::profile_config_dir().map(|d| d.join(INDEX_STATE_FILE))
+}
+
+/// 测试不读写用户配置目录中的索引状态
+#[cfg(test)]
 fn get_state_file_path() -> Option<PathBuf> {
-    dirs::config_dir().map(|d| d.join("neurospec").join(INDEX_STATE_FILE))
+    None
 }
 
 /// 从文件加载持久化的索引状态
@@ -684,11 +1409,15 @@ fn load_persisted_state() -> Option<HashMap<String, ProjectIndexState>> {
     let content = std::fs::read_to_string(&path).ok()?;
     let persisted: PersistedIndexState = serde_json::from_str(&content).ok()?;
     
-    // 重置所有项目的 indexing 状态（重启后不可能还在索引）
-    let mut projects = persisted.projects;
-    for state in projects.values_mut() {
-        state.indexing = false;
-    }
+    // 重置所有项目的 indexing 状态（重启后不可能还在索引），旧版本写入的键重新规范化
+    let projects: HashMap<String, ProjectIndexState> = persisted
+        .projects
+        .into_iter()
+        .map(|(key, mut state)| {
+            state.indexing = false;
+            (crate::utils::rekey(&key), state)
+        })
+        .collect();
     
     crate::log_important!(info, "Loaded {} persisted index states", projects.len());
     Some(projects)
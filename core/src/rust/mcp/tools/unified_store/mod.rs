@@ -3,39 +3,83 @@
 //! 为 Search、X-Ray、Graph 提供共享的底层索引基础设施
 //! - 符号提取（一次扫描，多方共享）
 //! - 增量更新（文件变化时自动更新）
-//! - 文件监听（统一的变化检测）
+//! - 文件监听（统一的变化检测，事件驱动的增量索引任务）
 //! - 全局单例（应用生命周期内共享）
+//! - SQLite 持久化（重启后懒加载，跳过未变化文件的解析）
+//! - 符号名模糊匹配（三元组 + 编辑距离，拼写纠错建议）
+//! - 一致性检查（存储、Tantivy 索引与索引状态之间的漂移修复）
+//! - 更新事件订阅（文件索引/移除、索引就绪）
 
 pub mod store;
+pub mod persistence;
 pub mod watcher;
+mod change_tasks;
 pub mod global;
+pub mod consistency;
+pub mod fuzzy;
+pub mod events;
 
-pub use store::{UnifiedSymbolStore, UnifiedSymbol, IndexStats};
+pub use store::{
+    UnifiedSymbolStore, UnifiedSymbol, SymbolKind, SymbolFilter, SymbolRename, FuzzySymbol, IndexStats,
+    StoreLimits, StoreMetrics,
+};
 pub use watcher::{FileWatcher, FileChangeEvent};
+pub use fuzzy::{FuzzyName, default_edit_distance, MAX_EDIT_DISTANCE};
+pub use consistency::{verify_project, ConsistencyReport};
+pub use events::{subscribe_store_events, StoreEvent};
 pub use global::{
     init_global_store,
     get_global_store,
     with_global_store,
     init_global_watcher,
+    stop_global_watcher,
     watch_project,
+    unwatch_project,
+    is_project_watched,
+    touch_project,
+    unwatch_idle_projects,
+    set_file_watching_paused,
+    is_file_watching_paused,
     process_file_changes,
+    process_project_file_changes,
+    graph_generation,
     // 搜索引擎相关
     init_global_search_config,
+    apply_search_config,
     get_global_search_config,
     create_searcher_for_project,
+    acquire_searcher,
+    average_searcher_open_ms,
+    SearcherLease,
+    invalidate_searcher_pool,
+    invalidate_project_searcher,
+    reload_project_searcher,
     is_search_initialized,
+    flush_on_shutdown,
+    with_live_indexer,
+    persist_index_states_on_shutdown,
     // 索引状态管理
     IndexState,
     IndexHealth,
     EmbeddingStatus,
     ProjectIndexState,
+    IndexErrorRecord,
+    IndexErrorSource,
     is_project_indexed,
     is_project_indexing,
     mark_indexing_started,
+    report_indexing_progress,
     mark_indexing_complete,
     mark_index_corrupted,
+    finish_indexing,
+    IndexingCancelToken,
+    indexing_cancel_token,
+    cancel_indexing,
+    mark_indexing_cancelled,
     get_index_state,
     get_indexed_file_count,
+    known_projects,
+    index_state_snapshot,
     assess_index_health,
     transition_index_state,
     update_embedding_status,
@@ -0,0 +1,294 @@
+//! 统一符号存储的 SQLite 持久层
+//!
+//! 以 (项目, 文件, 内容哈希) 为粒度保存已提取的符号，
+//! 重启后按项目懒加载，内容未变化的文件无需重新解析
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+use anyhow::Result;
+use rusqlite::{params, Connection};
+
+use super::store::UnifiedSymbol;
+
+const DB_FILENAME: &str = "unified_symbols.db";
+/// 符号提取规则变化（如新增导入/继承信息）时递增，旧版本缓存会被清空重建
+const SCHEMA_VERSION: i32 = 2;
+
+/// 持久化的文件条目
+#[derive(Debug, Clone)]
+pub struct PersistedFile {
+    pub content_hash: String,
+    pub mtime: u64,
+    pub size: u64,
+    pub symbols: Vec<UnifiedSymbol>,
+}
+
+/// 持久层中的项目摘要
+#[derive(Debug, Clone)]
+pub struct ProjectSummary {
+    pub project: String,
+    pub file_count: usize,
+    pub symbol_count: usize,
+    pub last_full_scan: Option<u64>,
+}
+
+/// 符号持久层
+pub struct SymbolPersistence {
+    conn: Mutex<Connection>,
+}
+
+impl SymbolPersistence {
+    /// 在缓存目录下打开（或创建）符号数据库
+    pub fn new(cache_dir: &Path) -> Result<Self> {
+        let db_path = cache_dir.join(DB_FILENAME);
+        let conn = Connection::open(&db_path)?;
+
+        let persistence = Self {
+            conn: Mutex::new(conn),
+        };
+        persistence.initialize_schema()?;
+        persistence.rekey_projects()?;
+        Ok(persistence)
+    }
+
+    /// 旧版本以原始路径（未解析符号链接、反斜杠、`\\?\` 前缀等）写入的项目统一为路径键
+    ///
+    /// 新键下已有的条目优先保留，旧键的剩余条目删除
+    fn rekey_projects(&self) -> Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        let projects: Vec<String> = {
+            let mut stmt = conn.prepare(
+                "SELECT project FROM symbol_files UNION SELECT project FROM symbol_projects",
+            )?;
+            let rows = stmt.query_map([], |row| row.get(0))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+        };
+
+        for project in projects {
+            let key = crate::utils::rekey(&project);
+            if key == project {
+                continue;
+            }
+            for table in ["symbol_files", "symbol_projects"] {
+                conn.execute(
+                    &format!("UPDATE OR IGNORE {} SET project = ?1 WHERE project = ?2", table),
+                    params![key, project],
+                )?;
+                conn.execute(&format!("DELETE FROM {} WHERE project = ?1", table), params![project])?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 初始化数据库 schema
+    fn initialize_schema(&self) -> Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        // WAL 模式下读写互不阻塞，适合后台索引 + 前台查询
+        let _ = conn.pragma_update(None, "journal_mode", "WAL");
+        let _ = conn.pragma_update(None, "synchronous", "NORMAL");
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS symbol_files (
+                project TEXT NOT NULL,
+                rel_path TEXT NOT NULL,
+                content_hash TEXT NOT NULL,
+                mtime INTEGER NOT NULL,
+                size INTEGER NOT NULL,
+                symbols TEXT NOT NULL,
+                PRIMARY KEY (project, rel_path)
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS symbol_projects (
+                project TEXT PRIMARY KEY,
+                last_full_scan INTEGER
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (
+                version INTEGER PRIMARY KEY
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_symbol_files_hash ON symbol_files(project, content_hash)",
+            [],
+        )?;
+
+        // 旧版本提取的符号缺少新字段，清空后由下次索引重新解析
+        let stored_version: Option<i32> = conn
+            .query_row("SELECT MAX(version) FROM schema_version", [], |row| row.get(0))
+            .unwrap_or(None);
+        if stored_version.is_some_and(|version| version < SCHEMA_VERSION) {
+            conn.execute("DELETE FROM symbol_files", [])?;
+            conn.execute("DELETE FROM schema_version", [])?;
+        }
+
+        conn.execute(
+            "INSERT OR REPLACE INTO schema_version (version) VALUES (?1)",
+            [SCHEMA_VERSION],
+        )?;
+
+        Ok(())
+    }
+
+    /// 加载项目的全部文件条目
+    pub fn load_project(&self, project: &str) -> Result<(HashMap<String, PersistedFile>, Option<u64>)> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT rel_path, content_hash, mtime, size, symbols
+             FROM symbol_files WHERE project = ?1",
+        )?;
+
+        let rows = stmt.query_map(params![project], |row| {
+            let rel_path: String = row.get(0)?;
+            let content_hash: String = row.get(1)?;
+            let mtime: i64 = row.get(2)?;
+            let size: i64 = row.get(3)?;
+            let symbols_json: String = row.get(4)?;
+            Ok((rel_path, content_hash, mtime, size, symbols_json))
+        })?;
+
+        let mut files = HashMap::new();
+        for row in rows {
+            let (rel_path, content_hash, mtime, size, symbols_json) = match row {
+                Ok(r) => r,
+                Err(_) => continue,
+            };
+            // 单条记录损坏时跳过，下次索引会重新解析
+            let symbols: Vec<UnifiedSymbol> = match serde_json::from_str(&symbols_json) {
+                Ok(s) => s,
+                Err(_) => continue,
+            };
+            files.insert(rel_path, PersistedFile {
+                content_hash,
+                mtime: mtime as u64,
+                size: size as u64,
+                symbols,
+            });
+        }
+
+        let last_full_scan: Option<i64> = conn
+            .query_row(
+                "SELECT last_full_scan FROM symbol_projects WHERE project = ?1",
+                params![project],
+                |row| row.get(0),
+            )
+            .ok()
+            .flatten();
+
+        Ok((files, last_full_scan.map(|ts| ts as u64)))
+    }
+
+    /// 批量写入变更（单事务），并删除已不存在的文件
+    pub fn save_changes(
+        &self,
+        project: &str,
+        upserts: &[(String, PersistedFile)],
+        removals: &[String],
+        last_full_scan: Option<u64>,
+    ) -> Result<()> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let tx = conn.transaction()?;
+
+        {
+            let mut upsert_stmt = tx.prepare(
+                "INSERT OR REPLACE INTO symbol_files (project, rel_path, content_hash, mtime, size, symbols)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            for (rel_path, file) in upserts {
+                let symbols_json = serde_json::to_string(&file.symbols)?;
+                upsert_stmt.execute(params![
+                    project,
+                    rel_path,
+                    file.content_hash,
+                    file.mtime as i64,
+                    file.size as i64,
+                    symbols_json,
+                ])?;
+            }
+
+            let mut remove_stmt = tx.prepare(
+                "DELETE FROM symbol_files WHERE project = ?1 AND rel_path = ?2",
+            )?;
+            for rel_path in removals {
+                remove_stmt.execute(params![project, rel_path])?;
+            }
+        }
+
+        if let Some(ts) = last_full_scan {
+            tx.execute(
+                "INSERT OR REPLACE INTO symbol_projects (project, last_full_scan) VALUES (?1, ?2)",
+                params![project, ts as i64],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 列出所有已持久化项目的摘要（不反序列化符号）
+    pub fn project_summaries(&self) -> Result<Vec<ProjectSummary>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT f.project, COUNT(*), COALESCE(SUM(json_array_length(f.symbols)), 0), p.last_full_scan
+             FROM symbol_files f
+             LEFT JOIN symbol_projects p ON p.project = f.project
+             GROUP BY f.project",
+        )?;
+
+        let summaries = stmt.query_map([], |row| {
+            let file_count: i64 = row.get(1)?;
+            let symbol_count: i64 = row.get(2)?;
+            let last_full_scan: Option<i64> = row.get(3)?;
+            Ok(ProjectSummary {
+                project: row.get(0)?,
+                file_count: file_count as usize,
+                symbol_count: symbol_count as usize,
+                last_full_scan: last_full_scan.map(|ts| ts as u64),
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+        Ok(summaries)
+    }
+
+    /// 将 WAL 合并回主库并更新查询统计（关闭前调用）
+    pub fn checkpoint(&self) -> Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+        conn.execute_batch("PRAGMA optimize")?;
+        Ok(())
+    }
+
+    /// 标记文件需要重新检查（清除 mtime，保留符号与内容哈希）
+    pub fn mark_stale(&self, project: &str, rel_path: &str) -> Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        conn.execute(
+            "UPDATE symbol_files SET mtime = 0 WHERE project = ?1 AND rel_path = ?2",
+            params![project, rel_path],
+        )?;
+        Ok(())
+    }
+}
+
+/// 计算文件内容哈希（SHA-256，十六进制）
+pub fn content_hash(content: &[u8]) -> String {
+    let digest = ring::digest::digest(&ring::digest::SHA256, content);
+    hex::encode(digest.as_ref())
+}
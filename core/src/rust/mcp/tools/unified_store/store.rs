@@ -2,16 +2,24 @@
 //!
 //! 提供符号缓存、增量更新、多消费者接口
 
-use std::collections::HashMap;
-use std::path::{Path, PathBuf};
-use std::sync::{Arc, RwLock};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
 use std::time::UNIX_EPOCH;
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
+use super::events::{emit_store_event, StoreEvent};
+use super::fuzzy::{FuzzyName, SymbolNameIndex};
+use crate::mcp::tools::acemcp::local_engine::indexer::exclude_overrides;
+use crate::mcp::tools::acemcp::local_engine::{FileLimits, SkippedFiles};
+use crate::mcp::tools::acemcp::local_engine::neurospec_ignore::{is_neurospec_ignored, load_neurospec_ignore};
+use super::persistence::{content_hash, PersistedFile, SymbolPersistence};
+
 /// 符号类型
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SymbolKind {
     File,
     Module,
@@ -29,56 +37,467 @@ pub struct UnifiedSymbol {
     pub language: Option<String>,
     pub signature: Option<String>,
     pub references: Vec<String>,
+    /// 继承或实现的父类型名称
+    #[serde(default)]
+    pub inherits: Vec<String>,
     pub start_line: Option<u32>,
     pub end_line: Option<u32>,
+    /// 定义体指纹（忽略空白与自身名称），用于识别重命名/移动
+    #[serde(default)]
+    pub fingerprint: Option<String>,
 }
 
-/// 文件缓存条目
+/// 检测到的符号重命名/移动
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolRename {
+    pub old_name: String,
+    pub new_name: String,
+    /// 原所在文件（相对路径）
+    pub old_path: String,
+    /// 新所在文件（相对路径）
+    pub new_path: String,
+    pub fingerprint: String,
+    /// 检测时间（Unix 时间戳）
+    pub detected_at: u64,
+}
+
+/// 每个项目保留的重命名记录上限
+const MAX_RENAME_HISTORY: usize = 256;
+
+impl UnifiedSymbol {
+    /// 估算内存占用（字节）
+    fn estimated_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.name.capacity()
+            + self.path.capacity()
+            + self.language.as_ref().map(|s| s.capacity()).unwrap_or(0)
+            + self.signature.as_ref().map(|s| s.capacity()).unwrap_or(0)
+            + self.references.iter().map(|r| std::mem::size_of::<String>() + r.capacity()).sum::<usize>()
+            + self.inherits.iter().map(|r| std::mem::size_of::<String>() + r.capacity()).sum::<usize>()
+    }
+}
+
+impl SymbolKind {
+    /// 从字符串解析（大小写不敏感）
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "file" => Some(Self::File),
+            "module" | "mod" => Some(Self::Module),
+            "class" | "struct" | "interface" => Some(Self::Class),
+            "function" | "fn" | "method" => Some(Self::Function),
+            "variable" | "var" | "const" => Some(Self::Variable),
+            _ => None,
+        }
+    }
+}
+
+/// 符号查询过滤条件
+///
+/// 所有条件为 AND 关系，未设置的条件不参与过滤；名称匹配大小写不敏感
+#[derive(Debug, Clone, Default)]
+pub struct SymbolFilter {
+    /// 文件相对路径前缀（如 `src/mcp/`）
+    pub path_prefix: Option<String>,
+    /// 文件相对路径子串
+    pub path_contains: Option<String>,
+    /// 符号类型（任一匹配即可）
+    pub kinds: Vec<SymbolKind>,
+    /// 名称前缀
+    pub name_prefix: Option<String>,
+    /// 名称子串
+    pub name_contains: Option<String>,
+    /// 语言（如 `rust`、`typescript`）
+    pub language: Option<String>,
+    /// 最大返回数量（None 表示不限制）
+    pub limit: Option<usize>,
+}
+
+impl SymbolFilter {
+    /// 文件路径是否满足条件
+    fn matches_path(&self, rel_path: &str) -> bool {
+        if let Some(ref prefix) = self.path_prefix {
+            let prefix = prefix.replace('\\', "/");
+            if !rel_path.starts_with(prefix.trim_start_matches("./")) {
+                return false;
+            }
+        }
+        if let Some(ref needle) = self.path_contains {
+            if !rel_path.contains(&needle.replace('\\', "/")) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// 符号是否满足条件
+    fn matches_symbol(&self, symbol: &UnifiedSymbol) -> bool {
+        if !self.kinds.is_empty() && !self.kinds.contains(&symbol.kind) {
+            return false;
+        }
+
+        if let Some(ref language) = self.language {
+            match symbol.language {
+                Some(ref l) if l.eq_ignore_ascii_case(language) => {}
+                _ => return false,
+            }
+        }
+
+        if self.name_prefix.is_some() || self.name_contains.is_some() {
+            let name = symbol.name.to_lowercase();
+            if let Some(ref prefix) = self.name_prefix {
+                if !name.starts_with(&prefix.to_lowercase()) {
+                    return false;
+                }
+            }
+            if let Some(ref needle) = self.name_contains {
+                if !name.contains(&needle.to_lowercase()) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+/// 文件缓存条目
+#[derive(Debug, Clone)]
 struct FileCacheEntry {
     mtime: u64,
     size: u64,
+    /// 内容哈希（不支持的语言为空字符串，不读取内容）
+    content_hash: String,
     symbols: Vec<UnifiedSymbol>,
 }
 
+impl From<PersistedFile> for FileCacheEntry {
+    fn from(file: PersistedFile) -> Self {
+        Self {
+            mtime: file.mtime,
+            size: file.size,
+            content_hash: file.content_hash,
+            symbols: file.symbols,
+        }
+    }
+}
+
+impl From<&FileCacheEntry> for PersistedFile {
+    fn from(entry: &FileCacheEntry) -> Self {
+        Self {
+            content_hash: entry.content_hash.clone(),
+            mtime: entry.mtime,
+            size: entry.size,
+            symbols: entry.symbols.clone(),
+        }
+    }
+}
+
 /// 项目缓存
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default)]
 struct ProjectCache {
     files: HashMap<String, FileCacheEntry>,
     last_full_scan: Option<u64>,
+    /// 最近访问时间（逻辑时钟，用于 LRU 淘汰）
+    last_access: AtomicU64,
+    /// 符号名模糊索引（首次模糊查询时构建，文件变化后重建）
+    name_index: OnceLock<SymbolNameIndex>,
+}
+
+impl ProjectCache {
+    /// 常驻内存的符号数量
+    fn symbol_count(&self) -> usize {
+        self.files.values().map(|f| f.symbols.len()).sum()
+    }
+
+    /// 获取（必要时构建）符号名模糊索引
+    fn name_index(&self) -> &SymbolNameIndex {
+        self.name_index.get_or_init(|| {
+            SymbolNameIndex::build(
+                self.files
+                    .values()
+                    .flat_map(|entry| entry.symbols.iter())
+                    .filter(|symbol| symbol.kind != SymbolKind::File)
+                    .map(|symbol| symbol.name.as_str()),
+            )
+        })
+    }
+
+    /// 汇总项目统计（文件、符号、语言分布、内存占用）
+    fn fill_stats(&self, project_root: &str, stats: &mut IndexStats) {
+        stats.project_root = project_root.to_string();
+        stats.total_files = self.files.len();
+        stats.last_updated = self.last_full_scan;
+        stats.resident = true;
+        stats.languages.clear();
+        stats.total_symbols = 0;
+        stats.memory_bytes = 0;
+
+        for (rel_path, entry) in &self.files {
+            stats.total_symbols += entry.symbols.len();
+            stats.memory_bytes += std::mem::size_of::<FileCacheEntry>()
+                + rel_path.capacity()
+                + entry.content_hash.capacity()
+                + entry.symbols.iter().map(|s| s.estimated_size()).sum::<usize>();
+
+            if let Some(language) = detect_language(Path::new(rel_path)) {
+                *stats.languages.entry(language.to_string()).or_insert(0) += 1;
+            }
+        }
+    }
+}
+
+/// 模糊匹配到的符号
+#[derive(Debug, Clone, Serialize)]
+pub struct FuzzySymbol {
+    #[serde(flatten)]
+    pub symbol: UnifiedSymbol,
+    /// 名称与查询的编辑距离
+    pub distance: usize,
+}
+
+/// 内存上限配置
+///
+/// 超出任一上限时按项目 LRU 淘汰，被淘汰的项目在下次访问时从 SQLite 重新加载
+#[derive(Debug, Clone)]
+pub struct StoreLimits {
+    /// 最多常驻内存的项目数
+    pub max_resident_projects: usize,
+    /// 最多常驻内存的符号总数
+    pub max_resident_symbols: usize,
+}
+
+impl StoreLimits {
+    /// 按搜索配置构建（0 视为至少 1）
+    pub fn from_config(search_config: &crate::config::SearchConfig) -> Self {
+        Self {
+            max_resident_projects: search_config.max_resident_projects.max(1),
+            max_resident_symbols: search_config.max_resident_symbols.max(1),
+        }
+    }
+}
+
+impl Default for StoreLimits {
+    fn default() -> Self {
+        Self {
+            max_resident_projects: crate::config::default_search_max_resident_projects(),
+            max_resident_symbols: crate::config::default_search_max_resident_symbols(),
+        }
+    }
+}
+
+/// 存储运行指标
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StoreMetrics {
+    /// 常驻内存的项目数
+    pub resident_projects: usize,
+    /// 常驻内存的符号数
+    pub resident_symbols: usize,
+    /// 累计淘汰次数
+    pub evictions: u64,
+    /// 累计从持久层加载次数
+    pub reloads: u64,
 }
 
 /// 统一符号存储
 pub struct UnifiedSymbolStore {
-    /// 项目根路径 -> 项目缓存
+    /// 项目根路径 -> 项目缓存（仅包含已加载的项目）
     projects: Arc<RwLock<HashMap<String, ProjectCache>>>,
-    /// 缓存文件路径
-    cache_path: PathBuf,
+    /// SQLite 持久层
+    persistence: SymbolPersistence,
+    /// 内存上限
+    limits: StoreLimits,
+    /// LRU 逻辑时钟
+    access_clock: AtomicU64,
+    evictions: AtomicU64,
+    reloads: AtomicU64,
+    /// 项目根路径 -> 最近检测到的重命名
+    renames: RwLock<HashMap<String, Vec<SymbolRename>>>,
 }
 
 
 impl UnifiedSymbolStore {
     /// 创建新的统一存储
+    ///
+    /// 项目数据不会在此处加载，而是在首次访问时从 SQLite 懒加载
     pub fn new(cache_dir: &Path) -> Result<Self> {
+        Self::with_limits(cache_dir, StoreLimits::default())
+    }
+
+    /// 使用指定内存上限创建统一存储
+    pub fn with_limits(cache_dir: &Path, limits: StoreLimits) -> Result<Self> {
         std::fs::create_dir_all(cache_dir)?;
-        let cache_path = cache_dir.join("unified_symbols.json");
-        
-        let projects = if cache_path.exists() {
-            let data = std::fs::read_to_string(&cache_path)?;
-            serde_json::from_str(&data).unwrap_or_default()
-        } else {
-            HashMap::new()
-        };
+
+        // 旧版 JSON 缓存没有内容哈希，无法复用，直接清理
+        let legacy_json = cache_dir.join("unified_symbols.json");
+        if legacy_json.exists() {
+            let _ = std::fs::remove_file(&legacy_json);
+        }
+
+        let persistence = SymbolPersistence::new(cache_dir)?;
 
         Ok(Self {
-            projects: Arc::new(RwLock::new(projects)),
-            cache_path,
+            projects: Arc::new(RwLock::new(HashMap::new())),
+            persistence,
+            limits,
+            access_clock: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            reloads: AtomicU64::new(0),
+            renames: RwLock::new(HashMap::new()),
         })
     }
 
+    /// 获取当前运行指标
+    pub fn metrics(&self) -> StoreMetrics {
+        let (resident_projects, resident_symbols) = match self.projects.read() {
+            Ok(projects) => (
+                projects.len(),
+                projects.values().map(|p| p.symbol_count()).sum(),
+            ),
+            Err(_) => (0, 0),
+        };
+
+        StoreMetrics {
+            resident_projects,
+            resident_symbols,
+            evictions: self.evictions.load(Ordering::Relaxed),
+            reloads: self.reloads.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 获取单个项目的统计（未加载时从持久层加载）
+    pub fn project_stats(&self, project_root: &Path) -> Result<IndexStats> {
+        let root_key = crate::utils::path_key(project_root);
+        self.ensure_project_loaded(&root_key)?;
+        let projects = self.projects.read().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let mut stats = IndexStats::default();
+        match projects.get(&root_key) {
+            Some(cache) => cache.fill_stats(&root_key, &mut stats),
+            None => stats.project_root = root_key,
+        }
+        Ok(stats)
+    }
+
+    /// 获取所有已知项目的统计
+    ///
+    /// 常驻项目给出完整统计；仅存在于持久层的项目不会被加载，只给出文件/符号总数
+    pub fn all_project_stats(&self) -> Result<Vec<IndexStats>> {
+        let mut result = Vec::new();
+        let mut seen = HashSet::new();
+
+        {
+            let projects = self.projects.read().map_err(|e| anyhow::anyhow!("{}", e))?;
+            for (root_key, cache) in projects.iter() {
+                let mut stats = IndexStats::default();
+                cache.fill_stats(root_key, &mut stats);
+                seen.insert(root_key.clone());
+                result.push(stats);
+            }
+        }
+
+        for summary in self.persistence.project_summaries()? {
+            if seen.contains(&summary.project) {
+                continue;
+            }
+            result.push(IndexStats {
+                project_root: summary.project,
+                total_files: summary.file_count,
+                total_symbols: summary.symbol_count,
+                last_updated: summary.last_full_scan,
+                resident: false,
+                ..Default::default()
+            });
+        }
+
+        result.sort_by(|a, b| a.project_root.cmp(&b.project_root));
+        Ok(result)
+    }
+
+    /// 推进逻辑时钟
+    fn tick(&self) -> u64 {
+        self.access_clock.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// 确保项目已从持久层加载到内存，并刷新其访问时间
+    fn ensure_project_loaded(&self, root_key: &str) -> Result<()> {
+        {
+            let projects = self.projects.read().map_err(|e| anyhow::anyhow!("{}", e))?;
+            if let Some(cache) = projects.get(root_key) {
+                cache.last_access.store(self.tick(), Ordering::Relaxed);
+                return Ok(());
+            }
+        }
+
+        let (files, last_full_scan) = self.persistence.load_project(root_key)?;
+        if !files.is_empty() {
+            self.reloads.fetch_add(1, Ordering::Relaxed);
+        }
+
+        {
+            let mut projects = self.projects.write().map_err(|e| anyhow::anyhow!("{}", e))?;
+            projects.entry(root_key.to_string()).or_insert_with(|| ProjectCache {
+                files: files.into_iter().map(|(k, v)| (k, v.into())).collect(),
+                last_full_scan,
+                last_access: AtomicU64::new(self.tick()),
+                name_index: OnceLock::new(),
+            });
+        }
+
+        self.enforce_limits(root_key)
+    }
+
+    /// 按 LRU 淘汰项目直到满足内存上限
+    ///
+    /// `keep` 为当前正在使用的项目，不会被淘汰
+    fn enforce_limits(&self, keep: &str) -> Result<()> {
+        let mut projects = self.projects.write().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let mut total_symbols: usize = projects.values().map(|p| p.symbol_count()).sum();
+
+        while projects.len() > self.limits.max_resident_projects
+            || total_symbols > self.limits.max_resident_symbols
+        {
+            let victim = projects
+                .iter()
+                .filter(|(k, _)| k.as_str() != keep)
+                .min_by_key(|(_, p)| p.last_access.load(Ordering::Relaxed))
+                .map(|(k, _)| k.clone());
+
+            let Some(victim) = victim else {
+                break;
+            };
+
+            if let Some(evicted) = projects.remove(&victim) {
+                total_symbols -= evicted.symbol_count();
+                self.evictions.fetch_add(1, Ordering::Relaxed);
+                crate::log_debug!(
+                    "[UnifiedStore] Evicted project {} ({} files) from memory",
+                    victim,
+                    evicted.files.len()
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 获取项目已缓存的文件（相对路径，已排序）
+    pub fn project_file_paths(&self, project_root: &Path) -> Result<Vec<String>> {
+        let root_key = crate::utils::path_key(project_root);
+        self.ensure_project_loaded(&root_key)?;
+        let projects = self.projects.read().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let mut paths: Vec<String> = projects
+            .get(&root_key)
+            .map(|cache| cache.files.keys().cloned().collect())
+            .unwrap_or_default();
+        paths.sort();
+        Ok(paths)
+    }
+
     /// 获取或创建项目缓存
     pub fn get_project_symbols(&self, project_root: &Path) -> Result<Vec<UnifiedSymbol>> {
-        let root_key = project_root.to_string_lossy().to_string();
+        let root_key = crate::utils::path_key(project_root);
+        self.ensure_project_loaded(&root_key)?;
         let projects = self.projects.read().map_err(|e| anyhow::anyhow!("{}", e))?;
         
         if let Some(cache) = projects.get(&root_key) {
@@ -92,6 +511,145 @@ impl UnifiedSymbolStore {
         Ok(Vec::new())
     }
 
+    /// 项目是否已有缓存的符号（不复制符号）
+    pub fn has_project_symbols(&self, project_root: &Path) -> Result<bool> {
+        let root_key = crate::utils::path_key(project_root);
+        self.ensure_project_loaded(&root_key)?;
+        let projects = self.projects.read().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        Ok(projects
+            .get(&root_key)
+            .is_some_and(|cache| cache.files.values().any(|entry| !entry.symbols.is_empty())))
+    }
+
+    /// 统计满足条件的项目符号数（忽略 `limit`）
+    pub fn count_symbols(&self, project_root: &Path, filter: &SymbolFilter) -> Result<usize> {
+        let root_key = crate::utils::path_key(project_root);
+        self.ensure_project_loaded(&root_key)?;
+        let projects = self.projects.read().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        Ok(projects
+            .get(&root_key)
+            .map(|cache| {
+                cache.files
+                    .iter()
+                    .filter(|(rel_path, _)| filter.matches_path(rel_path))
+                    .flat_map(|(_, entry)| entry.symbols.iter())
+                    .filter(|symbol| filter.matches_symbol(symbol))
+                    .count()
+            })
+            .unwrap_or(0))
+    }
+
+    /// 按条件查询项目符号
+    ///
+    /// 先按文件路径裁剪，再逐个符号过滤，结果按 (路径, 名称) 排序
+    pub fn query_symbols(&self, project_root: &Path, filter: &SymbolFilter) -> Result<Vec<UnifiedSymbol>> {
+        let root_key = crate::utils::path_key(project_root);
+        self.ensure_project_loaded(&root_key)?;
+        let projects = self.projects.read().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let Some(cache) = projects.get(&root_key) else {
+            return Ok(Vec::new());
+        };
+
+        let mut files: Vec<(&String, &FileCacheEntry)> = cache.files
+            .iter()
+            .filter(|(rel_path, _)| filter.matches_path(rel_path))
+            .collect();
+        files.sort_by(|a, b| a.0.cmp(b.0));
+
+        let limit = filter.limit.unwrap_or(usize::MAX);
+        let mut results = Vec::new();
+        'outer: for (_, entry) in files {
+            let mut matched: Vec<&UnifiedSymbol> = entry.symbols
+                .iter()
+                .filter(|s| filter.matches_symbol(s))
+                .collect();
+            matched.sort_by(|a, b| a.name.cmp(&b.name));
+
+            for symbol in matched {
+                if results.len() >= limit {
+                    break 'outer;
+                }
+                results.push(symbol.clone());
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// 按名称模糊查询项目符号
+    ///
+    /// 名称与 `name` 的编辑距离不超过 `max_distance`（大小写不敏感）；其余条件同 `query_symbols`，
+    /// 名称前缀/子串条件不参与。结果按（距离, 路径, 名称）排序
+    pub fn fuzzy_symbols(
+        &self,
+        project_root: &Path,
+        name: &str,
+        max_distance: usize,
+        filter: &SymbolFilter,
+    ) -> Result<Vec<FuzzySymbol>> {
+        let root_key = crate::utils::path_key(project_root);
+        self.ensure_project_loaded(&root_key)?;
+        let projects = self.projects.read().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        let Some(cache) = projects.get(&root_key) else {
+            return Ok(Vec::new());
+        };
+
+        let distances: HashMap<String, usize> = cache
+            .name_index()
+            .search(name, max_distance, usize::MAX)
+            .into_iter()
+            .map(|m| (m.name, m.distance))
+            .collect();
+        if distances.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let filter = SymbolFilter {
+            name_prefix: None,
+            name_contains: None,
+            ..filter.clone()
+        };
+        let mut results: Vec<FuzzySymbol> = cache.files
+            .iter()
+            .filter(|(rel_path, _)| filter.matches_path(rel_path))
+            .flat_map(|(_, entry)| entry.symbols.iter())
+            .filter(|symbol| filter.matches_symbol(symbol))
+            .filter_map(|symbol| {
+                let distance = *distances.get(&symbol.name)?;
+                Some(FuzzySymbol { symbol: symbol.clone(), distance })
+            })
+            .collect();
+
+        results.sort_by(|a, b| {
+            a.distance
+                .cmp(&b.distance)
+                .then_with(|| a.symbol.path.cmp(&b.symbol.path))
+                .then_with(|| a.symbol.name.cmp(&b.symbol.name))
+        });
+        if let Some(limit) = filter.limit {
+            results.truncate(limit);
+        }
+        Ok(results)
+    }
+
+    /// 拼写纠错建议：与 `term` 相近但不相同的符号名（按距离、出现次数排序）
+    ///
+    /// 项目中已有同名符号时返回空
+    pub fn did_you_mean(&self, project_root: &Path, term: &str, limit: usize) -> Result<Vec<FuzzyName>> {
+        let root_key = crate::utils::path_key(project_root);
+        self.ensure_project_loaded(&root_key)?;
+        let projects = self.projects.read().map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        Ok(projects
+            .get(&root_key)
+            .map(|cache| cache.name_index().did_you_mean(term, limit))
+            .unwrap_or_default())
+    }
+
     /// 检查文件是否需要重新索引
     fn should_reindex(&self, path: &Path, cached: Option<&FileCacheEntry>) -> Option<(u64, u64)> {
         let metadata = std::fs::metadata(path).ok()?;
@@ -110,9 +668,24 @@ impl UnifiedSymbolStore {
     }
 
     /// 增量索引项目
+    ///
+    /// mtime/size 变化但内容哈希未变的文件只刷新元数据，不重新解析
     pub fn index_project(&self, project_root: &Path) -> Result<IndexStats> {
-        let root_key = project_root.to_string_lossy().to_string();
+        let root_key = crate::utils::path_key(project_root);
         let mut stats = IndexStats::default();
+        self.ensure_project_loaded(&root_key)?;
+
+        let mut upserts: Vec<(String, PersistedFile)> = Vec::new();
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut diff = RenameDiff::default();
+
+        // 配置的排除模式、文件限制与 .neurospecignore（与搜索索引保持一致，被排除的文件视为已删除）
+        let search_config = super::global::get_global_search_config().ok();
+        let excludes = search_config
+            .as_ref()
+            .and_then(|config| exclude_overrides(project_root, &config.exclude_patterns));
+        let limits = search_config.map(|config| config.file_limits).unwrap_or_default();
+        let neurospec_ignore = load_neurospec_ignore(project_root);
 
         // 获取当前缓存
         let mut projects = self.projects.write().map_err(|e| anyhow::anyhow!("{}", e))?;
@@ -121,7 +694,15 @@ impl UnifiedSymbolStore {
         // 遍历文件
         for entry in walkdir::WalkDir::new(project_root)
             .into_iter()
-            .filter_entry(|e| !is_ignored(e))
+            .filter_entry(|e| {
+                !is_ignored(e)
+                    && !excludes
+                        .as_ref()
+                        .is_some_and(|o| o.matched(e.path(), e.file_type().is_dir()).is_ignore())
+                    && !neurospec_ignore
+                        .as_ref()
+                        .is_some_and(|ignore| is_neurospec_ignored(ignore, e.path(), e.file_type().is_dir()))
+            })
             .filter_map(|e| e.ok())
             .filter(|e| e.file_type().is_file())
         {
@@ -131,82 +712,513 @@ impl UnifiedSymbolStore {
                 .unwrap_or(path)
                 .to_string_lossy()
                 .replace('\\', "/");
+            seen.insert(rel_path.clone());
 
-            let cached = cache.files.get(&rel_path);
-            
-            if let Some((mtime, size)) = self.should_reindex(path, cached) {
-                // 需要重新索引
-                if let Ok(symbols) = extract_symbols_from_file(path) {
-                    cache.files.insert(rel_path, FileCacheEntry {
-                        mtime,
-                        size,
-                        symbols,
-                    });
-                    stats.indexed += 1;
-                }
-            } else {
-                stats.skipped += 1;
+            let Some(new_entry) = self.refresh_file(path, &rel_path, cache.files.get(&rel_path), &limits, &mut stats, &mut diff) else {
+                continue;
+            };
+
+            upserts.push((rel_path.clone(), PersistedFile::from(&new_entry)));
+            cache.files.insert(rel_path, new_entry);
+        }
+
+        // 清理已删除的文件
+        let removals: Vec<String> = cache.files
+            .keys()
+            .filter(|k| !seen.contains(*k))
+            .cloned()
+            .collect();
+        for rel_path in &removals {
+            if let Some(removed) = cache.files.remove(rel_path) {
+                diff.record_file(rel_path, &removed.symbols, &[]);
             }
         }
+        stats.removed = removals.len();
+
+        let now = std::time::SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        cache.last_full_scan = Some(now);
+        if !upserts.is_empty() || !removals.is_empty() {
+            cache.name_index = OnceLock::new();
+        }
+        cache.fill_stats(&root_key, &mut stats);
 
-        // 保存缓存
         drop(projects);
-        self.save_cache()?;
+        self.commit_changes(&root_key, &upserts, &removals, diff, Some(now), &mut stats)?;
+
+        crate::app::telemetry::record_languages_indexed(stats.languages.keys().map(|l| l.as_str()));
 
         Ok(stats)
     }
 
+    /// 按文件变化增量更新项目符号，只重新解析 `changed` 中内容变化的文件（均为相对路径）
+    ///
+    /// 被忽略或排除的文件视为已删除。项目还没有完成过全量扫描时只使文件失效，
+    /// 由下次 `index_project` 统一处理
+    pub fn update_files(&self, project_root: &Path, changed: &[String], removed: &[String]) -> Result<IndexStats> {
+        let root_key = crate::utils::path_key(project_root);
+        let mut stats = IndexStats::default();
+        self.ensure_project_loaded(&root_key)?;
+
+        let search_config = super::global::get_global_search_config().ok();
+        let excludes = search_config
+            .as_ref()
+            .and_then(|config| exclude_overrides(project_root, &config.exclude_patterns));
+        let limits = search_config.map(|config| config.file_limits).unwrap_or_default();
+        let neurospec_ignore = load_neurospec_ignore(project_root);
+
+        let mut projects = self.projects.write().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let cache = projects.entry(root_key.clone()).or_default();
+        if cache.last_full_scan.is_none() {
+            // 已删除的文件直接移除其符号，避免仍能查到；其余文件只使其失效
+            let gone: Vec<String> = changed
+                .iter()
+                .chain(removed)
+                .filter(|rel_path| !project_root.join(rel_path).is_file())
+                .cloned()
+                .collect();
+            for rel_path in &gone {
+                cache.files.remove(rel_path);
+            }
+            if !gone.is_empty() {
+                cache.name_index = OnceLock::new();
+            }
+            drop(projects);
+
+            if !gone.is_empty() {
+                self.persistence.save_changes(&root_key, &[], &gone, None)?;
+            }
+            for rel_path in changed.iter().chain(removed).filter(|rel_path| !gone.contains(*rel_path)) {
+                self.invalidate_file(project_root, rel_path)?;
+            }
+            stats.removed = gone.len();
+            return Ok(stats);
+        }
+
+        let mut upserts: Vec<(String, PersistedFile)> = Vec::new();
+        let mut removals: Vec<String> = Vec::new();
+        let mut diff = RenameDiff::default();
+
+        // 与 index_project 的遍历规则一致：文件或其所在目录被跳过/排除时不再跟踪
+        let tracked = |rel_path: &str| {
+            let path = project_root.join(rel_path);
+            let ignored = Path::new(rel_path)
+                .components()
+                .any(|c| is_ignored_name(&c.as_os_str().to_string_lossy()))
+                || excludes.as_ref().is_some_and(|o| {
+                    path.ancestors()
+                        .take_while(|p| *p != project_root)
+                        .any(|p| o.matched(p, p != path).is_ignore())
+                })
+                || neurospec_ignore
+                    .as_ref()
+                    .is_some_and(|ignore| is_neurospec_ignored(ignore, &path, false));
+            path.is_file() && !ignored
+        };
+
+        for rel_path in changed.iter().chain(removed) {
+            let new_entry = if tracked(rel_path) {
+                self.refresh_file(&project_root.join(rel_path), rel_path, cache.files.get(rel_path), &limits, &mut stats, &mut diff)
+            } else {
+                if let Some(old) = cache.files.remove(rel_path) {
+                    diff.record_file(rel_path, &old.symbols, &[]);
+                    removals.push(rel_path.clone());
+                }
+                continue;
+            };
+            if let Some(new_entry) = new_entry {
+                upserts.push((rel_path.clone(), PersistedFile::from(&new_entry)));
+                cache.files.insert(rel_path.clone(), new_entry);
+            }
+        }
+        stats.removed = removals.len();
+        if !upserts.is_empty() || !removals.is_empty() {
+            cache.name_index = OnceLock::new();
+        }
+        cache.fill_stats(&root_key, &mut stats);
+
+        drop(projects);
+        self.commit_changes(&root_key, &upserts, &removals, diff, None, &mut stats)?;
+        Ok(stats)
+    }
+
+    /// 重新检查单个文件，返回新的缓存条目
+    ///
+    /// mtime/size 未变化或文件不可读时返回 None；内容哈希未变时复用已有符号
+    fn refresh_file(
+        &self,
+        path: &Path,
+        rel_path: &str,
+        cached: Option<&FileCacheEntry>,
+        limits: &FileLimits,
+        stats: &mut IndexStats,
+        diff: &mut RenameDiff,
+    ) -> Option<FileCacheEntry> {
+        let Some((mtime, size)) = self.should_reindex(path, cached) else {
+            stats.skipped += 1;
+            return None;
+        };
+
+        let entry = match detect_language(path) {
+            None => FileCacheEntry {
+                mtime,
+                size,
+                content_hash: String::new(),
+                symbols: Vec::new(),
+            },
+            Some(language) => {
+                let checked = match limits.check_size(size) {
+                    Some(reason) => Err(reason),
+                    None => {
+                        let bytes = std::fs::read(path).ok()?;
+                        limits.check_content(path, &bytes).map_or(Ok(bytes), Err)
+                    }
+                };
+                let content = match checked {
+                    Ok(bytes) => String::from_utf8(bytes).ok()?,
+                    // 超出限制的文件不解析符号，只记录元数据，内容变化后重新检查
+                    Err(reason) => {
+                        stats.skipped_files.record(reason);
+                        let old_symbols = cached.map(|c| c.symbols.as_slice()).unwrap_or(&[]);
+                        diff.record_file(rel_path, old_symbols, &[]);
+                        return Some(FileCacheEntry {
+                            mtime,
+                            size,
+                            content_hash: String::new(),
+                            symbols: Vec::new(),
+                        });
+                    }
+                };
+                let hash = content_hash(content.as_bytes());
+
+                match cached {
+                    // 内容未变（如 git checkout 只改了 mtime），复用已有符号
+                    Some(c) if !c.content_hash.is_empty() && c.content_hash == hash => {
+                        stats.skipped += 1;
+                        FileCacheEntry {
+                            mtime,
+                            size,
+                            content_hash: hash,
+                            symbols: c.symbols.clone(),
+                        }
+                    }
+                    _ => {
+                        stats.indexed += 1;
+                        let symbols = extract_symbols_from_content(path, &content, language);
+                        let old_symbols = cached.map(|c| c.symbols.as_slice()).unwrap_or(&[]);
+                        diff.record_file(rel_path, old_symbols, &symbols);
+                        FileCacheEntry {
+                            mtime,
+                            size,
+                            content_hash: hash,
+                            symbols,
+                        }
+                    }
+                }
+            }
+        };
+        Some(entry)
+    }
+
+    /// 保存变更到持久层、发布事件并记录识别出的重命名
+    fn commit_changes(
+        &self,
+        root_key: &str,
+        upserts: &[(String, PersistedFile)],
+        removals: &[String],
+        diff: RenameDiff,
+        last_full_scan: Option<u64>,
+        stats: &mut IndexStats,
+    ) -> Result<()> {
+        let now = last_full_scan.unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0)
+        });
+
+        // 保存到持久层
+        self.persistence.save_changes(root_key, upserts, removals, last_full_scan)?;
+        self.enforce_limits(root_key)?;
+
+        for (rel_path, file) in upserts {
+            emit_store_event(StoreEvent::FileIndexed {
+                project_root: root_key.to_string(),
+                path: rel_path.clone(),
+                symbol_count: file.symbols.len(),
+            });
+        }
+        for rel_path in removals {
+            emit_store_event(StoreEvent::FileRemoved {
+                project_root: root_key.to_string(),
+                path: rel_path.clone(),
+            });
+        }
+
+        // 按指纹匹配消失/新增的符号，识别重命名与移动
+        stats.renames = diff.into_renames(now);
+        if !stats.renames.is_empty() {
+            self.record_renames(root_key, &stats.renames)?;
+        }
+        Ok(())
+    }
+
     /// 使单个文件失效
+    ///
+    /// 只清除 mtime，保留旧符号与内容哈希：下次索引时会重新检查该文件，
+    /// 内容未变则复用符号，内容变化时可与旧符号比对识别重命名
     pub fn invalidate_file(&self, project_root: &Path, rel_path: &str) -> Result<()> {
-        let root_key = project_root.to_string_lossy().to_string();
-        let mut projects = self.projects.write().map_err(|e| anyhow::anyhow!("{}", e))?;
-        
-        if let Some(cache) = projects.get_mut(&root_key) {
-            cache.files.remove(rel_path);
+        let root_key = crate::utils::path_key(project_root);
+        {
+            let mut projects = self.projects.write().map_err(|e| anyhow::anyhow!("{}", e))?;
+            if let Some(cache) = projects.get_mut(&root_key) {
+                if let Some(entry) = cache.files.get_mut(rel_path) {
+                    entry.mtime = 0;
+                }
+            }
         }
-        
+
+        self.persistence.mark_stale(&root_key, rel_path)?;
         Ok(())
     }
 
-    /// 保存缓存到磁盘
-    fn save_cache(&self) -> Result<()> {
-        let projects = self.projects.read().map_err(|e| anyhow::anyhow!("{}", e))?;
-        let data = serde_json::to_string_pretty(&*projects)?;
-        std::fs::write(&self.cache_path, data)?;
+    /// 刷新持久层（变更在每次索引后已写入，这里只做 WAL 检查点）
+    pub fn flush(&self) -> Result<()> {
+        self.persistence.checkpoint()
+    }
+
+    /// 记录重命名（保留最近 `MAX_RENAME_HISTORY` 条）
+    fn record_renames(&self, root_key: &str, renames: &[SymbolRename]) -> Result<()> {
+        let mut all = self.renames.write().map_err(|e| anyhow::anyhow!("{}", e))?;
+        let history = all.entry(root_key.to_string()).or_default();
+        history.extend(renames.iter().cloned());
+        if history.len() > MAX_RENAME_HISTORY {
+            let excess = history.len() - MAX_RENAME_HISTORY;
+            history.drain(..excess);
+        }
+        drop(all);
+
+        relink_change_memories(root_key, renames);
         Ok(())
     }
+
+    /// 获取项目最近检测到的重命名/移动（按时间先后）
+    pub fn recent_renames(&self, project_root: &Path) -> Vec<SymbolRename> {
+        let root_key = crate::utils::path_key(project_root);
+        self.renames
+            .read()
+            .ok()
+            .and_then(|all| all.get(&root_key).cloned())
+            .unwrap_or_default()
+    }
+
+    /// 沿重命名链解析符号的当前名称
+    ///
+    /// 返回 None 表示没有该名称的重命名记录
+    pub fn resolve_renamed_symbol(&self, project_root: &Path, name: &str) -> Option<SymbolRename> {
+        let history = self.recent_renames(project_root);
+        let mut current: Option<SymbolRename> = None;
+        let mut lookup = name.to_string();
+
+        // 链长受历史上限约束，防止环
+        for _ in 0..history.len() {
+            let next = history
+                .iter()
+                .rev()
+                .find(|r| r.old_name == lookup && r.new_name != lookup)
+                .cloned();
+            match next {
+                Some(r) => {
+                    lookup = r.new_name.clone();
+                    current = Some(match current {
+                        Some(first) => SymbolRename {
+                            new_name: r.new_name,
+                            new_path: r.new_path,
+                            detected_at: r.detected_at,
+                            ..first
+                        },
+                        None => r,
+                    });
+                }
+                None => break,
+            }
+        }
+
+        current
+    }
+}
+
+/// 将重命名同步到项目的代码修改记忆（仅当项目已启用记忆）
+fn relink_change_memories(root_key: &str, renames: &[SymbolRename]) {
+    if !crate::config::project_memory_dir(Path::new(root_key)).exists() {
+        return;
+    }
+
+    if renames.iter().all(|r| r.old_name == r.new_name) {
+        return;
+    }
+
+    match crate::mcp::tools::memory::ChangeTracker::new(root_key)
+        .and_then(|tracker| tracker.apply_symbol_renames(renames))
+    {
+        Ok(updated) if updated > 0 => {
+            crate::log_debug!("Relinked {} change memories after symbol renames", updated);
+        }
+        Ok(_) => {}
+        Err(e) => {
+            crate::log_important!(warn, "Failed to relink change memories: {}", e);
+        }
+    }
+}
+
+/// 参与重命名配对的符号定义体最少行数，过短的定义体（空函数、getter 等）指纹容易重复
+const MIN_RENAME_BODY_LINES: u32 = 4;
+
+/// 重命名配对候选：带指纹的消失/新增符号
+struct RenameCandidate {
+    fingerprint: String,
+    kind: SymbolKind,
+    name: String,
+    path: String,
+}
+
+impl RenameCandidate {
+    fn from_symbol(symbol: &UnifiedSymbol, rel_path: &str) -> Option<Self> {
+        let fingerprint = symbol.fingerprint.as_ref()?;
+        let (start, end) = (symbol.start_line?, symbol.end_line?);
+        if end.saturating_sub(start) + 1 < MIN_RENAME_BODY_LINES {
+            return None;
+        }
+        Some(Self {
+            fingerprint: fingerprint.clone(),
+            kind: symbol.kind,
+            name: symbol.name.clone(),
+            path: rel_path.to_string(),
+        })
+    }
+}
+
+/// 单次索引中消失/新增的带指纹符号
+#[derive(Default)]
+struct RenameDiff {
+    vanished: Vec<RenameCandidate>,
+    appeared: Vec<RenameCandidate>,
+}
+
+impl RenameDiff {
+    /// 对比同一文件的新旧符号
+    fn record_file(&mut self, rel_path: &str, old: &[UnifiedSymbol], new: &[UnifiedSymbol]) {
+        let old_names: HashSet<&str> = old.iter().map(|s| s.name.as_str()).collect();
+        let new_names: HashSet<&str> = new.iter().map(|s| s.name.as_str()).collect();
+
+        self.vanished.extend(
+            old.iter()
+                .filter(|s| !new_names.contains(s.name.as_str()))
+                .filter_map(|s| RenameCandidate::from_symbol(s, rel_path)),
+        );
+        self.appeared.extend(
+            new.iter()
+                .filter(|s| !old_names.contains(s.name.as_str()))
+                .filter_map(|s| RenameCandidate::from_symbol(s, rel_path)),
+        );
+    }
+
+    /// 按指纹一一配对
+    ///
+    /// 指纹在消失和新增两侧都唯一、且符号类型相同时才视为重命名；有歧义的配对直接丢弃，
+    /// 以免误把修改记忆中的符号改写成无关符号
+    fn into_renames(self, now: u64) -> Vec<SymbolRename> {
+        let mut counts: HashMap<&str, (usize, usize)> = HashMap::new();
+        for c in &self.vanished {
+            counts.entry(c.fingerprint.as_str()).or_default().0 += 1;
+        }
+        for c in &self.appeared {
+            counts.entry(c.fingerprint.as_str()).or_default().1 += 1;
+        }
+
+        let mut renames = Vec::new();
+        for new in &self.appeared {
+            if counts.get(new.fingerprint.as_str()) != Some(&(1, 1)) {
+                continue;
+            }
+            let Some(old) = self.vanished.iter().find(|c| c.fingerprint == new.fingerprint) else {
+                continue;
+            };
+            if old.kind != new.kind || (old.name == new.name && old.path == new.path) {
+                continue;
+            }
+            renames.push(SymbolRename {
+                old_name: old.name.clone(),
+                new_name: new.name.clone(),
+                old_path: old.path.clone(),
+                new_path: new.path.clone(),
+                fingerprint: new.fingerprint.clone(),
+                detected_at: now,
+            });
+        }
+
+        renames
+    }
 }
 
 /// 索引统计
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize)]
 pub struct IndexStats {
+    /// 本次重新解析的文件数
     pub indexed: usize,
+    /// 本次跳过（未变化）的文件数
     pub skipped: usize,
+    /// 本次清理的已删除文件数
+    pub removed: usize,
+    /// 本次因文件限制（过大、二进制、压缩文件）未解析的文件数
+    pub skipped_files: SkippedFiles,
+    /// 项目根路径
+    pub project_root: String,
+    /// 文件总数
+    pub total_files: usize,
+    /// 符号总数
+    pub total_symbols: usize,
+    /// 语言 -> 文件数
+    pub languages: BTreeMap<String, usize>,
+    /// 估算内存占用（字节，仅常驻项目）
+    pub memory_bytes: usize,
+    /// 上次全量扫描时间（Unix 时间戳）
+    pub last_updated: Option<u64>,
+    /// 是否常驻内存
+    pub resident: bool,
+    /// 本次检测到的符号重命名/移动
+    pub renames: Vec<SymbolRename>,
 }
 
-/// 从文件提取符号（使用 AST 分析）
-fn extract_symbols_from_file(path: &Path) -> Result<Vec<UnifiedSymbol>> {
+/// 根据扩展名判断支持的语言
+pub(crate) fn detect_language(path: &Path) -> Option<&'static str> {
     let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("");
-    let language = match ext {
-        "rs" => "rust",
-        "ts" | "tsx" => "typescript",
-        "js" | "jsx" => "javascript",
-        "py" => "python",
-        _ => return Ok(vec![]),
-    };
-
-    let content = std::fs::read_to_string(path)?;
+    match ext {
+        "rs" => Some("rust"),
+        "ts" | "tsx" => Some("typescript"),
+        "js" | "jsx" => Some("javascript"),
+        "py" => Some("python"),
+        "c" | "h" => Some("c"),
+        "cpp" | "cc" | "cxx" | "hpp" | "hh" | "hxx" => Some("cpp"),
+        _ => None,
+    }
+}
+
+/// 从文件内容提取符号（使用 AST 分析）
+fn extract_symbols_from_content(path: &Path, content: &str, language: &str) -> Vec<UnifiedSymbol> {
     let rel_path = path.to_string_lossy().replace('\\', "/");
 
     // 使用 AST 分析器提取符号
     let ast_symbols = std::panic::catch_unwind(|| {
-        crate::neurospec::services::analyzer::analyze_file_thread_local(path, &content, language)
+        crate::neurospec::services::analyzer::analyze_file_thread_local(path, content, language)
     });
 
     match ast_symbols {
         Ok(symbols) if !symbols.is_empty() => {
             // 转换为 UnifiedSymbol 格式
-            Ok(symbols.into_iter().map(|s| UnifiedSymbol {
+            symbols.into_iter().map(|s| UnifiedSymbol {
                 kind: match s.kind {
                     crate::neurospec::models::SymbolKind::File => SymbolKind::File,
                     crate::neurospec::models::SymbolKind::Module => SymbolKind::Module,
@@ -218,13 +1230,15 @@ fn extract_symbols_from_file(path: &Path) -> Result<Vec<UnifiedSymbol>> {
                 language: s.language,
                 signature: s.signature,
                 references: s.references,
-                start_line: None,
-                end_line: None,
-            }).collect())
+                inherits: s.inherits,
+                start_line: s.start_line,
+                end_line: s.end_line,
+                fingerprint: s.fingerprint,
+            }).collect()
         }
         _ => {
             // AST 分析失败或无符号，回退到文件级符号
-            Ok(vec![UnifiedSymbol {
+            vec![UnifiedSymbol {
                 kind: SymbolKind::File,
                 name: path.file_name()
                     .and_then(|n| n.to_str())
@@ -234,26 +1248,92 @@ fn extract_symbols_from_file(path: &Path) -> Result<Vec<UnifiedSymbol>> {
                 language: Some(language.to_string()),
                 signature: None,
                 references: Vec::new(),
+                inherits: Vec::new(),
                 start_line: Some(1),
                 end_line: Some(content.lines().count() as u32),
-            }])
+                fingerprint: None,
+            }]
         }
     }
 }
 
 /// 检查是否应该忽略
 fn is_ignored(entry: &walkdir::DirEntry) -> bool {
-    entry
-        .file_name()
-        .to_str()
-        .map(|s| {
-            s.starts_with('.')
-                || s == "target"
-                || s == "node_modules"
-                || s == "dist"
-                || s == "vendor"
-                || s == "build"
-                || s == "__pycache__"
-        })
-        .unwrap_or(false)
+    entry.file_name().to_str().map(is_ignored_name).unwrap_or(false)
+}
+
+/// 遍历时跳过的文件或目录名
+fn is_ignored_name(name: &str) -> bool {
+    name.starts_with('.')
+        || name == "target"
+        || name == "node_modules"
+        || name == "dist"
+        || name == "vendor"
+        || name == "build"
+        || name == "__pycache__"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn function(name: &str, fingerprint: &str, lines: u32) -> UnifiedSymbol {
+        UnifiedSymbol {
+            kind: SymbolKind::Function,
+            name: name.to_string(),
+            path: "src/lib.rs".to_string(),
+            language: Some("rust".to_string()),
+            signature: None,
+            references: Vec::new(),
+            inherits: Vec::new(),
+            start_line: Some(10),
+            end_line: Some(10 + lines - 1),
+            fingerprint: Some(fingerprint.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_unique_fingerprint_is_rename() {
+        let mut diff = RenameDiff::default();
+        diff.record_file("src/lib.rs", &[function("parse_config", "fp1", 8)], &[function("load_config", "fp1", 8)]);
+
+        let renames = diff.into_renames(0);
+        assert_eq!(renames.len(), 1);
+        assert_eq!(renames[0].old_name, "parse_config");
+        assert_eq!(renames[0].new_name, "load_config");
+    }
+
+    #[test]
+    fn test_ambiguous_fingerprint_is_dropped() {
+        // 两侧都有重复指纹
+        let mut diff = RenameDiff::default();
+        diff.record_file(
+            "src/lib.rs",
+            &[function("a", "fp", 8), function("b", "fp", 8)],
+            &[function("c", "fp", 8), function("d", "fp", 8)],
+        );
+        assert!(diff.into_renames(0).is_empty());
+
+        // 仅新增一侧重复
+        let mut diff = RenameDiff::default();
+        diff.record_file("src/lib.rs", &[function("a", "fp", 8)], &[function("c", "fp", 8), function("d", "fp", 8)]);
+        assert!(diff.into_renames(0).is_empty());
+    }
+
+    #[test]
+    fn test_trivial_body_is_not_rename() {
+        let mut diff = RenameDiff::default();
+        diff.record_file("src/lib.rs", &[function("new", "fp", 1)], &[function("create", "fp", 1)]);
+        assert!(diff.into_renames(0).is_empty());
+    }
+
+    #[test]
+    fn test_kind_mismatch_is_not_rename() {
+        let mut class = function("Config", "fp", 8);
+        class.kind = SymbolKind::Class;
+
+        let mut diff = RenameDiff::default();
+        diff.record_file("src/lib.rs", &[function("config", "fp", 8)], &[class]);
+        assert!(diff.into_renames(0).is_empty());
+    }
 }
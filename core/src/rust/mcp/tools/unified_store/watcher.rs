@@ -1,19 +1,31 @@
 //! 文件监听器
 //!
 //! 使用 notify crate 监听文件变化，触发增量更新
-//! 包含防抖处理避免频繁更新
+//! 包含防抖与合并处理：同一路径的 Create/Modify/Remove 合并为一个事件，
+//! 静默期结束后一次性输出整批事件，避免 git checkout 等操作触发大量重复索引
+//! 事件在 notify 回调中直接按项目根目录分发到各自的队列（每个项目一个防抖批次），
+//! 并通过该项目的 watch 通道唤醒对应的处理任务，无需轮询；
+//! 长时间未被查询的项目可取消监听以控制句柄数量
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::{channel, Receiver};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use anyhow::Result;
-use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
+use ignore::gitignore::Gitignore;
+use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::watch;
 
-/// 防抖时间（毫秒）
-const DEBOUNCE_MS: u64 = 500;
+use crate::mcp::tools::acemcp::local_engine::neurospec_ignore::{
+    is_neurospec_ignored, load_neurospec_ignore, NEUROSPEC_IGNORE_FILE,
+};
+
+/// 防抖时间（毫秒）：最后一个事件之后需静默多久才输出批次
+const DEBOUNCE_MS: u64 = 300;
+
+/// 最长批次等待（毫秒）：持续有事件时也至少按此间隔输出一次，避免饥饿
+const MAX_BATCH_DELAY_MS: u64 = 5000;
 
 /// 文件变化事件
 #[derive(Debug, Clone)]
@@ -23,118 +35,335 @@ pub enum FileChangeEvent {
     Removed(PathBuf),
 }
 
-/// 文件监听器（带防抖）
+impl FileChangeEvent {
+    /// 事件对应的文件路径
+    pub fn path(&self) -> &Path {
+        match self {
+            FileChangeEvent::Created(p)
+            | FileChangeEvent::Modified(p)
+            | FileChangeEvent::Removed(p) => p,
+        }
+    }
+}
+
+/// 合并中的变化类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChangeKind {
+    Created,
+    Modified,
+    Removed,
+}
+
+impl ChangeKind {
+    /// 合并同一路径上的先后两个变化
+    fn merge(prev: ChangeKind, next: ChangeKind) -> ChangeKind {
+        use ChangeKind::*;
+        match (prev, next) {
+            // 新建后又修改，仍是新建
+            (Created, Modified) => Created,
+            // 新建后又删除：可能是临时文件，也可能是替换已有文件（删除、新建、再删除），
+            // 统一视为删除，由消费方忽略从未索引过的文件
+            (Created, Removed) => Removed,
+            // 删除后重建（编辑器原子保存、git checkout），视为修改
+            (Removed, Created) | (Removed, Modified) => Modified,
+            (Modified, Created) => Modified,
+            (_, next) => next,
+        }
+    }
+}
+
+/// 待输出的批次
+#[derive(Debug, Default)]
+struct PendingBatch {
+    changes: HashMap<PathBuf, ChangeKind>,
+    /// 批次中第一个事件的时间
+    first_event_at: Option<Instant>,
+    /// 批次中最后一个事件的时间
+    last_event_at: Option<Instant>,
+}
+
+impl PendingBatch {
+    fn record(&mut self, path: PathBuf, kind: ChangeKind, now: Instant) {
+        let merged = match self.changes.get(&path).copied() {
+            Some(prev) => ChangeKind::merge(prev, kind),
+            None => kind,
+        };
+        self.changes.insert(path, merged);
+
+        self.first_event_at.get_or_insert(now);
+        self.last_event_at = Some(now);
+    }
+
+    /// 距离批次可以输出还需等待多久（已就绪时为 0），没有待输出的事件时返回 None
+    fn ready_in(&self, now: Instant) -> Option<Duration> {
+        let (first, last) = (self.first_event_at?, self.last_event_at?);
+
+        let ready_at = (last + Duration::from_millis(DEBOUNCE_MS))
+            .min(first + Duration::from_millis(MAX_BATCH_DELAY_MS));
+        Some(ready_at.saturating_duration_since(now))
+    }
+
+    /// 批次是否可以输出
+    fn is_ready(&self, now: Instant) -> bool {
+        self.ready_in(now).is_some_and(|delay| delay.is_zero())
+    }
+
+    /// 取出整批事件，并以文件系统当前状态校正事件类型
+    fn drain(&mut self) -> Vec<FileChangeEvent> {
+        self.first_event_at = None;
+        self.last_event_at = None;
+
+        let mut events: Vec<FileChangeEvent> = self.changes
+            .drain()
+            .map(|(path, kind)| match (kind, path.exists()) {
+                (ChangeKind::Removed, true) => FileChangeEvent::Modified(path),
+                (_, false) => FileChangeEvent::Removed(path),
+                (ChangeKind::Created, true) => FileChangeEvent::Created(path),
+                (ChangeKind::Modified, true) => FileChangeEvent::Modified(path),
+            })
+            .collect();
+        events.sort_by(|a, b| a.path().cmp(b.path()));
+        events
+    }
+}
+
+/// 单个项目的事件队列
+#[derive(Debug)]
+struct ProjectQueue {
+    batch: PendingBatch,
+    /// 最近一次被查询的时间（用于空闲自动取消监听）
+    last_queried: Instant,
+    /// 内容变化计数，每记录一个变化递增一次（队列移除时发送端随之释放，订阅方据此退出）
+    changes: watch::Sender<u64>,
+    /// 项目的 .neurospecignore 规则（文件变化时重新加载）
+    ignore: Option<Gitignore>,
+}
+
+impl ProjectQueue {
+    fn new(root: &Path) -> Self {
+        Self {
+            batch: PendingBatch::default(),
+            last_queried: Instant::now(),
+            changes: watch::channel(0u64).0,
+            ignore: load_neurospec_ignore(root),
+        }
+    }
+}
+
+/// 项目根目录 -> 事件队列（notify 回调与监听器共享）
+type ProjectQueues = Arc<Mutex<HashMap<PathBuf, ProjectQueue>>>;
+
+/// 文件监听器（带防抖与合并，按项目分队列）
 pub struct FileWatcher {
     watcher: RecommendedWatcher,
-    receiver: Receiver<Result<Event, notify::Error>>,
-    watched_paths: Arc<RwLock<Vec<PathBuf>>>,
-    /// 防抖缓存：文件路径 -> 最后变化时间
-    pending_changes: Arc<RwLock<HashMap<PathBuf, Instant>>>,
+    projects: ProjectQueues,
 }
 
 impl FileWatcher {
     /// 创建新的文件监听器
     pub fn new() -> Result<Self> {
-        let (tx, rx) = channel();
-        
+        let projects: ProjectQueues = Arc::new(Mutex::new(HashMap::new()));
+        let queues = Arc::clone(&projects);
+
         let watcher = RecommendedWatcher::new(
-            move |res| {
-                let _ = tx.send(res);
+            move |res: Result<Event, notify::Error>| {
+                let Ok(event) = res else {
+                    return;
+                };
+                if let Ok(mut projects) = queues.lock() {
+                    dispatch_event(&mut projects, event, Instant::now());
+                }
             },
             Config::default().with_poll_interval(Duration::from_secs(2)),
         )?;
 
-        Ok(Self {
-            watcher,
-            receiver: rx,
-            watched_paths: Arc::new(RwLock::new(Vec::new())),
-            pending_changes: Arc::new(RwLock::new(HashMap::new())),
-        })
+        Ok(Self { watcher, projects })
     }
 
     /// 监听目录
+    ///
+    /// 项目以规范化路径为键，经符号链接或带尾部斜杠打开的同一项目只监听一次
     pub fn watch(&mut self, path: &Path) -> Result<()> {
-        self.watcher.watch(path, RecursiveMode::Recursive)?;
-        
-        let mut paths = self.watched_paths.write().map_err(|e| anyhow::anyhow!("{}", e))?;
-        paths.push(path.to_path_buf());
-        
+        let path = project_key(path);
+        let mut projects = self.projects.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        if projects.contains_key(&path) {
+            return Ok(());
+        }
+
+        self.watcher.watch(&path, RecursiveMode::Recursive)?;
+        let queue = ProjectQueue::new(&path);
+        projects.insert(path, queue);
+
         Ok(())
     }
 
-    /// 停止监听目录
+    /// 停止监听目录，并丢弃该项目尚未处理的事件
     pub fn unwatch(&mut self, path: &Path) -> Result<()> {
-        self.watcher.unwatch(path)?;
-        
-        let mut paths = self.watched_paths.write().map_err(|e| anyhow::anyhow!("{}", e))?;
-        paths.retain(|p| p != path);
-        
+        let path = project_key(path);
+        let mut projects = self.projects.lock().map_err(|e| anyhow::anyhow!("{}", e))?;
+        if projects.remove(&path).is_none() {
+            return Ok(());
+        }
+        drop(projects);
+
+        self.watcher.unwatch(&path)?;
         Ok(())
     }
 
-    /// 获取待处理的变化事件（非阻塞，带防抖）
-    /// 
-    /// 只返回超过防抖时间的事件，避免频繁更新
-    pub fn poll_events(&self) -> Vec<FileChangeEvent> {
-        let now = Instant::now();
-        let debounce_duration = Duration::from_millis(DEBOUNCE_MS);
-        
-        // 1. 收集新事件到 pending_changes
-        while let Ok(result) = self.receiver.try_recv() {
-            if let Ok(event) = result {
-                for path in event.paths {
-                    // 只处理代码文件
-                    if !is_code_file(&path) {
-                        continue;
-                    }
-                    
-                    if let Ok(mut pending) = self.pending_changes.write() {
-                        pending.insert(path, now);
-                    }
-                }
-            }
-        }
-        
-        // 2. 提取超过防抖时间的事件
-        let mut events = Vec::new();
-        let mut to_remove = Vec::new();
-        
-        if let Ok(pending) = self.pending_changes.read() {
-            for (path, last_change) in pending.iter() {
-                if now.duration_since(*last_change) >= debounce_duration {
-                    // 根据文件是否存在判断事件类型
-                    let event = if path.exists() {
-                        FileChangeEvent::Modified(path.clone())
-                    } else {
-                        FileChangeEvent::Removed(path.clone())
-                    };
-                    events.push(event);
-                    to_remove.push(path.clone());
-                }
+    /// 是否正在监听该项目
+    pub fn is_watching(&self, path: &Path) -> bool {
+        let path = project_key(path);
+        self.projects
+            .lock()
+            .map(|projects| projects.contains_key(&path))
+            .unwrap_or(false)
+    }
+
+    /// 标记项目被查询过（刷新空闲计时）
+    pub fn touch(&self, path: &Path) {
+        let path = project_key(path);
+        if let Ok(mut projects) = self.projects.lock() {
+            if let Some(queue) = projects.get_mut(&path) {
+                queue.last_queried = Instant::now();
             }
         }
-        
-        // 3. 清理已处理的事件
-        if !to_remove.is_empty() {
-            if let Ok(mut pending) = self.pending_changes.write() {
-                for path in to_remove {
-                    pending.remove(&path);
+    }
+
+    /// 返回超过 `max_idle` 未被查询的项目
+    pub fn idle_projects(&self, max_idle: Duration) -> Vec<PathBuf> {
+        let now = Instant::now();
+        self.projects
+            .lock()
+            .map(|projects| {
+                projects
+                    .iter()
+                    .filter(|(_, q)| now.duration_since(q.last_queried) >= max_idle)
+                    .map(|(p, _)| p.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 获取所有项目待处理的变化事件（非阻塞，带防抖与合并），按监听的项目根目录分组
+    /// 
+    /// 事件先在各项目批次中按路径合并，静默 `DEBOUNCE_MS` 后整批返回；
+    /// 批次未就绪的项目不出现在结果中
+    pub fn poll_events(&self) -> HashMap<PathBuf, Vec<FileChangeEvent>> {
+        let now = Instant::now();
+        let Ok(mut projects) = self.projects.lock() else {
+            return HashMap::new();
+        };
+
+        projects
+            .iter_mut()
+            .filter(|(_, queue)| queue.batch.is_ready(now))
+            .map(|(root, queue)| (root.clone(), queue.batch.drain()))
+            .filter(|(_, events)| !events.is_empty())
+            .collect()
+    }
+
+    /// 获取单个项目待处理的变化事件
+    ///
+    /// 后台处理任务取事件不算作查询，不刷新空闲计时
+    pub fn poll_project_events(&self, project_root: &Path) -> Vec<FileChangeEvent> {
+        let project_root = project_key(project_root);
+        let now = Instant::now();
+        let Ok(mut projects) = self.projects.lock() else {
+            return Vec::new();
+        };
+
+        match projects.get_mut(&project_root) {
+            Some(queue) => {
+                if queue.batch.is_ready(now) {
+                    queue.batch.drain()
+                } else {
+                    Vec::new()
                 }
             }
+            None => Vec::new(),
         }
-        
-        events
+    }
+
+    /// 订阅项目的内容变化通知，项目未被监听时返回 None
+    ///
+    /// 取消监听或监听器被释放后通道关闭
+    pub fn subscribe_changes(&self, project_root: &Path) -> Option<watch::Receiver<u64>> {
+        self.projects
+            .lock()
+            .ok()?
+            .get(&project_key(project_root))
+            .map(|queue| queue.changes.subscribe())
+    }
+
+    /// 距离项目的变化批次就绪还需等待多久（已就绪时为 0），没有待处理事件时返回 None
+    pub fn next_batch_delay(&self, project_root: &Path) -> Option<Duration> {
+        self.projects
+            .lock()
+            .ok()?
+            .get(&project_key(project_root))
+            .and_then(|queue| queue.batch.ready_in(Instant::now()))
     }
 
     /// 获取当前监听的路径
     pub fn watched_paths(&self) -> Vec<PathBuf> {
-        self.watched_paths
-            .read()
-            .map(|paths| paths.clone())
+        self.projects
+            .lock()
+            .map(|projects| projects.keys().cloned().collect())
             .unwrap_or_default()
     }
 }
 
+/// 监听表中项目的键：规范化后的根目录
+fn project_key(path: &Path) -> PathBuf {
+    crate::utils::canonical_path(path)
+}
+
+/// 把 notify 事件按项目分发并合并到对应批次，并唤醒有新变化的项目
+fn dispatch_event(projects: &mut HashMap<PathBuf, ProjectQueue>, event: Event, now: Instant) {
+    let kind = match event.kind {
+        EventKind::Create(_) => ChangeKind::Created,
+        EventKind::Modify(_) => ChangeKind::Modified,
+        EventKind::Remove(_) => ChangeKind::Removed,
+        // 访问等事件不影响内容
+        _ => return,
+    };
+
+    for path in event.paths {
+        // 只处理代码文件和忽略规则文件
+        let is_ignore_file = path.file_name().is_some_and(|name| name == NEUROSPEC_IGNORE_FILE);
+        if !is_ignore_file && !is_code_file(&path) {
+            continue;
+        }
+
+        // 嵌套项目时归属到最深的根目录
+        let Some(root) = projects
+            .keys()
+            .filter(|root| path.starts_with(root))
+            .max_by_key(|root| root.components().count())
+            .cloned()
+        else {
+            continue;
+        };
+        let Some(queue) = projects.get_mut(&root) else {
+            continue;
+        };
+
+        // 忽略规则变化后，之后的事件按新规则过滤
+        if is_ignore_file {
+            if path.parent() == Some(root.as_path()) {
+                queue.ignore = load_neurospec_ignore(&root);
+            }
+            continue;
+        }
+        if queue.ignore.as_ref().is_some_and(|ignore| is_neurospec_ignored(ignore, &path, false)) {
+            continue;
+        }
+
+        queue.batch.record(path, kind, now);
+        queue.changes.send_modify(|count| *count = count.wrapping_add(1));
+    }
+}
+
 /// 检查是否为代码文件
 fn is_code_file(path: &Path) -> bool {
     let code_extensions = ["rs", "ts", "tsx", "js", "jsx", "py", "go", "java", "c", "cpp", "h", "hpp", "vue", "svelte"];
@@ -144,3 +373,77 @@ fn is_code_file(path: &Path) -> bool {
         .map(|ext| code_extensions.contains(&ext))
         .unwrap_or(false)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replacing_existing_file_reports_removal() {
+        let now = Instant::now();
+        let path = std::env::temp_dir().join("neurospec-watcher-test-missing.rs");
+        let _ = std::fs::remove_file(&path);
+
+        // 编辑器替换已有文件后又删除：删除、新建、再删除
+        let mut batch = PendingBatch::default();
+        batch.record(path.clone(), ChangeKind::Removed, now);
+        batch.record(path.clone(), ChangeKind::Created, now);
+        batch.record(path.clone(), ChangeKind::Removed, now);
+
+        let events = batch.drain();
+        assert!(matches!(events.as_slice(), [FileChangeEvent::Removed(p)] if *p == path));
+    }
+
+    #[test]
+    fn created_then_removed_is_not_dropped() {
+        let now = Instant::now();
+        let path = std::env::temp_dir().join("neurospec-watcher-test-temp.rs");
+        let _ = std::fs::remove_file(&path);
+
+        let mut batch = PendingBatch::default();
+        batch.record(path.clone(), ChangeKind::Created, now);
+        batch.record(path.clone(), ChangeKind::Removed, now);
+
+        let events = batch.drain();
+        assert!(matches!(events.as_slice(), [FileChangeEvent::Removed(p)] if *p == path));
+    }
+
+    #[test]
+    fn merge_keeps_recreated_files_as_modified() {
+        use ChangeKind::*;
+        assert_eq!(ChangeKind::merge(Removed, Created), Modified);
+        assert_eq!(ChangeKind::merge(Created, Modified), Created);
+        assert_eq!(ChangeKind::merge(Created, Removed), Removed);
+    }
+
+    #[test]
+    fn events_belong_to_the_opened_root_not_the_git_ancestor() {
+        let now = Instant::now();
+        let repo = tempfile::tempdir().unwrap();
+        let repo_root = project_key(repo.path());
+        std::fs::create_dir(repo_root.join(".git")).unwrap();
+        let sub_root = repo_root.join("sub");
+        std::fs::create_dir(&sub_root).unwrap();
+        std::fs::write(sub_root.join("a.rs"), "").unwrap();
+        std::fs::write(repo_root.join("b.rs"), "").unwrap();
+
+        // 只以子目录打开时，事件归属子目录项目
+        let mut projects = HashMap::new();
+        projects.insert(sub_root.clone(), ProjectQueue::new(&sub_root));
+        let event = Event::new(EventKind::Modify(notify::event::ModifyKind::Any))
+            .add_path(sub_root.join("a.rs"));
+        dispatch_event(&mut projects, event, now);
+        assert_eq!(projects.get_mut(&sub_root).unwrap().batch.drain().len(), 1);
+
+        // 仓库与子目录都打开时各自接收自己的事件
+        projects.insert(repo_root.clone(), ProjectQueue::new(&repo_root));
+        let event = Event::new(EventKind::Modify(notify::event::ModifyKind::Any))
+            .add_path(sub_root.join("a.rs"))
+            .add_path(repo_root.join("b.rs"));
+        dispatch_event(&mut projects, event, now);
+        let sub_events = projects.get_mut(&sub_root).unwrap().batch.drain();
+        let repo_events = projects.get_mut(&repo_root).unwrap().batch.drain();
+        assert!(matches!(sub_events.as_slice(), [FileChangeEvent::Modified(p)] if *p == sub_root.join("a.rs")));
+        assert!(matches!(repo_events.as_slice(), [FileChangeEvent::Modified(p)] if *p == repo_root.join("b.rs")));
+    }
+}
@@ -1,6 +1,8 @@
 use chrono;
 use serde::{Deserialize, Serialize};
 
+use crate::config::OutputLanguage;
+
 // Interaction tool request (interactive dialogue with user)
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct InteractRequest {
@@ -12,6 +14,30 @@ pub struct InteractRequest {
     #[schemars(description = "Whether the message is in Markdown format, defaults to true")]
     #[serde(default = "default_is_markdown")]
     pub is_markdown: bool,
+    #[schemars(description = "Allow choosing several predefined options; the answer lists every chosen option (defaults to false, single choice)")]
+    #[serde(default)]
+    pub multi_select: bool,
+    #[schemars(description = "Constraints for the free-form answer: placeholder, regex pattern, length limits (optional)")]
+    #[serde(default)]
+    pub input: Option<InputConstraints>,
+    #[schemars(description = "Content previewed above the options, e.g. the unified diff of a proposed change or an image (optional)")]
+    #[serde(default)]
+    pub preview: Option<PopupPreview>,
+    #[schemars(description = "Open the native file/folder picker scoped to the project root instead of a popup; the message is used as the picker title (optional)")]
+    #[serde(default)]
+    pub picker: Option<PathPickerRequest>,
+    #[schemars(description = "Seconds to wait for the user before giving up (optional, 10-3600; defaults to the popup timeout in settings)")]
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    #[schemars(description = "Option returned as the answer when the user does not respond in time (optional; without it a timeout returns an error)")]
+    #[serde(default)]
+    pub default_option: Option<String>,
+    #[schemars(description = "Multi-field form (text, select, checkbox) submitted as one JSON object of field values (optional)")]
+    #[serde(default)]
+    pub form: Option<PopupForm>,
+    #[schemars(description = "Language of the returned text: 'zh' or 'en' (optional, defaults to the output language in settings)")]
+    #[serde(default)]
+    pub language: Option<OutputLanguage>,
 }
 
 
@@ -19,6 +45,344 @@ fn default_is_markdown() -> bool {
     true
 }
 
+/// 自由输入的约束：输入框提示与校验规则
+#[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct InputConstraints {
+    #[schemars(description = "Placeholder text shown in the input box")]
+    #[serde(default)]
+    pub placeholder: Option<String>,
+    #[schemars(description = "Regex the whole answer must match, e.g. '[A-Za-z_][A-Za-z0-9_]*' or '\\d+\\.\\d+\\.\\d+'")]
+    #[serde(default)]
+    pub pattern: Option<String>,
+    #[schemars(description = "Minimum answer length in characters")]
+    #[serde(default)]
+    pub min_length: Option<usize>,
+    #[schemars(description = "Maximum answer length in characters")]
+    #[serde(default)]
+    pub max_length: Option<usize>,
+    #[schemars(description = "Message shown when the answer does not match the pattern")]
+    #[serde(default)]
+    pub error_message: Option<String>,
+}
+
+impl InputConstraints {
+    /// 整体匹配的正则（前端使用相同的包裹方式）
+    fn full_match_regex(&self) -> Option<Result<regex::Regex, regex::Error>> {
+        self.pattern
+            .as_deref()
+            .map(|pattern| regex::Regex::new(&format!("^(?:{})$", pattern)))
+    }
+
+    /// 检查约束本身是否有效（正则可编译、长度范围合理）
+    pub fn check(&self) -> Result<(), String> {
+        if let Some(Err(e)) = self.full_match_regex() {
+            return Err(format!("Invalid input pattern: {}", e));
+        }
+        if let (Some(min), Some(max)) = (self.min_length, self.max_length) {
+            if min > max {
+                return Err(format!("min_length ({}) is greater than max_length ({})", min, max));
+            }
+        }
+        Ok(())
+    }
+
+    /// 校验用户输入，失败时返回展示给用户的错误信息
+    pub fn validate(&self, input: &str) -> Result<(), String> {
+        let length = input.chars().count();
+        if let Some(min) = self.min_length {
+            if length < min {
+                return Err(format!("至少需要 {} 个字符", min));
+            }
+        }
+        if let Some(max) = self.max_length {
+            if length > max {
+                return Err(format!("最多允许 {} 个字符", max));
+            }
+        }
+        if let Some(Ok(regex)) = self.full_match_regex() {
+            if !regex.is_match(input) {
+                return Err(self
+                    .error_message
+                    .clone()
+                    .unwrap_or_else(|| format!("输入格式不符合要求: {}", self.pattern.as_deref().unwrap_or_default())));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 通知级别
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NotifyLevel {
+    #[default]
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+// Notification tool request (non-blocking status message)
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct NotifyRequest {
+    #[schemars(description = "Notification text")]
+    pub message: String,
+    #[schemars(description = "Notification title (optional, defaults to the app name)")]
+    #[serde(default)]
+    pub title: Option<String>,
+    #[schemars(description = "Level: 'info' (default), 'success', 'warning' or 'error'")]
+    #[serde(default)]
+    pub level: NotifyLevel,
+    #[schemars(description = "Play a notification sound, defaults to false")]
+    #[serde(default)]
+    pub sound: bool,
+    #[schemars(description = "true forces an OS notification, false an in-app toast; by default an OS notification is used only when the app window is not focused")]
+    #[serde(default)]
+    pub system: Option<bool>,
+}
+
+/// 弹窗预览内容的大小上限（与响应图片上限一致）
+pub const MAX_PREVIEW_SIZE: usize = 10 * 1024 * 1024;
+
+/// 弹窗中的预览内容
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PopupPreview {
+    /// 统一 diff（按行着色，指定语言时语法高亮）
+    Diff {
+        #[schemars(description = "Unified diff text")]
+        diff: String,
+        #[schemars(description = "Language used for syntax highlighting, e.g. 'rust' (optional, guessed from file names)")]
+        #[serde(default)]
+        language: Option<String>,
+    },
+    /// 图片：直接提供 base64 数据，或提供项目内的图片路径
+    Image {
+        #[schemars(description = "Base64 image data (without the data: prefix)")]
+        #[serde(default)]
+        data: Option<String>,
+        #[schemars(description = "Path of an image file to load instead of data")]
+        #[serde(default)]
+        path: Option<String>,
+        #[schemars(description = "Image media type, e.g. 'image/png' (optional, guessed from the path)")]
+        #[serde(default)]
+        media_type: Option<String>,
+    },
+}
+
+impl PopupPreview {
+    /// 把图片路径读取为 base64 数据，并检查预览大小
+    pub fn resolve(self) -> Result<Self, String> {
+        match self {
+            PopupPreview::Diff { diff, language } => {
+                if diff.len() > MAX_PREVIEW_SIZE {
+                    return Err(format!("Diff preview exceeds {} bytes", MAX_PREVIEW_SIZE));
+                }
+                Ok(PopupPreview::Diff { diff, language })
+            }
+            PopupPreview::Image { data: Some(data), media_type, .. } => {
+                if data.len() > MAX_PREVIEW_SIZE {
+                    return Err(format!("Image preview exceeds {} bytes", MAX_PREVIEW_SIZE));
+                }
+                Ok(PopupPreview::Image {
+                    data: Some(data),
+                    path: None,
+                    media_type: Some(media_type.unwrap_or_else(|| "image/png".to_string())),
+                })
+            }
+            PopupPreview::Image { data: None, path: Some(path), media_type } => {
+                use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+
+                let bytes = std::fs::read(&path).map_err(|e| format!("Failed to read image {}: {}", path, e))?;
+                if bytes.len() * 4 / 3 > MAX_PREVIEW_SIZE {
+                    return Err(format!("Image preview exceeds {} bytes", MAX_PREVIEW_SIZE));
+                }
+                let media_type = media_type.unwrap_or_else(|| image_media_type(&path).to_string());
+                Ok(PopupPreview::Image {
+                    data: Some(BASE64.encode(bytes)),
+                    path: None,
+                    media_type: Some(media_type),
+                })
+            }
+            PopupPreview::Image { data: None, path: None, .. } => {
+                Err("Image preview requires either data or path".to_string())
+            }
+        }
+    }
+}
+
+/// 按扩展名推断图片类型
+fn image_media_type(path: &str) -> &'static str {
+    let extension = std::path::Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "svg" => "image/svg+xml",
+        _ => "image/png",
+    }
+}
+
+/// 原生选择器模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PickerMode {
+    /// 单个文件
+    #[default]
+    File,
+    /// 多个文件
+    Files,
+    /// 目录
+    Directory,
+}
+
+/// 原生文件/目录选择器请求
+#[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct PathPickerRequest {
+    #[schemars(description = "What to pick: 'file' (default), 'files' (several files) or 'directory'")]
+    #[serde(default)]
+    pub mode: PickerMode,
+    #[schemars(description = "File extensions to offer, e.g. ['toml', 'json'] (optional, ignored for directories)")]
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    #[schemars(description = "Project root the picker opens in and is limited to (optional, auto-detected from the working directory)")]
+    #[serde(default)]
+    pub project_root: Option<String>,
+}
+
+/// 选择器结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PickedPaths {
+    pub project_root: String,
+    /// 相对项目根目录的路径（统一为正斜杠）
+    pub paths: Vec<String>,
+    /// 位于项目根目录之外、被拒绝的路径
+    #[serde(default)]
+    pub rejected: Vec<String>,
+    /// 用户关闭了选择器
+    pub cancelled: bool,
+}
+
+/// 表单字段类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FormFieldKind {
+    /// 单行文本
+    #[default]
+    Text,
+    /// 从 options 中选择一项
+    Select,
+    /// 勾选框（布尔值）
+    Checkbox,
+}
+
+/// 表单字段
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct FormField {
+    #[schemars(description = "Key of this field in the submitted values")]
+    pub name: String,
+    #[schemars(description = "Label shown to the user (optional, defaults to name)")]
+    #[serde(default)]
+    pub label: Option<String>,
+    #[schemars(description = "Field type: 'text' (default), 'select' or 'checkbox'")]
+    #[serde(rename = "type", default)]
+    pub kind: FormFieldKind,
+    #[schemars(description = "Choices for a 'select' field")]
+    #[serde(default)]
+    pub options: Vec<String>,
+    #[schemars(description = "Whether a text/select field must be filled in, defaults to false")]
+    #[serde(default)]
+    pub required: bool,
+    #[schemars(description = "Initial value: a string for text/select, a boolean for checkbox (optional)")]
+    #[serde(default)]
+    pub default: Option<serde_json::Value>,
+    #[schemars(description = "Placeholder text for a 'text' field (optional)")]
+    #[serde(default)]
+    pub placeholder: Option<String>,
+}
+
+impl FormField {
+    fn label(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.name)
+    }
+
+    /// 检查字段值的类型与取值范围
+    fn check_value(&self, value: &serde_json::Value) -> Result<(), String> {
+        match (self.kind, value) {
+            (FormFieldKind::Checkbox, serde_json::Value::Bool(_)) => Ok(()),
+            (FormFieldKind::Checkbox, _) => Err(format!("{} 应为布尔值", self.label())),
+            (FormFieldKind::Text, serde_json::Value::String(_)) => Ok(()),
+            (FormFieldKind::Select, serde_json::Value::String(s)) if s.is_empty() || self.options.contains(s) => Ok(()),
+            (FormFieldKind::Select, serde_json::Value::String(s)) => {
+                Err(format!("{} 的取值 \"{}\" 不在可选项中", self.label(), s))
+            }
+            _ => Err(format!("{} 应为字符串", self.label())),
+        }
+    }
+}
+
+/// 多字段表单：一次交互收集多个决定
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct PopupForm {
+    #[schemars(description = "Fields in display order")]
+    pub fields: Vec<FormField>,
+}
+
+impl PopupForm {
+    /// 检查表单定义是否有效
+    pub fn check(&self) -> Result<(), String> {
+        if self.fields.is_empty() {
+            return Err("Form must have at least one field".to_string());
+        }
+        let mut names = std::collections::HashSet::new();
+        for field in &self.fields {
+            if field.name.trim().is_empty() {
+                return Err("Form field name must not be empty".to_string());
+            }
+            if !names.insert(field.name.as_str()) {
+                return Err(format!("Duplicate form field name: {}", field.name));
+            }
+            if field.kind == FormFieldKind::Select && field.options.is_empty() {
+                return Err(format!("Select field '{}' has no options", field.name));
+            }
+            if let Some(ref default) = field.default {
+                field
+                    .check_value(default)
+                    .map_err(|_| format!("Invalid default value for form field '{}'", field.name))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 校验提交的字段值，失败时返回展示给用户的错误信息
+    pub fn validate(&self, values: &serde_json::Map<String, serde_json::Value>) -> Result<(), String> {
+        if let Some(unknown) = values.keys().find(|k| !self.fields.iter().any(|f| &f.name == *k)) {
+            return Err(format!("未知的表单字段: {}", unknown));
+        }
+        for field in &self.fields {
+            let value = values.get(&field.name);
+            if let Some(value) = value {
+                field.check_value(value)?;
+            }
+            // 勾选框未勾选也是有效答案，不参与必填检查
+            let missing = match value {
+                None => true,
+                Some(serde_json::Value::String(s)) => s.trim().is_empty(),
+                Some(_) => false,
+            };
+            if field.required && field.kind != FormFieldKind::Checkbox && missing {
+                return Err(format!("请填写 {}", field.label()));
+            }
+        }
+        Ok(())
+    }
+}
+
 // Memory management tool request
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct MemoryRequest {
@@ -30,7 +394,7 @@ pub struct MemoryRequest {
     #[schemars(description = "Memory content (required for 'remember'/'update' action)")]
     #[serde(default)]
     pub content: String,
-    #[schemars(description = "Memory category: rule, preference, pattern, context")]
+    #[schemars(description = "Memory category: rule, preference, pattern, context. For 'export_rules'/'import_rules'/'sync_rules': target rule file, 'agents' (AGENTS.md, default) or 'cursor' (.cursor/rules/neurospec.mdc)")]
     #[serde(default = "default_category")]
     pub category: String,
     #[schemars(description = "Memory ID (required for 'update'/'delete' action)")]
@@ -45,6 +409,9 @@ pub struct MemoryRequest {
     #[schemars(description = "Context for smart recall (optional, improves relevance)")]
     #[serde(default)]
     pub context: Option<String>,
+    #[schemars(description = "Language of the returned text: 'zh' or 'en' (optional, defaults to the output language in settings)")]
+    #[serde(default)]
+    pub language: Option<OutputLanguage>,
 }
 
 
@@ -66,6 +433,30 @@ pub struct PopupRequest {
     pub message: String,
     pub predefined_options: Option<Vec<String>>,
     pub is_markdown: bool,
+    /// 是否允许选择多个预定义选项（默认单选）
+    #[serde(default)]
+    pub multi_select: bool,
+    /// 自由输入的约束
+    #[serde(default)]
+    pub input: Option<InputConstraints>,
+    /// 弹窗中展示的 diff 或图片预览
+    #[serde(default)]
+    pub preview: Option<PopupPreview>,
+    /// 原生文件/目录选择器（设置后不显示弹窗）
+    #[serde(default)]
+    pub picker: Option<PathPickerRequest>,
+    /// 等待用户响应的超时时间（秒），None 时使用配置中的弹窗超时
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// 超时后作为用户选择返回的默认选项
+    #[serde(default)]
+    pub default_option: Option<String>,
+    /// 多字段表单
+    #[serde(default)]
+    pub form: Option<PopupForm>,
+    /// 返回文本的语言，None 时使用配置
+    #[serde(default)]
+    pub language: Option<OutputLanguage>,
 }
 
 /// 新的结构化响应数据格式
@@ -74,6 +465,9 @@ pub struct McpResponse {
     pub user_input: Option<String>,
     pub selected_options: Vec<String>,
     pub images: Vec<ImageAttachment>,
+    /// 表单提交的字段值
+    #[serde(default)]
+    pub form_values: Option<serde_json::Map<String, serde_json::Value>>,
     pub metadata: ResponseMetadata,
 }
 
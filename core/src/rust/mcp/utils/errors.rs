@@ -1,7 +1,181 @@
 /// MCP 错误处理工具模块
 ///
-/// 提供统一的错误处理和转换功能
+/// 提供统一的错误处理和转换功能。所有工具错误和 daemon 错误都带有稳定错误码
+/// （[`ToolErrorCode`]），调用方可以按错误码分支，而不是匹配错误消息文本
 use rmcp::{model::ErrorCode, ErrorData as McpError};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// 稳定错误码（机器可解析，序列化名称跨版本不变）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ToolErrorCode {
+    /// 参数缺失或格式错误
+    InvalidParams,
+    /// 项目路径无效或不存在
+    InvalidProjectPath,
+    /// 请求的对象（符号、计划、文件等）不存在
+    NotFound,
+    /// 索引尚未就绪，正在后台构建
+    IndexNotReady,
+    /// 搜索引擎内部错误
+    SearchEngineError,
+    /// 嵌入服务未配置或不可用
+    EmbeddingUnavailable,
+    /// 编辑区间重叠、越界或与文件当前内容不一致
+    EditConflict,
+    /// 修改后的文件未通过语法校验（已回滚）
+    ValidationFailed,
+    /// 重命名的目标名称与现有符号冲突（重复定义或遮蔽）
+    RenameConflict,
+    /// 记忆库读写错误
+    MemoryError,
+    /// 弹窗创建失败
+    PopupFailed,
+    /// 等待用户响应超时
+    PopupTimeout,
+    /// 无法解析用户或 daemon 的响应
+    InvalidResponse,
+    /// Daemon 未运行或无法连接
+    DaemonUnavailable,
+    /// 文件读取/写入错误
+    IoError,
+    /// 其他内部错误
+    InternalError,
+}
+
+impl ToolErrorCode {
+    /// 序列化名称（如 `INDEX_NOT_READY`）
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InvalidParams => "INVALID_PARAMS",
+            Self::InvalidProjectPath => "INVALID_PROJECT_PATH",
+            Self::NotFound => "NOT_FOUND",
+            Self::IndexNotReady => "INDEX_NOT_READY",
+            Self::SearchEngineError => "SEARCH_ENGINE_ERROR",
+            Self::EmbeddingUnavailable => "EMBEDDING_UNAVAILABLE",
+            Self::EditConflict => "EDIT_CONFLICT",
+            Self::ValidationFailed => "VALIDATION_FAILED",
+            Self::RenameConflict => "RENAME_CONFLICT",
+            Self::MemoryError => "MEMORY_ERROR",
+            Self::PopupFailed => "POPUP_FAILED",
+            Self::PopupTimeout => "POPUP_TIMEOUT",
+            Self::InvalidResponse => "INVALID_RESPONSE",
+            Self::DaemonUnavailable => "DAEMON_UNAVAILABLE",
+            Self::IoError => "IO_ERROR",
+            Self::InternalError => "INTERNAL_ERROR",
+        }
+    }
+
+    /// 不改变请求、稍后重试是否可能成功
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::IndexNotReady
+                | Self::SearchEngineError
+                | Self::EmbeddingUnavailable
+                | Self::PopupTimeout
+                | Self::DaemonUnavailable
+                | Self::IoError
+        )
+    }
+
+    /// 对应的 JSON-RPC 错误码
+    fn rpc_code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidParams | Self::InvalidProjectPath | Self::NotFound | Self::RenameConflict => {
+                ErrorCode::INVALID_PARAMS
+            }
+            // -32000 表示 daemon 未运行，-32001 表示弹窗超时
+            Self::DaemonUnavailable => ErrorCode(-32000),
+            Self::PopupTimeout => ErrorCode(-32001),
+            _ => ErrorCode::INTERNAL_ERROR,
+        }
+    }
+}
+
+impl std::fmt::Display for ToolErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 结构化错误：工具结果、MCP 错误数据与 daemon 响应使用同一格式
+///
+/// 序列化为 `{"code": "INDEX_NOT_READY", "message": "...", "retryable": true}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, thiserror::Error)]
+#[error("{message}")]
+pub struct ToolError {
+    /// 机器可解析的错误码
+    pub code: ToolErrorCode,
+    /// 人类可读的错误消息
+    pub message: String,
+    /// 是否可重试
+    pub retryable: bool,
+}
+
+impl ToolError {
+    pub fn new(code: ToolErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            retryable: code.is_retryable(),
+        }
+    }
+
+    pub fn index_not_ready() -> Self {
+        Self::new(
+            ToolErrorCode::IndexNotReady,
+            "索引尚未就绪，正在后台构建中。请稍后重试，或使用 ripgrep 回退搜索。",
+        )
+    }
+
+    pub fn invalid_project_path(path: &str) -> Self {
+        Self::new(ToolErrorCode::InvalidProjectPath, format!("项目路径无效或不存在: {}", path))
+    }
+
+    pub fn io_error(detail: &str) -> Self {
+        Self::new(ToolErrorCode::IoError, format!("文件读取/写入错误: {}", detail))
+    }
+
+    pub fn search_engine_error(detail: &str) -> Self {
+        Self::new(ToolErrorCode::SearchEngineError, format!("搜索引擎内部错误: {}", detail))
+    }
+
+    /// 从 anyhow 错误恢复结构化错误：错误链中有 [`ToolError`] 或 [`McpToolError`] 时保留其错误码，
+    /// 否则为 `INTERNAL_ERROR`
+    pub fn from_anyhow(error: &anyhow::Error) -> Self {
+        for cause in error.chain() {
+            if let Some(tool_error) = cause.downcast_ref::<ToolError>() {
+                return tool_error.clone();
+            }
+            if let Some(mcp_error) = cause.downcast_ref::<McpToolError>() {
+                return Self::new(mcp_error.code(), error.to_string());
+            }
+        }
+        Self::new(ToolErrorCode::InternalError, error.to_string())
+    }
+
+    /// 附加在 MCP 错误上的结构化数据
+    pub fn data(&self) -> serde_json::Value {
+        serde_json::json!({ "code": self.code, "retryable": self.retryable })
+    }
+
+    /// 格式化为 JSON 字符串（用于工具结果文本）
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| {
+            serde_json::json!({ "code": "INTERNAL_ERROR", "message": self.message, "retryable": false })
+                .to_string()
+        })
+    }
+}
+
+impl From<ToolError> for McpError {
+    fn from(error: ToolError) -> Self {
+        let data = error.data();
+        McpError::new(error.code.rpc_code(), error.message, Some(data))
+    }
+}
 
 /// MCP 错误类型枚举
 #[derive(Debug, thiserror::Error)]
@@ -12,6 +186,9 @@ pub enum McpToolError {
     #[error("弹窗创建失败: {0}")]
     PopupCreation(String),
 
+    #[error("等待用户响应超时（{0} 秒）")]
+    PopupTimeout(u64),
+
     #[error("响应解析失败: {0}")]
     ResponseParsing(String),
 
@@ -30,44 +207,82 @@ pub enum McpToolError {
     #[error("无效参数: {0}")]
     InvalidParams(String),
 
+    #[error("{0}")]
+    Tool(#[from] ToolError),
+
     #[error("通用错误: {0}")]
     Generic(#[from] anyhow::Error),
 }
 
-impl From<McpToolError> for McpError {
-    fn from(error: McpToolError) -> Self {
-        match error {
-            McpToolError::ProjectPath(msg) | McpToolError::InvalidParams(msg) => {
-                McpError::invalid_params(msg, None)
-            }
-            McpToolError::DaemonConnection(msg) => {
-                // 使用 JSON-RPC 错误码 -32000 表示 daemon 未运行
-                McpError::new(ErrorCode(-32000), msg, None)
-            }
-            McpToolError::PopupCreation(msg)
-            | McpToolError::ResponseParsing(msg)
-            | McpToolError::Memory(msg) => McpError::internal_error(msg, None),
-            McpToolError::Io(e) => McpError::internal_error(format!("IO 错误: {}", e), None),
-            McpToolError::Json(e) => McpError::internal_error(format!("JSON 错误: {}", e), None),
+impl McpToolError {
+    /// 稳定错误码
+    pub fn code(&self) -> ToolErrorCode {
+        match self {
+            McpToolError::ProjectPath(_) => ToolErrorCode::InvalidProjectPath,
+            McpToolError::PopupCreation(_) => ToolErrorCode::PopupFailed,
+            McpToolError::PopupTimeout(_) => ToolErrorCode::PopupTimeout,
+            McpToolError::ResponseParsing(_) => ToolErrorCode::InvalidResponse,
+            McpToolError::Memory(_) => ToolErrorCode::MemoryError,
+            McpToolError::DaemonConnection(_) => ToolErrorCode::DaemonUnavailable,
+            McpToolError::Io(_) => ToolErrorCode::IoError,
+            McpToolError::Json(_) => ToolErrorCode::InternalError,
+            McpToolError::InvalidParams(_) => ToolErrorCode::InvalidParams,
+            McpToolError::Tool(e) => e.code,
             McpToolError::Generic(e) => {
-                // 检查是否为 daemon 连接错误
-                let error_str = e.to_string();
-                if error_str.contains("NeuroSpec Daemon not running")
-                    || error_str.contains("Failed to connect to NeuroSpec daemon")
-                {
-                    McpError::new(
-                        ErrorCode(-32000),
-                        "NeuroSpec Daemon not running".to_string(),
-                        None,
-                    )
+                if is_daemon_not_running(&e.to_string()) {
+                    ToolErrorCode::DaemonUnavailable
                 } else {
-                    McpError::internal_error(error_str, None)
+                    ToolError::from_anyhow(e).code
                 }
             }
         }
     }
 }
 
+/// 检查错误消息是否表示 daemon 未运行
+fn is_daemon_not_running(message: &str) -> bool {
+    message.contains("NeuroSpec Daemon not running") || message.contains("Failed to connect to NeuroSpec daemon")
+}
+
+impl From<McpToolError> for ToolError {
+    fn from(error: McpToolError) -> Self {
+        let code = error.code();
+        match error {
+            McpToolError::Tool(e) => e,
+            McpToolError::ProjectPath(msg)
+            | McpToolError::PopupCreation(msg)
+            | McpToolError::ResponseParsing(msg)
+            | McpToolError::Memory(msg)
+            | McpToolError::DaemonConnection(msg)
+            | McpToolError::InvalidParams(msg) => ToolError::new(code, msg),
+            McpToolError::PopupTimeout(secs) => ToolError::new(code, format!("等待用户响应超时（{} 秒）", secs)),
+            McpToolError::Io(e) => ToolError::new(code, format!("IO 错误: {}", e)),
+            McpToolError::Json(e) => ToolError::new(code, format!("JSON 错误: {}", e)),
+            McpToolError::Generic(_) if code == ToolErrorCode::DaemonUnavailable => {
+                ToolError::new(code, "NeuroSpec Daemon not running")
+            }
+            McpToolError::Generic(e) => ToolError::from_anyhow(&e),
+        }
+    }
+}
+
+impl From<McpToolError> for McpError {
+    fn from(error: McpToolError) -> Self {
+        // 弹窗超时额外附带超时时长，便于调用方区分超时与其他弹窗错误
+        let timeout_secs = match &error {
+            McpToolError::PopupTimeout(secs) => Some(*secs),
+            _ => None,
+        };
+        let error = ToolError::from(error);
+        let mut data = error.data();
+        if let Some(secs) = timeout_secs {
+            data["timed_out"] = true.into();
+            data["timeout_secs"] = secs.into();
+        }
+        McpError::new(error.code.rpc_code(), error.message, Some(data))
+    }
+}
+
 /// 创建项目路径错误
 pub fn project_path_error(msg: impl Into<String>) -> McpToolError {
     McpToolError::ProjectPath(msg.into())
@@ -78,6 +293,11 @@ pub fn popup_error(msg: impl Into<String>) -> McpToolError {
     McpToolError::PopupCreation(msg.into())
 }
 
+/// 创建弹窗超时错误
+pub fn popup_timeout_error(timeout_secs: u64) -> McpToolError {
+    McpToolError::PopupTimeout(timeout_secs)
+}
+
 /// 创建响应解析错误
 pub fn response_error(msg: impl Into<String>) -> McpToolError {
     McpToolError::ResponseParsing(msg.into())
@@ -97,3 +317,8 @@ pub fn daemon_connection_error(msg: impl Into<String>) -> McpToolError {
 pub fn invalid_params_error(msg: impl Into<String>) -> McpToolError {
     McpToolError::InvalidParams(msg.into())
 }
+
+/// 创建带错误码的错误
+pub fn coded_error(code: ToolErrorCode, msg: impl Into<String>) -> McpToolError {
+    McpToolError::Tool(ToolError::new(code, msg))
+}
@@ -0,0 +1,48 @@
+//! 工具输出语言
+//!
+//! 弹窗结果、搜索结果和记忆工具的输出文本按配置的语言生成，
+//! 单次请求可以通过 `language` 参数覆盖全局设置
+
+use std::time::Duration;
+
+use crate::config::OutputLanguage;
+use crate::utils::TtlCache;
+
+/// 重新读取配置中输出语言的间隔
+const CONFIG_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// 配置中的输出语言
+static CONFIGURED_LANGUAGE: TtlCache<OutputLanguage> = TtlCache::new(CONFIG_CACHE_TTL);
+
+/// 请求的输出语言：请求指定时优先，否则使用配置
+pub fn resolve_output_language(requested: Option<OutputLanguage>) -> OutputLanguage {
+    requested.unwrap_or_else(configured_output_language)
+}
+
+/// 设置修改后更新缓存，使本进程立即生效
+pub fn set_cached_output_language(language: OutputLanguage) {
+    CONFIGURED_LANGUAGE.set(language);
+}
+
+fn configured_output_language() -> OutputLanguage {
+    CONFIGURED_LANGUAGE.get_or_refresh(|| {
+        crate::config::load_standalone_config()
+            .map(|config| config.mcp_config.output_language)
+            .unwrap_or_default()
+    })
+}
+
+/// 按输出语言选择格式化字符串
+///
+/// ```ignore
+/// let text = tr!(lang, "找到 {} 条记忆", "Found {} memories", count);
+/// ```
+#[macro_export]
+macro_rules! tr {
+    ($lang:expr, $zh:literal, $en:literal $(, $arg:expr)* $(,)?) => {
+        match $lang {
+            $crate::config::OutputLanguage::Zh => format!($zh $(, $arg)*),
+            $crate::config::OutputLanguage::En => format!($en $(, $arg)*),
+        }
+    };
+}
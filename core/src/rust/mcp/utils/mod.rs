@@ -1,7 +1,9 @@
 pub mod common;
 pub mod errors;
+pub mod i18n;
 pub mod project;
 
 pub use common::*;
 pub use errors::*;
+pub use i18n::{resolve_output_language, set_cached_output_language};
 pub use project::{detect_project_root, detect_git_root_from, resolve_project_path};
@@ -1,6 +1,7 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 
 /// NSP (NeuroSpec Protocol) 数据模型
 ///
@@ -121,3 +122,468 @@ fn default_nsp_version() -> String {
 fn default_risk_level() -> NSPRiskLevel {
     NSPRiskLevel::Medium
 }
+
+// ============================================================================
+// JSON Schema 导出
+// ============================================================================
+
+/// 可导出 JSON Schema 的 NSP 结构
+pub const NSP_SCHEMA_PARTS: &[&str] = &["plan", "step", "meta"];
+
+/// 导出 NSP 结构的 JSON Schema（plan / step / meta），供外部 agent 与 CI 校验计划
+///
+/// 未知的结构名返回 None
+pub fn nsp_json_schema(part: &str) -> Option<serde_json::Value> {
+    let schema = match part {
+        "plan" => schemars::schema_for!(NSP),
+        "step" => schemars::schema_for!(NSPExecutionStep),
+        "meta" => schemars::schema_for!(NSPMeta),
+        _ => return None,
+    };
+    serde_json::to_value(schema).ok()
+}
+
+// ============================================================================
+// 计划校验
+// ============================================================================
+
+/// 已知的操作类型（与 NSPAction 的序列化名称一致）
+const KNOWN_ACTIONS: &[&str] = &["CREATE", "MODIFY", "DELETE", "REFACTOR", "ANALYZE"];
+
+/// 校验问题类型（机器可读）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum NSPViolationCode {
+    /// 计划不符合 NSP 结构（缺少字段、类型错误等）
+    InvalidStructure,
+    /// 步骤的 action 不是已知操作
+    UnknownAction,
+    /// execution_plan 为空
+    EmptyPlan,
+    /// 步骤 ID 重复
+    DuplicateStepId,
+    /// depends_on 引用了不存在的步骤
+    UnknownDependency,
+    /// 步骤之间存在循环依赖
+    DependencyCycle,
+    /// 修改类步骤没有指定 path
+    MissingPath,
+    /// path 指向项目根目录之外
+    PathOutsideProject,
+    /// 要修改或删除的文件不存在
+    FileNotFound,
+    /// 要创建的文件已经存在
+    FileAlreadyExists,
+    /// 修改了 context_lock 中的只读文件
+    ReadOnlyTarget,
+    /// 修改的文件不在 context_lock.target_files 中
+    NotInTargetFiles,
+    /// 多个步骤修改同一文件但没有先后依赖，或删除后仍被修改
+    ConflictingEdits,
+}
+
+/// 单条校验问题
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NSPViolation {
+    pub code: NSPViolationCode,
+    /// 相关步骤 ID（计划级问题为空）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step_id: Option<i32>,
+    /// 相关文件路径
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// 人类可读说明
+    pub message: String,
+}
+
+/// 计划校验结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct NSPValidationReport {
+    pub valid: bool,
+    pub violations: Vec<NSPViolation>,
+}
+
+impl NSPValidationReport {
+    fn from_violations(violations: Vec<NSPViolation>) -> Self {
+        Self {
+            valid: violations.is_empty(),
+            violations,
+        }
+    }
+}
+
+fn violation(code: NSPViolationCode, step_id: Option<i32>, path: Option<&str>, message: String) -> NSPViolation {
+    NSPViolation {
+        code,
+        step_id,
+        path: path.map(|p| p.to_string()),
+        message,
+    }
+}
+
+impl NSPAction {
+    /// 是否会修改文件
+    pub fn is_edit(&self) -> bool {
+        !matches!(self, NSPAction::Analyze)
+    }
+}
+
+/// 校验提交的 NSP 计划（JSON 形式）
+///
+/// 先检查反序列化时会被解析错误掩盖的未知 action，再对解析后的计划做结构校验。
+/// risk_level 由风险评估根据代码图谱计算，不要求手写
+pub fn validate_nsp_value(plan: &serde_json::Value, project_root: &Path) -> NSPValidationReport {
+    let mut violations = Vec::new();
+
+    let steps = plan.get("execution_plan").and_then(|v| v.as_array());
+    for step in steps.into_iter().flatten() {
+        let action = step.get("action").and_then(|v| v.as_str()).unwrap_or_default();
+        if !KNOWN_ACTIONS.contains(&action) {
+            let step_id = step.get("step_id").and_then(|v| v.as_i64()).map(|id| id as i32);
+            violations.push(violation(
+                NSPViolationCode::UnknownAction,
+                step_id,
+                None,
+                format!("Unknown action '{}', expected one of {}", action, KNOWN_ACTIONS.join(", ")),
+            ));
+        }
+    }
+
+    match serde_json::from_value::<NSP>(plan.clone()) {
+        Ok(nsp) => violations.extend(nsp.validate(project_root).violations),
+        // 未知 action 已单独报告，不再重复报告解析错误
+        Err(_) if violations.iter().any(|v| v.code == NSPViolationCode::UnknownAction) => {}
+        Err(e) => violations.push(violation(
+            NSPViolationCode::InvalidStructure,
+            None,
+            None,
+            format!("Plan does not match the NSP schema: {}", e),
+        )),
+    }
+
+    NSPValidationReport::from_violations(violations)
+}
+
+impl NSP {
+    /// 结构校验：步骤 ID 与依赖、目标文件、上下文锁和同文件的冲突修改
+    pub fn validate(&self, project_root: &Path) -> NSPValidationReport {
+        let mut violations = Vec::new();
+
+        if self.execution_plan.is_empty() {
+            violations.push(violation(
+                NSPViolationCode::EmptyPlan,
+                None,
+                None,
+                "execution_plan has no steps".to_string(),
+            ));
+        }
+
+        let mut step_ids = HashSet::new();
+        for step in &self.execution_plan {
+            if !step_ids.insert(step.step_id) {
+                violations.push(violation(
+                    NSPViolationCode::DuplicateStepId,
+                    Some(step.step_id),
+                    None,
+                    format!("Step id {} is used more than once", step.step_id),
+                ));
+            }
+        }
+
+        for step in &self.execution_plan {
+            for dep in &step.depends_on {
+                if !step_ids.contains(dep) {
+                    violations.push(violation(
+                        NSPViolationCode::UnknownDependency,
+                        Some(step.step_id),
+                        None,
+                        format!("Step {} depends on unknown step {}", step.step_id, dep),
+                    ));
+                }
+            }
+        }
+
+        let ancestors = self.step_ancestors();
+        for step in &self.execution_plan {
+            if ancestors.get(&step.step_id).is_some_and(|a| a.contains(&step.step_id)) {
+                violations.push(violation(
+                    NSPViolationCode::DependencyCycle,
+                    Some(step.step_id),
+                    None,
+                    format!("Step {} is part of a dependency cycle", step.step_id),
+                ));
+            }
+        }
+
+        violations.extend(self.validate_paths(project_root));
+        violations.extend(self.validate_conflicts(&ancestors));
+
+        NSPValidationReport::from_violations(violations)
+    }
+
+    /// 只检查修改类步骤的路径都在项目内
+    ///
+    /// 继续执行中断的计划时使用：已执行的步骤改变了文件，无法重新做完整校验，
+    /// 但保存的计划可能在磁盘上被修改过
+    pub fn validate_scope(&self) -> NSPValidationReport {
+        let violations = self
+            .execution_plan
+            .iter()
+            .filter(|s| s.action.is_edit())
+            .filter_map(|step| {
+                let path = step.path.as_deref()?;
+                plan_path_escapes_root(path).then(|| outside_project(step.step_id, path))
+            })
+            .collect();
+        NSPValidationReport::from_violations(violations)
+    }
+
+    /// 每个步骤直接或间接依赖的全部步骤
+    fn step_ancestors(&self) -> HashMap<i32, HashSet<i32>> {
+        let direct: HashMap<i32, &Vec<i32>> = self
+            .execution_plan
+            .iter()
+            .map(|step| (step.step_id, &step.depends_on))
+            .collect();
+
+        direct
+            .keys()
+            .map(|&id| {
+                let mut seen = HashSet::new();
+                let mut stack: Vec<i32> = direct[&id].clone();
+                while let Some(dep) = stack.pop() {
+                    if seen.insert(dep) {
+                        if let Some(next) = direct.get(&dep) {
+                            stack.extend(next.iter().copied());
+                        }
+                    }
+                }
+                (id, seen)
+            })
+            .collect()
+    }
+
+    /// 检查修改类步骤的目标文件：存在性、项目范围和上下文锁
+    fn validate_paths(&self, project_root: &Path) -> Vec<NSPViolation> {
+        let mut violations = Vec::new();
+        let read_only: Vec<String> = self.context_lock.read_only_refs.iter().map(|p| normalize_plan_path(p)).collect();
+        let targets: Vec<String> = self.context_lock.target_files.iter().map(|p| normalize_plan_path(p)).collect();
+        // 按计划顺序跟踪文件状态：计划内创建的视为已存在，删除的视为不存在
+        let mut created = HashSet::new();
+        let mut deleted = HashSet::new();
+
+        for step in self.execution_plan.iter().filter(|s| s.action.is_edit()) {
+            let Some(ref raw_path) = step.path else {
+                violations.push(violation(
+                    NSPViolationCode::MissingPath,
+                    Some(step.step_id),
+                    None,
+                    format!("Step {} ({:?}) has no target path", step.step_id, step.action),
+                ));
+                continue;
+            };
+            let path = normalize_plan_path(raw_path);
+            let id = Some(step.step_id);
+
+            if plan_path_escapes_root(raw_path) {
+                violations.push(outside_project(step.step_id, raw_path));
+                continue;
+            }
+
+            let exists = created.contains(&path)
+                || (project_root.join(&path).is_file() && !deleted.contains(&path));
+            match step.action {
+                NSPAction::Create if exists => violations.push(violation(
+                    NSPViolationCode::FileAlreadyExists,
+                    id,
+                    Some(raw_path),
+                    format!("Cannot create '{}': file already exists", raw_path),
+                )),
+                NSPAction::Create => {
+                    deleted.remove(&path);
+                    created.insert(path.clone());
+                }
+                _ if !exists => violations.push(violation(
+                    NSPViolationCode::FileNotFound,
+                    id,
+                    Some(raw_path),
+                    format!("File '{}' does not exist", raw_path),
+                )),
+                NSPAction::Delete => {
+                    created.remove(&path);
+                    deleted.insert(path.clone());
+                }
+                _ => {}
+            }
+
+            if read_only.contains(&path) {
+                violations.push(violation(
+                    NSPViolationCode::ReadOnlyTarget,
+                    id,
+                    Some(raw_path),
+                    format!("'{}' is listed in context_lock.read_only_refs", raw_path),
+                ));
+            } else if !targets.is_empty() && !targets.contains(&path) {
+                violations.push(violation(
+                    NSPViolationCode::NotInTargetFiles,
+                    id,
+                    Some(raw_path),
+                    format!("'{}' is not listed in context_lock.target_files", raw_path),
+                ));
+            }
+        }
+
+        violations
+    }
+
+    /// 检查同一文件上的冲突修改
+    ///
+    /// 修改同一文件的步骤之间必须有先后依赖；文件被删除后不能再被其它步骤修改
+    fn validate_conflicts(&self, ancestors: &HashMap<i32, HashSet<i32>>) -> Vec<NSPViolation> {
+        let mut violations = Vec::new();
+        let mut by_path: HashMap<String, Vec<&NSPExecutionStep>> = HashMap::new();
+        for step in self.execution_plan.iter().filter(|s| s.action.is_edit()) {
+            if let Some(ref path) = step.path {
+                by_path.entry(normalize_plan_path(path)).or_default().push(step);
+            }
+        }
+
+        let depends = |a: i32, b: i32| ancestors.get(&a).is_some_and(|set| set.contains(&b));
+        // 前一步删除文件后，后一步仍修改它（重新创建除外）
+        let edits_deleted = |earlier: &NSPExecutionStep, later: &NSPExecutionStep| {
+            matches!(earlier.action, NSPAction::Delete) && !matches!(later.action, NSPAction::Create)
+        };
+        for (path, steps) in &by_path {
+            for (i, first) in steps.iter().enumerate() {
+                for second in &steps[i + 1..] {
+                    let (earlier, later) = if depends(second.step_id, first.step_id) {
+                        (first, second)
+                    } else if depends(first.step_id, second.step_id) {
+                        (second, first)
+                    } else {
+                        violations.push(violation(
+                            NSPViolationCode::ConflictingEdits,
+                            Some(second.step_id),
+                            Some(path),
+                            format!(
+                                "Steps {} and {} both edit '{}' without a dependency between them",
+                                first.step_id, second.step_id, path
+                            ),
+                        ));
+                        continue;
+                    };
+                    if edits_deleted(earlier, later) {
+                        violations.push(violation(
+                            NSPViolationCode::ConflictingEdits,
+                            Some(later.step_id),
+                            Some(path),
+                            format!("Step {} edits '{}' after step {} deletes it", later.step_id, path, earlier.step_id),
+                        ));
+                    }
+                }
+            }
+        }
+
+        violations.sort_by_key(|v| v.step_id);
+        violations
+    }
+}
+
+fn outside_project(step_id: i32, path: &str) -> NSPViolation {
+    violation(
+        NSPViolationCode::PathOutsideProject,
+        Some(step_id),
+        Some(path),
+        format!("Path '{}' must be relative to the project root", path),
+    )
+}
+
+/// 计划中的路径是否可能指向项目根目录之外（绝对路径或含 `..`）
+///
+/// 按字符串判断而不依赖当前平台：Windows 盘符（`C:`）与 UNC（`\\server\share`）前缀在任何平台上都拒绝
+pub fn plan_path_escapes_root(path: &str) -> bool {
+    let path = normalize_plan_path(path);
+    let bytes = path.as_bytes();
+    let drive = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+    path.starts_with('/') || drive || Path::new(&path).is_absolute() || path.split('/').any(|part| part == "..")
+}
+
+/// 统一计划中的路径写法（分隔符、开头的 ./）
+fn normalize_plan_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    path.trim_start_matches("./").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn create_plan(path: &str) -> serde_json::Value {
+        json!({
+            "meta": { "intent_summary": "test plan" },
+            "context_lock": {},
+            "execution_plan": [{
+                "step_id": 1,
+                "title": "create",
+                "action": "CREATE",
+                "path": path,
+                "instruction": "test",
+            }],
+        })
+    }
+
+    fn codes(path: &str) -> Vec<NSPViolationCode> {
+        let dir = tempfile::tempdir().unwrap();
+        validate_nsp_value(&create_plan(path), dir.path())
+            .violations
+            .into_iter()
+            .map(|v| v.code)
+            .collect()
+    }
+
+    #[test]
+    fn relative_paths_inside_the_project_are_accepted() {
+        for path in ["src/new.rs", "./src/new.rs", "src\\new.rs", "notes..md", "a/.hidden/b.rs"] {
+            assert!(codes(path).is_empty(), "path: {}", path);
+        }
+    }
+
+    #[test]
+    fn absolute_paths_are_outside_the_project() {
+        for path in ["/etc/passwd", ".//etc/passwd", "/", "\\tmp\\x.rs"] {
+            assert_eq!(codes(path), vec![NSPViolationCode::PathOutsideProject], "path: {}", path);
+        }
+    }
+
+    #[test]
+    fn parent_segments_are_outside_the_project() {
+        for path in ["../outside.rs", "src/../../outside.rs", "src/..", "..\\outside.rs", "./../outside.rs"] {
+            assert_eq!(codes(path), vec![NSPViolationCode::PathOutsideProject], "path: {}", path);
+        }
+    }
+
+    #[test]
+    fn windows_drive_and_unc_prefixes_are_outside_the_project() {
+        for path in [
+            "C:\\Windows\\system32\\x.dll",
+            "c:/temp/x.rs",
+            "D:relative.rs",
+            "\\\\server\\share\\x.rs",
+            "//server/share/x.rs",
+            "\\\\?\\C:\\x.rs",
+        ] {
+            assert_eq!(codes(path), vec![NSPViolationCode::PathOutsideProject], "path: {}", path);
+        }
+    }
+
+    #[test]
+    fn validate_scope_only_checks_paths() {
+        let mut plan: NSP = serde_json::from_value(create_plan("../outside.rs")).unwrap();
+        assert!(!plan.validate_scope().valid);
+
+        // 已存在的文件不影响范围校验（继续执行时部分步骤已应用）
+        plan.execution_plan[0].path = Some("Cargo.toml".to_string());
+        assert!(plan.validate_scope().valid);
+    }
+}
@@ -33,9 +33,21 @@ pub struct Symbol {
     /// 可选的函数/类签名信息
     #[serde(skip_serializing_if = "Option::is_none")]
     pub signature: Option<String>,
-    /// 可选的引用信息列表，例如 ['src/api.py:42']
+    /// 可选的引用信息列表，例如 ['src/api.py:42']；文件符号为其导入的模块路径或名称（含 `#include`）
     #[serde(default)]
     pub references: Vec<String>,
+    /// 继承或实现的父类型名称（基类、接口、trait）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inherits: Vec<String>,
+    /// 定义起始行（1-based）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<u32>,
+    /// 定义结束行（1-based）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<u32>,
+    /// 定义体指纹（忽略空白与自身名称），用于识别重命名/移动
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
 }
 
 /// X-Ray快照
@@ -1,12 +1,20 @@
 //! AGENTS.md 解析器和生成器
 //!
-//! 解析和生成符合 NeuroSpec 规范的 AGENTS.md 文件
+//! 解析和生成符合 NeuroSpec 规范的 AGENTS.md 文件。
+//! 子目录中的 AGENTS.md 覆盖上层目录的规则，按与目标路径的远近合并
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::fs;
 use anyhow::{Result, Context};
 use serde::{Serialize, Deserialize};
 
+use crate::mcp::tools::acemcp::mcp::ProjectInsight;
+use crate::mcp::tools::acemcp::AcemcpTool;
+use crate::mcp::tools::memory::ai_suggester::{CodePatternAnalysis, CodePatternAnalyzer};
+
+/// 草稿中列出的 package.json 脚本数量上限
+const MAX_DRAFT_SCRIPTS: usize = 10;
+
 /// AGENTS.md 配置结构
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AgentsConfig {
@@ -170,6 +178,25 @@ impl AgentsConfig {
         Self::parse(&content)
     }
 
+    /// 加载作用于目标路径的配置
+    ///
+    /// 从项目根目录到目标路径沿途的 AGENTS.md 依次合并，离目标越近的文件优先级越高：
+    /// 它的规则排在后面，与上层重复的规则只保留它的位置
+    pub fn load_scoped(project_root: &Path, target: &Path) -> Result<Self> {
+        let mut config = Self::default_config();
+
+        for path in detect_agents_md_chain(project_root, target) {
+            let scoped = Self::load_from_file(&path)
+                .with_context(|| format!("Failed to load {}", path.display()))?;
+            for rule in scoped.custom_rules {
+                config.custom_rules.retain(|r| r != &rule);
+                config.custom_rules.push(rule);
+            }
+        }
+
+        Ok(config)
+    }
+
     /// 解析 Markdown 内容
     pub fn parse(content: &str) -> Result<Self> {
         let mut config = Self::default_config();
@@ -270,10 +297,200 @@ pub fn detect_agents_md(project_root: &Path) -> Option<std::path::PathBuf> {
     }
 }
 
+/// 项目常用命令
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyCommand {
+    pub command: String,
+    pub description: String,
+}
+
+/// 根据 Project Insight 生成 AGENTS.md 草稿
+///
+/// 在默认配置的基础上加入项目概览、模块地图、常用命令和代码约定，
+/// 草稿需要用户确认后再写入
+pub fn generate_agents_draft(project_root: &Path) -> String {
+    let insight = AcemcpTool::collect_project_insight(project_root);
+    let commands = detect_key_commands(project_root);
+    let patterns = CodePatternAnalyzer::analyze_project(&project_root.to_string_lossy())
+        .inspect_err(|e| log::warn!("Code pattern analysis failed: {}", e))
+        .ok();
+
+    let mut config = AgentsConfig::default_config();
+    if let Some(ref patterns) = patterns {
+        config.custom_rules = patterns.suggestions.iter().map(|s| s.content.clone()).collect();
+    }
+
+    let mut md = config.generate();
+    md.push_str(&project_context_markdown(&insight, &commands, patterns.as_ref()));
+    md
+}
+
+/// 草稿中的项目上下文章节
+fn project_context_markdown(
+    insight: &ProjectInsight,
+    commands: &[KeyCommand],
+    patterns: Option<&CodePatternAnalysis>,
+) -> String {
+    let mut md = String::new();
+
+    md.push_str(&format!("\n# 项目概览 ({})\n", insight.name));
+    if let Some(ref project_type) = insight.project_type {
+        md.push_str(&format!("- **类型**: {}\n", project_type));
+    }
+    let stack: Vec<&str> = insight.lang_stats.iter().take(3).map(|(l, _)| l.as_str()).collect();
+    if !stack.is_empty() {
+        md.push_str(&format!("- **技术栈**: {}\n", stack.join(", ")));
+    }
+    if !insight.external_deps.is_empty() {
+        md.push_str(&format!("- **主要依赖**: {}\n", insight.external_deps.join(", ")));
+    }
+
+    let modules: Vec<_> = insight.module_map.iter().filter(|m| m.is_dir && m.depth <= 2).collect();
+    if !modules.is_empty() {
+        md.push_str("\n# 模块地图\n");
+        for module in modules {
+            match module.description {
+                Some(ref description) => md.push_str(&format!("- `{}/` - {}\n", module.path, description)),
+                None => md.push_str(&format!("- `{}/`\n", module.path)),
+            }
+        }
+    }
+
+    if !commands.is_empty() {
+        md.push_str("\n# 常用命令\n");
+        for command in commands {
+            md.push_str(&format!("- `{}` - {}\n", command.command, command.description));
+        }
+    }
+
+    if let Some(patterns) = patterns {
+        let mut conventions = Vec::new();
+        if let Some(ref naming) = patterns.naming_convention {
+            conventions.push(format!("- **命名规范**: {:?}", naming));
+        }
+        if let Some(ref error_handling) = patterns.error_handling {
+            conventions.push(format!("- **错误处理**: {:?}", error_handling));
+        }
+        if let Some(ref logging) = patterns.logging_style {
+            conventions.push(format!("- **日志**: {}", logging));
+        }
+        if !conventions.is_empty() {
+            md.push_str("\n# 代码约定\n");
+            md.push_str(&conventions.join("\n"));
+            md.push('\n');
+        }
+    }
+
+    md
+}
+
+/// 从 Cargo.toml / package.json / pyproject.toml 推断常用命令
+pub fn detect_key_commands(project_root: &Path) -> Vec<KeyCommand> {
+    let command = |command: &str, description: &str| KeyCommand {
+        command: command.to_string(),
+        description: description.to_string(),
+    };
+    let mut commands = Vec::new();
+
+    if project_root.join("Cargo.toml").exists() {
+        commands.push(command("cargo build", "构建"));
+        commands.push(command("cargo test", "运行测试"));
+        commands.push(command("cargo clippy --all-targets -- -D warnings", "静态检查"));
+    }
+
+    let scripts = fs::read_to_string(project_root.join("package.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|json| json.get("scripts").and_then(|s| s.as_object()).cloned());
+    if let Some(scripts) = scripts {
+        let runner = if project_root.join("pnpm-lock.yaml").exists() {
+            "pnpm"
+        } else if project_root.join("yarn.lock").exists() {
+            "yarn"
+        } else {
+            "npm run"
+        };
+        for (name, script) in scripts.iter().take(MAX_DRAFT_SCRIPTS) {
+            commands.push(command(&format!("{} {}", runner, name), script.as_str().unwrap_or_default()));
+        }
+    }
+
+    let pyproject = fs::read_to_string(project_root.join("pyproject.toml")).unwrap_or_default();
+    if pyproject.contains("pytest") {
+        commands.push(command("pytest", "运行测试"));
+    }
+
+    commands
+}
+
+/// 收集从项目根目录到目标路径沿途的 AGENTS.md（根目录在前，离目标最近的在后）
+///
+/// `target` 可以是文件或目录，相对路径按项目根目录解析；不在项目内时只检查根目录
+pub fn detect_agents_md_chain(project_root: &Path, target: &Path) -> Vec<PathBuf> {
+    let target = if target.is_absolute() {
+        target.to_path_buf()
+    } else {
+        project_root.join(target)
+    };
+    let relative = target.strip_prefix(project_root).unwrap_or(Path::new(""));
+
+    let mut dir = project_root.to_path_buf();
+    let mut files: Vec<PathBuf> = detect_agents_md(&dir).into_iter().collect();
+    for component in relative.components() {
+        dir.push(component);
+        if !dir.is_dir() {
+            break;
+        }
+        files.extend(detect_agents_md(&dir));
+    }
+    files
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_detect_key_commands() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("package.json"), r#"{ "scripts": { "build": "vite build", "lint": "eslint ." } }"#).unwrap();
+        fs::write(root.path().join("pnpm-lock.yaml"), "").unwrap();
+
+        let commands = detect_key_commands(root.path());
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].command, "pnpm build");
+        assert_eq!(commands[0].description, "vite build");
+
+        fs::write(root.path().join("Cargo.toml"), "[package]\nname = \"demo\"\n").unwrap();
+        let commands = detect_key_commands(root.path());
+        assert_eq!(commands[0].command, "cargo build");
+        assert_eq!(commands.len(), 5);
+    }
+
+    #[test]
+    fn test_load_scoped_merges_by_proximity() {
+        let root = tempfile::tempdir().unwrap();
+        let nested = root.path().join("crates/api");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.path().join("AGENTS.md"), "✅请记住 使用 4 空格缩进\n✅请记住 提交前运行测试\n").unwrap();
+        fs::write(root.path().join("crates/AGENTS.md"), "✅请记住 使用 4 空格缩进\n").unwrap();
+        fs::write(nested.join("AGENTS.md"), "❌请记住 不要修改公开 API\n").unwrap();
+
+        let chain = detect_agents_md_chain(root.path(), Path::new("crates/api/src/lib.rs"));
+        assert_eq!(chain.len(), 3);
+        assert_eq!(chain[0], root.path().join("AGENTS.md"));
+        assert_eq!(chain[2], nested.join("AGENTS.md"));
+
+        let config = AgentsConfig::load_scoped(root.path(), Path::new("crates/api/src/lib.rs")).unwrap();
+        assert_eq!(
+            config.custom_rules,
+            vec!["请记住 提交前运行测试", "请记住 使用 4 空格缩进", "请记住 不要修改公开 API"]
+        );
+
+        let config = AgentsConfig::load_scoped(root.path(), Path::new("docs")).unwrap();
+        assert_eq!(config.custom_rules.len(), 2);
+    }
+
     #[test]
     fn test_default_config() {
         let config = AgentsConfig::default_config();
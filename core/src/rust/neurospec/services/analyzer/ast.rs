@@ -1,9 +1,10 @@
 use log::{debug, warn};
 use std::cell::RefCell;
 use std::path::Path;
-use tree_sitter::{Language, Parser, Query, QueryCursor, StreamingIterator};
+use tree_sitter::{Language, Node, Parser, Query, QueryCursor, QueryError, StreamingIterator};
 
 use crate::neurospec::models::{Symbol, SymbolKind};
+use super::fingerprint::symbol_fingerprint;
 
 extern "C" {
     fn tree_sitter_rust() -> Language;
@@ -15,15 +16,25 @@ extern "C" {
     fn tree_sitter_python() -> Language;
 }
 
+/// A definition found in the current file and the byte range it covers
+struct DefInfo {
+    symbol: Symbol,
+    range: std::ops::Range<usize>,
+}
+
 /// AST-based code analyzer using tree-sitter
 pub struct AstAnalyzer {
     rust_parser: Parser,
     typescript_parser: Parser,
     python_parser: Parser,
+    c_parser: Parser,
+    cpp_parser: Parser,
 
     rust_lang: Language,
     typescript_lang: Language,
     python_lang: Language,
+    c_lang: Language,
+    cpp_lang: Language,
 }
 
 impl AstAnalyzer {
@@ -32,6 +43,8 @@ impl AstAnalyzer {
         let rust_lang = unsafe { tree_sitter_rust() };
         let typescript_lang = unsafe { tree_sitter_typescript() };
         let python_lang = unsafe { tree_sitter_python() };
+        let c_lang: Language = tree_sitter_c::LANGUAGE.into();
+        let cpp_lang: Language = tree_sitter_cpp::LANGUAGE.into();
 
         let mut rust_parser = Parser::new();
         rust_parser
@@ -48,13 +61,27 @@ impl AstAnalyzer {
             .set_language(&python_lang)
             .map_err(|e| format!("Failed to set Python language: {}", e))?;
 
+        let mut c_parser = Parser::new();
+        c_parser
+            .set_language(&c_lang)
+            .map_err(|e| format!("Failed to set C language: {}", e))?;
+
+        let mut cpp_parser = Parser::new();
+        cpp_parser
+            .set_language(&cpp_lang)
+            .map_err(|e| format!("Failed to set C++ language: {}", e))?;
+
         Ok(Self {
             rust_parser,
             typescript_parser,
             python_parser,
+            c_parser,
+            cpp_parser,
             rust_lang,
             typescript_lang,
             python_lang,
+            c_lang,
+            cpp_lang,
         })
     }
 
@@ -66,6 +93,7 @@ impl AstAnalyzer {
             "rust" => self.analyze_rust(&rel_path, content),
             "typescript" | "javascript" => self.analyze_typescript(&rel_path, content),
             "python" => self.analyze_python(&rel_path, content),
+            "c" | "cpp" => self.analyze_c_family(&rel_path, content, language),
             _ => Vec::new(),
         }
     }
@@ -88,6 +116,7 @@ impl AstAnalyzer {
             (struct_item name: (type_identifier) @struct.name) @struct.def
             (enum_item name: (type_identifier) @enum.name) @enum.def
             (function_item name: (identifier) @function.name) @function.def
+            (trait_item name: (type_identifier) @trait.name) @trait.def
             (impl_item type: (_) @impl.type) @impl.def
         "#;
 
@@ -103,10 +132,6 @@ impl AstAnalyzer {
         let mut matches = cursor.matches(&def_query, root_node, content.as_bytes());
 
         // Store definitions with their ranges
-        struct DefInfo {
-            symbol: Symbol,
-            range: std::ops::Range<usize>,
-        }
         let mut definitions: Vec<DefInfo> = Vec::new();
 
         while let Some(match_) = matches.next() {
@@ -119,7 +144,9 @@ impl AstAnalyzer {
                 }
 
                 let text = node.utf8_text(content.as_bytes()).unwrap_or("").to_string();
-                let kind = if capture_name.starts_with("struct") || capture_name.starts_with("enum")
+                let kind = if capture_name.starts_with("struct")
+                    || capture_name.starts_with("enum")
+                    || capture_name.starts_with("trait")
                 {
                     SymbolKind::Class
                 } else if capture_name.starts_with("function") {
@@ -138,6 +165,11 @@ impl AstAnalyzer {
                     .ok()
                     .and_then(|s| s.lines().next().map(|l| l.trim().to_string()));
 
+                let fingerprint = def_node
+                    .utf8_text(content.as_bytes())
+                    .ok()
+                    .map(|body| symbol_fingerprint(body, &text));
+
                 definitions.push(DefInfo {
                     symbol: Symbol {
                         kind,
@@ -146,6 +178,10 @@ impl AstAnalyzer {
                         language: Some("rust".to_string()),
                         signature,
                         references: Vec::new(),
+                        inherits: Vec::new(),
+                        start_line: Some(def_node.start_position().row as u32 + 1),
+                        end_line: Some(def_node.end_position().row as u32 + 1),
+                        fingerprint,
                     },
                     range,
                 });
@@ -197,12 +233,63 @@ impl AstAnalyzer {
             }
         }
 
+        // 3. Extract Trait Impls (`impl Trait for Type` makes Type inherit Trait)
+        let impl_query_str = r#"
+            (impl_item trait: (_) @impl.trait type: (_) @impl.type)
+        "#;
+
+        match Query::new(&self.rust_lang, impl_query_str) {
+            Ok(impl_query) => {
+                let mut cursor = QueryCursor::new();
+                let mut matches = cursor.matches(&impl_query, root_node, content.as_bytes());
+
+                while let Some(match_) = matches.next() {
+                    let mut trait_name = "";
+                    let mut type_name = "";
+                    for capture in match_.captures {
+                        let name = base_type_name(capture.node, content);
+                        if impl_query.capture_names()[capture.index as usize] == "impl.trait" {
+                            trait_name = name;
+                        } else {
+                            type_name = name;
+                        }
+                    }
+
+                    // Only types defined in this file can be linked
+                    if let Some(def) = definitions.iter_mut().find(|d| {
+                        matches!(d.symbol.kind, SymbolKind::Class) && d.symbol.name == type_name
+                    }) {
+                        if !trait_name.is_empty() && !def.symbol.inherits.iter().any(|t| t == trait_name) {
+                            def.symbol.inherits.push(trait_name.to_string());
+                        }
+                    }
+                }
+            }
+            Err(e) => warn!("Failed to create Rust impl query: {}", e),
+        }
+
+        // 4. Extract Imports
+        let mut imports = Vec::new();
+        match Query::new(&self.rust_lang, "(use_declaration argument: (_) @import)") {
+            Ok(import_query) => {
+                let mut cursor = QueryCursor::new();
+                let mut matches = cursor.matches(&import_query, root_node, content.as_bytes());
+
+                while let Some(match_) = matches.next() {
+                    for capture in match_.captures {
+                        collect_use_names(capture.node, content, &mut imports);
+                    }
+                }
+            }
+            Err(e) => warn!("Failed to create Rust import query: {}", e),
+        }
+
         debug!(
             "Extracted {} symbols from Rust file: {}",
             definitions.len(),
             path
         );
-        definitions.into_iter().map(|d| d.symbol).collect()
+        with_file_symbol(path, content, "rust", imports, definitions)
     }
 
     /// Analyze TypeScript/JavaScript code
@@ -237,10 +324,6 @@ impl AstAnalyzer {
         let mut cursor = QueryCursor::new();
         let mut matches = cursor.matches(&def_query, root_node, content.as_bytes());
 
-        struct DefInfo {
-            symbol: Symbol,
-            range: std::ops::Range<usize>,
-        }
         let mut definitions: Vec<DefInfo> = Vec::new();
 
         while let Some(match_) = matches.next() {
@@ -265,6 +348,11 @@ impl AstAnalyzer {
                     .ok()
                     .and_then(|s| s.lines().next().map(|l| l.trim().to_string()));
 
+                let fingerprint = def_node
+                    .utf8_text(content.as_bytes())
+                    .ok()
+                    .map(|body| symbol_fingerprint(body, &text));
+
                 definitions.push(DefInfo {
                     symbol: Symbol {
                         kind,
@@ -273,6 +361,10 @@ impl AstAnalyzer {
                         language: Some("typescript".to_string()),
                         signature,
                         references: Vec::new(),
+                        inherits: Vec::new(),
+                        start_line: Some(def_node.start_position().row as u32 + 1),
+                        end_line: Some(def_node.end_position().row as u32 + 1),
+                        fingerprint,
                     },
                     range,
                 });
@@ -322,12 +414,40 @@ impl AstAnalyzer {
             }
         }
 
+        // 3. Extract Inheritance
+        let inherit_query_str = r#"
+            (extends_clause value: (identifier) @inherit.name)
+            (extends_clause value: (member_expression property: (property_identifier) @inherit.name))
+            (implements_clause (type_identifier) @inherit.name)
+            (extends_type_clause type: (type_identifier) @inherit.name)
+        "#;
+
+        match query_captures(&self.typescript_lang, inherit_query_str, root_node, content) {
+            Ok(captures) => attach_inherits(&mut definitions, captures),
+            Err(e) => warn!("Failed to create TypeScript inherit query: {}", e),
+        }
+
+        // 4. Extract Imports (module specifiers and imported names)
+        let import_query_str = r#"
+            (import_statement source: (string (string_fragment) @import.source))
+            (import_specifier name: (identifier) @import.name)
+            (import_clause (identifier) @import.default)
+        "#;
+
+        let imports = match query_captures(&self.typescript_lang, import_query_str, root_node, content) {
+            Ok(captures) => captures.into_iter().map(|(_, text, _)| text).collect(),
+            Err(e) => {
+                warn!("Failed to create TypeScript import query: {}", e);
+                Vec::new()
+            }
+        };
+
         debug!(
             "Extracted {} symbols from TypeScript file: {}",
             definitions.len(),
             path
         );
-        definitions.into_iter().map(|d| d.symbol).collect()
+        with_file_symbol(path, content, "typescript", imports, definitions)
     }
 
     /// Analyze Python code
@@ -359,10 +479,6 @@ impl AstAnalyzer {
         let mut cursor = QueryCursor::new();
         let mut matches = cursor.matches(&def_query, root_node, content.as_bytes());
 
-        struct DefInfo {
-            symbol: Symbol,
-            range: std::ops::Range<usize>,
-        }
         let mut definitions: Vec<DefInfo> = Vec::new();
 
         while let Some(match_) = matches.next() {
@@ -385,6 +501,11 @@ impl AstAnalyzer {
                     .ok()
                     .and_then(|s| s.lines().next().map(|l| l.trim().to_string()));
 
+                let fingerprint = def_node
+                    .utf8_text(content.as_bytes())
+                    .ok()
+                    .map(|body| symbol_fingerprint(body, &text));
+
                 definitions.push(DefInfo {
                     symbol: Symbol {
                         kind,
@@ -393,6 +514,10 @@ impl AstAnalyzer {
                         language: Some("python".to_string()),
                         signature,
                         references: Vec::new(),
+                        inherits: Vec::new(),
+                        start_line: Some(def_node.start_position().row as u32 + 1),
+                        end_line: Some(def_node.end_position().row as u32 + 1),
+                        fingerprint,
                     },
                     range,
                 });
@@ -441,12 +566,377 @@ impl AstAnalyzer {
             }
         }
 
+        // 3. Extract Inheritance
+        let inherit_query_str = r#"
+            (class_definition superclasses: (argument_list (identifier) @inherit.name))
+            (class_definition superclasses: (argument_list (attribute attribute: (identifier) @inherit.name)))
+        "#;
+
+        match query_captures(&self.python_lang, inherit_query_str, root_node, content) {
+            Ok(captures) => attach_inherits(&mut definitions, captures),
+            Err(e) => warn!("Failed to create Python inherit query: {}", e),
+        }
+
+        // 4. Extract Imports (dotted module paths and imported names)
+        let import_query_str = r#"
+            (import_statement name: (dotted_name) @import.module)
+            (import_statement name: (aliased_import name: (dotted_name) @import.module))
+            (import_from_statement module_name: (_) @import.module)
+            (import_from_statement name: (dotted_name) @import.name)
+            (import_from_statement name: (aliased_import name: (dotted_name) @import.name))
+        "#;
+
+        let imports = match query_captures(&self.python_lang, import_query_str, root_node, content) {
+            Ok(captures) => captures.into_iter().map(|(_, text, _)| text).collect(),
+            Err(e) => {
+                warn!("Failed to create Python import query: {}", e);
+                Vec::new()
+            }
+        };
+
         debug!(
             "Extracted {} symbols from Python file: {}",
             definitions.len(),
             path
         );
-        definitions.into_iter().map(|d| d.symbol).collect()
+        with_file_symbol(path, content, "python", imports, definitions)
+    }
+    /// Analyze C/C++ code
+    ///
+    /// Besides functions and types, emits one `File` symbol per file whose
+    /// `references` are the `#include` paths, so the graph builder can turn
+    /// them into `Imports` edges between files.
+    fn analyze_c_family(&mut self, path: &str, content: &str, language: &str) -> Vec<Symbol> {
+        let is_cpp = language == "cpp";
+        let (parser, lang, display_name) = if is_cpp {
+            (&mut self.cpp_parser, &self.cpp_lang, "C++")
+        } else {
+            (&mut self.c_parser, &self.c_lang, "C")
+        };
+
+        let tree = match parser.parse(content, None) {
+            Some(t) => t,
+            None => {
+                warn!("Failed to parse {} file: {}", display_name, path);
+                return Vec::new();
+            }
+        };
+
+        let root_node = tree.root_node();
+
+        // 1. Extract Definitions
+        let mut def_query_str = String::from(
+            r#"
+            (function_definition
+                declarator: (function_declarator declarator: (identifier) @function.name))
+            (function_definition
+                declarator: (pointer_declarator
+                    declarator: (function_declarator declarator: (identifier) @function.name)))
+            (struct_specifier name: (type_identifier) @struct.name body: (field_declaration_list))
+            (union_specifier name: (type_identifier) @struct.name body: (field_declaration_list))
+            (enum_specifier name: (type_identifier) @enum.name body: (enumerator_list))
+        "#,
+        );
+        if is_cpp {
+            def_query_str.push_str(
+                r#"
+            (function_definition
+                declarator: (function_declarator declarator: (field_identifier) @function.name))
+            (function_definition
+                declarator: (function_declarator
+                    declarator: (qualified_identifier name: (identifier) @function.name)))
+            (function_definition
+                declarator: (reference_declarator
+                    (function_declarator declarator: [(identifier) (field_identifier)] @function.name)))
+            (class_specifier name: (type_identifier) @class.name body: (field_declaration_list))
+        "#,
+            );
+        }
+
+        let def_query = match Query::new(lang, &def_query_str) {
+            Ok(q) => q,
+            Err(e) => {
+                warn!("Failed to create {} def query: {}", display_name, e);
+                return Vec::new();
+            }
+        };
+
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(&def_query, root_node, content.as_bytes());
+
+        let mut definitions: Vec<DefInfo> = Vec::new();
+
+        while let Some(match_) = matches.next() {
+            for capture in match_.captures {
+                let capture_name = &def_query.capture_names()[capture.index as usize];
+                let node = capture.node;
+                let text = node.utf8_text(content.as_bytes()).unwrap_or("").to_string();
+
+                let kind = if capture_name.starts_with("function") {
+                    SymbolKind::Function
+                } else {
+                    SymbolKind::Class
+                };
+
+                // Function names sit inside nested declarators, so walk up to the definition itself
+                let mut def_node = node.parent().unwrap_or(node);
+                if matches!(kind, SymbolKind::Function) {
+                    while def_node.kind() != "function_definition" {
+                        match def_node.parent() {
+                            Some(parent) => def_node = parent,
+                            None => break,
+                        }
+                    }
+                }
+                let range = def_node.start_byte()..def_node.end_byte();
+
+                let signature = def_node
+                    .utf8_text(content.as_bytes())
+                    .ok()
+                    .and_then(|s| s.lines().next().map(|l| l.trim().to_string()));
+
+                let fingerprint = def_node
+                    .utf8_text(content.as_bytes())
+                    .ok()
+                    .map(|body| symbol_fingerprint(body, &text));
+
+                definitions.push(DefInfo {
+                    symbol: Symbol {
+                        kind,
+                        name: text,
+                        path: path.to_string(),
+                        language: Some(language.to_string()),
+                        signature,
+                        references: Vec::new(),
+                        inherits: Vec::new(),
+                        start_line: Some(def_node.start_position().row as u32 + 1),
+                        end_line: Some(def_node.end_position().row as u32 + 1),
+                        fingerprint,
+                    },
+                    range,
+                });
+            }
+        }
+
+        // 2. Extract Calls
+        let mut call_query_str = String::from(
+            r#"
+            (call_expression function: (identifier) @call.name)
+            (call_expression function: (field_expression field: (field_identifier) @call.method))
+        "#,
+        );
+        if is_cpp {
+            call_query_str.push_str(
+                r#"
+            (call_expression function: (qualified_identifier name: (identifier) @call.qualified))
+            (new_expression type: (type_identifier) @call.new)
+        "#,
+            );
+        }
+
+        match Query::new(lang, &call_query_str) {
+            Ok(call_query) => {
+                let mut cursor = QueryCursor::new();
+                let mut matches = cursor.matches(&call_query, root_node, content.as_bytes());
+
+                while let Some(match_) = matches.next() {
+                    for capture in match_.captures {
+                        let node = capture.node;
+                        let call_name = node.utf8_text(content.as_bytes()).unwrap_or("").to_string();
+                        let call_pos = node.start_byte();
+
+                        let mut best_def_idx = None;
+                        let mut min_len = usize::MAX;
+
+                        for (i, def) in definitions.iter().enumerate() {
+                            if def.range.contains(&call_pos) {
+                                let len = def.range.len();
+                                if len < min_len {
+                                    min_len = len;
+                                    best_def_idx = Some(i);
+                                }
+                            }
+                        }
+
+                        if let Some(idx) = best_def_idx {
+                            definitions[idx].symbol.references.push(call_name);
+                        }
+                    }
+                }
+            }
+            Err(e) => warn!("Failed to create {} call query: {}", display_name, e),
+        }
+
+        // 3. Extract Inheritance (C++ base classes)
+        if is_cpp {
+            let inherit_query_str = r#"
+                (base_class_clause (type_identifier) @inherit.name)
+                (base_class_clause (qualified_identifier name: (type_identifier) @inherit.name))
+            "#;
+
+            match query_captures(lang, inherit_query_str, root_node, content) {
+                Ok(captures) => attach_inherits(&mut definitions, captures),
+                Err(e) => warn!("Failed to create {} inherit query: {}", display_name, e),
+            }
+        }
+
+        // 4. Extract Includes
+        let include_query_str = r#"
+            (preproc_include path: [(string_literal) (system_lib_string)] @include.path)
+        "#;
+
+        let includes = match query_captures(lang, include_query_str, root_node, content) {
+            Ok(captures) => captures
+                .into_iter()
+                .map(|(_, text, _)| text.trim_matches(|c| c == '"' || c == '<' || c == '>').trim().to_string())
+                .collect(),
+            Err(e) => {
+                warn!("Failed to create {} include query: {}", display_name, e);
+                Vec::new()
+            }
+        };
+
+        debug!(
+            "Extracted {} symbols from {} file: {}",
+            definitions.len(),
+            display_name,
+            path
+        );
+        with_file_symbol(path, content, language, includes, definitions)
+    }
+}
+
+/// Prepend a `File` symbol whose `references` are the file's imports
+///
+/// The graph builder resolves these into `Imports` edges, either to the
+/// imported file or to the imported symbol.
+fn with_file_symbol(
+    path: &str,
+    content: &str,
+    language: &str,
+    imports: Vec<String>,
+    definitions: Vec<DefInfo>,
+) -> Vec<Symbol> {
+    let mut references: Vec<String> = Vec::with_capacity(imports.len());
+    for import in imports {
+        if !import.is_empty() && !references.contains(&import) {
+            references.push(import);
+        }
+    }
+
+    let file_name = Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(path)
+        .to_string();
+
+    let mut symbols = Vec::with_capacity(definitions.len() + 1);
+    symbols.push(Symbol {
+        kind: SymbolKind::File,
+        name: file_name,
+        path: path.to_string(),
+        language: Some(language.to_string()),
+        signature: None,
+        references,
+        inherits: Vec::new(),
+        start_line: Some(1),
+        end_line: Some(content.lines().count().max(1) as u32),
+        fingerprint: None,
+    });
+    symbols.extend(definitions.into_iter().map(|d| d.symbol));
+    symbols
+}
+
+/// Run a query and return every capture as (capture name, text, start byte)
+fn query_captures(
+    lang: &Language,
+    query_str: &str,
+    root_node: Node,
+    content: &str,
+) -> Result<Vec<(String, String, usize)>, QueryError> {
+    let query = Query::new(lang, query_str)?;
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(&query, root_node, content.as_bytes());
+
+    let mut captures = Vec::new();
+    while let Some(match_) = matches.next() {
+        for capture in match_.captures {
+            captures.push((
+                query.capture_names()[capture.index as usize].to_string(),
+                capture.node.utf8_text(content.as_bytes()).unwrap_or("").to_string(),
+                capture.node.start_byte(),
+            ));
+        }
+    }
+    Ok(captures)
+}
+
+/// Index of the smallest definition containing `pos`
+fn innermost_definition(definitions: &[DefInfo], pos: usize) -> Option<usize> {
+    definitions
+        .iter()
+        .enumerate()
+        .filter(|(_, def)| def.range.contains(&pos))
+        .min_by_key(|(_, def)| def.range.len())
+        .map(|(i, _)| i)
+}
+
+/// Attach base type names to the class whose heritage clause contains them
+fn attach_inherits(definitions: &mut [DefInfo], captures: Vec<(String, String, usize)>) {
+    for (_, name, pos) in captures {
+        if let Some(idx) = innermost_definition(definitions, pos) {
+            let symbol = &mut definitions[idx].symbol;
+            if matches!(symbol.kind, SymbolKind::Class) && !symbol.inherits.contains(&name) {
+                symbol.inherits.push(name);
+            }
+        }
+    }
+}
+
+/// Collect the names a Rust `use` tree brings into scope (`a::{b, c as d}` → `b`, `c`)
+fn collect_use_names(node: Node, content: &str, names: &mut Vec<String>) {
+    match node.kind() {
+        "identifier" | "type_identifier" => {
+            if let Ok(text) = node.utf8_text(content.as_bytes()) {
+                names.push(text.to_string());
+            }
+        }
+        "scoped_identifier" => {
+            if let Some(name) = node.child_by_field_name("name") {
+                collect_use_names(name, content, names);
+            }
+        }
+        "scoped_use_list" => {
+            if let Some(list) = node.child_by_field_name("list") {
+                collect_use_names(list, content, names);
+            }
+        }
+        "use_as_clause" => {
+            if let Some(path) = node.child_by_field_name("path") {
+                collect_use_names(path, content, names);
+            }
+        }
+        "use_list" => {
+            let mut cursor = node.walk();
+            for child in node.named_children(&mut cursor) {
+                collect_use_names(child, content, names);
+            }
+        }
+        // Glob imports and `self`/`super`/`crate` don't name a symbol
+        _ => {}
+    }
+}
+
+/// Bare name of a Rust type node (`a::B<T>` → `B`)
+fn base_type_name<'a>(node: Node, content: &'a str) -> &'a str {
+    let inner = match node.kind() {
+        "generic_type" => node.child_by_field_name("type"),
+        "scoped_type_identifier" => node.child_by_field_name("name"),
+        _ => None,
+    };
+    match inner {
+        Some(inner) => base_type_name(inner, content),
+        None => node.utf8_text(content.as_bytes()).unwrap_or(""),
     }
 }
 
@@ -0,0 +1,85 @@
+//! 符号指纹
+//!
+//! 对定义体做规范化（折叠空白、将符号自身名称替换为占位符）后取哈希，
+//! 使仅改名或移动位置的符号保持相同指纹，便于识别重命名/移动
+
+/// 符号自身名称的占位符
+const SELF_PLACEHOLDER: &str = "$SELF";
+
+/// 计算符号定义体的指纹（SHA-256 前 16 位十六进制）
+pub fn symbol_fingerprint(body: &str, name: &str) -> String {
+    let normalized = normalize_body(body, name);
+    let digest = ring::digest::digest(&ring::digest::SHA256, normalized.as_bytes());
+    hex::encode(&digest.as_ref()[..8])
+}
+
+/// 规范化定义体：折叠空白，并把与符号名相同的标识符替换为占位符
+fn normalize_body(body: &str, name: &str) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut ident = String::new();
+    let mut pending_space = false;
+
+    let flush_ident = |ident: &mut String, out: &mut String| {
+        if ident.is_empty() {
+            return;
+        }
+        if ident == name {
+            out.push_str(SELF_PLACEHOLDER);
+        } else {
+            out.push_str(ident);
+        }
+        ident.clear();
+    };
+
+    for c in body.chars() {
+        if c.is_alphanumeric() || c == '_' {
+            if pending_space && !out.is_empty() && ident.is_empty() {
+                out.push(' ');
+            }
+            pending_space = false;
+            ident.push(c);
+        } else if c.is_whitespace() {
+            flush_ident(&mut ident, &mut out);
+            pending_space = true;
+        } else {
+            flush_ident(&mut ident, &mut out);
+            if pending_space && !out.is_empty() {
+                out.push(' ');
+            }
+            pending_space = false;
+            out.push(c);
+        }
+    }
+    flush_ident(&mut ident, &mut out);
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rename_keeps_fingerprint() {
+        let before = "fn parse_config(path: &str) -> Config {\n    parse_config_inner(path)\n}";
+        let after = "fn load_config(path: &str) -> Config {\n    parse_config_inner(path)\n}";
+        assert_eq!(
+            symbol_fingerprint(before, "parse_config"),
+            symbol_fingerprint(after, "load_config")
+        );
+    }
+
+    #[test]
+    fn test_whitespace_is_ignored() {
+        let a = "fn add(a: i32, b: i32) -> i32 { a + b }";
+        let b = "fn add(a: i32,\n       b: i32) -> i32 {\n    a + b\n}";
+        assert_eq!(symbol_fingerprint(a, "add"), symbol_fingerprint(b, "add"));
+    }
+
+    #[test]
+    fn test_body_change_changes_fingerprint() {
+        let a = "fn add(a: i32, b: i32) -> i32 { a + b }";
+        let b = "fn add(a: i32, b: i32) -> i32 { a - b }";
+        assert_ne!(symbol_fingerprint(a, "add"), symbol_fingerprint(b, "add"));
+    }
+}
@@ -1,3 +1,5 @@
 pub mod ast;
+pub mod fingerprint;
 
 pub use ast::{analyze_file_thread_local, AstAnalyzer};
+pub use fingerprint::symbol_fingerprint;
@@ -0,0 +1,151 @@
+//! 架构分层检查
+//!
+//! 项目在 `.neurospec/architecture.toml` 中声明分层（每层包含哪些目录、可以依赖哪些层），
+//! 用代码图谱中的 Imports/Calls 边检查跨层依赖是否符合规则
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::neurospec::services::graph::{CodeGraph, RelationType};
+use crate::neurospec::services::risk::normalize_path;
+
+/// 分层规则文件（相对项目根目录）
+pub const ARCHITECTURE_FILE: &str = ".neurospec/architecture.toml";
+
+/// 分层规则
+///
+/// ```toml
+/// [[layers]]
+/// name = "ui"
+/// paths = ["src/ui"]
+/// may_depend_on = ["services"]
+///
+/// [[layers]]
+/// name = "services"
+/// paths = ["src/services"]
+/// may_depend_on = ["models"]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchitectureRules {
+    pub layers: Vec<LayerRule>,
+}
+
+/// 单个分层
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayerRule {
+    pub name: String,
+    /// 属于该层的目录（相对项目根目录）
+    pub paths: Vec<String>,
+    /// 允许依赖的其他层，未列出的层都不允许依赖
+    #[serde(default)]
+    pub may_depend_on: Vec<String>,
+}
+
+/// 违反分层规则的依赖边
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayerViolation {
+    pub from_layer: String,
+    pub to_layer: String,
+    pub relation: RelationType,
+    pub from_symbol: String,
+    pub from_file: String,
+    pub to_symbol: String,
+    pub to_file: String,
+}
+
+impl ArchitectureRules {
+    /// 加载项目的分层规则，没有规则文件时返回 None
+    pub fn load(project_root: &Path) -> Result<Option<Self>> {
+        let path = project_root.join(ARCHITECTURE_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&content).map(Some)
+    }
+
+    /// 解析规则，检查层名唯一且 may_depend_on 引用的层存在
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut rules: Self = toml::from_str(content).context("Invalid architecture.toml")?;
+
+        for (i, layer) in rules.layers.iter().enumerate() {
+            if rules.layers[..i].iter().any(|l| l.name == layer.name) {
+                anyhow::bail!("Layer '{}' is defined more than once", layer.name);
+            }
+            if let Some(unknown) = layer
+                .may_depend_on
+                .iter()
+                .find(|name| !rules.layers.iter().any(|l| &l.name == *name))
+            {
+                anyhow::bail!("Layer '{}' may depend on unknown layer '{}'", layer.name, unknown);
+            }
+        }
+
+        for layer in &mut rules.layers {
+            for path in &mut layer.paths {
+                *path = path.replace('\\', "/").trim_start_matches("./").trim_end_matches('/').to_string();
+            }
+        }
+        Ok(rules)
+    }
+
+    /// 文件所属的层（匹配最长的目录前缀）
+    pub fn layer_of(&self, file: &str) -> Option<&LayerRule> {
+        self.layers
+            .iter()
+            .filter_map(|layer| {
+                layer
+                    .paths
+                    .iter()
+                    .filter(|dir| file == dir.as_str() || file.starts_with(&format!("{}/", dir)))
+                    .map(|dir| dir.len())
+                    .max()
+                    .map(|len| (len, layer))
+            })
+            .max_by_key(|(len, _)| *len)
+            .map(|(_, layer)| layer)
+    }
+
+    /// 检查图谱中的 Imports/Calls 边，不属于任何层的文件不受约束
+    pub fn check(&self, graph: &CodeGraph, project_root: &Path) -> Vec<LayerViolation> {
+        let mut violations = Vec::new();
+
+        for edge in graph.graph.edge_indices() {
+            let relation = graph.graph[edge];
+            if !matches!(relation, RelationType::Imports | RelationType::Calls) {
+                continue;
+            }
+            let Some((from, to)) = graph.graph.edge_endpoints(edge) else {
+                continue;
+            };
+            let (from, to) = (&graph.graph[from], &graph.graph[to]);
+            let from_file = normalize_path(&from.file_path, project_root);
+            let to_file = normalize_path(&to.file_path, project_root);
+
+            let (Some(from_layer), Some(to_layer)) = (self.layer_of(&from_file), self.layer_of(&to_file)) else {
+                continue;
+            };
+            if from_layer.name == to_layer.name || from_layer.may_depend_on.contains(&to_layer.name) {
+                continue;
+            }
+
+            violations.push(LayerViolation {
+                from_layer: from_layer.name.clone(),
+                to_layer: to_layer.name.clone(),
+                relation,
+                from_symbol: from.name.clone(),
+                from_file,
+                to_symbol: to.name.clone(),
+                to_file,
+            });
+        }
+
+        violations.sort_by(|a, b| {
+            (&a.from_layer, &a.to_layer, &a.from_file).cmp(&(&b.from_layer, &b.to_layer, &b.from_file))
+        });
+        violations
+    }
+}
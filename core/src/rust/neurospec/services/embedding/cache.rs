@@ -87,6 +87,58 @@ impl EmbeddingCache {
         Ok(())
     }
 
+    /// 批量获取缓存的嵌入向量（单个事务），结果与 `texts` 一一对应
+    pub fn get_many(&self, texts: &[String]) -> Result<Vec<Option<Vec<f32>>>> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let tx = conn.transaction()?;
+
+        let mut results = Vec::with_capacity(texts.len());
+        {
+            let mut stmt = tx.prepare("SELECT vector, dimension FROM embeddings WHERE text_hash = ?1")?;
+            for text in texts {
+                let row: Option<(Vec<u8>, i64)> = stmt
+                    .query_row(params![Self::hash_text(text)], |row| Ok((row.get(0)?, row.get(1)?)))
+                    .ok();
+                results.push(row.map(|(blob, dimension)| Self::bytes_to_vector(&blob, dimension as usize)));
+            }
+        }
+        tx.commit()?;
+
+        Ok(results)
+    }
+
+    /// 批量存入缓存（单个事务）
+    pub fn set_many(&self, entries: &[(&str, &[f32])]) -> Result<()> {
+        let now = chrono::Utc::now().timestamp();
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let tx = conn.transaction()?;
+
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO embeddings (text_hash, vector, dimension, created_at) 
+                 VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for (text, vector) in entries {
+                stmt.execute(params![
+                    Self::hash_text(text),
+                    Self::vector_to_bytes(vector),
+                    vector.len() as i64,
+                    now
+                ])?;
+            }
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// 将缓存写入主数据库文件（WAL 模式下执行检查点，其他模式下无操作）
+    pub fn flush(&self) -> Result<()> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+        Ok(())
+    }
+
     /// 清理过期缓存
     /// 
     /// 删除超过 `days` 天的缓存
@@ -40,10 +40,7 @@ fn default_timeout() -> u64 { 30 }
 fn default_max_retries() -> u32 { 3 }
 
 fn default_cache_path() -> PathBuf {
-    dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(".neurospec")
-        .join("embedding_cache")
+    crate::config::profile_embedding_dir().join("embedding_cache")
 }
 
 impl Default for EmbeddingConfig {
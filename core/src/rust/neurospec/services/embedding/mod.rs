@@ -13,12 +13,16 @@ pub use config::EmbeddingConfig;
 use std::sync::Arc;
 use anyhow::Result;
 
+use crate::mcp::utils::{ToolError, ToolErrorCode};
+
 /// 统一嵌入服务
 /// 
 /// 封装 Provider 和 Cache，提供简单的接口
 pub struct EmbeddingService {
     provider: Arc<dyn EmbeddingProvider>,
     cache: Option<EmbeddingCache>,
+    /// 模型名（随向量一起存储，便于识别换模型后失效的向量）
+    model: String,
 }
 
 impl EmbeddingService {
@@ -32,10 +36,11 @@ impl EmbeddingService {
             None
         };
         
-        Ok(Self { provider, cache })
+        Ok(Self { provider, cache, model: config.model.clone() })
     }
 
     /// 获取文本的嵌入向量
+    #[tracing::instrument(name = "embedding.embed", skip_all)]
     pub async fn embed(&self, text: &str) -> Result<Vec<f32>> {
         // 检查缓存
         if let Some(ref cache) = self.cache {
@@ -45,7 +50,7 @@ impl EmbeddingService {
         }
 
         // 调用 Provider
-        let vector = self.provider.embed(text).await?;
+        let vector = self.provider.embed(text).await.map_err(provider_error)?;
 
         // 存入缓存
         if let Some(ref cache) = self.cache {
@@ -56,6 +61,7 @@ impl EmbeddingService {
     }
 
     /// 批量获取嵌入向量
+    #[tracing::instrument(name = "embedding.embed_batch", skip_all, fields(count = texts.len()))]
     pub async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
         // 检查缓存，找出未缓存的
         let mut results: Vec<Option<Vec<f32>>> = vec![None; texts.len()];
@@ -63,8 +69,10 @@ impl EmbeddingService {
         let mut uncached_texts = Vec::new();
 
         if let Some(ref cache) = self.cache {
-            for (i, text) in texts.iter().enumerate() {
-                if let Ok(Some(cached)) = cache.get(text) {
+            // 一次事务查询全部文本，读取失败时视为全部未命中
+            let cached = cache.get_many(texts).unwrap_or_else(|_| vec![None; texts.len()]);
+            for (i, (text, cached)) in texts.iter().zip(cached).enumerate() {
+                if let Some(cached) = cached {
                     results[i] = Some(cached);
                 } else {
                     uncached_indices.push(i);
@@ -78,15 +86,20 @@ impl EmbeddingService {
 
         // 批量调用 Provider
         if !uncached_texts.is_empty() {
-            let vectors = self.provider.embed_batch(&uncached_texts).await?;
+            let vectors = self.provider.embed_batch(&uncached_texts).await.map_err(provider_error)?;
             
             for (idx, vector) in uncached_indices.iter().zip(vectors.iter()) {
                 results[*idx] = Some(vector.clone());
-                
-                // 存入缓存
-                if let Some(ref cache) = self.cache {
-                    let _ = cache.set(&texts[*idx], vector);
-                }
+            }
+
+            // 存入缓存（单个事务）
+            if let Some(ref cache) = self.cache {
+                let entries: Vec<(&str, &[f32])> = uncached_texts
+                    .iter()
+                    .zip(vectors.iter())
+                    .map(|(text, vector)| (text.as_str(), vector.as_slice()))
+                    .collect();
+                let _ = cache.set_many(&entries);
             }
         }
 
@@ -123,12 +136,22 @@ impl EmbeddingService {
         Ok(scores)
     }
 
+    /// 获取模型名
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
     /// 获取向量维度
     pub fn dimension(&self) -> usize {
         self.provider.dimension()
     }
 }
 
+/// 嵌入服务调用失败统一标记为 `EMBEDDING_UNAVAILABLE`
+fn provider_error(error: anyhow::Error) -> anyhow::Error {
+    ToolError::new(ToolErrorCode::EmbeddingUnavailable, format!("嵌入服务调用失败: {}", error)).into()
+}
+
 /// 计算余弦相似度
 pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
     if a.len() != b.len() || a.is_empty() {
@@ -150,18 +173,29 @@ pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
 // 全局单例管理
 // ============================================================================
 
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::OnceLock;
 use tokio::sync::RwLock;
 use std::path::PathBuf;
 
 static GLOBAL_EMBEDDING_SERVICE: OnceLock<RwLock<Option<EmbeddingService>>> = OnceLock::new();
 
+/// 运行时停用嵌入服务（托盘开关，不修改配置文件，重启后恢复）
+static EMBEDDING_PAUSED: AtomicBool = AtomicBool::new(false);
+
 /// 获取配置文件路径
 fn get_config_path() -> PathBuf {
-    dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(".neurospec")
-        .join("embedding_config.json")
+    crate::config::profile_embedding_dir().join("embedding_config.json")
+}
+
+/// 嵌入服务配置文件路径
+pub fn embedding_config_path() -> PathBuf {
+    get_config_path()
+}
+
+/// 读取嵌入服务配置文件（不存在或格式错误时返回 None）
+pub fn load_embedding_config() -> Option<EmbeddingConfig> {
+    load_config_from_file()
 }
 
 /// 从配置文件加载配置
@@ -197,6 +231,11 @@ fn load_config_from_file() -> Option<EmbeddingConfig> {
 /// 初始化全局嵌入服务
 pub async fn init_global_embedding_service() -> Result<bool> {
     let lock = GLOBAL_EMBEDDING_SERVICE.get_or_init(|| RwLock::new(None));
+
+    if is_embedding_paused() {
+        log::info!("嵌入服务已在运行时停用，跳过初始化");
+        return Ok(false);
+    }
     
     // 尝试从配置文件加载
     if let Some(config) = load_config_from_file() {
@@ -273,11 +312,51 @@ pub fn is_embedding_available() -> bool {
         .unwrap_or(false)
 }
 
+/// 退出时刷新嵌入缓存（不阻塞：服务正被写锁占用时跳过）
+pub fn flush_embedding_cache() {
+    let Some(lock) = GLOBAL_EMBEDDING_SERVICE.get() else {
+        return;
+    };
+    let Ok(guard) = lock.try_read() else {
+        return;
+    };
+    if let Some(cache) = guard.as_ref().and_then(|service| service.cache.as_ref()) {
+        if let Err(e) = cache.flush() {
+            log::warn!("刷新嵌入缓存失败: {}", e);
+        }
+    }
+}
+
 /// 重新加载嵌入服务配置
+///
+/// 先丢弃现有服务，配置被删除或缺少 API Key 时嵌入服务随之停用
 pub async fn reload_embedding_service() -> Result<bool> {
+    if let Some(lock) = GLOBAL_EMBEDDING_SERVICE.get() {
+        *lock.write().await = None;
+    }
     init_global_embedding_service().await
 }
 
+/// 运行时启用或停用嵌入服务，返回服务是否可用
+///
+/// 停用时丢弃现有服务，语义搜索与向量嵌入任务随之跳过；启用时按配置文件重新初始化
+pub async fn set_embedding_enabled(enabled: bool) -> Result<bool> {
+    EMBEDDING_PAUSED.store(!enabled, Ordering::SeqCst);
+    if enabled {
+        return reload_embedding_service().await;
+    }
+    if let Some(lock) = GLOBAL_EMBEDDING_SERVICE.get() {
+        *lock.write().await = None;
+    }
+    log::info!("嵌入服务已停用");
+    Ok(false)
+}
+
+/// 嵌入服务是否在运行时被停用
+pub fn is_embedding_paused() -> bool {
+    EMBEDDING_PAUSED.load(Ordering::SeqCst)
+}
+
 /// 使用嵌入服务计算相似度（便捷函数）
 pub async fn compute_similarity(text1: &str, text2: &str) -> Option<f32> {
     let lock = match get_global_embedding_service() {
@@ -289,6 +368,14 @@ pub async fn compute_similarity(text1: &str, text2: &str) -> Option<f32> {
     service.similarity(text1, text2).await.ok()
 }
 
+/// 使用嵌入服务计算单个文本的向量（便捷函数，服务不可用或调用失败时为 None）
+pub async fn embed_text(text: &str) -> Option<Vec<f32>> {
+    let lock = get_global_embedding_service()?;
+    let guard = lock.read().await;
+    let service = guard.as_ref()?;
+    service.embed(text).await.ok().filter(|vector| !vector.is_empty())
+}
+
 /// 使用嵌入服务找最相似的（便捷函数）
 pub async fn find_similar(query: &str, candidates: &[String], top_k: usize) -> Option<Vec<(usize, f32)>> {
     let lock = match get_global_embedding_service() {
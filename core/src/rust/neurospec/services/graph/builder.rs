@@ -1,8 +1,10 @@
 use ignore::WalkBuilder;
 use log::info;
+use petgraph::graph::NodeIndex;
 use std::collections::HashMap;
 
-use crate::neurospec::models::Symbol;
+use crate::mcp::tools::acemcp::local_engine::neurospec_ignore::filter_walker;
+use crate::neurospec::models::{Symbol, SymbolKind};
 use crate::neurospec::services::analyzer::analyze_file_thread_local;
 use crate::neurospec::services::graph::{CodeGraph, RelationType};
 
@@ -13,31 +15,29 @@ impl GraphBuilder {
     pub fn build_from_project(project_root: &str) -> CodeGraph {
         let mut graph = CodeGraph::new();
         let mut symbols_by_name: HashMap<String, Vec<String>> = HashMap::new();
+        let mut file_nodes: HashMap<String, String> = HashMap::new();
         let mut all_symbols: Vec<Symbol> = Vec::new();
 
         info!("Building graph for project: {}", project_root);
 
         // 1. First Pass: Collect all symbols
-        // 使用 ignore crate 遵守 .gitignore，避免扫描 node_modules/dist 等目录
-        let walker = WalkBuilder::new(project_root)
+        // 使用 ignore crate 遵守 .gitignore 与 .neurospecignore，避免扫描 node_modules/dist 等目录
+        let mut builder = WalkBuilder::new(project_root);
+        builder
             .hidden(false)
             .git_ignore(true)
             .git_global(true)
-            .git_exclude(true)
-            .build();
+            .git_exclude(true);
+        filter_walker(&mut builder, std::path::Path::new(project_root));
+        let walker = builder.build();
 
         for entry in walker
             .filter_map(|e| e.ok())
             .filter(|e| e.path().is_file())
         {
             let path = entry.path();
-            let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("");
-
-            let language = match ext {
-                "rs" => "rust",
-                "ts" | "js" | "tsx" | "jsx" => "typescript",
-                "py" => "python",
-                _ => continue,
+            let Some(language) = graph_language(path) else {
+                continue;
             };
 
             if let Ok(content) = std::fs::read_to_string(path) {
@@ -48,6 +48,10 @@ impl GraphBuilder {
                     // Add to graph
                     let _node_idx = graph.add_symbol(&symbol);
 
+                    if matches!(symbol.kind, SymbolKind::File) {
+                        file_nodes.insert(symbol.path.clone(), format!("{}::{}", symbol.path, symbol.name));
+                    }
+
                     // Index by name for resolution
                     symbols_by_name
                         .entry(symbol.name.clone())
@@ -64,6 +68,14 @@ impl GraphBuilder {
             let from_id = format!("{}::{}", symbol.path, symbol.name);
 
             if let Some(from_idx) = graph.node_map.get(&from_id).cloned() {
+                // 文件符号的引用是导入项而不是调用名
+                if matches!(symbol.kind, SymbolKind::File) {
+                    Self::link_imports(&mut graph, from_idx, &symbol, &file_nodes, &symbols_by_name);
+                    continue;
+                }
+
+                Self::link_inherits(&mut graph, from_idx, &symbol, &symbols_by_name);
+
                 for ref_name in &symbol.references {
                     // Try to resolve ref_name
                     if let Some(target_paths) = symbols_by_name.get(ref_name) {
@@ -101,6 +113,7 @@ impl GraphBuilder {
 mod tests {
     use super::*;
     use crate::neurospec::models::{Symbol, SymbolKind};
+    use crate::neurospec::services::graph::CycleLevel;
 
     #[test]
     fn test_graph_builder_linking() {
@@ -114,6 +127,10 @@ mod tests {
             language: Some("rust".to_string()),
             signature: None,
             references: vec!["callee_func".to_string()],
+            inherits: vec![],
+            start_line: None,
+            end_line: None,
+            fingerprint: None,
         };
 
         // Create Symbol B (Callee)
@@ -124,6 +141,10 @@ mod tests {
             language: Some("rust".to_string()),
             signature: None,
             references: vec![],
+            inherits: vec![],
+            start_line: None,
+            end_line: None,
+            fingerprint: None,
         };
 
         // Add symbols manually (simulating builder pass 1)
@@ -136,6 +157,178 @@ mod tests {
 
         // Verify edge
         assert!(graph.graph.contains_edge(idx_a, idx_b));
+
+        let callers = graph.callers_of("src/utils.rs::callee_func");
+        assert_eq!(callers.len(), 1);
+        assert_eq!(callers[0].name, "caller_func");
+    }
+
+    #[test]
+    fn test_resolve_module() {
+        let file_nodes: HashMap<String, String> = [
+            "src/main.c",
+            "src/util.h",
+            "include/net/socket.h",
+            "vendor/include/net/socket.h",
+        ]
+        .iter()
+        .map(|p| (p.to_string(), format!("{}::x", p)))
+        .collect();
+
+        // 相对包含它的文件
+        assert_eq!(
+            resolve_module("src/main.c", "util.h", true, &file_nodes).as_deref(),
+            Some("src/util.h")
+        );
+        assert_eq!(
+            resolve_module("src/main.c", "../src/./util.h", true, &file_nodes).as_deref(),
+            Some("src/util.h")
+        );
+        // 通过包含目录查找，取最短匹配
+        assert_eq!(
+            resolve_module("src/main.c", "net/socket.h", true, &file_nodes).as_deref(),
+            Some("include/net/socket.h")
+        );
+        // 系统头文件不解析
+        assert_eq!(resolve_module("src/main.c", "stdio.h", true, &file_nodes), None);
+        // 只按相对路径解析时不搜索包含目录
+        assert_eq!(resolve_module("src/main.c", "net/socket.h", false, &file_nodes), None);
+    }
+
+    #[test]
+    fn test_module_candidates() {
+        assert_eq!(
+            module_candidates("python", "..pkg.models"),
+            (vec!["../pkg/models.py".to_string(), "../pkg/models/__init__.py".to_string()], false)
+        );
+        assert_eq!(
+            module_candidates("python", "."),
+            (vec!["__init__.py".to_string()], false)
+        );
+        assert!(module_candidates("python", "app.core").1);
+        assert!(module_candidates("typescript", "./api").0.contains(&"./api/index.ts".to_string()));
+        assert!(module_candidates("typescript", "react").0.is_empty());
+        assert!(module_candidates("rust", "HashMap").0.is_empty());
+    }
+
+    #[test]
+    fn test_import_and_inherit_edges() {
+        let symbol = |kind, name: &str, path: &str, references: Vec<&str>, inherits: Vec<&str>| Symbol {
+            kind,
+            name: name.to_string(),
+            path: path.to_string(),
+            language: Some("python".to_string()),
+            signature: None,
+            references: references.into_iter().map(String::from).collect(),
+            inherits: inherits.into_iter().map(String::from).collect(),
+            start_line: None,
+            end_line: None,
+            fingerprint: None,
+        };
+        let snapshot = crate::neurospec::models::XRaySnapshot {
+            project_root: "/project".to_string(),
+            symbols: vec![
+                symbol(SymbolKind::File, "views.py", "app/views.py", vec![".models", "Base"], vec![]),
+                symbol(SymbolKind::Class, "UserView", "app/views.py", vec![], vec!["Base"]),
+                symbol(SymbolKind::File, "models.py", "app/models.py", vec![], vec![]),
+                symbol(SymbolKind::Class, "Base", "app/models.py", vec![], vec![]),
+            ],
+            confidence: 1.0,
+            warnings: vec![],
+            skipped_files: 0,
+            failed_files: 0,
+        };
+
+        let graph = GraphBuilder::build_from_xray(&snapshot);
+        let edge = |from: &str, to: &str| {
+            let edge = graph.graph.find_edge(graph.node_map[from], graph.node_map[to])?;
+            graph.graph.edge_weight(edge).copied()
+        };
+
+        assert_eq!(edge("app/views.py::views.py", "app/models.py::models.py"), Some(RelationType::Imports));
+        assert_eq!(edge("app/views.py::views.py", "app/models.py::Base"), Some(RelationType::Imports));
+        assert_eq!(edge("app/views.py::UserView", "app/models.py::Base"), Some(RelationType::Inherits));
+
+        // 没有调用 Base，但子类和导入它的文件依赖它
+        assert!(graph.callers_of("app/models.py::Base").is_empty());
+        let mut dependents: Vec<(&str, usize)> = graph
+            .transitive_dependents("app/models.py::Base", 2)
+            .into_iter()
+            .map(|(node, hops)| (node.id.as_str(), hops))
+            .collect();
+        dependents.sort();
+        assert_eq!(
+            dependents,
+            vec![("app/views.py::UserView", 1), ("app/views.py::views.py", 1)]
+        );
+        let dependencies = graph.transitive_dependencies("app/views.py::views.py", 2);
+        assert_eq!(dependencies.len(), 2);
+    }
+
+    #[test]
+    fn test_dependency_cycles() {
+        let symbol = |name: &str, path: &str, line: u32, references: Vec<&str>| Symbol {
+            kind: SymbolKind::Function,
+            name: name.to_string(),
+            path: path.to_string(),
+            language: Some("rust".to_string()),
+            signature: None,
+            references: references.into_iter().map(String::from).collect(),
+            inherits: vec![],
+            start_line: Some(line),
+            end_line: None,
+            fingerprint: None,
+        };
+        let graph = GraphBuilder::build_from_symbols(&[
+            symbol("parse", "src/parser.rs", 3, vec!["eval", "parse"]),
+            symbol("eval", "src/eval.rs", 10, vec!["parse"]),
+            symbol("print", "src/eval.rs", 20, vec!["format"]),
+            symbol("format", "src/fmt.rs", 1, vec![]),
+        ]);
+
+        let modules = graph.dependency_cycles(CycleLevel::Module);
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].members, vec!["src/eval.rs", "src/parser.rs"]);
+        assert_eq!(modules[0].edges.len(), 2);
+        assert_eq!(modules[0].edges[0].0.start_line, Some(10));
+
+        // `parse` 的直接递归不报告，相互调用才报告
+        let functions = graph.dependency_cycles(CycleLevel::Function);
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0].members, vec!["src/eval.rs::eval", "src/parser.rs::parse"]);
+    }
+
+    #[test]
+    fn test_references_and_enclosing_symbol() {
+        let symbol = |kind, name: &str, lines: (u32, u32), references: Vec<&str>| Symbol {
+            kind,
+            name: name.to_string(),
+            path: "src/service.rs".to_string(),
+            language: Some("rust".to_string()),
+            signature: None,
+            references: references.into_iter().map(String::from).collect(),
+            inherits: vec![],
+            start_line: Some(lines.0),
+            end_line: Some(lines.1),
+            fingerprint: None,
+        };
+        let graph = GraphBuilder::build_from_symbols(&[
+            symbol(SymbolKind::Class, "Service", (1, 30), vec![]),
+            symbol(SymbolKind::Function, "run", (5, 12), vec!["load"]),
+            symbol(SymbolKind::Function, "load", (14, 20), vec![]),
+        ]);
+
+        let references = graph.references_to("src/service.rs::load");
+        assert_eq!(references.len(), 1);
+        assert_eq!(references[0].0.name, "run");
+        assert_eq!(references[0].1, RelationType::Calls);
+
+        // 取最内层的定义；不在任何定义内的行没有结果
+        let enclosing = |line| graph.enclosing_symbol("src/service.rs", line).map(|node| node.name.as_str());
+        assert_eq!(enclosing(8), Some("run"));
+        assert_eq!(enclosing(25), Some("Service"));
+        assert_eq!(enclosing(40), None);
+        assert_eq!(graph.enclosing_symbol("src/other.rs", 8).map(|node| node.name.as_str()), None);
     }
 }
 
@@ -147,15 +340,24 @@ impl GraphBuilder {
     /// - 复用 X-Ray 已提取的符号，避免重复扫描
     /// - 性能更好，特别是与 scan_project_cached 配合使用时
     pub fn build_from_xray(snapshot: &crate::neurospec::models::XRaySnapshot) -> CodeGraph {
+        info!("Building graph from X-Ray snapshot: {}", snapshot.project_root);
+        Self::build_from_symbols(&snapshot.symbols)
+    }
+
+    /// 从已提取的符号构建图谱（只做链接，不读取文件）
+    pub fn build_from_symbols(symbols: &[Symbol]) -> CodeGraph {
         let mut graph = CodeGraph::new();
         let mut symbols_by_name: HashMap<String, Vec<String>> = HashMap::new();
-
-        info!("Building graph from X-Ray snapshot: {}", snapshot.project_root);
+        let mut file_nodes: HashMap<String, String> = HashMap::new();
 
         // 1. First Pass: Add all symbols to graph
-        for symbol in &snapshot.symbols {
+        for symbol in symbols {
             let _node_idx = graph.add_symbol(symbol);
 
+            if matches!(symbol.kind, SymbolKind::File) {
+                file_nodes.insert(symbol.path.clone(), format!("{}::{}", symbol.path, symbol.name));
+            }
+
             // Index by name for resolution
             symbols_by_name
                 .entry(symbol.name.clone())
@@ -164,10 +366,17 @@ impl GraphBuilder {
         }
 
         // 2. Second Pass: Link references
-        for symbol in &snapshot.symbols {
+        for symbol in symbols {
             let from_id = format!("{}::{}", symbol.path, symbol.name);
 
             if let Some(from_idx) = graph.node_map.get(&from_id).cloned() {
+                if matches!(symbol.kind, SymbolKind::File) {
+                    Self::link_imports(&mut graph, from_idx, symbol, &file_nodes, &symbols_by_name);
+                    continue;
+                }
+
+                Self::link_inherits(&mut graph, from_idx, symbol, &symbols_by_name);
+
                 for ref_name in &symbol.references {
                     if let Some(target_paths) = symbols_by_name.get(ref_name) {
                         // Prefer symbol in same file, fallback to first
@@ -212,4 +421,156 @@ impl GraphBuilder {
         // 复用 build_from_xray
         Ok(Self::build_from_xray(&snapshot))
     }
+
+    /// 添加文件到其导入项的 `Imports` 边
+    ///
+    /// 模块路径与 `#include` 解析到被导入文件的 `File` 节点，其余导入项按名称解析到符号；
+    /// 无法解析的（系统头文件、外部包）跳过
+    fn link_imports(
+        graph: &mut CodeGraph,
+        from_idx: NodeIndex,
+        symbol: &Symbol,
+        file_nodes: &HashMap<String, String>,
+        symbols_by_name: &HashMap<String, Vec<String>>,
+    ) {
+        let language = symbol.language.as_deref().unwrap_or("");
+
+        for import in &symbol.references {
+            let (candidates, search_suffix) = module_candidates(language, import);
+            let target_file = candidates
+                .iter()
+                .find_map(|module| resolve_module(&symbol.path, module, search_suffix, file_nodes));
+
+            if let Some(target_path) = target_file {
+                if target_path != symbol.path {
+                    let target_id = &file_nodes[&target_path];
+                    graph.add_relation_by_id(from_idx, target_id, RelationType::Imports);
+                }
+                continue;
+            }
+
+            // 导入的名称不会指向导入它的文件本身
+            if let Some(target_paths) = symbols_by_name.get(import) {
+                if let Some(path) = target_paths.iter().find(|p| **p != symbol.path) {
+                    let target_id = format!("{}::{}", path, import);
+                    graph.add_relation_by_id(from_idx, &target_id, RelationType::Imports);
+                }
+            }
+        }
+    }
+
+    /// 添加类型到其基类、接口和 trait 的 `Inherits` 边
+    fn link_inherits(
+        graph: &mut CodeGraph,
+        from_idx: NodeIndex,
+        symbol: &Symbol,
+        symbols_by_name: &HashMap<String, Vec<String>>,
+    ) {
+        for base in &symbol.inherits {
+            if let Some(target_paths) = symbols_by_name.get(base) {
+                // 优先同一文件中的符号，否则取第一个
+                let target_path = if target_paths.contains(&symbol.path) {
+                    Some(&symbol.path)
+                } else {
+                    target_paths.first()
+                };
+
+                if let Some(path) = target_path {
+                    let target_id = format!("{}::{}", path, base);
+                    graph.add_relation_by_id(from_idx, &target_id, RelationType::Inherits);
+                }
+            }
+        }
+    }
+}
+
+/// 文件使用的分析语言，不参与图谱的文件返回 None
+pub(crate) fn graph_language(path: &std::path::Path) -> Option<&'static str> {
+    match path.extension().and_then(|s| s.to_str()).unwrap_or("") {
+        "rs" => Some("rust"),
+        "ts" | "js" | "tsx" | "jsx" => Some("typescript"),
+        "py" => Some("python"),
+        "c" | "h" => Some("c"),
+        "cpp" | "cc" | "cxx" | "hpp" | "hh" | "hxx" => Some("cpp"),
+        _ => None,
+    }
+}
+
+/// 导入项对应的候选文件路径（相对导入方所在目录）
+///
+/// 第二个值表示是否还可以按路径后缀匹配（经包含目录或包根目录解析的导入）
+fn module_candidates(language: &str, import: &str) -> (Vec<String>, bool) {
+    match language {
+        "c" | "cpp" => (vec![import.to_string()], true),
+        "typescript" | "javascript" if import.starts_with('.') => {
+            let candidates = ["", ".ts", ".tsx", ".js", ".jsx", "/index.ts", "/index.tsx", "/index.js", "/index.jsx"]
+                .iter()
+                .map(|ext| format!("{}{}", import, ext))
+                .collect();
+            (candidates, false)
+        }
+        "python" => {
+            let module = import.trim_start_matches('.');
+            let level = import.len() - module.len();
+            // `from . import x` 指当前包，每多一个点上移一级
+            let prefix = "../".repeat(level.saturating_sub(1));
+            let base = format!("{}{}", prefix, module.replace('.', "/"));
+            let candidates = if module.is_empty() {
+                vec![format!("{}__init__.py", prefix)]
+            } else {
+                vec![format!("{}.py", base), format!("{}/__init__.py", base)]
+            };
+            (candidates, level == 0)
+        }
+        _ => (Vec::new(), false),
+    }
+}
+
+/// 把模块路径解析为已知文件路径
+///
+/// 先按导入方所在目录解析（`#include "x.h"` 与相对导入），允许时再回退到路径以模块路径结尾的文件（经包含目录找到的头文件）
+fn resolve_module(
+    from_path: &str,
+    module: &str,
+    search_suffix: bool,
+    file_nodes: &HashMap<String, String>,
+) -> Option<String> {
+    let module = module.replace('\\', "/");
+    let dir = from_path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+    let relative = normalize_path(&if dir.is_empty() {
+        module.clone()
+    } else {
+        format!("{}/{}", dir, module)
+    });
+    if file_nodes.contains_key(&relative) {
+        return Some(relative);
+    }
+    if !search_suffix {
+        return None;
+    }
+
+    let module = normalize_path(&module);
+    let suffix = format!("/{}", module);
+    let mut candidates: Vec<&String> = file_nodes
+        .keys()
+        .filter(|path| **path == module || path.ends_with(&suffix))
+        .collect();
+    // 取最短匹配，保证结果确定
+    candidates.sort_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+    candidates.first().map(|path| (*path).clone())
+}
+
+/// 按字面去掉 `/` 分隔路径中的 `.` 段并折叠 `..` 段
+fn normalize_path(path: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "." => {}
+            ".." if matches!(parts.last(), Some(last) if !last.is_empty() && *last != "..") => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    parts.join("/")
 }
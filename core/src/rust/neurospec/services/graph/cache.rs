@@ -0,0 +1,302 @@
+//! 代码图谱持久化与增量重建
+//!
+//! 图谱的节点、边连同每个文件的 mtime、大小、内容哈希和提取出的符号一起保存在缓存目录中。
+//! 项目处于文件监听且图谱代数未变化时直接复用内存中的图谱；否则只重新解析
+//! mtime/大小变化且内容哈希不同的文件，再用全部符号重新链接。
+//! 没有文件变化时从保存的节点和边直接恢复图谱，不需要重新解析或链接。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, UNIX_EPOCH};
+
+use ignore::WalkBuilder;
+use lazy_static::lazy_static;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::builder::{graph_language, GraphBuilder};
+use super::{impact_cache, CodeGraph, RelationType, SymbolNode};
+use crate::mcp::tools::unified_store::persistence::content_hash;
+use crate::neurospec::models::Symbol;
+use crate::neurospec::services::analyzer::analyze_file_thread_local;
+use crate::utils::path_key;
+
+/// 缓存格式版本，结构或符号提取规则变化时递增
+const GRAPH_CACHE_VERSION: u32 = 2;
+
+/// 缓存目录下的子目录
+const GRAPH_CACHE_DIR: &str = "code_graph";
+
+/// 内存中最多保留的项目图谱数
+const MAX_CACHED_PROJECTS: usize = 8;
+
+/// 单个文件的缓存条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedFile {
+    mtime: u64,
+    size: u64,
+    hash: String,
+    symbols: Vec<Symbol>,
+}
+
+/// 持久化的图谱
+#[derive(Debug, Deserialize)]
+struct PersistedGraph {
+    version: u32,
+    /// 相对路径 → 文件条目
+    files: HashMap<String, CachedFile>,
+    nodes: Vec<SymbolNode>,
+    edges: Vec<(usize, usize, RelationType)>,
+}
+
+/// 内存中的项目图谱
+struct CachedGraph {
+    files: HashMap<String, CachedFile>,
+    graph: Arc<CodeGraph>,
+    /// 构建时的图谱代数（项目未被监听时为 None，下次使用需重新检查文件）
+    generation: Option<u64>,
+    last_used: Instant,
+}
+
+lazy_static! {
+    static ref GRAPH_CACHE: Mutex<HashMap<String, CachedGraph>> = Mutex::new(HashMap::new());
+}
+
+/// 获取项目图谱，只重建发生变化的文件
+pub fn load_graph(project_root: &Path) -> Arc<CodeGraph> {
+    let key = path_key(project_root);
+    // 先消费该项目的监听事件，代数未变说明没有文件变化
+    let generation = impact_cache::current_generation(project_root);
+
+    let previous = {
+        let Ok(mut cache) = GRAPH_CACHE.lock() else {
+            return Arc::new(GraphBuilder::build_from_project(&project_root.to_string_lossy()));
+        };
+        if let Some(entry) = cache.get_mut(&key) {
+            if generation.is_some() && entry.generation == generation {
+                entry.last_used = Instant::now();
+                return entry.graph.clone();
+            }
+        }
+        cache.remove(&key)
+    };
+
+    let (files, graph) = match previous {
+        Some(entry) => (entry.files, Some(entry.graph)),
+        None => match load_from_disk(&key) {
+            Some(persisted) => {
+                let graph = restore_graph(persisted.nodes, persisted.edges);
+                (persisted.files, Some(Arc::new(graph)))
+            }
+            None => (HashMap::new(), None),
+        },
+    };
+
+    let refresh = refresh_files(project_root, files);
+    let graph = match graph {
+        Some(graph) if !refresh.symbols_changed => graph,
+        _ => {
+            let symbols: Vec<Symbol> = refresh
+                .files
+                .values()
+                .flat_map(|file| file.symbols.iter().cloned())
+                .collect();
+            crate::log_debug!(
+                "Relinking code graph for {} ({} files re-parsed)",
+                project_root.display(),
+                refresh.reparsed
+            );
+            Arc::new(GraphBuilder::build_from_symbols(&symbols))
+        }
+    };
+
+    if refresh.symbols_changed || refresh.metadata_changed {
+        if let Err(e) = save_to_disk(&key, &refresh.files, &graph) {
+            crate::log_important!(warn, "Failed to persist code graph for {}: {}", project_root.display(), e);
+        }
+    }
+
+    if let Ok(mut cache) = GRAPH_CACHE.lock() {
+        if cache.len() >= MAX_CACHED_PROJECTS {
+            let oldest = cache
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                cache.remove(&oldest);
+            }
+        }
+        cache.insert(
+            key,
+            CachedGraph {
+                files: refresh.files,
+                graph: graph.clone(),
+                generation,
+                last_used: Instant::now(),
+            },
+        );
+    }
+
+    graph
+}
+
+/// 丢弃项目的图谱缓存（内存与磁盘）
+pub fn invalidate(project_root: &Path) {
+    let key = path_key(project_root);
+    if let Ok(mut cache) = GRAPH_CACHE.lock() {
+        cache.remove(&key);
+    }
+    let _ = std::fs::remove_file(cache_file(&key));
+}
+
+/// 文件检查结果
+struct FileRefresh {
+    files: HashMap<String, CachedFile>,
+    /// 有文件新增、删除或内容变化，需要重新链接
+    symbols_changed: bool,
+    /// 只有 mtime/大小变化（内容相同），只需更新缓存
+    metadata_changed: bool,
+    reparsed: usize,
+}
+
+/// 对比文件元数据与缓存条目，只解析内容变化的文件
+fn refresh_files(project_root: &Path, mut cached: HashMap<String, CachedFile>) -> FileRefresh {
+    let walker = WalkBuilder::new(project_root)
+        .hidden(false)
+        .git_ignore(true)
+        .git_global(true)
+        .git_exclude(true)
+        .build();
+
+    let mut files = HashMap::new();
+    let mut to_check: Vec<(String, PathBuf, &'static str, u64, u64, Option<CachedFile>)> = Vec::new();
+    let mut metadata_changed = false;
+
+    for entry in walker.filter_map(|e| e.ok()).filter(|e| e.path().is_file()) {
+        let path = entry.path();
+        let Some(language) = graph_language(path) else {
+            continue;
+        };
+        let Ok(rel_path) = path.strip_prefix(project_root) else {
+            continue;
+        };
+        let rel_path = rel_path.to_string_lossy().replace('\\', "/");
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let mtime = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let size = metadata.len();
+
+        match cached.remove(&rel_path) {
+            Some(file) if file.mtime == mtime && file.size == size => {
+                files.insert(rel_path, file);
+            }
+            previous => to_check.push((rel_path, path.to_path_buf(), language, mtime, size, previous)),
+        }
+    }
+
+    // 缓存中剩下的文件已被删除
+    let mut symbols_changed = !cached.is_empty();
+
+    let checked: Vec<(String, CachedFile, bool)> = to_check
+        .into_par_iter()
+        .filter_map(|(rel_path, path, language, mtime, size, previous)| {
+            let content = std::fs::read_to_string(&path).ok()?;
+            let hash = content_hash(content.as_bytes());
+            if let Some(previous) = previous.filter(|p| p.hash == hash) {
+                return Some((rel_path, CachedFile { mtime, size, ..previous }, false));
+            }
+            let symbols = analyze_file_thread_local(Path::new(&rel_path), &content, language);
+            Some((rel_path, CachedFile { mtime, size, hash, symbols }, true))
+        })
+        .collect();
+
+    let mut reparsed = 0;
+    for (rel_path, file, parsed) in checked {
+        if parsed {
+            reparsed += 1;
+            symbols_changed = true;
+        } else {
+            metadata_changed = true;
+        }
+        files.insert(rel_path, file);
+    }
+
+    FileRefresh {
+        files,
+        symbols_changed,
+        metadata_changed,
+        reparsed,
+    }
+}
+
+/// 项目图谱的缓存文件
+fn cache_file(key: &str) -> PathBuf {
+    let name = &content_hash(key.as_bytes())[..16];
+    crate::config::profile_cache_dir()
+        .join(GRAPH_CACHE_DIR)
+        .join(format!("{}.json", name))
+}
+
+fn load_from_disk(key: &str) -> Option<PersistedGraph> {
+    let content = std::fs::read_to_string(cache_file(key)).ok()?;
+    let persisted: PersistedGraph = serde_json::from_str(&content).ok()?;
+    (persisted.version == GRAPH_CACHE_VERSION).then_some(persisted)
+}
+
+fn save_to_disk(key: &str, files: &HashMap<String, CachedFile>, graph: &CodeGraph) -> anyhow::Result<()> {
+    #[derive(Serialize)]
+    struct PersistedGraphRef<'a> {
+        version: u32,
+        files: &'a HashMap<String, CachedFile>,
+        nodes: Vec<&'a SymbolNode>,
+        edges: Vec<(usize, usize, RelationType)>,
+    }
+
+    let persisted = PersistedGraphRef {
+        version: GRAPH_CACHE_VERSION,
+        files,
+        nodes: graph.graph.node_weights().collect(),
+        edges: graph
+            .graph
+            .edge_references()
+            .map(|edge| (edge.source().index(), edge.target().index(), *edge.weight()))
+            .collect(),
+    };
+
+    let path = cache_file(key);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // 先写临时文件再替换，避免中途退出留下损坏的缓存
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec(&persisted)?)?;
+    std::fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+/// 从保存的节点和边恢复图谱（节点按原索引顺序保存）
+fn restore_graph(nodes: Vec<SymbolNode>, edges: Vec<(usize, usize, RelationType)>) -> CodeGraph {
+    let mut graph = CodeGraph::new();
+    for node in nodes {
+        let id = node.id.clone();
+        let idx = graph.graph.add_node(node);
+        graph.node_map.insert(id, idx);
+    }
+
+    let node_count = graph.graph.node_count();
+    for (from, to, relation) in edges {
+        if from < node_count && to < node_count {
+            graph.graph.add_edge(NodeIndex::new(from), NodeIndex::new(to), relation);
+        }
+    }
+    graph
+}
@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::str::FromStr;
+
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+
+use super::{CodeGraph, RelationType, SymbolNode};
+use crate::neurospec::models::SymbolKind;
+
+/// Output format for graph export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// GraphML (yEd, Gephi, Cytoscape)
+    GraphMl,
+    /// Graphviz DOT
+    Dot,
+    /// Mermaid flowchart (Markdown renderers, GitHub)
+    Mermaid,
+}
+
+impl GraphFormat {
+    /// Conventional file extension for the format
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::GraphMl => "graphml",
+            Self::Dot => "dot",
+            Self::Mermaid => "mmd",
+        }
+    }
+}
+
+impl FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "graphml" => Ok(Self::GraphMl),
+            "dot" | "graphviz" => Ok(Self::Dot),
+            "mermaid" | "mmd" => Ok(Self::Mermaid),
+            other => Err(format!("Unknown graph format '{}', expected graphml, dot or mermaid", other)),
+        }
+    }
+}
+
+/// Serialize the graph, optionally keeping only symbols whose file path starts with `path_prefix`
+///
+/// Edges are kept when both endpoints survive the filter.
+pub fn export_graph(graph: &CodeGraph, format: GraphFormat, path_prefix: Option<&str>) -> String {
+    let mut nodes: Vec<NodeIndex> = graph
+        .graph
+        .node_indices()
+        .filter(|&idx| path_prefix.map_or(true, |prefix| graph.graph[idx].file_path.starts_with(prefix)))
+        .collect();
+    nodes.sort_by(|&a, &b| graph.graph[a].id.cmp(&graph.graph[b].id));
+
+    // Stable short IDs (n0, n1, ...) in ID order, independent of graph insertion order
+    let ids: HashMap<NodeIndex, String> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, &idx)| (idx, format!("n{}", i)))
+        .collect();
+
+    let mut edges: Vec<(&str, &str, RelationType)> = graph
+        .graph
+        .edge_references()
+        .filter_map(|edge| {
+            let from = ids.get(&edge.source())?;
+            let to = ids.get(&edge.target())?;
+            Some((from.as_str(), to.as_str(), *edge.weight()))
+        })
+        .collect();
+    edges.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+
+    let nodes: Vec<(&str, &SymbolNode)> = nodes
+        .iter()
+        .map(|idx| (ids[idx].as_str(), &graph.graph[*idx]))
+        .collect();
+
+    match format {
+        GraphFormat::GraphMl => to_graphml(&nodes, &edges),
+        GraphFormat::Dot => to_dot(&nodes, &edges),
+        GraphFormat::Mermaid => to_mermaid(&nodes, &edges),
+    }
+}
+
+fn kind_name(kind: &SymbolKind) -> &'static str {
+    match kind {
+        SymbolKind::File => "file",
+        SymbolKind::Module => "module",
+        SymbolKind::Class => "class",
+        SymbolKind::Function => "function",
+    }
+}
+
+fn location(node: &SymbolNode) -> String {
+    match node.start_line {
+        Some(line) => format!("{}:{}", node.file_path, line),
+        None => node.file_path.clone(),
+    }
+}
+
+fn to_graphml(nodes: &[(&str, &SymbolNode)], edges: &[(&str, &str, RelationType)]) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    for (key, target, name) in [
+        ("d0", "node", "symbol_id"),
+        ("d1", "node", "name"),
+        ("d2", "node", "kind"),
+        ("d3", "node", "file"),
+        ("d4", "node", "line"),
+        ("d5", "node", "language"),
+        ("d6", "edge", "relation"),
+    ] {
+        let kind = if name == "line" { "int" } else { "string" };
+        let _ = writeln!(
+            out,
+            "  <key id=\"{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"{}\"/>",
+            key, target, name, kind
+        );
+    }
+    out.push_str("  <graph id=\"code_graph\" edgedefault=\"directed\">\n");
+
+    for (id, node) in nodes {
+        let _ = writeln!(out, "    <node id=\"{}\">", id);
+        let _ = writeln!(out, "      <data key=\"d0\">{}</data>", xml_escape(&node.id));
+        let _ = writeln!(out, "      <data key=\"d1\">{}</data>", xml_escape(&node.name));
+        let _ = writeln!(out, "      <data key=\"d2\">{}</data>", kind_name(&node.kind));
+        let _ = writeln!(out, "      <data key=\"d3\">{}</data>", xml_escape(&node.file_path));
+        if let Some(line) = node.start_line {
+            let _ = writeln!(out, "      <data key=\"d4\">{}</data>", line);
+        }
+        let _ = writeln!(out, "      <data key=\"d5\">{}</data>", xml_escape(&node.language));
+        out.push_str("    </node>\n");
+    }
+
+    for (i, (from, to, relation)) in edges.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "    <edge id=\"e{}\" source=\"{}\" target=\"{}\"><data key=\"d6\">{:?}</data></edge>",
+            i, from, to, relation
+        );
+    }
+
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+fn to_dot(nodes: &[(&str, &SymbolNode)], edges: &[(&str, &str, RelationType)]) -> String {
+    let mut out = String::from("digraph code_graph {\n  rankdir=LR;\n  node [fontsize=10];\n");
+
+    for (id, node) in nodes {
+        let shape = match node.kind {
+            SymbolKind::File => "folder",
+            SymbolKind::Module => "tab",
+            SymbolKind::Class => "box",
+            SymbolKind::Function => "ellipse",
+        };
+        let label = format!("{}\n{}", node.name, location(node));
+        let _ = writeln!(out, "  {} [label=\"{}\", shape={}];", id, dot_escape(&label), shape);
+    }
+
+    for (from, to, relation) in edges {
+        let style = match relation {
+            RelationType::Calls => "solid",
+            RelationType::Imports => "dashed",
+            RelationType::Inherits => "bold",
+            RelationType::Defines | RelationType::References => "dotted",
+        };
+        let _ = writeln!(out, "  {} -> {} [label=\"{:?}\", style={}];", from, to, relation, style);
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+fn to_mermaid(nodes: &[(&str, &SymbolNode)], edges: &[(&str, &str, RelationType)]) -> String {
+    let mut out = String::from("flowchart LR\n");
+
+    for (id, node) in nodes {
+        let label = mermaid_escape(&format!("{}<br/>{}", node.name, location(node)));
+        let _ = match node.kind {
+            SymbolKind::File | SymbolKind::Module => writeln!(out, "  {}[/\"{}\"/]", id, label),
+            SymbolKind::Class => writeln!(out, "  {}[\"{}\"]", id, label),
+            SymbolKind::Function => writeln!(out, "  {}(\"{}\")", id, label),
+        };
+    }
+
+    for (from, to, relation) in edges {
+        let arrow = match relation {
+            RelationType::Imports => "-.->",
+            RelationType::Inherits => "==>",
+            _ => "-->",
+        };
+        let _ = writeln!(out, "  {} {}|{:?}| {}", from, arrow, relation, to);
+    }
+
+    out
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn mermaid_escape(s: &str) -> String {
+    // `<br/>` is kept as a line break, everything else that would end the label is entity-encoded
+    s.replace('"', "#quot;")
+}
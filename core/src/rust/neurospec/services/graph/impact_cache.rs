@@ -0,0 +1,101 @@
+//! 影响分析结果缓存
+//!
+//! 以（项目、符号、深度、图谱代数）为键缓存分析结果，连续追问
+//! "改 X 会怎样、改 Y 又会怎样" 时无需重复构建图谱和遍历。
+//! 文件监听事件或全量重建会推进图谱代数，旧结果随之失效。
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use lazy_static::lazy_static;
+
+use crate::mcp::tools::unified_store::{
+    graph_generation, is_project_watched, is_search_initialized, process_project_file_changes,
+};
+
+/// 最多缓存的结果数
+const MAX_ENTRIES: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    project_root: String,
+    symbol: String,
+    depth: usize,
+}
+
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    generation: u64,
+    result: String,
+    cached_at: Instant,
+}
+
+lazy_static! {
+    static ref IMPACT_CACHE: Mutex<HashMap<CacheKey, CacheEntry>> = Mutex::new(HashMap::new());
+}
+
+/// 当前可用于缓存的图谱代数
+///
+/// 先消费该项目待处理的文件变化；项目未被监听时无法感知变化，返回 None 表示不缓存
+pub fn current_generation(project_root: &Path) -> Option<u64> {
+    if !is_search_initialized() {
+        return None;
+    }
+    if let Err(e) = process_project_file_changes(project_root) {
+        crate::log_debug!("Failed to process file changes before impact lookup: {}", e);
+    }
+    is_project_watched(project_root).then(graph_generation)
+}
+
+/// 查找同一图谱代数下的缓存结果
+pub fn get(project_root: &str, symbol: &str, depth: usize, generation: u64) -> Option<String> {
+    let key = CacheKey {
+        project_root: project_root.to_string(),
+        symbol: symbol.to_string(),
+        depth,
+    };
+    let mut cache = IMPACT_CACHE.lock().ok()?;
+    match cache.get(&key) {
+        Some(entry) if entry.generation == generation => Some(entry.result.clone()),
+        Some(_) => {
+            cache.remove(&key);
+            None
+        }
+        None => None,
+    }
+}
+
+/// 写入结果，超出容量时先清理过期代数，再淘汰最早的条目
+pub fn insert(project_root: &str, symbol: &str, depth: usize, generation: u64, result: String) {
+    let Ok(mut cache) = IMPACT_CACHE.lock() else {
+        return;
+    };
+
+    if cache.len() >= MAX_ENTRIES {
+        cache.retain(|_, entry| entry.generation == generation);
+    }
+    if cache.len() >= MAX_ENTRIES {
+        let oldest = cache
+            .iter()
+            .min_by_key(|(_, entry)| entry.cached_at)
+            .map(|(key, _)| key.clone());
+        if let Some(oldest) = oldest {
+            cache.remove(&oldest);
+        }
+    }
+
+    cache.insert(
+        CacheKey {
+            project_root: project_root.to_string(),
+            symbol: symbol.to_string(),
+            depth,
+        },
+        CacheEntry {
+            generation,
+            result,
+            cached_at: Instant::now(),
+        },
+    );
+}
@@ -1,8 +1,14 @@
 pub mod builder;
+pub mod cache;
+pub mod export;
+pub mod impact_cache;
 
-use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::algo::tarjan_scc;
+use petgraph::graph::{DiGraph, EdgeIndex, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::Direction;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use crate::neurospec::models::{Symbol, SymbolKind};
 
@@ -21,6 +27,15 @@ pub enum RelationType {
     References,
 }
 
+impl RelationType {
+    /// Whether a change to B can break A for an edge A → B
+    ///
+    /// Impact analysis follows these edges backwards: callers, importers and subclasses.
+    pub fn propagates_impact(self) -> bool {
+        matches!(self, Self::Calls | Self::Imports | Self::Inherits)
+    }
+}
+
 /// Node in the code knowledge graph
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SymbolNode {
@@ -30,6 +45,12 @@ pub struct SymbolNode {
     pub file_path: String,
     pub language: String,
     pub signature: Option<String>,
+    /// Definition start line (1-based), if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<u32>,
+    /// Definition end line (1-based), if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<u32>,
     // Store the original symbol for reference if needed
     // pub original_symbol: Symbol,
 }
@@ -47,10 +68,30 @@ impl SymbolNode {
             file_path: symbol.path.clone(),
             language: symbol.language.clone().unwrap_or_default(),
             signature: symbol.signature.clone(),
+            start_line: symbol.start_line,
+            end_line: symbol.end_line,
         }
     }
 }
 
+/// Granularity of dependency cycle detection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CycleLevel {
+    /// Files that depend on each other through imports, calls or inheritance
+    Module,
+    /// Functions and classes that call each other (mutual recursion)
+    Function,
+}
+
+/// A dependency cycle (strongly connected component with more than one member)
+#[derive(Debug)]
+pub struct DependencyCycle<'a> {
+    /// File paths (module level) or symbol IDs (function level), sorted
+    pub members: Vec<String>,
+    /// Edges that keep the members connected, one per ordered member pair
+    pub edges: Vec<(&'a SymbolNode, &'a SymbolNode, RelationType)>,
+}
+
 /// The Code Knowledge Graph
 pub struct CodeGraph {
     pub graph: DiGraph<SymbolNode, RelationType>,
@@ -105,4 +146,163 @@ impl CodeGraph {
         // If target doesn't exist yet, we might want to create a "Ghost" node or queue it
         // For now, we skip it
     }
+
+    /// Symbols that directly call `symbol_id`
+    pub fn callers_of(&self, symbol_id: &str) -> Vec<&SymbolNode> {
+        let Some(&idx) = self.node_map.get(symbol_id) else {
+            return Vec::new();
+        };
+
+        self.graph
+            .edges_directed(idx, Direction::Incoming)
+            .filter(|edge| *edge.weight() == RelationType::Calls)
+            .map(|edge| &self.graph[edge.source()])
+            .collect()
+    }
+
+    /// Symbols that use `symbol_id` directly, with how they use it (everything but `Defines`)
+    pub fn references_to(&self, symbol_id: &str) -> Vec<(&SymbolNode, RelationType)> {
+        let Some(&idx) = self.node_map.get(symbol_id) else {
+            return Vec::new();
+        };
+
+        self.graph
+            .edges_directed(idx, Direction::Incoming)
+            .filter(|edge| *edge.weight() != RelationType::Defines)
+            .map(|edge| (&self.graph[edge.source()], *edge.weight()))
+            .collect()
+    }
+
+    /// Innermost non-file symbol in `file_path` whose definition spans `line`
+    pub fn enclosing_symbol(&self, file_path: &str, line: u32) -> Option<&SymbolNode> {
+        self.graph
+            .node_weights()
+            .filter(|node| node.file_path == file_path && !matches!(node.kind, SymbolKind::File))
+            .filter_map(|node| {
+                let start = node.start_line?;
+                let end = node.end_line?;
+                (start <= line && line <= end).then_some((node, end - start))
+            })
+            .min_by_key(|&(_, span)| span)
+            .map(|(node, _)| node)
+    }
+
+    /// Symbols that depend on `symbol_id` (callers, importers, subclasses) within `depth` hops
+    ///
+    /// Each node is paired with the hop count at which it was first reached.
+    pub fn transitive_dependents(&self, symbol_id: &str, depth: usize) -> Vec<(&SymbolNode, usize)> {
+        self.walk_dependencies(symbol_id, depth, Direction::Incoming)
+    }
+
+    /// Symbols that `symbol_id` depends on (callees, imports, base types) within `depth` hops
+    pub fn transitive_dependencies(&self, symbol_id: &str, depth: usize) -> Vec<(&SymbolNode, usize)> {
+        self.walk_dependencies(symbol_id, depth, Direction::Outgoing)
+    }
+
+    /// Breadth-first walk over impact-propagating edges in one direction
+    fn walk_dependencies(
+        &self,
+        symbol_id: &str,
+        depth: usize,
+        direction: Direction,
+    ) -> Vec<(&SymbolNode, usize)> {
+        let Some(&start) = self.node_map.get(symbol_id) else {
+            return Vec::new();
+        };
+
+        let mut visited = HashSet::from([start]);
+        let mut queue = VecDeque::from([(start, 0)]);
+        let mut result = Vec::new();
+
+        while let Some((idx, d)) = queue.pop_front() {
+            if d >= depth {
+                continue;
+            }
+            for edge in self.graph.edges_directed(idx, direction) {
+                if !edge.weight().propagates_impact() {
+                    continue;
+                }
+                let next = match direction {
+                    Direction::Incoming => edge.source(),
+                    Direction::Outgoing => edge.target(),
+                };
+                if visited.insert(next) {
+                    result.push((&self.graph[next], d + 1));
+                    queue.push_back((next, d + 1));
+                }
+            }
+        }
+        result
+    }
+
+    /// Dependency cycles at the given level, largest first
+    pub fn dependency_cycles(&self, level: CycleLevel) -> Vec<DependencyCycle<'_>> {
+        // Project the symbol graph onto the units of the requested level
+        let unit_of = |idx: NodeIndex| {
+            let node = &self.graph[idx];
+            match level {
+                CycleLevel::Module => Some(node.file_path.as_str()),
+                CycleLevel::Function => {
+                    (!matches!(node.kind, SymbolKind::File)).then_some(node.id.as_str())
+                }
+            }
+        };
+        let follows = |relation: RelationType| match level {
+            CycleLevel::Module => relation.propagates_impact(),
+            CycleLevel::Function => relation == RelationType::Calls,
+        };
+
+        let mut units: DiGraph<&str, ()> = DiGraph::new();
+        let mut unit_index: HashMap<&str, NodeIndex> = HashMap::new();
+        let mut unit_edges: HashMap<(NodeIndex, NodeIndex), Vec<EdgeIndex>> = HashMap::new();
+        for edge in self.graph.edge_references() {
+            if !follows(*edge.weight()) {
+                continue;
+            }
+            let (Some(from), Some(to)) = (unit_of(edge.source()), unit_of(edge.target())) else {
+                continue;
+            };
+            // Same-file references and direct recursion are not cycles between units
+            if from == to {
+                continue;
+            }
+            let from = *unit_index.entry(from).or_insert_with(|| units.add_node(from));
+            let to = *unit_index.entry(to).or_insert_with(|| units.add_node(to));
+            units.update_edge(from, to, ());
+            unit_edges.entry((from, to)).or_default().push(edge.id());
+        }
+
+        let mut cycles: Vec<DependencyCycle<'_>> = tarjan_scc(&units)
+            .into_iter()
+            .filter(|component| component.len() > 1)
+            .map(|component| {
+                let mut members: Vec<String> = component.iter().map(|&idx| units[idx].to_string()).collect();
+                members.sort();
+
+                let member_set: HashSet<NodeIndex> = component.iter().copied().collect();
+                let mut edges: Vec<(&SymbolNode, &SymbolNode, RelationType)> = unit_edges
+                    .iter()
+                    .filter(|((from, to), _)| member_set.contains(from) && member_set.contains(to))
+                    .filter_map(|(_, symbol_edges)| {
+                        // Report the first edge by location so output is stable
+                        symbol_edges
+                            .iter()
+                            .filter_map(|&edge| {
+                                let (from, to) = self.graph.edge_endpoints(edge)?;
+                                Some((&self.graph[from], &self.graph[to], self.graph[edge]))
+                            })
+                            .min_by(|a, b| (&a.0.id, a.0.start_line).cmp(&(&b.0.id, b.0.start_line)))
+                    })
+                    .collect();
+                edges.sort_by(|a, b| {
+                    (&a.0.file_path, a.0.start_line, &a.1.id).cmp(&(&b.0.file_path, b.0.start_line, &b.1.id))
+                });
+
+                DependencyCycle { members, edges }
+            })
+            .collect();
+
+        cycles.sort_by(|a, b| b.members.len().cmp(&a.members.len()).then_with(|| a.members.cmp(&b.members)));
+        cycles
+    }
 }
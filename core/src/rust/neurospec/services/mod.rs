@@ -3,20 +3,25 @@
 //! 提供项目分析和重构辅助功能
 
 pub mod agents_parser;
+pub mod architecture;
 pub mod analyzer;
 pub mod embedding;
 pub mod graph;
+pub mod plan_executor;
 pub mod refactor;
+pub mod risk;
 pub mod xray_engine;
 
-pub use agents_parser::{AgentsConfig, detect_agents_md};
+pub use agents_parser::{AgentsConfig, detect_agents_md, detect_agents_md_chain};
 pub use analyzer::*;
 pub use embedding::{
     EmbeddingService, EmbeddingConfig, EmbeddingProvider, cosine_similarity,
     init_global_embedding_service, get_global_embedding_service,
     has_embedding_service, is_embedding_available, reload_embedding_service,
-    compute_similarity, find_similar,
+    flush_embedding_cache, set_embedding_enabled, is_embedding_paused,
+    compute_similarity, embed_text, find_similar,
 };
 pub use graph::*;
+pub use plan_executor::{NSPStepEdit, NSPTextReplacement, PlanDryRunReport, PlanExecutionReport, PlanExecutor, StepOutcome, StepStatus};
 pub use refactor::*;
 pub use xray_engine::*;
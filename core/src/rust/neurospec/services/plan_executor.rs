@@ -0,0 +1,778 @@
+//! NSP 计划执行器
+//!
+//! 按依赖顺序逐步执行 NSP 计划：CREATE/MODIFY/REFACTOR 转换为编辑交给 EditApplier，
+//! 在检查点弹窗等待确认，并把每一步的结果记录为代码修改记忆。
+//! 执行进度保存在项目记忆库中，中断（崩溃、检查点中止）后可从已完成的步骤继续。
+//! 执行前可以在内存副本上预演整个计划，得到每一步的 diff 与冲突
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::mcp::handlers::extract_selected_options;
+use crate::mcp::tools::interaction::InteractionTool;
+use crate::mcp::tools::memory::{infer_change_type, ChangeTracker, ChangeType, PlanRunStatus, StoredPlan};
+use crate::mcp::types::{PopupPreview, PopupRequest};
+use crate::mcp::utils::{ToolError, ToolErrorCode};
+use crate::neurospec::models::{plan_path_escapes_root, NSPAction, NSPExecutionStep, NSPRiskLevel, NSP};
+use crate::neurospec::services::refactor::validator::Validator;
+use crate::neurospec::services::refactor::{Edit, EditApplier};
+
+/// 检查点选项：执行
+const CHECKPOINT_RUN: &str = "▶️ 执行此步骤";
+/// 检查点选项：跳过
+const CHECKPOINT_SKIP: &str = "⏭️ 跳过此步骤";
+/// 检查点选项：中止
+const CHECKPOINT_ABORT: &str = "⏹️ 中止计划";
+
+/// 步骤要写入的内容
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NSPStepEdit {
+    /// Step this content belongs to
+    pub step_id: i32,
+    /// Full file content: required for CREATE, replaces the whole file for MODIFY/REFACTOR
+    #[serde(default)]
+    pub content: Option<String>,
+    /// Text replacements for MODIFY/REFACTOR; each `old` must occur exactly once in the file
+    #[serde(default)]
+    pub replacements: Vec<NSPTextReplacement>,
+}
+
+/// 文本替换
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NSPTextReplacement {
+    /// Existing text to replace
+    pub old: String,
+    /// Replacement text
+    pub new: String,
+}
+
+/// 步骤执行状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    /// 已执行（ANALYZE 步骤没有可应用的修改，也视为已执行）
+    Applied,
+    /// 在检查点被跳过，或依赖的步骤未执行
+    Skipped,
+    /// 执行失败（修改已回滚）
+    Failed,
+    /// 在检查点中止了计划
+    Aborted,
+    /// 计划提前结束，未执行
+    NotRun,
+}
+
+/// 单个步骤的执行结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepOutcome {
+    pub step_id: i32,
+    pub title: String,
+    pub status: StepStatus,
+    pub message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
+    /// 记录的代码修改记忆 ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_id: Option<String>,
+    /// 失败时的稳定错误码
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<ToolErrorCode>,
+}
+
+/// 计划执行报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanExecutionReport {
+    /// 计划 ID，用于继续执行
+    pub plan_id: String,
+    /// 所有步骤都已执行
+    pub completed: bool,
+    pub steps: Vec<StepOutcome>,
+}
+
+/// 预演状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DryRunStatus {
+    /// 可以应用
+    Ok,
+    /// 无法应用（替换文本找不到、文件状态不符、引入语法错误等）
+    Conflict,
+    /// 依赖的步骤无法应用
+    Blocked,
+}
+
+/// 单个步骤的预演结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepPreview {
+    pub step_id: i32,
+    pub title: String,
+    pub status: DryRunStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// 该步骤对文件的统一 diff
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// 计划预演报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanDryRunReport {
+    /// 所有步骤都可以应用
+    pub ok: bool,
+    pub steps: Vec<StepPreview>,
+}
+
+/// 步骤对文件的修改
+enum StepChange {
+    Delete,
+    Edits(Vec<Edit>),
+}
+
+/// 检查点的用户决定
+enum CheckpointDecision {
+    Run,
+    Skip,
+    Abort,
+}
+
+/// NSP 计划执行器
+pub struct PlanExecutor {
+    project_root: PathBuf,
+    plan: NSP,
+    edits: HashMap<i32, NSPStepEdit>,
+    checkpoints: HashSet<i32>,
+    plan_id: String,
+    /// 之前的执行中已完成的步骤
+    applied: HashSet<i32>,
+}
+
+impl PlanExecutor {
+    /// 创建执行器
+    ///
+    /// `checkpoints` 为 None 时使用默认检查点：带风险标签的步骤，
+    /// 以及 HIGH 风险计划中的所有修改步骤。DELETE 步骤始终需要确认。
+    pub fn new(project_root: &Path, plan: NSP, edits: Vec<NSPStepEdit>, checkpoints: Option<Vec<i32>>) -> Self {
+        let high_risk = matches!(plan.meta.risk_level, NSPRiskLevel::High);
+        let mut checkpoints: HashSet<i32> = match checkpoints {
+            Some(ids) => ids.into_iter().collect(),
+            None => plan
+                .execution_plan
+                .iter()
+                .filter(|s| !s.risk_tags.is_empty() || (high_risk && s.action.is_edit()))
+                .map(|s| s.step_id)
+                .collect(),
+        };
+        checkpoints.extend(
+            plan.execution_plan
+                .iter()
+                .filter(|s| matches!(s.action, NSPAction::Delete))
+                .map(|s| s.step_id),
+        );
+
+        Self {
+            project_root: project_root.to_path_buf(),
+            plan,
+            edits: edits.into_iter().map(|e| (e.step_id, e)).collect(),
+            checkpoints,
+            plan_id: uuid::Uuid::new_v4().to_string(),
+            applied: HashSet::new(),
+        }
+    }
+
+    /// 继续之前中断的执行：沿用计划 ID，跳过已完成的步骤
+    pub fn resume(mut self, plan_id: String, applied: HashSet<i32>) -> Self {
+        self.plan_id = plan_id;
+        self.applied = applied;
+        self
+    }
+
+    /// 执行计划，遇到失败或中止时停止后续步骤
+    pub async fn run(self) -> PlanExecutionReport {
+        let tracker = ChangeTracker::new(&self.project_root.to_string_lossy())
+            .inspect_err(|e| log::warn!("Change tracker unavailable, step outcomes will not be recorded: {}", e))
+            .ok();
+
+        let mut outcomes: Vec<StepOutcome> = Vec::new();
+        let mut statuses: HashMap<i32, StepStatus> = HashMap::new();
+        let mut stopped = false;
+
+        if let Some(ref tracker) = tracker {
+            self.save_progress(tracker, PlanRunStatus::Running, &outcomes);
+        }
+
+        for step in self.ordered_steps() {
+            if stopped {
+                outcomes.push(Self::outcome(step, StepStatus::NotRun, "Plan stopped before this step".to_string(), vec![]));
+                continue;
+            }
+            if self.applied.contains(&step.step_id) {
+                outcomes.push(Self::outcome(step, StepStatus::Applied, "Applied in a previous run".to_string(), vec![]));
+                statuses.insert(step.step_id, StepStatus::Applied);
+                continue;
+            }
+
+            let mut outcome = self.run_step(step, &statuses).await;
+            stopped = matches!(outcome.status, StepStatus::Failed | StepStatus::Aborted);
+
+            if let Some(ref tracker) = tracker {
+                outcome.memory_id = self.record_outcome(tracker, step, &outcome);
+            }
+            statuses.insert(step.step_id, outcome.status);
+            outcomes.push(outcome);
+
+            if let Some(ref tracker) = tracker {
+                self.save_progress(tracker, PlanRunStatus::Running, &outcomes);
+            }
+        }
+
+        let status = if outcomes.iter().any(|o| o.status == StepStatus::Failed) {
+            PlanRunStatus::Failed
+        } else if outcomes.iter().any(|o| o.status == StepStatus::Aborted) {
+            PlanRunStatus::Paused
+        } else {
+            PlanRunStatus::Completed
+        };
+        if let Some(ref tracker) = tracker {
+            self.save_progress(tracker, status, &outcomes);
+        }
+
+        PlanExecutionReport {
+            plan_id: self.plan_id.clone(),
+            completed: outcomes.iter().all(|o| o.status == StepStatus::Applied),
+            steps: outcomes,
+        }
+    }
+
+    /// 保存计划与执行进度，进程崩溃时停留在最后完成的步骤
+    fn save_progress(&self, tracker: &ChangeTracker, status: PlanRunStatus, outcomes: &[StepOutcome]) {
+        let now = chrono::Utc::now();
+        let record = StoredPlan {
+            id: self.plan_id.clone(),
+            intent_summary: self.plan.meta.intent_summary.clone(),
+            status,
+            plan: serde_json::to_value(&self.plan).unwrap_or_default(),
+            edits: serde_json::to_value(self.edits.values().collect::<Vec<_>>()).unwrap_or_default(),
+            checkpoints: self.checkpoints.iter().copied().collect(),
+            outcomes: serde_json::to_value(outcomes).unwrap_or_default(),
+            // 已存在的记录保留原创建时间
+            created_at: now,
+            updated_at: now,
+        };
+        if let Err(e) = tracker.save_plan(&record) {
+            log::warn!("Failed to save progress of plan {}: {}", self.plan_id, e);
+        }
+    }
+
+    /// 按依赖排序步骤，同一层级保持计划中的顺序（计划已通过校验，不存在循环依赖）
+    fn ordered_steps(&self) -> Vec<&NSPExecutionStep> {
+        let mut remaining: Vec<&NSPExecutionStep> = self.plan.execution_plan.iter().collect();
+        let mut done = HashSet::new();
+        let mut ordered = Vec::with_capacity(remaining.len());
+
+        while !remaining.is_empty() {
+            let next = remaining
+                .iter()
+                .position(|s| s.depends_on.iter().all(|d| done.contains(d)))
+                .unwrap_or(0);
+            let step = remaining.remove(next);
+            done.insert(step.step_id);
+            ordered.push(step);
+        }
+        ordered
+    }
+
+    async fn run_step(&self, step: &NSPExecutionStep, statuses: &HashMap<i32, StepStatus>) -> StepOutcome {
+        if let Some(dep) = step.depends_on.iter().find(|d| statuses.get(d) != Some(&StepStatus::Applied)) {
+            return Self::outcome(step, StepStatus::Skipped, format!("Dependency step {} was not applied", dep), vec![]);
+        }
+
+        if self.checkpoints.contains(&step.step_id) {
+            match self.confirm_step(step).await {
+                CheckpointDecision::Run => {}
+                CheckpointDecision::Skip => {
+                    return Self::outcome(step, StepStatus::Skipped, "Skipped at checkpoint".to_string(), vec![]);
+                }
+                CheckpointDecision::Abort => {
+                    return Self::outcome(step, StepStatus::Aborted, "Plan aborted at checkpoint".to_string(), vec![]);
+                }
+            }
+        }
+
+        match self.apply_step(step) {
+            Ok((message, files)) => Self::outcome(step, StepStatus::Applied, message, files),
+            Err(e) => StepOutcome {
+                error_code: Some(ToolError::from_anyhow(&e).code),
+                ..Self::outcome(step, StepStatus::Failed, e.to_string(), vec![])
+            },
+        }
+    }
+
+    /// 检查点弹窗；无法获得明确答复（超时、取消、无 GUI）时中止
+    async fn confirm_step(&self, step: &NSPExecutionStep) -> CheckpointDecision {
+        let mut message = format!(
+            "# ⏸️ NSP 检查点\n\n**{}**\n\n#### Step {}: {}\n- **action**: {:?}\n",
+            self.plan.meta.intent_summary, step.step_id, step.title, step.action
+        );
+        if let Some(ref path) = step.path {
+            message.push_str(&format!("- **path**: `{}`\n", path));
+        }
+        if !step.risk_tags.is_empty() {
+            message.push_str(&format!("- **risk_tags**: {}\n", step.risk_tags.join(", ")));
+        }
+        message.push_str(&format!("- **instruction**: {}\n", step.instruction));
+
+        // 附上该步骤将产生的 diff，用户确认的是具体修改而非意图
+        let preview = if step.action.is_edit() {
+            self.simulate_step(step, &HashMap::new())
+                .ok()
+                .and_then(|(_, _, diff)| PopupPreview::Diff { diff, language: None }.resolve().ok())
+        } else {
+            None
+        };
+
+        let popup_request = PopupRequest {
+            id: uuid::Uuid::new_v4().to_string(),
+            message,
+            predefined_options: Some(vec![
+                CHECKPOINT_RUN.to_string(),
+                CHECKPOINT_SKIP.to_string(),
+                CHECKPOINT_ABORT.to_string(),
+            ]),
+            is_markdown: true,
+            multi_select: false,
+            input: None,
+            preview,
+            picker: None,
+            timeout_secs: None,
+            default_option: None,
+            form: None,
+            language: None,
+        };
+
+        let response = match InteractionTool::show_popup_or_headless(&popup_request).await {
+            Ok(response) => response,
+            Err(e) => {
+                log::warn!("Checkpoint popup for step {} failed: {}", step.step_id, e);
+                return CheckpointDecision::Abort;
+            }
+        };
+
+        // 无 GUI 时返回结构化 JSON，否则为 daemon 解析后的文本
+        let selected = match serde_json::from_str::<serde_json::Value>(&response) {
+            Ok(json) => json
+                .pointer("/selected_options/0")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            Err(_) => extract_selected_options(&response).into_iter().next(),
+        };
+        match selected.as_deref() {
+            Some(CHECKPOINT_RUN) => CheckpointDecision::Run,
+            Some(CHECKPOINT_SKIP) => CheckpointDecision::Skip,
+            _ => CheckpointDecision::Abort,
+        }
+    }
+
+    /// 应用步骤的修改，语法校验失败时回滚
+    fn apply_step(&self, step: &NSPExecutionStep) -> anyhow::Result<(String, Vec<String>)> {
+        if matches!(step.action, NSPAction::Analyze) {
+            return Ok(("Analysis step, nothing to apply".to_string(), vec![]));
+        }
+
+        let path = Self::step_path(step)?;
+        let file = self.project_root.join(path);
+        let original = read_optional(&file)?;
+
+        match self.step_change(step, path, original.as_deref())? {
+            StepChange::Delete => {
+                fs::remove_file(&file).map_err(|e| anyhow::anyhow!("Failed to delete {}: {}", path, e))?;
+                return Ok((format!("Deleted {}", path), vec![path.to_string()]));
+            }
+            StepChange::Edits(edits) => {
+                EditApplier::apply(edits)?;
+            }
+        }
+
+        if let Some(language) = Self::language_for(path) {
+            if !Validator::validate_file(&file.to_string_lossy(), language).unwrap_or(true) {
+                Self::rollback(&file, original.as_deref());
+                return Err(ToolError::new(
+                    ToolErrorCode::ValidationFailed,
+                    format!("Syntax errors introduced in {}, changes rolled back", path),
+                )
+                .into());
+            }
+        }
+
+        let verb = if original.is_some() { "Modified" } else { "Created" };
+        Ok((format!("{} {}", verb, path), vec![path.to_string()]))
+    }
+
+    /// 预演整个计划：在内存副本上依次应用各步骤，返回每一步的 diff 与冲突
+    pub fn dry_run(&self) -> PlanDryRunReport {
+        // 相对路径 → 预演到当前步骤时的内容（None 表示文件不存在）
+        let mut files: HashMap<String, Option<String>> = HashMap::new();
+        let mut statuses: HashMap<i32, DryRunStatus> = HashMap::new();
+        let mut steps = Vec::new();
+
+        for step in self.ordered_steps() {
+            let mut preview = StepPreview {
+                step_id: step.step_id,
+                title: step.title.clone(),
+                status: DryRunStatus::Ok,
+                path: step.path.clone(),
+                diff: None,
+                message: None,
+            };
+
+            if let Some(dep) = step.depends_on.iter().find(|d| statuses.get(d) != Some(&DryRunStatus::Ok)) {
+                preview.status = DryRunStatus::Blocked;
+                preview.message = Some(format!("Dependency step {} cannot be applied", dep));
+            } else if step.action.is_edit() {
+                match self.simulate_step(step, &files) {
+                    Ok((path, content, diff)) => {
+                        let broken = content.as_deref().zip(Self::language_for(&path)).is_some_and(|(content, language)| {
+                            !Validator::validate_source(content, language).unwrap_or(true)
+                        });
+                        if broken {
+                            preview.status = DryRunStatus::Conflict;
+                            preview.message = Some(format!("Would introduce syntax errors in {}", path));
+                        } else {
+                            files.insert(path, content);
+                        }
+                        preview.diff = Some(diff);
+                    }
+                    Err(e) => {
+                        preview.status = DryRunStatus::Conflict;
+                        preview.message = Some(e.to_string());
+                    }
+                }
+            }
+
+            statuses.insert(step.step_id, preview.status);
+            steps.push(preview);
+        }
+
+        PlanDryRunReport {
+            ok: steps.iter().all(|s| s.status == DryRunStatus::Ok),
+            steps,
+        }
+    }
+
+    /// 在内存中应用一步，返回 (路径, 修改后的内容, diff)
+    fn simulate_step(
+        &self,
+        step: &NSPExecutionStep,
+        files: &HashMap<String, Option<String>>,
+    ) -> anyhow::Result<(String, Option<String>, String)> {
+        let path = Self::step_path(step)?;
+        let current = match files.get(path) {
+            Some(content) => content.clone(),
+            None => read_optional(&self.project_root.join(path))?,
+        };
+
+        let updated = match self.step_change(step, path, current.as_deref())? {
+            StepChange::Delete => None,
+            StepChange::Edits(edits) => Some(EditApplier::apply_to_content(current.as_deref().unwrap_or_default(), &edits)?),
+        };
+        let diff = unified_diff(path, current.as_deref(), updated.as_deref());
+        Ok((path.to_string(), updated, diff))
+    }
+
+    /// 步骤的目标路径，不允许指向项目根目录之外
+    fn step_path(step: &NSPExecutionStep) -> anyhow::Result<&str> {
+        let path = step.path
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("Step {} has no target path", step.step_id))?;
+        if plan_path_escapes_root(path) {
+            return Err(ToolError::new(
+                ToolErrorCode::InvalidParams,
+                format!("Step {} path '{}' is outside the project root", step.step_id, path),
+            )
+            .into());
+        }
+        Ok(path)
+    }
+
+    /// 根据文件当前内容计算步骤的修改（执行与预演共用）
+    fn step_change(&self, step: &NSPExecutionStep, path: &str, current: Option<&str>) -> anyhow::Result<StepChange> {
+        let Some(current) = current else {
+            if !matches!(step.action, NSPAction::Create) {
+                return Err(anyhow::anyhow!("File {} does not exist", path));
+            }
+            let content = self
+                .edits
+                .get(&step.step_id)
+                .and_then(|e| e.content.clone())
+                .ok_or_else(|| anyhow::anyhow!("CREATE step {} needs content", step.step_id))?;
+            let file = self.project_root.join(path).to_string_lossy().to_string();
+            return Ok(StepChange::Edits(vec![Edit::new(file, 0, 0, content)]));
+        };
+
+        match step.action {
+            NSPAction::Create => Err(anyhow::anyhow!("Cannot create {}: file already exists", path)),
+            NSPAction::Delete => Ok(StepChange::Delete),
+            _ => {
+                let step_edit = self
+                    .edits
+                    .get(&step.step_id)
+                    .ok_or_else(|| anyhow::anyhow!("No content supplied for step {}", step.step_id))?;
+                let file = self.project_root.join(path).to_string_lossy().to_string();
+                Self::modify_edits(&file, current, step_edit).map(StepChange::Edits)
+            }
+        }
+    }
+
+    /// MODIFY/REFACTOR 的编辑：整体替换或逐段文本替换
+    fn modify_edits(file: &str, current: &str, step_edit: &NSPStepEdit) -> anyhow::Result<Vec<Edit>> {
+        if let Some(ref content) = step_edit.content {
+            return Ok(vec![Edit::new(file.to_string(), 0, current.len(), content.clone())]);
+        }
+        if step_edit.replacements.is_empty() {
+            return Err(anyhow::anyhow!("Step {} needs content or replacements", step_edit.step_id));
+        }
+
+        step_edit
+            .replacements
+            .iter()
+            .map(|r| {
+                let mut matches = current.match_indices(&r.old);
+                match (matches.next(), matches.next()) {
+                    (Some((start, _)), None) if !r.old.is_empty() => {
+                        Ok(Edit::new(file.to_string(), start, start + r.old.len(), r.new.clone()))
+                    }
+                    (None, _) => Err(anyhow::anyhow!("Text to replace not found: {:?}", r.old)),
+                    _ => Err(anyhow::anyhow!("Text to replace is not unique: {:?}", r.old)),
+                }
+            })
+            .collect()
+    }
+
+    fn rollback(file: &Path, original: Option<&str>) {
+        let result = match original {
+            Some(content) => fs::write(file, content),
+            None => fs::remove_file(file),
+        };
+        if let Err(e) = result {
+            log::error!("Failed to roll back {}: {}", file.display(), e);
+        }
+    }
+
+    /// 可做语法校验的语言
+    fn language_for(path: &str) -> Option<&'static str> {
+        match Path::new(path).extension()?.to_str()? {
+            "rs" => Some("rust"),
+            "ts" | "js" => Some("typescript"),
+            "py" => Some("python"),
+            "c" | "h" => Some("c"),
+            "cpp" | "cc" | "cxx" | "hpp" | "hh" | "hxx" => Some("cpp"),
+            _ => None,
+        }
+    }
+
+    /// 记录步骤结果为代码修改记忆
+    fn record_outcome(&self, tracker: &ChangeTracker, step: &NSPExecutionStep, outcome: &StepOutcome) -> Option<String> {
+        let change_type = match step.action {
+            NSPAction::Refactor => ChangeType::Refactor,
+            _ => infer_change_type(&step.title, &self.plan.meta.intent_summary),
+        };
+        let summary = format!(
+            "[NSP step {}] {} ({:?}): {:?} - {}",
+            step.step_id, step.title, step.action, outcome.status, outcome.message
+        );
+        let files = step.path.clone().into_iter().collect();
+
+        tracker
+            .record_change(change_type, files, vec![], summary, self.plan.meta.intent_summary.clone())
+            .inspect_err(|e| log::warn!("Failed to record step {} outcome: {}", step.step_id, e))
+            .ok()
+    }
+
+    fn outcome(step: &NSPExecutionStep, status: StepStatus, message: String, files: Vec<String>) -> StepOutcome {
+        StepOutcome {
+            step_id: step.step_id,
+            title: step.title.clone(),
+            status,
+            message,
+            files,
+            memory_id: None,
+            error_code: None,
+        }
+    }
+}
+
+/// 读取文件，不存在时返回 None
+fn read_optional(file: &Path) -> anyhow::Result<Option<String>> {
+    match fs::read_to_string(file) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(anyhow::anyhow!("Failed to read {}: {}", file.display(), e)),
+    }
+}
+
+/// 生成统一 diff，新建/删除的文件以 /dev/null 表示
+fn unified_diff(path: &str, old: Option<&str>, new: Option<&str>) -> String {
+    let old_name = old.map_or_else(|| "/dev/null".to_string(), |_| format!("a/{}", path));
+    let new_name = new.map_or_else(|| "/dev/null".to_string(), |_| format!("b/{}", path));
+    similar::TextDiff::from_lines(old.unwrap_or_default(), new.unwrap_or_default())
+        .unified_diff()
+        .context_radius(3)
+        .header(&old_name, &new_name)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn plan(steps: Vec<serde_json::Value>) -> NSP {
+        serde_json::from_value(json!({
+            "meta": { "intent_summary": "test plan", "risk_level": "LOW" },
+            "context_lock": {},
+            "execution_plan": steps,
+        }))
+        .unwrap()
+    }
+
+    fn step(step_id: i32, action: &str, path: &str, depends_on: &[i32]) -> serde_json::Value {
+        json!({
+            "step_id": step_id,
+            "title": format!("step {}", step_id),
+            "action": action,
+            "path": path,
+            "instruction": "test",
+            "depends_on": depends_on,
+        })
+    }
+
+    fn content(step_id: i32, content: &str) -> NSPStepEdit {
+        NSPStepEdit { step_id, content: Some(content.to_string()), replacements: vec![] }
+    }
+
+    fn replace(step_id: i32, old: &str, new: &str) -> NSPStepEdit {
+        NSPStepEdit {
+            step_id,
+            content: None,
+            replacements: vec![NSPTextReplacement { old: old.to_string(), new: new.to_string() }],
+        }
+    }
+
+    /// 不设检查点，执行时不会弹窗
+    fn executor(root: &Path, steps: Vec<serde_json::Value>, edits: Vec<NSPStepEdit>) -> PlanExecutor {
+        PlanExecutor::new(root, plan(steps), edits, Some(vec![]))
+    }
+
+    fn run(executor: PlanExecutor) -> PlanExecutionReport {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(executor.run())
+    }
+
+    #[test]
+    fn dry_run_reports_diffs_without_writing() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("notes.txt"), "hello world\n").unwrap();
+
+        let report = executor(
+            root,
+            vec![step(1, "CREATE", "new.txt", &[]), step(2, "MODIFY", "notes.txt", &[1])],
+            vec![content(1, "created\n"), replace(2, "world", "plan")],
+        )
+        .dry_run();
+
+        assert!(report.ok);
+        assert!(report.steps[0].diff.as_deref().unwrap().contains("+created"));
+        assert!(report.steps[1].diff.as_deref().unwrap().contains("+hello plan"));
+        assert!(!root.join("new.txt").exists());
+        assert_eq!(fs::read_to_string(root.join("notes.txt")).unwrap(), "hello world\n");
+    }
+
+    #[test]
+    fn run_applies_creates_and_replacements() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("config.txt"), "retries = 1\ntimeout = 5\n").unwrap();
+
+        let report = run(executor(
+            root,
+            vec![step(1, "MODIFY", "config.txt", &[]), step(2, "CREATE", "src/new.txt", &[1])],
+            vec![replace(1, "retries = 1", "retries = 3"), content(2, "created\n")],
+        ));
+
+        assert!(report.completed);
+        assert_eq!(fs::read_to_string(root.join("config.txt")).unwrap(), "retries = 3\ntimeout = 5\n");
+        assert_eq!(fs::read_to_string(root.join("src/new.txt")).unwrap(), "created\n");
+    }
+
+    #[test]
+    fn ambiguous_replacement_fails_without_touching_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("config.txt"), "a = 1\nb = 1\n").unwrap();
+
+        let report = run(executor(
+            root,
+            vec![step(1, "MODIFY", "config.txt", &[]), step(2, "CREATE", "after.txt", &[])],
+            vec![replace(1, "= 1", "= 2"), content(2, "after\n")],
+        ));
+
+        assert!(!report.completed);
+        assert_eq!(report.steps[0].status, StepStatus::Failed);
+        assert_eq!(report.steps[1].status, StepStatus::NotRun);
+        assert_eq!(fs::read_to_string(root.join("config.txt")).unwrap(), "a = 1\nb = 1\n");
+        assert!(!root.join("after.txt").exists());
+    }
+
+    #[test]
+    fn paths_outside_the_project_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("project");
+        fs::create_dir(&root).unwrap();
+        let absolute = dir.path().join("absolute.txt").to_string_lossy().to_string();
+
+        let steps = vec![step(1, "CREATE", "../escape.txt", &[]), step(2, "CREATE", &absolute, &[])];
+        let edits = vec![content(1, "escaped\n"), content(2, "escaped\n")];
+        assert!(!plan(steps.clone()).validate_scope().valid);
+
+        let preview = executor(&root, steps.clone(), edits.clone()).dry_run();
+        assert!(preview.steps.iter().all(|s| s.status == DryRunStatus::Conflict));
+
+        let report = run(executor(&root, steps, edits));
+        assert_eq!(report.steps[0].status, StepStatus::Failed);
+        assert_eq!(report.steps[0].error_code, Some(ToolErrorCode::InvalidParams));
+        assert!(!dir.path().join("escape.txt").exists());
+        assert!(!dir.path().join("absolute.txt").exists());
+    }
+
+    #[test]
+    fn resume_skips_steps_applied_in_a_previous_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        // 第 1 步已在上次执行中应用
+        fs::write(root.join("counter.txt"), "count = 2\n").unwrap();
+
+        let report = run(
+            executor(
+                root,
+                vec![step(1, "MODIFY", "counter.txt", &[]), step(2, "CREATE", "done.txt", &[1])],
+                vec![replace(1, "count = 1", "count = 2"), content(2, "done\n")],
+            )
+            .resume("plan-1".to_string(), HashSet::from([1])),
+        );
+
+        assert_eq!(report.plan_id, "plan-1");
+        assert!(report.completed);
+        assert_eq!(report.steps[0].message, "Applied in a previous run");
+        assert_eq!(fs::read_to_string(root.join("counter.txt")).unwrap(), "count = 2\n");
+        assert_eq!(fs::read_to_string(root.join("done.txt")).unwrap(), "done\n");
+    }
+}
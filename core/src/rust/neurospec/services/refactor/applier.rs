@@ -0,0 +1,101 @@
+use log::info;
+use std::collections::HashMap;
+use std::fs;
+
+use crate::mcp::utils::{ToolError, ToolErrorCode};
+use crate::neurospec::services::refactor::Edit;
+use crate::utils::text_position::{normalize_line_endings, LineIndex};
+
+/// Applies byte-range edits to files on disk
+pub struct EditApplier;
+
+impl EditApplier {
+    /// Apply edits grouped by file, returning the modified files
+    ///
+    /// Edits in the same file must not overlap; overlapping or out-of-range edits fail with `EDIT_CONFLICT`.
+    pub fn apply(edits: Vec<Edit>) -> anyhow::Result<Vec<String>> {
+        let mut edits_by_file: HashMap<String, Vec<Edit>> = HashMap::new();
+        for edit in edits {
+            edits_by_file.entry(edit.file_path.clone()).or_default().push(edit);
+        }
+
+        let mut modified_files = Vec::new();
+        for (file, edits) in edits_by_file {
+            // Missing files are treated as empty so that an edit can create them
+            let content = match fs::read_to_string(&file) {
+                Ok(content) => content,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+                Err(e) => return Err(anyhow::anyhow!("Failed to read file {}: {}", file, e)),
+            };
+            let content = Self::apply_to_content(&content, &edits)?;
+
+            if let Some(parent) = std::path::Path::new(&file).parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| anyhow::anyhow!("Failed to create directory for {}: {}", file, e))?;
+            }
+            fs::write(&file, content)
+                .map_err(|e| anyhow::anyhow!("Failed to write file {}: {}", file, e))?;
+
+            info!("Modified file: {}", file);
+            modified_files.push(file);
+        }
+
+        Ok(modified_files)
+    }
+
+    /// Apply edits of a single file to its content in memory
+    ///
+    /// Edits are applied from the end of the file backwards so earlier offsets stay valid.
+    /// Line endings in replacements are converted to the file's own style, so CRLF files stay CRLF.
+    pub fn apply_to_content(content: &str, edits: &[Edit]) -> anyhow::Result<String> {
+        let mut edits: Vec<&Edit> = edits.iter().collect();
+        edits.sort_by(|a, b| b.start_byte.cmp(&a.start_byte));
+        let index = LineIndex::new(content);
+        if let Some(pair) = edits.windows(2).find(|pair| pair[1].end_byte > pair[0].start_byte) {
+            return Err(ToolError::new(
+                ToolErrorCode::EditConflict,
+                format!(
+                    "Overlapping edits at {} in {}",
+                    Self::describe(&index, pair[0].start_byte),
+                    pair[0].file_path
+                ),
+            )
+            .into());
+        }
+
+        let line_ending = index.line_ending();
+        let mut output = content.to_string();
+        for edit in edits {
+            if edit.start_byte > edit.end_byte
+                || edit.end_byte > content.len()
+                || !content.is_char_boundary(edit.start_byte)
+                || !content.is_char_boundary(edit.end_byte)
+            {
+                return Err(ToolError::new(
+                    ToolErrorCode::EditConflict,
+                    format!(
+                        "Edit range {}..{} ({}) is invalid for {}",
+                        edit.start_byte,
+                        edit.end_byte,
+                        Self::describe(&index, edit.start_byte),
+                        edit.file_path
+                    ),
+                )
+                .into());
+            }
+            let replacement = normalize_line_endings(&edit.replacement, line_ending);
+            output.replace_range(edit.start_byte..edit.end_byte, &replacement);
+        }
+        Ok(output)
+    }
+
+    /// Human readable 1-based line:column of a byte offset, for error messages
+    fn describe(index: &LineIndex, offset: usize) -> String {
+        let line = index.line_of(offset);
+        let column = index
+            .line_range(line)
+            .map(|range| offset.saturating_sub(range.start) + 1)
+            .unwrap_or(1);
+        format!("line {}, byte column {}", line + 1, column)
+    }
+}
@@ -1,8 +1,15 @@
+pub mod applier;
 pub mod renamer;
+pub mod transaction;
 pub mod validator;
 
+pub use applier::EditApplier;
+pub use transaction::RefactorTransaction;
+
 use serde::{Deserialize, Serialize};
 
+use crate::utils::{LineIndex, PositionEncoding, TextRange};
+
 /// Represents a single code edit operation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Edit {
@@ -14,6 +21,9 @@ pub struct Edit {
     pub end_byte: usize,
     /// Replacement text
     pub replacement: String,
+    /// The same range as editor positions (line / UTF-16 character), for clients
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<TextRange>,
 }
 
 impl Edit {
@@ -24,8 +34,15 @@ impl Edit {
             start_byte,
             end_byte,
             replacement,
+            range: None,
         }
     }
+
+    /// Fill in the editor range from the content the byte offsets refer to
+    pub fn with_range(mut self, index: &LineIndex) -> Self {
+        self.range = index.range(self.start_byte..self.end_byte, PositionEncoding::Utf16);
+        self
+    }
 }
 
 /// Result of a refactoring operation
@@ -1,9 +1,38 @@
 use log::info;
+use petgraph::Direction;
+use serde::{Deserialize, Serialize};
 use std::fs;
 
+use crate::mcp::utils::{ToolError, ToolErrorCode};
 use crate::neurospec::models::SymbolKind;
 use crate::neurospec::services::graph::CodeGraph;
-use crate::neurospec::services::refactor::{Edit, RefactorResult};
+use crate::neurospec::services::refactor::{Edit, RefactorResult, RefactorTransaction};
+use crate::utils::LineIndex;
+
+/// Why an existing symbol conflicts with the new name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// The new name is already defined or imported in the renamed symbol's own module
+    SameModule,
+    /// The new name is defined or imported in a file that uses the symbol, so references there would be shadowed
+    Shadowing,
+}
+
+/// An existing symbol that already uses the new name where the rename applies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameConflict {
+    pub kind: ConflictKind,
+    /// File in which the names would clash
+    pub file_path: String,
+    /// ID of the existing symbol, or the import spec that brings the name into scope
+    pub existing: String,
+    /// Definition line of the existing symbol, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    /// Whether the name is imported rather than defined in the file
+    pub imported: bool,
+}
 
 pub struct Renamer;
 
@@ -28,99 +57,141 @@ impl Renamer {
             old_name, new_name, file_path
         );
 
-        // 1. Find the target symbol in the graph
-        let symbol_id = format!("{}::{}", file_path, old_name);
-        let target_idx = graph
-            .node_map
-            .get(&symbol_id)
-            .ok_or_else(|| anyhow::anyhow!("Symbol '{}' not found in graph", old_name))?;
-
-        // 2. Find all references using the graph (reverse edges)
-        use petgraph::Direction;
-        let mut edit_locations = Vec::new();
-
-        // Add the definition itself
-        if let Some(node) = graph.graph.node_weight(*target_idx) {
-            edit_locations.push((node.file_path.clone(), node.name.clone()));
-        }
-
-        // Add all references (who calls this symbol)
-        let mut neighbors = graph
-            .graph
-            .neighbors_directed(*target_idx, Direction::Incoming)
-            .detach();
-        while let Some(neighbor_idx) = neighbors.next_node(&graph.graph) {
-            if let Some(node) = graph.graph.node_weight(neighbor_idx) {
-                // Check if this node's references contain our symbol
-                if node.file_path != file_path || node.name != old_name {
-                    edit_locations.push((node.file_path.clone(), old_name.to_string()));
-                }
-            }
-        }
+        // 1-2. Find the definition and all references using the graph (reverse edges)
+        let files = Self::affected_files(graph, file_path, old_name)?;
 
-        info!("Found {} locations to rename", edit_locations.len());
+        info!("Found {} files to rename in", files.len());
 
-        // 3. Group by file and create edits
-        use std::collections::HashMap;
-        let mut edits_by_file: HashMap<String, Vec<Edit>> = HashMap::new();
+        // 3. Create edits per file
+        let mut transaction = RefactorTransaction::new();
+        let mut all_edits = Vec::new();
 
-        for (file, _) in edit_locations {
+        for file in files {
             // Read file content
             let content = fs::read_to_string(&file)
                 .map_err(|e| anyhow::anyhow!("Failed to read file {}: {}", file, e))?;
 
             // Find all occurrences of old_name in this file
-            // Simple approach: string search + boundary check
+            // Simple approach: string search + identifier boundary check
+            let index = LineIndex::new(&content);
             let mut file_edits = Vec::new();
             for (idx, _) in content.match_indices(old_name) {
-                // Basic boundary check (word boundaries)
-                let before_ok = idx == 0 || !content.as_bytes()[idx - 1].is_ascii_alphanumeric();
-                let after_ok = {
-                    let end = idx + old_name.len();
-                    end >= content.len() || !content.as_bytes()[end].is_ascii_alphanumeric()
-                };
+                let end = idx + old_name.len();
+                let before_ok = !content[..idx].chars().next_back().is_some_and(is_identifier_char);
+                let after_ok = !content[end..].chars().next().is_some_and(is_identifier_char);
 
                 if before_ok && after_ok {
-                    file_edits.push(Edit::new(
-                        file.clone(),
-                        idx,
-                        idx + old_name.len(),
-                        new_name.to_string(),
-                    ));
+                    file_edits.push(
+                        Edit::new(file.clone(), idx, end, new_name.to_string()).with_range(&index),
+                    );
                 }
             }
 
             if !file_edits.is_empty() {
-                edits_by_file.insert(file, file_edits);
+                all_edits.extend(file_edits.iter().cloned());
+                transaction.add_edits(&file, Some(&content), file_edits);
             }
         }
 
-        // 4. Apply edits (reverse order per file to avoid offset issues)
-        let mut modified_files = Vec::new();
-        let mut all_edits = Vec::new();
+        // 4. Apply all files at once; a failure in any file leaves every file untouched
+        let modified_files = transaction.commit()?;
 
-        for (file, mut edits) in edits_by_file {
-            // Sort edits in reverse order (end -> start)
-            edits.sort_by(|a, b| b.start_byte.cmp(&a.start_byte));
+        Ok(RefactorResult::success(modified_files, all_edits))
+    }
 
-            // Read original content
-            let mut content = fs::read_to_string(&file)
-                .map_err(|e| anyhow::anyhow!("Failed to read file {}: {}", file, e))?;
+    /// Files a rename of `old_name` would edit: the defining file first, then files that use it
+    pub fn affected_files(graph: &CodeGraph, file_path: &str, old_name: &str) -> anyhow::Result<Vec<String>> {
+        let symbol_id = format!("{}::{}", file_path, old_name);
+        let target_idx = graph
+            .node_map
+            .get(&symbol_id)
+            .ok_or_else(|| {
+                ToolError::new(ToolErrorCode::NotFound, format!("Symbol '{}' not found in graph", old_name))
+            })?;
+
+        let mut files = vec![graph.graph[*target_idx].file_path.clone()];
+        for neighbor_idx in graph.graph.neighbors_directed(*target_idx, Direction::Incoming) {
+            let file = &graph.graph[neighbor_idx].file_path;
+            if !files.contains(file) {
+                files.push(file.clone());
+            }
+        }
+        Ok(files)
+    }
 
-            // Apply edits in reverse order
-            for edit in &edits {
-                content.replace_range(edit.start_byte..edit.end_byte, &edit.replacement);
+    /// Existing symbols that already use `new_name` in the files a rename would edit
+    ///
+    /// Definitions and resolved imports come from the graph; `imports` adds the raw import
+    /// specs of those files (e.g. from the symbol store), which also covers names imported
+    /// from external packages. A clash in `file_path` itself is a `SameModule` conflict,
+    /// anywhere else it is `Shadowing`.
+    pub fn find_conflicts(
+        graph: &CodeGraph,
+        file_path: &str,
+        new_name: &str,
+        files: &[String],
+        imports: &[(String, String)],
+    ) -> Vec<RenameConflict> {
+        let kind_for = |file: &str| {
+            if file == file_path {
+                ConflictKind::SameModule
+            } else {
+                ConflictKind::Shadowing
+            }
+        };
+        let mut conflicts = Vec::new();
+
+        for file in files {
+            if let Some(&idx) = graph.node_map.get(&format!("{}::{}", file, new_name)) {
+                let node = &graph.graph[idx];
+                if !matches!(node.kind, SymbolKind::File) {
+                    conflicts.push(RenameConflict {
+                        kind: kind_for(file),
+                        file_path: file.clone(),
+                        existing: node.id.clone(),
+                        line: node.start_line,
+                        imported: false,
+                    });
+                }
             }
 
-            // Write back
-            fs::write(&file, content)
-                .map_err(|e| anyhow::anyhow!("Failed to write file {}: {}", file, e))?;
+            // Names the file imports from elsewhere in the project
+            let file_node = file.rsplit('/').next().map(|name| format!("{}::{}", file, name));
+            if let Some(&file_idx) = file_node.as_ref().and_then(|id| graph.node_map.get(id)) {
+                for target in graph.graph.neighbors_directed(file_idx, Direction::Outgoing) {
+                    let node = &graph.graph[target];
+                    if node.name == new_name && !matches!(node.kind, SymbolKind::File) {
+                        conflicts.push(RenameConflict {
+                            kind: kind_for(file),
+                            file_path: file.clone(),
+                            existing: node.id.clone(),
+                            line: node.start_line,
+                            imported: true,
+                        });
+                    }
+                }
+            }
+        }
 
-            info!("Modified file: {}", file);
-            modified_files.push(file.clone());
-            all_edits.extend(edits);
+        for (file, spec) in imports {
+            let imported_name = spec.rsplit(['.', '/', ':']).next().unwrap_or(spec);
+            let already_reported = conflicts.iter().any(|c| &c.file_path == file && c.imported);
+            if imported_name == new_name && files.contains(file) && !already_reported {
+                conflicts.push(RenameConflict {
+                    kind: kind_for(file),
+                    file_path: file.clone(),
+                    existing: spec.clone(),
+                    line: None,
+                    imported: true,
+                });
+            }
         }
 
-        Ok(RefactorResult::success(modified_files, all_edits))
+        conflicts
     }
 }
+
+/// Characters that continue an identifier (so `foo` does not match inside `foo_bar` or `über_foo`)
+fn is_identifier_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
@@ -0,0 +1,130 @@
+use log::{info, warn};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use tempfile::NamedTempFile;
+
+use crate::mcp::tools::unified_store::persistence::content_hash;
+use crate::mcp::utils::{ToolError, ToolErrorCode};
+use crate::neurospec::services::refactor::validator::Validator;
+use crate::neurospec::services::refactor::{Edit, EditApplier};
+
+/// Edits of one file together with the content they were computed against
+struct FileChange {
+    /// Hash of the content the byte offsets refer to (`None` if the file did not exist)
+    base_hash: Option<String>,
+    edits: Vec<Edit>,
+}
+
+/// A file whose new content is written to a temp file next to it, ready to be swapped in
+struct StagedFile {
+    path: String,
+    /// Original content, restored on rollback (`None` if the file is created by the transaction)
+    original: Option<String>,
+    temp: NamedTempFile,
+}
+
+/// Applies edits across several files as one unit
+///
+/// New contents are first written to temp files in the same directories, after checking that
+/// each file still has the content the edits were computed against and still parses. The temp files are then
+/// renamed over the originals; if any step fails, files already swapped are restored, so a
+/// rename is never left half-applied.
+#[derive(Default)]
+pub struct RefactorTransaction {
+    files: BTreeMap<String, FileChange>,
+}
+
+impl RefactorTransaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add edits computed against `base_content` (`None` if the file does not exist yet)
+    pub fn add_edits(&mut self, file_path: &str, base_content: Option<&str>, edits: Vec<Edit>) {
+        let change = self.files.entry(file_path.to_string()).or_insert_with(|| FileChange {
+            base_hash: base_content.map(|content| content_hash(content.as_bytes())),
+            edits: Vec::new(),
+        });
+        change.edits.extend(edits);
+    }
+
+    /// Apply all edits, or none of them
+    ///
+    /// Fails with `EDIT_CONFLICT` if a file changed since its edits were computed or an edit
+    /// range is invalid, and with `VALIDATION_FAILED` if an edited file no longer parses;
+    /// in these cases no file is modified.
+    pub fn commit(self) -> anyhow::Result<Vec<String>> {
+        // 1. Stage every file; dropping the staged temp files on error removes them
+        let mut staged = Vec::with_capacity(self.files.len());
+        for (path, change) in self.files {
+            staged.push(Self::stage(path, change)?);
+        }
+
+        // 2. Swap the temp files in, restoring the already swapped ones on failure
+        let mut committed: Vec<(String, Option<String>)> = Vec::with_capacity(staged.len());
+        for file in staged {
+            if let Err(e) = file.temp.persist(&file.path) {
+                let error = anyhow::anyhow!("Failed to replace {}: {}", file.path, e.error);
+                Self::rollback(&committed);
+                return Err(error);
+            }
+            info!("Modified file: {}", file.path);
+            committed.push((file.path, file.original));
+        }
+
+        Ok(committed.into_iter().map(|(path, _)| path).collect())
+    }
+
+    /// Verify the file against its base hash, re-parse the edited content and write it to a temp file
+    fn stage(path: String, change: FileChange) -> anyhow::Result<StagedFile> {
+        let original = match fs::read_to_string(&path) {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(anyhow::anyhow!("Failed to read file {}: {}", path, e)),
+        };
+
+        let current_hash = original.as_deref().map(|content| content_hash(content.as_bytes()));
+        if current_hash != change.base_hash {
+            return Err(ToolError::new(
+                ToolErrorCode::EditConflict,
+                format!("{} changed since the edits were computed, nothing was applied", path),
+            )
+            .into());
+        }
+
+        let content = EditApplier::apply_to_content(original.as_deref().unwrap_or_default(), &change.edits)?;
+        Validator::check_edited_buffer(&path, original.as_deref(), &content)?;
+
+        let dir = Path::new(&path).parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        fs::create_dir_all(dir).map_err(|e| anyhow::anyhow!("Failed to create directory for {}: {}", path, e))?;
+        let mut temp = NamedTempFile::new_in(dir)
+            .map_err(|e| anyhow::anyhow!("Failed to create temp file for {}: {}", path, e))?;
+        temp.write_all(content.as_bytes())
+            .and_then(|_| temp.as_file().sync_all())
+            .map_err(|e| anyhow::anyhow!("Failed to write temp file for {}: {}", path, e))?;
+
+        // Keep the original permissions when replacing an existing file
+        if let Ok(metadata) = fs::metadata(&path) {
+            let _ = fs::set_permissions(temp.path(), metadata.permissions());
+        }
+
+        Ok(StagedFile { path, original, temp })
+    }
+
+    /// Restore swapped files to their original content (or remove files the transaction created)
+    fn rollback(committed: &[(String, Option<String>)]) {
+        for (path, original) in committed.iter().rev() {
+            let restored = match original {
+                Some(content) => fs::write(path, content),
+                None => fs::remove_file(path),
+            };
+            match restored {
+                Ok(()) => info!("Rolled back {}", path),
+                Err(e) => warn!("Failed to roll back {}: {}", path, e),
+            }
+        }
+    }
+}
@@ -1,6 +1,27 @@
 use log::{info, warn};
 use std::fs;
-use tree_sitter::{Language, Parser};
+use std::path::Path;
+use tree_sitter::{Language, Node, Parser};
+
+use crate::mcp::utils::{ToolError, ToolErrorCode};
+use crate::neurospec::services::graph::builder::graph_language;
+
+/// Location of the first syntax error in a buffer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    /// 1-based line
+    pub line: usize,
+    /// 1-based byte column
+    pub column: usize,
+    /// What the parser found, e.g. "unexpected `(`" or "missing `)`"
+    pub message: String,
+}
+
+impl std::fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
 
 /// Validator for ensuring code correctness after refactoring
 pub struct Validator;
@@ -14,6 +35,22 @@ impl Validator {
         let content = fs::read_to_string(file_path)
             .map_err(|e| anyhow::anyhow!("Failed to read file: {}", e))?;
 
+        let is_valid = Self::validate_source(&content, language)?;
+        if is_valid {
+            info!("File {} is syntactically valid", file_path);
+        } else {
+            warn!("Syntax errors found in {}", file_path);
+        }
+        Ok(is_valid)
+    }
+
+    /// Validate that in-memory source code has correct syntax
+    pub fn validate_source(content: &str, language: &str) -> anyhow::Result<bool> {
+        Ok(Self::first_syntax_error(content, language)?.is_none())
+    }
+
+    /// Parse in-memory source code and locate its first syntax error, if any
+    pub fn first_syntax_error(content: &str, language: &str) -> anyhow::Result<Option<SyntaxError>> {
         // Get appropriate parser
         let mut parser = Parser::new();
         let lang = Self::get_language(language)?;
@@ -23,20 +60,54 @@ impl Validator {
 
         // Parse
         let tree = parser
-            .parse(&content, None)
+            .parse(content, None)
             .ok_or_else(|| anyhow::anyhow!("Failed to parse file"))?;
 
         // Check for errors
         let root = tree.root_node();
-        let has_error = Self::check_for_errors(&root);
+        if !root.has_error() {
+            return Ok(None);
+        }
+        Ok(Self::find_first_error(&root).map(|node| {
+            let position = node.start_position();
+            let message = if node.is_missing() {
+                format!("missing `{}`", node.kind())
+            } else {
+                let text = content.get(node.byte_range()).unwrap_or_default();
+                let snippet: String = text.chars().take(20).collect();
+                format!("unexpected `{}`", snippet.lines().next().unwrap_or_default())
+            };
+            SyntaxError {
+                line: position.row + 1,
+                column: position.column + 1,
+                message,
+            }
+        }))
+    }
 
-        if has_error {
-            warn!("Syntax errors found in {}", file_path);
-            return Ok(false);
+    /// Reject an edited buffer that no longer parses
+    ///
+    /// Files in unsupported languages, and files that already had syntax errors before the edit,
+    /// are accepted as is. Fails with `VALIDATION_FAILED` and the first error location otherwise.
+    pub fn check_edited_buffer(file_path: &str, original: Option<&str>, edited: &str) -> anyhow::Result<()> {
+        let Some(language) = graph_language(Path::new(file_path)) else {
+            return Ok(());
+        };
+        let Some(error) = Self::first_syntax_error(edited, language)? else {
+            return Ok(());
+        };
+        if let Some(original) = original {
+            if Self::first_syntax_error(original, language)?.is_some() {
+                warn!("{} already had syntax errors before the edit, skipping validation", file_path);
+                return Ok(());
+            }
         }
 
-        info!("File {} is syntactically valid", file_path);
-        Ok(true)
+        Err(ToolError::new(
+            ToolErrorCode::ValidationFailed,
+            format!("Edit would introduce a syntax error in {} at {}", file_path, error),
+        )
+        .into())
     }
 
     /// Get tree-sitter language for a given language string
@@ -45,24 +116,24 @@ impl Validator {
             "rust" => Ok(tree_sitter_rust::LANGUAGE.into()),
             "typescript" | "javascript" => Ok(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()),
             "python" => Ok(tree_sitter_python::LANGUAGE.into()),
+            "c" => Ok(tree_sitter_c::LANGUAGE.into()),
+            "cpp" => Ok(tree_sitter_cpp::LANGUAGE.into()),
             _ => Err(anyhow::anyhow!("Unsupported language: {}", language)),
         }
     }
 
-    /// Recursively check for error nodes in the AST
-    fn check_for_errors(node: &tree_sitter::Node) -> bool {
+    /// First error or missing node in document order
+    fn find_first_error<'t>(node: &Node<'t>) -> Option<Node<'t>> {
         if node.is_error() || node.is_missing() {
-            return true;
+            return Some(*node);
         }
-
-        let mut cursor = node.walk();
-        for child in node.children(&mut cursor) {
-            if Self::check_for_errors(&child) {
-                return true;
-            }
+        if !node.has_error() {
+            return None;
         }
 
-        false
+        let mut cursor = node.walk();
+        let first = node.children(&mut cursor).find_map(|child| Self::find_first_error(&child));
+        first
     }
 }
 
@@ -91,4 +162,28 @@ mod tests {
         assert!(result.is_ok());
         assert!(!result.unwrap());
     }
+
+    #[test]
+    fn test_first_syntax_error() {
+        assert_eq!(Validator::first_syntax_error("fn main() {}\n", "rust").unwrap(), None);
+
+        let error = Validator::first_syntax_error("fn main() {\n    let x = ;\n}\n", "rust")
+            .unwrap()
+            .unwrap();
+        assert_eq!(error.line, 2);
+
+        // Broken after the edit: rejected with the location
+        let err = Validator::check_edited_buffer("src/lib.rs", Some("fn a() {}\n"), "fn a( {}\n").unwrap_err();
+        assert!(err.to_string().contains("line 1"));
+        // Already broken before the edit, or not a supported language: accepted
+        assert!(Validator::check_edited_buffer("src/lib.rs", Some("fn a( {}\n"), "fn b( {}\n").is_ok());
+        assert!(Validator::check_edited_buffer("README.md", None, "fn a( {").is_ok());
+    }
+
+    #[test]
+    fn test_validate_source() {
+        assert!(Validator::validate_source("def f():\n    return 1\n", "python").unwrap());
+        assert!(!Validator::validate_source("def f(:\n", "python").unwrap());
+        assert!(Validator::validate_source("", "cobol").is_err());
+    }
 }
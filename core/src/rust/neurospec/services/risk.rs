@@ -0,0 +1,320 @@
+//! NSP 计划风险评估
+//!
+//! 把计划修改的文件放进代码图谱，按被依赖程度（fan-in）、跨语言引用和测试覆盖
+//! 计算风险级别，取代手写的 risk_level；高风险时把需要确认的问题写入 open_questions。
+//! 影响分析复用同样的因素，为每个受波及的文件计算 blast radius 分数并生成审查清单
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+use petgraph::graph::NodeIndex;
+use petgraph::Direction;
+use serde::{Deserialize, Serialize};
+
+use crate::neurospec::models::{NSPAction, NSPRiskLevel, NSP};
+use crate::neurospec::services::graph::CodeGraph;
+
+/// fan-in 计分上限，避免单个公共模块淹没其他因素
+const MAX_FAN_IN_SCORE: u32 = 10;
+/// 每个跨语言引用方的分数
+const CROSS_LANGUAGE_WEIGHT: u32 = 2;
+/// 有外部依赖但没有测试覆盖时的加分
+const UNTESTED_PENALTY: u32 = 3;
+/// 删除仍被依赖的文件时的加分
+const DELETE_PENALTY: u32 = 5;
+/// 达到 MEDIUM 的文件分数
+const MEDIUM_THRESHOLD: u32 = 4;
+/// 达到 HIGH 的文件分数
+const HIGH_THRESHOLD: u32 = 10;
+/// 修改文件数超过该值时至少为 MEDIUM
+const WIDE_PLAN_FILES: usize = 10;
+/// 受影响符号按距离计分（距离 1、2、3 及以上）
+const BLAST_DEPTH_WEIGHTS: [u32; 3] = [3, 2, 1];
+/// 审查清单中逐个列出的文件数
+const CHECKLIST_FILES: usize = 5;
+
+/// 单个文件的风险因素
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileRisk {
+    pub path: String,
+    pub action: NSPAction,
+    /// 引用该文件中符号的其他文件符号数
+    pub fan_in: usize,
+    /// 其中使用其他语言的引用方数
+    pub cross_language: usize,
+    /// 是否有测试覆盖（测试文件引用或文件内含测试）
+    pub has_tests: bool,
+    pub score: u32,
+}
+
+/// 影响分析中单个文件的波及程度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileBlastRadius {
+    pub path: String,
+    /// 文件中受影响的符号数
+    pub symbols: usize,
+    /// 距离被修改符号最近的跳数
+    pub min_depth: usize,
+    /// 引用该文件中符号的其他文件符号数
+    pub fan_in: usize,
+    /// 是否为测试文件
+    pub is_test: bool,
+    /// 是否有测试覆盖（测试文件引用或文件内含测试）
+    pub has_tests: bool,
+    pub score: u32,
+}
+
+/// 计划风险评估结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskAssessment {
+    pub risk_level: NSPRiskLevel,
+    /// 各文件分数的最大值
+    pub score: u32,
+    pub files: Vec<FileRisk>,
+    /// 高风险时需要人类确认的问题
+    pub open_questions: Vec<String>,
+}
+
+impl RiskAssessment {
+    /// 用评估结果覆盖计划的 risk_level，并补充 open_questions
+    pub fn apply_to(&self, plan: &mut NSP) {
+        plan.meta.risk_level = self.risk_level.clone();
+        for question in &self.open_questions {
+            if !plan.meta.open_questions.contains(question) {
+                plan.meta.open_questions.push(question.clone());
+            }
+        }
+    }
+}
+
+/// 根据代码图谱评估计划风险
+pub fn assess_plan_risk(graph: &CodeGraph, project_root: &Path, plan: &NSP) -> RiskAssessment {
+    let mut seen = HashSet::new();
+    let files: Vec<FileRisk> = plan
+        .execution_plan
+        .iter()
+        .filter(|step| step.action.is_edit())
+        .filter_map(|step| Some((step, step.path.as_deref()?)))
+        .filter(|(_, path)| seen.insert(normalize_path(path, project_root)))
+        .map(|(step, path)| file_risk(graph, project_root, path, &step.action))
+        .collect();
+
+    let score = files.iter().map(|f| f.score).max().unwrap_or(0);
+    let risk_level = if score >= HIGH_THRESHOLD {
+        NSPRiskLevel::High
+    } else if score >= MEDIUM_THRESHOLD || files.len() > WIDE_PLAN_FILES {
+        NSPRiskLevel::Medium
+    } else {
+        NSPRiskLevel::Low
+    };
+
+    let open_questions = if matches!(risk_level, NSPRiskLevel::High) {
+        files
+            .iter()
+            .filter(|f| f.score >= HIGH_THRESHOLD)
+            .map(open_question)
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    RiskAssessment {
+        risk_level,
+        score,
+        files,
+        open_questions,
+    }
+}
+
+/// 统计文件在图谱中的引用方并计分
+fn file_risk(graph: &CodeGraph, project_root: &Path, path: &str, action: &NSPAction) -> FileRisk {
+    let target = normalize_path(path, project_root);
+    let (referrers, languages) = file_referrers(graph, project_root, &target);
+
+    let cross_language = referrers
+        .iter()
+        .filter(|&&referrer| !languages.contains(&graph.graph[referrer].language))
+        .count();
+    let has_tests = has_test_coverage(graph, project_root, &target, &referrers);
+
+    let fan_in = referrers.len();
+    let mut score = (fan_in as u32).min(MAX_FAN_IN_SCORE) + CROSS_LANGUAGE_WEIGHT * cross_language as u32;
+    if fan_in > 0 && !has_tests {
+        score += UNTESTED_PENALTY;
+    }
+    if fan_in > 0 && matches!(action, NSPAction::Delete) {
+        score += DELETE_PENALTY;
+    }
+
+    FileRisk {
+        path: target,
+        action: action.clone(),
+        fan_in,
+        cross_language,
+        has_tests,
+        score,
+    }
+}
+
+/// 按文件汇总影响分析结果并计算 blast radius 分数，分数高的在前
+///
+/// `impacted` 为受影响的符号及其与被修改符号的距离
+pub fn blast_radius(graph: &CodeGraph, project_root: &Path, impacted: &[(NodeIndex, usize)]) -> Vec<FileBlastRadius> {
+    let mut by_file: BTreeMap<String, (usize, usize, u32)> = BTreeMap::new();
+    for &(idx, depth) in impacted {
+        let path = normalize_path(&graph.graph[idx].file_path, project_root);
+        let weight = BLAST_DEPTH_WEIGHTS[depth.clamp(1, BLAST_DEPTH_WEIGHTS.len()) - 1];
+        let entry = by_file.entry(path).or_insert((0, depth, 0));
+        entry.0 += 1;
+        entry.1 = entry.1.min(depth);
+        entry.2 += weight;
+    }
+
+    let mut files: Vec<FileBlastRadius> = by_file
+        .into_iter()
+        .map(|(path, (symbols, min_depth, symbol_score))| {
+            let (referrers, _) = file_referrers(graph, project_root, &path);
+            let fan_in = referrers.len();
+            let is_test = is_test_path(&path);
+            let has_tests = has_test_coverage(graph, project_root, &path, &referrers);
+
+            let mut score = symbol_score + (fan_in as u32).min(MAX_FAN_IN_SCORE);
+            if !has_tests {
+                score += UNTESTED_PENALTY;
+            }
+
+            FileBlastRadius {
+                path,
+                symbols,
+                min_depth,
+                fan_in,
+                is_test,
+                has_tests,
+                score,
+            }
+        })
+        .collect();
+
+    files.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+    files
+}
+
+/// 根据 blast radius 生成修改前后的审查清单
+pub fn review_checklist(symbol: &str, files: &[FileBlastRadius]) -> Vec<String> {
+    let mut checklist = Vec::new();
+
+    let (tests, sources): (Vec<&FileBlastRadius>, Vec<&FileBlastRadius>) = files.iter().partition(|f| f.is_test);
+    let direct = sources.iter().filter(|f| f.min_depth == 1).count();
+    if direct > 0 {
+        checklist.push(format!(
+            "Confirm the {} file(s) that use `{}` directly still compile and behave the same (signature, return values, errors)",
+            direct, symbol
+        ));
+    }
+
+    for file in sources.iter().filter(|f| f.score >= MEDIUM_THRESHOLD).take(CHECKLIST_FILES) {
+        let mut item = format!(
+            "Review `{}` (score {}, {} symbol(s), depth {}, fan-in {})",
+            file.path, file.score, file.symbols, file.min_depth, file.fan_in
+        );
+        if !file.has_tests {
+            item.push_str(" - no tests cover it");
+        }
+        checklist.push(item);
+    }
+
+    if tests.is_empty() {
+        checklist.push(format!("No test file reaches `{}`; add or extend tests before changing it", symbol));
+    } else {
+        let names: Vec<&str> = tests.iter().take(CHECKLIST_FILES).map(|f| f.path.as_str()).collect();
+        let more = tests.len().saturating_sub(CHECKLIST_FILES);
+        let mut item = format!("Run the affected tests: {}", names.join(", "));
+        if more > 0 {
+            item.push_str(&format!(" (+{} more)", more));
+        }
+        checklist.push(item);
+    }
+
+    let untested: Vec<&str> = sources.iter().filter(|f| !f.has_tests).map(|f| f.path.as_str()).collect();
+    if !untested.is_empty() && !tests.is_empty() {
+        checklist.push(format!(
+            "{} impacted file(s) have no test coverage; verify them manually",
+            untested.len()
+        ));
+    }
+
+    checklist
+}
+
+/// 其他文件中引用该文件符号的图谱节点，以及该文件使用的语言
+fn file_referrers(graph: &CodeGraph, project_root: &Path, target: &str) -> (HashSet<NodeIndex>, HashSet<String>) {
+    let mut referrers = HashSet::new();
+    let mut languages = HashSet::new();
+
+    for idx in graph.graph.node_indices() {
+        let node = &graph.graph[idx];
+        if normalize_path(&node.file_path, project_root) != target {
+            continue;
+        }
+        languages.insert(node.language.clone());
+        for referrer in graph.graph.neighbors_directed(idx, Direction::Incoming) {
+            let referrer_node = &graph.graph[referrer];
+            if normalize_path(&referrer_node.file_path, project_root) != target {
+                referrers.insert(referrer);
+            }
+        }
+    }
+
+    (referrers, languages)
+}
+
+/// 文件本身是测试、被测试文件引用或内含测试
+fn has_test_coverage(graph: &CodeGraph, project_root: &Path, target: &str, referrers: &HashSet<NodeIndex>) -> bool {
+    referrers
+        .iter()
+        .any(|&referrer| is_test_path(&normalize_path(&graph.graph[referrer].file_path, project_root)))
+        || is_test_path(target)
+        || has_inline_tests(&project_root.join(target))
+}
+
+fn open_question(file: &FileRisk) -> String {
+    let mut question = format!("`{}` 被 {} 个外部符号引用", file.path, file.fan_in);
+    if file.cross_language > 0 {
+        question.push_str(&format!("（其中 {} 个来自其他语言）", file.cross_language));
+    }
+    if !file.has_tests {
+        question.push_str("，且没有测试覆盖");
+    }
+    if matches!(file.action, NSPAction::Delete) {
+        question.push_str("，删除后这些调用方如何处理？");
+    } else {
+        question.push_str("，调用方是否需要同步修改？");
+    }
+    question
+}
+
+/// 统一为相对项目根目录的 POSIX 路径
+pub(crate) fn normalize_path(path: &str, project_root: &Path) -> String {
+    let path = Path::new(path).strip_prefix(project_root).map_or_else(
+        |_| path.to_string(),
+        |relative| relative.to_string_lossy().to_string(),
+    );
+    path.replace('\\', "/").trim_start_matches("./").to_string()
+}
+
+/// 按常见命名约定判断是否为测试文件
+pub(crate) fn is_test_path(path: &str) -> bool {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    let stem = file_name.split('.').next().unwrap_or(file_name);
+    path.split('/').any(|segment| matches!(segment, "test" | "tests" | "__tests__" | "spec"))
+        || stem.starts_with("test_")
+        || stem.ends_with("_test")
+        || stem.ends_with("_spec")
+        || file_name.contains(".test.")
+        || file_name.contains(".spec.")
+}
+
+/// 文件内是否包含测试（Rust 的 #[cfg(test)] 模块）
+fn has_inline_tests(file: &Path) -> bool {
+    std::fs::read_to_string(file).is_ok_and(|content| content.contains("#[cfg(test)]"))
+}
@@ -88,6 +88,8 @@ pub fn scan_project<P: AsRef<Path>>(
                     || lang == "typescript"
                     || lang == "javascript"
                     || lang == "python"
+                    || lang == "c"
+                    || lang == "cpp"
                 {
                     // Read file content for AST analysis
                     match fs::read_to_string(path) {
@@ -132,6 +134,10 @@ pub fn scan_project<P: AsRef<Path>>(
                     language,
                     signature: None,
                     references: Vec::new(),
+                    inherits: Vec::new(),
+                    start_line: None,
+                    end_line: None,
+                    fingerprint: None,
                 };
                 vec![symbol]
             } else {
@@ -178,7 +184,7 @@ fn guess_language(path: &Path) -> Option<String> {
             "py" => "python",
             "go" => "go",
             "c" | "h" => "c",
-            "cpp" | "hpp" | "cc" => "cpp",
+            "cpp" | "cc" | "cxx" | "hpp" | "hh" | "hxx" => "cpp",
             "java" => "java",
             "md" => "markdown",
             "json" => "json",
@@ -235,6 +241,10 @@ pub fn scan_project_cached<P: AsRef<Path>>(
             language: us.language,
             signature: us.signature,
             references: us.references,
+            inherits: us.inherits,
+            start_line: us.start_line,
+            end_line: us.end_line,
+            fingerprint: us.fingerprint,
         })
         .collect();
 
@@ -1,9 +1,15 @@
+use std::sync::Arc;
+
 use rmcp::{model::Content, ErrorData as McpError};
 use schemars::JsonSchema;
 use serde::Deserialize;
 
+use crate::neurospec::models::SymbolKind;
 use crate::neurospec::services::graph::builder::GraphBuilder;
-use crate::neurospec::services::graph::RelationType;
+use crate::neurospec::services::architecture::{ArchitectureRules, ARCHITECTURE_FILE};
+use crate::neurospec::services::graph::export::{export_graph, GraphFormat};
+use crate::neurospec::services::graph::{cache, impact_cache, CodeGraph, CycleLevel, SymbolNode};
+use crate::neurospec::services::risk::{blast_radius, is_test_path, normalize_path, review_checklist};
 use crate::mcp::tools::unified_store::{with_global_store, is_search_initialized};
 
 /// Arguments for neurospec.graph.impact_analysis
@@ -17,26 +23,278 @@ pub struct ImpactAnalysisArgs {
     pub depth: Option<usize>,
 }
 
-pub fn handle_impact_analysis(
-    args: ImpactAnalysisArgs,
-) -> Result<Vec<Content>, McpError> {
+/// Arguments for neurospec.impact.changeset
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ImpactChangesetArgs {
+    /// Project root directory path
+    pub project_root: String,
+    /// Modified files relative to the project root (e.g. output of `git diff --name-only`)
+    pub files: Vec<String>,
+    /// Max depth for analysis (default: 2)
+    pub depth: Option<usize>,
+}
+
+/// Arguments for neurospec.graph.callers
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GraphCallersArgs {
+    /// Project root directory path
+    pub project_root: String,
+    /// Symbol name or ID (`path::name`) to look up
+    pub symbol_name: String,
+    /// Max depth; 1 lists direct callers only, larger values also walk transitive dependents (default: 1)
+    pub depth: Option<usize>,
+    /// Also list what the symbol itself depends on, up to the same depth (default: false)
+    pub include_dependencies: Option<bool>,
+}
+
+/// Arguments for neurospec.graph.cycles
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GraphCyclesArgs {
+    /// Project root directory path
+    pub project_root: String,
+    /// Cycle granularity: "module" (file dependencies), "function" (mutual calls) or "all" (default: "all")
+    pub level: Option<String>,
+    /// Max cycles to report per level (default: 20)
+    pub max_cycles: Option<usize>,
+}
+
+/// Arguments for neurospec.graph.export
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GraphExportArgs {
+    /// Project root directory path
+    pub project_root: String,
+    /// Output format: "graphml", "dot" or "mermaid"
+    pub format: String,
+    /// Only export symbols whose file path starts with this prefix (e.g. "src/api/")
+    pub path_prefix: Option<String>,
+    /// Write the export to this file (relative to the project root) instead of returning it inline
+    pub output_path: Option<String>,
+}
+
+/// Arguments for neurospec.architecture.check
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ArchitectureCheckArgs {
+    /// Project root directory path (layer rules are read from .neurospec/architecture.toml)
+    pub project_root: String,
+}
+
+/// 构建项目图谱
+fn build_graph(project_root: &str) -> Result<Arc<CodeGraph>, McpError> {
     // 优先使用全局 Store（增量索引，性能更好）
-    let graph = if is_search_initialized() {
-        with_global_store(|store| {
-            GraphBuilder::build_from_store(&args.project_root, store)
-        })
-        .map_err(|e| McpError::internal_error(format!("Failed to build graph from store: {}", e), None))?
+    if is_search_initialized() {
+        with_global_store(|store| GraphBuilder::build_from_store(project_root, store))
+            .map(Arc::new)
+            .map_err(|e| McpError::internal_error(format!("Failed to build graph from store: {}", e), None))
     } else {
-        // 回退到直接扫描（兼容 MCP 独立运行）
-        GraphBuilder::build_from_project(&args.project_root)
+        // 回退到持久化图谱（兼容 MCP 独立运行，只重新解析变化的文件）
+        Ok(cache::load_graph(std::path::Path::new(project_root)))
+    }
+}
+
+/// 按名称或 ID 查找图谱中的符号 ID
+fn find_symbol_ids(graph: &CodeGraph, name: &str) -> Vec<String> {
+    let suffix = format!("::{}", name);
+    let mut ids: Vec<String> = graph
+        .node_map
+        .keys()
+        .filter(|id| id.ends_with(&suffix) || id.as_str() == name)
+        .cloned()
+        .collect();
+    ids.sort();
+    ids
+}
+
+/// 符号位置（`path:line`，行号未知时只有路径）
+fn node_location(node: &SymbolNode) -> String {
+    match node.start_line {
+        Some(line) => format!("{}:{}", node.file_path, line),
+        None => node.file_path.clone(),
+    }
+}
+
+pub fn handle_graph_callers(args: GraphCallersArgs) -> Result<Vec<Content>, McpError> {
+    let depth = args.depth.unwrap_or(1).max(1);
+    let graph = build_graph(&args.project_root)?;
+
+    let ids = find_symbol_ids(&graph, &args.symbol_name);
+    if ids.is_empty() {
+        return Err(McpError::invalid_params(
+            format!("Symbol '{}' not found in project", args.symbol_name),
+            None,
+        ));
+    }
+
+    let mut output = String::new();
+    for id in &ids {
+        let node = &graph.graph[graph.node_map[id]];
+        output.push_str(&format!("## {} ({})
+", id, node_location(node)));
+
+        let mut callers = graph.callers_of(id);
+        callers.sort_by(|a, b| a.id.cmp(&b.id));
+        output.push_str(&format!("\nDirect callers ({}):\n", callers.len()));
+        if callers.is_empty() {
+            output.push_str("- none\n");
+        }
+        for caller in callers {
+            output.push_str(&format!("- {} ({})\n", caller.name, node_location(caller)));
+        }
+
+        if depth > 1 {
+            let dependents = graph.transitive_dependents(id, depth);
+            output.push_str(&format!("\nTransitive dependents (depth {}, {}):\n", depth, dependents.len()));
+            for (dependent, hops) in dependents {
+                output.push_str(&format!("- [{}] {} ({})\n", hops, dependent.name, node_location(dependent)));
+            }
+        }
+
+        if args.include_dependencies.unwrap_or(false) {
+            let dependencies = graph.transitive_dependencies(id, depth);
+            output.push_str(&format!("\nDependencies (depth {}, {}):\n", depth, dependencies.len()));
+            for (dependency, hops) in dependencies {
+                output.push_str(&format!("- [{}] {} ({})\n", hops, dependency.name, node_location(dependency)));
+            }
+        }
+        output.push('\n');
+    }
+
+    Ok(vec![Content::text(output.trim_end().to_string())])
+}
+
+pub fn handle_graph_cycles(args: GraphCyclesArgs) -> Result<Vec<Content>, McpError> {
+    let levels = match args.level.as_deref().unwrap_or("all") {
+        "module" => vec![CycleLevel::Module],
+        "function" => vec![CycleLevel::Function],
+        "all" => vec![CycleLevel::Module, CycleLevel::Function],
+        other => {
+            return Err(McpError::invalid_params(
+                format!("Unknown level '{}', expected module, function or all", other),
+                None,
+            ))
+        }
     };
+    let max_cycles = args.max_cycles.unwrap_or(20).max(1);
+    let graph = build_graph(&args.project_root)?;
+
+    let mut output = String::new();
+    for level in levels {
+        let (label, unit) = match level {
+            CycleLevel::Module => ("Module", "files"),
+            CycleLevel::Function => ("Function", "symbols"),
+        };
+        let cycles = graph.dependency_cycles(level);
+        output.push_str(&format!("# {}-level cycles: {}\n", label, cycles.len()));
+        if cycles.is_empty() {
+            output.push_str("- none\n\n");
+            continue;
+        }
+
+        for (i, cycle) in cycles.iter().take(max_cycles).enumerate() {
+            output.push_str(&format!("\n## Cycle {} ({} {})\n", i + 1, cycle.members.len(), unit));
+            for member in &cycle.members {
+                output.push_str(&format!("- {}\n", member));
+            }
+            output.push_str("Edges:\n");
+            for (from, to, relation) in &cycle.edges {
+                output.push_str(&format!(
+                    "- {} ({}) --{:?}--> {} ({})\n",
+                    from.name,
+                    node_location(from),
+                    relation,
+                    to.name,
+                    node_location(to)
+                ));
+            }
+        }
+        if cycles.len() > max_cycles {
+            output.push_str(&format!("\n... {} more cycles not shown\n", cycles.len() - max_cycles));
+        }
+        output.push('\n');
+    }
+
+    Ok(vec![Content::text(output.trim_end().to_string())])
+}
+
+pub fn handle_graph_export(args: GraphExportArgs) -> Result<Vec<Content>, McpError> {
+    let format: GraphFormat = args
+        .format
+        .parse()
+        .map_err(|e: String| McpError::invalid_params(e, None))?;
+    let graph = build_graph(&args.project_root)?;
+
+    let exported = export_graph(&graph, format, args.path_prefix.as_deref());
+
+    let Some(output_path) = args.output_path else {
+        return Ok(vec![Content::text(exported)]);
+    };
+    let path = std::path::Path::new(&args.project_root).join(output_path);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| McpError::internal_error(format!("Failed to create {}: {}", parent.display(), e), None))?;
+    }
+    std::fs::write(&path, &exported)
+        .map_err(|e| McpError::internal_error(format!("Failed to write {}: {}", path.display(), e), None))?;
+
+    Ok(vec![Content::text(format!(
+        "Exported code graph as {:?} to {} ({} bytes).",
+        format,
+        path.display(),
+        exported.len()
+    ))])
+}
+
+pub fn handle_impact_analysis(
+    args: ImpactAnalysisArgs,
+) -> Result<Vec<Content>, McpError> {
+    let depth = args.depth.unwrap_or(1);
+
+    // 图谱未变化时直接复用上次的分析结果
+    let generation = impact_cache::current_generation(std::path::Path::new(&args.project_root));
+    if let Some(generation) = generation {
+        if let Some(result) = impact_cache::get(&args.project_root, &args.symbol_name, depth, generation) {
+            return Ok(vec![Content::text(result)]);
+        }
+    }
+
+    let result = analyze_impact(&args, depth)?;
+    if let Some(generation) = generation {
+        impact_cache::insert(&args.project_root, &args.symbol_name, depth, generation, result.clone());
+    }
+
+    Ok(vec![Content::text(result)])
+}
+
+/// 构建图谱并查找依赖该符号的上游调用方
+fn analyze_impact(args: &ImpactAnalysisArgs, depth: usize) -> Result<String, McpError> {
+    let graph = build_graph(&args.project_root)?;
 
     // Find the node for the symbol
     // We search by name since ID might be complex
-    let mut target_indices = Vec::new();
-    for (id, idx) in &graph.node_map {
-        if id.ends_with(&format!("::{}", args.symbol_name)) || id == &args.symbol_name {
-            target_indices.push(*idx);
+    let find_targets = |name: &str| {
+        graph
+            .node_map
+            .iter()
+            .filter(|(id, _)| id.ends_with(&format!("::{}", name)) || id.as_str() == name)
+            .map(|(_, idx)| *idx)
+            .collect::<Vec<_>>()
+    };
+    let mut target_indices = find_targets(&args.symbol_name);
+    let mut renamed_note = None;
+
+    // The symbol may have been renamed since the caller last saw it
+    if target_indices.is_empty() && is_search_initialized() {
+        let root = std::path::Path::new(&args.project_root);
+        let rename = with_global_store(|store| Ok(store.resolve_renamed_symbol(root, &args.symbol_name)))
+            .ok()
+            .flatten();
+        if let Some(rename) = rename {
+            target_indices = find_targets(&rename.new_name);
+            if !target_indices.is_empty() {
+                renamed_note = Some(format!(
+                    "Note: '{}' was renamed to '{}' ({}).\n",
+                    args.symbol_name, rename.new_name, rename.new_path
+                ));
+            }
         }
     }
 
@@ -47,13 +305,14 @@ pub fn handle_impact_analysis(
         ));
     }
 
-    let depth = args.depth.unwrap_or(1);
     let mut impacted_symbols = Vec::new();
+    // Closest distance of each impacted node, for blast-radius scoring
+    let mut impacted_depths: std::collections::HashMap<petgraph::graph::NodeIndex, usize> =
+        std::collections::HashMap::new();
 
-    // Find all nodes that depend on (call) the target nodes
-    // We traverse edges in reverse direction of 'Calls'
-    // Or if A calls B, then B impacts A? No, if B changes, A is impacted.
-    // So we look for incoming edges of type 'Calls' to B.
+    // Find all nodes that depend on the target nodes
+    // If A calls, imports or inherits from B, then when B changes A is impacted.
+    // So we look for incoming Calls/Imports/Inherits edges to B.
 
     use petgraph::Direction;
 
@@ -68,7 +327,7 @@ pub fn handle_impact_analysis(
                 continue;
             }
 
-            // Find who calls 'idx'
+            // Find who depends on 'idx'
             let mut neighbors = graph
                 .graph
                 .neighbors_directed(idx, Direction::Incoming)
@@ -82,10 +341,12 @@ pub fn handle_impact_analysis(
                 let edge = graph.graph.find_edge(neighbor_idx, idx).unwrap();
                 let relation = graph.graph.edge_weight(edge).unwrap();
 
-                if *relation == RelationType::Calls {
+                if relation.propagates_impact() {
                     if let Some(node) = graph.graph.node_weight(neighbor_idx) {
                         impacted_symbols
                             .push(format!("{} ({}) in {}", node.name, node.id, node.file_path));
+                        let closest = impacted_depths.entry(neighbor_idx).or_insert(d + 1);
+                        *closest = (*closest).min(d + 1);
                         visited.insert(neighbor_idx);
                         queue.push_back((neighbor_idx, d + 1));
                     }
@@ -97,12 +358,214 @@ pub fn handle_impact_analysis(
     let result = if impacted_symbols.is_empty() {
         "No impacted symbols found.".to_string()
     } else {
-        format!(
+        let mut result = format!(
             "Impacted symbols (Depth {}):\n- {}",
             depth,
             impacted_symbols.join("\n- ")
-        )
+        );
+        result.push_str(&format_blast_radius(&graph, &args.project_root, &args.symbol_name, impacted_depths));
+        result
     };
 
+    Ok(match renamed_note {
+        Some(note) => format!("{}{}", note, result),
+        None => result,
+    })
+}
+
+/// 按文件汇总 blast radius 分数并附上审查清单
+fn format_blast_radius(
+    graph: &CodeGraph,
+    project_root: &str,
+    symbol: &str,
+    impacted: std::collections::HashMap<petgraph::graph::NodeIndex, usize>,
+) -> String {
+    let impacted: Vec<_> = impacted.into_iter().collect();
+    let files = blast_radius(graph, std::path::Path::new(project_root), &impacted);
+
+    let mut output = String::from("\n\nBlast radius by file (higher = riskier):\n");
+    for file in &files {
+        let hint = if file.is_test {
+            ", test"
+        } else if !file.has_tests {
+            ", untested"
+        } else {
+            ""
+        };
+        output.push_str(&format!(
+            "- [{}] {} ({} symbols, depth {}, fan-in {}{})\n",
+            file.score, file.path, file.symbols, file.min_depth, file.fan_in, hint
+        ));
+    }
+
+    output.push_str("\nReview checklist:\n");
+    for item in review_checklist(symbol, &files) {
+        output.push_str(&format!("- [ ] {}\n", item));
+    }
+    output.trim_end().to_string()
+}
+
+pub fn handle_impact_changeset(
+    args: ImpactChangesetArgs,
+) -> Result<Vec<Content>, McpError> {
+    if args.files.is_empty() {
+        return Err(McpError::invalid_params("files must not be empty".to_string(), None));
+    }
+
+    let graph = build_graph(&args.project_root)?;
+
+    let root = std::path::Path::new(&args.project_root);
+    let changed: std::collections::BTreeSet<String> =
+        args.files.iter().map(|f| normalize_path(f, root)).collect();
+    let depth = args.depth.unwrap_or(2);
+
+    Ok(vec![Content::text(format_changeset_impact(&graph, root, &changed, depth))])
+}
+
+/// 汇总变更文件中符号的下游影响和受影响的测试
+fn format_changeset_impact(
+    graph: &CodeGraph,
+    root: &std::path::Path,
+    changed: &std::collections::BTreeSet<String>,
+    depth: usize,
+) -> String {
+    use petgraph::Direction;
+    use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+
+    let file_of = |idx| normalize_path(&graph.graph[idx].file_path, root);
+
+    // 变更文件中的符号即为被修改的符号
+    let mut touched: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut queue = VecDeque::new();
+    let mut visited = HashSet::new();
+    for idx in graph.graph.node_indices() {
+        let file = file_of(idx);
+        if changed.contains(&file) {
+            let names = touched.entry(file).or_default();
+            if !matches!(graph.graph[idx].kind, SymbolKind::File) {
+                names.push(graph.graph[idx].name.clone());
+            }
+            queue.push_back((idx, 0));
+            visited.insert(idx);
+        }
+    }
+
+    // 沿调用、导入和继承关系反向遍历，只保留变更文件之外的符号
+    let mut impacted: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    while let Some((idx, d)) = queue.pop_front() {
+        if d >= depth {
+            continue;
+        }
+        for caller in graph.graph.neighbors_directed(idx, Direction::Incoming) {
+            let is_dependent = graph
+                .graph
+                .find_edge(caller, idx)
+                .and_then(|edge| graph.graph.edge_weight(edge))
+                .is_some_and(|relation| relation.propagates_impact());
+            if !is_dependent || !visited.insert(caller) {
+                continue;
+            }
+            let file = file_of(caller);
+            if !changed.contains(&file) {
+                let names = impacted.entry(file).or_default();
+                // 导入方文件节点只标记文件受影响，不作为符号列出
+                if !matches!(graph.graph[caller].kind, SymbolKind::File) {
+                    names.insert(graph.graph[caller].name.clone());
+                }
+            }
+            queue.push_back((caller, d + 1));
+        }
+    }
+
+    let affected_tests: BTreeSet<&String> = impacted
+        .keys()
+        .chain(changed.iter())
+        .filter(|file| is_test_path(file))
+        .collect();
+    let unknown: Vec<&String> = changed.iter().filter(|f| !touched.contains_key(*f)).collect();
+
+    let mut output = format!(
+        "Change set: {} files, {} touched symbols, {} impacted files (Depth {})\n",
+        changed.len(),
+        touched.values().map(Vec::len).sum::<usize>(),
+        impacted.len(),
+        depth
+    );
+
+    output.push_str("\n## Touched symbols\n");
+    for (file, symbols) in &touched {
+        output.push_str(&format!("- {}: {}\n", file, symbols.join(", ")));
+    }
+    if !unknown.is_empty() {
+        output.push_str(&format!(
+            "- No indexed symbols: {}\n",
+            unknown.iter().map(|f| f.as_str()).collect::<Vec<_>>().join(", ")
+        ));
+    }
+
+    output.push_str("\n## Downstream impact\n");
+    if impacted.is_empty() {
+        output.push_str("No impacted symbols found.\n");
+    }
+    for (file, symbols) in &impacted {
+        if symbols.is_empty() {
+            output.push_str(&format!("- {}: (imports changed files)\n", file));
+            continue;
+        }
+        output.push_str(&format!(
+            "- {}: {}\n",
+            file,
+            symbols.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(", ")
+        ));
+    }
+
+    output.push_str("\n## Affected tests\n");
+    if affected_tests.is_empty() {
+        output.push_str("No affected tests found.\n");
+    }
+    for file in affected_tests {
+        output.push_str(&format!("- {}\n", file));
+    }
+
+    output
+}
+
+pub fn handle_architecture_check(
+    args: ArchitectureCheckArgs,
+) -> Result<Vec<Content>, McpError> {
+    let root = std::path::Path::new(&args.project_root);
+    let rules = ArchitectureRules::load(root)
+        .map_err(|e| McpError::invalid_params(format!("{:#}", e), None))?
+        .ok_or_else(|| {
+            McpError::invalid_params(
+                format!("No layer rules found, create {} in the project root", ARCHITECTURE_FILE),
+                None,
+            )
+        })?;
+
+    let graph = build_graph(&args.project_root)?;
+
+    let violations = rules.check(&graph, root);
+    if violations.is_empty() {
+        return Ok(vec![Content::text(format!(
+            "No layering violations found ({} layers checked).",
+            rules.layers.len()
+        ))]);
+    }
+
+    let mut result = format!("Found {} layering violations:\n", violations.len());
+    let mut current = None;
+    for violation in &violations {
+        let layers = (&violation.from_layer, &violation.to_layer);
+        if current != Some(layers) {
+            result.push_str(&format!("\n## {} → {} (not allowed)\n", violation.from_layer, violation.to_layer));
+            current = Some(layers);
+        }
+        result.push_str(&format!(
+            "- {} ({}) --{:?}--> {} ({})\n",
+            violation.from_symbol, violation.from_file, violation.relation, violation.to_symbol, violation.to_file
+        ));
+    }
+
     Ok(vec![Content::text(result)])
 }
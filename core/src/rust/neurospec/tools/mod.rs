@@ -1,6 +1,6 @@
 //! NeuroSpec 高级工具（重构辅助）
 //!
-//! 提供依赖影响分析和跨文件重命名功能
+//! 提供依赖影响分析、调用方查询、循环依赖检测、图谱导出、跨文件重命名以及 NSP 计划的校验与执行功能
 
 use rmcp::{
     model::CallToolResult,
@@ -8,9 +8,15 @@ use rmcp::{
 };
 
 pub mod graph_tools;
+pub mod plan_tools;
 pub mod refactor_tools;
 
-pub use graph_tools::ImpactAnalysisArgs;
+pub use graph_tools::{
+    ArchitectureCheckArgs, GraphCallersArgs, GraphCyclesArgs, GraphExportArgs, ImpactAnalysisArgs, ImpactChangesetArgs,
+};
+pub use plan_tools::{
+    PlanAssessRiskArgs, PlanDryRunArgs, PlanExecuteArgs, PlanResumeArgs, PlanSchemaArgs, PlanValidateArgs, PlansArgs,
+};
 pub use refactor_tools::RenameArgs;
 
 /// 处理 NeuroSpec 工具调用
@@ -29,6 +35,46 @@ pub async fn handle_neurospec_tool(
 
             graph_tools::handle_impact_analysis(args)?
         }
+        "neurospec_impact_changeset" => {
+            let args: ImpactChangesetArgs = serde_json::from_value(serde_json::Value::Object(args))
+                .map_err(|e| {
+                    McpError::invalid_params(format!("Invalid parameters: {}", e), None)
+                })?;
+
+            graph_tools::handle_impact_changeset(args)?
+        }
+        "neurospec_graph_callers" => {
+            let args: GraphCallersArgs = serde_json::from_value(serde_json::Value::Object(args))
+                .map_err(|e| {
+                    McpError::invalid_params(format!("Invalid parameters: {}", e), None)
+                })?;
+
+            graph_tools::handle_graph_callers(args)?
+        }
+        "neurospec_graph_cycles" => {
+            let args: GraphCyclesArgs = serde_json::from_value(serde_json::Value::Object(args))
+                .map_err(|e| {
+                    McpError::invalid_params(format!("Invalid parameters: {}", e), None)
+                })?;
+
+            graph_tools::handle_graph_cycles(args)?
+        }
+        "neurospec_graph_export" => {
+            let args: GraphExportArgs = serde_json::from_value(serde_json::Value::Object(args))
+                .map_err(|e| {
+                    McpError::invalid_params(format!("Invalid parameters: {}", e), None)
+                })?;
+
+            graph_tools::handle_graph_export(args)?
+        }
+        "neurospec_architecture_check" => {
+            let args: ArchitectureCheckArgs = serde_json::from_value(serde_json::Value::Object(args))
+                .map_err(|e| {
+                    McpError::invalid_params(format!("Invalid parameters: {}", e), None)
+                })?;
+
+            graph_tools::handle_architecture_check(args)?
+        }
         "neurospec_refactor_rename" => {
             let args: RenameArgs = serde_json::from_value(serde_json::Value::Object(args))
                 .map_err(|e| {
@@ -37,6 +83,62 @@ pub async fn handle_neurospec_tool(
 
             refactor_tools::handle_rename(args)?
         }
+        "neurospec_plan_validate" => {
+            let args: PlanValidateArgs = serde_json::from_value(serde_json::Value::Object(args))
+                .map_err(|e| {
+                    McpError::invalid_params(format!("Invalid parameters: {}", e), None)
+                })?;
+
+            plan_tools::handle_plan_validate(args)?
+        }
+        "neurospec_plan_assess_risk" => {
+            let args: PlanAssessRiskArgs = serde_json::from_value(serde_json::Value::Object(args))
+                .map_err(|e| {
+                    McpError::invalid_params(format!("Invalid parameters: {}", e), None)
+                })?;
+
+            plan_tools::handle_plan_assess_risk(args)?
+        }
+        "neurospec_plan_dry_run" => {
+            let args: PlanDryRunArgs = serde_json::from_value(serde_json::Value::Object(args))
+                .map_err(|e| {
+                    McpError::invalid_params(format!("Invalid parameters: {}", e), None)
+                })?;
+
+            plan_tools::handle_plan_dry_run(args)?
+        }
+        "neurospec_plan_execute" => {
+            let args: PlanExecuteArgs = serde_json::from_value(serde_json::Value::Object(args))
+                .map_err(|e| {
+                    McpError::invalid_params(format!("Invalid parameters: {}", e), None)
+                })?;
+
+            plan_tools::handle_plan_execute(args).await?
+        }
+        "neurospec_plan_resume" => {
+            let args: PlanResumeArgs = serde_json::from_value(serde_json::Value::Object(args))
+                .map_err(|e| {
+                    McpError::invalid_params(format!("Invalid parameters: {}", e), None)
+                })?;
+
+            plan_tools::handle_plan_resume(args).await?
+        }
+        "neurospec_plans" => {
+            let args: PlansArgs = serde_json::from_value(serde_json::Value::Object(args))
+                .map_err(|e| {
+                    McpError::invalid_params(format!("Invalid parameters: {}", e), None)
+                })?;
+
+            plan_tools::handle_plans(args)?
+        }
+        "neurospec_plan_schema" => {
+            let args: PlanSchemaArgs = serde_json::from_value(serde_json::Value::Object(args))
+                .map_err(|e| {
+                    McpError::invalid_params(format!("Invalid parameters: {}", e), None)
+                })?;
+
+            plan_tools::handle_plan_schema(args)?
+        }
         _ => {
             return Err(McpError::invalid_request(
                 format!("Unknown tool: {}", name),
@@ -0,0 +1,285 @@
+use std::sync::Arc;
+
+use rmcp::{model::Content, ErrorData as McpError};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::mcp::tools::memory::{ChangeTracker, PlanRunStatus, StoredPlan};
+use crate::mcp::tools::unified_store::{is_search_initialized, with_global_store};
+use crate::mcp::utils::{ToolError, ToolErrorCode};
+use crate::neurospec::models::{nsp_json_schema, validate_nsp_value, NSP, NSP_SCHEMA_PARTS};
+use crate::neurospec::services::graph::builder::GraphBuilder;
+use crate::neurospec::services::graph::cache;
+use crate::neurospec::services::risk::{assess_plan_risk, RiskAssessment};
+use crate::neurospec::services::{NSPStepEdit, PlanExecutor, StepOutcome, StepStatus};
+
+/// 计划列表默认返回数量
+const DEFAULT_PLAN_LIST_LIMIT: usize = 20;
+
+/// Arguments for neurospec.plan.validate
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PlanValidateArgs {
+    /// Project root directory path
+    pub project_root: String,
+    /// NSP plan to validate (JSON object, or a JSON string)
+    pub plan: serde_json::Value,
+}
+
+/// Arguments for neurospec.plan.execute
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PlanExecuteArgs {
+    /// Project root directory path
+    pub project_root: String,
+    /// NSP plan to execute (JSON object, or a JSON string)
+    pub plan: serde_json::Value,
+    /// File contents for the CREATE/MODIFY/REFACTOR steps
+    #[serde(default)]
+    pub edits: Vec<NSPStepEdit>,
+    /// Step ids to pause at for confirmation (default: steps with risk_tags, every edit step of a HIGH risk plan; DELETE steps always pause)
+    #[serde(default)]
+    pub checkpoints: Option<Vec<i32>>,
+}
+
+/// Arguments for neurospec.plan.assess_risk
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PlanAssessRiskArgs {
+    /// Project root directory path
+    pub project_root: String,
+    /// NSP plan to assess (JSON object, or a JSON string)
+    pub plan: serde_json::Value,
+}
+
+/// Arguments for neurospec.plan.dry_run
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PlanDryRunArgs {
+    /// Project root directory path
+    pub project_root: String,
+    /// NSP plan to simulate (JSON object, or a JSON string)
+    pub plan: serde_json::Value,
+    /// File contents for the CREATE/MODIFY/REFACTOR steps
+    #[serde(default)]
+    pub edits: Vec<NSPStepEdit>,
+}
+
+/// Arguments for neurospec.plan.resume
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PlanResumeArgs {
+    /// Project root directory path
+    pub project_root: String,
+    /// ID of the interrupted plan (from neurospec_plans or a previous execution report)
+    pub plan_id: String,
+}
+
+/// Arguments for neurospec.plans
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PlansArgs {
+    /// Project root directory path
+    pub project_root: String,
+    /// Max number of plans to list, most recently updated first (default: 20)
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// Arguments for neurospec.plan.schema
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PlanSchemaArgs {
+    /// Structure to export: "plan", "step" or "meta" (default: all three)
+    #[serde(default)]
+    pub part: Option<String>,
+}
+
+pub fn handle_plan_validate(args: PlanValidateArgs) -> Result<Vec<Content>, McpError> {
+    let plan = parse_plan(args.plan)?;
+    let root = project_root(&args.project_root)?;
+
+    let report = validate_nsp_value(&plan, root);
+    to_json_content(&report)
+}
+
+pub async fn handle_plan_execute(args: PlanExecuteArgs) -> Result<Vec<Content>, McpError> {
+    let plan = parse_plan(args.plan)?;
+    let root = project_root(&args.project_root)?;
+
+    // 未通过校验的计划不执行，直接返回校验结果
+    let report = validate_nsp_value(&plan, root);
+    if !report.valid {
+        return to_json_content(&report);
+    }
+    let mut nsp: NSP = serde_json::from_value(plan)
+        .map_err(|e| McpError::invalid_params(format!("Invalid plan: {}", e), None))?;
+
+    // 检查点按计算出的风险级别决定，而不是计划里手写的标签
+    assess_risk(&args.project_root, &nsp)?.apply_to(&mut nsp);
+
+    let report = PlanExecutor::new(root, nsp, args.edits, args.checkpoints).run().await;
+    to_json_content(&report)
+}
+
+pub fn handle_plan_assess_risk(args: PlanAssessRiskArgs) -> Result<Vec<Content>, McpError> {
+    let plan = parse_plan(args.plan)?;
+    let root = project_root(&args.project_root)?;
+
+    let report = validate_nsp_value(&plan, root);
+    if !report.valid {
+        return to_json_content(&report);
+    }
+    let mut nsp: NSP = serde_json::from_value(plan)
+        .map_err(|e| McpError::invalid_params(format!("Invalid plan: {}", e), None))?;
+
+    // 返回评估结果和填好 risk_level / open_questions 的计划
+    let assessment = assess_risk(&args.project_root, &nsp)?;
+    assessment.apply_to(&mut nsp);
+    to_json_content(&serde_json::json!({ "assessment": assessment, "plan": nsp }))
+}
+
+pub fn handle_plan_dry_run(args: PlanDryRunArgs) -> Result<Vec<Content>, McpError> {
+    let plan = parse_plan(args.plan)?;
+    let root = project_root(&args.project_root)?;
+
+    let report = validate_nsp_value(&plan, root);
+    if !report.valid {
+        return to_json_content(&report);
+    }
+    let nsp: NSP = serde_json::from_value(plan)
+        .map_err(|e| McpError::invalid_params(format!("Invalid plan: {}", e), None))?;
+
+    let report = PlanExecutor::new(root, nsp, args.edits, None).dry_run();
+    to_json_content(&report)
+}
+
+pub async fn handle_plan_resume(args: PlanResumeArgs) -> Result<Vec<Content>, McpError> {
+    let root = project_root(&args.project_root)?;
+    let tracker = plan_tracker(&args.project_root)?;
+
+    let record = tracker
+        .get_plan(&args.plan_id)
+        .map_err(|e| McpError::internal_error(format!("Failed to load plan: {}", e), None))?
+        .ok_or_else(|| {
+            McpError::from(ToolError::new(ToolErrorCode::NotFound, format!("Plan '{}' not found", args.plan_id)))
+        })?;
+    if record.status == PlanRunStatus::Completed {
+        return Err(McpError::invalid_params(
+            format!("Plan '{}' has already completed", args.plan_id),
+            None,
+        ));
+    }
+
+    // 已执行的步骤改变了文件，不再重新校验整个计划，剩余步骤的问题由执行器逐步报告；
+    // 保存的计划可能在磁盘上被修改过，路径仍需限制在项目内
+    let nsp: NSP = serde_json::from_value(record.plan)
+        .map_err(|e| McpError::internal_error(format!("Stored plan is corrupted: {}", e), None))?;
+    let report = nsp.validate_scope();
+    if !report.valid {
+        return to_json_content(&report);
+    }
+    let edits: Vec<NSPStepEdit> = serde_json::from_value(record.edits)
+        .map_err(|e| McpError::internal_error(format!("Stored plan edits are corrupted: {}", e), None))?;
+    let outcomes: Vec<StepOutcome> = serde_json::from_value(record.outcomes).unwrap_or_default();
+    let applied = outcomes
+        .iter()
+        .filter(|o| o.status == StepStatus::Applied)
+        .map(|o| o.step_id)
+        .collect();
+
+    let report = PlanExecutor::new(root, nsp, edits, Some(record.checkpoints))
+        .resume(record.id, applied)
+        .run()
+        .await;
+    to_json_content(&report)
+}
+
+pub fn handle_plan_schema(args: PlanSchemaArgs) -> Result<Vec<Content>, McpError> {
+    let schema = match args.part.as_deref() {
+        Some(part) => nsp_json_schema(part).ok_or_else(|| {
+            McpError::invalid_params(
+                format!("Unknown part '{}', expected one of {}", part, NSP_SCHEMA_PARTS.join(", ")),
+                None,
+            )
+        })?,
+        None => NSP_SCHEMA_PARTS
+            .iter()
+            .filter_map(|part| Some((part.to_string(), nsp_json_schema(part)?)))
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+    };
+    to_json_content(&schema)
+}
+
+pub fn handle_plans(args: PlansArgs) -> Result<Vec<Content>, McpError> {
+    project_root(&args.project_root)?;
+    let tracker = plan_tracker(&args.project_root)?;
+
+    let plans = tracker
+        .list_plans(args.limit.unwrap_or(DEFAULT_PLAN_LIST_LIMIT))
+        .map_err(|e| McpError::internal_error(format!("Failed to list plans: {}", e), None))?;
+    let summaries: Vec<serde_json::Value> = plans.iter().map(plan_summary).collect();
+    to_json_content(&summaries)
+}
+
+/// 计划列表中的一项：状态与进度，不包含计划正文
+fn plan_summary(plan: &StoredPlan) -> serde_json::Value {
+    let total_steps = plan
+        .plan
+        .get("execution_plan")
+        .and_then(|v| v.as_array())
+        .map_or(0, |steps| steps.len());
+    let outcomes: Vec<StepOutcome> = serde_json::from_value(plan.outcomes.clone()).unwrap_or_default();
+    let applied_steps = outcomes.iter().filter(|o| o.status == StepStatus::Applied).count();
+
+    serde_json::json!({
+        "plan_id": plan.id,
+        "intent_summary": plan.intent_summary,
+        "status": plan.status,
+        "applied_steps": applied_steps,
+        "total_steps": total_steps,
+        "resumable": plan.status != PlanRunStatus::Completed,
+        "created_at": plan.created_at,
+        "updated_at": plan.updated_at,
+    })
+}
+
+fn plan_tracker(project_root: &str) -> Result<ChangeTracker, McpError> {
+    ChangeTracker::new(project_root)
+        .map_err(|e| McpError::internal_error(format!("Project memory is unavailable: {}", e), None))
+}
+
+/// 用代码图谱评估计划风险
+fn assess_risk(project_root: &str, plan: &NSP) -> Result<RiskAssessment, McpError> {
+    // 优先使用全局 Store（增量索引，性能更好）
+    let graph = if is_search_initialized() {
+        with_global_store(|store| GraphBuilder::build_from_store(project_root, store))
+            .map(Arc::new)
+            .map_err(|e| McpError::internal_error(format!("Failed to build graph from store: {}", e), None))?
+    } else {
+        // 回退到持久化图谱（只重新解析变化的文件）
+        cache::load_graph(std::path::Path::new(project_root))
+    };
+
+    Ok(assess_plan_risk(&graph, std::path::Path::new(project_root), plan))
+}
+
+/// 部分客户端会把计划作为字符串传入
+fn parse_plan(plan: serde_json::Value) -> Result<serde_json::Value, McpError> {
+    match plan {
+        serde_json::Value::String(text) => serde_json::from_str(&text)
+            .map_err(|e| McpError::invalid_params(format!("Plan is not valid JSON: {}", e), None)),
+        plan => Ok(plan),
+    }
+}
+
+fn project_root(path: &str) -> Result<&std::path::Path, McpError> {
+    let root = std::path::Path::new(path);
+    if !root.is_dir() {
+        return Err(McpError::invalid_params(
+            format!("Project root '{}' is not a directory", path),
+            None,
+        ));
+    }
+    Ok(root)
+}
+
+fn to_json_content<T: serde::Serialize>(value: &T) -> Result<Vec<Content>, McpError> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| McpError::internal_error(format!("Failed to serialize report: {}", e), None))?;
+    Ok(vec![Content::text(json)])
+}
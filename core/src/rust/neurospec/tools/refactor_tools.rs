@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use rmcp::{model::Content, ErrorData as McpError};
 use schemars::JsonSchema;
 use serde::Deserialize;
@@ -5,9 +7,11 @@ use tree_sitter::StreamingIterator;
 
 use crate::neurospec::models::SymbolKind;
 use crate::neurospec::services::graph::builder::GraphBuilder;
-use crate::neurospec::services::refactor::renamer::Renamer;
+use crate::neurospec::services::graph::cache;
+use crate::neurospec::services::refactor::renamer::{ConflictKind, RenameConflict, Renamer};
 use crate::neurospec::services::refactor::validator::Validator;
-use crate::mcp::tools::unified_store::{with_global_store, is_search_initialized};
+use crate::mcp::tools::unified_store::{with_global_store, is_search_initialized, SymbolFilter};
+use crate::mcp::utils::{ToolError, ToolErrorCode};
 
 /// Arguments for neurospec.refactor.rename
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -23,6 +27,9 @@ pub struct RenameArgs {
     /// Symbol kind (function, class, etc.)
     #[serde(default = "default_kind")]
     pub kind: String,
+    /// Rename even if the new name shadows a symbol in files that use it (clashes in the symbol's own module are always rejected)
+    #[serde(default)]
+    pub allow_shadowing: bool,
 }
 
 fn default_kind() -> String {
@@ -48,10 +55,11 @@ pub fn handle_rename(args: RenameArgs) -> Result<Vec<Content>, McpError> {
         with_global_store(|store| {
             GraphBuilder::build_from_store(&args.project_root, store)
         })
+        .map(Arc::new)
         .map_err(|e| McpError::internal_error(format!("Failed to build graph from store: {}", e), None))?
     } else {
-        // 回退到直接扫描
-        GraphBuilder::build_from_project(&args.project_root)
+        // 回退到持久化图谱（只重新解析变化的文件）
+        cache::load_graph(std::path::Path::new(&args.project_root))
     };
 
     // Parse symbol kind
@@ -62,6 +70,22 @@ pub fn handle_rename(args: RenameArgs) -> Result<Vec<Content>, McpError> {
         _ => SymbolKind::Function,
     };
 
+    // Refuse to redefine or shadow an existing symbol with the new name
+    let files = Renamer::affected_files(&graph, &args.file_path, &args.old_name)
+        .map_err(|e| McpError::from(ToolError::from_anyhow(&e)))?;
+    let imports = if is_search_initialized() {
+        file_imports(&args.project_root, &files)
+    } else {
+        Vec::new()
+    };
+    let conflicts = Renamer::find_conflicts(&graph, &args.file_path, &args.new_name, &files, &imports);
+    if conflicts
+        .iter()
+        .any(|c| c.kind == ConflictKind::SameModule || !args.allow_shadowing)
+    {
+        return Err(rename_conflict_error(&args, &conflicts));
+    }
+
     // Perform rename
     let result = Renamer::rename_symbol(
         &graph,
@@ -70,7 +94,7 @@ pub fn handle_rename(args: RenameArgs) -> Result<Vec<Content>, McpError> {
         &args.new_name,
         kind,
     )
-    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+    .map_err(|e| McpError::from(ToolError::from_anyhow(&e)))?;
 
     if !result.success {
         return Err(McpError::internal_error(
@@ -79,30 +103,6 @@ pub fn handle_rename(args: RenameArgs) -> Result<Vec<Content>, McpError> {
         ));
     }
 
-    // Validate all modified files
-    for file in &result.modified_files {
-        // Infer language from file extension
-        let lang = if file.ends_with(".rs") {
-            "rust"
-        } else if file.ends_with(".ts") || file.ends_with(".js") {
-            "typescript"
-        } else if file.ends_with(".py") {
-            "python"
-        } else {
-            continue;
-        };
-
-        let is_valid = Validator::validate_file(file, lang)
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-        if !is_valid {
-            return Err(McpError::internal_error(
-                format!("Syntax errors introduced in {}", file),
-                None,
-            ));
-        }
-    }
-
     // Format result
     let summary = format!(
         "Renamed '{}' to '{}'\nModified {} file(s):\n- {}",
@@ -115,6 +115,55 @@ pub fn handle_rename(args: RenameArgs) -> Result<Vec<Content>, McpError> {
     Ok(vec![Content::text(summary)])
 }
 
+/// Import specs of the given files from the symbol store, as (file, spec) pairs
+fn file_imports(project_root: &str, files: &[String]) -> Vec<(String, String)> {
+    let filter = SymbolFilter {
+        kinds: vec![crate::mcp::tools::unified_store::SymbolKind::File],
+        ..Default::default()
+    };
+    with_global_store(|store| store.query_symbols(std::path::Path::new(project_root), &filter))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|symbol| files.contains(&symbol.path))
+        .flat_map(|symbol| {
+            let path = symbol.path;
+            symbol.references.into_iter().map(move |spec| (path.clone(), spec))
+        })
+        .collect()
+}
+
+/// `RENAME_CONFLICT` error listing the clashing symbols, with the same list in the error data
+fn rename_conflict_error(args: &RenameArgs, conflicts: &[RenameConflict]) -> McpError {
+    let mut message = format!(
+        "Renaming '{}' to '{}' conflicts with {} existing symbol(s):",
+        args.old_name,
+        args.new_name,
+        conflicts.len()
+    );
+    for conflict in conflicts {
+        let location = match conflict.line {
+            Some(line) => format!("{}:{}", conflict.file_path, line),
+            None => conflict.file_path.clone(),
+        };
+        let (what, how) = match (conflict.kind, conflict.imported) {
+            (ConflictKind::SameModule, false) => ("already defined in the same module", "redefinition"),
+            (ConflictKind::SameModule, true) => ("already imported into the same module", "redefinition"),
+            (ConflictKind::Shadowing, false) => ("defined in a file that uses the symbol", "shadowing"),
+            (ConflictKind::Shadowing, true) => ("imported in a file that uses the symbol", "shadowing"),
+        };
+        message.push_str(&format!("\n- {} ({}): {}, {}", conflict.existing, location, what, how));
+    }
+    if !args.allow_shadowing && conflicts.iter().all(|c| c.kind == ConflictKind::Shadowing) {
+        message.push_str("\nPass allow_shadowing=true to rename anyway.");
+    }
+
+    let mut error = McpError::from(ToolError::new(ToolErrorCode::RenameConflict, message));
+    if let Some(data) = error.data.as_mut() {
+        data["conflicts"] = serde_json::to_value(conflicts).unwrap_or_default();
+    }
+    error
+}
+
 pub fn handle_safe_edit(args: SafeEditArgs) -> Result<Vec<Content>, McpError> {
     // Read original file
     let content = std::fs::read_to_string(&args.file_path)
@@ -188,10 +237,10 @@ pub fn handle_safe_edit(args: SafeEditArgs) -> Result<Vec<Content>, McpError> {
     }
 
     let range = target_range.ok_or_else(|| {
-        McpError::invalid_params(
+        McpError::from(ToolError::new(
+            ToolErrorCode::NotFound,
             format!("Symbol '{}' not found in file", args.target_symbol),
-            None,
-        )
+        ))
     })?;
 
     // Apply replacement
@@ -210,10 +259,11 @@ pub fn handle_safe_edit(args: SafeEditArgs) -> Result<Vec<Content>, McpError> {
         std::fs::write(&args.file_path, &content)
             .map_err(|e| McpError::internal_error(format!("Rollback failed: {}", e), None))?;
 
-        return Err(McpError::internal_error(
-            "Syntax errors introduced by edit, changes rolled back".to_string(),
-            None,
-        ));
+        return Err(ToolError::new(
+            ToolErrorCode::ValidationFailed,
+            "Syntax errors introduced by edit, changes rolled back",
+        )
+        .into());
     }
 
     Ok(vec![Content::text(format!(
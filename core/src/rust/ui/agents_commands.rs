@@ -191,7 +191,7 @@ pub async fn get_index_status() -> Result<IndexStatusResponse, String> {
 
 /// 配置文件路径
 fn get_config_path() -> Option<PathBuf> {
-    dirs::data_dir().map(|d| d.join("neurospec").join("project_config.json"))
+    crate::config::base_data_dir().map(|d| d.join("project_config.json"))
 }
 
 /// 项目配置
@@ -259,6 +259,21 @@ pub async fn load_agents_config(_path: String) -> Result<serde_json::Value, Stri
     Err("需要启用 experimental-neurospec 特性".to_string())
 }
 
+/// 加载作用于指定路径的 AGENTS.md 配置（合并项目根目录到该路径沿途的 AGENTS.md）
+#[tauri::command]
+#[cfg(feature = "experimental-neurospec")]
+pub async fn load_scoped_agents_config(path: String, target: String) -> Result<AgentsConfig, String> {
+    AgentsConfig::load_scoped(&PathBuf::from(&path), &PathBuf::from(&target))
+        .map_err(|e| format!("加载配置失败: {}", e))
+}
+
+/// 加载作用于指定路径的 AGENTS.md 配置（非 neurospec 版本）
+#[tauri::command]
+#[cfg(not(feature = "experimental-neurospec"))]
+pub async fn load_scoped_agents_config(_path: String, _target: String) -> Result<serde_json::Value, String> {
+    Err("需要启用 experimental-neurospec 特性".to_string())
+}
+
 /// 保存 AGENTS.md 配置
 #[tauri::command]
 #[cfg(feature = "experimental-neurospec")]
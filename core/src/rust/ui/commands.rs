@@ -897,7 +897,10 @@ pub async fn reset_shortcuts_to_default(
 // Interact 历史记录命令
 // ============================================================================
 
-use crate::mcp::tools::interaction::{InteractRecord, get_interact_history, search_interact_history, clear_interact_history};
+use crate::mcp::tools::interaction::{
+    InteractRecord, HistoryQuery, get_interact_history, search_interact_history, clear_interact_history,
+    filter_interact_history, export_interact_history,
+};
 
 /// 获取 interact 历史记录
 #[tauri::command]
@@ -911,6 +914,18 @@ pub async fn search_interact_history_cmd(query: String) -> Result<Vec<InteractRe
     search_interact_history(&query).map_err(|e| format!("搜索历史记录失败: {}", e))
 }
 
+/// 按日期范围、响应类型、关键词查询 interact 历史记录
+#[tauri::command]
+pub async fn query_interact_history_cmd(query: HistoryQuery) -> Result<Vec<InteractRecord>, String> {
+    filter_interact_history(&query, query.limit).map_err(|e| format!("查询历史记录失败: {}", e))
+}
+
+/// 按条件导出 interact 历史记录为 JSON 文件，返回导出条数
+#[tauri::command]
+pub async fn export_interact_history_cmd(query: HistoryQuery, path: String) -> Result<usize, String> {
+    export_interact_history(&query, std::path::Path::new(&path)).map_err(|e| format!("导出历史记录失败: {}", e))
+}
+
 /// 清空 interact 历史记录
 #[tauri::command]
 pub async fn clear_interact_history_cmd() -> Result<(), String> {
@@ -936,10 +951,7 @@ pub struct EmbeddingConfigFrontend {
 
 /// 获取嵌入配置文件路径
 fn get_embedding_config_path() -> PathBuf {
-    dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(".neurospec")
-        .join("embedding_config.json")
+    crate::neurospec::services::embedding::embedding_config_path()
 }
 
 /// 获取嵌入配置
@@ -1035,3 +1047,50 @@ pub struct TestConnectionResult {
     pub success: bool,
     pub message: String,
 }
+
+// ============================================================================
+// 配置档案
+// ============================================================================
+
+/// 配置档案列表
+#[derive(Debug, Serialize)]
+pub struct ConfigProfilesInfo {
+    pub active: String,
+    pub profiles: Vec<String>,
+}
+
+/// 列出配置档案及当前档案
+#[tauri::command]
+pub async fn list_config_profiles() -> Result<ConfigProfilesInfo, String> {
+    Ok(ConfigProfilesInfo {
+        active: crate::config::active_profile().to_string(),
+        profiles: crate::config::list_profiles(),
+    })
+}
+
+/// 创建配置档案
+#[tauri::command]
+pub async fn create_config_profile(name: String) -> Result<(), String> {
+    crate::config::create_profile(&name).map_err(|e| e.to_string())
+}
+
+/// 切换配置档案（持久化后重启应用生效）
+#[tauri::command]
+pub async fn switch_config_profile(name: String, app: AppHandle) -> Result<(), String> {
+    switch_profile_and_restart(&app, &name)
+}
+
+/// 持久化默认档案并重启，使所有服务按新档案重新解析路径
+pub fn switch_profile_and_restart(app: &AppHandle, name: &str) -> Result<(), String> {
+    if name == crate::config::active_profile() {
+        return Ok(());
+    }
+
+    crate::config::set_default_profile(name).map_err(|e| e.to_string())?;
+    if std::env::var(crate::config::PROFILE_ENV_VAR).is_ok() {
+        log::warn!("已设置 {}，重启后仍以环境变量指定的档案为准", crate::config::PROFILE_ENV_VAR);
+    }
+
+    crate::ui::exit_handler::cleanup_exit_handlers(app);
+    app.restart()
+}
@@ -0,0 +1,33 @@
+//! 崩溃报告相关的 Tauri 命令
+//!
+//! 启动时前端查询未处理的崩溃报告，提示用户附加到 GitHub issue 或忽略，
+//! 两种情况处理后都调用 `dismiss_crash_report` 归档
+
+use crate::app::crash::{
+    archive_crash_report, crash_issue_url, load_crash_report, pending_crash_reports, CrashReport,
+    CrashReportSummary,
+};
+
+/// 获取未处理的崩溃报告
+#[tauri::command]
+pub async fn get_pending_crash_reports() -> Result<Vec<CrashReportSummary>, String> {
+    Ok(pending_crash_reports())
+}
+
+/// 获取完整崩溃报告
+#[tauri::command]
+pub async fn get_crash_report(id: String) -> Result<CrashReport, String> {
+    load_crash_report(&id)
+}
+
+/// 生成预填内容的 issue 链接（由前端通过 shell 插件打开，URL 含 `&`，不走 `cmd /C start`）
+#[tauri::command]
+pub async fn get_crash_issue_url(id: String) -> Result<String, String> {
+    crash_issue_url(&load_crash_report(&id)?)
+}
+
+/// 将崩溃报告标记为已处理（已附加到 issue 或忽略）
+#[tauri::command]
+pub async fn dismiss_crash_report(id: String) -> Result<(), String> {
+    archive_crash_report(&id)
+}
@@ -1,5 +1,9 @@
+use std::sync::Once;
+
 use crate::config::AppState;
 use crate::log_important;
+use crate::mcp::tools::memory::SqliteStorage;
+use crate::mcp::tools::unified_store;
 use tauri::{AppHandle, Manager};
 
 /// 处理应用退出请求（从前端快捷键调用）
@@ -21,7 +25,43 @@ pub fn setup_exit_handlers(_app_handle: &AppHandle) -> Result<(), String> {
     Ok(())
 }
 
-/// 清理退出处理器（空操作，保持接口一致性）
+/// 应用退出时的清理（见 [`shutdown_subsystems`]）
 pub fn cleanup_exit_handlers(_app_handle: &AppHandle) {
-    log_important!(info, "应用退出处理器无需清理");
-} 
+    shutdown_subsystems();
+}
+
+/// 依次关闭各子系统，多次调用只执行一次
+///
+/// 1. 设置退出标志，后台索引与轮询线程在下一次检查时结束
+/// 2. 停止文件监听
+/// 3. 刷新符号存储 WAL 并提交/压缩搜索索引
+/// 4. 持久化索引状态（未完成的索引标记为过期）
+/// 5. 刷新嵌入缓存与各项目记忆数据库的 WAL
+/// 6. 写入本进程的使用统计
+/// 7. 导出剩余的链路追踪数据
+///
+/// 索引锁（`.indexing.lock` 上的 flock）由索引任务持有，进程退出时由系统释放，无需在此处理
+pub fn shutdown_subsystems() {
+    static SHUTDOWN: Once = Once::new();
+    SHUTDOWN.call_once(|| {
+        log_important!(info, "应用退出，清理各子系统");
+        crate::utils::request_shutdown();
+
+        unified_store::stop_global_watcher();
+        if unified_store::is_search_initialized() {
+            unified_store::flush_on_shutdown();
+        }
+        unified_store::persist_index_states_on_shutdown();
+
+        crate::neurospec::services::flush_embedding_cache();
+        for project in unified_store::known_projects() {
+            let memory_dir = crate::config::project_memory_dir(&project);
+            if let Err(e) = SqliteStorage::checkpoint(&memory_dir) {
+                log_important!(warn, "刷新记忆数据库失败 {}: {}", project.display(), e);
+            }
+        }
+
+        crate::app::telemetry::flush_queue();
+        crate::app::otel::shutdown();
+    });
+}
@@ -0,0 +1,32 @@
+//! 代码图谱相关的 Tauri 命令
+
+/// 将项目代码图谱导出为 GraphML / DOT / Mermaid 文本
+#[tauri::command]
+#[cfg(feature = "experimental-neurospec")]
+pub async fn export_code_graph(
+    project_root: String,
+    format: String,
+    path_prefix: Option<String>,
+) -> Result<String, String> {
+    use crate::neurospec::services::graph::cache;
+    use crate::neurospec::services::graph::export::{export_graph, GraphFormat};
+
+    let format: GraphFormat = format.parse()?;
+    tokio::task::spawn_blocking(move || {
+        let graph = cache::load_graph(std::path::Path::new(&project_root));
+        export_graph(&graph, format, path_prefix.as_deref())
+    })
+    .await
+    .map_err(|e| format!("导出图谱失败: {}", e))
+}
+
+/// 导出代码图谱（非 neurospec 版本）
+#[tauri::command]
+#[cfg(not(feature = "experimental-neurospec"))]
+pub async fn export_code_graph(
+    _project_root: String,
+    _format: String,
+    _path_prefix: Option<String>,
+) -> Result<String, String> {
+    Err("需要启用 experimental-neurospec 特性".to_string())
+}
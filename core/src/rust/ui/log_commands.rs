@@ -0,0 +1,101 @@
+//! 日志相关的 Tauri 命令
+//!
+//! 运行时修改日志级别与按模块过滤规则（如只对 `acemcp` 开启 debug），
+//! 以及获取最近日志供用户复制到问题反馈中。日志文件按大小和日期轮转，
+//! 日志目录可在设置界面中查看和打开
+
+use serde::Serialize;
+
+use crate::utils::{current_log_filter, list_log_files, log_directory, log_file_path, recent_logs, set_log_filter, LogFileInfo};
+
+/// 默认返回的最近日志行数
+const DEFAULT_RECENT_LINES: usize = 200;
+
+/// 按模块设置的日志级别
+#[derive(Debug, Serialize)]
+pub struct ModuleLogLevel {
+    pub module: String,
+    pub level: String,
+}
+
+/// 当前日志设置
+#[derive(Debug, Serialize)]
+pub struct LogSettings {
+    /// 完整过滤规则（RUST_LOG 格式）
+    pub filter: String,
+    /// 全局级别
+    pub level: String,
+    pub modules: Vec<ModuleLogLevel>,
+    pub log_file: Option<String>,
+}
+
+/// 日志目录信息
+#[derive(Debug, Serialize)]
+pub struct LogDirectoryInfo {
+    pub directory: Option<String>,
+    /// 当前及轮转后的日志文件，按修改时间倒序
+    pub files: Vec<LogFileInfo>,
+}
+
+fn log_settings() -> LogSettings {
+    let filter = current_log_filter();
+    LogSettings {
+        filter: filter.spec(),
+        level: filter.level.to_string().to_lowercase(),
+        modules: filter
+            .modules
+            .iter()
+            .map(|(module, level)| ModuleLogLevel {
+                module: module.clone(),
+                level: level.to_string().to_lowercase(),
+            })
+            .collect(),
+        log_file: log_file_path().map(|p| p.to_string_lossy().to_string()),
+    }
+}
+
+/// 获取当前日志设置
+#[tauri::command]
+pub async fn get_log_settings() -> Result<LogSettings, String> {
+    Ok(log_settings())
+}
+
+/// 修改日志过滤规则，立即生效（重启后恢复为 RUST_LOG / 默认级别）
+#[tauri::command]
+pub async fn set_log_level(filter: String) -> Result<LogSettings, String> {
+    set_log_filter(&filter)?;
+    log::info!("日志过滤规则已修改为: {}", filter);
+    Ok(log_settings())
+}
+
+/// 获取最近的日志（附带版本与环境信息），由前端复制到剪贴板
+#[tauri::command]
+pub async fn get_recent_logs(lines: Option<usize>) -> Result<String, String> {
+    let lines = recent_logs(lines.unwrap_or(DEFAULT_RECENT_LINES));
+    let header = format!(
+        "NeuroSpec v{} ({} {})\n日志过滤规则: {}\n配置档案: {}{}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        current_log_filter().spec(),
+        crate::config::active_profile(),
+        if crate::config::is_portable_mode() { "（便携模式）" } else { "" },
+    );
+    Ok(format!("{}\n{}", header, lines.join("\n")))
+}
+
+/// 获取日志目录及其中的日志文件
+#[tauri::command]
+pub async fn get_log_directory() -> Result<LogDirectoryInfo, String> {
+    Ok(LogDirectoryInfo {
+        directory: log_directory().map(|d| d.to_string_lossy().to_string()),
+        files: list_log_files(),
+    })
+}
+
+/// 在系统文件管理器中打开日志目录
+#[tauri::command]
+pub async fn open_log_directory() -> Result<(), String> {
+    let dir = log_directory().ok_or("日志未输出到文件")?;
+    crate::ui::commands::open_external_url(dir.to_string_lossy().to_string()).await
+}
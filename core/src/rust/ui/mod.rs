@@ -7,6 +7,12 @@ pub mod exit;
 pub mod window_events;
 pub mod exit_handler;
 pub mod tray;
+pub mod setup_commands;
+pub mod settings_commands;
+pub mod log_commands;
+pub mod crash_commands;
+pub mod telemetry_commands;
+pub mod graph_commands;
 
 pub use agents_commands::*;
 pub use commands::*;
@@ -0,0 +1,286 @@
+//! 设置导出/导入相关的 Tauri 命令
+//!
+//! 将应用配置（含编排器、搜索排除模式）和嵌入服务配置打包为单个 JSON 文件，
+//! 便于在另一台机器上恢复。密钥默认不导出；导入前先预览与当前配置的冲突，
+//! 由用户逐段选择是否覆盖
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::config::{diff_config, save_config, AppConfig, AppState, ConfigSection};
+use crate::ui::commands::{get_embedding_config_cmd, save_embedding_config_cmd, EmbeddingConfigFrontend};
+
+/// 设置包格式版本
+const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// 设置包
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SettingsBundle {
+    pub format_version: u32,
+    pub app_version: String,
+    pub exported_at: String,
+    /// 是否包含 API Key / Token 等密钥
+    pub includes_secrets: bool,
+    pub config: AppConfig,
+    #[serde(default)]
+    pub embedding: Option<EmbeddingConfigFrontend>,
+}
+
+/// 导出结果
+#[derive(Debug, Serialize)]
+pub struct SettingsExportResult {
+    pub path: String,
+    pub includes_secrets: bool,
+}
+
+/// 单个配置段的冲突
+#[derive(Debug, Serialize)]
+pub struct SectionConflict {
+    pub section: ConfigSection,
+    /// 与当前配置不同的字段
+    pub changed_keys: Vec<String>,
+}
+
+/// 导入预览
+#[derive(Debug, Serialize)]
+pub struct SettingsImportPreview {
+    pub app_version: String,
+    pub exported_at: String,
+    pub includes_secrets: bool,
+    /// 与当前配置存在差异的配置段（无差异的段导入时直接跳过）
+    pub conflicts: Vec<SectionConflict>,
+}
+
+/// 导出全部设置
+#[tauri::command]
+pub async fn export_settings_bundle(
+    path: String,
+    include_secrets: bool,
+    state: State<'_, AppState>,
+) -> Result<SettingsExportResult, String> {
+    let mut config = state
+        .config
+        .lock()
+        .map_err(|e| format!("获取配置失败: {}", e))?
+        .clone();
+    let mut embedding = get_embedding_config_cmd().await?;
+
+    // 安装 ID 属于本机，不随设置迁移
+    config.telemetry_config.install_id = None;
+    config.updater_config.rollout_id = None;
+    if !include_secrets {
+        config.mcp_config.acemcp_token = None;
+        if let Some(ref mut embedding) = embedding {
+            embedding.api_key.clear();
+        }
+    }
+
+    let bundle = SettingsBundle {
+        format_version: BUNDLE_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        includes_secrets: include_secrets,
+        config,
+        embedding,
+    };
+
+    let content = serde_json::to_string_pretty(&bundle).map_err(|e| format!("序列化设置失败: {}", e))?;
+    if let Some(parent) = Path::new(&path).parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    std::fs::write(&path, content).map_err(|e| format!("写入设置包失败: {}", e))?;
+
+    log::info!("设置已导出到: {}", path);
+    Ok(SettingsExportResult { path, includes_secrets: include_secrets })
+}
+
+/// 预览导入：列出设置包与当前配置存在差异的配置段
+#[tauri::command]
+pub async fn preview_settings_import(
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<SettingsImportPreview, String> {
+    let bundle = read_bundle(&path)?;
+    let current = state
+        .config
+        .lock()
+        .map_err(|e| format!("获取配置失败: {}", e))?
+        .clone();
+
+    // 不含密钥的设置包导入时会保留本机密钥，不应算作冲突
+    let mut incoming = bundle.config.clone();
+    if incoming.mcp_config.acemcp_token.is_none() {
+        incoming.mcp_config.acemcp_token = current.mcp_config.acemcp_token.clone();
+    }
+    incoming.telemetry_config.install_id = current.telemetry_config.install_id.clone();
+    incoming.updater_config.rollout_id = current.updater_config.rollout_id.clone();
+
+    let mut conflicts: Vec<SectionConflict> = diff_config(&current, &incoming)
+        .into_iter()
+        .map(|section| SectionConflict {
+            section,
+            changed_keys: changed_keys(section_value(&current, section), section_value(&incoming, section)),
+        })
+        .collect();
+
+    if let Some(ref incoming) = bundle.embedding {
+        let current_embedding = get_embedding_config_cmd().await?;
+        let keys = changed_keys(
+            current_embedding.as_ref().and_then(|c| serde_json::to_value(c).ok()),
+            merge_embedding_secret(incoming, current_embedding.as_ref()).and_then(|c| serde_json::to_value(c).ok()),
+        );
+        if !keys.is_empty() {
+            conflicts.push(SectionConflict { section: ConfigSection::Embedding, changed_keys: keys });
+        }
+    }
+
+    Ok(SettingsImportPreview {
+        app_version: bundle.app_version,
+        exported_at: bundle.exported_at,
+        includes_secrets: bundle.includes_secrets,
+        conflicts,
+    })
+}
+
+/// 导入设置：只覆盖 `sections` 中选择的配置段，其余保持不变
+///
+/// 设置包不含密钥时保留本机已有的密钥
+#[tauri::command]
+pub async fn apply_settings_import(
+    path: String,
+    sections: Vec<ConfigSection>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<ConfigSection>, String> {
+    let bundle = read_bundle(&path)?;
+    let mut applied = Vec::new();
+
+    {
+        let mut config = state
+            .config
+            .lock()
+            .map_err(|e| format!("获取配置失败: {}", e))?;
+
+        for &section in &sections {
+            let incoming = &bundle.config;
+            match section {
+                ConfigSection::Ui => config.ui_config = incoming.ui_config.clone(),
+                ConfigSection::Reply => config.reply_config = incoming.reply_config.clone(),
+                ConfigSection::Mcp => {
+                    let token = config.mcp_config.acemcp_token.take();
+                    config.mcp_config = incoming.mcp_config.clone();
+                    if config.mcp_config.acemcp_token.is_none() {
+                        config.mcp_config.acemcp_token = token;
+                    }
+                }
+                ConfigSection::CustomPrompt => config.custom_prompt_config = incoming.custom_prompt_config.clone(),
+                ConfigSection::Shortcut => config.shortcut_config = incoming.shortcut_config.clone(),
+                ConfigSection::Daemon => config.daemon_config = incoming.daemon_config.clone(),
+                ConfigSection::Search => config.search_config = incoming.search_config.clone(),
+                ConfigSection::Orchestrator => config.orchestrator_config = incoming.orchestrator_config.clone(),
+                ConfigSection::Telemetry => {
+                    let install_id = config.telemetry_config.install_id.take();
+                    config.telemetry_config = incoming.telemetry_config.clone();
+                    config.telemetry_config.install_id = if config.telemetry_config.enabled {
+                        install_id.or_else(|| Some(crate::app::telemetry::new_install_id()))
+                    } else {
+                        None
+                    };
+                }
+                ConfigSection::Updater => {
+                    let rollout_id = config.updater_config.rollout_id.take();
+                    config.updater_config = incoming.updater_config.clone();
+                    config.updater_config.rollout_id = rollout_id;
+                }
+                ConfigSection::Tracing => config.tracing_config = incoming.tracing_config.clone(),
+                // 嵌入配置保存在独立文件，下面单独处理
+                ConfigSection::Embedding => continue,
+                // 项目注册表不在设置包中
+                ConfigSection::Projects => continue,
+            }
+            applied.push(section);
+        }
+    }
+
+    if !applied.is_empty() {
+        // 配置文件写入后由配置监听器广播变更
+        save_config(&state, &app).await.map_err(|e| format!("保存配置失败: {}", e))?;
+    }
+
+    if sections.contains(&ConfigSection::Embedding) {
+        if let Some(ref incoming) = bundle.embedding {
+            let current = get_embedding_config_cmd().await?;
+            if let Some(merged) = merge_embedding_secret(incoming, current.as_ref()) {
+                save_embedding_config_cmd(merged).await?;
+                applied.push(ConfigSection::Embedding);
+            }
+        }
+    }
+
+    log::info!("已从 {} 导入设置: {:?}", path, applied);
+    Ok(applied)
+}
+
+fn read_bundle(path: &str) -> Result<SettingsBundle, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("读取设置包失败: {}", e))?;
+    let bundle: SettingsBundle = serde_json::from_str(&content).map_err(|e| format!("解析设置包失败: {}", e))?;
+
+    if bundle.format_version > BUNDLE_FORMAT_VERSION {
+        return Err(format!(
+            "设置包格式版本 {} 高于当前支持的版本 {}，请升级应用后再导入",
+            bundle.format_version, BUNDLE_FORMAT_VERSION
+        ));
+    }
+    Ok(bundle)
+}
+
+/// 设置包不含 API Key 时沿用本机的 Key；两边都没有 Key 时不导入
+fn merge_embedding_secret(
+    incoming: &EmbeddingConfigFrontend,
+    current: Option<&EmbeddingConfigFrontend>,
+) -> Option<EmbeddingConfigFrontend> {
+    let mut merged = incoming.clone();
+    if merged.api_key.is_empty() {
+        merged.api_key = current.map(|c| c.api_key.clone()).unwrap_or_default();
+    }
+    (!merged.api_key.is_empty()).then_some(merged)
+}
+
+fn section_value(config: &AppConfig, section: ConfigSection) -> Option<serde_json::Value> {
+    let value = match section {
+        ConfigSection::Ui => serde_json::to_value(&config.ui_config),
+        ConfigSection::Reply => serde_json::to_value(&config.reply_config),
+        ConfigSection::Mcp => serde_json::to_value(&config.mcp_config),
+        ConfigSection::CustomPrompt => serde_json::to_value(&config.custom_prompt_config),
+        ConfigSection::Shortcut => serde_json::to_value(&config.shortcut_config),
+        ConfigSection::Daemon => serde_json::to_value(&config.daemon_config),
+        ConfigSection::Search => serde_json::to_value(&config.search_config),
+        ConfigSection::Orchestrator => serde_json::to_value(&config.orchestrator_config),
+        ConfigSection::Telemetry => serde_json::to_value(&config.telemetry_config),
+        ConfigSection::Updater => serde_json::to_value(&config.updater_config),
+        ConfigSection::Tracing => serde_json::to_value(&config.tracing_config),
+        ConfigSection::Embedding | ConfigSection::Projects => return None,
+    };
+    value.ok()
+}
+
+/// 比较两个 JSON 对象的顶层字段，返回值不同的字段名
+fn changed_keys(current: Option<serde_json::Value>, incoming: Option<serde_json::Value>) -> Vec<String> {
+    let empty = serde_json::Map::new();
+    let current = current.unwrap_or_default();
+    let incoming = incoming.unwrap_or_default();
+    let current = current.as_object().unwrap_or(&empty);
+    let incoming = incoming.as_object().unwrap_or(&empty);
+
+    let mut keys: Vec<String> = current
+        .keys()
+        .chain(incoming.keys())
+        .filter(|key| current.get(*key) != incoming.get(*key))
+        .cloned()
+        .collect();
+    keys.sort();
+    keys.dedup();
+    keys
+}
@@ -0,0 +1,280 @@
+//! 首次运行引导相关的 Tauri 命令
+//!
+//! 引导流程：检测搜索引擎 → 配置并校验嵌入服务 → 选择项目 → 后台建立初始索引。
+//! 索引进度通过 `setup-index-progress` 事件推送给前端
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::app::doctor::{check_ctags, check_ripgrep, DoctorCheck};
+use crate::mcp::tools::unified_store::{get_indexed_file_count, is_project_indexed, known_projects, with_global_store};
+use crate::ui::commands::{save_embedding_config_cmd, test_embedding_connection_cmd, EmbeddingConfigFrontend, TestConnectionResult};
+
+/// 引导完成标记文件（位于当前档案的配置目录）
+const SETUP_COMPLETE_FILE: &str = "setup_complete";
+
+/// 索引进度事件名
+const SETUP_PROGRESS_EVENT: &str = "setup-index-progress";
+
+/// 是否有初始索引任务在运行
+static SETUP_INDEXING: AtomicBool = AtomicBool::new(false);
+
+/// 首次运行状态
+#[derive(Debug, Serialize)]
+pub struct FirstRunStatus {
+    /// 是否已完成引导
+    pub completed: bool,
+    pub profile: String,
+    pub portable: bool,
+    pub embedding_configured: bool,
+    pub indexing: bool,
+}
+
+/// 嵌入服务预设
+#[derive(Debug, Serialize)]
+pub struct EmbeddingProviderPreset {
+    pub provider: &'static str,
+    pub name: &'static str,
+    pub base_url: &'static str,
+    pub default_model: &'static str,
+}
+
+/// 候选项目
+#[derive(Debug, Serialize)]
+pub struct ProjectCandidate {
+    pub path: String,
+    pub name: String,
+    pub indexed: bool,
+    pub file_count: Option<usize>,
+}
+
+/// 初始索引进度
+#[derive(Debug, Clone, Serialize)]
+pub struct SetupIndexProgress {
+    pub project: String,
+    /// 当前项目序号（从 1 开始）
+    pub index: usize,
+    pub total: usize,
+    /// started / completed / failed / finished
+    pub stage: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 选择的项目
+#[derive(Debug, Deserialize)]
+pub struct SetupIndexArgs {
+    pub projects: Vec<String>,
+    /// 全量重建（默认增量）
+    #[serde(default)]
+    pub rebuild: bool,
+}
+
+fn setup_marker_path() -> Option<PathBuf> {
+    crate::config::profile_config_dir().map(|d| d.join(SETUP_COMPLETE_FILE))
+}
+
+/// 获取首次运行状态
+#[tauri::command]
+pub async fn get_first_run_status() -> Result<FirstRunStatus, String> {
+    Ok(FirstRunStatus {
+        completed: setup_marker_path().map(|p| p.exists()).unwrap_or(false),
+        profile: crate::config::active_profile().to_string(),
+        portable: crate::config::is_portable_mode(),
+        embedding_configured: crate::neurospec::services::embedding::load_embedding_config()
+            .map(|c| !c.api_key.trim().is_empty())
+            .unwrap_or(false),
+        indexing: SETUP_INDEXING.load(Ordering::SeqCst),
+    })
+}
+
+/// 标记引导完成
+#[tauri::command]
+pub async fn complete_first_run() -> Result<(), String> {
+    let path = setup_marker_path().ok_or("无法获取配置目录")?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建配置目录失败: {}", e))?;
+    }
+    std::fs::write(&path, chrono::Utc::now().to_rfc3339()).map_err(|e| format!("写入引导标记失败: {}", e))?;
+    Ok(())
+}
+
+/// 检测外部搜索引擎（ripgrep / ctags），缺失时附带安装建议
+#[tauri::command]
+pub async fn detect_search_engines() -> Result<Vec<DoctorCheck>, String> {
+    tokio::task::spawn_blocking(|| vec![check_ripgrep(), check_ctags()])
+        .await
+        .map_err(|e| format!("检测失败: {}", e))
+}
+
+/// 支持的嵌入服务预设
+#[tauri::command]
+pub async fn list_embedding_provider_presets() -> Result<Vec<EmbeddingProviderPreset>, String> {
+    Ok(vec![
+        EmbeddingProviderPreset {
+            provider: "siliconflow",
+            name: "SiliconFlow",
+            base_url: "https://api.siliconflow.cn/v1",
+            default_model: "BAAI/bge-m3",
+        },
+        EmbeddingProviderPreset {
+            provider: "jina",
+            name: "Jina AI",
+            base_url: "https://api.jina.ai/v1",
+            default_model: "jina-embeddings-v3",
+        },
+        EmbeddingProviderPreset {
+            provider: "openai",
+            name: "OpenAI",
+            base_url: "https://api.openai.com/v1",
+            default_model: "text-embedding-3-small",
+        },
+        EmbeddingProviderPreset {
+            provider: "dashscope",
+            name: "DashScope",
+            base_url: "https://dashscope.aliyuncs.com/compatible-mode/v1",
+            default_model: "text-embedding-v3",
+        },
+    ])
+}
+
+/// 校验 API Key 后保存嵌入配置（校验失败不保存）
+#[tauri::command]
+pub async fn setup_embedding_provider(config: EmbeddingConfigFrontend) -> Result<TestConnectionResult, String> {
+    if config.api_key.trim().is_empty() {
+        return Ok(TestConnectionResult {
+            success: false,
+            message: "请填写 API Key".to_string(),
+        });
+    }
+
+    let result = test_embedding_connection_cmd(config.clone()).await?;
+    if result.success {
+        // 配置文件写入后由配置监听器触发嵌入服务重新加载
+        save_embedding_config_cmd(config).await?;
+    }
+    Ok(result)
+}
+
+/// 列出可索引的候选项目：当前项目、已知项目和工作目录所在的 Git 仓库
+#[tauri::command]
+pub async fn suggest_setup_projects() -> Result<Vec<ProjectCandidate>, String> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    if let Some(path) = crate::ui::agents_commands::get_cached_project_path() {
+        paths.push(PathBuf::from(path));
+    }
+    paths.extend(known_projects());
+    if let Some(root) = std::env::current_dir().ok().and_then(|cwd| git_root(&cwd)) {
+        paths.push(root);
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    let candidates = paths
+        .into_iter()
+        .filter(|p| p.is_dir())
+        .filter(|p| seen.insert(p.to_string_lossy().to_string()))
+        .map(|path| ProjectCandidate {
+            name: path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.to_string_lossy().to_string()),
+            indexed: is_project_indexed(&path),
+            file_count: get_indexed_file_count(&path),
+            path: path.to_string_lossy().to_string(),
+        })
+        .collect();
+
+    Ok(candidates)
+}
+
+fn git_root(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(|dir| dir.to_path_buf())
+}
+
+/// 在后台依次索引选择的项目，进度通过 `setup-index-progress` 事件推送
+#[tauri::command]
+pub async fn start_setup_indexing(args: SetupIndexArgs, app: AppHandle) -> Result<(), String> {
+    let mut projects = Vec::new();
+    for path in &args.projects {
+        let root = PathBuf::from(path)
+            .canonicalize()
+            .map_err(|e| format!("项目路径无效 '{}': {}", path, e))?;
+        projects.push(root);
+    }
+    if projects.is_empty() {
+        return Err("请至少选择一个项目".to_string());
+    }
+
+    if SETUP_INDEXING.swap(true, Ordering::SeqCst) {
+        return Err("初始索引正在进行中".to_string());
+    }
+
+    let rebuild = args.rebuild;
+    std::thread::spawn(move || {
+        crate::mcp::dispatcher::ensure_search_system_initialized();
+        run_setup_indexing(&app, &projects, rebuild);
+        SETUP_INDEXING.store(false, Ordering::SeqCst);
+    });
+
+    Ok(())
+}
+
+fn run_setup_indexing(app: &AppHandle, projects: &[PathBuf], rebuild: bool) {
+    let total = projects.len();
+    let emit = |progress: SetupIndexProgress| {
+        let _ = app.emit(SETUP_PROGRESS_EVENT, &progress);
+    };
+
+    for (i, root) in projects.iter().enumerate() {
+        if crate::utils::is_shutting_down() {
+            break;
+        }
+        let project = root.to_string_lossy().to_string();
+        let progress = |stage, file_count, symbol_count, error| SetupIndexProgress {
+            project: project.clone(),
+            index: i + 1,
+            total,
+            stage,
+            file_count,
+            symbol_count,
+            error,
+        };
+
+        emit(progress("started", None, None, None));
+
+        let result = crate::app::headless::index_project(root, rebuild).and_then(|file_count| {
+            let stats = with_global_store(|store| store.index_project(root))?;
+            Ok((file_count, stats.total_symbols))
+        });
+
+        match result {
+            Ok((file_count, symbols)) => {
+                emit(progress("completed", Some(file_count), Some(symbols), None));
+            }
+            Err(e) => {
+                crate::log_important!(warn, "Setup indexing failed for {}: {}", project, e);
+                emit(progress("failed", None, None, Some(e.to_string())));
+            }
+        }
+    }
+
+    emit(SetupIndexProgress {
+        project: String::new(),
+        index: total,
+        total,
+        stage: "finished",
+        file_count: None,
+        symbol_count: None,
+        error: None,
+    });
+}
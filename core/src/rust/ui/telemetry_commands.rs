@@ -0,0 +1,80 @@
+//! 匿名使用统计相关的 Tauri 命令
+//!
+//! 设置界面提供开关，并可预览下一次上报的完整内容
+
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+use crate::app::telemetry::{self, TelemetryReport};
+use crate::config::{save_config, AppState};
+
+/// 使用统计状态
+#[derive(Debug, Serialize)]
+pub struct TelemetryStatus {
+    pub enabled: bool,
+    pub install_id: Option<String>,
+    /// 是否配置了上报地址（未配置时数据只保存在本地）
+    pub endpoint_configured: bool,
+    /// 下一次上报的内容
+    pub pending: Option<TelemetryReport>,
+}
+
+/// 获取使用统计状态
+#[tauri::command]
+pub async fn get_telemetry_status(state: State<'_, AppState>) -> Result<TelemetryStatus, String> {
+    let config = state
+        .config
+        .lock()
+        .map_err(|e| format!("获取配置失败: {}", e))?
+        .telemetry_config
+        .clone();
+
+    Ok(TelemetryStatus {
+        enabled: config.enabled,
+        install_id: config.install_id,
+        endpoint_configured: config.endpoint.as_deref().is_some_and(|e| !e.trim().is_empty())
+            || crate::constants::app::TELEMETRY_ENDPOINT.is_some(),
+        pending: tokio::task::spawn_blocking(telemetry::pending_report)
+            .await
+            .map_err(|e| format!("读取统计队列失败: {}", e))?,
+    })
+}
+
+/// 开启或关闭使用统计
+///
+/// 开启时生成新的匿名安装 ID；关闭时清除安装 ID 与本地队列
+#[tauri::command]
+pub async fn set_telemetry_enabled(
+    enabled: bool,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    {
+        let mut config = state
+            .config
+            .lock()
+            .map_err(|e| format!("获取配置失败: {}", e))?;
+        config.telemetry_config.enabled = enabled;
+        config.telemetry_config.install_id = if enabled {
+            config
+                .telemetry_config
+                .install_id
+                .take()
+                .or_else(|| Some(telemetry::new_install_id()))
+        } else {
+            None
+        };
+    }
+
+    save_config(&state, &app).await.map_err(|e| format!("保存配置失败: {}", e))?;
+    telemetry::set_enabled(enabled);
+    log::info!("匿名使用统计已{}", if enabled { "开启" } else { "关闭" });
+    Ok(())
+}
+
+/// 清空本地待上报的统计
+#[tauri::command]
+pub async fn clear_telemetry_queue() -> Result<(), String> {
+    telemetry::clear_queue();
+    Ok(())
+}
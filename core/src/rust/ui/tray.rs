@@ -1,26 +1,89 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
 use tauri::{
-    menu::{Menu, MenuItem},
+    image::Image,
+    menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    AppHandle, Manager,
+    AppHandle, Manager, Wry,
+};
+
+use crate::mcp::tools::unified_store::{
+    is_file_watching_paused, is_project_indexing, set_file_watching_paused, subscribe_store_events,
+    with_global_store, IndexState, StoreEvent,
 };
 
+/// 托盘图标 id
+const TRAY_ID: &str = "main";
+
+/// 索引中的角标颜色（琥珀色）
+const BADGE_INDEXING: [u8; 3] = [0xF5, 0x9E, 0x0B];
+
+/// 索引失败的角标颜色（红色）
+const BADGE_FAILED: [u8; 3] = [0xEF, 0x44, 0x44];
+
 /// Creates the system tray with menu items
 pub fn create_tray(app: &AppHandle) -> tauri::Result<()> {
     let toggle = MenuItem::with_id(app, "toggle", "Show/Hide", true, None::<&str>)?;
+    let status = MenuItem::with_id(app, "index_status", IndexIndicator::Idle.text(), false, None::<&str>)?;
+    let reindex = MenuItem::with_id(app, "reindex", "Reindex Current Project", true, None::<&str>)?;
+    let watch = CheckMenuItem::with_id(
+        app,
+        "toggle_watch",
+        "Watch File Changes",
+        true,
+        !is_file_watching_paused(),
+        None::<&str>,
+    )?;
+    let embedding = CheckMenuItem::with_id(
+        app,
+        "toggle_embedding",
+        "Enable Embedding",
+        true,
+        !crate::neurospec::services::is_embedding_paused(),
+        None::<&str>,
+    )?;
+    let profiles = create_profile_submenu(app)?;
     let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let separator = PredefinedMenuItem::separator(app)?;
+    let separator2 = PredefinedMenuItem::separator(app)?;
 
-    let menu = Menu::with_items(app, &[&toggle, &quit])?;
+    let menu = Menu::with_items(
+        app,
+        &[&toggle, &separator, &status, &reindex, &watch, &embedding, &separator2, &profiles, &quit],
+    )?;
 
-    let mut builder = TrayIconBuilder::new()
+    let watch_item = watch.clone();
+    let embedding_item = embedding.clone();
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
         .menu(&menu)
-        .on_menu_event(|app, event| match event.id.as_ref() {
+        .tooltip(crate::constants::app::NAME)
+        .on_menu_event(move |app, event| match event.id.as_ref() {
             "toggle" => {
                 toggle_window_visibility(app);
             }
+            "reindex" => {
+                reindex_current_project();
+            }
+            "toggle_watch" => {
+                if let Err(e) = set_file_watching_paused(!is_file_watching_paused()) {
+                    log::warn!("切换文件监听失败: {}", e);
+                }
+                let _ = watch_item.set_checked(!is_file_watching_paused());
+            }
+            "toggle_embedding" => {
+                toggle_embedding(embedding_item.clone());
+            }
             "quit" => {
                 app.exit(0);
             }
-            _ => {}
+            id => {
+                if let Some(name) = id.strip_prefix(PROFILE_ITEM_PREFIX) {
+                    if let Err(e) = crate::ui::commands::switch_profile_and_restart(app, name) {
+                        log::warn!("切换配置档案失败: {}", e);
+                    }
+                }
+            }
         })
         .on_tray_icon_event(|tray, event| {
             if let TrayIconEvent::Click {
@@ -41,9 +104,185 @@ pub fn create_tray(app: &AppHandle) -> tauri::Result<()> {
 
     builder.build(app)?;
 
+    spawn_index_status_updater(app.clone(), status);
+
     Ok(())
 }
 
+/// 托盘中显示的索引状态
+enum IndexIndicator {
+    Idle,
+    Indexing { count: usize },
+    Ready { project: String, file_count: usize },
+    Failed { project: String },
+}
+
+impl IndexIndicator {
+    /// 正在索引的项目优先；否则显示最近一次状态变化的项目
+    fn from_states(states: &HashMap<String, IndexState>, last_project: &str) -> Self {
+        let indexing = states
+            .values()
+            .filter(|state| matches!(state, IndexState::Indexing { .. }))
+            .count();
+        if indexing > 0 {
+            return Self::Indexing { count: indexing };
+        }
+
+        let project = project_display_name(last_project);
+        match states.get(last_project) {
+            Some(IndexState::Ready { file_count, .. }) => Self::Ready { project, file_count: *file_count },
+            Some(IndexState::Corrupted { .. }) => Self::Failed { project },
+            _ => Self::Idle,
+        }
+    }
+
+    fn text(&self) -> String {
+        match self {
+            Self::Idle => "Index: Idle".to_string(),
+            Self::Indexing { count: 1 } => "Index: Indexing…".to_string(),
+            Self::Indexing { count } => format!("Index: Indexing {} projects…", count),
+            Self::Ready { project, file_count } => format!("Index: {} ready ({} files)", project, file_count),
+            Self::Failed { project } => format!("Index: {} failed", project),
+        }
+    }
+
+    fn badge(&self) -> Option<[u8; 3]> {
+        match self {
+            Self::Indexing { .. } => Some(BADGE_INDEXING),
+            Self::Failed { .. } => Some(BADGE_FAILED),
+            Self::Idle | Self::Ready { .. } => None,
+        }
+    }
+}
+
+fn project_display_name(project_root: &str) -> String {
+    Path::new(project_root)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| project_root.to_string())
+}
+
+/// 订阅索引状态变化，更新状态菜单项、提示文字和图标角标
+fn spawn_index_status_updater(app: AppHandle, status: MenuItem<Wry>) {
+    use tokio::sync::broadcast::error::RecvError;
+
+    let mut receiver = subscribe_store_events();
+    tauri::async_runtime::spawn(async move {
+        let mut states: HashMap<String, IndexState> = HashMap::new();
+        loop {
+            let (project_root, state) = match receiver.recv().await {
+                Ok(StoreEvent::IndexStateChanged { project_root, state }) => (project_root, state),
+                Ok(_) => continue,
+                Err(RecvError::Lagged(skipped)) => {
+                    crate::log_debug!("Tray status consumer lagged, skipped {} events", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+
+            states.insert(project_root.clone(), state);
+            let indicator = IndexIndicator::from_states(&states, &project_root);
+            let text = indicator.text();
+            let _ = status.set_text(&text);
+
+            if let Some(tray) = app.tray_by_id(TRAY_ID) {
+                let _ = tray.set_tooltip(Some(format!("{} - {}", crate::constants::app::NAME, text)));
+                if let Some(icon) = app.default_window_icon() {
+                    let icon = match indicator.badge() {
+                        Some(color) => badged_icon(icon, color),
+                        None => icon.clone().to_owned(),
+                    };
+                    let _ = tray.set_icon(Some(icon));
+                }
+            }
+        }
+    });
+}
+
+/// 在图标右下角绘制圆形角标
+fn badged_icon(base: &Image<'_>, color: [u8; 3]) -> Image<'static> {
+    let (width, height) = (base.width() as i64, base.height() as i64);
+    let mut rgba = base.rgba().to_vec();
+    let radius = width.min(height) / 4;
+    let (cx, cy) = (width - radius - 1, height - radius - 1);
+
+    for y in (cy - radius).max(0)..=(cy + radius).min(height - 1) {
+        for x in (cx - radius).max(0)..=(cx + radius).min(width - 1) {
+            let (dx, dy) = (x - cx, y - cy);
+            if dx * dx + dy * dy <= radius * radius {
+                let offset = ((y * width + x) * 4) as usize;
+                rgba[offset..offset + 4].copy_from_slice(&[color[0], color[1], color[2], 0xFF]);
+            }
+        }
+    }
+
+    Image::new_owned(rgba, base.width(), base.height())
+}
+
+/// 在后台线程中全量重建当前项目的索引
+fn reindex_current_project() {
+    let Some(root) = crate::ui::agents_commands::get_cached_project_path().map(PathBuf::from) else {
+        log::warn!("没有当前项目，无法重建索引");
+        return;
+    };
+    if is_project_indexing(&root) {
+        log::info!("项目正在索引中，跳过: {}", root.display());
+        return;
+    }
+
+    std::thread::spawn(move || {
+        crate::mcp::dispatcher::ensure_search_system_initialized();
+        match crate::app::headless::index_project(&root, true) {
+            Ok(count) => log::info!("已重建索引: {}（{} 个文件）", root.display(), count),
+            Err(e) => {
+                log::warn!("重建索引失败: {}", e);
+                return;
+            }
+        }
+        if let Err(e) = with_global_store(|store| store.index_project(&root)) {
+            log::warn!("更新符号存储失败: {}", e);
+        }
+    });
+}
+
+/// 运行时启用或停用嵌入服务，并按结果更新菜单勾选状态
+fn toggle_embedding(item: CheckMenuItem<Wry>) {
+    let enable = crate::neurospec::services::is_embedding_paused();
+    tauri::async_runtime::spawn(async move {
+        match crate::neurospec::services::set_embedding_enabled(enable).await {
+            Ok(available) if enable && !available => log::warn!("嵌入服务已启用，但配置不完整，暂不可用"),
+            Ok(_) => {}
+            Err(e) => log::warn!("切换嵌入服务失败: {}", e),
+        }
+        let _ = item.set_checked(!crate::neurospec::services::is_embedding_paused());
+    });
+}
+
+/// 配置档案菜单项 id 前缀
+const PROFILE_ITEM_PREFIX: &str = "profile:";
+
+/// Creates the profile submenu, checking the active profile
+fn create_profile_submenu(app: &AppHandle) -> tauri::Result<Submenu<Wry>> {
+    let active = crate::config::active_profile();
+    let items = crate::config::list_profiles()
+        .into_iter()
+        .map(|name| {
+            CheckMenuItem::with_id(
+                app,
+                format!("{}{}", PROFILE_ITEM_PREFIX, name),
+                &name,
+                true,
+                name == active,
+                None::<&str>,
+            )
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+
+    let refs: Vec<&dyn IsMenuItem<Wry>> =
+        items.iter().map(|item| item as &dyn IsMenuItem<Wry>).collect();
+    Submenu::with_items(app, format!("Profile: {}", active), true, &refs)
+}
+
 /// Toggles the visibility of the main window
 fn toggle_window_visibility(app: &AppHandle) {
     if let Some(window) = app.get_webview_window("main") {
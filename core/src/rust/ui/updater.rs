@@ -1,6 +1,26 @@
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager, State};
 use serde::{Deserialize, Serialize};
-use std::{fs, io::Write, path::PathBuf, process::Command};
+use std::{cmp::Ordering, collections::HashMap, fs, io::{Read, Write}, path::{Path, PathBuf}, process::Command};
+
+use crate::config::{save_config, AppState, UpdateChannel};
+
+/// GitHub releases API
+const RELEASES_API: &str = "https://api.github.com/repos/neurospec/neurospec/releases";
+
+/// 测试版通道检查的最近发布数
+const BETA_RELEASES_PER_PAGE: u32 = 20;
+
+/// 获取更新日志时检查的最近发布数
+const CHANGELOG_RELEASES_PER_PAGE: u32 = 50;
+
+/// 增量更新包文件名标记：`NeuroSpec-<新版本>-<平台>.delta-<旧版本>.zst`
+const DELTA_ASSET_MARKER: &str = ".delta-";
+
+/// 更新清单：各平台可执行文件的 SHA-256，用于校验增量更新生成的文件
+const UPDATE_MANIFEST_ASSET: &str = "update-manifest.json";
+
+/// 更新清单的 Ed25519 签名（Base64）
+const UPDATE_MANIFEST_SIGNATURE_ASSET: &str = "update-manifest.json.sig";
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UpdateInfo {
@@ -9,6 +29,25 @@ pub struct UpdateInfo {
     pub latest_version: String,
     pub release_notes: String,
     pub download_url: String,
+    /// 检查所用的更新通道
+    pub channel: UpdateChannel,
+    /// 最新版本是否为预发布版本
+    pub prerelease: bool,
+    /// 从当前版本出发的增量更新包（发布中没有时为 None）
+    pub delta_url: Option<String>,
+    /// 分阶段发布比例（发布说明含 `<!-- rollout: N% -->` 标记时）
+    pub rollout_percentage: Option<u8>,
+    /// 更新清单及其签名（发布中没有时无法使用增量更新）
+    pub manifest_url: Option<String>,
+    pub manifest_signature_url: Option<String>,
+}
+
+/// 更新清单内容
+#[derive(Debug, Deserialize)]
+struct UpdateManifest {
+    version: String,
+    /// 平台标识 -> 可执行文件 SHA-256（十六进制）
+    binaries: HashMap<String, String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -19,17 +58,149 @@ pub struct UpdateProgress {
     pub percentage: f64,
 }
 
+/// 单个版本的更新日志
+#[derive(Debug, Serialize, Clone)]
+pub struct ReleaseNote {
+    pub version: String,
+    pub name: String,
+    pub published_at: Option<String>,
+    pub prerelease: bool,
+    pub notes: String,
+    pub url: String,
+}
+
 /// 检查是否有可用更新
 #[tauri::command]
 pub async fn check_for_updates(app: AppHandle) -> Result<UpdateInfo, String> {
-    log::info!("🔍 开始检查更新");
+    let channel = current_channel(&app);
+    log::info!("🔍 开始检查更新（通道: {:?}）", channel);
+
+    let release = fetch_latest_release(channel).await?;
+
+    log::info!("📋 成功获取 release 数据");
+
+    let current_version = app.package_info().version.to_string();
+    log::info!("📦 当前版本: {}", current_version);
     
+    // 提取最新版本号，处理中文tag
+    let tag_name = release["tag_name"]
+        .as_str()
+        .unwrap_or("")
+        .to_string();
+    
+    log::info!("🏷️ GitHub tag: {}", tag_name);
+    
+    let latest_version = parse_tag_version(&tag_name);
+
+    log::info!("🆕 解析后的最新版本: {}", latest_version);
+
+    if latest_version.is_empty() {
+        let error_msg = "无法解析版本号".to_string();
+        log::error!("❌ {}", error_msg);
+        return Err(error_msg);
+    }
+
+    // 比较版本号
+    let mut has_update = compare_versions(&latest_version, &current_version);
+    log::info!("🔄 版本比较结果 - 有更新: {}", has_update);
+
+    let release_notes = release["body"].as_str().unwrap_or("").to_string();
+
+    // 分阶段发布：正式版通道中不在本轮范围内的安装暂不提示更新，测试版通道始终提示
+    let rollout_percentage = rollout_percentage(&release_notes);
+    if let (Some(percentage), UpdateChannel::Stable, true) = (rollout_percentage, channel, has_update) {
+        let bucket = rollout_bucket(&rollout_id(&app).await);
+        if bucket >= percentage {
+            log::info!("⏳ 新版本正在分阶段发布（{}%），本机分桶 {} 暂不更新", percentage, bucket);
+            has_update = false;
+        }
+    }
+
+    // 获取实际的下载URL（从assets中找到对应平台的文件）
+    let download_url = get_platform_download_url(&release)?;
+    let delta_url = get_platform_delta_url(&release, &current_version);
+
+    let update_info = UpdateInfo {
+        available: has_update,
+        current_version,
+        latest_version,
+        release_notes,
+        download_url,
+        channel,
+        prerelease: release["prerelease"].as_bool().unwrap_or(false),
+        delta_url,
+        rollout_percentage,
+        manifest_url: get_asset_url(&release, UPDATE_MANIFEST_ASSET),
+        manifest_signature_url: get_asset_url(&release, UPDATE_MANIFEST_SIGNATURE_ASSET),
+    };
+
+    log::info!("✅ 更新检查完成: {:?}", update_info);
+    Ok(update_info)
+}
+
+/// 获取当前版本之后各版本的更新日志（按当前通道过滤，新版本在前），在应用更新前展示
+#[tauri::command]
+pub async fn get_changelog_since_current(app: AppHandle) -> Result<Vec<ReleaseNote>, String> {
+    let channel = current_channel(&app);
+    let current_version = app.package_info().version.to_string();
+
+    let releases = fetch_github_json(&format!("{}?per_page={}", RELEASES_API, CHANGELOG_RELEASES_PER_PAGE)).await?;
+    let mut notes: Vec<ReleaseNote> = releases
+        .as_array()
+        .map(|releases| {
+            releases
+                .iter()
+                .filter_map(release_note)
+                .filter(|note| channel == UpdateChannel::Beta || !note.prerelease)
+                .filter(|note| compare_versions(&note.version, &current_version))
+                .collect()
+        })
+        .unwrap_or_default();
+    notes.sort_by(|a, b| version_ordering(&b.version, &a.version));
+
+    log::info!("📋 当前版本 {} 之后共有 {} 个版本", current_version, notes.len());
+    Ok(notes)
+}
+
+/// 获取更新通道
+#[tauri::command]
+pub async fn get_update_channel(state: State<'_, AppState>) -> Result<UpdateChannel, String> {
+    let config = state.config.lock().map_err(|e| format!("获取配置失败: {}", e))?;
+    Ok(config.updater_config.channel)
+}
+
+/// 切换更新通道（下次检查更新时生效）
+#[tauri::command]
+pub async fn set_update_channel(
+    channel: UpdateChannel,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    {
+        let mut config = state.config.lock().map_err(|e| format!("获取配置失败: {}", e))?;
+        config.updater_config.channel = channel;
+    }
+    save_config(&state, &app).await.map_err(|e| format!("保存配置失败: {}", e))?;
+    log::info!("更新通道已切换为: {:?}", channel);
+    Ok(())
+}
+
+fn current_channel(app: &AppHandle) -> UpdateChannel {
+    app.state::<AppState>()
+        .config
+        .lock()
+        .map(|config| config.updater_config.channel)
+        .unwrap_or_default()
+}
+
+/// 请求 GitHub API 并解析 JSON
+async fn fetch_github_json(url: &str) -> Result<serde_json::Value, String> {
     // 由于Tauri更新器无法处理中文tag，这里直接使用GitHub API检查
     let client = reqwest::Client::new();
-    log::info!("📡 发送 GitHub API 请求");
+    log::info!("📡 发送 GitHub API 请求: {}", url);
     
     let response = client
-        .get("https://api.github.com/repos/neurospec/neurospec/releases/latest")
+        .get(url)
         .header("User-Agent", "neurospec-app/1.0")
         .header("Accept", "application/vnd.github.v3+json")
         .timeout(std::time::Duration::from_secs(30))
@@ -55,80 +226,167 @@ pub async fn check_for_updates(app: AppHandle) -> Result<UpdateInfo, String> {
         return Err(error_msg);
     }
 
-    let release: serde_json::Value = response
+    response
         .json()
         .await
         .map_err(|e| {
             log::error!("❌ 解析响应失败: {}", e);
             format!("解析响应失败: {}", e)
-        })?;
-
-    log::info!("📋 成功获取 release 数据");
-
-    let current_version = app.package_info().version.to_string();
-    log::info!("📦 当前版本: {}", current_version);
-    
-    // 提取最新版本号，处理中文tag
-    let tag_name = release["tag_name"]
-        .as_str()
-        .unwrap_or("")
-        .to_string();
-    
-    log::info!("🏷️ GitHub tag: {}", tag_name);
-    
-    // 移除前缀v和中文字符，只保留数字和点
-    let latest_version = tag_name
-        .replace("v", "")
-        .chars()
-        .filter(|c| c.is_numeric() || *c == '.')
-        .collect::<String>();
-
-    log::info!("🆕 解析后的最新版本: {}", latest_version);
+        })
+}
 
-    if latest_version.is_empty() {
-        let error_msg = "无法解析版本号".to_string();
-        log::error!("❌ {}", error_msg);
-        return Err(error_msg);
+/// 获取通道内的最新发布
+///
+/// 正式版直接取 latest（GitHub 不含预发布版本）；测试版在最近的发布中取版本号最高的
+async fn fetch_latest_release(channel: UpdateChannel) -> Result<serde_json::Value, String> {
+    match channel {
+        UpdateChannel::Stable => fetch_github_json(&format!("{}/latest", RELEASES_API)).await,
+        UpdateChannel::Beta => {
+            let releases = fetch_github_json(&format!("{}?per_page={}", RELEASES_API, BETA_RELEASES_PER_PAGE)).await?;
+            releases
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|release| !release["draft"].as_bool().unwrap_or(false))
+                .filter(|release| !parse_tag_version(release["tag_name"].as_str().unwrap_or("")).is_empty())
+                .max_by(|a, b| {
+                    version_ordering(
+                        &parse_tag_version(a["tag_name"].as_str().unwrap_or("")),
+                        &parse_tag_version(b["tag_name"].as_str().unwrap_or("")),
+                    )
+                })
+                .cloned()
+                .ok_or_else(|| "没有找到可用的发布版本".to_string())
+        }
     }
+}
 
-    // 比较版本号
-    let has_update = compare_versions(&latest_version, &current_version);
-    log::info!("🔄 版本比较结果 - 有更新: {}", has_update);
+fn release_note(release: &serde_json::Value) -> Option<ReleaseNote> {
+    if release["draft"].as_bool().unwrap_or(false) {
+        return None;
+    }
+    let tag_name = release["tag_name"].as_str().unwrap_or("");
+    let version = parse_tag_version(tag_name);
+    if version.is_empty() {
+        return None;
+    }
 
-    // 获取实际的下载URL（从assets中找到对应平台的文件）
-    let download_url = get_platform_download_url(&release)?;
+    Some(ReleaseNote {
+        version,
+        name: release["name"].as_str().filter(|n| !n.is_empty()).unwrap_or(tag_name).to_string(),
+        published_at: release["published_at"].as_str().map(|s| s.to_string()),
+        prerelease: release["prerelease"].as_bool().unwrap_or(false),
+        notes: release["body"].as_str().unwrap_or("").to_string(),
+        url: release["html_url"].as_str().unwrap_or("").to_string(),
+    })
+}
 
-    let update_info = UpdateInfo {
-        available: has_update,
-        current_version,
-        latest_version,
-        release_notes: release["body"].as_str().unwrap_or("").to_string(),
-        download_url,
+/// 从 tag 中提取版本号（忽略 v 前缀和中文字符），保留预发布后缀，如 `1.3.0-beta.2`
+fn parse_tag_version(tag: &str) -> String {
+    let Some(start) = tag.find(|c: char| c.is_ascii_digit()) else {
+        return String::new();
     };
-
-    log::info!("✅ 更新检查完成: {:?}", update_info);
-    Ok(update_info)
+    let rest = &tag[start..];
+    let core_len = rest
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(rest.len());
+    let core = rest[..core_len].trim_end_matches('.');
+    let pre: String = rest[core_len..]
+        .strip_prefix('-')
+        .map(|p| p.chars().take_while(|c| c.is_ascii_alphanumeric() || *c == '.').collect())
+        .unwrap_or_default();
+
+    match pre.trim_end_matches('.') {
+        "" => core.to_string(),
+        pre => format!("{}-{}", core, pre),
+    }
 }
 
-/// 简单的版本比较函数
+/// 版本比较：v1 是否比 v2 新
 fn compare_versions(v1: &str, v2: &str) -> bool {
-    let v1_parts: Vec<u32> = v1.split('.').filter_map(|s| s.parse().ok()).collect();
-    let v2_parts: Vec<u32> = v2.split('.').filter_map(|s| s.parse().ok()).collect();
-    
+    version_ordering(v1, v2) == Ordering::Greater
+}
+
+/// 按数字段逐位比较；数字段相同时正式版高于预发布版本
+fn version_ordering(v1: &str, v2: &str) -> Ordering {
+    fn split(version: &str) -> (Vec<u32>, Option<&str>) {
+        let (core, pre) = match version.split_once('-') {
+            Some((core, pre)) => (core, Some(pre).filter(|p| !p.is_empty())),
+            None => (version, None),
+        };
+        (core.split('.').filter_map(|s| s.parse().ok()).collect(), pre)
+    }
+
+    let (v1_parts, v1_pre) = split(v1);
+    let (v2_parts, v2_pre) = split(v2);
     let max_len = v1_parts.len().max(v2_parts.len());
     
     for i in 0..max_len {
         let v1_part = v1_parts.get(i).unwrap_or(&0);
         let v2_part = v2_parts.get(i).unwrap_or(&0);
         
-        if v1_part > v2_part {
-            return true;
-        } else if v1_part < v2_part {
-            return false;
+        match v1_part.cmp(v2_part) {
+            Ordering::Equal => {}
+            ordering => return ordering,
         }
     }
     
-    false
+    match (v1_pre, v2_pre) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => compare_prerelease(a, b),
+    }
+}
+
+/// 预发布标识逐段比较：数字段按数值，其余按字典序，数字段低于字母段
+fn compare_prerelease(a: &str, b: &str) -> Ordering {
+    for (x, y) in a.split('.').zip(b.split('.')) {
+        let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(m), Ok(n)) => m.cmp(&n),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => x.cmp(y),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.split('.').count().cmp(&b.split('.').count())
+}
+
+/// 解析发布说明中的分阶段发布标记 `<!-- rollout: N% -->`
+fn rollout_percentage(notes: &str) -> Option<u8> {
+    let re = regex::Regex::new(r"(?i)<!--\s*rollout:\s*(\d{1,3})\s*%\s*-->").ok()?;
+    let percentage: u8 = re.captures(notes)?.get(1)?.as_str().parse().ok()?;
+    Some(percentage.min(100))
+}
+
+/// 本机的分阶段发布 ID，首次使用时随机生成并保存到配置
+async fn rollout_id(app: &AppHandle) -> String {
+    let state = app.state::<AppState>();
+    let id = {
+        let Ok(mut config) = state.config.lock() else {
+            return String::new();
+        };
+        if let Some(ref id) = config.updater_config.rollout_id {
+            return id.clone();
+        }
+        let id = uuid::Uuid::new_v4().to_string();
+        config.updater_config.rollout_id = Some(id.clone());
+        id
+    };
+    if let Err(e) = save_config(&state, app).await {
+        log::warn!("⚠️ 保存分阶段发布 ID 失败: {}", e);
+    }
+    id
+}
+
+/// 分阶段发布分桶（0-99，由本机的分阶段发布 ID 计算，固定且不上报）
+fn rollout_bucket(rollout_id: &str) -> u8 {
+    let digest = ring::digest::digest(&ring::digest::SHA256, rollout_id.as_bytes());
+    let bytes = digest.as_ref();
+    (u16::from_be_bytes([bytes[0], bytes[1]]) % 100) as u8
 }
 
 /// 下载并安装更新
@@ -197,28 +455,7 @@ fn get_platform_download_url(release: &serde_json::Value) -> Result<String, Stri
 
     log::info!("📦 Release assets 总数: {}", assets.len());
 
-    // 确定当前平台（匹配实际的文件名格式）
-    let platform = if cfg!(target_os = "macos") {
-        if cfg!(target_arch = "aarch64") {
-            "macos-aarch64"
-        } else {
-            "macos-x86_64"
-        }
-    } else if cfg!(target_os = "windows") {
-        if cfg!(target_arch = "aarch64") {
-            "windows-aarch64"
-        } else {
-            "windows-x86_64"
-        }
-    } else if cfg!(target_os = "linux") {
-        if cfg!(target_arch = "aarch64") {
-            "linux-aarch64"
-        } else {
-            "linux-x86_64"
-        }
-    } else {
-        return Err("不支持的平台".to_string());
-    };
+    let platform = platform_asset_name().ok_or_else(|| "不支持的平台".to_string())?;
 
     log::info!("🔍 查找平台 {} 的下载文件", platform);
 
@@ -233,7 +470,8 @@ fn get_platform_download_url(release: &serde_json::Value) -> Result<String, Stri
     for asset in assets {
         if let Some(name) = asset["name"].as_str() {
             log::info!("🔍 检查文件: {} (是否包含 '{}')", name, platform);
-            if name.contains(platform) {
+            // 增量包只能配合指定的旧版本使用，不作为完整安装包
+            if name.contains(platform) && !name.contains(DELTA_ASSET_MARKER) {
                 if let Some(download_url) = asset["browser_download_url"].as_str() {
                     log::info!("✅ 找到匹配的下载文件: {}", name);
                     log::info!("🔗 下载URL: {}", download_url);
@@ -249,11 +487,69 @@ fn get_platform_download_url(release: &serde_json::Value) -> Result<String, Stri
     Ok(release["html_url"].as_str().unwrap_or("").to_string())
 }
 
+/// 当前平台在发布文件名中的标识（匹配实际的文件名格式）
+fn platform_asset_name() -> Option<&'static str> {
+    if cfg!(target_os = "macos") {
+        if cfg!(target_arch = "aarch64") {
+            Some("macos-aarch64")
+        } else {
+            Some("macos-x86_64")
+        }
+    } else if cfg!(target_os = "windows") {
+        if cfg!(target_arch = "aarch64") {
+            Some("windows-aarch64")
+        } else {
+            Some("windows-x86_64")
+        }
+    } else if cfg!(target_os = "linux") {
+        if cfg!(target_arch = "aarch64") {
+            Some("linux-aarch64")
+        } else {
+            Some("linux-x86_64")
+        }
+    } else {
+        None
+    }
+}
+
+/// 查找从当前版本出发的增量更新包
+fn get_platform_delta_url(release: &serde_json::Value, current_version: &str) -> Option<String> {
+    let platform = platform_asset_name()?;
+    let suffix = format!("{}{}.zst", DELTA_ASSET_MARKER, current_version);
+
+    release["assets"].as_array()?.iter().find_map(|asset| {
+        let name = asset["name"].as_str()?;
+        if name.contains(platform) && name.ends_with(&suffix) {
+            log::info!("✅ 找到增量更新包: {}", name);
+            asset["browser_download_url"].as_str().map(|url| url.to_string())
+        } else {
+            None
+        }
+    })
+}
+
+/// 查找发布中指定名称的文件
+fn get_asset_url(release: &serde_json::Value, name: &str) -> Option<String> {
+    release["assets"].as_array()?.iter().find_map(|asset| {
+        (asset["name"].as_str()? == name)
+            .then(|| asset["browser_download_url"].as_str().map(|url| url.to_string()))
+            .flatten()
+    })
+}
+
 /// 实际的下载和安装实现
 async fn download_and_install_update_impl(app: &AppHandle, update_info: &UpdateInfo) -> Result<(), String> {
     log::info!("🚀 开始自动更新实现");
     log::info!("📋 更新信息: {:?}", update_info);
 
+    // 优先尝试增量更新，失败时回退到完整安装包
+    if let Some(ref delta_url) = update_info.delta_url {
+        match install_delta_update(app, update_info, delta_url).await {
+            Ok(()) => return Ok(()),
+            Err(e) => log::warn!("⚠️ 增量更新失败，改为下载完整安装包: {}", e),
+        }
+    }
+
     // 如果下载URL是GitHub页面而不是直接下载链接，引导用户手动下载
     if update_info.download_url.contains("/releases/tag/") {
         log::info!("🔗 下载URL是release页面，需要手动下载: {}", update_info.download_url);
@@ -264,9 +560,7 @@ async fn download_and_install_update_impl(app: &AppHandle, update_info: &UpdateI
     log::info!("📥 开始下载文件: {}", update_info.download_url);
 
     // 创建临时目录
-    let temp_dir = std::env::temp_dir().join("neurospec_update");
-    fs::create_dir_all(&temp_dir)
-        .map_err(|e| format!("创建临时目录失败: {}", e))?;
+    let temp_dir = update_temp_dir()?;
 
     // 确定文件名
     let file_name = update_info.download_url
@@ -277,10 +571,32 @@ async fn download_and_install_update_impl(app: &AppHandle, update_info: &UpdateI
 
     let file_path = temp_dir.join(&file_name);
 
-    // 下载文件
+    download_with_progress(app, &update_info.download_url, &file_path).await?;
+
+    log::info!("✅ 文件下载完成: {}", file_path.display());
+
+    // 开始安装
+    let _ = app.emit("update_install_started", ());
+
+    // 根据平台执行不同的安装逻辑
+    install_update(&file_path).await?;
+
+    Ok(())
+}
+
+/// 更新文件的临时目录
+fn update_temp_dir() -> Result<PathBuf, String> {
+    let temp_dir = std::env::temp_dir().join("neurospec_update");
+    fs::create_dir_all(&temp_dir)
+        .map_err(|e| format!("创建临时目录失败: {}", e))?;
+    Ok(temp_dir)
+}
+
+/// 下载文件并报告进度
+async fn download_with_progress(app: &AppHandle, url: &str, file_path: &PathBuf) -> Result<(), String> {
     let client = reqwest::Client::new();
     let mut response = client
-        .get(&update_info.download_url)
+        .get(url)
         .send()
         .await
         .map_err(|e| format!("下载请求失败: {}", e))?;
@@ -291,7 +607,7 @@ async fn download_and_install_update_impl(app: &AppHandle, update_info: &UpdateI
 
     let total_size = response.content_length();
     let mut downloaded = 0u64;
-    let mut file = fs::File::create(&file_path)
+    let mut file = fs::File::create(file_path)
         .map_err(|e| format!("创建文件失败: {}", e))?;
 
     // 下载并报告进度
@@ -319,14 +635,154 @@ async fn download_and_install_update_impl(app: &AppHandle, update_info: &UpdateI
         let _ = app.emit("update_download_progress", &progress);
     }
 
-    log::info!("✅ 文件下载完成: {}", file_path.display());
+    Ok(())
+}
+
+/// 下载增量包并以当前可执行文件为基础生成新版本，校验通过后才替换
+async fn install_delta_update(app: &AppHandle, update_info: &UpdateInfo, delta_url: &str) -> Result<(), String> {
+    // 先取得签名清单中的期望哈希，无法校验时不使用增量更新
+    let expected_hash = fetch_expected_binary_hash(update_info).await?;
+
+    log::info!("📥 开始下载增量更新包: {}", delta_url);
+
+    let temp_dir = update_temp_dir()?;
+    let patch_name = delta_url.split('/').last().unwrap_or("update.delta.zst");
+    let patch_path = temp_dir.join(patch_name);
+    download_with_progress(app, delta_url, &patch_path).await?;
 
-    // 开始安装
     let _ = app.emit("update_install_started", ());
 
-    // 根据平台执行不同的安装逻辑
-    install_update(&file_path).await?;
+    let current_exe = std::env::current_exe()
+        .map_err(|e| format!("无法获取当前可执行文件路径: {}", e))?;
+    let exe_name = current_exe
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "neurospec".to_string());
+    let new_exe = temp_dir.join(format!("{}.new", exe_name));
+
+    apply_delta_patch(&current_exe, &patch_path, &new_exe)?;
+
+    let actual_hash = sha256_file(&new_exe)?;
+    if !actual_hash.eq_ignore_ascii_case(&expected_hash) {
+        let _ = fs::remove_file(&new_exe);
+        let _ = fs::remove_file(&patch_path);
+        return Err(format!("增量更新生成的文件校验失败（期望 {}，实际 {}）", expected_hash, actual_hash));
+    }
+    log::info!("🔒 新版本可执行文件校验通过");
+
+    replace_executable(&current_exe, &new_exe)?;
 
+    let _ = fs::remove_file(&patch_path);
+    log::info!("✅ 增量更新安装完成！");
+    Ok(())
+}
+
+/// 下载并验证更新清单，返回当前平台新版本可执行文件的 SHA-256
+async fn fetch_expected_binary_hash(update_info: &UpdateInfo) -> Result<String, String> {
+    let public_key = crate::constants::app::UPDATE_PUBLIC_KEY
+        .ok_or_else(|| "未配置更新签名公钥".to_string())?;
+    let manifest_url = update_info.manifest_url.as_deref()
+        .ok_or_else(|| "发布中没有更新清单".to_string())?;
+    let signature_url = update_info.manifest_signature_url.as_deref()
+        .ok_or_else(|| "发布中没有更新清单签名".to_string())?;
+    let platform = platform_asset_name().ok_or_else(|| "不支持的平台".to_string())?;
+
+    let manifest = fetch_bytes(manifest_url).await?;
+    let signature = fetch_bytes(signature_url).await?;
+    let manifest = verify_manifest(&manifest, &signature, public_key)?;
+    manifest_binary_hash(&manifest, &update_info.latest_version, platform)
+}
+
+/// 下载小文件到内存
+async fn fetch_bytes(url: &str) -> Result<Vec<u8>, String> {
+    let response = reqwest::Client::new()
+        .get(url)
+        .header("User-Agent", "neurospec-app/1.0")
+        .timeout(std::time::Duration::from_secs(30))
+        .send()
+        .await
+        .map_err(|e| format!("下载请求失败: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("下载失败: HTTP {}", response.status()));
+    }
+
+    response
+        .bytes()
+        .await
+        .map(|bytes| bytes.to_vec())
+        .map_err(|e| format!("下载数据失败: {}", e))
+}
+
+/// 用 Ed25519 公钥（Base64）验证清单签名（Base64），通过后解析清单
+fn verify_manifest(manifest: &[u8], signature: &[u8], public_key: &str) -> Result<UpdateManifest, String> {
+    use base64::Engine;
+    let engine = base64::engine::general_purpose::STANDARD;
+
+    let public_key = engine.decode(public_key.trim())
+        .map_err(|e| format!("更新签名公钥格式错误: {}", e))?;
+    let signature = engine.decode(String::from_utf8_lossy(signature).trim())
+        .map_err(|e| format!("更新清单签名格式错误: {}", e))?;
+
+    ring::signature::UnparsedPublicKey::new(&ring::signature::ED25519, &public_key)
+        .verify(manifest, &signature)
+        .map_err(|_| "更新清单签名无效".to_string())?;
+
+    serde_json::from_slice(manifest).map_err(|e| format!("解析更新清单失败: {}", e))
+}
+
+/// 清单中指定版本、平台的可执行文件哈希（清单版本必须与待安装版本一致）
+fn manifest_binary_hash(manifest: &UpdateManifest, version: &str, platform: &str) -> Result<String, String> {
+    if version_ordering(&parse_tag_version(&manifest.version), version) != Ordering::Equal {
+        return Err(format!("更新清单版本 {} 与待安装版本 {} 不一致", manifest.version, version));
+    }
+    manifest
+        .binaries
+        .get(platform)
+        .cloned()
+        .ok_or_else(|| format!("更新清单中没有平台 {} 的哈希", platform))
+}
+
+/// 计算文件的 SHA-256（十六进制）
+fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = fs::File::open(path)
+        .map_err(|e| format!("打开文件失败: {}", e))?;
+    let mut context = ring::digest::Context::new(&ring::digest::SHA256);
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)
+            .map_err(|e| format!("读取文件失败: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        context.update(&buffer[..read]);
+    }
+    Ok(hex::encode(context.finish().as_ref()))
+}
+
+/// 以旧文件为字典解压 `zstd --patch-from` 生成的增量包
+///
+/// zstd 帧带内容校验，基础文件与增量包不匹配时解压失败
+fn apply_delta_patch(base: &PathBuf, patch: &PathBuf, output: &PathBuf) -> Result<(), String> {
+    // 增量包的窗口需覆盖整个旧文件
+    const WINDOW_LOG_MAX: u32 = if cfg!(target_pointer_width = "64") { 31 } else { 30 };
+
+    let dictionary = fs::read(base)
+        .map_err(|e| format!("读取当前可执行文件失败: {}", e))?;
+    let patch_file = fs::File::open(patch)
+        .map_err(|e| format!("打开增量包失败: {}", e))?;
+
+    let mut decoder = zstd::stream::read::Decoder::with_dictionary(std::io::BufReader::new(patch_file), &dictionary)
+        .map_err(|e| format!("初始化增量解码失败: {}", e))?;
+    decoder.window_log_max(WINDOW_LOG_MAX)
+        .map_err(|e| format!("设置解码窗口失败: {}", e))?;
+
+    let mut output_file = fs::File::create(output)
+        .map_err(|e| format!("创建文件失败: {}", e))?;
+    std::io::copy(&mut decoder, &mut output_file)
+        .map_err(|e| format!("应用增量包失败: {}", e))?;
+
+    log::info!("✅ 增量包已应用: {}", output.display());
     Ok(())
 }
 
@@ -806,3 +1262,95 @@ fn extract_version_from_filename(filename: &str) -> Option<String> {
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use ring::signature::KeyPair;
+
+    #[test]
+    fn parse_tag_version_keeps_prerelease_suffix() {
+        assert_eq!(parse_tag_version("v1.2.3"), "1.2.3");
+        assert_eq!(parse_tag_version("版本 1.3.0"), "1.3.0");
+        assert_eq!(parse_tag_version("v1.3.0-beta.2"), "1.3.0-beta.2");
+        assert_eq!(parse_tag_version("v1.3.0-beta."), "1.3.0-beta");
+        assert_eq!(parse_tag_version("v2.0."), "2.0");
+        assert_eq!(parse_tag_version("latest"), "");
+    }
+
+    #[test]
+    fn version_ordering_compares_numbers_then_prerelease() {
+        assert_eq!(version_ordering("1.10.0", "1.9.0"), Ordering::Greater);
+        assert_eq!(version_ordering("1.2", "1.2.0"), Ordering::Equal);
+        assert_eq!(version_ordering("1.3.0", "1.3.0-beta.1"), Ordering::Greater);
+        assert_eq!(version_ordering("1.3.0-beta.2", "1.3.0-beta.10"), Ordering::Less);
+        assert_eq!(version_ordering("1.3.0-beta", "1.3.0-alpha"), Ordering::Greater);
+        assert_eq!(version_ordering("1.3.0-1", "1.3.0-alpha"), Ordering::Less);
+        assert_eq!(version_ordering("1.3.0-beta", "1.3.0-beta.1"), Ordering::Less);
+        assert!(compare_versions("1.3.1", "1.3.0"));
+        assert!(!compare_versions("1.3.0", "1.3.0"));
+    }
+
+    #[test]
+    fn rollout_percentage_parses_marker() {
+        assert_eq!(rollout_percentage("notes\n<!-- rollout: 25% -->"), Some(25));
+        assert_eq!(rollout_percentage("<!--ROLLOUT:5 %-->"), Some(5));
+        assert_eq!(rollout_percentage("<!-- rollout: 250% -->"), Some(100));
+        assert_eq!(rollout_percentage("rollout: 25%"), None);
+        assert_eq!(rollout_percentage(""), None);
+    }
+
+    #[test]
+    fn rollout_bucket_is_stable_per_id() {
+        let bucket = rollout_bucket("5f0e7c1a-0000-4000-8000-000000000000");
+        assert!(bucket < 100);
+        assert_eq!(bucket, rollout_bucket("5f0e7c1a-0000-4000-8000-000000000000"));
+    }
+
+    fn signed_manifest(manifest: &[u8]) -> (String, String) {
+        let rng = ring::rand::SystemRandom::new();
+        let pkcs8 = ring::signature::Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        let key_pair = ring::signature::Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let engine = base64::engine::general_purpose::STANDARD;
+        (
+            engine.encode(key_pair.public_key().as_ref()),
+            engine.encode(key_pair.sign(manifest).as_ref()),
+        )
+    }
+
+    #[test]
+    fn verify_manifest_accepts_valid_signature() {
+        let manifest = br#"{"version":"v1.4.0","binaries":{"linux-x86_64":"abc123"}}"#;
+        let (public_key, signature) = signed_manifest(manifest);
+
+        let parsed = verify_manifest(manifest, signature.as_bytes(), &public_key).unwrap();
+        assert_eq!(manifest_binary_hash(&parsed, "1.4.0", "linux-x86_64").unwrap(), "abc123");
+        assert!(manifest_binary_hash(&parsed, "1.4.0", "windows-x86_64").is_err());
+        assert!(manifest_binary_hash(&parsed, "1.4.1", "linux-x86_64").is_err());
+    }
+
+    #[test]
+    fn verify_manifest_rejects_tampered_manifest_and_foreign_key() {
+        let manifest = br#"{"version":"1.4.0","binaries":{"linux-x86_64":"abc123"}}"#;
+        let (public_key, signature) = signed_manifest(manifest);
+
+        let tampered = br#"{"version":"1.4.0","binaries":{"linux-x86_64":"evil00"}}"#;
+        assert!(verify_manifest(tampered, signature.as_bytes(), &public_key).is_err());
+
+        let (other_key, _) = signed_manifest(manifest);
+        assert!(verify_manifest(manifest, signature.as_bytes(), &other_key).is_err());
+        assert!(verify_manifest(manifest, b"not base64!", &public_key).is_err());
+    }
+
+    #[test]
+    fn sha256_file_matches_known_digest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("binary");
+        fs::write(&path, b"abc").unwrap();
+        assert_eq!(
+            sha256_file(&path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
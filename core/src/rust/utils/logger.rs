@@ -1,12 +1,326 @@
+use std::collections::VecDeque;
 use std::env;
-use std::fs::OpenOptions;
+use std::fs::{self, File, OpenOptions};
 use std::io::Write;
-use std::sync::Once;
-use log::LevelFilter;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, Once, OnceLock, RwLock};
+use log::{LevelFilter, Log, Metadata, Record};
 use env_logger::{Builder, Target};
 
 static INIT: Once = Once::new();
 
+/// 内存中保留的最近日志行数（用于问题反馈）
+const RECENT_LOG_CAPACITY: usize = 1000;
+
+/// 单个日志文件的默认大小上限（MB）
+const DEFAULT_MAX_FILE_SIZE_MB: u64 = 10;
+
+/// 默认保留的历史日志文件数
+const DEFAULT_RETENTION: usize = 5;
+
+/// 覆盖单个日志文件大小上限（MB）的环境变量
+pub const LOG_MAX_SIZE_ENV_VAR: &str = "NEUROSPEC_LOG_MAX_SIZE_MB";
+
+/// 覆盖历史日志保留数量的环境变量
+pub const LOG_RETENTION_ENV_VAR: &str = "NEUROSPEC_LOG_RETENTION";
+
+/// 当前日志文件路径
+static LOG_FILE_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+lazy_static::lazy_static! {
+    /// 运行时过滤规则，可在不重启的情况下修改
+    static ref RUNTIME_FILTER: RwLock<RuntimeFilter> = RwLock::new(RuntimeFilter::default());
+
+    /// 最近的日志行
+    static ref RECENT_LOGS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::with_capacity(RECENT_LOG_CAPACITY));
+}
+
+/// 运行时日志过滤规则：全局级别 + 按模块覆盖
+///
+/// 规则字符串与 `RUST_LOG` 相同，如 `info,acemcp=debug`；
+/// 模块名匹配模块路径中的任一段（`acemcp` 匹配 `neurospec::mcp::tools::acemcp::mcp`），
+/// 含 `::` 时按前缀匹配
+#[derive(Debug, Clone)]
+pub struct RuntimeFilter {
+    pub level: LevelFilter,
+    pub modules: Vec<(String, LevelFilter)>,
+}
+
+impl Default for RuntimeFilter {
+    fn default() -> Self {
+        Self {
+            level: LevelFilter::Info,
+            modules: Vec::new(),
+        }
+    }
+}
+
+impl RuntimeFilter {
+    /// 解析规则字符串
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut filter = Self::default();
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            match part.split_once('=') {
+                Some((module, level)) => {
+                    let level = level
+                        .trim()
+                        .parse::<LevelFilter>()
+                        .map_err(|_| format!("无效的日志级别: {}", level))?;
+                    filter.modules.push((module.trim().to_string(), level));
+                }
+                None => {
+                    filter.level = part
+                        .parse::<LevelFilter>()
+                        .map_err(|_| format!("无效的日志级别: {}", part))?;
+                }
+            }
+        }
+        Ok(filter)
+    }
+
+    /// 转换回规则字符串
+    pub fn spec(&self) -> String {
+        std::iter::once(self.level.to_string().to_lowercase())
+            .chain(
+                self.modules
+                    .iter()
+                    .map(|(module, level)| format!("{}={}", module, level.to_string().to_lowercase())),
+            )
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// 目标模块的生效级别（匹配最长的模块规则优先）
+    fn level_for(&self, target: &str) -> LevelFilter {
+        self.modules
+            .iter()
+            .filter(|(module, _)| module_matches(module, target))
+            .max_by_key(|(module, _)| module.len())
+            .map(|(_, level)| *level)
+            .unwrap_or(self.level)
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.modules
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.level, std::cmp::max)
+    }
+}
+
+fn module_matches(module: &str, target: &str) -> bool {
+    if module.contains("::") {
+        target == module || target.starts_with(&format!("{}::", module))
+    } else {
+        target.split("::").any(|segment| segment == module)
+    }
+}
+
+/// 在 env_logger 之前应用运行时过滤规则
+struct RuntimeLogger {
+    inner: env_logger::Logger,
+}
+
+impl Log for RuntimeLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let allowed = RUNTIME_FILTER
+            .read()
+            .map(|filter| metadata.level() <= filter.level_for(metadata.target()))
+            .unwrap_or(true);
+        allowed && self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// 修改运行时日志过滤规则（如 `debug` 或 `warn,acemcp=debug`）
+pub fn set_log_filter(spec: &str) -> Result<(), String> {
+    let filter = RuntimeFilter::parse(spec)?;
+    log::set_max_level(filter.max_level());
+    let mut current = RUNTIME_FILTER.write().map_err(|e| e.to_string())?;
+    *current = filter;
+    Ok(())
+}
+
+/// 当前的运行时日志过滤规则
+pub fn current_log_filter() -> RuntimeFilter {
+    RUNTIME_FILTER
+        .read()
+        .map(|filter| filter.clone())
+        .unwrap_or_default()
+}
+
+/// 最近的日志行（最多 `limit` 行，按时间顺序）
+pub fn recent_logs(limit: usize) -> Vec<String> {
+    RECENT_LOGS
+        .lock()
+        .map(|logs| {
+            let skip = logs.len().saturating_sub(limit);
+            logs.iter().skip(skip).cloned().collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 当前日志文件路径
+pub fn log_file_path() -> Option<PathBuf> {
+    LOG_FILE_PATH.get().cloned()
+}
+
+fn remember_log_line(line: &str) {
+    if let Ok(mut logs) = RECENT_LOGS.lock() {
+        if logs.len() >= RECENT_LOG_CAPACITY {
+            logs.pop_front();
+        }
+        logs.push_back(line.to_string());
+    }
+}
+
+/// 按大小和日期轮转的日志文件
+///
+/// 当前文件超过大小上限或跨天后，依次重命名为 `<文件>.1`、`<文件>.2` ……，
+/// 只保留最近 `retention` 个历史文件
+struct RotatingFileWriter {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+    retention: usize,
+    date: chrono::NaiveDate,
+}
+
+impl RotatingFileWriter {
+    fn open(path: &Path, max_size: u64, retention: usize) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let metadata = file.metadata()?;
+        // 以文件最后修改日期作为起始日期，上次运行遗留的旧文件会在首次写入时轮转
+        let date = metadata
+            .modified()
+            .map(|t| chrono::DateTime::<chrono::Local>::from(t).date_naive())
+            .unwrap_or_else(|_| chrono::Local::now().date_naive());
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            size: metadata.len(),
+            max_size,
+            retention,
+            date,
+        })
+    }
+
+    fn needs_rotation(&self, incoming: usize) -> bool {
+        if self.size == 0 {
+            return false;
+        }
+        (self.max_size > 0 && self.size + incoming as u64 > self.max_size)
+            || chrono::Local::now().date_naive() != self.date
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file.flush()?;
+
+        if self.retention == 0 {
+            self.file = File::create(&self.path)?;
+        } else {
+            let _ = fs::remove_file(rotated_path(&self.path, self.retention));
+            for i in (1..self.retention).rev() {
+                let from = rotated_path(&self.path, i);
+                if from.exists() {
+                    let _ = fs::rename(&from, rotated_path(&self.path, i + 1));
+                }
+            }
+            // Windows 上无法重命名已打开的文件，失败时退化为截断
+            if fs::rename(&self.path, rotated_path(&self.path, 1)).is_err() {
+                self.file = File::create(&self.path)?;
+            } else {
+                self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+            }
+        }
+
+        self.size = 0;
+        self.date = chrono::Local::now().date_naive();
+        Ok(())
+    }
+}
+
+impl Write for RotatingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.needs_rotation(buf.len()) {
+            // 轮转失败不影响继续写入当前文件
+            let _ = self.rotate();
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+/// 日志目录中的单个日志文件
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LogFileInfo {
+    pub name: String,
+    pub path: String,
+    pub size: u64,
+    pub modified: Option<String>,
+}
+
+/// 当前日志所在目录
+pub fn log_directory() -> Option<PathBuf> {
+    log_file_path().and_then(|p| p.parent().map(|d| d.to_path_buf()))
+}
+
+/// 列出日志目录中的 NeuroSpec 日志文件（含轮转后的历史文件），按修改时间倒序
+pub fn list_log_files() -> Vec<LogFileInfo> {
+    let Some(dir) = log_directory() else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<(std::time::SystemTime, LogFileInfo)> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            name.starts_with("neurospec") && name.contains(".log")
+        })
+        .filter_map(|e| {
+            let metadata = e.metadata().ok().filter(|m| m.is_file())?;
+            let modified = metadata.modified().ok();
+            Some((
+                modified.unwrap_or(std::time::UNIX_EPOCH),
+                LogFileInfo {
+                    name: e.file_name().to_string_lossy().to_string(),
+                    path: e.path().to_string_lossy().to_string(),
+                    size: metadata.len(),
+                    modified: modified.map(|t| chrono::DateTime::<chrono::Local>::from(t).to_rfc3339()),
+                },
+            ))
+        })
+        .collect();
+
+    files.sort_by(|a, b| b.0.cmp(&a.0));
+    files.into_iter().map(|(_, info)| info).collect()
+}
+
 /// 日志配置
 #[derive(Debug, Clone)]
 pub struct LogConfig {
@@ -16,6 +330,12 @@ pub struct LogConfig {
     pub file_path: Option<String>,
     /// 是否为 MCP 模式（MCP 模式下不输出到 stderr）
     pub is_mcp_mode: bool,
+    /// 按模块覆盖的日志级别
+    pub module_filters: Vec<(String, LevelFilter)>,
+    /// 单个日志文件大小上限（字节，0 表示只按日期轮转）
+    pub max_file_size: u64,
+    /// 保留的历史日志文件数
+    pub retention: usize,
 }
 
 impl Default for LogConfig {
@@ -24,6 +344,9 @@ impl Default for LogConfig {
             level: LevelFilter::Warn,
             file_path: None,
             is_mcp_mode: false,
+            module_filters: Vec::new(),
+            max_file_size: DEFAULT_MAX_FILE_SIZE_MB * 1024 * 1024,
+            retention: DEFAULT_RETENTION,
         }
     }
 }
@@ -33,8 +356,8 @@ pub fn init_logger(config: LogConfig) -> Result<(), Box<dyn std::error::Error>>
     INIT.call_once(|| {
         let mut builder = Builder::new();
         
-        // 设置日志级别
-        builder.filter_level(config.level);
+        // 级别过滤由 RuntimeLogger 负责，env_logger 本身放行全部记录
+        builder.filter_level(LevelFilter::Trace);
         
         // 设置日志格式
         builder.format(|buf, record| {
@@ -46,6 +369,8 @@ pub fn init_logger(config: LogConfig) -> Result<(), Box<dyn std::error::Error>>
                 record.args()
             );
             
+            remember_log_line(&log_line);
+
             // 写入到原始目标（stderr 或文件）
             writeln!(buf, "{}", log_line)?;
             
@@ -56,11 +381,7 @@ pub fn init_logger(config: LogConfig) -> Result<(), Box<dyn std::error::Error>>
         if config.is_mcp_mode {
             // MCP 模式：只输出到文件，不输出到 stderr
             if let Some(file_path) = &config.file_path {
-                if let Ok(log_file) = OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open(file_path) 
-                {
+                if let Ok(log_file) = RotatingFileWriter::open(Path::new(file_path), config.max_file_size, config.retention) {
                     builder.target(Target::Pipe(Box::new(log_file)));
                 } else {
                     // 如果文件打开失败，禁用日志输出
@@ -74,15 +395,10 @@ pub fn init_logger(config: LogConfig) -> Result<(), Box<dyn std::error::Error>>
             // 非 MCP 模式：如果指定了文件路径，同时输出到文件和 stderr
             if let Some(file_path) = &config.file_path {
                 // 尝试打开文件，如果成功则同时输出到文件和 stderr
-                if let Ok(log_file) = OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open(file_path) 
-                {
+                if let Ok(log_file) = RotatingFileWriter::open(Path::new(file_path), config.max_file_size, config.retention) {
                     // 使用自定义目标，同时写入文件和 stderr
-                    use std::io::Write;
                     struct DualWriter {
-                        file: std::fs::File,
+                        file: RotatingFileWriter,
                     }
                     impl Write for DualWriter {
                         fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
@@ -106,7 +422,21 @@ pub fn init_logger(config: LogConfig) -> Result<(), Box<dyn std::error::Error>>
             }
         }
         
-        builder.init();
+        if let Some(file_path) = &config.file_path {
+            let _ = LOG_FILE_PATH.set(PathBuf::from(file_path));
+        }
+
+        let filter = RuntimeFilter {
+            level: config.level,
+            modules: config.module_filters.clone(),
+        };
+        log::set_max_level(filter.max_level());
+        if let Ok(mut current) = RUNTIME_FILTER.write() {
+            *current = filter;
+        }
+
+        let logger = RuntimeLogger { inner: builder.build() };
+        let _ = log::set_boxed_logger(Box::new(logger));
     });
     
     Ok(())
@@ -118,6 +448,7 @@ pub fn auto_init_logger() -> Result<(), Box<dyn std::error::Error>> {
     let exe_name = args.get(0).map(|s| s.to_lowercase()).unwrap_or_default();
     // 检测 MCP 模式：通过命令行参数或可执行文件名
     let is_mcp_mode = (args.len() >= 3 && args[1] == "--mcp-request") 
+        || args.iter().any(|a| a == "--mcp")
         || exe_name.contains("mcp")
         || exe_name.contains("neurospec-mcp");
     
@@ -129,13 +460,14 @@ pub fn auto_init_logger() -> Result<(), Box<dyn std::error::Error>> {
                 temp_dir.join("neurospec-mcp.log").to_string_lossy().to_string()
             });
             
+        let filter = env_log_filter();
         LogConfig {
-            level: env::var("RUST_LOG")
-                .unwrap_or_else(|_| "info".to_string()) // 改为 info 级别以便调试
-                .parse::<LevelFilter>()
-                .unwrap_or(LevelFilter::Info),
+            level: filter.level,
             file_path: Some(log_file_path),
             is_mcp_mode: true,
+            module_filters: filter.modules,
+            max_file_size: env_max_file_size(),
+            retention: env_retention(),
         }
     } else {
         // GUI 模式：输出到文件和 stderr
@@ -145,19 +477,44 @@ pub fn auto_init_logger() -> Result<(), Box<dyn std::error::Error>> {
                 temp_dir.join("neurospec-gui.log").to_string_lossy().to_string()
             });
             
+        let filter = env_log_filter();
         LogConfig {
-            level: env::var("RUST_LOG")
-                .unwrap_or_else(|_| "info".to_string())
-                .parse::<LevelFilter>()
-                .unwrap_or(LevelFilter::Info),
+            level: filter.level,
             file_path: Some(log_file_path),
             is_mcp_mode: false,
+            module_filters: filter.modules,
+            max_file_size: env_max_file_size(),
+            retention: env_retention(),
         }
     };
     
     init_logger(config)
 }
 
+/// 从 `RUST_LOG` 读取过滤规则（默认 info，支持 `info,acemcp=debug` 形式）
+fn env_log_filter() -> RuntimeFilter {
+    env::var("RUST_LOG")
+        .ok()
+        .and_then(|spec| RuntimeFilter::parse(&spec).ok())
+        .unwrap_or_default()
+}
+
+fn env_max_file_size() -> u64 {
+    env::var(LOG_MAX_SIZE_ENV_VAR)
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_MAX_FILE_SIZE_MB)
+        * 1024
+        * 1024
+}
+
+fn env_retention() -> usize {
+    env::var(LOG_RETENTION_ENV_VAR)
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .unwrap_or(DEFAULT_RETENTION)
+}
+
 /// 便利宏：只在重要情况下记录日志
 #[macro_export]
 macro_rules! log_important {
@@ -200,6 +557,39 @@ mod tests {
         assert_eq!(config.is_mcp_mode, false);
     }
     
+    #[test]
+    fn test_runtime_filter_modules() {
+        let filter = RuntimeFilter::parse("warn,acemcp=debug,neurospec::daemon=trace").unwrap();
+        assert_eq!(filter.level, LevelFilter::Warn);
+        assert_eq!(filter.level_for("neurospec::mcp::tools::acemcp::mcp"), LevelFilter::Debug);
+        assert_eq!(filter.level_for("neurospec::daemon::server"), LevelFilter::Trace);
+        assert_eq!(filter.level_for("neurospec::mcp::dispatcher"), LevelFilter::Warn);
+        assert_eq!(filter.max_level(), LevelFilter::Trace);
+        assert_eq!(filter.spec(), "warn,acemcp=debug,neurospec::daemon=trace");
+        assert!(RuntimeFilter::parse("acemcp=verbose").is_err());
+    }
+    
+    #[test]
+    fn test_rotating_file_writer() {
+        let dir = env::temp_dir().join(format!("neurospec-log-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.log");
+
+        let mut writer = RotatingFileWriter::open(&path, 16, 2).unwrap();
+        for _ in 0..4 {
+            writer.write_all(b"0123456789\n").unwrap();
+        }
+        writer.flush().unwrap();
+
+        assert!(path.exists());
+        assert!(rotated_path(&path, 1).exists());
+        assert!(rotated_path(&path, 2).exists());
+        assert!(!rotated_path(&path, 3).exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+    
     #[test]
     fn test_mcp_mode_detection() {
         // 这个测试需要在实际环境中运行
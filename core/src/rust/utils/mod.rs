@@ -1,3 +1,15 @@
 pub mod logger;
+pub mod shutdown;
+pub mod paths;
+pub mod text_position;
+pub mod ttl_cache;
 
-pub use logger::{LogConfig, init_logger, auto_init_logger};
+pub use logger::{
+    LogConfig, RuntimeFilter, init_logger, auto_init_logger,
+    set_log_filter, current_log_filter, recent_logs, log_file_path,
+    LogFileInfo, log_directory, list_log_files,
+};
+pub use shutdown::{request_shutdown, is_shutting_down};
+pub use paths::{canonical_path, path_key, rekey};
+pub use text_position::{LineIndex, Position, PositionEncoding, TextRange};
+pub use ttl_cache::TtlCache;
@@ -0,0 +1,161 @@
+//! 路径规范化
+//!
+//! 项目路径会作为索引状态、索引元数据、项目注册表和记忆库的键。同一目录可能以多种形式出现：
+//! `\` 与 `/` 混用、`\\?\` 长路径前缀、盘符大小写、符号链接、`..` 片段。
+//! 路径在成为键之前统一经过这里转换，避免同一项目产生多条记录或缓存未命中
+
+use std::path::{Component, Path, PathBuf};
+
+/// 规范化路径：尽量解析符号链接，并去掉 Windows 的 `\\?\` / `\\?\UNC\` 前缀
+///
+/// 路径不存在时退化为按字面处理 `.` 与 `..`（相对路径先基于当前目录补全）
+pub fn canonical_path(path: &Path) -> PathBuf {
+    match path.canonicalize() {
+        Ok(canonical) => strip_verbatim_prefix(canonical),
+        Err(_) => {
+            let absolute = if path.is_absolute() {
+                path.to_path_buf()
+            } else {
+                std::env::current_dir()
+                    .map(|cwd| cwd.join(path))
+                    .unwrap_or_else(|_| path.to_path_buf())
+            };
+            strip_verbatim_prefix(lexical_normalize(&absolute))
+        }
+    }
+}
+
+/// 路径键：规范化后统一使用 `/` 分隔，盘符大写，去掉末尾分隔符
+///
+/// 路径存在时大小写以文件系统中的实际名称为准（由 `canonicalize` 解析）
+pub fn path_key(path: &Path) -> String {
+    key_from_str(&canonical_path(path).to_string_lossy())
+}
+
+/// 对已经是键或旧格式键的字符串重新计算键（用于迁移持久化数据）
+pub fn rekey(key: &str) -> String {
+    path_key(Path::new(key))
+}
+
+fn key_from_str(path: &str) -> String {
+    let mut key = path.replace('\\', "/");
+
+    // `c:/foo` 与 `C:/foo` 是同一目录
+    let bytes = key.as_bytes();
+    if bytes.len() >= 2 && bytes[1] == b':' && bytes[0].is_ascii_alphabetic() {
+        key[..1].make_ascii_uppercase();
+    }
+
+    while key.len() > 1 && key.ends_with('/') && !key.ends_with(":/") {
+        key.pop();
+    }
+    key
+}
+
+/// 去掉 Windows 扩展长度路径前缀（`\\?\C:\x` → `C:\x`，`\\?\UNC\server\share` → `\\server\share`）
+fn strip_verbatim_prefix(path: PathBuf) -> PathBuf {
+    let stripped = {
+        let text = path.to_string_lossy();
+        if let Some(rest) = text.strip_prefix(r"\\?\UNC\") {
+            Some(PathBuf::from(format!(r"\\{}", rest)))
+        } else {
+            text.strip_prefix(r"\\?\").map(PathBuf::from)
+        }
+    };
+    stripped.unwrap_or(path)
+}
+
+/// 不访问文件系统，按字面去掉 `.` 并回退 `..`
+fn lexical_normalize(path: &Path) -> PathBuf {
+    let mut components: Vec<Component> = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if matches!(components.last(), Some(Component::Normal(_))) {
+                    components.pop();
+                }
+            }
+            _ => components.push(component),
+        }
+    }
+    components.iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("neurospec-paths-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn lexical_normalize_drops_dot_and_parent_segments() {
+        assert_eq!(lexical_normalize(Path::new("/a/./b/../c")), PathBuf::from("/a/c"));
+        // 根目录之上的 `..` 直接丢弃
+        assert_eq!(lexical_normalize(Path::new("/../a")), PathBuf::from("/a"));
+        assert_eq!(lexical_normalize(Path::new("/a/b/../../..")), PathBuf::from("/"));
+    }
+
+    #[test]
+    fn strip_verbatim_prefix_handles_drive_and_unc_paths() {
+        assert_eq!(strip_verbatim_prefix(PathBuf::from(r"\\?\C:\work\app")), PathBuf::from(r"C:\work\app"));
+        assert_eq!(
+            strip_verbatim_prefix(PathBuf::from(r"\\?\UNC\server\share\app")),
+            PathBuf::from(r"\\server\share\app")
+        );
+        assert_eq!(strip_verbatim_prefix(PathBuf::from("/work/app")), PathBuf::from("/work/app"));
+    }
+
+    #[test]
+    fn key_uses_forward_slashes_and_uppercase_drive() {
+        assert_eq!(key_from_str(r"c:\work\app\"), "C:/work/app");
+        assert_eq!(key_from_str("C:/"), "C:/");
+        assert_eq!(key_from_str("/work/app//"), "/work/app");
+        assert_eq!(key_from_str("/"), "/");
+    }
+
+    #[test]
+    fn path_key_normalizes_missing_paths_lexically() {
+        let base = std::env::temp_dir().join("neurospec-paths-missing");
+        let key = path_key(&base.join("a").join("..").join("b"));
+        assert_eq!(key, path_key(&base.join("b")));
+        assert!(key.ends_with("neurospec-paths-missing/b"));
+    }
+
+    #[test]
+    fn path_key_matches_trailing_slash_and_dot_variants() {
+        let dir = temp_dir("variants");
+        let key = path_key(&dir);
+        assert_eq!(path_key(Path::new(&format!("{}/", dir.display()))), key);
+        assert_eq!(path_key(&dir.join(".")), key);
+        assert_eq!(path_key(&dir.join("sub").join("..")), key);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn path_key_resolves_symlinks() {
+        let dir = temp_dir("symlink");
+        let target = dir.join("project");
+        let link = dir.join("link");
+        std::fs::create_dir_all(&target).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        assert_eq!(path_key(&link), path_key(&target));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rekey_is_idempotent() {
+        let dir = temp_dir("rekey");
+        let key = path_key(&dir);
+        assert_eq!(rekey(&key), key);
+        assert_eq!(rekey(&format!("{}/", key)), key);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
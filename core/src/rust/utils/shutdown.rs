@@ -0,0 +1,18 @@
+//! 进程退出标志
+//!
+//! 后台任务（文件变化处理、后台索引、初始索引、统计上报）在循环中检查该标志，
+//! 退出时尽快结束，避免在清理过程中继续写入索引或数据库
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static SHUTDOWN_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// 通知所有后台任务停止
+pub fn request_shutdown() {
+    SHUTDOWN_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+/// 是否正在退出
+pub fn is_shutting_down() -> bool {
+    SHUTDOWN_REQUESTED.load(Ordering::SeqCst)
+}
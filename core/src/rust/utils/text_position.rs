@@ -0,0 +1,183 @@
+//! 文本位置换算
+//!
+//! 内部编辑以 UTF-8 字节偏移表示，编辑器和 LSP 使用「行 + UTF-16 列」。
+//! [`LineIndex`] 一次建立行起始索引，在字节偏移与行列之间互相换算；
+//! `\n`、`\r\n` 与单独的 `\r` 都视为换行，列不包含行尾换行符
+
+use std::ops::Range;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// 列的计量单位
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PositionEncoding {
+    /// UTF-8 字节
+    Utf8,
+    /// UTF-16 码元（LSP 默认）
+    #[default]
+    Utf16,
+    /// Unicode 标量值（字符）
+    Utf32,
+}
+
+/// 行列位置（均从 0 开始）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+pub struct Position {
+    pub line: u32,
+    pub character: u32,
+}
+
+impl Position {
+    pub fn new(line: u32, character: u32) -> Self {
+        Self { line, character }
+    }
+}
+
+/// 行列区间（左闭右开）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TextRange {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// 文本的行起始索引
+pub struct LineIndex<'a> {
+    text: &'a str,
+    /// 每行起始字节偏移
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let bytes = text.as_bytes();
+        let mut line_starts = vec![0];
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\r' if bytes.get(i + 1) == Some(&b'\n') => {
+                    i += 2;
+                    line_starts.push(i);
+                }
+                b'\r' | b'\n' => {
+                    i += 1;
+                    line_starts.push(i);
+                }
+                _ => i += 1,
+            }
+        }
+        Self { text, line_starts }
+    }
+
+    /// 行数（以换行结尾的文本最后还有一个空行）
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// 行内容的字节区间（不含换行符）
+    pub fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        let start = *self.line_starts.get(line)?;
+        let end = self.line_starts.get(line + 1).copied().unwrap_or(self.text.len());
+        let content = &self.text[start..end];
+        let content = content.strip_suffix('\n').unwrap_or(content);
+        let content = content.strip_suffix('\r').unwrap_or(content);
+        Some(start..start + content.len())
+    }
+
+    /// 行内容（不含换行符）
+    pub fn line_text(&self, line: usize) -> Option<&'a str> {
+        self.line_range(line).map(|range| &self.text[range])
+    }
+
+    /// 字节偏移所在的行
+    pub fn line_of(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= offset) - 1
+    }
+
+    /// 字节偏移 → 行列
+    ///
+    /// 偏移超出文本或不在字符边界上时返回 None；位于换行符中间时视为行尾
+    pub fn position(&self, offset: usize, encoding: PositionEncoding) -> Option<Position> {
+        if offset > self.text.len() || !self.text.is_char_boundary(offset) {
+            return None;
+        }
+        let line = self.line_of(offset);
+        let range = self.line_range(line)?;
+        let prefix = &self.text[range.start..offset.min(range.end)];
+        Some(Position::new(line as u32, measure(prefix, encoding) as u32))
+    }
+
+    /// 行列 → 字节偏移
+    ///
+    /// 与 LSP 一致，列超过行长度时取行尾；行号越界或列落在 UTF-16 代理对中间时返回 None
+    pub fn offset(&self, position: Position, encoding: PositionEncoding) -> Option<usize> {
+        let range = self.line_range(position.line as usize)?;
+        let line = &self.text[range.clone()];
+        let target = position.character as usize;
+
+        let mut units = 0;
+        for (index, ch) in line.char_indices() {
+            if units == target {
+                return Some(range.start + index);
+            }
+            units += char_units(ch, encoding);
+            if units > target {
+                return None;
+            }
+        }
+        Some(range.end)
+    }
+
+    /// 字节区间 → 行列区间
+    pub fn range(&self, bytes: Range<usize>, encoding: PositionEncoding) -> Option<TextRange> {
+        Some(TextRange {
+            start: self.position(bytes.start, encoding)?,
+            end: self.position(bytes.end, encoding)?,
+        })
+    }
+
+    /// 行列区间 → 字节区间
+    pub fn byte_range(&self, range: TextRange, encoding: PositionEncoding) -> Option<Range<usize>> {
+        let start = self.offset(range.start, encoding)?;
+        let end = self.offset(range.end, encoding)?;
+        (start <= end).then_some(start..end)
+    }
+
+    /// 文本主要使用的换行符（没有换行时为 `\n`）
+    pub fn line_ending(&self) -> &'static str {
+        let crlf = self.text.matches("\r\n").count();
+        if crlf > 0 && crlf * 2 >= self.line_starts.len() - 1 {
+            "\r\n"
+        } else {
+            "\n"
+        }
+    }
+}
+
+/// 把文本中的换行统一为 `line_ending`
+pub fn normalize_line_endings(text: &str, line_ending: &str) -> String {
+    let unified = text.replace("\r\n", "\n");
+    if line_ending == "\n" {
+        unified
+    } else {
+        unified.replace('\n', line_ending)
+    }
+}
+
+/// 文本在给定编码下的长度
+pub fn measure(text: &str, encoding: PositionEncoding) -> usize {
+    match encoding {
+        PositionEncoding::Utf8 => text.len(),
+        PositionEncoding::Utf16 => text.encode_utf16().count(),
+        PositionEncoding::Utf32 => text.chars().count(),
+    }
+}
+
+fn char_units(ch: char, encoding: PositionEncoding) -> usize {
+    match encoding {
+        PositionEncoding::Utf8 => ch.len_utf8(),
+        PositionEncoding::Utf16 => ch.len_utf16(),
+        PositionEncoding::Utf32 => 1,
+    }
+}
@@ -0,0 +1,63 @@
+//! 定时刷新的配置值缓存
+//!
+//! MCP 服务进程长期运行，设置界面（GUI 进程）中的修改只会写入配置文件。
+//! 需要感知这类修改的值缓存一段时间后重新读取配置：不必每次调用都读文件，修改也最迟在一个刷新间隔后生效
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 带有效期的单值缓存
+pub struct TtlCache<T> {
+    ttl: Duration,
+    value: Mutex<Option<(T, Instant)>>,
+}
+
+impl<T: Clone> TtlCache<T> {
+    pub const fn new(ttl: Duration) -> Self {
+        Self { ttl, value: Mutex::new(None) }
+    }
+
+    /// 返回缓存值，未加载或已过期时调用 `load` 重新读取
+    pub fn get_or_refresh(&self, load: impl FnOnce() -> T) -> T {
+        let mut cached = self.value.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((ref value, loaded_at)) = *cached {
+            if loaded_at.elapsed() < self.ttl {
+                return value.clone();
+            }
+        }
+
+        let value = load();
+        *cached = Some((value.clone(), Instant::now()));
+        value
+    }
+
+    /// 写入新值并重新计时（本进程中的修改立即生效）
+    pub fn set(&self, value: T) {
+        let mut cached = self.value.lock().unwrap_or_else(|e| e.into_inner());
+        *cached = Some((value, Instant::now()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caches_until_expired() {
+        let cache = TtlCache::new(Duration::from_secs(60));
+        assert_eq!(cache.get_or_refresh(|| 1), 1);
+        assert_eq!(cache.get_or_refresh(|| 2), 1);
+
+        let expired = TtlCache::new(Duration::ZERO);
+        assert_eq!(expired.get_or_refresh(|| 1), 1);
+        assert_eq!(expired.get_or_refresh(|| 2), 2);
+    }
+
+    #[test]
+    fn set_replaces_cached_value() {
+        let cache = TtlCache::new(Duration::from_secs(60));
+        assert_eq!(cache.get_or_refresh(|| "config"), "config");
+        cache.set("updated");
+        assert_eq!(cache.get_or_refresh(|| "config"), "updated");
+    }
+}